@@ -0,0 +1,2 @@
+﻿pragma solidity ^0.8.13; contract ByteOrderMark { function f(uint256 a) public pure returns (uint256) { return a + 1; } }
+// SPDX-License-Identifier: GPL-3.0-only
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+pragma solidity ^0.8.13;
+
+import "@openzeppelin/contracts/utils/math/Math.sol";
+
+contract Vault {
+    uint256 public total;
+
+    function deposit(uint256 amount) public {
+        total = Math.max(total, total + amount);
+    }
+}
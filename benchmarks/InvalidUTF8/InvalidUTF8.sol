@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+pragma solidity ^0.8.13;
+
+// This comment was copied from a PDF:  caf
+contract InvalidUTF8 {
+    function f(uint256 a, uint256 b) public pure returns (uint256) {
+        return a /*  */ + b;
+    }
+
+    function g(uint256 c, uint256 d) public pure returns (uint256) {
+        return c * d;
+    }
+}
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+pragma solidity ^0.8.13;
+
+import "./SafeCastLib.sol";
+
+contract C {
+    using SafeCastLib for uint256;
+
+    function half(uint256 x) public pure returns (uint128) {
+        return (x / 2).toUint128();
+    }
+}
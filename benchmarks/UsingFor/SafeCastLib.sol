@@ -0,0 +1,10 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+pragma solidity ^0.8.13;
+
+library SafeCastLib {
+    function toUint128(uint256 x) internal pure returns (uint128) {
+        require(x < 1 << 128);
+        return uint128(x);
+    }
+}
@@ -4,8 +4,7 @@ pragma solidity ^0.8.13;
 
 library Utils {
     function getarray(address[] memory c, address e) internal pure {
-        /// DeleteExpressionMutation(`assert(c[0] == e)` |==> `assert(true)`) of: `assert(c[0] == e);`
-        assert(true);
+        assert(c[0] == e);
     }
 
     function add(int8 a, int8 b) public pure returns (int8) {
@@ -22,7 +21,8 @@ contract C {
 
     function get10PowerDecimals(uint8 decimals) public pure returns (uint256) {
         uint256 a = 10;
-        uint256 res = a ** decimals;
+        /// BinaryOpMutation(`**` |==> `+`) of: `uint256 res = a ** decimals;`
+        uint256 res = a+decimals;
         return res;
     }
 
@@ -4,12 +4,12 @@ pragma solidity ^0.8.13;
 
 library Utils {
     function getarray(address[] memory c, address e) internal pure {
-        /// DeleteExpressionMutation(`assert(c[0] == e)` |==> `assert(true)`) of: `assert(c[0] == e);`
-        assert(true);
+        assert(c[0] == e);
     }
 
     function add(int8 a, int8 b) public pure returns (int8) {
-        return a + b;
+        /// BinaryOpMutation(`+` |==> `-`) of: `return a + b;`
+        return a-b;
     }
 }
 
@@ -21,8 +21,7 @@ contract C {
 
     function get10PowerDecimals(uint8 decimals) public pure returns (uint256) {
         uint256 a = 10;
-        /// SwapArgumentsOperatorMutation(`a ** decimals` |==> `decimals ** a`) of: `uint256 res = a ** decimals;`
-        uint256 res = decimals ** a;
+        uint256 res = a ** decimals;
         return res;
     }
 
@@ -32,7 +31,8 @@ contract C {
 
     function callmyself() external view {
         address[] memory b = this.foo();
-        Utils.getarray(b, address(this));
+        /// DeleteExpressionMutation(`Utils.getarray(b, address(this))` |==> `assert(true)`) of: `Utils.getarray(b, address(this));`
+        assert(true);
     }
 
     function add(int8 c, int8 d) public pure returns (int8) {
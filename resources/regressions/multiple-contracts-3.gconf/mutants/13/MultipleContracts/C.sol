@@ -26,8 +26,7 @@ contract C {
     }
 
     function getarray(address[] memory c, address e) public pure {
-        /// DeleteExpressionMutation(`assert(c[0] == e)` |==> `assert(true)`) of: `assert(c[0] == e);`
-        assert(true);
+        assert(c[0] == e);
     }
 
     function callmyself() external view {
@@ -36,6 +35,7 @@ contract C {
     }
 
     function add(int8 c, int8 d) public pure returns (int8) {
-        return c + d;
+        /// BinaryOpMutation(`+` |==> `-`) of: `return c + d;`
+        return c-d;
     }
 }
@@ -31,11 +31,11 @@ contract C {
 
     function callmyself() external view {
         address[] memory b = this.foo();
-        /// DeleteExpressionMutation(`Utils.getarray(b, address(this))` |==> `assert(true)`) of: `Utils.getarray(b, address(this));`
-        assert(true);
+        Utils.getarray(b, address(this));
     }
 
     function add(int8 c, int8 d) public pure returns (int8) {
-        return c + d;
+        /// BinaryOpMutation(`+` |==> `*`) of: `return c + d;`
+        return c*d;
     }
 }
@@ -35,7 +35,7 @@ contract C {
     }
 
     function add(int8 c, int8 d) public pure returns (int8) {
-        /// BinaryOpMutation(`+` |==> `-`) of: `return c + d;`
-        return c-d;
+        /// BinaryOpMutation(`+` |==> `/`) of: `return c + d;`
+        return c/d;
     }
 }
@@ -8,15 +8,15 @@ library Utils {
     }
 
     function add(int8 a, int8 b) public pure returns (int8) {
-        /// BinaryOpMutation(`+` |==> `%`) of: `return a + b;`
-        return a%b;
+        return a + b;
     }
 }
 
 contract C {
     function foo() external view returns (address[] memory) {
         address[] memory a = new address[](1);
-        a[0] = msg.sender;
+        /// DeleteExpressionMutation(`a[0] = msg.sender` |==> `assert(true)`) of: `a[0] = msg.sender;`
+        assert(true);
         return a;
     }
 
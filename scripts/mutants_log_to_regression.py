@@ -9,7 +9,7 @@ def read_test(json):
         lines = f.readlines()
     testcases = []
     for line in lines:
-        mid, op, filepath, linecol, orig, repl = line.strip().split(",")
+        mid, op, filepath, linecol, orig, repl, validated = line.strip().split(",")
         line, col = linecol.split(":")
         orig = orig.strip()
         repl = repl.strip()
@@ -3,13 +3,14 @@
 ################################################################################
 # remove_sourceroots.sh
 #
-# remove sourceroot fields from JSON since these are absolute paths
+# remove sourceroot and solc fields from JSON since these are absolute paths
+# (and solc versions) that depend on the machine running Gambit
 
 if [[ "$(uname)" == "Linux" ]]; then
-    sed -i '/"sourceroot":/d' "$1"
+    sed -i '/"sourceroot":/d; /"solc":/d; /"solc_version":/d' "$1"
 elif [[ "$(uname)" == "Darwin" ]]; then
-    sed -i "" '/"sourceroot":/d' "$1"
+    sed -i "" '/"sourceroot":/d; /"solc":/d; /"solc_version":/d' "$1"
 else
     echo "Unknown operating system: using the GNU sed interface"
-    sed -i '/"sourceroot":/d' "$1"
+    sed -i '/"sourceroot":/d; /"solc":/d; /"solc_version":/d' "$1"
 fi
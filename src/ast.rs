@@ -1,6 +1,7 @@
 use regex::Regex;
 use serde::Deserialize;
 use serde_json::Value;
+use std::string::FromUtf8Error;
 
 /// This is a thin wrapper around the json AST
 /// generated by the solidity compiler.
@@ -180,6 +181,59 @@ impl SolAST {
         }
     }
 
+    /// Returns the `nodes` of a source unit or contract definition.
+    pub fn nodes(&self) -> Vec<SolAST> {
+        let o = self.get_object();
+        match o {
+            None => vec![],
+            Some(v) => {
+                let arg = &v["nodes"].as_array();
+                match arg {
+                    Some(lst) => lst.iter().map(|e| Self::new(e.clone())).collect(),
+                    None => vec![],
+                }
+            }
+        }
+    }
+
+    /// Returns the `stateMutability` field of a function definition (one of
+    /// `pure`, `view`, `nonpayable`, or `payable`).
+    pub fn state_mutability(&self) -> Option<String> {
+        self.get_string("stateMutability")
+    }
+
+    /// Returns the `clauses` of a `try` statement. The first clause is always
+    /// the success clause, and is followed by the `catch` clauses in source
+    /// order.
+    pub fn clauses(&self) -> Vec<SolAST> {
+        let o = self.get_object();
+        match o {
+            None => vec![],
+            Some(v) => {
+                let arg = &v["clauses"].as_array();
+                match arg {
+                    Some(lst) => lst.iter().map(|e| Self::new(e.clone())).collect(),
+                    None => vec![],
+                }
+            }
+        }
+    }
+
+    /// Returns the `block` field.
+    pub fn block(&self) -> SolAST {
+        self.get_node("block")
+    }
+
+    /// Returns the `parameters` field.
+    pub fn parameters(&self) -> SolAST {
+        self.get_node("parameters")
+    }
+
+    /// Returns the `errorName` field of a `try`/`catch` clause.
+    pub fn error_name(&self) -> Option<String> {
+        self.get_string("errorName")
+    }
+
     /// Returns the `condition` field.
     pub fn condition(&self) -> SolAST {
         self.get_node("condition")
@@ -201,78 +255,95 @@ impl SolAST {
             .map(|obj| TypeDescriptions::new(obj["typeDescriptions"].clone()))
     }
 
-    /// Recursively traverses the AST.
+    /// Prune the subtrees of this AST that are nested more than `max_depth`
+    /// JSON levels deep, returning the number of pruned subtrees.
+    ///
+    /// Cloning and dropping a `Value` recurses once per level, so a
+    /// pathologically deep AST (e.g., of a sum of thousands of terms) would
+    /// overflow the stack as soon as a subtree is cloned. Each pruned node is
+    /// replaced by a stub that keeps only its `src`, so that its parent can
+    /// still be mutated, but the stub itself is never mutated. The pruned
+    /// subtrees are torn down without recursion.
+    pub fn prune_deeper_than(&mut self, max_depth: usize) -> usize {
+        let mut pruned: Vec<Value> = vec![];
+        let mut worklist: Vec<(&mut Value, usize)> =
+            self.element.iter_mut().map(|e| (e, 0)).collect();
+        while let Some((value, depth)) = worklist.pop() {
+            let children: Vec<&mut Value> = match value {
+                Value::Object(o) => o.values_mut().collect(),
+                Value::Array(a) => a.iter_mut().collect(),
+                _ => continue,
+            };
+            for child in children {
+                if depth < max_depth {
+                    worklist.push((child, depth + 1));
+                } else if child.is_object() || child.is_array() {
+                    let stub = match &child["src"] {
+                        Value::String(src) => serde_json::json!({ "src": src }),
+                        _ => Value::Null,
+                    };
+                    pruned.push(std::mem::replace(child, stub));
+                }
+            }
+        }
+        let count = pruned.len();
+        while let Some(value) = pruned.pop() {
+            match value {
+                Value::Object(o) => pruned.extend(o.into_iter().map(|(_, v)| v)),
+                Value::Array(a) => pruned.extend(a),
+                _ => (),
+            }
+        }
+        count
+    }
+
+    /// Traverses the AST in pre-order, visiting each node before its
+    /// children (in order).
     ///
     /// This is how Gambit determines what nodes can be mutated using which
     /// types of mutations and the exact location in the source where the
     /// mutation must be done.
     ///
+    /// The traversal uses an explicit worklist rather than recursion, so that
+    /// deeply nested ASTs (e.g., of a sum of thousands of terms) cannot
+    /// overflow the stack. Each node is moved out of its parent once the
+    /// parent has been visited, so no subtree is ever cloned.
+    ///
     /// # Arguments
     ///
-    /// * `visitor` - see [`run::RunMutations::mk_closures()`]
-    /// * `skip` - see [`run::RunMutations::mk_closures()`]
-    /// * `accept` - see [`run::RunMutations::mk_closures()`]
+    /// * `visitor` - decides which nodes to skip (along with their children),
+    ///   and produces a result for each visited node
+    /// * `arg` - passed to each call of `visitor`
     pub fn traverse<A, T>(self, visitor: &dyn SolASTVisitor<A, T>, arg: A) -> Vec<T> {
         let mut result: Vec<T> = vec![];
-        self.traverse_internal(visitor, &arg, &mut result);
-        result
-    }
-
-    /// Helper function to traverse AST
-    ///
-    /// # Arguments
-    ///
-    /// * `visitor` - see [`run::RunMutations::mk_closures()`]
-    /// * `skip` - see [`run::RunMutations::mk_closures()`]
-    /// * `accept` - see [`run::RunMutations::mk_closures()`]
-    /// * `accepted` - is this node the descendent of an accepted node? This
-    ///   value is monotonic as we descend an AST: it begins as false but once
-    ///   set to true will be true for all recursive calls
-    /// * `acc` - TODO: ?
-    fn traverse_internal<A, T>(
-        &self,
-        visitor: &dyn SolASTVisitor<A, T>,
-        arg: &A,
-        acc: &mut Vec<T>,
-    ) {
-        log::debug!(
-            "Traversing Node: kind: {:?}, type: {:?}",
-            self.node_kind(),
-            self.node_type(),
-        );
-        if visitor.skip_node(self, arg) {
-            log::debug!("    Skipping");
-            return;
-        }
-
-        if let Some(result) = visitor.visit_node(self, arg) {
-            log::debug!("    Visit successful");
-            acc.push(result);
-        } else {
-            log::debug!("    Visit failed")
-        }
-
-        if self.element.is_none() {
-            return;
-        }
-
-        let e = self.element.as_ref().unwrap();
-        if e.is_object() {
-            let e_obj = e.as_object().unwrap();
-
-            // TODO: We are _cloning_ entire ASTs! This is no bueno!
-            log::debug!("    Recursively traversing children");
-            for v in e_obj.values() {
-                let child: SolAST = SolAST::new(v.clone());
-                child.traverse_internal(visitor, arg, acc);
+        let mut worklist: Vec<SolAST> = vec![self];
+        while let Some(node) = worklist.pop() {
+            log::debug!(
+                "Traversing Node: kind: {:?}, type: {:?}",
+                node.node_kind(),
+                node.node_type(),
+            );
+            if visitor.skip_node(&node, &arg) {
+                log::debug!("    Skipping");
+                continue;
             }
-        } else if e.is_array() {
-            let e_arr = e.as_array().unwrap();
-            for a in e_arr {
-                let child: SolAST = SolAST::new(a.clone());
-                child.traverse_internal(visitor, arg, acc);
+
+            if let Some(r) = visitor.visit_node(&node, &arg) {
+                log::debug!("    Visit successful");
+                result.push(r);
+            } else {
+                log::debug!("    Visit failed")
             }
+
+            let children: Vec<Value> = match node.element {
+                Some(Value::Object(o)) => o.into_iter().map(|(_, v)| v).collect(),
+                Some(Value::Array(a)) => a,
+                _ => continue,
+            };
+            // Push the children in reverse so that they are visited in order
+            worklist.extend(children.into_iter().rev().map(SolAST::new));
         }
+        result
     }
 
     /// Extracts the bounds from the AST that indicate where in the source
@@ -286,11 +357,12 @@ impl SolAST {
         (start, start + parts[1].parse::<usize>().unwrap())
     }
 
-    /// Returns the text corresponding to an AST node in the given `source`.
-    pub fn get_text(&self, source: &[u8]) -> String {
+    /// Returns the text corresponding to an AST node in the given `source`,
+    /// or an error if that text is not valid UTF-8.
+    pub fn get_text(&self, source: &[u8]) -> Result<String, FromUtf8Error> {
         let (start, end) = self.get_bounds();
         let byte_vec = source[start..end].to_vec();
-        String::from_utf8(byte_vec).expect("Slice is not u8.")
+        String::from_utf8(byte_vec)
     }
 
     /// This method is used by a variety of mutations like `FunctionCallMutation`,
@@ -0,0 +1,481 @@
+use crate::{mutator::is_statement_type, Mutant, SolAST, Source};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error;
+
+/// The name of the AST context that is written next to `gambit_results.json`
+/// with `--emit_original_ast`
+pub const AST_CONTEXT_FILENAME: &str = "ast_context.json";
+
+/// The AST context, `ast_context.json`, describes the functions, statements,
+/// and mutated expressions of each mutated file, as they appear in the AST
+/// that solc produced for the original file. Nodes are identified by their
+/// solc AST node ids, which the `ast_context` of each `gambit_results.json`
+/// entry refers to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AstContext {
+    /// The mutated files, in the order they were mutated
+    pub files: Vec<FileAstContext>,
+}
+
+impl AstContext {
+    /// Collect the contexts of `files`, keeping the first context of each
+    /// file that was mutated more than once
+    pub fn new(files: impl IntoIterator<Item = FileAstContext>) -> AstContext {
+        let mut context = AstContext::default();
+        for file in files {
+            if !context
+                .files
+                .iter()
+                .any(|f| f.original == file.original && f.sourceroot == file.sourceroot)
+            {
+                context.files.push(file);
+            }
+        }
+        context
+    }
+}
+
+/// The AST context of a single mutated file. Spans are byte offsets into the
+/// original file, from `start` (inclusive) to `end` (exclusive).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileAstContext {
+    /// The path of the mutated file, relative to `sourceroot`
+    pub original: String,
+
+    /// The source root that `original` is relative to
+    pub sourceroot: String,
+
+    /// The functions and modifiers of the file, in source order
+    pub functions: Vec<AstFunction>,
+
+    /// The statements of the file's functions and modifiers, in source order
+    pub statements: Vec<AstStatement>,
+
+    /// The expressions that were mutated, in source order
+    pub expressions: Vec<AstExpression>,
+}
+
+/// A function or modifier definition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AstFunction {
+    /// The AST node id
+    pub id: i64,
+
+    /// `function`, `constructor`, `fallback`, `receive`, or `modifier`
+    pub kind: String,
+
+    /// The name of the function, which is empty for constructors, `fallback`
+    /// and `receive` functions
+    pub name: String,
+
+    /// The contract defining the function, if it is not a free function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+
+    /// The name and parameter types of the function, e.g.
+    /// `transfer(address,uint256)`
+    pub signature: String,
+
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A statement of a function or modifier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AstStatement {
+    /// The AST node id
+    pub id: i64,
+
+    /// The statement's node type, e.g. `ExpressionStatement` or
+    /// `IfStatement`
+    pub kind: String,
+
+    /// The id of the enclosing function or modifier
+    pub function: i64,
+
+    /// The id of the enclosing statement, if the statement is nested in
+    /// another one (e.g., in the body of an `if`). Statements with the same
+    /// `parent` (and `function`) are siblings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<i64>,
+
+    /// The number of statements enclosing the statement
+    pub depth: usize,
+
+    pub start: usize,
+    pub end: usize,
+}
+
+/// An expression that a mutant replaces (part of)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AstExpression {
+    /// The AST node id
+    pub id: i64,
+
+    /// The expression's node type, e.g. `BinaryOperation`
+    pub kind: String,
+
+    /// The type of the expression, as reported by solc (e.g., `uint256`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_string: Option<String>,
+
+    /// The id of the enclosing statement, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statement: Option<i64>,
+
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The innermost AST nodes enclosing a mutant, by their ids in
+/// `ast_context.json` (see `--emit_original_ast`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MutantAstContext {
+    /// The enclosing function or modifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<i64>,
+
+    /// The innermost enclosing statement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statement: Option<i64>,
+
+    /// The innermost expression containing the mutated source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<i64>,
+}
+
+/// Collect the AST context of `source` from its AST, and set the
+/// `ast_context` of each of `mutants`, which are mutants of `source`.
+/// Only nodes with an id and a source span are described.
+pub fn collect_ast_context(
+    ast: &SolAST,
+    source: &Source,
+    mutants: &mut [Mutant],
+) -> Result<FileAstContext, Box<dyn error::Error>> {
+    let mut context = FileAstContext {
+        original: source.relative_filename()?.to_str().unwrap().to_string(),
+        sourceroot: source.sourceroot().to_str().unwrap().to_string(),
+        ..FileAstContext::default()
+    };
+    // Every expression of the functions, of which only the mutated ones are
+    // described
+    let mut expressions: Vec<AstExpression> = vec![];
+    if let Some(element) = &ast.element {
+        walk(
+            element,
+            None,
+            None,
+            &mut vec![],
+            &mut context,
+            &mut expressions,
+        );
+    }
+    // Object fields are not walked in source order
+    context.functions.sort_by_key(|f| (f.start, f.end));
+    context.statements.sort_by_key(|s| (s.start, s.end));
+
+    for mutant in mutants.iter_mut() {
+        let (start, end) = (mutant.start, mutant.end);
+        let function = innermost(
+            context.functions.iter().map(|f| (f.id, f.start, f.end)),
+            start,
+            end,
+        );
+        let statement = innermost(
+            context.statements.iter().map(|s| (s.id, s.start, s.end)),
+            start,
+            end,
+        );
+        let expression = innermost(
+            expressions.iter().map(|e| (e.id, e.start, e.end)),
+            start,
+            end,
+        );
+        if let Some(id) = expression {
+            if !context.expressions.iter().any(|e| e.id == id) {
+                let node = expressions.iter().find(|e| e.id == id).unwrap();
+                context.expressions.push(node.clone());
+            }
+        }
+        mutant.ast_context = Some(MutantAstContext {
+            function,
+            statement,
+            expression,
+        });
+    }
+    context.expressions.sort_by_key(|e| (e.start, e.end));
+    Ok(context)
+}
+
+/// The id of the smallest of `spans` that contains `start..end`
+fn innermost(
+    spans: impl Iterator<Item = (i64, usize, usize)>,
+    start: usize,
+    end: usize,
+) -> Option<i64> {
+    spans
+        .filter(|(_, s, e)| *s <= start && end <= *e)
+        .min_by_key(|(_, s, e)| e - s)
+        .map(|(id, _, _)| id)
+}
+
+/// The id and span of an AST node
+fn node_span(node: &Value) -> Option<(i64, usize, usize)> {
+    let id = node["id"].as_i64()?;
+    let mut parts = node["src"].as_str()?.split(':');
+    let start: usize = parts.next()?.parse().ok()?;
+    let length: usize = parts.next()?.parse().ok()?;
+    Some((id, start, start + length))
+}
+
+/// The signature of a function definition, from the types of its parameters
+fn signature(node: &Value) -> String {
+    let types: Vec<&str> = node["parameters"]["parameters"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|p| p["typeDescriptions"]["typeString"].as_str().unwrap_or("?"))
+        .collect();
+    let name = match node["kind"].as_str() {
+        Some(kind @ ("constructor" | "fallback" | "receive")) => kind,
+        _ => node["name"].as_str().unwrap_or_default(),
+    };
+    format!("{}({})", name, types.join(","))
+}
+
+/// Walk the AST below `node`, which is in `contract` and `function` (if any)
+/// and nested in `statements`, recording its functions, statements, and
+/// expressions
+fn walk(
+    node: &Value,
+    contract: Option<&str>,
+    function: Option<i64>,
+    statements: &mut Vec<i64>,
+    context: &mut FileAstContext,
+    expressions: &mut Vec<AstExpression>,
+) {
+    let children: Vec<&Value> = match node {
+        Value::Object(o) => o.values().collect(),
+        Value::Array(a) => a.iter().collect(),
+        _ => return,
+    };
+    let node_type = node["nodeType"].as_str().unwrap_or_default();
+    let span = node_span(node);
+    let mut contract = contract;
+    let mut function = function;
+    let mut is_statement = false;
+    match (node_type, span) {
+        ("ContractDefinition", _) => contract = node["name"].as_str(),
+        ("FunctionDefinition" | "ModifierDefinition", Some((id, start, end))) => {
+            let kind = match node_type {
+                "ModifierDefinition" => "modifier",
+                _ => node["kind"].as_str().unwrap_or("function"),
+            };
+            context.functions.push(AstFunction {
+                id,
+                kind: kind.to_string(),
+                name: node["name"].as_str().unwrap_or_default().to_string(),
+                contract: contract.map(String::from),
+                signature: signature(node),
+                start,
+                end,
+            });
+            function = Some(id);
+        }
+        (t, Some((id, start, end))) if is_statement_type(t) => {
+            if let Some(function) = function {
+                context.statements.push(AstStatement {
+                    id,
+                    kind: t.to_string(),
+                    function,
+                    parent: statements.last().copied(),
+                    depth: statements.len(),
+                    start,
+                    end,
+                });
+                statements.push(id);
+                is_statement = true;
+            }
+        }
+        (t, Some((id, start, end)))
+            if function.is_some() && !t.is_empty() && !t.ends_with("Block") =>
+        {
+            expressions.push(AstExpression {
+                id,
+                kind: t.to_string(),
+                type_string: node["typeDescriptions"]["typeString"]
+                    .as_str()
+                    .map(String::from),
+                statement: statements.last().copied(),
+                start,
+                end,
+            });
+        }
+        _ => (),
+    }
+    for child in children {
+        walk(child, contract, function, statements, context, expressions);
+    }
+    if is_statement {
+        statements.pop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{collect_ast_context, mutate_source, MutationType, MutatorConf, SolAST, Source};
+    use serde_json::json;
+
+    #[test]
+    fn test_collect_ast_context() {
+        let source = "contract C {\n    function f(uint a) public returns (uint) {\n        if (a > 0) {\n            return a + 1;\n        }\n        return a;\n    }\n}\n";
+        let src = |text: &str| format!("{}:{}:0", source.find(text).unwrap(), text.len());
+        let typed = |id: i64, node_type: &str, at: &str, type_string: &str| {
+            json!({
+                "id": id,
+                "nodeType": node_type,
+                "src": format!("{}:1:0", source.find(at).unwrap()),
+                "typeDescriptions": {"typeString": type_string},
+            })
+        };
+        let operation = |id: i64, operator: &str, text: &str, type_string: &str, left, right| {
+            json!({
+                "id": id,
+                "nodeType": "BinaryOperation",
+                "operator": operator,
+                "src": src(text),
+                "typeDescriptions": {"typeString": type_string},
+                "leftExpression": left,
+                "rightExpression": right,
+            })
+        };
+        let if_statement = json!({
+            "id": 7,
+            "nodeType": "IfStatement",
+            "src": src("if (a > 0) {\n            return a + 1;\n        }"),
+            "condition": operation(
+                8,
+                ">",
+                "a > 0",
+                "bool",
+                typed(9, "Identifier", "a > 0", "uint256"),
+                typed(10, "Literal", "0)", "int_const 0"),
+            ),
+            "trueBody": {
+                "id": 11,
+                "nodeType": "Block",
+                "src": src("{\n            return a + 1;\n        }"),
+                "statements": [{
+                    "id": 12,
+                    "nodeType": "Return",
+                    "src": src("return a + 1;"),
+                    "expression": operation(
+                        13,
+                        "+",
+                        "a + 1",
+                        "uint256",
+                        typed(14, "Identifier", "a + 1", "uint256"),
+                        typed(15, "Literal", "1;", "int_const 1"),
+                    ),
+                }],
+            },
+        });
+        let function = json!({
+            "id": 3,
+            "nodeType": "FunctionDefinition",
+            "kind": "function",
+            "name": "f",
+            "stateMutability": "nonpayable",
+            "visibility": "public",
+            "src": src(&source[17..source.len() - 3]),
+            "parameters": {
+                "id": 4,
+                "nodeType": "ParameterList",
+                "src": src("(uint a)"),
+                "parameters": [typed(5, "VariableDeclaration", "a)", "uint256")],
+            },
+            "body": {
+                "id": 6,
+                "nodeType": "Block",
+                "src": src(&source[58..source.len() - 7]),
+                "statements": [if_statement, {
+                    "id": 16,
+                    "nodeType": "Return",
+                    "src": src("return a;"),
+                    "expression": typed(17, "Identifier", "a;", "uint256"),
+                }],
+            },
+        });
+        let ast = json!({
+            "id": 1,
+            "nodeType": "SourceUnit",
+            "src": format!("0:{}:0", source.len()),
+            "nodes": [{
+                "id": 2,
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "C",
+                "src": format!("0:{}:0", source.len() - 1),
+                "nodes": [function],
+            }],
+        });
+        let conf = MutatorConf {
+            mutation_operators: vec![MutationType::BinaryOpMutation],
+            ..MutatorConf::default()
+        };
+        let ast = SolAST::new(ast);
+        let mut mutants = mutate_source(source, "virtual/C.sol", ast.clone(), &conf);
+        assert_eq!(mutants.len(), 11);
+        let tmp = tempfile::tempdir().unwrap();
+        let filename = tmp.path().join("C.sol");
+        std::fs::write(&filename, source).unwrap();
+        let source = Source::new(filename, tmp.path().to_path_buf()).unwrap();
+        let context = collect_ast_context(&ast, &source, &mut mutants).unwrap();
+
+        assert_eq!(context.original, "C.sol");
+        assert_eq!(context.functions.len(), 1);
+        let function = &context.functions[0];
+        assert_eq!((function.id, function.contract.as_deref()), (3, Some("C")));
+        assert_eq!(function.signature, "f(uint256)");
+        let statements: Vec<(i64, &str, Option<i64>, usize)> = context
+            .statements
+            .iter()
+            .map(|s| (s.id, s.kind.as_str(), s.parent, s.depth))
+            .collect();
+        assert_eq!(
+            statements,
+            vec![
+                (7, "IfStatement", None, 0),
+                (12, "Return", Some(7), 1),
+                (16, "Return", None, 0)
+            ]
+        );
+        // Only the mutated expressions are described
+        let expressions: Vec<(i64, Option<&str>, Option<i64>)> = context
+            .expressions
+            .iter()
+            .map(|e| (e.id, e.type_string.as_deref(), e.statement))
+            .collect();
+        assert_eq!(
+            expressions,
+            vec![(8, Some("bool"), Some(7)), (13, Some("uint256"), Some(12))]
+        );
+
+        // Every mutant's context resolves to the described nodes
+        for mutant in mutants.iter() {
+            let ids = mutant.ast_context.as_ref().unwrap();
+            let function = ids.function.unwrap();
+            let statement = ids.statement.unwrap();
+            let expression = ids.expression.unwrap();
+            assert!(context.functions.iter().any(|f| f.id == function));
+            let statement = context.statements.iter().find(|s| s.id == statement);
+            assert_eq!(statement.unwrap().function, function);
+            let expression = context.expressions.iter().find(|e| e.id == expression);
+            let expression = expression.unwrap();
+            assert!(expression.start <= mutant.start && mutant.end <= expression.end);
+        }
+    }
+}
@@ -1,13 +1,41 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
+use std::{convert::Infallible, path::Path, str::FromStr};
+
+use crate::{
+    parse_seed, CommentStyle, ExportFormat, LineRange, MessageFormat, MutationType, OutdirLayout,
+    ValidationMode, EXIT_CODES_HELP,
+};
 
 static DEFAULT_NO_EXPORT_MUTANTS: bool = false;
 static DEFAULT_NO_OVERWRITE: bool = false;
 static DEFAULT_RANDOM_SEED: bool = false;
 static DEFAULT_SEED: u64 = 0;
+static DEFAULT_LEGACY_SAMPLING: bool = false;
 static DEFAULT_SKIP_VALIDATE: bool = false;
+static DEFAULT_SKIP_PURE_VIEW: bool = false;
+static DEFAULT_TAG_PURE_VIEW: bool = false;
+static DEFAULT_INCLUDE_CALLED_LIBRARIES: bool = false;
+static DEFAULT_CONTRACT_CLOSURE: bool = false;
+static DEFAULT_NO_MUTATE_MODIFIERS: bool = false;
+static DEFAULT_NO_MUTATE_UNCHECKED: bool = false;
+static DEFAULT_ONLY_MUTATE_PUBLIC_STATE_CHANGING_PATHS: bool = false;
+pub static DEFAULT_MAX_AST_DEPTH: usize = 512;
+pub static DEFAULT_SUMMARY_LIMIT: usize = 200;
 static DEFAULT_SOLC_OPTIMIZE: bool = false;
+static DEFAULT_ALLOW_REMAP_OVERRIDE: bool = false;
+static DEFAULT_EXPORT_FULL_PROJECT: bool = false;
+static DEFAULT_LINK_INTO_MUTANTS: bool = false;
+static DEFAULT_POST_EXPORT_TIMEOUT_SECONDS: u64 = 60;
+static DEFAULT_SOLC_RETRIES: usize = 2;
+static DEFAULT_STRICT_HOOKS: bool = false;
+static DEFAULT_ALLOW_EXPERIMENTAL: bool = false;
+static DEFAULT_FAIL_ON_ZERO_MUTANTS: bool = false;
+static DEFAULT_LOCATIONS_ONLY: bool = false;
+static DEFAULT_SKIP_TRIVIALLY_EQUIVALENT: bool = false;
 static DEFAULT_SOLC: &str = "solc";
+#[cfg(feature = "vyper")]
+static DEFAULT_VYPER: &str = "vyper";
 
 fn default_no_export_mutants() -> bool {
     DEFAULT_NO_EXPORT_MUTANTS
@@ -25,18 +53,122 @@ fn default_seed() -> u64 {
     DEFAULT_SEED
 }
 
+/// Deserialize a seed like `parse_seed()` parses it on the command line
+fn deserialize_seed<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let seed = match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    };
+    parse_seed(&seed).map_err(serde::de::Error::custom)
+}
+
+fn default_legacy_sampling() -> bool {
+    DEFAULT_LEGACY_SAMPLING
+}
+
 fn default_skip_validate() -> bool {
     DEFAULT_SKIP_VALIDATE
 }
 
+fn default_fail_on_zero_mutants() -> bool {
+    DEFAULT_FAIL_ON_ZERO_MUTANTS
+}
+
+fn default_locations_only() -> bool {
+    DEFAULT_LOCATIONS_ONLY
+}
+
+fn default_skip_pure_view() -> bool {
+    DEFAULT_SKIP_PURE_VIEW
+}
+
+fn default_tag_pure_view() -> bool {
+    DEFAULT_TAG_PURE_VIEW
+}
+
+fn default_skip_trivially_equivalent() -> bool {
+    DEFAULT_SKIP_TRIVIALLY_EQUIVALENT
+}
+
+fn default_include_called_libraries() -> bool {
+    DEFAULT_INCLUDE_CALLED_LIBRARIES
+}
+
+fn default_contract_closure() -> bool {
+    DEFAULT_CONTRACT_CLOSURE
+}
+
+fn default_no_mutate_modifiers() -> bool {
+    DEFAULT_NO_MUTATE_MODIFIERS
+}
+
+fn default_no_mutate_unchecked() -> bool {
+    DEFAULT_NO_MUTATE_UNCHECKED
+}
+
+fn default_only_mutate_public_state_changing_paths() -> bool {
+    DEFAULT_ONLY_MUTATE_PUBLIC_STATE_CHANGING_PATHS
+}
+
+fn default_max_ast_depth() -> usize {
+    DEFAULT_MAX_AST_DEPTH
+}
+
 fn default_solc_optimize() -> bool {
     DEFAULT_SOLC_OPTIMIZE
 }
 
+fn default_allow_remap_override() -> bool {
+    DEFAULT_ALLOW_REMAP_OVERRIDE
+}
+
+fn default_export_full_project() -> bool {
+    DEFAULT_EXPORT_FULL_PROJECT
+}
+
+fn default_link_into_mutants() -> bool {
+    DEFAULT_LINK_INTO_MUTANTS
+}
+
+fn default_post_export_timeout_seconds() -> u64 {
+    DEFAULT_POST_EXPORT_TIMEOUT_SECONDS
+}
+
+fn default_solc_retries() -> usize {
+    DEFAULT_SOLC_RETRIES
+}
+
+fn default_strict_hooks() -> bool {
+    DEFAULT_STRICT_HOOKS
+}
+
+fn default_allow_experimental() -> bool {
+    DEFAULT_ALLOW_EXPERIMENTAL
+}
+
+/// The help of `--allow_experimental`, listing the experimental operators
+fn allow_experimental_help() -> String {
+    let ops: Vec<String> = MutationType::experimental_mutation_operators()
+        .iter()
+        .map(|op| op.short_name())
+        .collect();
+    format!(
+        "Allow experimental mutation operators, whose behavior may change \
+         between releases [experimental: {}]",
+        ops.join(", ")
+    )
+}
+
 fn default_solc() -> String {
     DEFAULT_SOLC.to_string()
 }
 
+#[cfg(feature = "vyper")]
+fn default_vyper() -> String {
+    DEFAULT_VYPER.to_string()
+}
+
 fn default_source_root() -> Option<String> {
     None
 }
@@ -45,6 +177,10 @@ fn default_num_mutants() -> Option<usize> {
     None
 }
 
+fn default_max_validation_seconds() -> Option<u64> {
+    None
+}
+
 /// Mutate solidity code.
 ///
 /// The `mutate` command requires either a `--filename` or a `--json`
@@ -56,17 +192,24 @@ fn default_num_mutants() -> Option<usize> {
 /// 2. `gambit mutate --json path/to/config.json`: this runs mutations specified
 ///    in the configuration file
 ///
-/// Only one filename can be specified from command line at a time, but multiple
-/// files can be specified in a configuration.
+/// 3. `gambit mutate --filename A.sol B.sol`: this mutates both files into
+///    the same output directory, numbering their mutants consecutively.
 #[derive(Debug, Clone, Parser, Deserialize, Serialize)]
-#[command(rename_all = "snake_case")]
+#[command(rename_all = "snake_case", after_help = EXIT_CODES_HELP)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct MutateParams {
     /// Json file with config
     #[arg(long, short, conflicts_with = "filename")]
     pub json: Option<String>,
 
-    /// The name of the file to mutate. Note that this filename must be a
+    /// Don't look for a `gambit.json` or `gambit.toml` configuration in the
+    /// current directory and its ancestors when neither `--filename` nor
+    /// `--json` is given
+    #[arg(long, default_value = "false")]
+    #[serde(skip)]
+    pub no_discover: bool,
+
+    /// The names of the files to mutate. Note that each filename must be a
     /// descendent of the source root (`.` by default, or specified by the
     /// `--sourceroot` flag).
     ///
@@ -84,7 +227,24 @@ pub struct MutateParams {
     ///
     /// will work because `--sourceroot` is by default `.` which, in this case,
     /// expands to `/path/to`, which contains `file.sol`.
-    #[arg(long, short, conflicts_with = "json")]
+    ///
+    /// Several files can be mutated in one run, either by passing several
+    /// filenames (`--filename A.sol B.sol`) or by repeating the flag.
+    #[arg(
+        id = "filename",
+        long = "filename",
+        short,
+        value_name = "FILENAME",
+        num_args = 1..,
+        conflicts_with = "json"
+    )]
+    #[serde(skip)]
+    pub filenames: Vec<String>,
+
+    /// The name of the file to mutate, as given by a configuration file entry.
+    /// Each of the `--filename`s of a command line run is resolved into its
+    /// own parameter set with this field.
+    #[arg(skip)]
     pub filename: Option<String>,
 
     /// If specified, randomly downsamples the number of mutants
@@ -101,10 +261,18 @@ pub struct MutateParams {
     /// Specify a seed for randomized down sampling. By default seed=0 is used
     /// and is deterministic, but nondeterminism can be enabled with the
     /// `--random-seed` flag
-    #[arg(long, short, default_value = "0")]
-    #[serde(default = "default_seed")]
+    #[arg(long, short, default_value = "0", value_parser = parse_seed)]
+    #[serde(default = "default_seed", deserialize_with = "deserialize_seed")]
     pub seed: u64,
 
+    /// Sample mutants in the order they were generated, as Gambit 0.2.1 and
+    /// earlier did, instead of in an order derived from their contents. Only
+    /// use this to reproduce samples of older runs: with it, a seed selects
+    /// different mutants whenever the generation order changes
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_legacy_sampling")]
+    pub legacy_sampling: bool,
+
     /// Output directory to place results of mutation
     #[arg(long, short)]
     pub outdir: Option<String>,
@@ -117,20 +285,137 @@ pub struct MutateParams {
     #[serde(default = "default_source_root")]
     pub sourceroot: Option<String>,
 
-    /// Specify the mutation operators
+    /// Specify the mutation operators. An operator of the form `@file` is
+    /// replaced by the operators listed in `file`, a JSON (or YAML, for
+    /// `.yaml`/`.yml` files) list of operator names. In a configuration file,
+    /// `file` is relative to the configuration file, and an operator may also
+    /// be an object restricting its replacements (see `MutationSpec`).
     #[arg(long, num_args(1..))]
-    pub mutations: Option<Vec<String>>,
+    pub mutations: Option<Vec<MutationSpec>>,
+
+    /// Allow experimental mutation operators in `--mutations`. Without it,
+    /// requesting an experimental operator is a configuration error.
+    #[arg(long, default_value = "false", help = allow_experimental_help())]
+    #[serde(default = "default_allow_experimental")]
+    pub allow_experimental: bool,
 
     /// Skip mutant export
     #[arg(long, default_value_t = DEFAULT_NO_EXPORT_MUTANTS)]
     #[serde(default = "default_no_export_mutants")]
     pub no_export: bool,
 
-    /// Overwrite output directory (by default, a warning will print and this will exit)
+    /// Export each mutant as an overlay of the whole project: every file in
+    /// the project root is linked (or copied, where symlinks are not
+    /// supported) into `mutants/<id>/`, with the mutated file replacing its
+    /// original
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_export_full_project")]
+    pub export_full_project: bool,
+
+    /// The root of a Foundry or Hardhat project. Its `remappings.txt` and
+    /// `foundry.toml` remappings are passed to solc (explicit
+    /// `--solc_remappings` take precedence), and its `src/`, `lib/`, and
+    /// `node_modules/` directories are added as include paths. When any of
+    /// those directories exist, the project root is also solc's base path
+    /// unless `--solc_base_path` is given. This is also the directory
+    /// mirrored by `--export_full_project`, which defaults to the sourceroot
+    #[arg(long)]
+    pub project_root: Option<String>,
+
+    /// How each mutant is exported: `full` writes the whole mutated file to
+    /// `mutants/<id>/<path>` (the default), while `patch` writes a unified
+    /// diff to `mutants/<id>/<path>.patch` that `git apply` applies from the
+    /// sourceroot (or the `--project_root`, with `--export_full_project`)
+    #[arg(long, value_enum, default_value = "full")]
+    #[serde(default)]
+    pub export_format: ExportFormat,
+
+    /// How exported mutants are laid out in the output directory: `nested`
+    /// writes each mutant to its own directory, `mutants/<id>/<path>` (the
+    /// default), while `flat` writes the mutants of each contract to
+    /// `mutants-<contract>/mutant_<id>.sol`, grouping mutants outside any
+    /// contract by the stem of their file's name. Same-named contracts of
+    /// different files are told apart by a `-2`, `-3`, ... suffix. `flat`
+    /// cannot be combined with `--export_full_project`
+    #[arg(long, value_enum, default_value = "nested")]
+    #[serde(default)]
+    pub outdir_layout: OutdirLayout,
+
+    /// Identify each mutant by a hash of its file, span, operator, and
+    /// replacement instead of by its position, so that a mutant keeps its id
+    /// across runs that generate different sets of mutants. The id is used
+    /// for the mutant's directory, in `mutants.log`, and in
+    /// `gambit_results.json`
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub stable_ids: bool,
+
+    /// Files and directories (e.g., prover configurations and specs) to copy
+    /// into each exported mutant directory, at their paths relative to the
+    /// sourceroot
+    #[arg(long, num_args(1..))]
+    pub copy_into_mutants: Option<Vec<String>>,
+
+    /// Symlink the `--copy_into_mutants` files and directories into each
+    /// exported mutant directory instead of copying them
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_link_into_mutants")]
+    pub link_into_mutants: bool,
+
+    /// A command (run by `sh -c`) to post-process each exported mutant, e.g.,
+    /// a formatter. The placeholders `{mutant_path}` (the absolute path of
+    /// the exported mutant file), `{id}`, `{op}`, and `{original}` (the
+    /// original file, relative to the sourceroot) are substituted verbatim.
+    /// The command's output is captured in `post_export.stdout` and
+    /// `post_export.stderr` of the mutant directory
+    #[arg(long)]
+    pub post_export_cmd: Option<String>,
+
+    /// Kill `--post_export_cmd` invocations that run longer than this many
+    /// seconds
+    #[arg(long, default_value = "60")]
+    #[serde(default = "default_post_export_timeout_seconds")]
+    pub post_export_timeout_seconds: u64,
+
+    /// Run at most this many `--post_export_cmd` invocations at a time.
+    /// Defaults to the available parallelism
+    #[arg(long)]
+    pub post_export_jobs: Option<usize>,
+
+    /// Exit with a non-zero exit code (6) if a `--post_export_cmd` invocation
+    /// fails or times out. By default, failures are only reported
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_strict_hooks")]
+    pub strict_hooks: bool,
+
+    /// Never overwrite an existing output directory: print a warning and skip
+    /// it instead
     #[arg(long, default_value = "false")]
     #[serde(default = "default_no_overwrite")]
     pub no_overwrite: bool,
 
+    /// Replace the outputs of an earlier run in an existing output directory
+    /// without asking. Without it, Gambit asks for confirmation when run in a
+    /// terminal, and exits otherwise. Files that Gambit did not write are
+    /// kept either way
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub overwrite: bool,
+
+    /// The name of the subdirectory of the output directory that mutants are
+    /// exported to (default: `mutants`)
+    #[arg(long, value_name = "NAME")]
+    #[serde(default)]
+    pub mutants_subdir: Option<String>,
+
+    /// Delete the whole output directory, including files that Gambit did not
+    /// write, even if it is the current directory, an ancestor of it, or the
+    /// root of a repository, or if its mutants subdirectory was not written
+    /// by Gambit
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub force: bool,
+
     /// Solidity binary name, e.g., --solc solc8.10, --solc 7.5, etc.
     #[arg(long, default_value = "solc")]
     #[serde(default = "default_solc")]
@@ -145,7 +430,27 @@ pub struct MutateParams {
     #[arg(long)]
     pub solc_evm_version: Option<String>,
 
-    /// Specify function names to mutate
+    /// Further solc settings, in the format of the `settings` of solc's
+    /// standard JSON input (e.g., `{"viaIR": true, "optimizer": {"runs":
+    /// 1000}}`): either a JSON object, or the path of a JSON file holding
+    /// one. `viaIR`, `evmVersion`, and the optimizer's `enabled` and `runs`
+    /// also apply to solc's command line (`--via-ir`, `--evm-version`,
+    /// `--optimize`, `--optimize-runs`). Unknown settings are ignored with a
+    /// warning
+    #[arg(long)]
+    pub solc_settings: Option<SolcSettings>,
+
+    /// Retry solc up to this many times, with exponential backoff, when it
+    /// fails for reasons unrelated to its input (it is terminated with a
+    /// signal, exits with a non-zero code without any error output, or cannot
+    /// be started). Mutants that still fail to validate are exported, flagged
+    /// with `validation_error`
+    #[arg(long, default_value = "2")]
+    #[serde(default = "default_solc_retries")]
+    pub solc_retries: usize,
+
+    /// Specify function names to mutate. A name can be qualified by its
+    /// contract (`C.f`) and by its parameter types (`C.f(address,uint256)`).
     #[arg(long, num_args(1..))]
     pub functions: Option<Vec<String>>,
 
@@ -153,6 +458,175 @@ pub struct MutateParams {
     #[arg(long)]
     pub contract: Option<String>,
 
+    /// Exit with a configuration error (2) if a `--contract` or `--functions`
+    /// name matches nothing in any of the mutated files. By default, such
+    /// names are only warned about
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub strict: bool,
+
+    /// Only keep mutants that start on one of these lines of the mutated
+    /// file, given as inclusive `start:end` ranges (or single lines). This
+    /// combines with `--functions` and `--contract`
+    #[arg(long, num_args(1..))]
+    pub source_lines: Option<Vec<LineRange>>,
+
+    /// Specify function names not to mutate. This takes precedence over
+    /// `--functions`
+    #[arg(long, num_args(1..))]
+    pub exclude_functions: Option<Vec<String>>,
+
+    /// Specify contract names not to mutate. This takes precedence over
+    /// `--contract`
+    #[arg(long, num_args(1..))]
+    pub exclude_contracts: Option<Vec<String>>,
+
+    /// With `--contract`, also mutate the functions that the contract
+    /// inherits and does not override, whether their base contracts are
+    /// defined in the same file or in imported files. Mutants are tagged with
+    /// their `"defining_contract"` and `"filter_contract"` in
+    /// `gambit_results.json`
+    #[arg(long, default_value = "false", requires = "contract")]
+    #[serde(default = "default_contract_closure")]
+    pub contract_closure: bool,
+
+    /// Do not mutate functions whose state mutability is `view` or `pure`
+    #[arg(long, default_value = "false", conflicts_with = "tag_pure_view")]
+    #[serde(default = "default_skip_pure_view")]
+    pub skip_pure_view: bool,
+
+    /// Mutate `view` and `pure` functions, but tag their mutants with a
+    /// `"mutability"` field in `gambit_results.json`
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_tag_pure_view")]
+    pub tag_pure_view: bool,
+
+    /// Also mutate the internal functions of libraries in other files that
+    /// the mutated file references (e.g., with `using L for T;` or `L.f(x)`).
+    /// Their mutants are tagged with a `"target_file"` field in
+    /// `gambit_results.json`
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_include_called_libraries")]
+    pub include_called_libraries: bool,
+
+    /// Do not mutate the bodies of function modifiers (e.g., the `require`
+    /// of an `onlyOwner` modifier), which are mutated by default and selected
+    /// by name by `--functions`, like functions
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_no_mutate_modifiers")]
+    pub no_mutate_modifiers: bool,
+
+    /// Do not mutate the statements of `unchecked { ... }` blocks, whose
+    /// arithmetic is mutated by default
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_no_mutate_unchecked")]
+    pub no_mutate_unchecked: bool,
+
+    /// Only mutate functions that are reachable from an entry point (a
+    /// public or external function, constructor, `receive` or `fallback`
+    /// function) of the mutated contract, skipping dead internal helpers whose
+    /// mutants no test can kill
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_only_mutate_public_state_changing_paths")]
+    pub only_mutate_public_state_changing_paths: bool,
+
+    /// Do not mutate AST nodes nested more than this many levels deep in
+    /// solc's JSON AST. This guards against stack overflows on pathological
+    /// code, like machine-generated sums of thousands of terms
+    #[arg(long, default_value_t = DEFAULT_MAX_AST_DEPTH)]
+    #[serde(default = "default_max_ast_depth")]
+    pub max_ast_depth: usize,
+
+    /// The style of the comment describing the mutation that is inserted
+    /// above each mutated line: `natspec` (`///`, the default), `line` (`//`),
+    /// or `block` (`/* ... */`). Documentation generators pick up NatSpec
+    /// comments as documentation of the following statement
+    #[arg(long, value_enum, default_value = "natspec")]
+    #[serde(default)]
+    pub comment_style: CommentStyle,
+
+    /// How mutants are validated: `full` compiles each mutant (the default),
+    /// `fast` only checks that it parses (`solc --stop-after parsing`), and
+    /// `two-stage` checks that it parses before compiling it, which accepts
+    /// the same mutants as `full` while rejecting syntax errors cheaply. The
+    /// stages' rejections and timings are recorded in the `statistics` of
+    /// each file in `gambit_results.json`
+    #[arg(long, value_enum, default_value = "full")]
+    #[serde(default)]
+    pub validation_mode: ValidationMode,
+
+    /// Validate each mutant by writing it to a temporary file next to the
+    /// original and compiling that file, as Gambit 0.2.1 and earlier did,
+    /// instead of passing the mutated contents to `solc --standard-json`
+    /// under the original file's name. Only needed for solc versions without
+    /// standard JSON support for the project's import paths
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub legacy_validation: bool,
+
+    /// Extract the ABI of the original file's contracts and of each fully
+    /// compiled mutant during validation, and record whether the mutant
+    /// changes it as `"abi_changed"` in `gambit_results.json`. Mutants are
+    /// only fully compiled by the `full` and `two-stage` validation modes
+    #[arg(
+        long,
+        default_value = "false",
+        conflicts_with_all = ["legacy_validation", "skip_validate"]
+    )]
+    #[serde(default)]
+    pub compare_abi: bool,
+
+    /// Validate up to this many mutants at a time. Defaults to the available
+    /// parallelism
+    #[arg(long)]
+    pub jobs: Option<usize>,
+
+    /// Store the diffs of mutants that are longer than this many bytes in
+    /// their own files (`diffs/<mid>.diff` in the output directory), which are
+    /// referenced by the `diff_file` of the mutant's entry in
+    /// `gambit_results.json`. This keeps results of large runs small
+    #[arg(long)]
+    pub max_inline_diff_bytes: Option<usize>,
+
+    /// Store the diffs of mutants gzip-compressed in their own files
+    /// (`diffs/<mid>.diff.gz` in the output directory) instead of in
+    /// `gambit_results.json`. With `--max_inline_diff_bytes`, only the diffs
+    /// longer than that are moved out of `gambit_results.json`
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub compress_diffs: bool,
+
+    /// Write the functions, statements, and mutated expressions of each
+    /// mutated file, as solc parsed them, to `ast_context.json` in the output
+    /// directory, and record the nodes enclosing each mutant in the
+    /// `ast_context` field of its `gambit_results.json` entry
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub emit_original_ast: bool,
+
+    /// Write a job manifest, `manifest.json`, to the output directory: the
+    /// exported file, original file, operator, and location of each mutant,
+    /// along with the Gambit version and the configurations used, for
+    /// harnesses that verify each mutant in turn
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub emit_manifest: bool,
+
+    /// Function names (e.g., `require`) whose calls are never deleted by
+    /// `DeleteExpressionMutation`. Defaults to `require`, `assert`, and
+    /// `revert`; pass an empty list to delete every expression statement
+    #[arg(long, num_args(0..))]
+    pub delete_expression_exclusions: Option<Vec<String>>,
+
+    /// Drop mutants that are trivially equivalent to another mutant of the
+    /// same statement. Currently, this drops the `DeleteExpressionMutation`
+    /// mutant of a function's only statement when it is a `require` that
+    /// `RequireMutation` weakens to `true`, or an assignment (e.g., a trivial
+    /// setter) that `AssignmentMutation` already mutates
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_skip_trivially_equivalent")]
+    pub skip_trivially_equivalent: bool,
+
     /// Basepath argument to solc
     #[arg(long)]
     pub solc_base_path: Option<String>,
@@ -169,10 +643,181 @@ pub struct MutateParams {
     #[arg(long, num_args(1..))]
     pub solc_remappings: Option<Vec<String>>,
 
+    /// When two remappings share a prefix but have different targets, use the
+    /// last one (with a warning) instead of reporting an error
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_allow_remap_override")]
+    pub allow_remap_override: bool,
+
     /// Specify this
     #[arg(long, default_value = "false")]
     #[serde(default = "default_skip_validate")]
     pub skip_validate: bool,
+
+    /// Stop validating mutants once validation has taken this many seconds in
+    /// total. The remaining mutants are exported without being validated and
+    /// are marked as unvalidated in `gambit_results.json` and `mutants.log`
+    #[arg(long, default_value = None)]
+    #[serde(default = "default_max_validation_seconds")]
+    pub max_validation_seconds: Option<u64>,
+
+    /// Exit with a non-zero exit code (5) if no mutants were generated
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_fail_on_zero_mutants")]
+    pub fail_on_zero_mutants: bool,
+
+    /// Only print where the mutation operators apply, as a compact JSON array
+    /// of `{op, file, line, col, span}` objects, without generating,
+    /// validating, or exporting any mutants
+    #[arg(long, default_value = "false")]
+    #[serde(default = "default_locations_only")]
+    pub locations_only: bool,
+
+    /// Only estimate how many mutants each mutation operator generates in
+    /// each file, without generating, validating, or exporting any mutants:
+    /// print a table of files by operators and write it to `estimate.json` in
+    /// the output directory
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub estimate: bool,
+
+    /// Explain how each mutation operator fares at `LINE:COL` of the file:
+    /// for every AST node covering that location, print whether each
+    /// operator applies and the mutants it proposes, without validating or
+    /// exporting any mutants
+    #[arg(long, value_name = "LINE:COL")]
+    pub explain: Option<String>,
+
+    /// Explain the location of mutant `--mid` of the `gambit_results.json` in
+    /// this output directory (see `--explain`)
+    #[arg(long, requires = "mid")]
+    pub from_results: Option<String>,
+
+    /// The id of the mutant to explain (see `--from_results`)
+    #[arg(long, requires = "from_results")]
+    pub mid: Option<String>,
+
+    /// The language of the file to mutate
+    #[cfg(feature = "vyper")]
+    #[arg(long, value_enum, default_value = "solidity")]
+    #[serde(default)]
+    pub language: Language,
+
+    /// Vyper binary name, used to parse and validate `--language vyper` files
+    #[cfg(feature = "vyper")]
+    #[arg(long, default_value = "vyper")]
+    #[serde(default = "default_vyper")]
+    pub vyper: String,
+}
+
+/// A mutation operator of `mutations`: either its name, or (in a
+/// configuration file) an object that also restricts the replacements it
+/// tries, e.g. `{"operator": "binary-op-mutation", "replacements": ["+", "-"]}`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MutationSpec {
+    Name(String),
+    Detailed(DetailedOpConfig),
+}
+
+/// A mutation operator along with the replacements it is restricted to (see
+/// `MutationType::replacement_tokens`)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DetailedOpConfig {
+    pub operator: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacements: Option<Vec<String>>,
+}
+
+impl MutationSpec {
+    /// The name of the requested operator
+    pub fn name(&self) -> &str {
+        match self {
+            MutationSpec::Name(name) => name,
+            MutationSpec::Detailed(config) => &config.operator,
+        }
+    }
+
+    /// The replacements the operator is restricted to, if any
+    pub fn replacements(&self) -> Option<&[String]> {
+        match self {
+            MutationSpec::Name(_) => None,
+            MutationSpec::Detailed(config) => config.replacements.as_deref(),
+        }
+    }
+}
+
+impl From<String> for MutationSpec {
+    fn from(name: String) -> Self {
+        MutationSpec::Name(name)
+    }
+}
+
+impl FromStr for MutationSpec {
+    type Err = Infallible;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(MutationSpec::Name(name.to_string()))
+    }
+}
+
+/// The solc settings of `solc_settings`: either a JSON object, or the path of
+/// a JSON file holding one
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SolcSettings {
+    Inline(serde_json::Map<String, serde_json::Value>),
+    File(String),
+}
+
+impl SolcSettings {
+    /// The settings, reading them from their file, which is resolved relative
+    /// to `base`, if necessary
+    pub fn load(&self, base: &Path) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+        match self {
+            SolcSettings::Inline(settings) => Ok(settings.clone()),
+            SolcSettings::File(path) => {
+                let path = base.join(path);
+                let contents = std::fs::read_to_string(&path).map_err(|e| {
+                    format!("couldn't read solc settings {}: {}", path.display(), e)
+                })?;
+                match serde_json::from_str(&contents) {
+                    Ok(serde_json::Value::Object(settings)) => Ok(settings),
+                    Ok(_) => Err(format!(
+                        "solc settings {} must hold a JSON object",
+                        path.display()
+                    )),
+                    Err(e) => Err(format!("invalid solc settings {}: {}", path.display(), e)),
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for SolcSettings {
+    type Err = String;
+
+    /// Parse a JSON object, or take anything else as the path of a file
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.trim_start().starts_with('{') {
+            return Ok(SolcSettings::File(s.to_string()));
+        }
+        serde_json::from_str(s)
+            .map(SolcSettings::Inline)
+            .map_err(|e| format!("invalid solc settings: {}", e))
+    }
+}
+
+/// The languages that Gambit can mutate
+#[cfg(feature = "vyper")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    Solidity,
+    /// Experimental: only a reduced set of mutation operators is supported
+    Vyper,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -180,11 +825,33 @@ pub struct GambitConfigFile {
     pub configurations: Vec<MutateParams>,
 }
 
+/// Gambit's command line: a command along with the options shared by all
+/// commands
 #[derive(Parser)]
+#[command(after_help = EXIT_CODES_HELP)]
+pub struct Cli {
+    /// How to print errors and warnings: `json` prints each of them on stderr
+    /// as a single-line JSON object with `level`, `code`, `message`, and
+    /// `file` fields (and `span` and `notes` fields when available)
+    #[arg(long, global = true, value_enum, default_value = "human")]
+    pub message_format: MessageFormat,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
 #[clap(rename_all = "kebab-case")]
 pub enum Command {
     Mutate(Box<MutateParams>), // Maybe we want to do other things in the future like support checking mutants?
+    /// Check a configuration without generating any mutants: resolve its
+    /// paths, check that its mutation operators are recognized, compile each
+    /// file once, and check that the contract and functions selected by
+    /// `--contract` and `--functions` are defined. Nothing is written to the
+    /// output directory. Exits with a non-zero code if any check fails
+    Check(Box<MutateParams>),
     Summary(SummaryParams),
+    ValidateResults(ValidateResultsParams),
 }
 
 /// Summarize mutants generated by a Gambit run. By default, all mutant ids are
@@ -201,4 +868,105 @@ pub struct SummaryParams {
     /// Gambit results directory
     #[arg(long, default_value = crate::DEFAULT_GAMBIT_OUTPUT_DIRECTORY)]
     pub mutation_directory: String,
+
+    /// Print statistics on the generated mutants instead of printing each
+    /// mutant
+    #[arg(long, default_value = "false")]
+    pub statistics: bool,
+
+    /// How to group mutants when printing statistics
+    #[arg(long, value_enum, default_value_t = StatisticsGrouping::Operator)]
+    pub group_by: StatisticsGrouping,
+
+    /// When printing statistics by operator, only list the operators that were
+    /// requested in the run (as recorded in `gambit_manifest.json`)
+    #[arg(long, default_value = "false")]
+    pub requested_only: bool,
+
+    /// Compare two mutation runs (output directories or `gambit_results.json`
+    /// files), listing the mutants that were added, removed, or moved from
+    /// the first run to the second. Mutants are matched by operator, file,
+    /// and original and replacement text, never by id
+    #[arg(long, num_args(2), value_names = ["DIR_A", "DIR_B"])]
+    pub compare: Option<Vec<String>>,
+
+    /// When comparing runs, match mutants whose lines differ by at most this
+    /// many lines (matched mutants on different lines are reported as moved)
+    #[arg(long, default_value = "10")]
+    pub line_tolerance: usize,
+
+    /// The output format. With `json`, a single JSON object is printed: the
+    /// comparison of `--compare`, or the mutant counts of `--statistics`
+    /// along with the selected mutants (unless `--statistics` is given)
+    #[arg(long, value_enum, default_value_t = SummaryFormat::Text)]
+    pub format: SummaryFormat,
+
+    /// List at most this many mutants, in order of their ids, followed by
+    /// the number of mutants that were not shown. `0` lists every mutant.
+    /// This does not affect `--statistics`
+    #[arg(long, default_value_t = DEFAULT_SUMMARY_LIMIT)]
+    pub limit: usize,
+
+    /// Skip this many mutants, in order of their ids, before listing mutants
+    /// (see `--limit`)
+    #[arg(long, default_value = "0")]
+    pub offset: usize,
+
+    /// Only summarize the mutants generated by these operators (e.g.,
+    /// `binary-op-mutation`), both when listing mutants and when printing
+    /// statistics
+    #[arg(long, num_args(1..))]
+    pub ops: Option<Vec<String>>,
+
+    /// Only summarize the mutants of original files whose path (relative to
+    /// the sourceroot) contains this substring
+    #[arg(long)]
+    pub file: Option<String>,
+
+    /// Only summarize the mutants that change the ABI of their original
+    /// file's contracts (see `--compare_abi`)
+    #[arg(long, default_value = "false")]
+    pub abi_changed: bool,
+
+    /// Print the absolute path of each exported mutant along with a
+    /// ready-to-run `diff -u` command against its original file
+    #[arg(long, default_value = "false")]
+    pub with_commands: bool,
+}
+
+/// Check a `gambit_results.json` file against the results schema and its
+/// invariants (unique ids, positive line/column numbers, and diffs that apply
+/// to the original files), reporting each violation and exiting with a
+/// non-zero exit code if any are found
+#[derive(Debug, Clone, Parser, Deserialize, Serialize)]
+#[command(rename_all = "kebab-case")]
+pub struct ValidateResultsParams {
+    /// The `gambit_results.json` file to validate
+    #[arg(required_unless_present = "print_schema")]
+    pub results: Option<String>,
+
+    /// Print the JSON Schema of `gambit_results.json` and exit
+    #[arg(long, default_value = "false")]
+    pub print_schema: bool,
+}
+
+/// The output formats of `gambit summary`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+pub enum SummaryFormat {
+    /// Human-readable tables
+    Text,
+    /// A JSON object, for tooling
+    Json,
+}
+
+/// The ways that `gambit summary --statistics` can group mutants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+pub enum StatisticsGrouping {
+    /// Group mutants by mutation operator
+    Operator,
+    /// Group mutants by the version of solc used to validate them
+    Solc,
+    /// Group mutants by the file they mutate (read from the mutant index,
+    /// `index.json`, when there is one)
+    File,
 }
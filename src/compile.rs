@@ -1,15 +1,24 @@
-use crate::invoke_command;
 use crate::SolAST;
+use crate::{
+    invoke_command, invoke_command_with_input, parse_json_unbounded, report, resolve_executable,
+    Diagnostic, DiagnosticCode, GambitError,
+};
 use itertools::join;
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 use std::{
+    collections::{BTreeMap, HashMap},
     error,
     fs::File,
     path::{Path, PathBuf},
+    thread,
+    time::Duration,
 };
 
 type CompilerRet = (i32, Vec<u8>, Vec<u8>);
 
+/// The ABI of each contract of compiled sources, keyed by the contract's name
+pub type ContractAbis = BTreeMap<String, Value>;
+
 /// This module provides a wrapper around the solc compiler, as well as several
 /// helper functions. The main object of interest in this module is `Solc`.
 
@@ -21,6 +30,66 @@ static BASEPATH: &str = "--base-path";
 static OPTIMIZE: &str = "--optimize";
 static DOT_JSON: &str = ".json";
 static EVM_VERSION: &str = "--evm-version";
+static VIA_IR: &str = "--via-ir";
+static OPTIMIZE_RUNS: &str = "--optimize-runs";
+/// The delay before the first retry of a transient solc failure, which doubles
+/// with each further retry
+static SOLC_RETRY_BACKOFF_MS: u64 = 100;
+static STANDARD_JSON: &str = "--standard-json";
+
+/// The standard JSON settings that `--solc_settings` passes through to solc
+static PASSTHROUGH_SETTINGS: [&str; 8] = [
+    "debug",
+    "eofVersion",
+    "evmVersion",
+    "libraries",
+    "metadata",
+    "modelChecker",
+    "optimizer",
+    "viaIR",
+];
+
+/// The standard JSON settings that Gambit sets itself
+static GAMBIT_SETTINGS: [&str; 3] = ["outputSelection", "remappings", "stopAfter"];
+
+/// Split solc standard JSON `settings` (see `--solc_settings`) into those that
+/// are passed through to solc, and warnings about the others, which are
+/// ignored: solc rejects unknown settings, and Gambit sets some settings
+/// itself
+pub fn check_solc_settings(settings: &Map<String, Value>) -> (Map<String, Value>, Vec<String>) {
+    let mut passed = Map::new();
+    let mut warnings = vec![];
+    for (key, value) in settings {
+        if PASSTHROUGH_SETTINGS.contains(&key.as_str()) {
+            passed.insert(key.clone(), value.clone());
+        } else if GAMBIT_SETTINGS.contains(&key.as_str()) {
+            warnings.push(format!(
+                "Ignoring solc setting `{}`, which Gambit sets itself",
+                key
+            ));
+        } else {
+            warnings.push(format!(
+                "Ignoring unknown solc setting `{}` (expected one of {})",
+                key,
+                PASSTHROUGH_SETTINGS.join(", ")
+            ));
+        }
+    }
+    (passed, warnings)
+}
+
+/// Merge `overrides` into `settings`: objects are merged key by key, and other
+/// values of `overrides` replace those of `settings`
+fn merge_settings(settings: &mut Value, overrides: &Value) {
+    match (settings, overrides) {
+        (Value::Object(settings), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge_settings(settings.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (settings, overrides) => *settings = overrides.clone(),
+    }
+}
 
 /// Compilation configurations. This exists across compilations of individual
 /// files
@@ -31,10 +100,13 @@ pub struct Solc {
     output_directory: PathBuf,
     basepath: Option<String>,
     allow_paths: Option<Vec<String>>,
-    include_path: Option<String>,
+    include_paths: Vec<String>,
     remappings: Option<Vec<String>>,
     optimize: bool,
     evm_version: Option<String>,
+    /// Further standard JSON settings (see `--solc_settings`)
+    settings: Map<String, Value>,
+    retries: usize,
 }
 
 impl Solc {
@@ -44,10 +116,12 @@ impl Solc {
             output_directory,
             basepath: None,
             allow_paths: None,
-            include_path: None,
+            include_paths: vec![],
             remappings: None,
             optimize: false,
             evm_version: None,
+            settings: Map::new(),
+            retries: 0,
         }
     }
 
@@ -55,6 +129,23 @@ impl Solc {
         &self.output_directory
     }
 
+    pub fn remappings(&self) -> &[String] {
+        self.remappings.as_deref().unwrap_or_default()
+    }
+
+    /// Detect the version of this solc binary by running `solc --version`,
+    /// returning `None` if the version could not be determined.
+    pub fn version(&self) -> Option<String> {
+        let (code, stdout, _) = invoke_command(&self.solc, vec!["--version"]).ok()?;
+        if code != Some(0) {
+            return None;
+        }
+        String::from_utf8_lossy(&stdout)
+            .lines()
+            .find_map(|l| l.strip_prefix("Version: "))
+            .map(|v| v.trim().to_string())
+    }
+
     pub fn with_basepath(&mut self, basepath: String) -> &Self {
         self.basepath = Some(basepath);
         self
@@ -65,8 +156,9 @@ impl Solc {
         self
     }
 
+    /// Add a directory to solc's include paths
     pub fn with_include_path(&mut self, include_path: String) -> &Self {
-        self.include_path = Some(include_path);
+        self.include_paths.push(include_path);
         self
     }
 
@@ -84,6 +176,34 @@ impl Solc {
         self.evm_version = Some(evm_version);
         self
     }
+
+    /// Pass standard JSON `settings` to solc, e.g. `{"viaIR": true}`. Settings
+    /// that are not passed through are ignored (see `check_solc_settings()`).
+    /// The settings also apply to compilations on the command line, as far
+    /// as solc has flags for them: `viaIR`, `evmVersion`, and `optimizer`'s
+    /// `enabled` and `runs`
+    pub fn with_settings(&mut self, settings: &Map<String, Value>) -> &Self {
+        self.settings = check_solc_settings(settings).0;
+        self
+    }
+
+    /// The `evmVersion` to compile for: `--solc_evm_version` if given, or the
+    /// one in the settings
+    fn evm_version(&self) -> Option<String> {
+        self.evm_version.clone().or_else(|| {
+            self.settings
+                .get("evmVersion")
+                .and_then(Value::as_str)
+                .map(String::from)
+        })
+    }
+
+    /// Retry solc invocations that fail transiently up to `retries` times
+    /// (see `invoke()`)
+    pub fn with_retries(&mut self, retries: usize) -> &Self {
+        self.retries = retries;
+        self
+    }
 }
 
 impl Solc {
@@ -105,11 +225,17 @@ impl Solc {
         let (ast_dir, ast_path, json_path) = match mk_dir_result {
             Ok(x) => x,
             Err(e) => {
-                log::error!(
-                    "Error: Failed to run make_ast_dir({}, {})\nEncountered error {}",
-                    solidity_file.display(),
-                    outdir.as_path().display(),
-                    e
+                report(
+                    Diagnostic::new(
+                        DiagnosticCode::InternalError,
+                        format!(
+                            "Failed to run make_ast_dir({}, {}): {}",
+                            solidity_file.display(),
+                            outdir.as_path().display(),
+                            e
+                        ),
+                    )
+                    .with_file(solidity_file.display()),
                 );
                 return Err(e);
             }
@@ -118,23 +244,35 @@ impl Solc {
         match self.invoke_compiler(solidity_file, &ast_dir, false) {
             Ok((code, stdout, stderr)) => {
                 if code != 0 {
-                    log::error!(
-                        "Solidity compiler returned exit code {} on file `{}`",
-                        code,
-                        solidity_file.display()
+                    report(
+                        Diagnostic::new(
+                            DiagnosticCode::SolcError,
+                            format!(
+                                "Solidity compiler returned exit code {} on file `{}`",
+                                code,
+                                solidity_file.display()
+                            ),
+                        )
+                        .with_file(solidity_file.display())
+                        .with_note(format!("stdout: {}", String::from_utf8_lossy(&stdout)))
+                        .with_note(format!("stderr: {}", String::from_utf8_lossy(&stderr))),
                     );
-                    log::error!("stdout: {}", String::from_utf8(stdout).unwrap());
-                    log::error!("stderr: {}", String::from_utf8(stderr).unwrap());
                 }
             }
             Err(e) => {
-                log::error!(
-                "Failed to compile source with invoke_compiler({}, {}, {}) \nEncountered error {}",
-                solidity_file.display(),
-                ast_dir.display(),
-                true,
-                e
-            );
+                report(
+                    Diagnostic::new(
+                        DiagnosticCode::SolcError,
+                        format!(
+                            "Failed to compile source with invoke_compiler({}, {}, {}): {}",
+                            solidity_file.display(),
+                            ast_dir.display(),
+                            true,
+                            e
+                        ),
+                    )
+                    .with_file(solidity_file.display()),
+                );
                 return Err(e);
             }
         }
@@ -143,13 +281,56 @@ impl Solc {
         log::debug!("Wrote AST as JSON to {}", &json_path.display());
 
         let json_f = File::open(&json_path)?;
-        let ast_json: Value = serde_json::from_reader(json_f)?;
+        let ast_json: Value = parse_json_unbounded(json_f)?;
         log::debug!("Deserialized JSON AST from {}", &json_path.display());
         Ok(SolAST {
             element: Some(ast_json),
         })
     }
 
+    /// Compile a solidity file and return the ASTs of all of the source units
+    /// of the compilation: the file itself along with everything it imports.
+    /// The ASTs are written to a temporary directory, so that source units
+    /// of earlier compilations are never picked up.
+    pub fn compile_source_units(
+        &self,
+        solidity_file: &Path,
+    ) -> Result<Vec<SolAST>, Box<dyn error::Error>> {
+        let ast_dir = tempfile::tempdir()?;
+        let (code, _, stderr) = self.invoke_compiler(solidity_file, ast_dir.path(), false)?;
+        if code != 0 {
+            return Err(format!(
+                "Failed to compile {}: {}",
+                solidity_file.display(),
+                String::from_utf8_lossy(&stderr)
+            )
+            .into());
+        }
+        let mut units = vec![];
+        for entry in std::fs::read_dir(ast_dir.path())? {
+            let path = entry?.path();
+            if path.to_string_lossy().ends_with("_json.ast") {
+                let ast_json: Value = parse_json_unbounded(File::open(&path)?)?;
+                units.push(SolAST {
+                    element: Some(ast_json),
+                });
+            }
+        }
+        Ok(units)
+    }
+
+    /// Find the file of a source unit given its name (the `absolutePath` of its
+    /// AST), which solc resolves against the base path, the include paths, and
+    /// the current working directory.
+    pub fn resolve_source_unit_name(&self, name: &str) -> Option<PathBuf> {
+        self.basepath
+            .iter()
+            .chain(&self.include_paths)
+            .map(|dir| PathBuf::from(dir).join(name))
+            .chain(std::iter::once(PathBuf::from(name)))
+            .find(|p| p.is_file())
+    }
+
     /// Invoke the full solidity compiler and return the exit code, stdout, and stderr
     pub fn compile(
         &self,
@@ -160,6 +341,170 @@ impl Solc {
         self.invoke_compiler(solidity_file, outdir, false)
     }
 
+    /// Invoke the solidity compiler, stopping after parsing, and return the
+    /// exit code, stdout, and stderr. This only catches syntax errors, but is
+    /// much faster than a full compilation.
+    pub fn check_syntax(
+        &self,
+        solidity_file: &Path,
+        outdir: &Path,
+    ) -> Result<CompilerRet, Box<dyn error::Error>> {
+        log::debug!("Invoking syntax check on {}", solidity_file.display());
+        self.invoke_compiler(solidity_file, outdir, true)
+    }
+
+    /// Compile `sources`, which map source unit names (see
+    /// `source_unit_name()`) to their contents, with `solc --standard-json`.
+    /// Imports of other files are read from disk by solc, resolved against the
+    /// base path and include path, and remapped by the remappings given in the
+    /// input's `settings`. No file is written: a mutated file is compiled by
+    /// passing its contents under the name of the original file, so its
+    /// relative imports resolve as they do for the original.
+    ///
+    /// Returns the messages of the errors solc reported, which are empty if
+    /// the compilation succeeded.
+    pub fn compile_standard_json(
+        &self,
+        sources: &HashMap<String, String>,
+    ) -> Result<Vec<String>, Box<dyn error::Error>> {
+        log::debug!("Invoking full standard JSON compilation");
+        let output = self.invoke_standard_json(sources, false, false)?;
+        Ok(log_standard_json_errors(&output))
+    }
+
+    /// Like `compile_standard_json()`, stopping after parsing
+    pub fn check_syntax_standard_json(
+        &self,
+        sources: &HashMap<String, String>,
+    ) -> Result<Vec<String>, Box<dyn error::Error>> {
+        log::debug!("Invoking standard JSON syntax check");
+        let output = self.invoke_standard_json(sources, true, false)?;
+        Ok(log_standard_json_errors(&output))
+    }
+
+    /// Like `compile_standard_json()`, also returning the ABI of each contract
+    /// defined in `sources` (see `standard_json_abis()`). The ABI is produced
+    /// by solc's analysis, so this is as fast as `compile_standard_json()`.
+    pub fn compile_abi_standard_json(
+        &self,
+        sources: &HashMap<String, String>,
+    ) -> Result<(Vec<String>, ContractAbis), Box<dyn error::Error>> {
+        log::debug!("Invoking full standard JSON compilation with ABI output");
+        let output = self.invoke_standard_json(sources, false, true)?;
+        Ok((
+            log_standard_json_errors(&output),
+            standard_json_abis(&output),
+        ))
+    }
+
+    /// The source unit name solc gives `solidity_file` when it is compiled
+    /// from the command line: its path relative to the base path if it is
+    /// inside it, and its absolute path otherwise
+    pub fn source_unit_name(&self, solidity_file: &Path) -> Result<String, Box<dyn error::Error>> {
+        let path = solidity_file.canonicalize()?;
+        let relative = match &self.basepath {
+            Some(basepath) => PathBuf::from(basepath)
+                .canonicalize()
+                .ok()
+                .and_then(|basepath| path.strip_prefix(basepath).ok().map(Path::to_path_buf)),
+            None => None,
+        };
+        Ok(match relative {
+            Some(relative) => relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/"),
+            None => path.to_string_lossy().into_owned(),
+        })
+    }
+
+    /// The standard JSON input that compiles `sources`. Only the ASTs of the
+    /// given sources (and, with `abi`, the ABIs of their contracts) are
+    /// requested: this runs solc's analysis without generating code, like
+    /// `invoke_compiler()` does
+    fn standard_json_input(
+        &self,
+        sources: &HashMap<String, String>,
+        stop_after_parse: bool,
+        abi: bool,
+    ) -> Value {
+        let mut names: Vec<&String> = sources.keys().collect();
+        names.sort();
+        let selection = if abi {
+            json!({"": ["ast"], "*": ["abi"]})
+        } else {
+            json!({"": ["ast"]})
+        };
+        let mut settings = json!({
+            "outputSelection": names
+                .iter()
+                .map(|name| (name.to_string(), selection.clone()))
+                .collect::<serde_json::Map<String, Value>>(),
+            "remappings": self.remappings.clone().unwrap_or_default(),
+            "optimizer": {"enabled": self.optimize},
+        });
+        merge_settings(&mut settings, &Value::Object(self.settings.clone()));
+        if let Some(evm_version) = &self.evm_version {
+            settings["evmVersion"] = json!(evm_version);
+        }
+        if stop_after_parse {
+            settings["stopAfter"] = json!("parsing");
+        }
+        json!({
+            "language": "Solidity",
+            "sources": names
+                .iter()
+                .map(|name| (name.to_string(), json!({"content": sources[*name]})))
+                .collect::<serde_json::Map<String, Value>>(),
+            "settings": settings,
+        })
+    }
+
+    /// Run `solc --standard-json` on `sources`, returning its output
+    fn invoke_standard_json(
+        &self,
+        sources: &HashMap<String, String>,
+        stop_after_parse: bool,
+        abi: bool,
+    ) -> Result<Value, Box<dyn error::Error>> {
+        let input = self
+            .standard_json_input(sources, stop_after_parse, abi)
+            .to_string();
+        let mut flags: Vec<String> = vec![STANDARD_JSON.into()];
+        if let Some(basepath) = &self.basepath {
+            flags.push(BASEPATH.into());
+            flags.push(basepath.clone());
+        }
+        for include_path in &self.include_paths {
+            flags.push(INCLUDEPATH.into());
+            flags.push(include_path.clone());
+        }
+        // Sources named by their absolute paths import files next to them
+        let mut allow_paths = self.allow_paths.clone().unwrap_or_default();
+        for name in sources.keys().filter(|name| name.starts_with('/')) {
+            if let Some(parent) = Path::new(name).parent() {
+                allow_paths.push(parent.to_string_lossy().into_owned());
+            }
+        }
+        if !allow_paths.is_empty() {
+            flags.push(ALLOWPATHS.into());
+            flags.push(join(allow_paths, ","));
+        }
+        let flags: Vec<&str> = flags.iter().map(|s| s as &str).collect();
+        log::debug!("Invoking solc: `{} {}`", self.solc, flags.join(" "));
+
+        let (code, stdout, stderr) = self.invoke(flags, Some(input.as_bytes()))?;
+        if code != 0 {
+            return Err(Box::new(GambitError::Solc(format!(
+                "solc --standard-json failed with exit code {:?}: {}",
+                code,
+                String::from_utf8_lossy(&stderr)
+            ))));
+        }
+        Ok(parse_json_unbounded(stdout.as_slice())?)
+    }
+
     /// Perform the actual compilation by invoking a process. This is a wrapper
     /// around `util::invoke_command`.
     ///
@@ -191,33 +536,73 @@ impl Solc {
             pretty_flags,
         );
 
-        let (code, stdout, stderr) = invoke_command(&self.solc, flags)?;
+        let (code, stdout, stderr) = self.invoke(flags, None)?;
+        // We report non-zero exit codes as info/debug because they are
+        // expected during validation. We are returning stdout and stderr in
+        // case they are needed by the caller to explain an unexpected
+        // compilation failure
+        if code != 0 {
+            log::info!(
+                "Running solc on {} finished with non-zero code {}",
+                solidity_file.display(),
+                code
+            );
+            log::debug!("Ran `{} {}`", &self.solc, pretty_flags);
+            log::debug!("  stderr: {}", String::from_utf8_lossy(&stderr));
+            log::debug!("  stdout: {}", String::from_utf8_lossy(&stdout));
+        }
+        Ok((code, stdout, stderr))
+    }
 
-        match code {
-            None => {
-                // We report this as an error because something bad happened!
-                log::error!("Solc terminated with a signal");
-                log::error!("  stderr: {}", String::from_utf8_lossy(&stderr));
-                log::error!("  stdout: {}", String::from_utf8_lossy(&stdout));
-                Err("Solc terminated with a signal".into())
-            }
-            Some(code) => {
-                // We report this as a info/debug because non-zero exit codes
-                // are expected during validation. We are returning stdout and
-                // stderr in case they are needed by the caller to explain an
-                // unexpected compilation failure
-                if code != 0 {
-                    log::info!(
-                        "Running solc on {} finished with non-zero code {}",
-                        solidity_file.display(),
-                        code
-                    );
-                    log::debug!("Ran `{} {}`", &self.solc, pretty_flags);
-                    log::debug!("  stderr: {}", String::from_utf8_lossy(&stderr));
-                    log::debug!("  stdout: {}", String::from_utf8_lossy(&stdout));
+    /// Invoke solc with `flags`, passing `input` on its standard input if
+    /// given, and return the exit code, stdout, and stderr.
+    ///
+    /// Failures that have nothing to do with what solc was asked to compile
+    /// are retried up to `retries` times with exponential backoff: solc being
+    /// terminated with a signal (e.g., when it is killed for running out of
+    /// memory), exiting with a non-zero code without any error output, or
+    /// failing to start although the executable exists. If solc keeps failing,
+    /// this returns a `GambitError::SolcUnavailable`.
+    fn invoke(
+        &self,
+        flags: Vec<&str>,
+        input: Option<&[u8]>,
+    ) -> Result<CompilerRet, Box<dyn error::Error>> {
+        let mut attempt = 0;
+        loop {
+            let result = match input {
+                Some(input) => invoke_command_with_input(&self.solc, flags.clone(), input),
+                None => invoke_command(&self.solc, flags.clone()),
+            };
+            let failure = match &result {
+                Err(e) if resolve_executable(&self.solc).is_none() => {
+                    return Err(Box::new(GambitError::Solc(e.to_string())))
                 }
-                Ok((code, stdout, stderr))
+                Err(e) => e.to_string(),
+                Ok((None, _, _)) => "Solc terminated with a signal".into(),
+                Ok((Some(code), _, stderr))
+                    if *code != 0 && stderr.iter().all(u8::is_ascii_whitespace) =>
+                {
+                    format!("Solc exited with code {} without any error output", code)
+                }
+                Ok((Some(code), stdout, stderr)) => {
+                    return Ok((*code, stdout.clone(), stderr.clone()))
+                }
+            };
+            if attempt >= self.retries {
+                report(
+                    Diagnostic::new(DiagnosticCode::SolcError, failure.clone()).with_note(format!(
+                        "gave up after {} attempt{}",
+                        attempt + 1,
+                        if attempt == 0 { "" } else { "s" }
+                    )),
+                );
+                return Err(Box::new(GambitError::SolcUnavailable(failure)));
             }
+            let backoff = Duration::from_millis(SOLC_RETRY_BACKOFF_MS << attempt);
+            log::warn!("{}; retrying in {} ms", failure, backoff.as_millis());
+            thread::sleep(backoff);
+            attempt += 1;
         }
     }
 
@@ -304,7 +689,7 @@ impl Solc {
             flags.push(comma_separated);
         }
 
-        if let Some(include_path) = &self.include_path {
+        for include_path in &self.include_paths {
             flags.push(INCLUDEPATH.into());
             flags.push(include_path.clone());
         }
@@ -315,15 +700,90 @@ impl Solc {
             }
         }
 
-        if self.optimize {
+        let optimizer = self.settings.get("optimizer");
+        if self.optimize || optimizer.and_then(|o| o.get("enabled")) == Some(&Value::Bool(true)) {
             flags.push(OPTIMIZE.into());
         }
+        if let Some(runs) = optimizer
+            .and_then(|o| o.get("runs"))
+            .and_then(Value::as_u64)
+        {
+            flags.push(OPTIMIZE_RUNS.into());
+            flags.push(runs.to_string());
+        }
 
-        if let Some(evm_version) = &self.evm_version {
+        if let Some(evm_version) = self.evm_version() {
             flags.push(EVM_VERSION.into());
-            flags.push(evm_version.clone());
+            flags.push(evm_version);
+        }
+
+        if self.settings.get("viaIR") == Some(&Value::Bool(true)) {
+            flags.push(VIA_IR.into());
         }
 
         flags
     }
 }
+
+/// The messages of the errors in a standard JSON output, which are logged
+fn log_standard_json_errors(output: &Value) -> Vec<String> {
+    let errors = standard_json_errors(output);
+    if !errors.is_empty() {
+        log::debug!("  errors: {}", errors.join("\n"));
+    }
+    errors
+}
+
+/// The ABI of each contract in a standard JSON output
+pub fn standard_json_abis(output: &Value) -> ContractAbis {
+    output["contracts"]
+        .as_object()
+        .into_iter()
+        .flat_map(|sources| sources.values())
+        .filter_map(Value::as_object)
+        .flat_map(|contracts| contracts.iter())
+        .map(|(name, contract)| (name.clone(), contract["abi"].clone()))
+        .collect()
+}
+
+/// Check if two sets of contract ABIs differ.
+/// ABIs are compared structurally: the order of their entries, and of the
+/// keys of each entry, does not matter.
+pub fn abis_differ(original: &ContractAbis, mutant: &ContractAbis) -> bool {
+    let normalize = |abis: &ContractAbis| -> BTreeMap<String, Vec<String>> {
+        abis.iter()
+            .map(|(name, abi)| {
+                let mut entries: Vec<String> = abi
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(Value::to_string)
+                    .collect();
+                entries.sort();
+                (name.clone(), entries)
+            })
+            .collect()
+    };
+    normalize(original) != normalize(mutant)
+}
+
+/// The messages of the errors in a standard JSON output. Warnings and infos
+/// are left out.
+pub fn standard_json_errors(output: &Value) -> Vec<String> {
+    output["errors"]
+        .as_array()
+        .map(|errors| {
+            errors
+                .iter()
+                .filter(|e| e["severity"] == "error")
+                .map(|e| {
+                    e["formattedMessage"]
+                        .as_str()
+                        .or(e["message"].as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,359 @@
+//! User-facing errors and warnings. Every diagnostic is printed on stderr by
+//! `report()`, either as human readable text or, with `--message-format json`,
+//! as a single-line JSON object that editors and CI can parse.
+
+use crate::GambitError;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    error,
+    fmt::Display,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// How diagnostics are printed (see `--message-format`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageFormat {
+    /// Human readable text
+    #[default]
+    Human,
+    /// One JSON object per line with `level`, `code`, `message`, `file`, and
+    /// (optionally) `span` and `notes` fields
+    Json,
+}
+
+/// Whether diagnostics are printed as JSON
+static JSON_MESSAGES: AtomicBool = AtomicBool::new(false);
+
+/// Print all following diagnostics in `format`
+pub fn set_message_format(format: MessageFormat) {
+    JSON_MESSAGES.store(format == MessageFormat::Json, Ordering::Relaxed);
+}
+
+/// The format that diagnostics are printed in
+pub fn message_format() -> MessageFormat {
+    if JSON_MESSAGES.load(Ordering::Relaxed) {
+        MessageFormat::Json
+    } else {
+        MessageFormat::Human
+    }
+}
+
+/// The kinds of diagnostics, each with a stable code (see `code()`). Error
+/// codes start with `E` and warning codes with `W`. Codes are never reused:
+/// new kinds of diagnostics get new codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticCode {
+    /// An unexpected internal error
+    InternalError,
+    /// An invalid configuration (e.g., a malformed configuration file or an
+    /// illegal parameter value)
+    ConfigurationError,
+    /// A file that cannot be found or resolved
+    FileResolutionError,
+    /// A failure to invoke solc
+    SolcError,
+    /// No mutants were generated with `--fail_on_zero_mutants`
+    NoMutants,
+    /// Conflicting remappings of the same prefix. No longer reported:
+    /// conflicting remappings are configuration errors
+    ConflictingRemappings,
+    /// A configuration entry failed to be mutated
+    MutationFailed,
+    /// A `gambit_results.json` file is missing, ill-formed, or violates the
+    /// results format
+    InvalidResults,
+    /// A post-export hook failed with `--strict_hooks`
+    HookFailed,
+    /// The run was interrupted (e.g., with Ctrl-C)
+    Interrupted,
+    /// The validation budget ran out (with `--max_validation_seconds`)
+    BudgetExhausted,
+    /// An output directory exists and is not overwritten
+    OutputDirectoryExists,
+    /// `copy_into_mutants` is ignored in favor of `export_full_project` or
+    /// patch exports
+    SidecarsIgnored,
+    /// Fewer valid mutants exist than `--num_mutants` requested
+    FewerMutantsThanRequested,
+    /// Sampling mutants stopped before `--num_mutants` valid mutants were
+    /// found
+    SamplingStoppedEarly,
+    /// An output directory is targeted by different solc binaries
+    MixedSolc,
+    /// A remapping overrides an earlier remapping of the same prefix
+    RemappingOverride,
+    /// AST subtrees nested deeper than `--max_ast_depth` are not mutated
+    DeepSubtreesPruned,
+    /// A `--contract` or `--functions` name nearly matches an existing name
+    NearMissFilterName,
+    /// The location of a mutant cannot be computed
+    UnlocatableMutant,
+    /// The diff of a mutant cannot be computed
+    DiffFailed,
+    /// A called library outside of the sourceroot is not mutated
+    LibraryOutsideSourceroot,
+    /// A base contract outside of the sourceroot is not mutated (see
+    /// `--contract_closure`)
+    BaseContractOutsideSourceroot,
+    /// Post-export hook invocations failed or timed out
+    PostExportHookFailed,
+    /// Experimental mutation operators are enabled with
+    /// `--allow_experimental`
+    ExperimentalOperators,
+    /// A mutated file starts with a UTF-8 byte order mark
+    ByteOrderMark,
+    /// Mutants generated by several entries for the same output directory
+    /// were dropped
+    CrossEntryDuplicates,
+    /// A contract-qualified `--functions` entry names a contract that the
+    /// mutated file does not define
+    UnknownQualifiedContract,
+    /// A `--solc_settings` setting is not passed to solc
+    IgnoredSolcSetting,
+    /// A `--contract` or `--functions` name matches nothing in any mutated
+    /// file
+    FilterNameNotFound,
+    /// A configuration file is discovered and used (without `--filename` or
+    /// `--json`)
+    DiscoveredConfiguration,
+}
+
+impl DiagnosticCode {
+    /// The stable code of this kind of diagnostic
+    pub fn code(&self) -> &'static str {
+        match self {
+            DiagnosticCode::InternalError => "E0001",
+            DiagnosticCode::ConfigurationError => "E0002",
+            DiagnosticCode::FileResolutionError => "E0003",
+            DiagnosticCode::SolcError => "E0004",
+            DiagnosticCode::NoMutants => "E0005",
+            DiagnosticCode::ConflictingRemappings => "E0006",
+            DiagnosticCode::MutationFailed => "E0007",
+            DiagnosticCode::InvalidResults => "E0008",
+            DiagnosticCode::HookFailed => "E0009",
+            DiagnosticCode::Interrupted => "E0010",
+            DiagnosticCode::BudgetExhausted => "E0011",
+            DiagnosticCode::OutputDirectoryExists => "W0001",
+            DiagnosticCode::SidecarsIgnored => "W0002",
+            DiagnosticCode::FewerMutantsThanRequested => "W0003",
+            DiagnosticCode::SamplingStoppedEarly => "W0004",
+            DiagnosticCode::MixedSolc => "W0005",
+            DiagnosticCode::RemappingOverride => "W0006",
+            DiagnosticCode::DeepSubtreesPruned => "W0007",
+            DiagnosticCode::NearMissFilterName => "W0008",
+            DiagnosticCode::UnlocatableMutant => "W0009",
+            DiagnosticCode::DiffFailed => "W0010",
+            DiagnosticCode::LibraryOutsideSourceroot => "W0011",
+            DiagnosticCode::BaseContractOutsideSourceroot => "W0012",
+            DiagnosticCode::PostExportHookFailed => "W0013",
+            DiagnosticCode::ExperimentalOperators => "W0014",
+            DiagnosticCode::ByteOrderMark => "W0015",
+            DiagnosticCode::CrossEntryDuplicates => "W0016",
+            DiagnosticCode::UnknownQualifiedContract => "W0017",
+            DiagnosticCode::IgnoredSolcSetting => "W0018",
+            DiagnosticCode::FilterNameNotFound => "W0019",
+            DiagnosticCode::DiscoveredConfiguration => "W0020",
+        }
+    }
+
+    /// The level of this kind of diagnostic: `error` or `warning`
+    pub fn level(&self) -> &'static str {
+        if self.code().starts_with('E') {
+            "error"
+        } else {
+            "warning"
+        }
+    }
+}
+
+/// A line and column (both 1-indexed) that a diagnostic refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// A user-facing error or warning
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    pub message: String,
+
+    /// The file that the diagnostic is about, if any
+    pub file: Option<String>,
+
+    /// Where in `file` the diagnostic applies
+    pub span: Option<Span>,
+
+    /// Further lines of explanation (e.g., suggestions or individual
+    /// problems)
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(code: DiagnosticCode, message: impl Display) -> Diagnostic {
+        Diagnostic {
+            code,
+            message: message.to_string(),
+            file: None,
+            span: None,
+            notes: vec![],
+        }
+    }
+
+    pub fn with_file(mut self, file: impl Display) -> Diagnostic {
+        self.file = Some(file.to_string());
+        self
+    }
+
+    pub fn with_span(mut self, line: usize, col: usize) -> Diagnostic {
+        self.span = Some(Span { line, col });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Display) -> Diagnostic {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    /// The human readable form of this diagnostic: its message, marked `[!!]`
+    /// for errors and `[!]` for warnings, followed by its indented notes
+    pub fn human(&self) -> String {
+        let marker = if self.code.level() == "error" {
+            "[!!]"
+        } else {
+            "[!]"
+        };
+        let mut lines = vec![format!("{} {}", marker, self.message)];
+        lines.extend(self.notes.iter().map(|note| format!("  {}", note)));
+        lines.join("\n")
+    }
+
+    /// The JSON form of this diagnostic, on a single line. `span` and `notes`
+    /// are only present when set.
+    pub fn json(&self) -> String {
+        let mut json = json!({
+            "level": self.code.level(),
+            "code": self.code.code(),
+            "message": self.message,
+            "file": self.file,
+        });
+        if let Some(span) = self.span {
+            json["span"] = json!(span);
+        }
+        if !self.notes.is_empty() {
+            json["notes"] = json!(self.notes);
+        }
+        json.to_string()
+    }
+}
+
+/// The diagnostic code of an error that ends a Gambit command: errors that
+/// are not `GambitError`s are unexpected
+pub fn diagnostic_code_for(e: &(dyn error::Error + 'static)) -> DiagnosticCode {
+    match e.downcast_ref::<GambitError>() {
+        Some(GambitError::Configuration(_)) => DiagnosticCode::ConfigurationError,
+        Some(GambitError::FileResolution(_)) => DiagnosticCode::FileResolutionError,
+        Some(GambitError::Solc(_) | GambitError::SolcUnavailable(_)) => DiagnosticCode::SolcError,
+        Some(GambitError::NoMutants) => DiagnosticCode::NoMutants,
+        Some(GambitError::HookFailed(_)) => DiagnosticCode::HookFailed,
+        Some(GambitError::InvalidResults(_)) => DiagnosticCode::InvalidResults,
+        Some(GambitError::Interrupted(_)) => DiagnosticCode::Interrupted,
+        Some(GambitError::BudgetExhausted(_)) => DiagnosticCode::BudgetExhausted,
+        Some(GambitError::Internal(_)) | None => DiagnosticCode::InternalError,
+    }
+}
+
+/// Print `diagnostic` on stderr in the current message format
+pub fn report(diagnostic: Diagnostic) {
+    match message_format() {
+        MessageFormat::Human => eprintln!("{}", diagnostic.human()),
+        MessageFormat::Json => eprintln!("{}", diagnostic.json()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_diagnostic_formats() {
+        let warning = Diagnostic::new(
+            DiagnosticCode::UnlocatableMutant,
+            "Couldn't locate mutant 3",
+        )
+        .with_file("contracts/C.sol")
+        .with_span(4, 7);
+        assert_eq!(warning.human(), "[!] Couldn't locate mutant 3");
+        let json: Value = serde_json::from_str(&warning.json()).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "level": "warning",
+                "code": "W0009",
+                "message": "Couldn't locate mutant 3",
+                "file": "contracts/C.sol",
+                "span": {"line": 4, "col": 7},
+            })
+        );
+
+        let error = Diagnostic::new(DiagnosticCode::ConfigurationError, "Illegal Configuration")
+            .with_note("(1) fix it")
+            .with_note("(2) or not");
+        assert_eq!(
+            error.human(),
+            "[!!] Illegal Configuration\n  (1) fix it\n  (2) or not"
+        );
+        assert!(!error.json().contains('\n'));
+        let json: Value = serde_json::from_str(&error.json()).unwrap();
+        assert_eq!(json["level"], "error");
+        assert_eq!(json["code"], "E0002");
+        assert_eq!(json["file"], Value::Null);
+        assert!(json.get("span").is_none());
+        assert_eq!(json["notes"], json!(["(1) fix it", "(2) or not"]));
+    }
+
+    #[test]
+    fn test_stable_codes() {
+        let codes = [
+            DiagnosticCode::InternalError,
+            DiagnosticCode::ConfigurationError,
+            DiagnosticCode::FileResolutionError,
+            DiagnosticCode::SolcError,
+            DiagnosticCode::NoMutants,
+            DiagnosticCode::ConflictingRemappings,
+            DiagnosticCode::MutationFailed,
+            DiagnosticCode::InvalidResults,
+            DiagnosticCode::OutputDirectoryExists,
+            DiagnosticCode::SidecarsIgnored,
+            DiagnosticCode::FewerMutantsThanRequested,
+            DiagnosticCode::SamplingStoppedEarly,
+            DiagnosticCode::MixedSolc,
+            DiagnosticCode::RemappingOverride,
+            DiagnosticCode::DeepSubtreesPruned,
+            DiagnosticCode::NearMissFilterName,
+            DiagnosticCode::UnlocatableMutant,
+            DiagnosticCode::DiffFailed,
+            DiagnosticCode::LibraryOutsideSourceroot,
+            DiagnosticCode::BaseContractOutsideSourceroot,
+        ];
+        let unique: std::collections::HashSet<&str> = codes.iter().map(|c| c.code()).collect();
+        assert_eq!(unique.len(), codes.len());
+        assert_eq!(DiagnosticCode::InternalError.code(), "E0001");
+        assert_eq!(DiagnosticCode::DiffFailed.code(), "W0010");
+        assert_eq!(DiagnosticCode::SolcError.level(), "error");
+        assert_eq!(DiagnosticCode::MixedSolc.level(), "warning");
+        assert_eq!(
+            diagnostic_code_for(&GambitError::Configuration("".into())).code(),
+            "E0002"
+        );
+        assert_eq!(
+            diagnostic_code_for(&std::io::Error::other("")).code(),
+            "E0001"
+        );
+    }
+}
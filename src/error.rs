@@ -0,0 +1,136 @@
+use std::{error, fmt};
+
+/// Exit code for unexpected internal errors
+pub const EXIT_INTERNAL_ERROR: i32 = 1;
+
+/// Exit code for invalid configurations (e.g., malformed configuration files
+/// or illegal parameter values)
+pub const EXIT_CONFIGURATION_ERROR: i32 = 2;
+
+/// Exit code for files that cannot be found or resolved
+pub const EXIT_FILE_RESOLUTION_ERROR: i32 = 3;
+
+/// Exit code for failures to invoke solc
+pub const EXIT_SOLC_ERROR: i32 = 4;
+
+/// Exit code for runs that generated no mutants with `--fail_on_zero_mutants`
+pub const EXIT_NO_MUTANTS: i32 = 5;
+
+/// Exit code for failed post-export hooks with `--strict_hooks`
+pub const EXIT_HOOK_FAILED: i32 = 6;
+
+/// Exit code for runs whose validation budget (`--max_validation_seconds`)
+/// ran out, after exporting the remaining mutants unvalidated
+pub const EXIT_BUDGET_EXHAUSTED: i32 = 7;
+
+/// Exit code for `gambit_results.json` files that are ill-formed or violate
+/// the results format
+pub const EXIT_INVALID_RESULTS: i32 = 8;
+
+/// Exit code for runs that were interrupted (e.g., with Ctrl-C), after writing
+/// the mutants validated so far
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// The exit codes of `gambit`, as documented in `--help`
+pub const EXIT_CODES_HELP: &str = "Exit codes:
+  0  success
+  1  unexpected internal error
+  2  configuration error
+  3  file resolution error
+  4  solc invocation error
+  5  no mutants were generated (with --fail_on_zero_mutants)
+  6  a post-export hook failed (with --strict_hooks)
+  7  the validation budget ran out (with --max_validation_seconds)
+  8  a gambit_results.json is ill-formed or has violations
+  130  interrupted, after writing the mutants validated so far";
+
+/// Errors that end a Gambit run. Each kind of error is reported with its own
+/// exit code (see `GambitError::exit_code()`).
+#[derive(Debug)]
+pub enum GambitError {
+    /// An invalid configuration
+    Configuration(String),
+    /// A file that could not be found or resolved
+    FileResolution(String),
+    /// A failure to invoke solc
+    Solc(String),
+    /// Solc kept failing for reasons unrelated to its input (e.g., it was
+    /// killed by a signal), even after retrying
+    SolcUnavailable(String),
+    /// No mutants were generated and `--fail_on_zero_mutants` was set
+    NoMutants,
+    /// A post-export hook failed and `--strict_hooks` was set
+    HookFailed(String),
+    /// The validation budget ran out and mutants were exported unvalidated
+    BudgetExhausted(String),
+    /// A `gambit_results.json` file is ill-formed or violates the results
+    /// format
+    InvalidResults(String),
+    /// The run was interrupted (e.g., with Ctrl-C)
+    Interrupted(String),
+    /// An unexpected internal error
+    Internal(String),
+}
+
+impl GambitError {
+    /// The exit code that `gambit` reports this error with
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            GambitError::Configuration(_) => EXIT_CONFIGURATION_ERROR,
+            GambitError::FileResolution(_) => EXIT_FILE_RESOLUTION_ERROR,
+            GambitError::Solc(_) | GambitError::SolcUnavailable(_) => EXIT_SOLC_ERROR,
+            GambitError::NoMutants => EXIT_NO_MUTANTS,
+            GambitError::HookFailed(_) => EXIT_HOOK_FAILED,
+            GambitError::BudgetExhausted(_) => EXIT_BUDGET_EXHAUSTED,
+            GambitError::InvalidResults(_) => EXIT_INVALID_RESULTS,
+            GambitError::Interrupted(_) => EXIT_INTERRUPTED,
+            GambitError::Internal(_) => EXIT_INTERNAL_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for GambitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GambitError::Configuration(msg) => write!(f, "Illegal Configuration: {}", msg),
+            GambitError::FileResolution(msg) => write!(f, "File Resolution Error: {}", msg),
+            GambitError::Solc(msg) | GambitError::SolcUnavailable(msg) => {
+                write!(f, "Solc Error: {}", msg)
+            }
+            GambitError::NoMutants => write!(f, "No mutants were generated"),
+            GambitError::HookFailed(msg) => write!(f, "Post-export Hook Error: {}", msg),
+            GambitError::BudgetExhausted(msg) => write!(f, "Validation Budget Exhausted: {}", msg),
+            GambitError::InvalidResults(msg) => write!(f, "Invalid Results: {}", msg),
+            GambitError::Interrupted(msg) => write!(f, "Interrupted: {}", msg),
+            GambitError::Internal(msg) => write!(f, "Internal Error: {}", msg),
+        }
+    }
+}
+
+impl error::Error for GambitError {}
+
+impl From<Box<dyn error::Error>> for GambitError {
+    /// Recover the `GambitError` that `e` boxes; any other error is unexpected
+    fn from(e: Box<dyn error::Error>) -> Self {
+        match e.downcast::<GambitError>() {
+            Ok(e) => *e,
+            Err(e) => GambitError::Internal(e.to_string()),
+        }
+    }
+}
+
+/// The exit code to report `e` with: errors that are not `GambitError`s are
+/// unexpected
+pub fn exit_code_for(e: &(dyn error::Error + 'static)) -> i32 {
+    e.downcast_ref::<GambitError>()
+        .map_or(EXIT_INTERNAL_ERROR, GambitError::exit_code)
+}
+
+/// Whether `e` is a `GambitError::SolcUnavailable`: solc could not be run at
+/// all, so its failure says nothing about what it was asked to compile
+pub fn is_solc_unavailable(e: &(dyn error::Error + 'static)) -> bool {
+    matches!(
+        e.downcast_ref::<GambitError>(),
+        Some(GambitError::SolcUnavailable(_))
+    )
+}
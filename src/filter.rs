@@ -1,11 +1,51 @@
 use rand::prelude::*;
 use rand_chacha::ChaCha8Rng;
-use std::error;
+use std::{collections::VecDeque, error, path::Path};
 
-use crate::{Mutant, Mutator};
+use crate::{report, Diagnostic, DiagnosticCode, Mutant, Mutator};
 
 /// This module downsamples mutants.
 
+/// Check a requested number of mutants (`--num_mutants`). `0` is rejected:
+/// to keep all mutants, `num_mutants` should be omitted.
+pub fn validate_num_mutants(num_mutants: Option<usize>) -> Result<(), String> {
+    match num_mutants {
+        Some(0) => {
+            Err("num_mutants must be positive; omit it to keep all generated mutants".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parse a random seed (`--seed`), which must be an integer from 0 to
+/// `u64::MAX`. Configuration files converted from other formats sometimes
+/// contain negative seeds, which are rejected with a clear error instead of
+/// being wrapped around.
+pub fn parse_seed(seed: &str) -> Result<u64, String> {
+    let out_of_range = || {
+        format!(
+            "seed must be an integer from 0 to {}, found `{}`",
+            u64::MAX,
+            seed
+        )
+    };
+    match seed.trim().parse::<i128>() {
+        Ok(n) => u64::try_from(n).map_err(|_| out_of_range()),
+        Err(_) => Err(out_of_range()),
+    }
+}
+
+/// Get the number of mutants to downsample `num_candidates` generated mutants
+/// to, or `None` if no downsampling is needed because at least
+/// `num_candidates` mutants were requested.
+pub fn downsample_size(num_mutants: usize, num_candidates: usize) -> Option<usize> {
+    if num_mutants < num_candidates {
+        Some(num_mutants)
+    } else {
+        None
+    }
+}
+
 /// Implement this trait to filter mutants after they have been created.
 pub trait MutantFilter {
     /// Filter the mutants of a mutator, validating them via compilation if
@@ -19,58 +59,510 @@ pub trait MutantFilter {
     fn validate(&self) -> bool;
 }
 
+/// A 64-bit FNV-1a hash, which, unlike `std`'s hashers, is guaranteed not to
+/// change across Rust and Gambit versions
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf29ce484222325)
+    }
+
+    fn feed(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// A key of `mutant` that only depends on what the mutant is: its file
+/// (relative to its sourceroot), the span it replaces, its operator, and its
+/// replacement. This is a 64-bit FNV-1a hash, which, unlike `std`'s hashers,
+/// is guaranteed not to change across Rust and Gambit versions.
+pub fn sampling_key(mutant: &Mutant) -> u64 {
+    let path = mutant
+        .source
+        .relative_filename()
+        .unwrap_or_else(|_| mutant.source.filename().to_path_buf());
+    let mut hash = Fnv1a::new();
+    hash.feed(path.to_string_lossy().as_bytes());
+    hash.feed(&[0]);
+    hash.feed(&(mutant.start as u64).to_le_bytes());
+    hash.feed(&(mutant.end as u64).to_le_bytes());
+    hash.feed(mutant.op.to_string().as_bytes());
+    hash.feed(&[0]);
+    hash.feed(mutant.repl.as_bytes());
+    hash.0
+}
+
+/// The id of the random stream that samples the mutants of the file at `path`
+/// (relative to its sourceroot). Each file is sampled from its own stream of
+/// the seeded generator, so the sample of a file does not depend on which
+/// other files are sampled, or in which order (e.g., in parallel). Paths are
+/// hashed with `/` separators, so ids are the same on every platform.
+pub fn sampling_stream(path: &Path) -> u64 {
+    let components: Vec<String> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let mut hash = Fnv1a::new();
+    hash.feed(components.join("/").as_bytes());
+    hash.0
+}
+
 /// This struct randomly downsamples mutants.
+///
+/// Before sampling, candidates are sorted by their `sampling_key()`, so a seed
+/// selects the same mutants from the same set of candidates regardless of the
+/// order in which they were generated (e.g., after adding an operator).
 pub struct RandomDownSampleFilter {
     pub(crate) seed: Option<u64>,
 
     /// Should filtered mutants be validated with an external compiler run? This
     /// is more expensive but disabling this option may produce invalid mutants.
     validate: bool,
+
+    /// Sample candidates in generation order, as Gambit 0.2.1 and earlier did
+    /// (see `--legacy_sampling`)
+    legacy_order: bool,
+
+    /// The stream of the seeded generator to sample from (see
+    /// `sampling_stream()`). Legacy sampling always uses stream 0
+    stream: u64,
 }
 
 impl RandomDownSampleFilter {
-    pub fn new(seed: Option<u64>, validate: bool) -> Self {
-        Self { seed, validate }
+    pub fn new(seed: Option<u64>, validate: bool, legacy_order: bool) -> Self {
+        Self {
+            seed,
+            validate,
+            legacy_order,
+            stream: 0,
+        }
+    }
+
+    /// Sample from `stream` of the seeded generator
+    pub fn with_stream(mut self, stream: u64) -> Self {
+        self.stream = stream;
+        self
     }
 }
 
-impl MutantFilter for RandomDownSampleFilter {
-    fn filter_mutants(
+impl RandomDownSampleFilter {
+    /// Randomly sample up to `num_mutants` of `mutants`, validating each drawn
+    /// mutant with `validate` if `self.validate()` returns `true`. `validate`
+    /// returns `None` for a mutant that should be kept without validation.
+    ///
+    /// Invalid draws cause another draw. Once the remaining candidates can no
+    /// longer make up `num_mutants`, sampling stops early rather than
+    /// validating the rest of them one by one. This returns the sampled
+    /// mutants, in their original order, along with the number of validation
+    /// attempts that were made.
+    pub fn sample(
         &self,
-        mutator: &Mutator,
+        mutants: &[Mutant],
         num_mutants: usize,
-    ) -> Result<Vec<Mutant>, Box<dyn error::Error>> {
+        mut validate: impl FnMut(&Mutant) -> Option<bool>,
+    ) -> (Vec<Mutant>, usize) {
+        self.sample_in_batches(mutants, num_mutants, 1, |batch| {
+            batch.iter().map(|m| validate(m)).collect()
+        })
+    }
+
+    /// Like `sample()`, but draws up to `batch_size` candidates at a time and
+    /// validates them together with `validate_batch`, which returns the
+    /// result of each candidate of the batch in order (e.g., validating them
+    /// in parallel).
+    ///
+    /// A batch never holds more candidates than are still needed, and the
+    /// candidates of a batch are processed in the order they were drawn, so
+    /// this samples the same mutants as `sample()` for the same seed.
+    pub fn sample_in_batches(
+        &self,
+        mutants: &[Mutant],
+        num_mutants: usize,
+        batch_size: usize,
+        mut validate_batch: impl FnMut(&[&Mutant]) -> Vec<Option<bool>>,
+    ) -> (Vec<Mutant>, usize) {
         // Make a copy that we can mutate
-        let mutants = mutator.mutants();
         let mut mutants: Vec<(usize, Mutant)> = mutants.iter().cloned().enumerate().collect();
+        if !self.legacy_order {
+            // A stable sort: identical candidates keep their relative order
+            mutants.sort_by_cached_key(|(_, m)| sampling_key(m));
+        }
 
         // The sampled mutants. We want to sort by the original index into
         let mut sampled: Vec<(usize, Mutant)> = vec![];
+        let mut attempts = 0;
 
         let mut r = match self.seed {
             None => ChaCha8Rng::from_entropy(),
             Some(seed) => ChaCha8Rng::seed_from_u64(seed),
         };
+        if !self.legacy_order {
+            r.set_stream(self.stream);
+        }
 
-        while !mutants.is_empty() && sampled.len() < num_mutants {
-            // Get a random index into the current list of remaning mutants
-            let idx = r.gen_range(0..mutants.len());
-            let mutant = mutants.remove(idx);
-            if self.validate() {
-                if let Ok(true) = mutator.validate_mutant(&mutant.1) {
+        // Candidates that were drawn and validated, but not processed yet
+        let mut drawn: VecDeque<((usize, Mutant), Option<bool>)> = VecDeque::new();
+        loop {
+            let remaining = mutants.len() + drawn.len();
+            if remaining == 0 || sampled.len() >= num_mutants {
+                break;
+            }
+            if self.validate() && sampled.len() + remaining < num_mutants {
+                report(Diagnostic::new(
+                    DiagnosticCode::SamplingStoppedEarly,
+                    format!(
+                        "Stopping sampling after {} validation attempts: only {} candidates remain for the {} mutants still needed",
+                        attempts,
+                        remaining,
+                        num_mutants - sampled.len()
+                    ),
+                ));
+                break;
+            }
+            if !self.validate() {
+                // Get a random index into the current list of remaning mutants
+                let idx = r.gen_range(0..mutants.len());
+                sampled.push(mutants.remove(idx));
+                continue;
+            }
+            if drawn.is_empty() {
+                let size = batch_size
+                    .max(1)
+                    .min(num_mutants - sampled.len())
+                    .min(mutants.len());
+                let batch: Vec<(usize, Mutant)> = (0..size)
+                    .map(|_| mutants.remove(r.gen_range(0..mutants.len())))
+                    .collect();
+                let valid = validate_batch(&batch.iter().map(|(_, m)| m).collect::<Vec<_>>());
+                drawn.extend(batch.into_iter().zip(valid));
+            }
+            let (mut mutant, valid) = drawn.pop_front().unwrap();
+            attempts += 1;
+            // Past the validation deadline, or when solc could not be run,
+            // sampled mutants are kept without being validated
+            let outcome = match valid {
+                Some(true) => "valid",
+                Some(false) => "invalid",
+                None => "unvalidated",
+            };
+            let id = mutant.0 + 1;
+            match valid {
+                Some(true) => {
+                    mutant.1.validated = true;
                     sampled.push(mutant)
                 }
-            } else {
-                sampled.push(mutant);
+                Some(false) => (),
+                None => sampled.push(mutant),
             }
+            log::info!(
+                "Validation attempt {}: mutant {} is {} ({} of {} sampled, {} candidates left)",
+                attempts,
+                id,
+                outcome,
+                sampled.len(),
+                num_mutants,
+                mutants.len() + drawn.len()
+            );
         }
 
         sampled.sort_by(|m1, m2| m1.0.partial_cmp(&m2.0).unwrap());
 
-        Ok(sampled.iter().map(|m| m.1.clone()).collect())
+        (sampled.into_iter().map(|m| m.1).collect(), attempts)
+    }
+}
+
+impl MutantFilter for RandomDownSampleFilter {
+    fn filter_mutants(
+        &self,
+        mutator: &Mutator,
+        num_mutants: usize,
+    ) -> Result<Vec<Mutant>, Box<dyn error::Error>> {
+        let (sampled, attempts) = self.sample_in_batches(
+            mutator.mutants(),
+            num_mutants,
+            mutator.conf.validation_jobs,
+            |batch| mutator.validate_mutants_before_deadline(batch),
+        );
+        if self.validate() {
+            log::info!(
+                "Sampled {} mutants in {} validation attempts",
+                sampled.len(),
+                attempts
+            );
+        }
+        Ok(sampled)
     }
 
     fn validate(&self) -> bool {
         self.validate
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MutationType, Source};
+    use std::{cell::RefCell, collections::HashSet, path::PathBuf, rc::Rc};
+
+    /// `n` mutants of a single literal, each with a distinct replacement
+    fn mutants(n: usize) -> Vec<Mutant> {
+        file_mutants("virtual/C.sol", n)
+    }
+
+    /// Like `mutants()`, for the file at `path`
+    fn file_mutants(path: &str, n: usize) -> Vec<Mutant> {
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from(path),
+            PathBuf::new(),
+            b"contract C { uint256 x = 1; }".to_vec(),
+        ));
+        (0..n)
+            .map(|i| {
+                Mutant::new(
+                    source.clone(),
+                    MutationType::BoundaryLiteralMutation,
+                    25,
+                    26,
+                    (i + 2).to_string(),
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sample_with_mostly_invalid_mutants() {
+        let mutants = mutants(100);
+        let filter = RandomDownSampleFilter::new(Some(0), true, false);
+        // Reject 90% of the candidates, recording each one that is validated
+        let validated = RefCell::new(HashSet::new());
+        let validate = |m: &Mutant| {
+            assert!(validated.borrow_mut().insert(m.repl.clone()));
+            Some(m.repl.parse::<usize>().unwrap() % 10 == 0)
+        };
+
+        // Enough valid mutants are drawn without validating the whole pool
+        let (sampled, attempts) = filter.sample(&mutants, 5, validate);
+        assert_eq!(sampled.len(), 5);
+        assert!(sampled.iter().all(|m| m.validated));
+        assert_eq!(attempts, validated.borrow().len());
+        assert!(attempts < mutants.len());
+
+        // Only 10 candidates are valid: sampling stops as soon as the
+        // remaining candidates cannot make up 20 mutants
+        validated.borrow_mut().clear();
+        let (sampled, attempts) = filter.sample(&mutants, 20, validate);
+        assert!(sampled.len() <= 10);
+        assert_eq!(attempts, validated.borrow().len());
+        assert_eq!(attempts, mutants.len() - (19 - sampled.len()));
+
+        // Without validation, mutants are sampled without attempts
+        let filter = RandomDownSampleFilter::new(Some(0), false, false);
+        let (sampled, attempts) = filter.sample(&mutants, 20, |_| unreachable!());
+        assert_eq!(sampled.len(), 20);
+        assert_eq!(attempts, 0);
+    }
+
+    #[test]
+    fn test_sample_more_than_available() {
+        // An empty pool is exhausted at once, with or without validation
+        for validate in [true, false] {
+            let filter = RandomDownSampleFilter::new(Some(0), validate, false);
+            let (sampled, attempts) = filter.sample(&[], 1000, |_| unreachable!());
+            assert!(sampled.is_empty());
+            assert_eq!(attempts, 0);
+        }
+
+        // Without validation, the whole pool is sampled
+        let mutants = mutants(10);
+        let filter = RandomDownSampleFilter::new(Some(0), false, false);
+        let (sampled, _) = filter.sample(&mutants, 1000, |_| unreachable!());
+        assert_eq!(sampled.len(), 10);
+
+        // With validation, no candidate is validated in vain
+        let filter = RandomDownSampleFilter::new(Some(0), true, false);
+        let (sampled, attempts) = filter.sample(&mutants, 1000, |_| unreachable!());
+        assert!(sampled.is_empty());
+        assert_eq!(attempts, 0);
+    }
+
+    #[test]
+    fn test_sample_in_batches() {
+        let mutants = mutants(100);
+        let filter = RandomDownSampleFilter::new(Some(3), true, false);
+        let validate = |m: &Mutant| Some(m.repl.parse::<usize>().unwrap() % 4 == 0);
+        for num_mutants in [5, 30] {
+            let expected = filter.sample(&mutants, num_mutants, validate);
+            for batch_size in [2, 8, 100] {
+                // Batches never hold more candidates than are still needed
+                let (sampled, attempts) =
+                    filter.sample_in_batches(&mutants, num_mutants, batch_size, |batch| {
+                        assert!(batch.len() <= batch_size.min(num_mutants));
+                        batch.iter().map(|m| validate(m)).collect()
+                    });
+                let repls = |ms: &[Mutant]| ms.iter().map(|m| m.repl.clone()).collect::<Vec<_>>();
+                assert_eq!(repls(&sampled), repls(&expected.0));
+                assert_eq!(attempts, expected.1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sample_is_independent_of_generation_order() {
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("virtual/C.sol"),
+            PathBuf::new(),
+            b"contract C { uint256 x = 1 + 2; }".to_vec(),
+        ));
+        let mut mutants = mutants(20);
+        for repl in ["-", "*", "/", "%", "**"] {
+            let m = Mutant::new(
+                source.clone(),
+                MutationType::BinaryOpMutation,
+                26,
+                29,
+                repl.into(),
+            );
+            mutants.push(m.unwrap());
+        }
+        let sample = |filter: &RandomDownSampleFilter, mutants: &[Mutant]| {
+            let (sampled, _) = filter.sample(mutants, 8, |_| unreachable!());
+            let mut sampled: Vec<(String, String)> = sampled
+                .into_iter()
+                .map(|m| (m.op.to_string(), m.repl))
+                .collect();
+            sampled.sort();
+            sampled
+        };
+        let reversed: Vec<Mutant> = mutants.iter().rev().cloned().collect();
+        let mut rotated = mutants.clone();
+        rotated.rotate_left(7);
+        // Interleave the operators, as if they were generated node by node
+        let interleaved: Vec<Mutant> = mutants
+            .iter()
+            .step_by(2)
+            .chain(mutants.iter().skip(1).step_by(2))
+            .cloned()
+            .collect();
+
+        for seed in 0..10 {
+            let filter = RandomDownSampleFilter::new(Some(seed), false, false);
+            let expected = sample(&filter, &mutants);
+            assert_eq!(expected.len(), 8);
+            for permuted in [&reversed, &rotated, &interleaved] {
+                assert_eq!(sample(&filter, permuted), expected);
+            }
+        }
+
+        // Legacy sampling draws by generation order
+        let legacy = RandomDownSampleFilter::new(Some(0), false, true);
+        assert_ne!(sample(&legacy, &mutants), sample(&legacy, &reversed));
+        let (sampled, _) = legacy.sample(&mutants, 8, |_| unreachable!());
+        let mut r = ChaCha8Rng::seed_from_u64(0);
+        let mut remaining: Vec<&Mutant> = mutants.iter().collect();
+        let mut expected: Vec<&str> = (0..8)
+            .map(|_| {
+                remaining
+                    .remove(r.gen_range(0..remaining.len()))
+                    .repl
+                    .as_str()
+            })
+            .collect();
+        expected.sort();
+        let mut actual: Vec<&str> = sampled.iter().map(|m| m.repl.as_str()).collect();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parallel_sampling_matches_serial_sampling() {
+        let files = ["A.sol", "lib/B.sol", "lib/C.sol", "D.sol"];
+        let sample = |path: &str, seed: u64| {
+            let filter = RandomDownSampleFilter::new(Some(seed), false, false)
+                .with_stream(sampling_stream(Path::new(path)));
+            let (sampled, _) = filter.sample(&file_mutants(path, 50), 5, |_| unreachable!());
+            sampled
+                .into_iter()
+                .map(|m| (m.op.to_string(), m.repl))
+                .collect::<Vec<_>>()
+        };
+        for seed in [0, 7, u64::MAX] {
+            let serial: Vec<_> = files.iter().map(|path| sample(path, seed)).collect();
+            // Mutants are not `Send`, so each thread generates its own
+            let parallel: Vec<_> = std::thread::scope(|scope| {
+                let handles: Vec<_> = files
+                    .iter()
+                    .rev()
+                    .map(|path| scope.spawn(move || sample(path, seed)))
+                    .collect();
+                let mut samples: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+                samples.reverse();
+                samples
+            });
+            assert_eq!(serial, parallel);
+        }
+
+        // Files with the same candidates are sampled from different streams
+        assert_ne!(sample("A.sol", 0), sample("D.sol", 0));
+    }
+
+    #[test]
+    fn test_sampling_stream() {
+        // Stream ids are recorded in manifests: they must not change
+        assert_eq!(sampling_stream(Path::new("A.sol")), 6506229149919486468);
+        assert_eq!(
+            sampling_stream(Path::new("lib/B.sol")),
+            10007310872179364969
+        );
+        assert_eq!(
+            sampling_stream(Path::new("lib/B.sol")),
+            sampling_stream(&Path::new("lib").join("B.sol"))
+        );
+        assert_ne!(
+            sampling_stream(Path::new("lib/B.sol")),
+            sampling_stream(Path::new("lib/C.sol"))
+        );
+    }
+
+    #[test]
+    fn test_parse_seed() {
+        assert_eq!(parse_seed("0"), Ok(0));
+        assert_eq!(parse_seed("42"), Ok(42));
+        assert_eq!(parse_seed("18446744073709551615"), Ok(u64::MAX));
+        for seed in ["-1", "18446744073709551616", "abc", ""] {
+            let err = parse_seed(seed).unwrap_err();
+            assert!(err.starts_with("seed must be an integer"), "{}", err);
+        }
+
+        // Seeds of configuration files are validated the same way
+        let params = |seed: serde_json::Value| {
+            serde_json::from_value::<crate::MutateParams>(
+                serde_json::json!({"filename": "C.sol", "seed": seed}),
+            )
+        };
+        assert_eq!(params(serde_json::json!(12)).unwrap().seed, 12);
+        assert_eq!(params(serde_json::json!("12")).unwrap().seed, 12);
+        let err = params(serde_json::json!(-1)).unwrap_err().to_string();
+        assert!(err.contains("seed must be an integer"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_num_mutants() {
+        assert!(validate_num_mutants(Some(0)).is_err());
+        assert!(validate_num_mutants(Some(1)).is_ok());
+        assert!(validate_num_mutants(None).is_ok());
+    }
+
+    #[test]
+    fn test_downsample_size() {
+        let candidates = 10;
+        assert_eq!(downsample_size(0, candidates), Some(0));
+        assert_eq!(downsample_size(1, candidates), Some(1));
+        assert_eq!(downsample_size(candidates, candidates), None);
+        assert_eq!(downsample_size(candidates + 1, candidates), None);
+    }
+}
@@ -0,0 +1,170 @@
+//! Post-export hooks: a user command that is run on each exported mutant
+//! (e.g., to format it or to inject a license header), see
+//! `--post_export_cmd`.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The file of each mutant directory that captures the hook's stdout
+pub const HOOK_STDOUT_FILENAME: &str = "post_export.stdout";
+
+/// The file of each mutant directory that captures the hook's stderr
+pub const HOOK_STDERR_FILENAME: &str = "post_export.stderr";
+
+/// A command template that is run once per exported mutant. The template is
+/// run by `sh -c` after substituting the following placeholders (verbatim,
+/// without quoting):
+///
+/// * `{mutant_path}`: the absolute path of the exported mutant file
+/// * `{id}`: the mutant id
+/// * `{op}`: the mutation operator that generated the mutant
+/// * `{original}`: the original file, relative to the sourceroot
+#[derive(Debug, Clone)]
+pub struct PostExportHook {
+    /// The command template
+    cmd: String,
+
+    /// How long each invocation may run before it is killed
+    timeout: Duration,
+
+    /// How many invocations may run at the same time
+    jobs: usize,
+}
+
+/// An invocation of a post-export hook on an exported mutant
+#[derive(Debug, Clone)]
+pub struct HookJob {
+    /// The id of the mutant
+    pub mid: String,
+
+    /// The exported mutant directory (`mutants/<mid>/`), where the hook's
+    /// output is captured
+    pub mutant_dir: PathBuf,
+
+    /// The command, with its placeholders substituted
+    pub command: String,
+}
+
+/// A hook invocation that failed to run, exited with a non-zero status, or
+/// timed out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookFailure {
+    /// The id of the mutant the hook was run on
+    pub mid: String,
+
+    /// Why the hook failed
+    pub reason: String,
+}
+
+impl fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mutant {}: {}", self.mid, self.reason)
+    }
+}
+
+impl PostExportHook {
+    /// Run `cmd` on each exported mutant, killing invocations that run longer
+    /// than `timeout` and running at most `jobs` invocations at a time
+    pub fn new(cmd: String, timeout: Duration, jobs: usize) -> PostExportHook {
+        PostExportHook {
+            cmd,
+            timeout,
+            jobs: jobs.max(1),
+        }
+    }
+
+    /// The invocation of this hook on mutant `mid`, which was exported to
+    /// `mutant_path` in `mutant_dir` from `original`
+    pub fn job(
+        &self,
+        mid: &str,
+        mutant_dir: PathBuf,
+        mutant_path: &Path,
+        op: &str,
+        original: &Path,
+    ) -> HookJob {
+        let command = self
+            .cmd
+            .replace("{mutant_path}", &mutant_path.display().to_string())
+            .replace("{id}", mid)
+            .replace("{op}", op)
+            .replace("{original}", &original.display().to_string());
+        HookJob {
+            mid: mid.to_string(),
+            mutant_dir,
+            command,
+        }
+    }
+
+    /// Run `jobs`, returning the failed ones in the order of `jobs`
+    pub fn run(&self, jobs: &[HookJob]) -> Vec<HookFailure> {
+        let next = AtomicUsize::new(0);
+        let failures = Mutex::new(vec![]);
+        thread::scope(|scope| {
+            for _ in 0..self.jobs.min(jobs.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(i) else { break };
+                    log::info!("Running post-export hook on mutant {}", job.mid);
+                    if let Err(reason) = self.run_job(job) {
+                        let failure = HookFailure {
+                            mid: job.mid.clone(),
+                            reason,
+                        };
+                        failures.lock().unwrap().push((i, failure));
+                    }
+                });
+            }
+        });
+        let mut failures = failures.into_inner().unwrap();
+        failures.sort_by_key(|(i, _)| *i);
+        failures.into_iter().map(|(_, failure)| failure).collect()
+    }
+
+    /// Run a single job, capturing its output in its mutant directory
+    fn run_job(&self, job: &HookJob) -> Result<(), String> {
+        let mut child =
+            Self::spawn(job).map_err(|e| format!("could not run `{}`: {}", job.command, e))?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) if status.success() => return Ok(()),
+                Ok(Some(status)) => {
+                    return Err(format!("`{}` failed with {}", job.command, status))
+                }
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!(
+                        "`{}` timed out after {:.2} seconds",
+                        job.command,
+                        self.timeout.as_secs_f64()
+                    ));
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                Err(e) => return Err(format!("could not wait for `{}`: {}", job.command, e)),
+            }
+        }
+    }
+
+    fn spawn(job: &HookJob) -> io::Result<Child> {
+        let stdout = fs::File::create(job.mutant_dir.join(HOOK_STDOUT_FILENAME))?;
+        let stderr = fs::File::create(job.mutant_dir.join(HOOK_STDERR_FILENAME))?;
+        Command::new("sh")
+            .arg("-c")
+            .arg(&job.command)
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr)
+            .spawn()
+    }
+}
@@ -1,17 +1,42 @@
 mod ast;
-use std::{collections::HashMap, fs, path::PathBuf, time::Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
 
 pub use ast::*;
 
+mod ast_context;
+pub use ast_context::*;
+
 mod cli;
 pub use cli::*;
 
 mod compile;
 pub use compile::*;
 
+mod diagnostics;
+pub use diagnostics::*;
+
+mod error;
+pub use error::*;
+
 mod filter;
 pub use filter::*;
 
+mod hook;
+pub use hook::*;
+
 mod mutation;
 pub use mutation::*;
 
@@ -21,6 +46,9 @@ pub use mutant_writer::*;
 mod mutator;
 pub use mutator::*;
 
+mod results;
+pub use results::*;
+
 mod source;
 pub use source::*;
 
@@ -33,13 +61,32 @@ pub use test_util::*;
 mod util;
 pub use util::*;
 
+#[cfg(feature = "vyper")]
+mod vyper;
+#[cfg(feature = "vyper")]
+pub use vyper::*;
+
 /// Execute the `mutate` command. This returns a mapping from output directories
 /// to generated mutants.
 pub fn run_mutate(
     mutate_params: Vec<MutateParams>,
+) -> Result<HashMap<String, Vec<Mutant>>, Box<dyn std::error::Error>> {
+    run_mutate_cancellable(mutate_params, Arc::default())
+}
+
+/// Execute the `mutate` command like `run_mutate()`, until `cancelled` is
+/// raised (e.g., by Ctrl-C). Mutants are then no longer validated, and the
+/// remaining parameter sets are skipped: the mutants validated so far are
+/// written as usual, the manifest of the output directory being written is
+/// marked as partial, and this returns a `GambitError::Interrupted`.
+pub fn run_mutate_cancellable(
+    mutate_params: Vec<MutateParams>,
+    cancelled: Arc<AtomicBool>,
 ) -> Result<HashMap<String, Vec<Mutant>>, Box<dyn std::error::Error>> {
     log::info!("Running Gambit Mutate command");
     log::debug!("Mutate parameters: {:#?}", mutate_params);
+    let num_requested: Option<usize> = mutate_params.iter().map(|p| p.num_mutants).sum();
+    let strict = mutate_params.iter().any(|p| p.strict);
 
     let start = Instant::now();
     let mut mutants_by_out_dir: HashMap<String, Vec<(Mutant, bool)>> = HashMap::default();
@@ -55,9 +102,32 @@ pub fn run_mutate(
             .push(params);
     }
 
+    // The first source that each physical file was mutated through. All
+    // mutants of a file are reported under this source's path, even when
+    // entries reach the file under different sourceroots
+    let mut sources_by_path: HashMap<PathBuf, Rc<Source>> = HashMap::new();
+
+    // The resolved path and detected version of each solc binary
+    let mut solc_info: HashMap<String, (String, Option<String>)> = HashMap::new();
+
+    // The operators requested for each out dir, recorded in its manifest
+    let mut requested_operators: HashMap<String, BTreeSet<String>> = HashMap::new();
+
     let mut total_num_mutants = 0;
+    // The filenames (and outdirs) of the parameters whose mutants were written
+    let mut completed: Vec<(String, String)> = vec![];
+    // Time spent validating so far, which counts against each configuration's
+    // `max_validation_seconds`
+    let mut validation_time = Duration::ZERO;
+    // The `--contract` and `--functions` names of every entry, and whether
+    // any of the files mutated for them defines them
+    let mut filter_names = FilterNameMatches::default();
     // Iterate through each out dir and its associated parameters and generate mutants
     for (outdir, outdir_params) in outdir_map.iter() {
+        if cancelled.load(Ordering::SeqCst) {
+            break;
+        }
+
         /*                                                          *
          *               SETUP OUTPUT DIRECTORY                     *
          *               ======================                     */
@@ -74,117 +144,1030 @@ pub fn run_mutate(
         // that an output directory should not be overwritten, all parameters
         // targetting that directory are marked as no_output
         let no_overwrite = outdir_params.iter().any(|p| p.no_overwrite);
+        let overwrite = outdir_params.iter().any(|p| p.overwrite);
+        let force = outdir_params.iter().any(|p| p.force);
+        // Export to the mutants subdirectory of the first configuration that
+        // names one
+        let mutants_subdir = outdir_params
+            .iter()
+            .find_map(|p| p.mutants_subdir.clone())
+            .unwrap_or(DEFAULT_MUTANTS_SUBDIR.into());
+        check_mutants_subdir_name(&mutants_subdir).map_err(GambitError::Configuration)?;
+        // Lay out the mutants flat if any configuration asks for it
+        let layout = outdir_params
+            .iter()
+            .map(|p| p.outdir_layout)
+            .find(|l| *l == OutdirLayout::Flat)
+            .unwrap_or_default();
+        if layout == OutdirLayout::Flat && outdir_params.iter().any(|p| p.export_full_project) {
+            return Err(Box::new(GambitError::Configuration(format!(
+                "--outdir_layout flat cannot be combined with --export_full_project (output directory {})",
+                outdir
+            ))));
+        }
+
+        // The project mirrored into each mutant directory applies to every
+        // configuration of the output directory
+        let project_root = outdir_setting(outdir, outdir_params, "export_full_project", |p| {
+            p.export_full_project.then(|| {
+                PathBuf::from(
+                    p.project_root
+                        .clone()
+                        .or(p.sourceroot.clone())
+                        .unwrap_or(".".into()),
+                )
+            })
+        })?;
+
+        // The sidecars are copied into every mutant directory, whichever
+        // configuration generated the mutant
+        let mut sidecars = outdir_setting(outdir, outdir_params, "copy_into_mutants", |p| {
+            let mut sidecars: Vec<PathBuf> = vec![];
+            for sidecar in p.copy_into_mutants.iter().flatten() {
+                let sidecar = PathBuf::from(sidecar);
+                if !sidecars.contains(&sidecar) {
+                    sidecars.push(sidecar);
+                }
+            }
+            sidecars
+        })?;
+        let link_sidecars = outdir_setting(outdir, outdir_params, "link_into_mutants", |p| {
+            p.link_into_mutants
+        })?;
+        let export_format =
+            outdir_setting(outdir, outdir_params, "export_format", |p| p.export_format)?;
 
         let outdir_path = PathBuf::from(outdir);
 
         if outdir_path.exists() {
-            if !no_overwrite {
-                if fs::metadata(outdir_path.as_path()).is_ok() {
-                    match fs::remove_dir_all(&outdir_path) {
-                        Ok(_) => log::info!("Removed outdir {}", outdir_path.display()),
-                        Err(_) => log::info!(
-                            "Couldn't remove outdir {} even though it exists",
-                            outdir_path.display()
-                        ),
-                    }
+            if force && !no_overwrite {
+                match fs::remove_dir_all(&outdir_path) {
+                    Ok(_) => log::info!("Removed outdir {}", outdir_path.display()),
+                    Err(_) => log::info!(
+                        "Couldn't remove outdir {} even though it exists",
+                        outdir_path.display()
+                    ),
                 }
+            } else if !no_overwrite {
+                // Never touch a directory that may hold anything but Gambit's
+                // output without `--force`
+                check_outdir_deletion(&outdir_path, &mutants_subdir).map_err(|reason| {
+                    let (_, foreign) =
+                        outdir_entries(&outdir_path, &mutants_subdir).unwrap_or_default();
+                    let listing = if foreign.is_empty() {
+                        String::new()
+                    } else {
+                        format!(
+                            " (it holds files that Gambit did not write: {})",
+                            list_entries(&outdir_path, &foreign)
+                        )
+                    };
+                    GambitError::Configuration(format!(
+                        "Refusing to delete output directory {}: {}{}; pass --force to delete it anyway",
+                        outdir, reason, listing
+                    ))
+                })?;
+                remove_earlier_outputs(&outdir_path, &mutants_subdir, overwrite)?;
             } else {
-                eprintln!(
-                    "[!] Output directory {} exists! You can:",
-                    outdir_path.display()
-                );
-                eprintln!("  (1) Manually remove {}", outdir_path.display());
-                eprintln!(
-                    "  (2) Use the `--overwrite` flag to skip this message and overwrite {}",
-                    outdir_path.display()
+                report(
+                    Diagnostic::new(
+                        DiagnosticCode::OutputDirectoryExists,
+                        format!(
+                            "Output directory {} exists! You can:",
+                            outdir_path.display()
+                        ),
+                    )
+                    .with_file(outdir_path.display())
+                    .with_note(format!("(1) Manually remove {}", outdir_path.display()))
+                    .with_note(format!(
+                        "(2) Use the `--overwrite` flag instead of `--no_overwrite` to replace the outputs in {}",
+                        outdir_path.display()
+                    ))
+                    .with_note(
+                        "(3) Specify another output directory with `--outdir OUTPUT_LOCATION`",
+                    ),
                 );
-                eprintln!("  (3) Specify another output directory with `--outdir OUTPUT_LOCATION`");
                 continue;
             }
         }
         log::info!("Creating outdir {}", outdir_path.display());
         fs::create_dir_all(&outdir_path)?;
+        fs::write(outdir_path.join(GAMBIT_MARKER_FILENAME), "")?;
+
+        /*                                                 *
+         *               SETUP WRITER                      *
+         *               ============                      */
+
+        if project_root.is_some() && !sidecars.is_empty() {
+            report(Diagnostic::new(
+                DiagnosticCode::SidecarsIgnored,
+                format!("Ignoring copy_into_mutants for {}: export_full_project already mirrors the project into each mutant directory", outdir),
+            ));
+            sidecars.clear();
+        }
+        if export_format == ExportFormat::Patch && !sidecars.is_empty() {
+            report(Diagnostic::new(
+                DiagnosticCode::SidecarsIgnored,
+                format!(
+                    "Ignoring copy_into_mutants for {}: mutants are exported as patches",
+                    outdir
+                ),
+            ));
+            sidecars.clear();
+        }
+        if layout == OutdirLayout::Flat && !sidecars.is_empty() {
+            report(Diagnostic::new(
+                DiagnosticCode::SidecarsIgnored,
+                format!(
+                    "Ignoring copy_into_mutants for {}: mutants are exported to the flat layout",
+                    outdir
+                ),
+            ));
+            sidecars.clear();
+        }
+        let stable_ids = outdir_params.iter().any(|p| p.stable_ids);
+        let max_inline_diff_bytes = outdir_params
+            .iter()
+            .filter_map(|p| p.max_inline_diff_bytes)
+            .min();
+        let compress_diffs = outdir_params.iter().any(|p| p.compress_diffs);
+        // Run the post-export hook of the first configuration that has one
+        let post_export_hook = outdir_params
+            .iter()
+            .find(|p| p.post_export_cmd.is_some())
+            .map(|p| {
+                PostExportHook::new(
+                    p.post_export_cmd.clone().unwrap(),
+                    Duration::from_secs(p.post_export_timeout_seconds),
+                    p.post_export_jobs.unwrap_or_else(default_jobs),
+                )
+            });
+        let strict_hooks = outdir_params.iter().any(|p| p.strict_hooks);
+        // Record every configuration in the job manifest if one asks for it
+        let job_configurations = outdir_params
+            .iter()
+            .any(|p| p.emit_manifest)
+            .then(|| outdir_params.iter().map(canonicalize_paths).collect());
+        let writer = MutantWriter::new(outdir.clone(), project_root)
+            .with_sidecars(sidecars, link_sidecars)
+            .with_export_format(export_format)
+            .with_layout(layout)
+            .with_stable_ids(stable_ids)
+            .with_max_inline_diff_bytes(max_inline_diff_bytes)
+            .with_compress_diffs(compress_diffs)
+            .with_post_export_hook(post_export_hook)
+            .with_mutants_subdir(mutants_subdir)
+            .with_force(force)
+            .with_job_manifest(job_configurations);
 
         // Now, let's get to the fun stuff! Iterate through the parameters, and for each:
         // 1. generate mutants
         // 2. filter the mutants (if num-mutants was specified)
         // 3. optionally validate the mutants
-        for params in outdir_params.iter() {
+        // 4. write the mutants generated for this outdir so far
+        //
+        // Writing after each parameter set means that if a later parameter
+        // set fails, the mutants of earlier ones are still exported and logged.
+        let mut num_exported = 0;
+        // The mutation session of each parameter set written so far
+        let mut sessions: Vec<MutationSession> = vec![];
+        // The files that the parameter sets target: their mutants are
+        // attributed to the parameter sets targeting them, even when other
+        // parameter sets reach them (e.g., with `--include_called_libraries`)
+        let targets: HashSet<PathBuf> = outdir_params
+            .iter()
+            .filter_map(|p| fs::canonicalize(p.filename.as_ref()?).ok())
+            .collect();
+        let mut duplicates_dropped = 0;
+        for (i, params) in outdir_params.iter().enumerate() {
+            if cancelled.load(Ordering::SeqCst) {
+                log::info!(
+                    "Interrupted: skipping {} entries for output directory {}",
+                    outdir_params.len() - i,
+                    outdir
+                );
+                break;
+            }
             log::info!("Processing params: {:?}", params);
-            let export = !params.no_export;
-
-            /*                                          *
-             *               MUTATE                     *
-             *               ======                     */
-            log::info!("Creating mutator");
-            let mut mutator = Mutator::from(params);
-            log::info!("Generating mutants");
-            let mutants = mutator.mutate()?.clone();
+            let filename = params.filename.clone().unwrap_or_default();
+            requested_operators
+                .entry(outdir.clone())
+                .or_default()
+                .extend(
+                    MutatorConf::from(params)
+                        .mutation_operators
+                        .iter()
+                        .map(|op| op.to_string()),
+                );
+            let ms = mutants_by_out_dir.entry(outdir.clone()).or_default();
+            let checkpoint = generate_session_mutants(
+                params,
+                &mut solc_info,
+                &mut validation_time,
+                &mut filter_names,
+                &cancelled,
+                (ms, &targets),
+                &mut duplicates_dropped,
+            )
+            .and_then(|(mut session, mut mutants)| {
+                session.source = sources_by_path
+                    .entry(session.source.physical_path().to_path_buf())
+                    .or_insert_with(|| session.source.clone())
+                    .clone();
+                unify_mutant_sources(&mut mutants, &mut sources_by_path);
+                let target = session.source.physical_path().to_path_buf();
+                session
+                    .units
+                    .retain(|u| attributed_to(u.source.physical_path(), &target, &targets));
+                total_num_mutants += mutants.len();
+                log::info!("Adding {} mutants to global mutant pool", mutants.len());
+                session.mutants = ms.len()..ms.len() + mutants.len();
+                sessions.push(session);
+                ms.append(&mut mutants);
+
+                log::info!("Writing mutants for output directory {}", outdir);
+                writer.write_sessions_after(ms, &sessions, num_exported)?;
+                num_exported = ms.len();
+                writer.write_manifest(&RunManifest {
+                    requested_operators: requested_operators[outdir].iter().cloned().collect(),
+                    sampling_streams: sampling_streams(&sessions)?,
+                    partial: cancelled.load(Ordering::SeqCst),
+                })
+            });
+            if let Err(e) = checkpoint {
+                let mut diagnostic = Diagnostic::new(
+                    DiagnosticCode::MutationFailed,
+                    format!(
+                        "Failed to mutate {} (entry {} of {} for output directory {})",
+                        filename,
+                        i + 1,
+                        outdir_params.len(),
+                        outdir
+                    ),
+                )
+                .with_file(&filename);
+                if !completed.is_empty() {
+                    diagnostic = diagnostic
+                        .with_note("Mutants of these entries were written before the failure:");
+                    for (filename, outdir) in completed.iter() {
+                        diagnostic = diagnostic
+                            .with_note(format!("[+] {} (output directory {})", filename, outdir));
+                    }
+                }
+                report(diagnostic);
+                return Err(e);
+            }
+            completed.push((filename, outdir.clone()));
+        }
+        if cancelled.load(Ordering::SeqCst) {
+            writer.write_manifest(&RunManifest {
+                requested_operators: requested_operators
+                    .get(outdir)
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .collect(),
+                sampling_streams: sampling_streams(&sessions)?,
+                partial: true,
+            })?;
+        }
+
+        if duplicates_dropped > 0 {
+            report(Diagnostic::new(
+                DiagnosticCode::CrossEntryDuplicates,
+                format!(
+                    "Dropped {} mutants of output directory {} that other entries for it also generate",
+                    duplicates_dropped, outdir
+                ),
+            ));
+        }
+
+        let hook_failures = writer.hook_failures();
+        if !hook_failures.is_empty() {
+            let mut diagnostic = Diagnostic::new(
+                DiagnosticCode::PostExportHookFailed,
+                format!(
+                    "The post-export hook failed on {} mutants of output directory {}",
+                    hook_failures.len(),
+                    outdir
+                ),
+            );
+            for failure in hook_failures.iter() {
+                diagnostic = diagnostic.with_note(failure);
+            }
+            report(diagnostic);
+            if strict_hooks {
+                return Err(Box::new(GambitError::HookFailed(format!(
+                    "{} post-export hook invocations failed for output directory {}",
+                    hook_failures.len(),
+                    outdir
+                ))));
+            }
+        }
+    }
+
+    let results: HashMap<String, Vec<Mutant>> = mutants_by_out_dir
+        .into_iter()
+        .map(|(outdir, mutants)| (outdir, mutants.into_iter().map(|(m, _)| m).collect()))
+        .collect();
+
+    let t = start.elapsed().as_secs_f64();
+    match num_requested.filter(|n| *n > total_num_mutants) {
+        Some(n) => log::info!(
+            "Generated {} mutants ({} requested) in {}",
+            total_num_mutants,
+            n,
+            t
+        ),
+        None => log::info!("Generated {} mutants in {}", total_num_mutants, t),
+    }
+    if cancelled.load(Ordering::SeqCst) {
+        return Err(Box::new(GambitError::Interrupted(format!(
+            "wrote the {} mutants validated before the interruption",
+            total_num_mutants
+        ))));
+    }
+    // A name is only reported once every file had the chance to define it
+    let unmatched = filter_names.unmatched();
+    if !unmatched.is_empty() {
+        let mut diagnostic = Diagnostic::new(
+            DiagnosticCode::FilterNameNotFound,
+            format!(
+                "{} matched nothing in the mutated files, so no mutants were generated for {}",
+                unmatched.join(", "),
+                if unmatched.len() == 1 { "it" } else { "them" }
+            ),
+        );
+        if !filter_names.available.is_empty() {
+            diagnostic = diagnostic.with_note(format!(
+                "Available functions: {}",
+                filter_names
+                    .available
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        report(diagnostic);
+        if strict {
+            return Err(Box::new(GambitError::Configuration(format!(
+                "{} matched nothing in the mutated files (see --strict)",
+                unmatched.join(", ")
+            ))));
+        }
+    }
+    Ok(results)
+}
+
+/// Delete the outputs of earlier runs from the existing output directory
+/// `outdir`, keeping the files that Gambit did not write (see
+/// `outdir_entries()`). Unless `overwrite` is set, this asks for confirmation
+/// on the terminal first, and fails without a terminal to ask on.
+fn remove_earlier_outputs(
+    outdir: &Path,
+    mutants_subdir: &str,
+    overwrite: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (outputs, foreign) = outdir_entries(outdir, mutants_subdir)?;
+    if outputs.is_empty() {
+        return Ok(());
+    }
+    if !overwrite && !confirm_overwrite(outdir)? {
+        return Err(Box::new(GambitError::Configuration(format!(
+            "Output directory {} holds the outputs of an earlier run; pass --overwrite to replace them",
+            outdir.display()
+        ))));
+    }
+    for entry in outputs.iter() {
+        if entry.is_dir() && !entry.is_symlink() {
+            fs::remove_dir_all(entry)?;
+        } else {
+            fs::remove_file(entry)?;
+        }
+    }
+    log::info!(
+        "Removed the outputs of an earlier run from {}",
+        outdir.display()
+    );
+    if !foreign.is_empty() {
+        log::info!(
+            "Kept {} files that Gambit did not write: {}",
+            foreign.len(),
+            list_entries(outdir, &foreign)
+        );
+    }
+    Ok(())
+}
+
+/// The value of a setting that applies to a whole output directory, which
+/// every configuration for `outdir` must agree on. `option` names the setting
+/// in the error reported otherwise.
+fn outdir_setting<T: PartialEq>(
+    outdir: &str,
+    outdir_params: &[MutateParams],
+    option: &str,
+    setting: impl Fn(&MutateParams) -> T,
+) -> Result<T, GambitError> {
+    let mut values = outdir_params.iter().map(setting);
+    let first = values.next().ok_or_else(|| {
+        GambitError::Internal(format!("No configuration for output directory {}", outdir))
+    })?;
+    if values.any(|v| v != first) {
+        return Err(GambitError::Configuration(format!(
+            "The configurations for output directory {} disagree on {}, which applies to the whole output directory",
+            outdir, option
+        )));
+    }
+    Ok(first)
+}
+
+/// Ask on the terminal whether to replace the outputs of an earlier run in
+/// `outdir`. Without a terminal to ask on, the answer is no.
+fn confirm_overwrite(outdir: &Path) -> io::Result<bool> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Ok(false);
+    }
+    eprint!(
+        "Replace the outputs of an earlier run in {}? [y/N] ",
+        outdir.display()
+    );
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// List the names of `entries` of `outdir`, eliding all but the first few
+fn list_entries(outdir: &Path, entries: &[PathBuf]) -> String {
+    const MAX_LISTED: usize = 10;
+    let mut names: Vec<String> = entries
+        .iter()
+        .take(MAX_LISTED)
+        .map(|e| e.strip_prefix(outdir).unwrap_or(e).display().to_string())
+        .collect();
+    if entries.len() > MAX_LISTED {
+        names.push(format!("and {} more", entries.len() - MAX_LISTED));
+    }
+    names.join(", ")
+}
+
+/// The mutants generated for a parameter set, each along with whether it is
+/// exported, and the mutation session that describes them
+type SessionMutants = (MutationSession, Vec<(Mutant, bool)>);
+
+/// Generate, filter, and (optionally) validate the mutants of a single
+/// parameter set in memory. Unlike `run_mutate()`, this never creates, deletes,
+/// or writes to the output directory of `params`: solc's intermediate files
+/// are written to a temporary directory that is removed before returning.
+/// Like `run_mutate()`, a filename or sourceroot that cannot be resolved is
+/// reported as a `GambitError::FileResolution`.
+///
+/// ```no_run
+/// let params: gambit::MutateParams = serde_json::from_value(serde_json::json!({
+///     "filename": "benchmarks/10Power/TenPower.sol",
+///     "num_mutants": 3,
+/// }))
+/// .unwrap();
+/// for mutant in gambit::generate_mutants(&params).unwrap() {
+///     println!("{}", serde_json::to_string(&mutant).unwrap());
+///     println!("{}", mutant.as_source_string().unwrap());
+/// }
+/// ```
+pub fn generate_mutants(params: &MutateParams) -> Result<Vec<Mutant>, GambitError> {
+    let tmp = tempfile::tempdir().map_err(|e| GambitError::Internal(e.to_string()))?;
+    let params = MutateParams {
+        outdir: Some(tmp.path().to_str().unwrap().to_string()),
+        ..params.clone()
+    };
+    let mut validation_time = Duration::ZERO;
+    let (_, mutants) = generate_session_mutants(
+        &params,
+        &mut HashMap::new(),
+        &mut validation_time,
+        &mut FilterNameMatches::default(),
+        &Arc::default(),
+        (&[], &HashSet::new()),
+        &mut 0,
+    )?;
+    Ok(mutants.into_iter().map(|(m, _)| m).collect())
+}
+
+/// `params` with its filename and sourceroot resolved the way the CLI resolves
+/// them: the filename is canonicalized (or else resolved as an import path
+/// through the remappings and solc's search directories), and the sourceroot
+/// defaults to the current working directory and must contain the filename.
+fn resolve_source_paths(params: &MutateParams) -> Result<MutateParams, GambitError> {
+    let filename = params.filename.as_ref().ok_or_else(|| {
+        GambitError::FileResolution("Found a configuration without a filename".into())
+    })?;
+    let filename_path = match PathBuf::from(filename).canonicalize() {
+        Ok(path) => path,
+        Err(e) => {
+            let search_dirs: Vec<String> = params
+                .solc_base_path
+                .iter()
+                .chain(&params.solc_include_path)
+                .cloned()
+                .collect();
+            resolve_import_mapped_filename(
+                filename,
+                params.solc_remappings.as_deref().unwrap_or_default(),
+                &search_dirs,
+                Path::new("."),
+            )
+            .ok_or_else(|| {
+                GambitError::FileResolution(format!(
+                    "Could not resolve filename `{}`: {}",
+                    filename, e
+                ))
+            })?
+        }
+    };
+    let sourceroot = params.sourceroot.as_deref().unwrap_or(".");
+    let sourceroot_path = PathBuf::from(sourceroot).canonicalize().map_err(|e| {
+        GambitError::FileResolution(format!(
+            "Could not resolve sourceroot `{}`: {}",
+            sourceroot, e
+        ))
+    })?;
+    if !filename_path.starts_with(&sourceroot_path) {
+        return Err(GambitError::FileResolution(format!(
+            "Resolved filename `{}` is not prefixed by the sourceroot {}",
+            filename_path.display(),
+            sourceroot_path.display()
+        )));
+    }
+    Ok(MutateParams {
+        filename: Some(filename_path.to_str().unwrap().to_string()),
+        sourceroot: Some(sourceroot_path.to_str().unwrap().to_string()),
+        ..params.clone()
+    })
+}
+
+/// Generate, filter, and (optionally) validate the mutants of a single
+/// parameter set, returning each mutant along with whether it is exported.
+/// These mutants are described by the returned mutation session, whose
+/// `mutants` range is left for the caller to fill in.
+///
+/// `solc_info` caches the resolved path and detected version of each solc
+/// binary, and `validation_time` accumulates the time spent validating, which
+/// counts against `max_validation_seconds`. The `--contract` and
+/// `--functions` names that the mutated files define are recorded in
+/// `filter_names`. Once `cancelled` is raised, no more mutants are validated,
+/// and only the validated mutants are returned.
+///
+/// `(earlier, targets)` are the mutants of the earlier parameter sets for the
+/// same output directory and the files that all of them target: mutants that
+/// these account for are dropped before sampling and validation (see
+/// `drop_cross_entry_duplicates()`), and counted in `duplicates_dropped`.
+fn generate_session_mutants(
+    params: &MutateParams,
+    solc_info: &mut HashMap<String, (String, Option<String>)>,
+    validation_time: &mut Duration,
+    filter_names: &mut FilterNameMatches,
+    cancelled: &Arc<AtomicBool>,
+    (earlier, targets): (&[(Mutant, bool)], &HashSet<PathBuf>),
+    duplicates_dropped: &mut usize,
+) -> Result<SessionMutants, Box<dyn std::error::Error>> {
+    let params = &resolve_source_paths(params)?;
+    let export = !params.no_export;
+
+    /*                                          *
+     *               MUTATE                     *
+     *               ======                     */
+    log::info!("Creating mutator");
+    let mut mutator = Mutator::try_from(params)?;
+    mutator.set_cancellation(cancelled.clone());
+    let operators: BTreeSet<String> = mutator
+        .conf
+        .mutation_operators
+        .iter()
+        .map(|op| op.to_string())
+        .collect();
+    let mut session = MutationSession {
+        source: mutator.sources()[0].clone(),
+        operators: operators.into_iter().collect(),
+        functions: params.functions.clone(),
+        contract: params.contract.clone(),
+        solc: None,
+        solc_version: None,
+        validation_attempts: None,
+        validation_stages: None,
+        validation_times: None,
+        sampling_stream: None,
+        mutants: 0..0,
+        units: vec![],
+        requested_mutants: params.num_mutants,
+        ast_contexts: vec![],
+    };
+    log::info!("Generating mutants");
+    mutator.mutate()?;
+    // Mutants are sampled among those this parameter set accounts for, so
+    // that `num_mutants` of them are exported
+    *duplicates_dropped += drop_cross_entry_duplicates(
+        &mut mutator.mutants,
+        earlier,
+        session.source.physical_path(),
+        targets,
+    );
+    let mutants = mutator.mutants.clone();
+    filter_names.merge(&mutator.stats.filter_names);
+    session.units = mutator.stats.mutated_units.clone();
+    session.ast_contexts = mutator.stats.ast_contexts.clone();
+    log::info!(
+        "(pre filter/validate) Generated {} mutants for {}",
+        &mutants.len(),
+        params.filename.as_ref().unwrap()
+    );
+
+    /*                                                   *
+     *               FILTER/VALIDATE                     *
+     *               ===============                     */
+
+    // TODO: Separate out Filtering from Validation
+
+    // Check if we are filtering
+    let num_mutants = params
+        .num_mutants
+        .and_then(|n| downsample_size(n, mutants.len()));
+    if let Some(max_validation_seconds) = params.max_validation_seconds {
+        let budget = Duration::from_secs(max_validation_seconds);
+        mutator.set_validation_deadline(Some(
+            Instant::now() + budget.saturating_sub(*validation_time),
+        ));
+    }
+    let validation_start = Instant::now();
+    let mut mutants = if let Some(num_mutants) = num_mutants {
+        log::info!("Filtering down to {} mutants", num_mutants);
+        log::debug!("  seed: {:?}", params.seed);
+        log::debug!("  validating?: {}", !params.skip_validate);
+        let seed = if params.random_seed {
+            None
+        } else {
+            Some(params.seed)
+        };
+        let stream = sampling_stream(&session.source.relative_filename()?);
+        log::debug!("  stream: {}", stream);
+        let filter =
+            RandomDownSampleFilter::new(seed, !params.skip_validate, params.legacy_sampling)
+                .with_stream(stream);
+        if !params.legacy_sampling {
+            session.sampling_stream = Some(stream);
+        }
+        let mutants = filter.filter_mutants(&mutator, num_mutants)?;
+        log::info!("Filtering resulted in {} mutants", mutants.len());
+        if filter.validate() {
+            session.validation_attempts = Some(mutator.validation_attempts());
+        }
+        mutants
+    } else if params.skip_validate {
+        log::info!("Skipping validation");
+        mutants
+    } else {
+        let mutants = mutator.get_valid_mutants(&mutants);
+        log::info!("Validation resulted in {} mutants", mutants.len());
+        mutants
+    };
+    // The mutants that were left unvalidated by the interruption are dropped
+    let cancelled = mutator.is_cancelled() && !params.skip_validate;
+    if cancelled {
+        mutants.retain(|m| m.validated);
+        log::info!("Interrupted: keeping {} validated mutants", mutants.len());
+    }
+    if !params.skip_validate {
+        *validation_time += validation_start.elapsed();
+        if params.validation_mode != ValidationMode::Full {
+            let stages = mutator.validation_stages();
+            log::info!(
+                "Syntax checks rejected {} of {} mutants in {} ms; compilation rejected {} of {} mutants in {} ms",
+                stages.rejected_by_syntax_check,
+                stages.syntax_checks,
+                stages.syntax_check_ms,
+                stages.rejected_by_compilation,
+                stages.compilations,
+                stages.compilation_ms
+            );
+            session.validation_stages = Some(stages);
+        }
+        let times = mutator.validation_times(mutator.mutants());
+        if !times.is_empty() {
+            let times = ValidationTimes::of(times);
+            let slowest = &times.slowest[0];
             log::info!(
-                "(pre filter/validate) Generated {} mutants for {}",
-                &mutants.len(),
-                params.filename.as_ref().unwrap()
+                "Slowest mutant to validate: {} at {}:{} (`{}` -> `{}`) took {} ms",
+                slowest.operator,
+                slowest.line,
+                slowest.col,
+                slowest.orig,
+                slowest.repl,
+                slowest.validation_ms
             );
+            session.validation_times = Some(times);
+        }
+    }
+    if let Some(num_mutants) = params
+        .num_mutants
+        .filter(|n| *n > mutants.len() && !cancelled)
+    {
+        report(
+            Diagnostic::new(
+                DiagnosticCode::FewerMutantsThanRequested,
+                format!(
+                    "Requested {} mutants but only {} {}mutants were found for {}; emitting all {}",
+                    num_mutants,
+                    mutants.len(),
+                    if params.skip_validate { "" } else { "valid " },
+                    params.filename.as_ref().unwrap(),
+                    mutants.len()
+                ),
+            )
+            .with_file(params.filename.as_ref().unwrap()),
+        );
+    }
 
-            /*                                                   *
-             *               FILTER/VALIDATE                     *
-             *               ===============                     */
-
-            // TODO: Separate out Filtering from Validation
-
-            // Check if we are filtering
-            let mutants = if let Some(num_mutants) = params.num_mutants {
-                log::info!("Filtering down to {} mutants", num_mutants);
-                log::debug!("  seed: {:?}", params.seed);
-                log::debug!("  validating?: {}", !params.skip_validate);
-                let seed = if params.random_seed {
-                    None
-                } else {
-                    Some(params.seed)
-                };
-                let filter = RandomDownSampleFilter::new(seed, !params.skip_validate);
-                let mutants = filter.filter_mutants(&mutator, num_mutants)?;
-                log::info!("Filtering resulted in {} mutants", mutants.len());
-                mutants
-            } else if params.skip_validate {
-                log::info!("Skipping validation");
-                mutants
-            } else {
-                let mutants = mutator.get_valid_mutants(&mutants);
-                log::info!("Validation resulted in {} mutants", mutants.len());
-                mutants
-            };
-            total_num_mutants += mutants.len();
-            log::info!("Adding {} mutants to global mutant pool", mutants.len());
+    // Vyper mutants are neither compiled nor validated with solc
+    #[cfg(feature = "vyper")]
+    if params.language == Language::Vyper {
+        return Ok((session, mutants.into_iter().map(|m| (m, export)).collect()));
+    }
+
+    let (solc_path, solc_version) = solc_info
+        .entry(params.solc.clone())
+        .or_insert_with(|| {
+            let path = resolve_executable(&params.solc)
+                .map(|p| p.to_str().unwrap().to_string())
+                .unwrap_or(params.solc.clone());
+            (path, mutator.solc().version())
+        })
+        .clone();
+
+    session.solc = Some(solc_path.clone());
+    session.solc_version = solc_version.clone();
+    let mutants = mutants
+        .into_iter()
+        .map(|mut m| {
+            m.solc = Some(solc_path.clone());
+            m.solc_version = solc_version.clone();
+            m.abi_changed = mutator.abi_changed(&m);
+            m.validation_ms = mutator
+                .validation_time(&m)
+                .map(|time| time.as_millis() as u64);
+            m.validation_error = mutator.validation_error(&m);
+            (m, export)
+        })
+        .collect();
+    Ok((session, mutants))
+}
+
+/// Find where the mutation operators of each parameter set apply, without
+/// generating any mutants (see `--locations_only`). Nothing is written to the
+/// output directories.
+pub fn run_locate(
+    mutate_params: Vec<MutateParams>,
+) -> Result<Vec<MutationCandidate>, Box<dyn std::error::Error>> {
+    log::info!("Running Gambit Mutate command (locations only)");
+    let mut candidates = vec![];
+    for params in mutate_params.iter() {
+        candidates.append(&mut Mutator::try_from(params)?.locate()?);
+    }
+    Ok(candidates)
+}
+
+/// Estimate how many mutants each mutation operator of each parameter set
+/// generates, without generating any mutants (see `--estimate`). The estimate
+/// of the files of each output directory is written to its `estimate.json`;
+/// nothing in the output directories is deleted.
+pub fn run_estimate(
+    mutate_params: Vec<MutateParams>,
+) -> Result<MutantEstimate, Box<dyn std::error::Error>> {
+    log::info!("Running Gambit Mutate command (estimate)");
+    let outdir = |p: &MutateParams| {
+        p.outdir
+            .clone()
+            .unwrap_or(default_gambit_output_directory())
+    };
+    // Output directories created here are Gambit's to delete later
+    for outdir in mutate_params.iter().map(outdir).collect::<BTreeSet<_>>() {
+        let outdir_path = PathBuf::from(outdir);
+        if !outdir_path.exists() {
+            fs::create_dir_all(&outdir_path)?;
+            fs::write(outdir_path.join(GAMBIT_MARKER_FILENAME), "")?;
+        }
+    }
+    let mut files = vec![];
+    let mut files_by_outdir: BTreeMap<String, Vec<FileEstimate>> = BTreeMap::new();
+    for params in mutate_params.iter() {
+        let estimates = Mutator::try_from(params)?.estimate()?;
+        files_by_outdir
+            .entry(outdir(params))
+            .or_default()
+            .extend(estimates.iter().cloned());
+        files.extend(estimates);
+    }
+    for (outdir, files) in files_by_outdir {
+        let estimate = MutantEstimate::new(files);
+        fs::write(
+            PathBuf::from(outdir).join(ESTIMATE_FILENAME),
+            serde_json::to_string_pretty(&estimate)?,
+        )?;
+    }
+    Ok(MutantEstimate::new(files))
+}
 
-            let mut mutants: Vec<(Mutant, bool)> =
-                mutants.iter().map(|m| (m.clone(), export)).collect();
+/// Explain how the mutation operators of each parameter set with an
+/// `--explain` location fare at that location (see `Mutator::explain()`).
+/// Nothing is validated or written to the output directories.
+pub fn run_explain(
+    mutate_params: Vec<MutateParams>,
+) -> Result<Vec<Explanation>, Box<dyn std::error::Error>> {
+    log::info!("Running Gambit Mutate command (explain)");
+    let mut explanations = vec![];
+    for params in mutate_params.iter() {
+        if let Some(location) = &params.explain {
+            let (line, col) = parse_line_col(location)?;
+            explanations.append(&mut Mutator::try_from(params)?.explain(line, col)?);
+        }
+    }
+    Ok(explanations)
+}
 
-            if !mutants_by_out_dir.contains_key(outdir) {
-                mutants_by_out_dir.insert(outdir.clone(), vec![]);
+/// Check each parameter set without generating any mutants (see `gambit
+/// check`): its mutation operators must be recognized, and its sources must
+/// compile and define the selected contract and functions (see
+/// `Mutator::check()`). Nothing is written to the output directories.
+pub fn run_check(
+    mutate_params: Vec<MutateParams>,
+) -> Result<CheckReport, Box<dyn std::error::Error>> {
+    log::info!("Running Gambit Check command");
+    let operator_names: Vec<String> = MutationType::value_variants()
+        .iter()
+        .map(|op| op.short_name())
+        .collect();
+    let mut checks = vec![];
+    for params in mutate_params.iter() {
+        let mut operators_known = true;
+        for spec in params.mutations.iter().flatten() {
+            let op = spec.name();
+            let check = format!("mutation operator `{}` is recognized", op);
+            if MutationType::from_str(op, true).is_ok() {
+                checks.push(ConfigurationCheck::pass(check));
+                if spec.replacements().is_some() {
+                    let check = format!("the replacements of mutation operator `{}` are legal", op);
+                    checks.push(
+                        match check_operator_replacements(std::slice::from_ref(spec)) {
+                            Ok(()) => ConfigurationCheck::pass(check),
+                            Err(e) => ConfigurationCheck::fail(check, e),
+                        },
+                    );
+                }
+                continue;
             }
-            let ms: &mut Vec<(Mutant, bool)> = mutants_by_out_dir.get_mut(outdir).unwrap();
-            ms.append(&mut mutants);
+            operators_known = false;
+            let failure = match suggest_name(op, &operator_names) {
+                Some(suggestion) => format!(
+                    "unknown mutation operator `{}`; did you mean `{}`?",
+                    op, suggestion
+                ),
+                None => format!("unknown mutation operator `{}`", op),
+            };
+            checks.push(ConfigurationCheck::fail(check, failure));
+        }
+        // A mutator cannot be built with unrecognized operators
+        if operators_known {
+            checks.append(&mut Mutator::try_from(params)?.check()?);
         }
     }
+    Ok(CheckReport { checks })
+}
 
-    let mut results: HashMap<String, Vec<Mutant>> = HashMap::default();
+/// A copy of `params` whose paths are canonicalized (those that exist), as
+/// recorded in the job manifest (see `JobManifest`)
+fn canonicalize_paths(params: &MutateParams) -> MutateParams {
+    let canonical = |path: &Option<String>| {
+        path.as_ref().map(|p| match fs::canonicalize(p) {
+            Ok(canonical) => canonical.to_str().unwrap().to_string(),
+            Err(_) => p.clone(),
+        })
+    };
+    MutateParams {
+        json: canonical(&params.json),
+        filename: canonical(&params.filename),
+        outdir: canonical(&params.outdir),
+        sourceroot: canonical(&params.sourceroot),
+        project_root: canonical(&params.project_root),
+        solc_base_path: canonical(&params.solc_base_path),
+        ..params.clone()
+    }
+}
 
-    /*                                                 *
-     *               WRITE MUTANTS                     *
-     *               =============                     */
-    for (outdir, mutants) in mutants_by_out_dir {
-        log::info!("Writing mutants for output directory {}", &outdir);
-        results.insert(
-            outdir.clone(),
-            mutants.iter().map(|(m, _)| m).cloned().collect(),
-        );
-        MutantWriter::new(outdir).write_mutants(&mutants)?;
+/// Parse a `LINE:COL` location (see `--explain`)
+pub fn parse_line_col(location: &str) -> Result<(usize, usize), GambitError> {
+    location
+        .split_once(':')
+        .and_then(|(line, col)| Some((line.trim().parse().ok()?, col.trim().parse().ok()?)))
+        .filter(|(line, col)| *line > 0 && *col > 0)
+        .ok_or_else(|| {
+            GambitError::Configuration(format!(
+                "Invalid --explain location `{}`: expected LINE:COL (both 1-indexed)",
+                location
+            ))
+        })
+}
+
+/// Make all mutants of a physical file refer to the same `Source`: the first
+/// one recorded for that file in `sources_by_path`. This way a file that is
+/// reachable under different sourceroots is exported, logged, and reported
+/// under a single path.
+pub fn unify_mutant_sources(
+    mutants: &mut [(Mutant, bool)],
+    sources_by_path: &mut HashMap<PathBuf, Rc<Source>>,
+) {
+    for (mutant, _) in mutants.iter_mut() {
+        let source = sources_by_path
+            .entry(mutant.source.physical_path().to_path_buf())
+            .or_insert_with(|| mutant.source.clone());
+        mutant.source = source.clone();
     }
+}
 
-    let t = start.elapsed().as_secs_f64();
-    log::info!("Generated {} mutants in {}", total_num_mutants, t);
-    Ok(results)
+/// Drop the mutants of a parameter set that other parameter sets for the same
+/// output directory account for, returning how many were dropped. A mutant
+/// of a file is attributed to the parameter set targeting the file (see
+/// `attributed_to()`), and otherwise to the first parameter set generating
+/// it: mutants of the same physical file, span, operator, and replacement as
+/// one of the `earlier` mutants are dropped. `target` is the file targeted by
+/// this parameter set, and `targets` are the files targeted by all of them.
+pub fn drop_cross_entry_duplicates(
+    mutants: &mut Vec<Mutant>,
+    earlier: &[(Mutant, bool)],
+    target: &Path,
+    targets: &HashSet<PathBuf>,
+) -> usize {
+    let key = |m: &Mutant| {
+        (
+            m.source.physical_path().to_path_buf(),
+            m.start,
+            m.end,
+            m.op,
+            m.repl.clone(),
+        )
+    };
+    let seen: HashSet<_> = earlier.iter().map(|(m, _)| key(m)).collect();
+    let generated = mutants.len();
+    mutants.retain(|m| {
+        attributed_to(m.source.physical_path(), target, targets) && !seen.contains(&key(m))
+    });
+    generated - mutants.len()
+}
+
+/// Are the mutants of the file at `path` generated by a parameter set
+/// targeting `target` kept? They are unless another parameter set, among
+/// those targeting `targets`, targets the file itself.
+fn attributed_to(path: &Path, target: &Path, targets: &HashSet<PathBuf>) -> bool {
+    path == target || !targets.contains(path)
+}
+
+/// The random stream that each sampled session drew its mutants from, keyed
+/// by the session's file relative to its sourceroot.
+pub fn sampling_streams(
+    sessions: &[MutationSession],
+) -> Result<BTreeMap<String, u64>, Box<dyn std::error::Error>> {
+    let mut streams = BTreeMap::new();
+    for session in sessions {
+        if let Some(stream) = session.sampling_stream {
+            streams.insert(
+                session.source.relative_filename()?.display().to_string(),
+                stream,
+            );
+        }
+    }
+    Ok(streams)
+}
+
+/// Find output directories that are targeted by parameters specifying
+/// different solc binaries. This returns each such output directory along with
+/// the solc binaries that target it.
+pub fn outdirs_with_mixed_solc(mutate_params: &[MutateParams]) -> Vec<(String, Vec<String>)> {
+    let mut solcs_by_outdir: HashMap<String, Vec<String>> = HashMap::new();
+    for params in mutate_params {
+        let solcs = solcs_by_outdir
+            .entry(
+                params
+                    .outdir
+                    .clone()
+                    .unwrap_or(default_gambit_output_directory()),
+            )
+            .or_default();
+        if !solcs.contains(&params.solc) {
+            solcs.push(params.solc.clone());
+        }
+    }
+    let mut mixed: Vec<(String, Vec<String>)> = solcs_by_outdir
+        .into_iter()
+        .filter(|(_, solcs)| solcs.len() > 1)
+        .collect();
+    mixed.sort();
+    mixed
 }
 
 pub fn run_summary(params: SummaryParams) -> Result<(), Box<dyn std::error::Error>> {
@@ -192,3 +1175,12 @@ pub fn run_summary(params: SummaryParams) -> Result<(), Box<dyn std::error::Erro
     log::debug!("Summary parameters: {:?}", params);
     summarize(params)
 }
+
+/// Validate a `gambit_results.json` file
+pub fn run_validate_results(
+    params: ValidateResultsParams,
+) -> Result<(), Box<dyn std::error::Error>> {
+    log::info!("Running Gambit Validate Results");
+    log::debug!("Validate results parameters: {:?}", params);
+    validate(params)
+}
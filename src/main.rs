@@ -1,16 +1,57 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Once,
+    },
+};
 
-use clap::Parser;
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches};
 use gambit::{
-    default_gambit_output_directory, normalize_path, repair_remapping, run_mutate, run_summary,
-    Command, MutateParams,
+    append_cli_values, check_experimental_operators, check_operator_replacements,
+    check_solc_settings, default_gambit_output_directory, diagnostic_code_for,
+    discover_configuration, exit_code_for, expand_mutation_operators, find_results_entry,
+    normalize_path, normalize_remappings, outdirs_with_mixed_solc, repair_remapping, report,
+    resolve_import_mapped_filename, run_check, run_estimate, run_explain, run_locate,
+    run_mutate_cancellable, run_summary, run_validate_results, set_message_format,
+    toml_configuration_to_json, validate_num_mutants, Cli, Command, Diagnostic, DiagnosticCode,
+    GambitError, MutateParams, MutationReport, MutationSpec, MutationType, SolcSettings,
+    EXIT_CONFIGURATION_ERROR, EXIT_INTERRUPTED, MUTATION_REPORT_FILENAME,
 };
 
-/// Entry point
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// Entry point. Failures are reported with the exit codes listed in
+/// `EXIT_CODES_HELP`.
+fn main() {
     let _ = env_logger::builder().try_init();
-    match Command::parse() {
-        Command::Mutate(mut params) => {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    set_message_format(cli.message_format);
+    if let Err(e) = run(cli.command, &given_arguments(&matches)) {
+        report(Diagnostic::new(diagnostic_code_for(e.as_ref()), &e));
+        std::process::exit(exit_code_for(e.as_ref()));
+    }
+}
+
+/// The ids of the arguments of a `mutate` or `check` command that are given on
+/// the command line rather than defaulted
+fn given_arguments(matches: &ArgMatches) -> Vec<String> {
+    match matches.subcommand() {
+        Some(("mutate" | "check", matches)) => matches
+            .ids()
+            .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+            .map(|id| id.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Run a Gambit command, where `given` are the ids of the arguments given on
+/// the command line (see `given_arguments`)
+fn run(command: Command, given: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    // `check` resolves its configuration exactly like `mutate`
+    let check = matches!(command, Command::Check(_));
+    match command {
+        Command::Mutate(mut params) | Command::Check(mut params) => {
             // The user has specified a configuration file.
             //
             // Configuration files have two forms: (1) a JSON array of JSON
@@ -22,10 +63,56 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             // To tell the difference we deserialzie as a `serde_json::Value`
             // and check if it's an array or an object and create a
             // `Vec<MutateParams>` based on this.
+            //
+            // Without a file to mutate, the configuration file of the project
+            // containing the current directory is used, if any. The default
+            // source root and output directory of a discovered configuration
+            // are relative to the configuration file rather than to the
+            // current directory, which may be anywhere in the project.
+            let mut discovered = false;
+            if params.json.is_none()
+                && params.filenames.is_empty()
+                && params.from_results.is_none()
+                && !params.no_discover
+            {
+                if let Some(config) = discover_configuration(Path::new(".")) {
+                    report(
+                        Diagnostic::new(
+                            DiagnosticCode::DiscoveredConfiguration,
+                            format!("Using discovered configuration {}", config.display()),
+                        )
+                        .with_file(config.display()),
+                    );
+                    params.json = Some(config.to_str().unwrap().to_string());
+                    discovered = true;
+                }
+            }
             if let Some(json_path) = &params.json {
                 log::info!("Running from configuration");
                 // Run from config file
-                let json_contents = std::fs::read_to_string(json_path)?;
+                let contents = std::fs::read_to_string(json_path).map_err(|e| {
+                    GambitError::FileResolution(format!(
+                        "Could not read configuration file `{}`: {}",
+                        json_path, e
+                    ))
+                })?;
+                // TOML configuration files are translated to JSON
+                let translated = Path::new(json_path)
+                    .extension()
+                    .is_some_and(|ext| ext == "toml");
+                let json_contents = if translated {
+                    toml_configuration_to_json(&contents).unwrap_or_else(|msg| {
+                        exit_with_configuration_error(
+                            Diagnostic::new(
+                                DiagnosticCode::ConfigurationError,
+                                format!("Invalid configuration file: {}", msg),
+                            )
+                            .with_file(json_path),
+                        )
+                    })
+                } else {
+                    contents
+                };
                 let json: serde_json::Value = serde_json::from_reader(json_contents.as_bytes())?;
                 log::info!("Read configuration json: {:#?}", json);
 
@@ -33,21 +120,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     match serde_json::from_str(&json_contents) {
                         Ok(xs) => xs,
                         Err(msg) => {
-                            println!("{}", &msg);
-                            std::process::exit(1);
+                            exit_with_configuration_error(invalid_configuration(
+                                json_path, msg, translated,
+                            ));
                         }
                     }
                 } else if json.is_object() {
                     let single_param: MutateParams = match serde_json::from_str(&json_contents) {
                         Ok(xs) => xs,
                         Err(msg) => {
-                            println!("{}", &msg);
-                            std::process::exit(1);
+                            exit_with_configuration_error(invalid_configuration(
+                                json_path, msg, translated,
+                            ));
                         }
                     };
                     vec![single_param]
                 } else {
-                    panic!("Invalid configuration file: must be an array or an object")
+                    return Err(Box::new(GambitError::Configuration(
+                        "Invalid configuration file: must be an array or an object".into(),
+                    )));
                 };
                 log::debug!("Deserialized JSON into MutateParams: {:#?}", &mutate_params);
 
@@ -108,9 +199,61 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 log::info!("Performing Path Resolution for Configurations");
                 log::info!("Found {} configurations", mutate_params.len());
 
+                // # Command Line Pass-Through
+                //
+                // The solc remappings and allow paths given on the command line
+                // are appended to those of each configuration, dropping
+                // duplicates, and the command line's include path is used by
+                // configurations without one. Paths given on the command line
+                // are relative to the current working directory.
+                // `--allow_experimental`, `--overwrite`, `--force`, and
+                // `--no_overwrite` on the command line apply to every
+                // configuration. The other arguments given on the command line
+                // override the values of each entry of a discovered
+                // configuration.
+                let overrides = if discovered {
+                    cli_overrides(&params, given)?
+                } else {
+                    serde_json::Map::new()
+                };
+                let cli_remappings: Vec<String> = params
+                    .solc_remappings
+                    .iter()
+                    .flatten()
+                    .map(|rm| repair_remapping(rm.as_str(), None))
+                    .collect();
+                let cli_allow_paths = match &params.solc_allow_paths {
+                    Some(allow_paths) => resolve_config_file_paths(allow_paths, Path::new("."))?,
+                    None => vec![],
+                };
+                let allow_experimental = params.allow_experimental;
+                let (overwrite, force, no_overwrite) =
+                    (params.overwrite, params.force, params.no_overwrite);
+                let cli_include_path = match &params.solc_include_path {
+                    Some(include_path) => Some(
+                        resolve_config_file_path(include_path, Path::new("."))?
+                            .to_str()
+                            .unwrap()
+                            .to_string(),
+                    ),
+                    None => None,
+                };
+
                 for (i, params) in mutate_params.iter_mut().enumerate() {
-                    // Source Root Resolution
                     log::info!("Configuration {}", i + 1);
+                    override_entry(params, &overrides)?;
+                    check_num_mutants(params.num_mutants);
+                    // `@file` operator lists are relative to the configuration
+                    params.mutations =
+                        resolve_mutations(params.mutations.take(), &json_parent_directory);
+                    params.solc_settings =
+                        resolve_solc_settings(params.solc_settings.take(), &json_parent_directory);
+                    check_experimental(params, params.allow_experimental || allow_experimental);
+                    params.overwrite |= overwrite;
+                    params.force |= force;
+                    params.no_overwrite |= no_overwrite;
+
+                    // Source Root Resolution
                     log::info!("    Performing Source Root Resolution");
                     let source_root_path: PathBuf = match params.sourceroot.clone() {
                         Some(sr) => {
@@ -129,6 +272,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             );
                             resolved_source_root_path
                         }
+                        None if discovered => {
+                            log::info!("    No sourceroot provided in discovered configration");
+                            log::info!(
+                                "    [->] Resolved sourceroot to configuration directory `{}`",
+                                json_parent_directory.display()
+                            );
+                            json_parent_directory.clone()
+                        }
                         None => {
                             let resolved_source_root_path = PathBuf::from(".").canonicalize()?;
                             log::info!("    No sourceroot provided in configration");
@@ -154,34 +305,58 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     // | solc_include_path | Yes               | No                    |
                     // | solc_base_path    | Yes               | No                    |
                     // | solc_remappings   | Yes               | No                    |
+                    // | project_root      | Yes               | No                    |
+                    // | copy_into_mutants | Yes               | Yes                   |
                     log::info!("    Performing Filename Resolution");
 
                     // PARAM: Filename
                     log::info!("    [.] Resolving params.filename");
                     let filename_path: PathBuf = match params.filename.clone() {
                         Some(filename) => {
-                            resolve_config_file_path(&filename, &json_parent_directory)?
+                            match resolve_config_file_path(&filename, &json_parent_directory) {
+                                Ok(path) => path,
+                                // The filename may be an import path, such
+                                // as `@openzeppelin/contracts/token/ERC20/ERC20.sol`
+                                Err(e) => resolve_import_mapped_filename(
+                                    &filename,
+                                    &append_cli_values(
+                                        params.solc_remappings.clone(),
+                                        &cli_remappings,
+                                    )
+                                    .unwrap_or_default(),
+                                    &search_dirs(&params.solc_base_path, &params.solc_include_path),
+                                    &json_parent_directory,
+                                )
+                                .ok_or(e)?,
+                            }
                         }
                         None => {
-                            log::error!("[!!] Found a configuration without a filename!");
-                            log::error!("[!!] Parameters: {:#?}", params);
-                            log::error!("[!!] Exiting.");
                             // TODO: Replace exit with an error
-                            std::process::exit(1);
+                            exit_with_configuration_error(
+                                Diagnostic::new(
+                                    DiagnosticCode::ConfigurationError,
+                                    "Found a configuration without a filename!",
+                                )
+                                .with_note(format!("Parameters: {:#?}", params)),
+                            );
                         }
                     };
                     let filename_string = filename_path.to_str().unwrap().to_string();
 
                     // Check that filename is a member of sourceroot
                     if !filename_path.starts_with(&source_root_path) {
-                        log::error!( "[!!] Illegal Configuration: Resolved filename `{}` is not prefixed by the derived sourceroot {}",
-                            &filename_string,
-                            &source_root_string,
-                        );
-                        log::error!("[!!] Parameters:\n{:#?}", params);
-                        log::error!("[!!] Exiting.");
                         // TODO: Replace exit with an error
-                        std::process::exit(1);
+                        exit_with_configuration_error(
+                            Diagnostic::new(
+                                DiagnosticCode::ConfigurationError,
+                                format!("Illegal Configuration: Resolved filename `{}` is not prefixed by the derived sourceroot {}",
+                                    &filename_string,
+                                    &source_root_string,
+                                ),
+                            )
+                            .with_file(&filename_string)
+                            .with_note(format!("Parameters:\n{:#?}", params)),
+                        );
                     }
                     log::info!(
                         "    [->] Resolved filename `{}` belongs to sourceroot `{}`",
@@ -203,6 +378,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 normalize_path(&json_parent_directory.join(&outdir_path))
                             }
                         }
+                        None if discovered => normalize_path(
+                            &json_parent_directory.join(default_gambit_output_directory()),
+                        ),
                         None => normalize_path(
                             &PathBuf::from(".").join(default_gambit_output_directory()),
                         ),
@@ -227,6 +405,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     } else {
                         None
                     };
+                    let allow_paths = append_cli_values(allow_paths, &cli_allow_paths);
 
                     // PARAM: solc_include_path
                     log::info!("    [.] Resolving params.include_path");
@@ -237,7 +416,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         )?)
                         .map(|ip| ip.to_str().unwrap().to_string())
                     } else {
-                        None
+                        cli_include_path.clone()
                     };
 
                     // PARAM: solc_base_path
@@ -263,6 +442,35 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 })
                                 .collect()
                         });
+                    let remapping = append_cli_values(remapping, &cli_remappings);
+                    let remapping = resolve_remappings(remapping, params.allow_remap_override)?;
+
+                    // PARAM: project_root
+                    log::info!("    [.] Resolving params.project_root");
+                    let project_root = if let Some(project_root) = &params.project_root {
+                        Some(resolve_config_file_path(
+                            project_root,
+                            &json_parent_directory,
+                        )?)
+                        .map(|pr| pr.to_str().unwrap().to_string())
+                    } else {
+                        None
+                    };
+                    check_project_root(
+                        params.export_full_project,
+                        &project_root,
+                        &source_root_path,
+                        &filename_path,
+                    );
+
+                    // PARAM: copy_into_mutants
+                    log::info!("    [.] Resolving params.copy_into_mutants");
+                    let copy_into_mutants = if let Some(sidecars) = &params.copy_into_mutants {
+                        Some(resolve_config_file_paths(sidecars, &json_parent_directory)?)
+                    } else {
+                        None
+                    };
+                    check_copy_into_mutants(&copy_into_mutants, &source_root_path);
 
                     // Finally, update params with resolved source root and filename.
                     // (We don't update earlier to preserve the state of params
@@ -276,8 +484,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     params.solc_include_path = include_path;
                     params.solc_base_path = basepath;
                     params.solc_remappings = remapping;
+                    params.project_root = project_root;
+                    params.copy_into_mutants = copy_into_mutants;
+                }
+
+                for (outdir, solcs) in outdirs_with_mixed_solc(&mutate_params) {
+                    report(Diagnostic::new(
+                        DiagnosticCode::MixedSolc,
+                        format!(
+                            "Output directory {} is targeted by configurations with different solc binaries: {}",
+                            outdir,
+                            solcs.join(", ")
+                        ),
+                    ));
+                }
+                if check {
+                    execute_check(mutate_params)?;
+                } else {
+                    execute_mutation(mutate_params)?;
                 }
-                execute_mutation(mutate_params)?;
             } else {
                 log::debug!("Running CLI MutateParams: {:#?}", &params);
                 // # Path Resolution for CLI Provided Parameters
@@ -323,6 +548,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 // parameter's filename is prefixed by (or belongs to) it's
                 // source root. If not, Gambit reports an error and exits.
 
+                check_num_mutants(params.num_mutants);
+                if let (Some(results_dir), Some(mid)) =
+                    (params.from_results.clone(), params.mid.clone())
+                {
+                    explain_results_entry(&mut params, results_dir, mid)?;
+                }
+                params.mutations = resolve_mutations(params.mutations.take(), Path::new("."));
+                params.solc_settings =
+                    resolve_solc_settings(params.solc_settings.take(), Path::new("."));
+                check_experimental(&params, params.allow_experimental);
+
                 // Source Root Resolution
                 log::info!("Performing Path Resolution for CLI");
                 log::info!("    Performing Source Root Resolution");
@@ -362,46 +598,73 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 // | solc_include_path | No                    |
                 // | solc_base_path    | No                    |
                 // | solc_remappings   | No                    |
+                // | project_root      | No                    |
+                // | copy_into_mutants | Yes                   |
                 log::info!("    Performing Filename Resolution");
 
-                log::info!("    [.] Resolving params.filename");
-                let filename_path: PathBuf = match params.filename.clone() {
-                    Some(filename) => {
-                        let raw_filename_path = PathBuf::from(&filename);
-                        let resolved_filename_path = raw_filename_path.canonicalize()?;
-                        log::info!(
-                            "    [->] Resolved filename `{}` to `{}`",
-                            &filename,
-                            resolved_filename_path.display()
+                log::info!("    [.] Resolving params.filenames");
+                if params.filenames.is_empty() {
+                    // TODO: Replace exit with an error
+                    exit_with_configuration_error(
+                        Diagnostic::new(
+                            DiagnosticCode::ConfigurationError,
+                            "Found a configuration without a filename!",
+                        )
+                        .with_note(format!("Parameters: {:#?}", params)),
+                    );
+                }
+                let mut filename_paths: Vec<PathBuf> = vec![];
+                for filename in params.filenames.iter() {
+                    let raw_filename_path = PathBuf::from(filename);
+                    let filename_path = match raw_filename_path.canonicalize() {
+                        Ok(path) => path,
+                        // The filename may be an import path, such as
+                        // `@openzeppelin/contracts/token/ERC20/ERC20.sol`
+                        Err(e) => resolve_import_mapped_filename(
+                            filename,
+                            params.solc_remappings.as_deref().unwrap_or_default(),
+                            &search_dirs(&params.solc_base_path, &params.solc_include_path),
+                            Path::new("."),
+                        )
+                        .ok_or_else(|| {
+                            GambitError::FileResolution(format!(
+                                "Could not resolve filename `{}`: {}",
+                                filename, e
+                            ))
+                        })?,
+                    };
+                    log::info!(
+                        "    [->] Resolved filename `{}` to `{}`",
+                        filename,
+                        filename_path.display()
+                    );
+                    let filename_string = filename_path.to_str().unwrap().to_string();
+
+                    // Check that filename is a member of sourceroot
+                    if !filename_path.starts_with(&source_root_path) {
+                        // TODO: Replace exit with an error
+                        exit_with_configuration_error(
+                            Diagnostic::new(
+                                DiagnosticCode::ConfigurationError,
+                                format!("Illegal Configuration: Resolved filename `{}` is not prefixed by the derived sourceroot {}",
+                                    &filename_string,
+                                    &source_root_string,
+                                ),
+                            )
+                            .with_file(&filename_string)
+                            .with_note(format!("Parameters:\n{:#?}", params)),
                         );
-                        resolved_filename_path
                     }
-                    None => {
-                        log::error!("[!!] Found a configuration without a filename!");
-                        log::error!("[!!] Parameters: {:#?}", params);
-                        log::error!("[!!] Exiting.");
-                        // TODO: Replace exit with an error
-                        std::process::exit(1);
+                    log::info!(
+                        "    [->] Resolved filename `{}` belongs to sourceroot `{}`",
+                        &filename_string,
+                        &source_root_string
+                    );
+                    // A file named more than once is only mutated once
+                    if !filename_paths.contains(&filename_path) {
+                        filename_paths.push(filename_path);
                     }
-                };
-                let filename_string = filename_path.to_str().unwrap().to_string();
-
-                // Check that filename is a member of sourceroot
-                if !filename_path.starts_with(&source_root_path) {
-                    log::error!( "[!!] Illegal Configuration: Resolved filename `{}` is not prefixed by the derived sourceroot {}",
-                            &filename_string,
-                            &source_root_string,
-                        );
-                    log::error!("[!!] Parameters:\n{:#?}", params);
-                    log::error!("[!!] Exiting.");
-                    // TODO: Replace exit with an error
-                    std::process::exit(1);
                 }
-                log::info!(
-                    "    [->] Resolved filename `{}` belongs to sourceroot `{}`",
-                    &filename_string,
-                    &source_root_string
-                );
 
                 log::info!("    [.] Resolving params.outdir {:?}", &params.outdir);
                 let outdir = normalize_path(&PathBuf::from(
@@ -412,38 +675,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .to_string();
 
                 log::info!("    [.] Resolving params.solc_allow_paths");
-                let solc_allowpaths = params.solc_allow_paths.map(|aps| {
-                    aps.iter()
-                        .map(|p| {
-                            PathBuf::from(p)
-                                .canonicalize()
-                                .unwrap()
-                                .to_str()
-                                .unwrap()
-                                .to_string()
-                        })
-                        .collect()
-                });
+                let solc_allowpaths = match &params.solc_allow_paths {
+                    Some(allow_paths) => {
+                        Some(resolve_config_file_paths(allow_paths, Path::new("."))?)
+                    }
+                    None => None,
+                };
 
                 log::info!("    [.] Resolving params.solc_include_path");
-                let solc_include_path = params.solc_include_path.map(|ip| {
-                    PathBuf::from(ip)
-                        .canonicalize()
-                        .unwrap()
-                        .to_str()
-                        .unwrap()
-                        .to_string()
-                });
+                let solc_include_path = match &params.solc_include_path {
+                    Some(include_path) => Some(
+                        resolve_config_file_path(include_path, Path::new("."))?
+                            .to_str()
+                            .unwrap()
+                            .to_string(),
+                    ),
+                    None => None,
+                };
 
                 log::info!("    [.] Resolving params.solc_base_path");
-                let solc_basepath = params.solc_base_path.map(|bp| {
-                    PathBuf::from(bp)
-                        .canonicalize()
-                        .unwrap()
-                        .to_str()
-                        .unwrap()
-                        .to_string()
-                });
+                let solc_basepath = match &params.solc_base_path {
+                    Some(base_path) => Some(
+                        resolve_config_file_path(base_path, Path::new("."))?
+                            .to_str()
+                            .unwrap()
+                            .to_string(),
+                    ),
+                    None => None,
+                };
 
                 log::info!("    [.] Resolving params.solc_remapping");
                 let solc_remapping = params.solc_remappings.as_ref().map(|rms| {
@@ -451,48 +710,464 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .map(|rm| repair_remapping(rm.as_str(), None))
                         .collect()
                 });
+                let solc_remapping =
+                    resolve_remappings(solc_remapping, params.allow_remap_override)?;
                 log::info!(
                     "    [->] Resolved solc_remapping:\n    {:#?} to \n    {:#?}",
                     &params.solc_remappings,
                     &solc_remapping
                 );
 
-                // Finally, update params with resolved source root and filename.
+                log::info!("    [.] Resolving params.project_root");
+                let project_root = match &params.project_root {
+                    Some(project_root) => Some(
+                        resolve_config_file_path(project_root, Path::new("."))?
+                            .to_str()
+                            .unwrap()
+                            .to_string(),
+                    ),
+                    None => None,
+                };
+                for filename_path in filename_paths.iter() {
+                    check_project_root(
+                        params.export_full_project,
+                        &project_root,
+                        &source_root_path,
+                        filename_path,
+                    );
+                }
+
+                log::info!("    [.] Resolving params.copy_into_mutants");
+                let copy_into_mutants = match &params.copy_into_mutants {
+                    Some(sidecars) => Some(resolve_config_file_paths(sidecars, Path::new("."))?),
+                    None => None,
+                };
+                check_copy_into_mutants(&copy_into_mutants, &source_root_path);
+
+                // Finally, update params with resolved source root and filenames.
                 // (We don't update earlier to preserve the state of params
                 // for error reporting: reporting the parsed in value of
                 // `params` will be more helpful to the end user than
                 // reporting the modified value of params).
                 params.sourceroot = Some(source_root_string);
-                params.filename = Some(filename_string);
+                params.filenames = vec![];
                 params.outdir = Some(outdir);
                 params.solc_allow_paths = solc_allowpaths;
                 params.solc_include_path = solc_include_path;
                 params.solc_base_path = solc_basepath;
                 params.solc_remappings = solc_remapping;
+                params.project_root = project_root;
+                params.copy_into_mutants = copy_into_mutants;
 
-                execute_mutation(vec![*params])?;
+                // Each file is mutated by its own parameter set. These all
+                // share an output directory, so their mutants are numbered
+                // consecutively and logged together.
+                let mutate_params: Vec<MutateParams> = filename_paths
+                    .iter()
+                    .map(|filename_path| MutateParams {
+                        filename: Some(filename_path.to_str().unwrap().to_string()),
+                        ..(*params).clone()
+                    })
+                    .collect();
+                if check {
+                    execute_check(mutate_params)?;
+                } else {
+                    execute_mutation(mutate_params)?;
+                }
             }
         }
         Command::Summary(params) => {
             run_summary(params)?;
         }
+        Command::ValidateResults(params) => {
+            run_validate_results(params)?;
+        }
     }
     Ok(())
 }
 
+/// The values of the arguments given on the command line (`given`) that
+/// override those of each entry of a discovered configuration. Their paths are
+/// made absolute, since they are relative to the current working directory
+/// rather than to the configuration file. Pass-through arguments are merged
+/// into each entry instead.
+fn cli_overrides(
+    params: &MutateParams,
+    given: &[String],
+) -> Result<serde_json::Map<String, serde_json::Value>, Box<dyn std::error::Error>> {
+    const PASS_THROUGH: [&str; 7] = [
+        "solc_remappings",
+        "solc_allow_paths",
+        "solc_include_path",
+        "allow_experimental",
+        "overwrite",
+        "force",
+        "no_overwrite",
+    ];
+    let cwd = std::env::current_dir()?;
+    let absolute = |path: &str| cwd.join(path).to_str().unwrap().to_string();
+    let mut params = params.clone();
+    params.outdir = params.outdir.as_deref().map(absolute);
+    params.sourceroot = params.sourceroot.as_deref().map(absolute);
+    params.project_root = params.project_root.as_deref().map(absolute);
+    params.solc_base_path = params.solc_base_path.as_deref().map(absolute);
+    params.copy_into_mutants = params
+        .copy_into_mutants
+        .map(|paths| paths.iter().map(|path| absolute(path)).collect());
+    params.solc_settings = match params.solc_settings {
+        Some(SolcSettings::File(path)) => Some(SolcSettings::File(absolute(&path))),
+        settings => settings,
+    };
+    params.mutations = params.mutations.map(|specs| {
+        specs
+            .into_iter()
+            .map(|spec| match spec {
+                MutationSpec::Name(name) if name.starts_with('@') => {
+                    MutationSpec::Name(format!("@{}", absolute(&name[1..])))
+                }
+                spec => spec,
+            })
+            .collect()
+    });
+    let mut values = match serde_json::to_value(&params)? {
+        serde_json::Value::Object(values) => values,
+        _ => unreachable!("MutateParams serializes to an object"),
+    };
+    values.retain(|id, _| given.contains(id) && !PASS_THROUGH.contains(&id.as_str()));
+    Ok(values)
+}
+
+/// Override the values of a configuration entry with `overrides` (see
+/// `cli_overrides`)
+fn override_entry(
+    params: &mut MutateParams,
+    overrides: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if overrides.is_empty() {
+        return Ok(());
+    }
+    let mut entry = serde_json::to_value(&*params)?;
+    if let Some(entry) = entry.as_object_mut() {
+        entry.extend(overrides.clone());
+    }
+    *params = serde_json::from_value(entry)?;
+    Ok(())
+}
+
+/// Report a configuration error and exit
+fn exit_with_configuration_error(diagnostic: Diagnostic) -> ! {
+    report(diagnostic);
+    std::process::exit(EXIT_CONFIGURATION_ERROR);
+}
+
+/// The diagnostic of a configuration file at `path` that could not be
+/// deserialized. Errors in a translated TOML configuration have no span, which
+/// would point into the translation.
+fn invalid_configuration(path: &str, msg: serde_json::Error, translated: bool) -> Diagnostic {
+    let diagnostic = Diagnostic::new(
+        DiagnosticCode::ConfigurationError,
+        format!("Invalid configuration file: {}", msg),
+    )
+    .with_file(path);
+    if translated {
+        diagnostic
+    } else {
+        diagnostic.with_span(msg.line(), msg.column())
+    }
+}
+
+/// Exit with an error if the requested number of mutants is invalid
+fn check_num_mutants(num_mutants: Option<usize>) {
+    if let Err(e) = validate_num_mutants(num_mutants) {
+        exit_with_configuration_error(Diagnostic::new(
+            DiagnosticCode::ConfigurationError,
+            format!("Illegal Configuration: {}", e),
+        ));
+    }
+}
+
+/// Expand `@file` references in the mutation operators, exiting if a file
+/// cannot be read or lists an unrecognized operator
+fn resolve_mutations(
+    mutations: Option<Vec<MutationSpec>>,
+    base: &Path,
+) -> Option<Vec<MutationSpec>> {
+    let mutations = mutations?;
+    let mut resolved = vec![];
+    for spec in mutations {
+        let expanded = match spec {
+            MutationSpec::Name(name) => expand_mutation_operators(&[name], base)
+                .map(|names| names.into_iter().map(MutationSpec::Name).collect()),
+            detailed => check_operator_replacements(std::slice::from_ref(&detailed))
+                .map(|()| vec![detailed]),
+        };
+        match expanded {
+            Ok(mut specs) => resolved.append(&mut specs),
+            Err(e) => {
+                exit_with_configuration_error(Diagnostic::new(
+                    DiagnosticCode::ConfigurationError,
+                    format!("Illegal Configuration: {}", e),
+                ));
+            }
+        }
+    }
+    Some(resolved)
+}
+
+/// Read solc settings from their file, which is relative to `base`, exiting if
+/// it cannot be read, and warn about settings that are not passed to solc
+fn resolve_solc_settings(settings: Option<SolcSettings>, base: &Path) -> Option<SolcSettings> {
+    let settings = match settings?.load(base) {
+        Ok(settings) => settings,
+        Err(e) => exit_with_configuration_error(Diagnostic::new(
+            DiagnosticCode::ConfigurationError,
+            format!("Illegal Configuration: {}", e),
+        )),
+    };
+    for warning in check_solc_settings(&settings).1 {
+        report(Diagnostic::new(DiagnosticCode::IgnoredSolcSetting, warning));
+    }
+    Some(SolcSettings::Inline(settings))
+}
+
+/// Exit if `params` requests experimental operators without
+/// `allow_experimental`, and warn (once per run) if they are allowed
+fn check_experimental(params: &MutateParams, allow_experimental: bool) {
+    static WARNING: Once = Once::new();
+    let ops: Vec<String> = params
+        .mutations
+        .iter()
+        .flatten()
+        .map(|spec| spec.name().to_string())
+        .collect();
+    match check_experimental_operators(&ops, allow_experimental) {
+        Ok(ops) if !ops.is_empty() => WARNING.call_once(|| {
+            report(Diagnostic::new(
+                DiagnosticCode::ExperimentalOperators,
+                "experimental mutation operators are enabled; their behavior may change between releases",
+            ));
+        }),
+        Ok(_) => {}
+        Err(e) => exit_with_configuration_error(Diagnostic::new(
+            DiagnosticCode::ConfigurationError,
+            format!("Illegal Configuration: {}", e),
+        )),
+    }
+}
+
+/// Point `params` at the location of mutant `mid` of a previous run whose
+/// output directory is `results_dir`, explaining it with the operator that
+/// generated it (in addition to the selected operators)
+fn explain_results_entry(
+    params: &mut MutateParams,
+    results_dir: String,
+    mid: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let entry = find_results_entry(Path::new(&results_dir), &mid)?;
+    let filename = Path::new(&entry.sourceroot).join(&entry.original);
+    params.filenames = vec![filename.to_str().unwrap().to_string()];
+    params.sourceroot = Some(entry.sourceroot);
+    params.explain = Some(format!("{}:{}", entry.line, entry.col));
+    if let Some(op) = MutationType::from_name(&entry.description) {
+        let mut mutations = params.mutations.take().unwrap_or_else(|| {
+            MutationType::default_mutation_operators()
+                .iter()
+                .map(|op| MutationSpec::Name(op.short_name()))
+                .collect()
+        });
+        if !mutations.iter().any(|spec| spec.name() == op.short_name()) {
+            mutations.push(MutationSpec::Name(op.short_name()));
+        }
+        params.mutations = Some(mutations);
+    }
+    Ok(())
+}
+
+/// When exporting full project overlays, check that `filename` belongs to the
+/// resolved project root (the sourceroot if no project root was given), and
+/// exit otherwise
+fn check_project_root(
+    export_full_project: bool,
+    project_root: &Option<String>,
+    source_root: &Path,
+    filename: &Path,
+) {
+    if !export_full_project {
+        return;
+    }
+    let project_root = project_root
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or(source_root.to_path_buf());
+    if !filename.starts_with(&project_root) {
+        exit_with_configuration_error(
+            Diagnostic::new(
+                DiagnosticCode::ConfigurationError,
+                format!(
+                    "Illegal Configuration: Resolved filename `{}` is not prefixed by the project root {}",
+                    filename.display(),
+                    project_root.display(),
+                ),
+            )
+            .with_file(filename.display()),
+        );
+    }
+}
+
+/// Check that the files copied into each mutant directory belong to the
+/// sourceroot, since they are placed at their paths relative to it
+fn check_copy_into_mutants(copy_into_mutants: &Option<Vec<String>>, source_root: &Path) {
+    for sidecar in copy_into_mutants.iter().flatten() {
+        if !Path::new(sidecar).starts_with(source_root) {
+            exit_with_configuration_error(
+                Diagnostic::new(
+                    DiagnosticCode::ConfigurationError,
+                    format!(
+                        "Illegal Configuration: copy_into_mutants path `{}` is not prefixed by the sourceroot {}",
+                        sidecar,
+                        source_root.display(),
+                    ),
+                )
+                .with_file(sidecar),
+            );
+        }
+    }
+}
+
+/// Check a configuration and print a report of its checks, failing if any
+/// check failed
+fn execute_check(params: Vec<MutateParams>) -> Result<(), Box<dyn std::error::Error>> {
+    let report = run_check(params)?;
+    print!("{}", report.report());
+    if report.passed() {
+        return Ok(());
+    }
+    let failed = report.checks.iter().filter(|c| !c.passed()).count();
+    Err(Box::new(GambitError::Configuration(format!(
+        "{} of {} checks failed",
+        failed,
+        report.checks.len()
+    ))))
+}
+
 /// Execute mutation
 fn execute_mutation(params: Vec<MutateParams>) -> Result<(), Box<dyn std::error::Error>> {
+    if params.iter().any(|p| p.locations_only) {
+        let candidates = run_locate(params)?;
+        println!("{}", serde_json::to_string(&candidates)?);
+        return Ok(());
+    }
+    if params.iter().any(|p| p.estimate) {
+        print!("{}", run_estimate(params)?.table());
+        return Ok(());
+    }
+    if params.iter().any(|p| p.explain.is_some()) {
+        for explanation in run_explain(params)? {
+            println!("{}", explanation);
+        }
+        return Ok(());
+    }
+    let fail_on_zero_mutants = params.iter().any(|p| p.fail_on_zero_mutants);
+    let validation_budget = params
+        .iter()
+        .any(|p| p.max_validation_seconds.is_some() && !p.skip_validate);
+    // Without `--skip_validate`, the only mutants that are not validated are
+    // those passed through by the budget or because solc could not be run
+    let all_validated = params.iter().all(|p| !p.skip_validate);
+    // Fewer mutants than requested are generated when too few valid mutants
+    // exist: the summary reports the shortfall
+    let num_requested: Option<usize> = params.iter().map(|p| p.num_mutants).sum();
     let start = std::time::Instant::now();
-    let result = run_mutate(params)?;
+    let result = run_mutate_cancellable(params, interrupt_flag())?;
     let t = start.elapsed().as_secs_f64();
     let total_num_mutants = result.values().flatten().count();
-    println!(
-        "Generated {} mutants in {:.2} seconds",
-        total_num_mutants, t
-    );
+    match num_requested.filter(|n| *n > total_num_mutants) {
+        Some(n) => println!(
+            "Generated {} mutants ({} requested) in {:.2} seconds",
+            total_num_mutants, n, t
+        ),
+        None => println!(
+            "Generated {} mutants in {:.2} seconds",
+            total_num_mutants, t
+        ),
+    }
+    let mut num_over_budget = 0;
+    if validation_budget {
+        let num_validated = result.values().flatten().filter(|m| m.validated).count();
+        println!(
+            "Validated {} mutants and passed {} through unvalidated",
+            num_validated,
+            total_num_mutants - num_validated
+        );
+        if all_validated {
+            num_over_budget = result
+                .values()
+                .flatten()
+                .filter(|m| !m.validated && !m.validation_error)
+                .count();
+        }
+    }
+    // Break the mutants down by function when more than one was mutated
+    let mut outdirs: Vec<&String> = result.keys().collect();
+    outdirs.sort();
+    for outdir in outdirs {
+        let report = std::fs::read_to_string(Path::new(outdir).join(MUTATION_REPORT_FILENAME))
+            .ok()
+            .and_then(|json| serde_json::from_str::<MutationReport>(&json).ok());
+        if let Some(report) = report.filter(|r| r.num_functions() > 1) {
+            print!("{}", report.table());
+        }
+    }
+    if total_num_mutants == 0 && fail_on_zero_mutants {
+        return Err(Box::new(GambitError::NoMutants));
+    }
+    if num_over_budget > 0 {
+        return Err(Box::new(GambitError::BudgetExhausted(format!(
+            "exported {} mutants unvalidated (see --max_validation_seconds)",
+            num_over_budget
+        ))));
+    }
 
     Ok(())
 }
+
+/// A flag that is raised on the first Ctrl-C, so that the run stops
+/// validating and writes the mutants validated so far. The second Ctrl-C
+/// exits immediately.
+fn interrupt_flag() -> Arc<AtomicBool> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = interrupted.clone();
+    let handler = ctrlc::set_handler(move || {
+        if flag.swap(true, Ordering::SeqCst) {
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+        eprintln!("Interrupted: writing the mutants validated so far (press Ctrl-C again to exit immediately)");
+    });
+    if let Err(e) = handler {
+        log::warn!("Could not install the Ctrl-C handler: {}", e);
+    }
+    interrupted
+}
+
+/// Check resolved remappings for conflicting duplicate prefixes. The resulting
+/// remappings are used both to compile the original AST and to validate
+/// mutants, so both always see the same remappings.
+fn resolve_remappings(
+    remappings: Option<Vec<String>>,
+    allow_override: bool,
+) -> Result<Option<Vec<String>>, GambitError> {
+    remappings
+        .map(|remappings| {
+            normalize_remappings(&remappings, allow_override).map_err(|e| {
+                GambitError::Configuration(format!(
+                    "{} (use `--allow_remap_override` to use the last remapping of a prefix)",
+                    e
+                ))
+            })
+        })
+        .transpose()
+}
+
 /// Resolve a filename with respect to the directory containing the config file
 fn resolve_config_file_path(
     path: &String,
@@ -500,10 +1175,13 @@ fn resolve_config_file_path(
 ) -> Result<PathBuf, Box<dyn std::error::Error>> {
     let path = PathBuf::from(&path);
     let result = if path.is_absolute() {
-        path.canonicalize()?
+        path.canonicalize()
     } else {
-        json_parent_directory.join(&path).canonicalize()?
-    };
+        json_parent_directory.join(&path).canonicalize()
+    }
+    .map_err(|e| {
+        GambitError::FileResolution(format!("Could not resolve `{}`: {}", path.display(), e))
+    })?;
     log::info!(
         "    [->] Resolved path `{}` to `{}`",
         path.display(),
@@ -527,3 +1205,9 @@ fn resolve_config_file_paths(
     }
     Ok(result)
 }
+
+/// The directories that solc resolves imports against: the base path and
+/// the include path
+fn search_dirs(base_path: &Option<String>, include_path: &Option<String>) -> Vec<String> {
+    base_path.iter().chain(include_path).cloned().collect()
+}
@@ -1,41 +1,623 @@
-use crate::Mutant;
+use crate::{
+    mutant_index, rel_path_from_base, report, AstContext, ContractReport, Diagnostic,
+    DiagnosticCode, FileAstContext, FileReport, FileStatistics, FunctionReport, GambitResults,
+    HookFailure, JobManifest, Mutant, MutantEntry, MutantJob, MutateParams, MutatedUnit,
+    MutationReport, PostExportHook, ResultsFile, RunManifest, Source, ValidationStages,
+    ValidationTimes, AST_CONTEXT_FILENAME, ESTIMATE_FILENAME, INDEX_FILENAME,
+    JOB_MANIFEST_FILENAME, MANIFEST_FILENAME, MUTATION_REPORT_FILENAME, RESULTS_FORMAT_VERSION,
+};
+use clap::ValueEnum;
 use csv::Writer;
-use serde_json::Value::Array;
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use similar::TextDiff;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::error;
 use std::fs;
+use std::io::{self, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The subdirectory of the output directory that mutants are exported to,
+/// unless another one is given with `--mutants_subdir`
+pub const DEFAULT_MUTANTS_SUBDIR: &str = "mutants";
+
+/// The name of the (empty) marker file that Gambit writes into the output
+/// directories that it creates and their mutants subdirectories, so that it
+/// never deletes a directory of another tool
+pub const GAMBIT_MARKER_FILENAME: &str = ".gambit";
+
+/// Check that `name` can name the mutants subdirectory (see
+/// `--mutants_subdir`): a single component of a relative path
+pub fn check_mutants_subdir_name(name: &str) -> Result<(), String> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(std::path::Component::Normal(_)), None) => Ok(()),
+        _ => Err(format!(
+            "invalid mutants subdirectory `{}`: expected the name of a directory",
+            name
+        )),
+    }
+}
+
+/// Check that the mutants subdirectory `mutants_subdir` of `outdir` can be
+/// deleted: it does not exist, or Gambit wrote it, as its marker file (or,
+/// for output directories of earlier versions, the `gambit_results.json`
+/// next to it) shows. Otherwise, returns why it cannot be deleted.
+pub fn check_mutants_subdir(outdir: &Path, mutants_subdir: &str) -> Result<(), String> {
+    let dir = outdir.join(mutants_subdir);
+    if !dir.exists() && !dir.is_symlink() {
+        return Ok(());
+    }
+    if dir.join(GAMBIT_MARKER_FILENAME).is_file()
+        || (dir.is_dir() && outdir.join("gambit_results.json").is_file())
+    {
+        return Ok(());
+    }
+    Err(not_written_by_gambit(&dir))
+}
+
+/// Why the directory `dir` cannot be deleted if it lacks a marker file
+fn not_written_by_gambit(dir: &Path) -> String {
+    format!(
+        "{} was not written by Gambit (it has no `{}` marker file)",
+        dir.display(),
+        GAMBIT_MARKER_FILENAME
+    )
+}
+
+/// Check that the existing output directory `outdir` can be deleted and
+/// recreated: it is neither the current directory nor one of its ancestors,
+/// nor the root of a repository; it is empty or was written by Gambit, as its
+/// marker file (or, for output directories of earlier versions, its
+/// `gambit_results.json`) shows; and its mutants subdirectory
+/// `mutants_subdir` can be deleted (see `check_mutants_subdir()`).
+/// Otherwise, returns why it cannot be deleted.
+pub fn check_outdir_deletion(outdir: &Path, mutants_subdir: &str) -> Result<(), String> {
+    let canonical = outdir.canonicalize().map_err(|e| e.to_string())?;
+    let cwd = std::env::current_dir().and_then(|cwd| cwd.canonicalize());
+    if cwd.is_ok_and(|cwd| cwd.starts_with(&canonical)) {
+        return Err(format!(
+            "{} contains the current working directory",
+            outdir.display()
+        ));
+    }
+    if let Some(vcs) = [".git", ".hg", ".svn"]
+        .iter()
+        .find(|vcs| canonical.join(vcs).exists())
+    {
+        return Err(format!(
+            "{} is the root of a repository (it has a `{}` directory)",
+            outdir.display(),
+            vcs
+        ));
+    }
+    // Besides empty directories, directories that only hold the solc output
+    // of `--locations_only` and `--explain`, which don't mark the output
+    // directory, are deleted
+    let written_by_gambit = canonical.join(GAMBIT_MARKER_FILENAME).is_file()
+        || canonical.join("gambit_results.json").is_file()
+        || fs::read_dir(&canonical).is_ok_and(|mut entries| {
+            entries.all(|e| e.is_ok_and(|e| e.file_name() == "input_json"))
+        });
+    if !written_by_gambit {
+        return Err(not_written_by_gambit(outdir));
+    }
+    check_mutants_subdir(outdir, mutants_subdir)
+}
+
+/// The files and directories that Gambit writes into an output directory,
+/// besides its mutants subdirectory
+pub const GAMBIT_OUTPUT_NAMES: [&str; 11] = [
+    GAMBIT_MARKER_FILENAME,
+    "gambit_results.json",
+    MANIFEST_FILENAME,
+    JOB_MANIFEST_FILENAME,
+    INDEX_FILENAME,
+    MUTATION_REPORT_FILENAME,
+    AST_CONTEXT_FILENAME,
+    ESTIMATE_FILENAME,
+    "mutants.log",
+    "diffs",
+    "input_json",
+];
+
+/// Split the entries of the output directory `outdir` into the outputs of
+/// earlier Gambit runs and the entries that Gambit did not write, each sorted
+/// by name. Gambit's outputs are the `GAMBIT_OUTPUT_NAMES`, the mutants
+/// subdirectory `mutants_subdir` if it can be deleted (see
+/// `check_mutants_subdir()`), and any other subdirectory with a marker file
+/// (e.g., the mutants subdirectory of a run with another `--mutants_subdir`).
+pub fn outdir_entries(
+    outdir: &Path,
+    mutants_subdir: &str,
+) -> io::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(outdir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<io::Result<_>>()?;
+    entries.sort();
+    Ok(entries.into_iter().partition(|entry| {
+        let name = entry.file_name().unwrap_or_default();
+        GAMBIT_OUTPUT_NAMES.iter().any(|n| name == *n)
+            || (name == mutants_subdir && check_mutants_subdir(outdir, mutants_subdir).is_ok())
+            || (entry.is_dir() && entry.join(GAMBIT_MARKER_FILENAME).is_file())
+    }))
+}
+
+/// How mutants are exported (see `--export_format`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Write the whole mutated file
+    #[default]
+    Full,
+    /// Write a unified diff of the original and mutated file
+    Patch,
+}
+
+/// How exported mutants are laid out in the output directory (see
+/// `--outdir_layout`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutdirLayout {
+    /// Write each mutant to its own directory, `mutants/<id>/<path>`
+    #[default]
+    Nested,
+    /// Write the mutants of each contract to `mutants-<contract>/mutant_<id>.sol`
+    Flat,
+}
+
+/// The prefix of the directories that group the mutants of each contract in
+/// the flat layout (see `OutdirLayout::Flat`)
+pub const FLAT_MUTANTS_DIR_PREFIX: &str = "mutants-";
 
 /// This struct is responsible for logging and exporting mutants
 pub struct MutantWriter {
     /// The output directory to write mutants to
     outdir: PathBuf,
+
+    /// If this is `Some(root)`, export each mutant as an overlay of the
+    /// project rooted at `root` (see `write_mutant_overlay()`)
+    project_root: Option<PathBuf>,
+
+    /// Files and directories that are copied into each exported mutant
+    /// directory, at their paths relative to the mutant's sourceroot (see
+    /// `with_sidecars()`)
+    sidecars: Vec<PathBuf>,
+
+    /// Symlink the sidecars instead of copying them
+    link_sidecars: bool,
+
+    /// Whether mutants are exported as whole files or as patches
+    export_format: ExportFormat,
+
+    /// Identify mutants by a hash of their contents instead of by their
+    /// position (see `mutant_ids()`)
+    stable_ids: bool,
+
+    /// Diffs longer than this many bytes are written to their own file in
+    /// the `diffs` directory instead of `gambit_results.json`
+    max_inline_diff_bytes: Option<usize>,
+
+    /// Write the diffs in the `diffs` directory gzip-compressed (see
+    /// `with_compress_diffs()`)
+    compress_diffs: bool,
+
+    /// A command run on each exported mutant (see `with_post_export_hook()`)
+    post_export_hook: Option<PostExportHook>,
+
+    /// The post-export hook invocations that failed so far
+    hook_failures: RefCell<Vec<HookFailure>>,
+
+    /// The subdirectory of `outdir` that mutants are exported to
+    mutants_subdir: String,
+
+    /// Delete an existing mutants subdirectory even if Gambit did not write
+    /// it (see `check_mutants_subdir()`)
+    force: bool,
+
+    /// If this is `Some(configurations)`, write a job manifest recording
+    /// `configurations` (see `--emit_manifest`)
+    job_configurations: Option<Vec<MutateParams>>,
+
+    /// How the exported mutants are laid out (see `--outdir_layout`)
+    layout: OutdirLayout,
 }
 
 impl MutantWriter {
-    pub fn new(outdir: String) -> MutantWriter {
+    pub fn new(outdir: String, project_root: Option<PathBuf>) -> MutantWriter {
         MutantWriter {
             outdir: PathBuf::from(outdir),
+            project_root,
+            sidecars: vec![],
+            link_sidecars: false,
+            export_format: ExportFormat::default(),
+            stable_ids: false,
+            max_inline_diff_bytes: None,
+            compress_diffs: false,
+            post_export_hook: None,
+            hook_failures: RefCell::new(vec![]),
+            mutants_subdir: DEFAULT_MUTANTS_SUBDIR.into(),
+            force: false,
+            job_configurations: None,
+            layout: OutdirLayout::default(),
         }
     }
 
+    /// Lay out the exported mutants according to `layout`
+    pub fn with_layout(mut self, layout: OutdirLayout) -> MutantWriter {
+        self.layout = layout;
+        self
+    }
+
+    /// Write a job manifest, `manifest.json`, that records `configurations`
+    /// along with the mutants (see `JobManifest`)
+    pub fn with_job_manifest(mut self, configurations: Option<Vec<MutateParams>>) -> MutantWriter {
+        self.job_configurations = configurations;
+        self
+    }
+
+    /// Export mutants to the `mutants_subdir` subdirectory of the output
+    /// directory (see `--mutants_subdir`)
+    pub fn with_mutants_subdir(mut self, mutants_subdir: String) -> MutantWriter {
+        self.mutants_subdir = mutants_subdir;
+        self
+    }
+
+    /// Delete an existing mutants subdirectory that Gambit did not write
+    /// instead of failing (see `--force`)
+    pub fn with_force(mut self, force: bool) -> MutantWriter {
+        self.force = force;
+        self
+    }
+
+    /// Copy (or, if `link` is set, symlink) `sidecars` into each exported
+    /// mutant directory
+    pub fn with_sidecars(mut self, sidecars: Vec<PathBuf>, link: bool) -> MutantWriter {
+        self.sidecars = sidecars;
+        self.link_sidecars = link;
+        self
+    }
+
+    /// Export mutants in `export_format`. With `ExportFormat::Patch`, neither
+    /// project overlays nor sidecars are written: the project root only
+    /// determines the paths in the patches
+    pub fn with_export_format(mut self, export_format: ExportFormat) -> MutantWriter {
+        self.export_format = export_format;
+        self
+    }
+
+    /// Identify mutants by `stable_mutant_id()` instead of by their 1-based
+    /// position (see `--stable_ids`)
+    pub fn with_stable_ids(mut self, stable_ids: bool) -> MutantWriter {
+        self.stable_ids = stable_ids;
+        self
+    }
+
+    /// Store diffs longer than `max_inline_diff_bytes` in their own files
+    /// (`diffs/<mid>.diff`), referenced by the `diff_file` of their entry in
+    /// `gambit_results.json`
+    pub fn with_max_inline_diff_bytes(
+        mut self,
+        max_inline_diff_bytes: Option<usize>,
+    ) -> MutantWriter {
+        self.max_inline_diff_bytes = max_inline_diff_bytes;
+        self
+    }
+
+    /// Store diffs gzip-compressed in their own files (`diffs/<mid>.diff.gz`)
+    /// instead of in `gambit_results.json`. With a maximum inline diff size,
+    /// only the diffs longer than that are stored in files
+    pub fn with_compress_diffs(mut self, compress_diffs: bool) -> MutantWriter {
+        self.compress_diffs = compress_diffs;
+        self
+    }
+
+    /// Run `hook` on each mutant after it is exported. Failed invocations are
+    /// collected in `hook_failures()` rather than ending the export
+    pub fn with_post_export_hook(mut self, hook: Option<PostExportHook>) -> MutantWriter {
+        self.post_export_hook = hook;
+        self
+    }
+
+    /// The post-export hook invocations that failed so far, in the order of
+    /// their mutants
+    pub fn hook_failures(&self) -> Vec<HookFailure> {
+        self.hook_failures.borrow().clone()
+    }
+
     /// Write and log mutants based on `self`'s parameters
     pub fn write_mutants(&self, mutants: &[(Mutant, bool)]) -> Result<(), Box<dyn error::Error>> {
-        let mutants_dir = self.outdir.join("mutants");
+        self.write_mutants_after(mutants, 0)
+    }
+
+    /// Like `write_mutants_after()`, with no information about the mutation
+    /// sessions that generated the mutants: the `files` of
+    /// `gambit_results.json` are inferred from the mutants (see
+    /// `GambitResults::from_entries()`)
+    pub fn write_mutants_after(
+        &self,
+        mutants: &[(Mutant, bool)],
+        num_exported: usize,
+    ) -> Result<(), Box<dyn error::Error>> {
+        self.write_sessions_after(mutants, &[], num_exported)
+    }
 
-        if mutants_dir.is_file() {
-            fs::remove_file(mutants_dir.clone())?;
-        } else if mutants_dir.is_dir() {
-            fs::remove_dir_all(mutants_dir.clone())?;
+    /// Write and log mutants, assuming that the first `num_exported` mutants
+    /// were already exported by an earlier call with the same leading
+    /// mutants: only the remaining mutants are exported, while all mutants are
+    /// logged. This lets mutants be written incrementally as they are
+    /// generated.
+    ///
+    /// `sessions` describe the mutation sessions that generated the mutants,
+    /// which are reported as the `files` of `gambit_results.json`.
+    pub fn write_sessions_after(
+        &self,
+        mutants: &[(Mutant, bool)],
+        sessions: &[MutationSession],
+        num_exported: usize,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let mutants_dir = self.outdir.join(&self.mutants_subdir);
+
+        if num_exported == 0 {
+            if !self.force {
+                check_mutants_subdir(&self.outdir, &self.mutants_subdir)
+                    .map_err(MutantWriterError::ForeignMutantsSubdir)?;
+            }
+            if mutants_dir.is_file() || mutants_dir.is_symlink() {
+                fs::remove_file(mutants_dir.clone())?;
+            } else if mutants_dir.is_dir() {
+                fs::remove_dir_all(mutants_dir.clone())?;
+            }
+            if self.layout == OutdirLayout::Flat {
+                self.remove_flat_mutants_dirs()?;
+            }
         }
 
-        for (i, (mutant, export)) in mutants.iter().enumerate() {
+        let ids = self.mutant_ids(mutants)?;
+        let flat_paths = self.flat_mutant_paths(mutants, &ids, sessions)?;
+        // The number of bytes of mutant files exported by this call
+        let mut bytes_written = 0;
+        // The indices and files of the mutants exported by this call
+        let mut exported: Vec<(usize, PathBuf)> = vec![];
+        for (i, (mutant, export)) in mutants.iter().enumerate().skip(num_exported) {
             if *export {
-                let mid = i + 1;
-                Self::write_mutant_with_id_to_disk(&mutants_dir, mid, mutant)?;
+                let mid = ids[i].as_str();
+                let export = match &flat_paths {
+                    Some(paths) => {
+                        self.write_flat_mutant(&self.outdir.join(&paths[i]), mid, mutant)
+                    }
+                    None => self.export_mutant(&mutants_dir, mid, mutant),
+                }
+                .and_then(|filename| Ok((fs::metadata(&filename)?.len(), filename)));
+                match export {
+                    Ok((bytes, filename)) => {
+                        bytes_written += bytes;
+                        exported.push((i, filename));
+                    }
+                    Err(e) => {
+                        // Roll back the partially exported mutant, and log
+                        // exactly the mutants before it, which are all on disk
+                        let mutant_dir = mutants_dir.join(mid);
+                        if mutant_dir.is_dir() {
+                            fs::remove_dir_all(&mutant_dir)?;
+                        }
+                        if let Some(paths) = &flat_paths {
+                            let filename = self.outdir.join(&paths[i]);
+                            if filename.is_file() {
+                                fs::remove_file(filename)?;
+                            }
+                        }
+                        self.write_logs(&mutants[..i], &ids[..i], sessions)?;
+                        return Err(Box::new(MutantWriterError::ExportFailed {
+                            mid: mid.to_string(),
+                            filename: mutant.source.filename().display().to_string(),
+                            mutant_bytes: mutant.as_source_string().map_or(0, |s| s.len()),
+                            bytes_written,
+                            cause: e.to_string(),
+                        }));
+                    }
+                }
+            }
+        }
+        if mutants_dir.is_dir() {
+            fs::write(mutants_dir.join(GAMBIT_MARKER_FILENAME), "")?;
+        }
+        for path in flat_paths.iter().flatten() {
+            let dir = self.outdir.join(path.parent().unwrap());
+            if dir.is_dir() {
+                fs::write(dir.join(GAMBIT_MARKER_FILENAME), "")?;
+            }
+        }
+        self.write_logs(mutants, &ids, sessions)?;
+        if let Some(hook) = &self.post_export_hook {
+            let mut jobs = vec![];
+            for (i, filename) in exported {
+                let mutant = &mutants[i].0;
+                let mutant_dir = match self.layout {
+                    OutdirLayout::Nested => mutants_dir.join(&ids[i]),
+                    OutdirLayout::Flat => filename.parent().unwrap().to_path_buf(),
+                };
+                jobs.push(hook.job(
+                    &ids[i],
+                    mutant_dir,
+                    &filename.canonicalize()?,
+                    &mutant.op.to_string(),
+                    &mutant.source.relative_filename()?,
+                ));
+            }
+            self.hook_failures.borrow_mut().extend(hook.run(&jobs));
+        }
+        Ok(())
+    }
+
+    /// The ids of `mutants`: their 1-based positions, or, with stable ids,
+    /// their `stable_mutant_id()`s. Mutants whose stable ids collide are
+    /// told apart by a `-2`, `-3`, ... suffix in the order they were
+    /// generated, so writing more mutants never changes the ids of the
+    /// mutants before them.
+    fn mutant_ids(&self, mutants: &[(Mutant, bool)]) -> Result<Vec<String>, Box<dyn error::Error>> {
+        if !self.stable_ids {
+            return Ok((1..=mutants.len()).map(|mid| mid.to_string()).collect());
+        }
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut ids = vec![];
+        for (mutant, _) in mutants {
+            let id = stable_mutant_id(mutant)?;
+            let count = seen.entry(id.clone()).or_default();
+            *count += 1;
+            ids.push(match *count {
+                1 => id,
+                n => format!("{}-{}", id, n),
+            });
+        }
+        Ok(ids)
+    }
+
+    /// With the flat layout, the path of each of `mutants`, with ids `ids`,
+    /// relative to the output directory: `mutants-<contract>/mutant_<id>.sol`
+    /// (with the extension of the mutated file, and `.patch` appended when
+    /// exporting patches). A mutant belongs to the contract of `sessions` whose
+    /// bounds contain its start, like in the mutation report, and to the stem
+    /// of its file's name otherwise (e.g., for free functions). Contracts of the
+    /// same name in different files are told apart by a `-2`, `-3`, ...
+    /// suffix, in the order of their first mutant, so that writing more
+    /// mutants never moves the mutants before them. Returns `None` with the
+    /// nested layout.
+    fn flat_mutant_paths(
+        &self,
+        mutants: &[(Mutant, bool)],
+        ids: &[String],
+        sessions: &[MutationSession],
+    ) -> Result<Option<Vec<PathBuf>>, Box<dyn error::Error>> {
+        if self.layout != OutdirLayout::Flat {
+            return Ok(None);
+        }
+        let contracts: Vec<(&Path, &String, (usize, usize))> = sessions
+            .iter()
+            .flat_map(|s| s.units.iter())
+            .flat_map(|unit| {
+                unit.contracts
+                    .iter()
+                    .map(|(name, bounds)| (unit.source.physical_path(), name, *bounds))
+            })
+            .collect();
+        // The files whose mutants were grouped under each name so far
+        let mut files: HashMap<String, Vec<&Path>> = HashMap::new();
+        let mut paths = vec![];
+        for ((mutant, _), mid) in mutants.iter().zip(ids) {
+            let file = mutant.source.physical_path();
+            let name = contracts
+                .iter()
+                .find(|(path, _, (start, end))| {
+                    *path == file && *start <= mutant.start && mutant.start < *end
+                })
+                .map(|(_, name, _)| name.to_string())
+                .unwrap_or_else(|| {
+                    let stem = mutant.source.filename().file_stem().unwrap_or_default();
+                    stem.to_string_lossy().into_owned()
+                });
+            let group = files.entry(name.clone()).or_default();
+            let n = match group.iter().position(|f| *f == file) {
+                Some(n) => n,
+                None => {
+                    group.push(file);
+                    group.len() - 1
+                }
+            };
+            let dir = match n {
+                0 => format!("{}{}", FLAT_MUTANTS_DIR_PREFIX, name),
+                n => format!("{}{}-{}", FLAT_MUTANTS_DIR_PREFIX, name, n + 1),
+            };
+            let extension = mutant.source.filename().extension().unwrap_or_default();
+            let mut filename = format!("mutant_{}.{}", mid, extension.to_string_lossy());
+            if self.export_format == ExportFormat::Patch {
+                filename.push_str(".patch");
+            }
+            paths.push(PathBuf::from(dir).join(filename));
+        }
+        Ok(Some(paths))
+    }
+
+    /// Delete the directories of the flat layout (see `flat_mutant_paths()`)
+    /// written by an earlier run, which carry a marker file
+    fn remove_flat_mutants_dirs(&self) -> Result<(), Box<dyn error::Error>> {
+        if !self.outdir.is_dir() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(&self.outdir)? {
+            let path = entry?.path();
+            let flat = path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with(FLAT_MUTANTS_DIR_PREFIX));
+            if flat && path.is_dir() && path.join(GAMBIT_MARKER_FILENAME).is_file() {
+                fs::remove_dir_all(&path)?;
             }
         }
+        Ok(())
+    }
+
+    /// Export a single mutant to `filename` in the flat layout: the mutated
+    /// file, or a patch of the original file (see `write_mutant_patch()`)
+    fn write_flat_mutant(
+        &self,
+        filename: &Path,
+        mid: &str,
+        mutant: &Mutant,
+    ) -> Result<PathBuf, Box<dyn error::Error>> {
+        let contents = match self.export_format {
+            ExportFormat::Full => mutant.as_source_string()?,
+            ExportFormat::Patch => {
+                let path = self.patch_path(mutant)?;
+                Self::diff_mutant_with_header(mutant, &format!("a/{path}"), &format!("b/{path}"))?
+            }
+        };
+        log::info!(
+            "Writing mutant (mid={}) {:?} to {}",
+            mid,
+            mutant,
+            &filename.display()
+        );
+        fs::create_dir_all(filename.parent().unwrap())?;
+        fs::write(filename, contents)?;
+        Ok(filename.to_path_buf())
+    }
+
+    /// Export a single mutant to `mutants_dir/mid/`, returning the path of the
+    /// exported mutant file
+    fn export_mutant(
+        &self,
+        mutants_dir: &Path,
+        mid: &str,
+        mutant: &Mutant,
+    ) -> Result<PathBuf, Box<dyn error::Error>> {
+        if self.export_format == ExportFormat::Patch {
+            return self.write_mutant_patch(mutants_dir, mid, mutant);
+        }
+        let filename = match &self.project_root {
+            Some(root) => self.write_mutant_overlay(mutants_dir, mid, mutant, root)?,
+            None => {
+                let filename = Self::write_mutant_with_id_to_disk(mutants_dir, mid, mutant)?;
+                self.write_sidecars(&mutants_dir.join(mid), mutant)?;
+                filename
+            }
+        };
+        Ok(filename)
+    }
 
+    /// Write `mutants.log`, `gambit_results.json`, and the mutant index
+    /// (`index.json`) for `mutants`, with ids `ids`, which were generated by
+    /// `sessions` (if any)
+    fn write_logs(
+        &self,
+        mutants: &[(Mutant, bool)],
+        ids: &[String],
+        sessions: &[MutationSession],
+    ) -> Result<(), Box<dyn error::Error>> {
         // Log format:
         // 1. Mutant ID
         // 2. Operator
@@ -43,51 +625,280 @@ impl MutantWriter {
         // 4. line:column
         // 5. Initial
         // 6. To
+        // 7. `validated`, `unvalidated`, or `validation-error`
+        // 8. With the flat layout, the path of the exported mutant, which
+        //    cannot be told from its id
 
         // LOG MUTANTS
         let mutants_log = self.outdir.join("mutants.log");
         let mut w = Writer::from_path(mutants_log)?;
+        let flat_paths = self.flat_mutant_paths(mutants, ids, sessions)?;
 
-        for (i, (mutant, _)) in mutants.iter().enumerate() {
-            let mid = i + 1;
-            let (lineno, colno) = mutant.get_line_column()?;
+        for (i, ((mutant, _), mid)) in mutants.iter().zip(ids).enumerate() {
+            let (lineno, colno) = Self::mutant_location(mid, mutant);
             let line_col = format!("{}:{}", lineno, colno);
-            w.write_record([
-                mid.to_string().as_str(),
-                mutant.op.to_string().as_str(),
-                mutant.source.relative_filename()?.to_str().unwrap(),
+            let op = mutant.op.to_string();
+            let file = mutant.source.relative_filename()?;
+            let mut record = vec![
+                mid.as_str(),
+                op.as_str(),
+                file.to_str().unwrap(),
                 line_col.as_str(),
                 mutant.orig.as_str(),
                 mutant.repl.as_str(),
-            ])?;
-        }
-
-        let mut diffs: Vec<String> = vec![];
-        for (mutant, _) in mutants {
-            diffs.push(Self::diff_mutant(mutant)?);
+                if mutant.validated {
+                    "validated"
+                } else if mutant.validation_error {
+                    "validation-error"
+                } else {
+                    "unvalidated"
+                },
+            ];
+            if let Some(paths) = &flat_paths {
+                record.push(paths[i].to_str().unwrap());
+            }
+            w.write_record(record)?;
         }
 
+        let groups = span_groups(mutants);
         let gambit_results_json = self.outdir.join("gambit_results.json");
         log::info!(
             "Writing gambit_results.json to {}",
             &gambit_results_json.display()
         );
-        let mut json: Vec<serde_json::Value> = Vec::new();
-        for (i, ((mutant, _), diff)) in mutants.iter().zip(diffs).enumerate() {
-            let mid = i + 1;
+        // The entries are collected without their diffs, which are only
+        // computed as each entry is written
+        let mut entries: Vec<MutantEntry> = Vec::new();
+        let mutants_subdir = PathBuf::from(&self.mutants_subdir);
+        for (i, ((mutant, _), mid)) in mutants.iter().zip(ids).enumerate() {
             let sourceroot = mutant.source.sourceroot().to_str().unwrap().to_string();
-            json.push(serde_json::json!({
-                "name": Self::get_mutant_filename(&PathBuf::from("mutants"), mid, mutant),
-                "description": mutant.op.to_string(),
-                "id": mid.to_string(),
-                "diff": diff,
-                "sourceroot": sourceroot,
-                "original": mutant.source.relative_filename()?,
-            }));
-        }
-
-        let json_string = serde_json::to_string_pretty(&Array(json)).unwrap();
-        fs::write(gambit_results_json, json_string)?;
+            let name = match (&flat_paths, &self.project_root, self.export_format) {
+                (Some(paths), _, _) => paths[i].clone(),
+                (None, _, ExportFormat::Patch) => {
+                    self.get_mutant_patch_filename(&mutants_subdir, mid, mutant)?
+                }
+                (None, Some(root), ExportFormat::Full) => mutants_subdir
+                    .join(mid)
+                    .join(rel_path_from_base(mutant.source.filename(), root)?),
+                (None, None, ExportFormat::Full) => {
+                    Self::get_mutant_filename(&mutants_subdir, mid, mutant)
+                }
+            };
+            let (line, col) = Self::mutant_location(mid, mutant);
+            let entry = MutantEntry {
+                format_version: RESULTS_FORMAT_VERSION,
+                id: mid.clone(),
+                name: name.to_str().unwrap().to_string(),
+                description: mutant.op.to_string(),
+                diff: String::new(),
+                diff_file: None,
+                sourceroot,
+                original: mutant
+                    .source
+                    .relative_filename()?
+                    .to_str()
+                    .unwrap()
+                    .to_string(),
+                orig: mutant.orig.clone(),
+                repl: mutant.repl.clone(),
+                edits: mutant.edits.clone(),
+                line,
+                col,
+                span_group: Some(groups[i]),
+                line_shifts: mutant.line_shifts().unwrap_or_default(),
+                comment_inserted: mutant.insert_comment,
+                mutability: mutant.mutability.clone(),
+                solc: mutant.solc.clone(),
+                solc_version: mutant.solc_version.clone(),
+                validated: mutant.validated,
+                validation_error: mutant.validation_error,
+                abi_changed: mutant.abi_changed,
+                validation_ms: mutant.validation_ms,
+                language: mutant.language.clone(),
+                target_file: mutant.target_file.clone(),
+                defining_contract: mutant.defining_contract.clone(),
+                filter_contract: mutant.filter_contract.clone(),
+                user_defined_operator: mutant.user_defined_operator.clone(),
+                ast_context: mutant.ast_context.clone(),
+            };
+            entries.push(entry);
+        }
+
+        let results = if sessions.is_empty() {
+            GambitResults::from_entries(RESULTS_FORMAT_VERSION, entries)
+        } else {
+            let mut files = vec![];
+            for session in sessions.iter() {
+                // Only the mutants that are written, if the sessions describe
+                // more mutants
+                let ids = session.mutants.start.min(entries.len())
+                    ..session.mutants.end.min(entries.len());
+                files.push(ResultsFile {
+                    original: session
+                        .source
+                        .relative_filename()?
+                        .to_str()
+                        .unwrap()
+                        .to_string(),
+                    sourceroot: session.source.sourceroot().to_str().unwrap().to_string(),
+                    operators: session.operators.clone(),
+                    functions: session.functions.clone(),
+                    contract: session.contract.clone(),
+                    solc: session.solc.clone(),
+                    solc_version: session.solc_version.clone(),
+                    statistics: FileStatistics {
+                        requested: session.requested_mutants,
+                        validation_attempts: session.validation_attempts,
+                        validation_stages: session.validation_stages.clone(),
+                        validation_times: session.validation_times.clone(),
+                        ..FileStatistics::of(&entries[ids.clone()])
+                    },
+                    mutant_ids: entries[ids].iter().map(|e| e.id.clone()).collect(),
+                });
+            }
+            GambitResults {
+                format_version: RESULTS_FORMAT_VERSION,
+                files,
+                mutants: entries,
+            }
+        };
+        fs::write(
+            self.outdir.join(INDEX_FILENAME),
+            serde_json::to_string_pretty(&mutant_index(&results.mutants))?,
+        )?;
+        if !sessions.is_empty() {
+            fs::write(
+                self.outdir.join(MUTATION_REPORT_FILENAME),
+                serde_json::to_string_pretty(&mutation_report(mutants, sessions)?)?,
+            )?;
+        }
+        if let Some(configurations) = &self.job_configurations {
+            let outdir = self.outdir.canonicalize()?;
+            let jobs = results
+                .mutants
+                .iter()
+                .zip(mutants)
+                .map(|(entry, (mutant, export))| MutantJob {
+                    id: entry.id.clone(),
+                    mutant: export.then(|| outdir.join(&entry.name).to_str().unwrap().to_string()),
+                    original: entry.original.clone(),
+                    original_path: mutant.source.physical_path().to_str().unwrap().to_string(),
+                    operator: entry.description.clone(),
+                    line: entry.line,
+                    col: entry.col,
+                })
+                .collect();
+            let manifest = JobManifest {
+                gambit_version: env!("CARGO_PKG_VERSION").to_string(),
+                export_format: self.export_format,
+                configurations: configurations.clone(),
+                jobs,
+            };
+            fs::write(
+                self.outdir.join(JOB_MANIFEST_FILENAME),
+                serde_json::to_string_pretty(&manifest)?,
+            )?;
+        }
+        if sessions.iter().any(|s| !s.ast_contexts.is_empty()) {
+            let context =
+                AstContext::new(sessions.iter().flat_map(|s| s.ast_contexts.iter().cloned()));
+            fs::write(
+                self.outdir.join(AST_CONTEXT_FILENAME),
+                serde_json::to_string_pretty(&context)?,
+            )?;
+        }
+
+        let diffs_dir = self.outdir.join("diffs");
+        if diffs_dir.is_dir() {
+            fs::remove_dir_all(&diffs_dir)?;
+        }
+
+        // Write the results entry by entry, computing and exporting the diff
+        // of each mutant only when its entry is serialized. Serializing
+        // through a `Value` sorts the keys.
+        let format_version = results.format_version;
+        let files = serde_json::to_value(&results.files)?;
+        let entries = results.mutants.into_iter().zip(mutants).enumerate().map(
+            |(i, (mut entry, (mutant, _)))| -> Result<Value, Box<dyn error::Error>> {
+                let mid = &ids[i];
+                let diff = Self::diff_mutant(mutant).unwrap_or_else(|e| {
+                    report(
+                        Diagnostic::new(
+                            DiagnosticCode::DiffFailed,
+                            format!("Couldn't compute the diff of mutant {}: {}", mid, e),
+                        )
+                        .with_file(mutant.source.filename().display()),
+                    );
+                    String::new()
+                });
+                let inline = match self.max_inline_diff_bytes {
+                    Some(max) => diff.len() <= max,
+                    None => !self.compress_diffs,
+                };
+                if inline {
+                    entry.diff = diff;
+                } else {
+                    fs::create_dir_all(&diffs_dir)?;
+                    let diff_file = if self.compress_diffs {
+                        let diff_file = PathBuf::from("diffs").join(format!("{}.diff.gz", mid));
+                        let file = fs::File::create(self.outdir.join(&diff_file))?;
+                        let mut gz = GzEncoder::new(file, Compression::default());
+                        gz.write_all(diff.as_bytes())?;
+                        gz.finish()?;
+                        diff_file
+                    } else {
+                        let diff_file = PathBuf::from("diffs").join(format!("{}.diff", mid));
+                        fs::write(self.outdir.join(&diff_file), diff)?;
+                        diff_file
+                    };
+                    entry.diff_file = Some(diff_file.to_str().unwrap().to_string());
+                }
+                Ok(serde_json::to_value(entry)?)
+            },
+        );
+        let mut w = io::BufWriter::new(fs::File::create(gambit_results_json)?);
+        serde_json::to_writer_pretty(
+            &mut w,
+            &StreamedResults {
+                files,
+                format_version,
+                mutants: EntryStream(RefCell::new(Some(entries))),
+            },
+        )?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// The line and column of mutant `mid`. If these can't be computed, this
+    /// warns and returns the sentinel `(0, 0)` instead: a single bad location
+    /// must not lose the logs of a whole run.
+    fn mutant_location(mid: &str, mutant: &Mutant) -> (usize, usize) {
+        mutant.get_line_column().unwrap_or_else(|e| {
+            report(
+                Diagnostic::new(
+                    DiagnosticCode::UnlocatableMutant,
+                    format!(
+                        "Couldn't locate mutant {} of {} (recorded as 0:0): {}",
+                        mid,
+                        mutant.source.filename().display(),
+                        e
+                    ),
+                )
+                .with_file(mutant.source.filename().display()),
+            );
+            (0, 0)
+        })
+    }
+
+    /// Write the run manifest to `gambit_manifest.json` in the output directory
+    pub fn write_manifest(&self, manifest: &RunManifest) -> Result<(), Box<dyn error::Error>> {
+        let manifest_json = self.outdir.join(MANIFEST_FILENAME);
+        log::info!(
+            "Writing {} to {}",
+            MANIFEST_FILENAME,
+            manifest_json.display()
+        );
+        fs::write(manifest_json, serde_json::to_string_pretty(manifest)?)?;
         Ok(())
     }
 
@@ -156,7 +967,7 @@ impl MutantWriter {
     /// Return the path to the exported mutant file
     pub fn write_mutant_with_id_to_disk(
         mutants_dir: &Path,
-        mid: usize,
+        mid: &str,
         mutant: &Mutant,
     ) -> Result<PathBuf, Box<dyn error::Error>> {
         let filename = Self::get_mutant_filename(mutants_dir, mid, mutant);
@@ -175,11 +986,181 @@ impl MutantWriter {
         Ok(filename)
     }
 
+    /// Write a mutant to `mutants_dir/mid/` as a unified diff of the original
+    /// file, at the mutated file's path with a `.patch` extension. The diff's
+    /// `a/` and `b/` paths are relative to the project root (or, without one,
+    /// the sourceroot), so that `git apply` applies it from there.
+    ///
+    /// Return the path to the exported patch
+    pub fn write_mutant_patch(
+        &self,
+        mutants_dir: &Path,
+        mid: &str,
+        mutant: &Mutant,
+    ) -> Result<PathBuf, Box<dyn error::Error>> {
+        let filename = self.get_mutant_patch_filename(mutants_dir, mid, mutant)?;
+        let path = self.patch_path(mutant)?;
+        let patch =
+            Self::diff_mutant_with_header(mutant, &format!("a/{path}"), &format!("b/{path}"))?;
+
+        log::info!(
+            "Writing mutant (mid={}) {:?} to {}",
+            mid,
+            mutant,
+            &filename.display()
+        );
+
+        fs::create_dir_all(filename.parent().unwrap())?;
+        fs::write(filename.as_path(), patch)?;
+
+        Ok(filename)
+    }
+
+    /// The path of the mutated file in a patch: relative to the project root,
+    /// if there is one, and to the sourceroot otherwise. Paths always use `/`
+    fn patch_path(&self, mutant: &Mutant) -> Result<String, Box<dyn error::Error>> {
+        let path = match &self.project_root {
+            Some(root) => rel_path_from_base(
+                &mutant.source.filename().canonicalize()?,
+                &root.canonicalize()?,
+            )?,
+            None => mutant.source.relative_filename()?,
+        };
+        let components: Vec<String> = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        Ok(components.join("/"))
+    }
+
+    /// Get the filename where a mutant is exported to as a patch: the path of
+    /// the mutated file in the patch, with a `.patch` extension, in
+    /// `mutants_dir/mid/`
+    fn get_mutant_patch_filename(
+        &self,
+        mutants_dir: &Path,
+        mid: &str,
+        mutant: &Mutant,
+    ) -> Result<PathBuf, Box<dyn error::Error>> {
+        let mut filename = mutants_dir
+            .join(mid)
+            .join(self.patch_path(mutant)?)
+            .into_os_string();
+        filename.push(".patch");
+        Ok(PathBuf::from(filename))
+    }
+
+    /// Write a mutant to `mutants_dir/mid/` as an overlay of the project rooted
+    /// at `project_root`.
+    ///
+    /// The overlay mirrors `project_root`: each entry is symlinked (or copied
+    /// on platforms without symlink support), except that the mutated file is
+    /// written with the mutant's contents at its path relative to
+    /// `project_root`, and the output directory is left out when it lives
+    /// inside the project. This makes `mutants_dir/mid/` usable as a build
+    /// root.
+    ///
+    /// Return the path to the exported mutant file
+    pub fn write_mutant_overlay(
+        &self,
+        mutants_dir: &Path,
+        mid: &str,
+        mutant: &Mutant,
+        project_root: &Path,
+    ) -> Result<PathBuf, Box<dyn error::Error>> {
+        let mutated = &mutant.source.filename().canonicalize()?;
+        let project_root = &project_root.canonicalize()?;
+        if !mutated.starts_with(project_root) {
+            return Err(Box::new(MutantWriterError::NotInProjectRoot(
+                mutated.display().to_string(),
+                project_root.display().to_string(),
+            )));
+        }
+        let overlay_dir = mutants_dir.join(mid);
+        let filename = overlay_dir.join(rel_path_from_base(mutated, project_root)?);
+        let mutant_contents = mutant.as_source_string()?;
+
+        log::info!(
+            "Writing mutant (mid={}) {:?} to project overlay {}",
+            mid,
+            mutant,
+            &overlay_dir.display()
+        );
+
+        let outdir = self.outdir.canonicalize()?;
+        Self::write_overlay_dir(
+            project_root,
+            &overlay_dir,
+            mutated,
+            &mutant_contents,
+            &outdir,
+        )?;
+
+        Ok(filename)
+    }
+
+    /// Mirror `src_dir` into `dst_dir`. Directories on the path to `mutated`
+    /// or `outdir` are recreated and recursively mirrored, `mutated` is
+    /// written with `contents`, `outdir` is skipped, and all other entries
+    /// are linked.
+    fn write_overlay_dir(
+        src_dir: &Path,
+        dst_dir: &Path,
+        mutated: &Path,
+        contents: &str,
+        outdir: &Path,
+    ) -> Result<(), Box<dyn error::Error>> {
+        fs::create_dir_all(dst_dir)?;
+        for entry in fs::read_dir(src_dir)? {
+            let path = entry?.path();
+            let dst = dst_dir.join(path.file_name().unwrap());
+            if path == outdir {
+                continue;
+            } else if path == mutated {
+                fs::write(&dst, contents)?;
+            } else if mutated.starts_with(&path) || outdir.starts_with(&path) {
+                Self::write_overlay_dir(&path, &dst, mutated, contents, outdir)?;
+            } else {
+                link_or_copy(&path, &dst)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy (or link) the sidecars into `mutant_dir`, at their paths relative
+    /// to the mutant's sourceroot. Sidecars may not contain the mutated file,
+    /// since the exported mutant would otherwise be overwritten (or, when
+    /// linking, written through to the original).
+    fn write_sidecars(
+        &self,
+        mutant_dir: &Path,
+        mutant: &Mutant,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let mutated = mutant.source.filename().canonicalize()?;
+        for sidecar in self.sidecars.iter() {
+            if mutated.starts_with(sidecar.canonicalize()?) {
+                return Err(Box::new(MutantWriterError::SidecarContainsMutant(
+                    sidecar.display().to_string(),
+                    mutated.display().to_string(),
+                )));
+            }
+            let dst = mutant_dir.join(rel_path_from_base(sidecar, mutant.source.sourceroot())?);
+            log::debug!("Writing sidecar {} to {}", sidecar.display(), dst.display());
+            fs::create_dir_all(dst.parent().unwrap())?;
+            if self.link_sidecars {
+                link_or_copy(sidecar, &dst)?;
+            } else {
+                copy_recursively(sidecar, &dst)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Get the filename where a Mutant will be exported to.
     ///
     /// This is computed from the relative path of the original sourcefile, relative to
     /// the specified `sourceroot`, and is computed with `Source.relative_filename()`
-    fn get_mutant_filename(mutants_dir: &Path, mid: usize, mutant: &Mutant) -> PathBuf {
+    fn get_mutant_filename(mutants_dir: &Path, mid: &str, mutant: &Mutant) -> PathBuf {
         let rel_filename = match mutant.source.relative_filename() {
             Ok(rel_fn) => rel_fn,
             Err(e) => panic!(
@@ -187,30 +1168,375 @@ impl MutantWriter {
                 &mutant.source, e
             ),
         };
-        mutants_dir
-            .join(Path::new(&mid.to_string()))
-            .join(rel_filename)
+        mutants_dir.join(mid).join(rel_filename)
     }
 
     /// Get the diff of the mutant and the original file
-    fn diff_mutant(mutant: &Mutant) -> Result<String, Box<dyn error::Error>> {
+    pub fn diff_mutant(mutant: &Mutant) -> Result<String, Box<dyn error::Error>> {
+        Self::diff_mutant_with_header(mutant, "original", "mutant")
+    }
+
+    /// Get the diff of the mutant and the original file, naming the original
+    /// file `old` and the mutant `new` in the `---`/`+++` header
+    fn diff_mutant_with_header(
+        mutant: &Mutant,
+        old: &str,
+        new: &str,
+    ) -> Result<String, Box<dyn error::Error>> {
         let orig_contents: String = String::from_utf8_lossy(mutant.source.contents()).into();
-        let mutant_contents = mutant.as_source_string().unwrap();
+        let mutant_contents = mutant.as_source_string()?;
 
         let diff = TextDiff::from_lines(&orig_contents, &mutant_contents)
             .unified_diff()
-            .header("original", "mutant")
+            .header(old, new)
             .to_string();
 
         Ok(diff)
     }
 }
 
+/// The mutants that one configuration entry generated for a single file,
+/// which are reported as one of the `files` of `gambit_results.json` (see
+/// `ResultsFile`)
+#[derive(Debug, Clone)]
+pub struct MutationSession {
+    /// The mutated file
+    pub source: Rc<Source>,
+
+    /// The names of the mutation operators that were applied, sorted by name
+    pub operators: Vec<String>,
+
+    /// The functions that mutation was restricted to
+    pub functions: Option<Vec<String>>,
+
+    /// The contract that mutation was restricted to
+    pub contract: Option<String>,
+
+    /// The path to the solc binary used for the file
+    pub solc: Option<String>,
+
+    /// The version of the solc binary used for the file
+    pub solc_version: Option<String>,
+
+    /// The number of validation attempts made while sampling the mutants, if
+    /// they were sampled (see `RandomDownSampleFilter`)
+    pub validation_attempts: Option<usize>,
+
+    /// How the mutants were validated, stage by stage, if they were validated
+    /// with a `--validation_mode` other than `full`
+    pub validation_stages: Option<ValidationStages>,
+
+    /// How long it took to validate the mutants, if any was compiled to
+    /// validate it
+    pub validation_times: Option<ValidationTimes>,
+
+    /// The random stream that the mutants were sampled from, if they were
+    /// sampled (see `sampling_stream()`)
+    pub sampling_stream: Option<u64>,
+
+    /// The indices of this session's mutants among the mutants being written
+    pub mutants: Range<usize>,
+
+    /// The number of mutants requested with `num_mutants`, if any
+    pub requested_mutants: Option<usize>,
+
+    /// The contracts and functions in scope of the source units that were
+    /// mutated, which are counted in the mutation report
+    pub units: Vec<MutatedUnit>,
+
+    /// With `--emit_original_ast`, the AST context of the source units that
+    /// were mutated
+    pub ast_contexts: Vec<FileAstContext>,
+}
+
+/// The bounds of the contracts and functions of a file in a mutation report
+#[derive(Default)]
+struct ReportScopes {
+    /// The physical path of the file
+    path: PathBuf,
+
+    /// The bounds of each contract of the file's report
+    contracts: Vec<(usize, usize)>,
+
+    /// The bounds of each function of the file's report, along with the
+    /// index of its contract (`None` for free functions) and its index among
+    /// that contract's (or the free) functions
+    functions: Vec<((usize, usize), Option<usize>, usize)>,
+}
+
+/// The index of `source`'s file in a mutation report under construction,
+/// along with its scopes, adding the file if it is not reported yet
+fn report_file<'a>(
+    files: &mut Vec<FileReport>,
+    scopes: &'a mut Vec<ReportScopes>,
+    source: &Source,
+) -> Result<(usize, &'a mut ReportScopes), Box<dyn error::Error>> {
+    let i = match scopes.iter().position(|s| s.path == source.physical_path()) {
+        Some(i) => i,
+        None => {
+            files.push(FileReport {
+                file: source.relative_filename()?.to_str().unwrap().to_string(),
+                ..FileReport::default()
+            });
+            scopes.push(ReportScopes {
+                path: source.physical_path().to_path_buf(),
+                ..ReportScopes::default()
+            });
+            scopes.len() - 1
+        }
+    };
+    Ok((i, &mut scopes[i]))
+}
+
+/// Count the `mutants` of each file, contract, and function in scope of the
+/// mutation `sessions` (see `MutationReport`). A mutant is counted in the
+/// contract and function whose bounds contain its start.
+pub fn mutation_report(
+    mutants: &[(Mutant, bool)],
+    sessions: &[MutationSession],
+) -> Result<MutationReport, Box<dyn error::Error>> {
+    let mut files: Vec<FileReport> = vec![];
+    let mut scopes: Vec<ReportScopes> = vec![];
+    for unit in sessions.iter().flat_map(|s| s.units.iter()) {
+        let (i, scope) = report_file(&mut files, &mut scopes, &unit.source)?;
+        let file = &mut files[i];
+        for (name, bounds) in unit.contracts.iter() {
+            if !file.contracts.iter().any(|c| &c.name == name) {
+                file.contracts.push(ContractReport {
+                    name: name.clone(),
+                    ..ContractReport::default()
+                });
+                scope.contracts.push(*bounds);
+            }
+        }
+        for function in unit.functions.iter() {
+            let bounds = (function.start, function.end);
+            if scope.functions.iter().any(|(b, _, _)| *b == bounds) {
+                continue;
+            }
+            let name = match function.name.as_str() {
+                "" => function.kind.clone().unwrap_or_default(),
+                name => name.to_string(),
+            };
+            let report = FunctionReport {
+                name,
+                line: unit.source.get_line_column(function.start)?.0,
+                mutants: 0,
+            };
+            let contract = function
+                .contract
+                .as_ref()
+                .and_then(|c| file.contracts.iter().position(|r| &r.name == c));
+            let functions = match contract {
+                Some(c) => &mut file.contracts[c].functions,
+                None => &mut file.free_functions,
+            };
+            functions.push(report);
+            scope
+                .functions
+                .push((bounds, contract, functions.len() - 1));
+        }
+    }
+
+    let contains = |(start, end): (usize, usize), pos: usize| start <= pos && pos < end;
+    for (mutant, _) in mutants {
+        let (i, scope) = report_file(&mut files, &mut scopes, &mutant.source)?;
+        let file = &mut files[i];
+        file.mutants += 1;
+        if let Some(c) = scope
+            .contracts
+            .iter()
+            .position(|b| contains(*b, mutant.start))
+        {
+            file.contracts[c].mutants += 1;
+        }
+        let function = scope
+            .functions
+            .iter()
+            .find(|(b, _, _)| contains(*b, mutant.start));
+        match function {
+            Some((_, Some(c), j)) => file.contracts[*c].functions[*j].mutants += 1,
+            Some((_, None, j)) => file.free_functions[*j].mutants += 1,
+            None => (),
+        }
+    }
+
+    // Modifiers follow the functions of each unit: restore source order
+    for file in files.iter_mut() {
+        for contract in file.contracts.iter_mut() {
+            contract.functions.sort_by_key(|f| f.line);
+        }
+        file.free_functions.sort_by_key(|f| f.line);
+    }
+    Ok(MutationReport { files })
+}
+
+/// `gambit_results.json` as it is written: its mutant entries are only
+/// computed one at a time while they are serialized, so that the entries of
+/// a large run are never all held in memory at once
+#[derive(Serialize)]
+#[serde(bound = "EntryStream<I>: Serialize")]
+struct StreamedResults<I> {
+    files: Value,
+    format_version: u32,
+    mutants: EntryStream<I>,
+}
+
+/// The mutant entries of `StreamedResults`, serialized as a JSON array. The
+/// entries can only be serialized once.
+struct EntryStream<I>(RefCell<Option<I>>);
+
+impl<I> Serialize for EntryStream<I>
+where
+    I: Iterator<Item = Result<Value, Box<dyn error::Error>>>,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeSeq};
+        let entries = self
+            .0
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| S::Error::custom("the mutant entries were already serialized"))?;
+        let mut seq = serializer.serialize_seq(None)?;
+        for entry in entries {
+            seq.serialize_element(&entry.map_err(S::Error::custom)?)?;
+        }
+        seq.end()
+    }
+}
+
+/// The stable id of `mutant` (see `--stable_ids`): the first 8 hex digits of
+/// the SHA-256 of its file (relative to its sourceroot, with `/` separators),
+/// the span it replaces, its operator's short name, and its replacement. The
+/// id of a mutant doesn't depend on which other mutants were generated.
+pub fn stable_mutant_id(mutant: &Mutant) -> Result<String, Box<dyn error::Error>> {
+    let path: Vec<String> = mutant
+        .source
+        .relative_filename()?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let op = mutant.op.short_name();
+    let mut hasher = Sha256::new();
+    for field in [
+        path.join("/").as_str(),
+        &mutant.start.to_string(),
+        &mutant.end.to_string(),
+        &op,
+        &mutant.repl,
+    ] {
+        hasher.update(field.as_bytes());
+        hasher.update([0]);
+    }
+    Ok(hasher.finalize()[..4]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Assign each mutant a span group: mutants of the same file whose
+/// `[start, end)` spans intersect, directly or through other mutants, share a
+/// group. Groups are numbered from 1 in order of their first span, sorting
+/// spans by file and then by start, so that the numbering does not depend on
+/// the order in which mutants were generated.
+pub fn span_groups(mutants: &[(Mutant, bool)]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..mutants.len()).collect();
+    order.sort_by_key(|&i| {
+        let mutant = &mutants[i].0;
+        (mutant.source.filename(), mutant.start, mutant.end)
+    });
+
+    let mut groups = vec![0; mutants.len()];
+    let mut group = 0;
+    // The file and the end of the current group
+    let mut current: Option<(&Path, usize)> = None;
+    for i in order {
+        let mutant = &mutants[i].0;
+        let filename = mutant.source.filename();
+        current = match current {
+            Some((file, end)) if file == filename && mutant.start < end => {
+                Some((file, end.max(mutant.end)))
+            }
+            _ => {
+                group += 1;
+                Some((filename, mutant.end))
+            }
+        };
+        groups[i] = group;
+    }
+    groups
+}
+
+/// Symlink `dst` to `src`, falling back to a (recursive) copy where symlinks
+/// are not supported
+fn link_or_copy(src: &Path, dst: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        if std::os::unix::fs::symlink(src, dst).is_ok() {
+            return Ok(());
+        }
+    }
+    #[cfg(windows)]
+    {
+        let linked = if src.is_dir() {
+            std::os::windows::fs::symlink_dir(src, dst)
+        } else {
+            std::os::windows::fs::symlink_file(src, dst)
+        };
+        if linked.is_ok() {
+            return Ok(());
+        }
+    }
+    if src.is_dir() {
+        fs::create_dir_all(dst)?;
+        for entry in fs::read_dir(src)? {
+            let path = entry?.path();
+            link_or_copy(&path, &dst.join(path.file_name().unwrap()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(src, dst).map(|_| ())
+    }
+}
+
+/// Copy `src` to `dst`, recursively if `src` is a directory
+fn copy_recursively(src: &Path, dst: &Path) -> io::Result<()> {
+    if src.is_dir() {
+        fs::create_dir_all(dst)?;
+        for entry in fs::read_dir(src)? {
+            let path = entry?.path();
+            copy_recursively(&path, &dst.join(path.file_name().unwrap()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(src, dst).map(|_| ())
+    }
+}
+
 #[derive(Debug)]
 pub enum MutantWriterError {
     NoSuchDirectory(String),
     NoSuchFile(String),
     InvalidMutantDirFormat(String),
+    NotInProjectRoot(String, String),
+    SidecarContainsMutant(String, String),
+    /// An existing mutants subdirectory was not written by Gambit, and is not
+    /// deleted without `--force` (see `check_mutants_subdir()`)
+    ForeignMutantsSubdir(String),
+    /// A mutant could not be exported. The mutants before it were exported
+    /// and logged, and nothing of the failing mutant is left on disk.
+    ExportFailed {
+        /// The id of the mutant that could not be exported
+        mid: String,
+        /// The mutated file
+        filename: String,
+        /// The size of the mutant's contents
+        mutant_bytes: usize,
+        /// The number of bytes of mutant files exported before the failure
+        bytes_written: u64,
+        /// The error that stopped the export
+        cause: String,
+    },
 }
 
 impl std::fmt::Display for MutantWriterError {
@@ -223,8 +1549,1392 @@ impl std::fmt::Display for MutantWriterError {
                 "Invalid Mutant Directory: Expected a numeric name but found {}",
                 dirname
             ),
+            MutantWriterError::NotInProjectRoot(filename, root) => write!(
+                f,
+                "NotInProjectRoot: {} is not in project root {}",
+                filename, root
+            ),
+            MutantWriterError::SidecarContainsMutant(sidecar, filename) => write!(
+                f,
+                "SidecarContainsMutant: {} contains the mutated file {}",
+                sidecar, filename
+            ),
+            MutantWriterError::ForeignMutantsSubdir(reason) => write!(
+                f,
+                "ForeignMutantsSubdir: not deleting {}; pass --force to delete it anyway",
+                reason
+            ),
+            MutantWriterError::ExportFailed {
+                mid,
+                filename,
+                mutant_bytes,
+                bytes_written,
+                cause,
+            } => write!(
+                f,
+                "ExportFailed: could not export mutant {} of {} ({} bytes) after writing {} bytes of earlier mutants: {}",
+                mid, filename, mutant_bytes, bytes_written, cause
+            ),
         }
     }
 }
 
 impl std::error::Error for MutantWriterError {}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        check_mutants_subdir, check_mutants_subdir_name, check_outdir_deletion, outdir_entries,
+        parse_results, read_diff_file, read_index, read_results, span_groups, unify_mutant_sources,
+        validate_results, validate_results_in, ExportFormat, FileStatistics, FunctionInfo,
+        JobManifest, Mutant, MutantWriter, MutantWriterError, MutateParams, MutatedUnit,
+        MutationReport, MutationSession, MutationType, OutdirLayout, PostExportHook, Source,
+        GAMBIT_MARKER_FILENAME, HOOK_STDERR_FILENAME, HOOK_STDOUT_FILENAME, JOB_MANIFEST_FILENAME,
+    };
+    use serde_json::{json, Value};
+    use std::{
+        collections::HashMap,
+        fs,
+        path::{Path, PathBuf},
+        rc::Rc,
+        time::Duration,
+    };
+
+    #[test]
+    fn test_write_mutant_overlay() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(project.join("contracts")).unwrap();
+        fs::create_dir_all(project.join("lib")).unwrap();
+        let code = "contract A {\n    uint x = 1;\n}\n";
+        fs::write(project.join("contracts/A.sol"), code).unwrap();
+        fs::write(project.join("contracts/B.sol"), "contract B {}\n").unwrap();
+        fs::write(project.join("lib/L.sol"), "library L {}\n").unwrap();
+        fs::write(project.join("foundry.toml"), "[profile.default]\n").unwrap();
+        // The output directory lives inside the project, and is not mirrored
+        let outdir = project.join("gambit_out");
+        fs::create_dir_all(&outdir).unwrap();
+
+        let source = Source::new(project.join("contracts/A.sol"), project.clone()).unwrap();
+        let start = code.find('1').unwrap();
+        let mutant = Mutant::new(
+            Rc::new(source),
+            MutationType::AssignmentMutation,
+            start,
+            start + 1,
+            "2".into(),
+        )
+        .unwrap();
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), Some(project.clone()));
+        writer.write_mutants(&[(mutant, true)]).unwrap();
+
+        let overlay = outdir.join("mutants/1");
+        let mutated = fs::read_to_string(overlay.join("contracts/A.sol")).unwrap();
+        assert!(mutated.contains("uint x = 2;"));
+        assert_eq!(
+            fs::read_to_string(overlay.join("contracts/B.sol")).unwrap(),
+            "contract B {}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(overlay.join("lib/L.sol")).unwrap(),
+            "library L {}\n"
+        );
+        assert!(overlay.join("foundry.toml").exists());
+        assert!(!overlay.join("gambit_out").exists());
+        #[cfg(unix)]
+        assert!(fs::symlink_metadata(overlay.join("lib"))
+            .unwrap()
+            .file_type()
+            .is_symlink());
+
+        let results = fs::read_to_string(outdir.join("gambit_results.json")).unwrap();
+        let results: serde_json::Value = serde_json::from_str(&results).unwrap();
+        assert_eq!(
+            PathBuf::from(results["mutants"][0]["name"].as_str().unwrap()),
+            PathBuf::from("mutants/1/contracts/A.sol")
+        );
+    }
+
+    #[test]
+    fn test_write_mutant_patches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("contracts")).unwrap();
+        let code = "contract A {\n    uint x = 1;\n    uint y = 2;\n}\n";
+        fs::write(root.join("contracts/A.sol"), code).unwrap();
+        let outdir = root.join("gambit_out");
+
+        let source = Rc::new(Source::new(root.join("contracts/A.sol"), root.clone()).unwrap());
+        let mutant = |orig: &str, repl: &str| {
+            let start = code.find(orig).unwrap();
+            let op = MutationType::AssignmentMutation;
+            Mutant::new(source.clone(), op, start, start + orig.len(), repl.into()).unwrap()
+        };
+        let mutants = vec![(mutant("1", "0"), true), (mutant("2", "3"), true)];
+        MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+            .with_export_format(ExportFormat::Patch)
+            .write_mutants(&mutants)
+            .unwrap();
+        assert!(!outdir.join("mutants/1/contracts/A.sol").exists());
+
+        let results = read_results(&outdir.join("gambit_results.json")).unwrap();
+        for (i, (mutant, _)) in mutants.iter().enumerate() {
+            let name = format!("mutants/{}/contracts/A.sol.patch", i + 1);
+            assert_eq!(results[i]["name"], name);
+            let patch = fs::read_to_string(outdir.join(&name)).unwrap();
+            assert!(patch.starts_with("--- a/contracts/A.sol\n+++ b/contracts/A.sol\n"));
+
+            // Applying the patch from the sourceroot reproduces the mutant
+            let git_apply = |args: &[&str]| {
+                let status = std::process::Command::new("git")
+                    .current_dir(&root)
+                    .arg("apply")
+                    .args(args)
+                    .arg(outdir.join(&name))
+                    .status()
+                    .unwrap();
+                assert!(status.success());
+            };
+            git_apply(&[]);
+            assert_eq!(
+                fs::read_to_string(root.join("contracts/A.sol")).unwrap(),
+                mutant.as_source_string().unwrap()
+            );
+            git_apply(&["--reverse"]);
+            assert_eq!(
+                fs::read_to_string(root.join("contracts/A.sol")).unwrap(),
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_job_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("contracts")).unwrap();
+        let code = "contract A {\n    uint x = 1;\n    uint y = 2;\n}\n";
+        fs::write(root.join("contracts/A.sol"), code).unwrap();
+        let outdir = root.join("gambit_out");
+
+        let source = Rc::new(Source::new(root.join("contracts/A.sol"), root.clone()).unwrap());
+        let mutant = |orig: &str, repl: &str| {
+            let start = code.find(orig).unwrap();
+            let op = MutationType::AssignmentMutation;
+            Mutant::new(source.clone(), op, start, start + orig.len(), repl.into()).unwrap()
+        };
+        let params: MutateParams = serde_json::from_value(json!({
+            "filename": root.join("contracts/A.sol"),
+            "emit_manifest": true,
+        }))
+        .unwrap();
+        let mutants = vec![(mutant("1", "0"), true), (mutant("2", "3"), false)];
+        MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+            .with_job_manifest(Some(vec![params]))
+            .write_mutants(&mutants)
+            .unwrap();
+
+        let manifest: JobManifest =
+            serde_json::from_str(&fs::read_to_string(outdir.join(JOB_MANIFEST_FILENAME)).unwrap())
+                .unwrap();
+        assert_eq!(manifest.gambit_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(manifest.configurations.len(), 1);
+        assert!(manifest.configurations[0].emit_manifest);
+
+        let results = read_results(&outdir.join("gambit_results.json")).unwrap();
+        assert_eq!(manifest.jobs.len(), 2);
+        for (job, result) in manifest.jobs.iter().zip(results.iter()) {
+            assert_eq!(job.id, result["id"]);
+            assert_eq!(job.original, "contracts/A.sol");
+            assert_eq!(
+                job.original_path,
+                root.join("contracts/A.sol").to_str().unwrap()
+            );
+            assert_eq!(job.operator, "AssignmentMutation");
+            assert_eq!(
+                (job.line, job.col),
+                (result["line"].as_u64().unwrap() as usize, 14)
+            );
+        }
+
+        // The exported mutant has an absolute path that a harness can copy
+        // over the original file; the unexported one has none
+        let exported = manifest.jobs[0].mutant.as_ref().unwrap();
+        assert!(Path::new(exported).is_absolute());
+        assert_eq!(
+            fs::read_to_string(exported).unwrap(),
+            mutants[0].0.as_source_string().unwrap()
+        );
+        assert_eq!(manifest.jobs[1].mutant, None);
+
+        // Without `--emit_manifest` no manifest is written
+        let outdir = root.join("gambit_out_no_manifest");
+        MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+            .write_mutants(&mutants)
+            .unwrap();
+        assert!(!outdir.join(JOB_MANIFEST_FILENAME).exists());
+    }
+
+    #[test]
+    fn test_write_sidecars() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("contracts")).unwrap();
+        fs::create_dir_all(root.join("specs")).unwrap();
+        let code = "contract A {\n    uint x = 1;\n}\n";
+        fs::write(root.join("contracts/A.sol"), code).unwrap();
+        fs::write(root.join("certora.conf"), "{}\n").unwrap();
+        fs::write(root.join("specs/A.spec"), "rule r {}\n").unwrap();
+
+        let source = Rc::new(Source::new(root.join("contracts/A.sol"), root.clone()).unwrap());
+        let start = code.find('1').unwrap();
+        let mutants: Vec<(Mutant, bool)> = ["2", "3"]
+            .iter()
+            .map(|repl| {
+                let mutant = Mutant::new(
+                    source.clone(),
+                    MutationType::AssignmentMutation,
+                    start,
+                    start + 1,
+                    repl.to_string(),
+                )
+                .unwrap();
+                (mutant, true)
+            })
+            .collect();
+        let sidecars = vec![root.join("certora.conf"), root.join("specs")];
+
+        for link in [false, true] {
+            let outdir = root.join(format!("gambit_out_{}", link));
+            let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+                .with_sidecars(sidecars.clone(), link);
+            writer.write_mutants(&mutants).unwrap();
+            for mid in ["1", "2"] {
+                let mutant_dir = outdir.join("mutants").join(mid);
+                assert!(mutant_dir.join("contracts/A.sol").is_file());
+                assert_eq!(
+                    fs::read_to_string(mutant_dir.join("certora.conf")).unwrap(),
+                    "{}\n"
+                );
+                assert_eq!(
+                    fs::read_to_string(mutant_dir.join("specs/A.spec")).unwrap(),
+                    "rule r {}\n"
+                );
+                #[cfg(unix)]
+                assert_eq!(
+                    fs::symlink_metadata(mutant_dir.join("specs"))
+                        .unwrap()
+                        .file_type()
+                        .is_symlink(),
+                    link
+                );
+            }
+            // Sidecars are not mutants, and are not logged as such
+            let log = fs::read_to_string(outdir.join("mutants.log")).unwrap();
+            assert_eq!(log.lines().count(), 2);
+            assert!(!log.contains("certora.conf") && !log.contains("A.spec"));
+        }
+
+        // A sidecar may not contain the mutated file
+        let writer = MutantWriter::new(root.join("gambit_out").to_str().unwrap().to_string(), None)
+            .with_sidecars(vec![root.join("contracts")], false);
+        assert!(writer.write_mutants(&mutants).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_post_export_hook() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("contracts")).unwrap();
+        let code = "contract A {\n    uint x = 1;\n}\n";
+        fs::write(root.join("contracts/A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("contracts/A.sol"), root.clone()).unwrap());
+        let start = code.find('1').unwrap();
+        let mutants: Vec<(Mutant, bool)> = ["2", "3", "4"]
+            .iter()
+            .map(|repl| {
+                let mutant = Mutant::new(
+                    source.clone(),
+                    MutationType::AssignmentMutation,
+                    start,
+                    start + 1,
+                    repl.to_string(),
+                )
+                .unwrap();
+                // The second mutant is not exported, so the hook skips it
+                (mutant, *repl != "3")
+            })
+            .collect();
+
+        // A hook that prepends a license header to each mutant
+        let outdir = root.join("gambit_out");
+        let hook = PostExportHook::new(
+            "sed -i '1i // SPDX-License-Identifier: MIT' {mutant_path} && echo {id} {op} {original}"
+                .into(),
+            Duration::from_secs(10),
+            2,
+        );
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+            .with_post_export_hook(Some(hook));
+        writer.write_mutants(&mutants).unwrap();
+        assert!(writer.hook_failures().is_empty());
+        let mutant_dir = outdir.join("mutants/3");
+        let mutated = fs::read_to_string(mutant_dir.join("contracts/A.sol")).unwrap();
+        assert!(mutated.starts_with("// SPDX-License-Identifier: MIT\ncontract A {\n"));
+        assert!(mutated.contains("uint x = 4;"));
+        assert_eq!(
+            fs::read_to_string(mutant_dir.join(HOOK_STDOUT_FILENAME)).unwrap(),
+            "3 AssignmentMutation contracts/A.sol\n"
+        );
+        assert!(!outdir.join("mutants/2").exists());
+
+        // Failing and hanging hooks are collected, in order of mutant id
+        for (cmd, reason, stderr) in [
+            (
+                "echo oops >&2; exit {id}",
+                "failed with exit status: 1",
+                "oops\n",
+            ),
+            ("sleep 5", "timed out", ""),
+        ] {
+            let hook = PostExportHook::new(cmd.into(), Duration::from_millis(200), 2);
+            let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+                .with_post_export_hook(Some(hook));
+            writer.write_mutants(&mutants).unwrap();
+            let failures = writer.hook_failures();
+            assert_eq!(
+                failures.iter().map(|f| f.mid.as_str()).collect::<Vec<_>>(),
+                vec!["1", "3"]
+            );
+            assert!(
+                failures[0].reason.contains(reason),
+                "{}",
+                failures[0].reason
+            );
+            assert_eq!(
+                fs::read_to_string(outdir.join("mutants/1").join(HOOK_STDERR_FILENAME)).unwrap(),
+                stderr
+            );
+        }
+    }
+
+    #[test]
+    fn test_file_reached_from_two_sourceroots() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("lib")).unwrap();
+        let code = "contract L {\n    uint x = 1;\n}\n";
+        fs::write(root.join("lib/L.sol"), code).unwrap();
+
+        // Two entries reach lib/L.sol under different sourceroots
+        let start = code.find('1').unwrap();
+        let mut mutants: Vec<(Mutant, bool)> = [(root.clone(), "2"), (root.join("lib"), "3")]
+            .into_iter()
+            .map(|(sourceroot, repl)| {
+                let source = Rc::new(Source::new(root.join("lib/L.sol"), sourceroot).unwrap());
+                let mutant = Mutant::new(
+                    source,
+                    MutationType::AssignmentMutation,
+                    start,
+                    start + 1,
+                    repl.to_string(),
+                )
+                .unwrap();
+                (mutant, true)
+            })
+            .collect();
+        let mut sources_by_path = HashMap::new();
+        unify_mutant_sources(&mut mutants[..1], &mut sources_by_path);
+        unify_mutant_sources(&mut mutants[1..], &mut sources_by_path);
+
+        let outdir = root.join("gambit_out");
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None);
+        writer.write_mutants(&mutants).unwrap();
+
+        let log = fs::read_to_string(outdir.join("mutants.log")).unwrap();
+        let logged: Vec<&str> = log.lines().map(|l| l.split(',').nth(2).unwrap()).collect();
+        assert_eq!(logged, vec!["lib/L.sol", "lib/L.sol"]);
+        let results: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+                .unwrap();
+        for (mid, entry) in ["1", "2"]
+            .iter()
+            .zip(results["mutants"].as_array().unwrap())
+        {
+            assert_eq!(entry["original"], "lib/L.sol");
+            assert_eq!(entry["sourceroot"], root.to_str().unwrap());
+            assert_eq!(entry["name"], format!("mutants/{}/lib/L.sol", mid));
+            assert!(outdir.join("mutants").join(mid).join("lib/L.sol").is_file());
+        }
+    }
+
+    #[test]
+    fn test_span_groups() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract C {\n    uint x = (1 + 2) * 3;\n    uint y = 4;\n}\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        fs::write(root.join("B.sol"), code).unwrap();
+        let a = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let b = Rc::new(Source::new(root.join("B.sol"), root.clone()).unwrap());
+        let span = |text: &str| {
+            let start = code.find(text).unwrap();
+            (start, start + text.len())
+        };
+        // Listed out of order: groups are numbered by file, then span start
+        let spans = [
+            // A: `4` is disjoint from everything else
+            (&a, span("4")),
+            // A: `(1 + 2) * 3` contains `1 + 2`, which contains `+`
+            (&a, span("+")),
+            (&a, span("(1 + 2) * 3")),
+            (&a, span("1 + 2")),
+            // A: `3` is nested in `(1 + 2) * 3`
+            (&a, span("3;")),
+            // B: the same spans as in A are in different groups
+            (&b, span("1 + 2")),
+            // B: `2) * 3` overlaps both `1 + 2` and `3`, chaining them
+            (&b, span("3;")),
+            (&b, span("2) * 3")),
+            // B: `x` precedes every other span
+            (&b, span("x")),
+        ];
+        let mutants: Vec<(Mutant, bool)> = spans
+            .iter()
+            .map(|(source, (start, end))| {
+                let mutant = Mutant::new(
+                    (*source).clone(),
+                    MutationType::BinaryOpMutation,
+                    *start,
+                    *end,
+                    "0".to_string(),
+                )
+                .unwrap();
+                (mutant, true)
+            })
+            .collect();
+        assert_eq!(span_groups(&mutants), vec![2, 1, 1, 1, 1, 4, 4, 4, 3]);
+
+        let outdir = root.join("gambit_out");
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None);
+        writer.write_mutants(&mutants).unwrap();
+        let results: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+                .unwrap();
+        let groups: Vec<u64> = results["mutants"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["span_group"].as_u64().unwrap())
+            .collect();
+        assert_eq!(groups, vec![2, 1, 1, 1, 1, 4, 4, 4, 3]);
+    }
+
+    #[test]
+    fn test_stable_ids() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A {\n    uint x = 1 + 2;\n}\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let start = code.find('1').unwrap();
+        let mutant = |op: MutationType, end: usize, repl: &str| {
+            let mutant = Mutant::new(source.clone(), op, start, end, repl.to_string());
+            (mutant.unwrap(), true)
+        };
+        let assignment = |repl: &str| mutant(MutationType::AssignmentMutation, start + 5, repl);
+        let binary_op = |repl: &str| mutant(MutationType::BinaryOpMutation, start + 5, repl);
+
+        // Write the mutants of one run, returning the ids of its entries
+        let run = |mutants: &[(Mutant, bool)]| {
+            let outdir = root.join("gambit_out");
+            MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+                .with_stable_ids(true)
+                .write_mutants(mutants)
+                .unwrap();
+            let json = fs::read_to_string(outdir.join("gambit_results.json")).unwrap();
+            assert_eq!(validate_results(&json), Vec::<String>::new());
+            let log = fs::read_to_string(outdir.join("mutants.log")).unwrap();
+            let results = read_results(&outdir).unwrap();
+            let ids: Vec<String> = results
+                .iter()
+                .map(|entry| entry["id"].as_str().unwrap().to_string())
+                .collect();
+            for (id, line) in ids.iter().zip(log.lines()) {
+                assert!(line.starts_with(&format!("{},", id)));
+                assert!(outdir.join("mutants").join(id).join("A.sol").is_file());
+            }
+            ids
+        };
+
+        // Only the assignment operator is enabled
+        let first = run(&[assignment("0"), assignment("3")]);
+        assert!(first
+            .iter()
+            .all(|id| id.len() == 8 && id.chars().all(|c| c.is_ascii_hexdigit())));
+        assert_ne!(first[0], first[1]);
+
+        // With another operator enabled, the same mutants keep their ids,
+        // and identical mutants are told apart by a suffix
+        let second = run(&[
+            binary_op("1 - 2"),
+            assignment("3"),
+            binary_op("1 - 2"),
+            assignment("0"),
+        ]);
+        assert_eq!(second[1], first[1]);
+        assert_eq!(second[3], first[0]);
+        assert_eq!(second[2], format!("{}-2", second[0]));
+        assert!(!first.contains(&second[0]));
+    }
+
+    #[test]
+    fn test_operator_names_in_outputs() {
+        use clap::ValueEnum;
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A {\n    uint x = 1 + 2;\n}\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let start = code.find('1').unwrap();
+        let ops = MutationType::value_variants();
+        let mutants: Vec<(Mutant, bool)> = ops
+            .iter()
+            .enumerate()
+            .map(|(i, op)| {
+                let mutant = Mutant::new(source.clone(), *op, start, start + 1, i.to_string());
+                (mutant.unwrap(), true)
+            })
+            .collect();
+        let outdir = root.join("gambit_out");
+        MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+            .write_mutants(&mutants)
+            .unwrap();
+
+        let log = fs::read_to_string(outdir.join("mutants.log")).unwrap();
+        let results = read_results(&outdir).unwrap();
+        assert_eq!(log.lines().count(), ops.len());
+        for ((op, line), entry) in ops.iter().zip(log.lines()).zip(results.iter()) {
+            let logged = line.split(',').nth(1).unwrap();
+            let description = entry["description"].as_str().unwrap();
+            assert_eq!(logged, op.to_string());
+            assert_eq!(description, logged);
+            assert_eq!(MutationType::from_name(description), Some(*op));
+            assert_eq!(MutationType::from_str(&op.short_name(), true), Ok(*op));
+        }
+    }
+
+    #[test]
+    fn test_mutants_without_comments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A {\n    uint x = 1 + 2;\n    uint y = 3 + 4;\n}\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let mutant = |expr: &str, repl: &str| {
+            let start = code.find(expr).unwrap();
+            let end = start + expr.len();
+            Mutant::new(
+                source.clone(),
+                MutationType::BinaryOpMutation,
+                start,
+                end,
+                repl.into(),
+            )
+            .unwrap()
+        };
+        // As if generated by an operator that opts out of the comment
+        let mut uncommented = mutant("3 + 4", "3 - 4");
+        uncommented.insert_comment = false;
+        let mutants = vec![(mutant("1 + 2", "1 - 2"), true), (uncommented, true)];
+
+        let outdir = root.join("gambit_out");
+        MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+            .write_mutants(&mutants)
+            .unwrap();
+
+        let commented = fs::read_to_string(outdir.join("mutants/1/A.sol")).unwrap();
+        assert_eq!(commented.lines().count(), code.lines().count() + 1);
+        assert!(commented.contains("/// BinaryOpMutation(`1 + 2` |==> `1 - 2`)"));
+        let uncommented = fs::read_to_string(outdir.join("mutants/2/A.sol")).unwrap();
+        assert_eq!(uncommented, code.replace("3 + 4", "3 - 4"));
+
+        let results = read_results(&outdir).unwrap();
+        assert_eq!(results[0]["comment_inserted"], true);
+        assert_eq!(results[0]["line_shifts"][0]["delta"], 1);
+        assert_eq!(results[1]["comment_inserted"], false);
+        assert_eq!(results[1]["line_shifts"][0]["delta"], 0);
+        let json = fs::read_to_string(outdir.join("gambit_results.json")).unwrap();
+        assert_eq!(validate_results(&json), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_write_mutants_at_end_of_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        // The file's last byte is the closing semicolon of its last statement
+        let code = "contract A {}\nuint256 constant X = 1;";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let mutant = |start: usize, repl: &str| {
+            Mutant::new(
+                source.clone(),
+                MutationType::DeleteExpressionMutation,
+                start,
+                code.len(),
+                repl.to_string(),
+            )
+            .unwrap()
+        };
+        let mut unlocatable = mutant(code.len() - 1, "");
+        unlocatable.start = code.len() + 1;
+        let mutants = vec![
+            (mutant(code.find("uint256").unwrap(), "/* */"), true),
+            (mutant(code.len() - 1, ""), true),
+            // Logged with a sentinel location instead of failing the run
+            (unlocatable, false),
+        ];
+
+        let outdir = root.join("gambit_out");
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None);
+        writer.write_mutants(&mutants).unwrap();
+
+        let log = fs::read_to_string(outdir.join("mutants.log")).unwrap();
+        let locations: Vec<&str> = log
+            .lines()
+            .map(|line| line.split(',').nth(3).unwrap())
+            .collect();
+        assert_eq!(locations, vec!["2:1", "2:23", "0:0"]);
+        let results: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+                .unwrap();
+        let results = results["mutants"].as_array().unwrap();
+        assert_eq!(
+            (&results[1]["line"], &results[1]["col"]),
+            (&2.into(), &23.into())
+        );
+        assert!(results[1]["diff"]
+            .as_str()
+            .unwrap()
+            .contains("+uint256 constant X = 1"));
+        assert_eq!(
+            (&results[2]["line"], &results[2]["col"]),
+            (&0.into(), &0.into())
+        );
+        assert_eq!(results[2]["diff"], "");
+        assert!(outdir.join("mutants/2/A.sol").is_file());
+    }
+
+    #[test]
+    fn test_write_sessions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A {\n    uint x = 1;\n}\ncontract B {\n    uint y = 2;\n}\n";
+        fs::write(root.join("C.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("C.sol"), root.clone()).unwrap());
+        let start = code.find('1').unwrap();
+        let mutants: Vec<(Mutant, bool)> = ["2", "3"]
+            .iter()
+            .enumerate()
+            .map(|(i, repl)| {
+                let mut mutant = Mutant::new(
+                    source.clone(),
+                    MutationType::AssignmentMutation,
+                    start,
+                    start + 1,
+                    repl.to_string(),
+                )
+                .unwrap();
+                mutant.validated = i == 0;
+                mutant.validation_error = i == 1;
+                (mutant, true)
+            })
+            .collect();
+        // Two configuration entries mutate different contracts of the same
+        // file: only the first one generates mutants
+        let session = |contract: &str, mutants| MutationSession {
+            source: source.clone(),
+            operators: vec!["AssignmentMutation".into()],
+            functions: None,
+            contract: Some(contract.into()),
+            solc: Some("/usr/bin/solc".into()),
+            solc_version: Some("0.8.13".into()),
+            validation_attempts: None,
+            validation_stages: None,
+            validation_times: None,
+            units: vec![],
+            requested_mutants: None,
+            ast_contexts: vec![],
+            sampling_stream: None,
+            mutants,
+        };
+        let sessions = vec![
+            MutationSession {
+                validation_attempts: Some(3),
+                ..session("A", 0..2)
+            },
+            session("B", 2..2),
+        ];
+
+        let outdir = root.join("gambit_out");
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None);
+        writer.write_sessions_after(&mutants, &sessions, 0).unwrap();
+        let json = fs::read_to_string(outdir.join("gambit_results.json")).unwrap();
+        assert_eq!(validate_results(&json), Vec::<String>::new());
+
+        let results = parse_results(&json).unwrap();
+        assert_eq!(results.mutants.len(), 2);
+        assert_eq!(results.files.len(), 2);
+        let (a, b) = (&results.files[0], &results.files[1]);
+        assert_eq!(a.original, "C.sol");
+        assert_eq!(a.sourceroot, root.to_str().unwrap());
+        assert_eq!(a.contract.as_deref(), Some("A"));
+        assert_eq!(a.operators, vec!["AssignmentMutation"]);
+        assert_eq!(a.solc_version.as_deref(), Some("0.8.13"));
+        assert_eq!(a.mutant_ids, vec!["1", "2"]);
+        assert_eq!(
+            a.statistics,
+            FileStatistics {
+                mutants: 2,
+                validated: 1,
+                validation_errors: 1,
+                requested: None,
+                validation_attempts: Some(3),
+                validation_stages: None,
+                validation_times: None,
+            }
+        );
+        assert!(!results.mutants[0].validation_error);
+        assert!(results.mutants[1].validation_error);
+        let log = fs::read_to_string(outdir.join("mutants.log")).unwrap();
+        assert!(log.lines().nth(1).unwrap().ends_with(",validation-error"));
+        assert_eq!(b.contract.as_deref(), Some("B"));
+        assert!(b.mutant_ids.is_empty());
+        assert_eq!(b.statistics, FileStatistics::default());
+
+        // Without sessions, files are inferred from the mutants
+        writer.write_mutants(&mutants).unwrap();
+        let json = fs::read_to_string(outdir.join("gambit_results.json")).unwrap();
+        let results = parse_results(&json).unwrap();
+        assert_eq!(results.files.len(), 1);
+        assert_eq!(results.files[0].contract, None);
+        assert_eq!(results.files[0].mutant_ids, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_write_index() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract C {\n    uint x = 1 + 2;\n}\n";
+        let start = code.find('+').unwrap();
+        let mut sources = vec![];
+        for name in ["A.sol", "B.sol"] {
+            fs::write(root.join(name), code).unwrap();
+            sources.push(Rc::new(Source::new(root.join(name), root.clone()).unwrap()));
+        }
+        let mutant = |source: &Rc<Source>, op, repl: &str| {
+            let m = Mutant::new(source.clone(), op, start, start + 1, repl.into()).unwrap();
+            (m, true)
+        };
+        let session = |source: &Rc<Source>, mutants| MutationSession {
+            source: source.clone(),
+            operators: vec![],
+            functions: None,
+            contract: None,
+            solc: None,
+            solc_version: None,
+            validation_attempts: None,
+            validation_stages: None,
+            validation_times: None,
+            units: vec![],
+            requested_mutants: None,
+            ast_contexts: vec![],
+            sampling_stream: None,
+            mutants,
+        };
+        let (a, b) = (&sources[0], &sources[1]);
+        // Three configuration entries write to the same output directory:
+        // A.sol, B.sol, and A.sol again with another operator
+        let mut mutants = vec![
+            mutant(a, MutationType::BinaryOpMutation, "-"),
+            mutant(a, MutationType::BinaryOpMutation, "*"),
+        ];
+        let mut sessions = vec![session(a, 0..2)];
+        let outdir = root.join("gambit_out");
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None);
+        writer.write_sessions_after(&mutants, &sessions, 0).unwrap();
+
+        // The index agrees with the entries of `gambit_results.json`
+        let check_index = || {
+            let index = read_index(&outdir).unwrap().unwrap();
+            let entries = read_results(&outdir).unwrap();
+            for (file, entry) in index.iter() {
+                let ids: Vec<&str> = entries
+                    .iter()
+                    .filter(|e| e["original"] == file.as_str())
+                    .map(|e| e["id"].as_str().unwrap())
+                    .collect();
+                assert_eq!(entry.mutant_count, ids.len());
+                assert_eq!(entry.first_id, ids[0]);
+                assert_eq!(&entry.last_id, ids.last().unwrap());
+            }
+            let total: usize = index.values().map(|e| e.mutant_count).sum();
+            assert_eq!(total, entries.len());
+            index
+        };
+        let index = check_index();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index["A.sol"].operators_used, vec!["BinaryOpMutation"]);
+
+        mutants.push(mutant(b, MutationType::BinaryOpMutation, "-"));
+        sessions.push(session(b, 2..3));
+        writer.write_sessions_after(&mutants, &sessions, 2).unwrap();
+        mutants.push(mutant(a, MutationType::AssignmentMutation, "0"));
+        sessions.push(session(a, 3..4));
+        writer.write_sessions_after(&mutants, &sessions, 3).unwrap();
+        let index = check_index();
+        assert_eq!(index.len(), 2);
+        let a = &index["A.sol"];
+        assert_eq!(
+            (a.mutant_count, a.first_id.as_str(), a.last_id.as_str()),
+            (3, "1", "4")
+        );
+        assert_eq!(
+            a.operators_used,
+            vec!["AssignmentMutation", "BinaryOpMutation"]
+        );
+        let b = &index["B.sol"];
+        assert_eq!(
+            (b.mutant_count, b.first_id.as_str(), b.last_id.as_str()),
+            (1, "3", "3")
+        );
+
+        // An empty run has an empty index
+        writer.write_mutants(&[]).unwrap();
+        assert!(check_index().is_empty());
+    }
+
+    #[test]
+    fn test_foreign_mutants_subdir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract C {\n    uint x = 1 + 2;\n}\n";
+        fs::write(root.join("C.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("C.sol"), root.clone()).unwrap());
+        let start = code.find('+').unwrap();
+        let mutant = Mutant::new(
+            source,
+            MutationType::BinaryOpMutation,
+            start,
+            start + 1,
+            "-".into(),
+        );
+        let mutants = vec![(mutant.unwrap(), true)];
+
+        // Another tool's `mutants` directory is neither deleted nor written to
+        let foreign = root.join("mutants/keep.txt");
+        fs::create_dir_all(foreign.parent().unwrap()).unwrap();
+        fs::write(&foreign, "not Gambit's").unwrap();
+        let writer = MutantWriter::new(root.to_str().unwrap().to_string(), None);
+        let err = writer.write_mutants(&mutants).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<MutantWriterError>(),
+            Some(MutantWriterError::ForeignMutantsSubdir(_))
+        ));
+        assert!(foreign.is_file());
+        assert!(check_mutants_subdir(&root, "mutants").is_err());
+
+        // Mutants can be exported to another subdirectory, which is marked
+        let writer = MutantWriter::new(root.to_str().unwrap().to_string(), None)
+            .with_mutants_subdir("gambit_mutants".into());
+        writer.write_mutants(&mutants).unwrap();
+        assert!(root.join("gambit_mutants/1/C.sol").is_file());
+        assert!(root
+            .join("gambit_mutants")
+            .join(GAMBIT_MARKER_FILENAME)
+            .is_file());
+        let results = read_results(&root).unwrap();
+        assert_eq!(results[0]["name"], "gambit_mutants/1/C.sol");
+        assert!(foreign.is_file());
+        // ... and rewritten, since Gambit wrote it
+        writer.write_mutants(&mutants).unwrap();
+
+        // With `--force`, the foreign directory is replaced
+        let writer = MutantWriter::new(root.to_str().unwrap().to_string(), None).with_force(true);
+        writer.write_mutants(&mutants).unwrap();
+        assert!(!foreign.exists());
+        assert!(root.join("mutants/1/C.sol").is_file());
+        assert!(check_mutants_subdir(&root, "mutants").is_ok());
+    }
+
+    #[test]
+    fn test_check_outdir_deletion() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let outdir = root.join("gambit_out");
+        fs::create_dir_all(outdir.join("mutants")).unwrap();
+        assert!(check_outdir_deletion(&outdir, "mutants").is_err());
+        fs::write(outdir.join(GAMBIT_MARKER_FILENAME), "").unwrap();
+        assert!(check_outdir_deletion(&outdir, "mutants").is_err());
+        fs::write(outdir.join("mutants").join(GAMBIT_MARKER_FILENAME), "").unwrap();
+        assert!(check_outdir_deletion(&outdir, "mutants").is_ok());
+        // Empty directories are deleted, and so are directories with only
+        // solc's output
+        fs::create_dir(root.join("empty")).unwrap();
+        assert!(check_outdir_deletion(&root.join("empty"), "mutants").is_ok());
+        fs::create_dir(root.join("empty/input_json")).unwrap();
+        assert!(check_outdir_deletion(&root.join("empty"), "mutants").is_ok());
+        fs::write(root.join("empty/notes.txt"), "").unwrap();
+        assert!(check_outdir_deletion(&root.join("empty"), "mutants").is_err());
+
+        // Repository roots and directories containing the working directory
+        // are never deleted
+        fs::create_dir(outdir.join(".git")).unwrap();
+        let err = check_outdir_deletion(&outdir, "mutants").unwrap_err();
+        assert!(err.contains("repository"));
+        let cwd = std::env::current_dir().unwrap();
+        assert!(check_outdir_deletion(&cwd, "mutants").is_err());
+        assert!(check_outdir_deletion(cwd.parent().unwrap(), "mutants").is_err());
+
+        assert!(check_mutants_subdir_name("gambit_mutants").is_ok());
+        for name in ["", ".", "..", "a/b", "/mutants"] {
+            assert!(check_mutants_subdir_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_outdir_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let outdir = tmp.path().join("gambit_out");
+        for dir in ["mutants/1", "custom_mutants/1", "input_json", "notes"] {
+            fs::create_dir_all(outdir.join(dir)).unwrap();
+        }
+        for file in [
+            GAMBIT_MARKER_FILENAME,
+            "custom_mutants/.gambit",
+            "gambit_results.json",
+            "mutants.log",
+            "notes.md",
+        ] {
+            fs::write(outdir.join(file), "").unwrap();
+        }
+        let names = |entries: Vec<PathBuf>| -> Vec<String> {
+            entries
+                .iter()
+                .map(|e| e.file_name().unwrap().to_str().unwrap().to_string())
+                .collect()
+        };
+        // Unmarked mutants subdirectories are only Gambit's next to its
+        // results (as in output directories of earlier versions)
+        let (outputs, foreign) = outdir_entries(&outdir, "mutants").unwrap();
+        assert_eq!(
+            names(outputs),
+            vec![
+                ".gambit",
+                "custom_mutants",
+                "gambit_results.json",
+                "input_json",
+                "mutants",
+                "mutants.log"
+            ]
+        );
+        assert_eq!(names(foreign), vec!["notes", "notes.md"]);
+        fs::remove_file(outdir.join("gambit_results.json")).unwrap();
+        let (_, foreign) = outdir_entries(&outdir, "mutants").unwrap();
+        assert_eq!(names(foreign), vec!["mutants", "notes", "notes.md"]);
+    }
+
+    #[test]
+    fn test_write_mutation_report() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract C {\n    uint x;\n    function f() public {\n        x = 1 + 2;\n    }\n    function g() public {}\n}\n";
+        fs::write(root.join("C.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("C.sol"), root.clone()).unwrap());
+        let function = |name: &str, start: usize, end: usize| FunctionInfo {
+            name: name.into(),
+            contract: Some("C".into()),
+            kind: Some("function".into()),
+            state_mutability: None,
+            parameters: vec![],
+            start,
+            end,
+            sole_statement: None,
+        };
+        let f = code.find("function f").unwrap();
+        let g = code.find("function g").unwrap();
+        let unit = MutatedUnit {
+            source: source.clone(),
+            contracts: vec![("C".into(), (0, code.len()))],
+            functions: vec![
+                function("f", f, g),
+                function("g", g, g + "function g() public {}".len()),
+            ],
+        };
+        let start = code.find('+').unwrap();
+        let mutants: Vec<(Mutant, bool)> = ["-", "*"]
+            .iter()
+            .map(|repl| {
+                let m = Mutant::new(
+                    source.clone(),
+                    MutationType::BinaryOpMutation,
+                    start,
+                    start + 1,
+                    repl.to_string(),
+                )
+                .unwrap();
+                (m, true)
+            })
+            .collect();
+        let sessions = vec![MutationSession {
+            source: source.clone(),
+            operators: vec![],
+            functions: None,
+            contract: None,
+            solc: None,
+            solc_version: None,
+            validation_attempts: None,
+            validation_stages: None,
+            validation_times: None,
+            units: vec![unit.clone(), unit],
+            requested_mutants: None,
+            ast_contexts: vec![],
+            sampling_stream: None,
+            mutants: 0..2,
+        }];
+        let outdir = root.join("gambit_out");
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None);
+        writer.write_sessions_after(&mutants, &sessions, 0).unwrap();
+
+        // The function without mutants is reported with a count of zero, and
+        // units mutated twice are only reported once
+        let json = fs::read_to_string(outdir.join("mutation_report.json")).unwrap();
+        let report: MutationReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(report.files.len(), 1);
+        let file = &report.files[0];
+        assert_eq!((file.file.as_str(), file.mutants), ("C.sol", 2));
+        assert_eq!(file.contracts.len(), 1);
+        assert_eq!(file.contracts[0].mutants, 2);
+        let functions: Vec<(&str, usize, usize)> = file.contracts[0]
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.line, f.mutants))
+            .collect();
+        assert_eq!(functions, vec![("f", 3, 2), ("g", 6, 0)]);
+        assert!(file.free_functions.is_empty());
+        assert_eq!(report.num_functions(), 2);
+        assert!(report.table().contains("    g (line 6)"));
+    }
+
+    #[test]
+    fn test_write_flat_layout() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("lib")).unwrap();
+        let a = "contract A {\n    uint x = 1;\n}\ncontract B {\n    uint y = 2;\n}\n";
+        let lib = "function f() pure returns (uint) {\n    return 3;\n}\ncontract A {\n    uint z = 4;\n}\n";
+        fs::write(root.join("A.sol"), a).unwrap();
+        fs::write(root.join("lib/Lib.sol"), lib).unwrap();
+        let session = |path: &str, code: &str, contracts: Vec<(String, (usize, usize))>| {
+            let source = Rc::new(Source::new(root.join(path), root.clone()).unwrap());
+            let unit = MutatedUnit {
+                source: source.clone(),
+                contracts,
+                functions: vec![],
+            };
+            let mutants: Vec<(Mutant, bool)> = ["1", "2", "3", "4"]
+                .iter()
+                .filter_map(|orig| code.find(orig))
+                .map(|start| {
+                    let m = Mutant::new(
+                        source.clone(),
+                        MutationType::AssignmentMutation,
+                        start,
+                        start + 1,
+                        "0".to_string(),
+                    )
+                    .unwrap();
+                    (m, true)
+                })
+                .collect();
+            let session = MutationSession {
+                source,
+                operators: vec![],
+                functions: None,
+                contract: None,
+                solc: None,
+                solc_version: None,
+                validation_attempts: None,
+                validation_stages: None,
+                validation_times: None,
+                units: vec![unit],
+                requested_mutants: None,
+                ast_contexts: vec![],
+                sampling_stream: None,
+                mutants: 0..mutants.len(),
+            };
+            (session, mutants)
+        };
+        let b = a.find("contract B").unwrap();
+        let (s1, mut mutants) = session(
+            "A.sol",
+            a,
+            vec![("A".into(), (0, b)), ("B".into(), (b, a.len()))],
+        );
+        let c = lib.find("contract A").unwrap();
+        let (mut s2, lib_mutants) = session("lib/Lib.sol", lib, vec![("A".into(), (c, lib.len()))]);
+        s2.mutants = mutants.len()..mutants.len() + lib_mutants.len();
+        mutants.extend(lib_mutants);
+        let sessions = vec![s1, s2];
+
+        let outdir = root.join("gambit_out");
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+            .with_layout(OutdirLayout::Flat);
+        writer.write_sessions_after(&mutants, &sessions, 0).unwrap();
+
+        // Free functions are grouped under the file's stem, and the contract
+        // `A` of `lib/Lib.sol` gets a suffix
+        let expected = vec![
+            "mutants-A/mutant_1.sol",
+            "mutants-B/mutant_2.sol",
+            "mutants-Lib/mutant_3.sol",
+            "mutants-A-2/mutant_4.sol",
+        ];
+        for (path, (mutant, _)) in expected.iter().zip(&mutants) {
+            let exported = fs::read_to_string(outdir.join(path)).unwrap();
+            assert_eq!(exported, mutant.as_source_string().unwrap());
+            let dir = outdir.join(path).parent().unwrap().to_path_buf();
+            assert!(dir.join(GAMBIT_MARKER_FILENAME).is_file());
+        }
+        assert!(!outdir.join("mutants").exists());
+        let log = fs::read_to_string(outdir.join("mutants.log")).unwrap();
+        let logged: Vec<&str> = log
+            .lines()
+            .map(|line| line.split(',').nth(7).unwrap())
+            .collect();
+        assert_eq!(logged, expected);
+        let results: Value =
+            serde_json::from_str(&fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+                .unwrap();
+        let names: Vec<&str> = results["mutants"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, expected);
+
+        // Patches are named after the mutated file's extension, and the
+        // directories of the earlier run are replaced
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+            .with_layout(OutdirLayout::Flat)
+            .with_export_format(ExportFormat::Patch);
+        writer
+            .write_sessions_after(&mutants[..1], &sessions[..1], 0)
+            .unwrap();
+        assert!(outdir.join("mutants-A/mutant_1.sol.patch").is_file());
+        assert!(!outdir.join("mutants-A/mutant_1.sol").exists());
+        assert!(!outdir.join("mutants-Lib").exists());
+    }
+
+    #[test]
+    fn test_write_results_incrementally() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A {\n    uint x = 1;\n    uint y = 22222222222222222222;\n}\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let mutant = |orig: &str, repl: &str| {
+            let start = code.find(orig).unwrap();
+            let mutant = Mutant::new(
+                source.clone(),
+                MutationType::AssignmentMutation,
+                start,
+                start + orig.len(),
+                repl.to_string(),
+            )
+            .unwrap();
+            (mutant, false)
+        };
+        let mutants = vec![mutant("1", "0"), mutant("22222222222222222222", "0")];
+        let outdir = root.join("gambit_out");
+        fs::create_dir_all(&outdir).unwrap();
+        let results_json = outdir.join("gambit_results.json");
+
+        // The results are written in the layout of `to_string_pretty()`
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None);
+        for mutants in [&mutants[..], &[]] {
+            writer.write_mutants(mutants).unwrap();
+            let json = fs::read_to_string(&results_json).unwrap();
+            let value: Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(json, serde_json::to_string_pretty(&value).unwrap());
+            assert_eq!(value["mutants"].as_array().unwrap().len(), mutants.len());
+        }
+
+        // Diffs longer than the limit are written to their own files
+        writer.write_mutants(&mutants).unwrap();
+        let diffs: Vec<String> = parse_results(&fs::read_to_string(&results_json).unwrap())
+            .unwrap()
+            .mutants
+            .into_iter()
+            .map(|m| m.diff)
+            .collect();
+        let limit = diffs[0].len();
+        assert!(diffs[1].len() > limit);
+        let writer = writer.with_max_inline_diff_bytes(Some(limit));
+        writer.write_mutants(&mutants).unwrap();
+        let json = fs::read_to_string(&results_json).unwrap();
+        let results = parse_results(&json).unwrap();
+        assert_eq!(results.mutants[0].diff, diffs[0]);
+        assert_eq!(results.mutants[0].diff_file, None);
+        assert_eq!(results.mutants[1].diff, "");
+        assert_eq!(
+            results.mutants[1].diff_file.as_deref(),
+            Some("diffs/2.diff")
+        );
+        assert_eq!(
+            fs::read_to_string(outdir.join("diffs/2.diff")).unwrap(),
+            diffs[1]
+        );
+        assert_eq!(
+            validate_results_in(&json, Some(&outdir)),
+            Vec::<String>::new()
+        );
+        fs::remove_file(outdir.join("diffs/2.diff")).unwrap();
+        assert!(validate_results_in(&json, Some(&outdir))[0].contains("cannot read diff_file"));
+
+        // Stale diff files are removed when the results are rewritten
+        writer.write_mutants(&mutants[..1]).unwrap();
+        assert!(!outdir.join("diffs").exists());
+    }
+
+    #[test]
+    fn test_compress_diffs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A {\n    uint x = 1;\n    string s = \"\u{e9}\";\n}\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let mutant = |orig: &str, repl: &str| {
+            let start = code.find(orig).unwrap();
+            let mutant = Mutant::new(
+                source.clone(),
+                MutationType::AssignmentMutation,
+                start,
+                start + orig.len(),
+                repl.to_string(),
+            )
+            .unwrap();
+            (mutant, true)
+        };
+        let mutants = vec![mutant("1", "0"), mutant("\"\u{e9}\"", "\"\"")];
+        let outdir = root.join("gambit_out");
+        let results_json = outdir.join("gambit_results.json");
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None);
+        writer.write_mutants(&mutants).unwrap();
+        let inline: Vec<String> = parse_results(&fs::read_to_string(&results_json).unwrap())
+            .unwrap()
+            .mutants
+            .into_iter()
+            .map(|m| m.diff)
+            .collect();
+
+        // Compressed diffs round-trip byte for byte, also when the results
+        // are written incrementally
+        let writer = writer.with_compress_diffs(true);
+        writer.write_mutants_after(&mutants[..1], 0).unwrap();
+        writer.write_mutants_after(&mutants, 1).unwrap();
+        let json = fs::read_to_string(&results_json).unwrap();
+        for (i, entry) in parse_results(&json).unwrap().mutants.iter().enumerate() {
+            assert_eq!(entry.diff, "");
+            let diff_file = format!("diffs/{}.diff.gz", i + 1);
+            assert_eq!(entry.diff_file.as_deref(), Some(diff_file.as_str()));
+            let compressed = fs::read(outdir.join(&diff_file)).unwrap();
+            assert_eq!(compressed[..2], [0x1f, 0x8b]);
+            assert_eq!(
+                read_diff_file(&outdir.join(&diff_file)).unwrap().as_bytes(),
+                inline[i].as_bytes()
+            );
+        }
+        assert_eq!(
+            validate_results_in(&json, Some(&outdir)),
+            Vec::<String>::new()
+        );
+
+        // With a size limit, only longer diffs are compressed
+        let writer = writer.with_max_inline_diff_bytes(Some(inline[0].len()));
+        writer.write_mutants(&mutants).unwrap();
+        let results = parse_results(&fs::read_to_string(&results_json).unwrap()).unwrap();
+        assert_eq!(results.mutants[0].diff, inline[0]);
+        assert_eq!(results.mutants[0].diff_file, None);
+        assert_eq!(
+            results.mutants[1].diff_file.as_deref(),
+            Some("diffs/2.diff.gz")
+        );
+        assert!(!outdir.join("diffs/1.diff.gz").exists());
+    }
+
+    #[test]
+    fn test_failed_export_keeps_logs_consistent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A {\n    uint x = 1;\n}\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        fs::write(root.join("certora.conf"), "{}\n").unwrap();
+
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let start = code.find('1').unwrap();
+        let mutants: Vec<(Mutant, bool)> = ["2", "3", "4", "5"]
+            .iter()
+            .map(|repl| {
+                let mutant = Mutant::new(
+                    source.clone(),
+                    MutationType::AssignmentMutation,
+                    start,
+                    start + 1,
+                    repl.to_string(),
+                )
+                .unwrap();
+                (mutant, true)
+            })
+            .collect();
+        let outdir = root.join("gambit_out");
+        let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None)
+            .with_sidecars(vec![root.join("certora.conf")], false);
+
+        // Export the first two mutants, then make exporting fail after the
+        // third mutant's file (but not its sidecar) has been written
+        writer.write_mutants(&mutants[..2]).unwrap();
+        fs::remove_file(root.join("certora.conf")).unwrap();
+        let err = writer.write_mutants_after(&mutants, 2).unwrap_err();
+        match err.downcast_ref::<MutantWriterError>() {
+            Some(MutantWriterError::ExportFailed {
+                mid,
+                mutant_bytes,
+                bytes_written,
+                ..
+            }) => {
+                assert_eq!(mid, "3");
+                assert!(*mutant_bytes > 0);
+                assert_eq!(*bytes_written, 0);
+            }
+            _ => panic!("Expected ExportFailed but found {}", err),
+        }
+
+        // The third mutant is rolled back, and the logs cover exactly the
+        // mutants that are on disk
+        let mutants_dir = outdir.join("mutants");
+        assert!(mutants_dir.join("1/A.sol").is_file());
+        assert!(mutants_dir.join("2/A.sol").is_file());
+        assert!(!mutants_dir.join("3").exists());
+        assert!(!mutants_dir.join("4").exists());
+        let log = fs::read_to_string(outdir.join("mutants.log")).unwrap();
+        assert_eq!(log.lines().count(), 2);
+        let results: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+                .unwrap();
+        assert_eq!(results["mutants"].as_array().unwrap().len(), 2);
+        assert_eq!(results["files"][0]["mutant_ids"], json!(["1", "2"]));
+    }
+}
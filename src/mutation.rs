@@ -1,12 +1,16 @@
-use crate::{get_indent, SolAST, Source};
+use crate::{get_indent, MutantAstContext, SolAST, SolASTVisitor, Source, UTF8_BOM};
 use clap::ValueEnum;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::{error, fmt::Display, rc::Rc};
+use serde_json::Value;
+use std::{error, fmt::Display, rc::Rc, string::FromUtf8Error};
 
-/// This struct describes a mutant.
-#[derive(Debug, Clone)]
+/// This struct describes a mutant. Mutants serialize with their `source` as
+/// the path of the original file.
+#[derive(Debug, Clone, Serialize)]
 pub struct Mutant {
     /// The original program's source
+    #[serde(serialize_with = "serialize_source")]
     pub source: Rc<Source>,
 
     /// The mutation operator that was applied to generate this mutant
@@ -25,6 +29,154 @@ pub struct Mutant {
 
     /// The string replacement
     pub repl: String,
+
+    /// The state mutability (`view` or `pure`) of the function this mutant was
+    /// generated in. This is only set when pure/view tagging is enabled (see
+    /// `MutatorConf::tag_pure_view`).
+    pub mutability: Option<String>,
+
+    /// The path to the solc binary used to compile (and validate) this mutant
+    pub solc: Option<String>,
+
+    /// The detected version of the solc binary used for this mutant
+    pub solc_version: Option<String>,
+
+    /// Whether this mutant was validated by compiling it with solc. Mutants
+    /// are not validated when validation is skipped (`--skip_validate`) or when
+    /// the validation budget (`--max_validation_seconds`) runs out.
+    pub validated: bool,
+
+    /// Whether this mutant could not be validated because solc kept failing
+    /// for reasons unrelated to it. Such mutants are passed through
+    /// unvalidated.
+    pub validation_error: bool,
+
+    /// With `--compare_abi`, whether the ABI of the mutated file's contracts
+    /// differs from the original's. This is `None` when the ABIs were not
+    /// compared, e.g., when the mutant was not fully compiled.
+    pub abi_changed: Option<bool>,
+
+    /// The wall-clock time it took to validate the mutant, in milliseconds.
+    /// This is `None` when the mutant was not compiled to validate it.
+    pub validation_ms: Option<u64>,
+
+    /// The language of the mutated source when it is not Solidity (e.g.,
+    /// `vyper`)
+    pub language: Option<String>,
+
+    /// For mutants of a library called by the mutated file (see
+    /// `--include_called_libraries`), the mutated file that pulled the
+    /// library in, relative to its sourceroot
+    pub target_file: Option<String>,
+
+    /// With `--contract_closure`, the contract defining the mutated code,
+    /// which is either the `--contract` or one of its base contracts
+    pub defining_contract: Option<String>,
+
+    /// With `--contract_closure`, the `--contract` whose inheritance chain
+    /// the mutated code belongs to
+    pub filter_contract: Option<String>,
+
+    /// For `BinaryOpMutation` mutants of an operator that resolves to a
+    /// user-defined operator (`using {add as +} for T global`), the function
+    /// implementing the original operator
+    pub user_defined_operator: Option<String>,
+
+    /// With `--emit_original_ast`, the AST nodes enclosing the mutant
+    pub ast_context: Option<MutantAstContext>,
+
+    /// The edits of a mutant that edits several regions of the source (see
+    /// `with_edits()`), in order. This is empty for single-edit mutants,
+    /// whose only edit replaces `start..end` with `repl`.
+    pub edits: Vec<MutantEdit>,
+
+    /// The style of the comment describing the mutation that
+    /// `as_source_string()` inserts above the mutated line
+    pub comment_style: CommentStyle,
+
+    /// Does `as_source_string()` insert a comment describing the mutation
+    /// above the mutated line? This is `false` for the mutants of operators
+    /// that opt out of the comment (see `MutationType::no_comment()`)
+    pub insert_comment: bool,
+}
+
+/// Serialize a mutant's source as the path of the original file
+fn serialize_source<S: serde::Serializer>(source: &Rc<Source>, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&source.filename_as_str())
+}
+
+/// A single edit of a mutant: the source between the byte offsets `start`
+/// (inclusive) and `end` (exclusive) is replaced by `repl`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MutantEdit {
+    /// The offset where the replaced source starts
+    pub start: usize,
+
+    /// The offset where the replaced source ends
+    pub end: usize,
+
+    /// The replacement text
+    pub repl: String,
+}
+
+/// Why edits cannot be combined into a mutant (see `Mutant::with_edits()`)
+#[derive(Debug)]
+pub enum MutantEditError {
+    /// A mutant needs at least one edit
+    NoEdits,
+    /// An edit ends before it starts, or past the end of the source
+    OutOfBounds(MutantEdit),
+    /// An edit starts before the previous edit ends: edits must be ordered
+    /// by position and must not overlap
+    Overlap(MutantEdit, MutantEdit),
+    /// The source text of the edited region is not valid UTF-8
+    InvalidUtf8(FromUtf8Error),
+}
+
+impl Display for MutantEditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MutantEditError::NoEdits => write!(f, "a mutant needs at least one edit"),
+            MutantEditError::OutOfBounds(e) => {
+                write!(f, "edit {}..{} is out of bounds", e.start, e.end)
+            }
+            MutantEditError::Overlap(a, b) => write!(
+                f,
+                "edit {}..{} overlaps or precedes edit {}..{}",
+                b.start, b.end, a.start, a.end
+            ),
+            MutantEditError::InvalidUtf8(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for MutantEditError {}
+
+/// The styles of the comment inserted above a mutated line. NatSpec comments
+/// are picked up by documentation generators as documentation of the
+/// following statement; the other styles are plain comments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentStyle {
+    /// `/// ...`
+    #[default]
+    Natspec,
+    /// `// ...`
+    Line,
+    /// `/* ... */`
+    Block,
+}
+
+impl CommentStyle {
+    /// Comment out `text` in this style. In a block comment, occurrences of
+    /// `*/` in `text` are escaped as `*\/` so that they do not end the comment.
+    pub fn comment(&self, text: &str) -> String {
+        match self {
+            CommentStyle::Natspec => format!("/// {}", text),
+            CommentStyle::Line => format!("// {}", text),
+            CommentStyle::Block => format!("/* {} */", text.replace("*/", "*\\/")),
+        }
+    }
 }
 
 impl Mutant {
@@ -34,52 +186,139 @@ impl Mutant {
         start: usize,
         end: usize,
         repl: String,
-    ) -> Mutant {
-        let orig = String::from_utf8(source.contents()[start..end].to_vec()).unwrap();
-        Mutant {
+    ) -> Result<Mutant, FromUtf8Error> {
+        let orig = String::from_utf8(source.contents()[start..end].to_vec())?;
+        Ok(Mutant {
             source,
             op,
             orig,
             start,
             end,
             repl,
+            mutability: None,
+            solc: None,
+            solc_version: None,
+            validated: false,
+            validation_error: false,
+            abi_changed: None,
+            validation_ms: None,
+            language: None,
+            target_file: None,
+            defining_contract: None,
+            filter_contract: None,
+            user_defined_operator: None,
+            ast_context: None,
+            edits: vec![],
+            comment_style: CommentStyle::default(),
+            insert_comment: !op.no_comment(),
+        })
+    }
+
+    /// Create a mutant that applies several edits to `source` at once. The
+    /// edits must be ordered by position and must not overlap. The mutant's
+    /// `start`, `end`, `orig`, and `repl` describe the whole edited region,
+    /// from the start of the first edit to the end of the last one, so that
+    /// replacing `orig` by `repl` applies every edit.
+    pub fn with_edits(
+        source: Rc<Source>,
+        op: MutationType,
+        edits: Vec<MutantEdit>,
+    ) -> Result<Mutant, MutantEditError> {
+        let len = source.contents().len();
+        for edit in edits.iter() {
+            if edit.end < edit.start || len < edit.end {
+                return Err(MutantEditError::OutOfBounds(edit.clone()));
+            }
+        }
+        for pair in edits.windows(2) {
+            if pair[1].start < pair[0].end {
+                return Err(MutantEditError::Overlap(pair[0].clone(), pair[1].clone()));
+            }
+        }
+        let (start, end) = match (edits.first(), edits.last()) {
+            (Some(first), Some(last)) => (first.start, last.end),
+            _ => return Err(MutantEditError::NoEdits),
+        };
+        let region = &source.contents()[start..end];
+        let repl = String::from_utf8(apply_edits(region, &edits, start))
+            .map_err(MutantEditError::InvalidUtf8)?;
+        let mut mutant =
+            Mutant::new(source, op, start, end, repl).map_err(MutantEditError::InvalidUtf8)?;
+        if edits.len() > 1 {
+            mutant.edits = edits;
+        }
+        Ok(mutant)
+    }
+
+    /// The edits that this mutant applies to its source, in order
+    pub fn edits(&self) -> Vec<MutantEdit> {
+        if self.edits.is_empty() {
+            vec![MutantEdit {
+                start: self.start,
+                end: self.end,
+                repl: self.repl.clone(),
+            }]
+        } else {
+            self.edits.clone()
         }
     }
 
+    /// Apply this mutant's edits to `contents`, the contents of its source
+    pub fn apply_to(&self, contents: &[u8]) -> Vec<u8> {
+        apply_edits(contents, &self.edits(), 0)
+    }
+
     /// Render this mutant as String with the full source file contents
     ///
+    /// The mutant is spliced into the original bytes, so invalid UTF-8
+    /// elsewhere in the file (e.g., in a comment) does not affect the
+    /// mutation; such bytes are replaced by `U+FFFD` in the rendered string.
+    ///
     /// TODO: Cache these contents: this data might be needed multiple times,
     /// and if so this should be cached as it currently involves file IO (though
     /// Source::contents() should also be cached)
     pub fn as_source_string(&self) -> Result<String, Box<dyn error::Error>> {
+        let (line, _) = self.source.get_line_column(self.start)?;
         let contents = self.source.contents();
-        let prelude = &contents[0..self.start];
-        let postlude = &contents[self.end..contents.len()];
-
-        let res = [prelude, self.repl.as_bytes(), postlude].concat();
-        let mut_string = String::from_utf8(res)?;
+        let res = self.apply_to(contents);
+        let mut_string = String::from_utf8_lossy(&res);
+        // A byte order mark must stay at the start of the file, above the
+        // comment inserted on the first line
+        let (bom, mut_string) = match mut_string.strip_prefix(UTF8_BOM) {
+            Some(rest) => (UTF8_BOM, rest),
+            None => ("", mut_string.as_ref()),
+        };
         let mut lines = mut_string.lines();
 
-        let (line, _) = self.source.get_line_column(self.start)?;
         let mut lines2 = vec![];
         for _ in 1..line {
             lines2.push(lines.next().unwrap());
         }
 
-        let mut_line = lines.next().unwrap();
-        let orig_string = String::from_utf8(contents.to_vec())?;
-        let orig_line = orig_string.lines().nth(line - 1).unwrap();
+        // A mutant starting at the end of a file that ends with a newline is
+        // on an empty last line
+        let mut_line = lines.next().unwrap_or_default();
+        let orig_string = String::from_utf8_lossy(contents);
+        let orig_string = orig_string.strip_prefix(UTF8_BOM).unwrap_or(&orig_string);
+        let orig_line = orig_string.lines().nth(line - 1).unwrap_or_default();
 
         let indent = get_indent(mut_line);
-        let comment = format!(
-            "{}/// {}(`{}` |==> `{}`) of: `{}`",
-            indent,
+        // The comment must stay on one line: the lines of a multi-line
+        // original or replacement would otherwise end up outside of it
+        let description = format!(
+            "{}(`{}` |==> `{}`) of: `{}`",
             self.op.to_string(),
-            self.orig.trim(),
-            self.repl,
+            single_line(&self.orig),
+            single_line(&self.repl),
             orig_line.trim()
         );
-        lines2.push(&comment);
+        let comment = match self.language.as_deref() {
+            Some("vyper") => format!("{}# {}", indent, description),
+            _ => format!("{}{}", indent, self.comment_style.comment(&description)),
+        };
+        if self.insert_comment {
+            lines2.push(&comment);
+        }
         lines2.push(mut_line);
 
         for line in lines {
@@ -87,15 +326,94 @@ impl Mutant {
         }
 
         // XXX: this is a hack to avoid trailing newline diffs
-        if contents.last().unwrap() == &b'\n' {
+        if contents.last() == Some(&b'\n') {
             lines2.push("");
         }
-        Ok(lines2.join("\n"))
+        Ok(format!("{}{}", bom, lines2.join("\n")))
     }
 
     pub fn get_line_column(&self) -> Result<(usize, usize), Box<dyn error::Error>> {
         self.source.get_line_column(self.start)
     }
+
+    /// Describe how line numbers of the mutant produced by
+    /// `as_source_string()` map back to lines of the original file.
+    ///
+    /// The one-line comment inserted above the mutated line shifts every
+    /// following line down by one. If the replacement has a different number
+    /// of lines than the original text, the lines after the replaced span are
+    /// shifted again. For mutants without a comment, the first shift is 0.
+    pub fn line_shifts(&self) -> Result<Vec<LineShift>, Box<dyn error::Error>> {
+        let (line, _) = self.get_line_column()?;
+        let orig_newlines = self.orig.matches('\n').count() as isize;
+        let repl_newlines = self.repl.matches('\n').count() as isize;
+        let comment_lines = if self.insert_comment { 1 } else { 0 };
+        let mut shifts = vec![LineShift {
+            after_line: line - 1,
+            delta: comment_lines,
+        }];
+        if orig_newlines != repl_newlines {
+            shifts.push(LineShift {
+                after_line: line + orig_newlines as usize,
+                delta: comment_lines + repl_newlines - orig_newlines,
+            });
+        }
+        Ok(shifts)
+    }
+}
+
+/// Join the lines of `text`, trimmed, with single spaces
+fn single_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Apply ordered, non-overlapping `edits` to `contents`, which starts at
+/// byte offset `offset` of the edited source
+fn apply_edits(contents: &[u8], edits: &[MutantEdit], offset: usize) -> Vec<u8> {
+    let mut result = Vec::with_capacity(contents.len());
+    let mut pos = 0;
+    for edit in edits {
+        result.extend_from_slice(&contents[pos..edit.start - offset]);
+        result.extend_from_slice(edit.repl.as_bytes());
+        pos = edit.end - offset;
+    }
+    result.extend_from_slice(&contents[pos..]);
+    result
+}
+
+/// A shift of line numbers between an original file and its mutant: original
+/// lines after `after_line` are found `delta` lines later in the mutant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LineShift {
+    /// The last original line that is not affected by this shift
+    pub after_line: usize,
+
+    /// The number of lines that following lines are shifted by
+    pub delta: isize,
+}
+
+/// Map a line of a mutant back to the line of the original file it came from,
+/// using the mutant's `line_shifts`. Lines of the mutant that were inserted
+/// or replaced map to the first line of the mutated span.
+pub fn original_line(shifts: &[LineShift], mutant_line: usize) -> usize {
+    let mut result = mutant_line;
+    for (i, shift) in shifts.iter().enumerate() {
+        if mutant_line as isize > shift.after_line as isize + shift.delta {
+            result = (mutant_line as isize - shift.delta) as usize;
+            // Lines inserted by a multi-line replacement stay within its span
+            if let Some(next) = shifts.get(i + 1) {
+                result = result.min(next.after_line);
+            }
+        } else if i == 0 && mutant_line > shift.after_line {
+            // Lines of the inserted comment map to the mutated line
+            result = shift.after_line + 1;
+        }
+    }
+    result
 }
 
 impl Display for Mutant {
@@ -120,46 +438,84 @@ pub trait Mutation {
     /// Check if this mutation applies to this AST node
     fn applies_to(&self, node: &SolAST) -> bool;
 
-    /// Generate all mutants of a given node by this agent
-    fn mutate(&self, node: &SolAST, source: Rc<Source>) -> Vec<Mutant>;
+    /// Generate all mutants of a given node by this agent. This fails if the
+    /// source text that a mutant is built from is not valid UTF-8.
+    fn mutate(&self, node: &SolAST, source: Rc<Source>) -> Result<Vec<Mutant>, FromUtf8Error>;
 }
 
-/// Kinds of mutations.
+/// Kinds of mutations. This declaration is the single source of the names of
+/// each operator: its long name (e.g., `BinaryOpMutation`, see
+/// `to_string()`) is the variant name, its short name (e.g.,
+/// `binary-op-mutation`, see `short_name()`) is the variant name in kebab
+/// case, its aliases are the `alias`es of its `value` attribute, and its
+/// description is its doc comment.
 #[derive(Hash, Eq, PartialEq, Clone, Copy, Debug, ValueEnum, Deserialize, Serialize)]
 pub enum MutationType {
+    /// Offset or swap the array whose `.length` is read
+    ArrayLengthMutation,
+    /// Replace the right hand side of an assignment
     AssignmentMutation,
+    /// Replace a binary operator with another
+    #[value(aliases = ["aor", "arithmetic-operator-replacement"])]
     BinaryOpMutation,
+    /// Off-by-one an integer literal that is compared against, assigned, or
+    /// used as an array size
+    #[value(alias = "literal-boundary-mutation")]
+    BoundaryLiteralMutation,
+    /// Remove an integer cast, or narrow or widen it
+    CastMutation,
+    /// Replace an expression statement with a no-op (`assert(true)`)
     DeleteExpressionMutation,
+    /// Change a `delegatecall()` to a `call()`
     ElimDelegateMutation,
+    /// Replace a call by each of its arguments of the call's type
     FunctionCallMutation,
+    /// Mutate the condition of an `if` statement
     IfStatementMutation,
+    /// Compare a string with itself in a `keccak256` string comparison, or
+    /// replace the comparison with `true` or `false`
+    KeccakEqualityMutation,
+    /// Swap `&&` and `||`, or replace the operation with either operand
+    #[value(aliases = ["lor", "logical-operator-replacement"])]
+    LogicalOperatorMutation,
+    /// Set a `for` loop's literal bound to 0, 1, or one more
+    LoopBoundMutation,
+    /// Alter the condition of a `require` statement
     RequireMutation,
+    /// Delete the `if` guarding a custom error `revert`, delete the `revert`,
+    /// or swap its same-typed arguments
+    RevertMutation,
+    /// Swap the order of a function's arguments
     SwapArgumentsFunctionMutation,
+    /// Swap the operands of a non-commutative operator
     SwapArgumentsOperatorMutation,
+    /// Offset a time literal's quantity by one or step its unit, or flip
+    /// `+`/`-` next to `block.timestamp`
+    TimeMutation,
+    /// Empty a `catch` body, or swap `catch Error` and `catch`
+    TryCatchMutation,
+    /// Replace a unary operator with another
     UnaryOperatorMutation,
 }
 
 impl ToString for MutationType {
+    /// The long name of this operator, as written to `gambit_results.json`,
+    /// `mutants.log`, and the comment above each mutated line
     fn to_string(&self) -> String {
-        let str = match self {
-            MutationType::AssignmentMutation => "AssignmentMutation",
-            MutationType::BinaryOpMutation => "BinaryOpMutation",
-            MutationType::DeleteExpressionMutation => "DeleteExpressionMutation",
-            MutationType::ElimDelegateMutation => "ElimDelegateMutation",
-            MutationType::FunctionCallMutation => "FunctionCallMutation",
-            MutationType::IfStatementMutation => "IfStatementMutation",
-            MutationType::RequireMutation => "RequireMutation",
-            MutationType::SwapArgumentsFunctionMutation => "SwapArgumentsFunctionMutation",
-            MutationType::SwapArgumentsOperatorMutation => "SwapArgumentsOperatorMutation",
-            MutationType::UnaryOperatorMutation => "UnaryOperatorMutation",
-        };
-        str.into()
+        format!("{:?}", self)
     }
 }
 
 impl Mutation for MutationType {
     fn applies_to(&self, node: &SolAST) -> bool {
         match self {
+            MutationType::ArrayLengthMutation => {
+                // `.length` accesses are offset in place, while swapping the
+                // array whose length is read needs the enclosing function's
+                // parameters
+                return is_array_length(node)
+                    || node.node_type() == Some("FunctionDefinition".into());
+            }
             MutationType::AssignmentMutation => {
                 if let Some(n) = node.node_type() {
                     return n == "Assignment";
@@ -170,6 +526,12 @@ impl Mutation for MutationType {
                     return n == "BinaryOperation";
                 }
             }
+            MutationType::BoundaryLiteralMutation => {
+                return !boundary_literals(node).is_empty();
+            }
+            MutationType::CastMutation => {
+                return integer_cast_type(node).is_some();
+            }
             MutationType::DeleteExpressionMutation => {
                 if let Some(n) = node.node_type() {
                     return n == "ExpressionStatement";
@@ -192,15 +554,27 @@ impl Mutation for MutationType {
                 );
             }
             MutationType::FunctionCallMutation => {
-                if let Some(n) = node.node_type() {
-                    return n == "FunctionCall" && !node.arguments().is_empty();
-                }
+                return !same_type_call_arguments(node).is_empty();
             }
             MutationType::IfStatementMutation => {
                 if let Some(n) = node.node_type() {
                     return n == "IfStatement";
                 }
             }
+            MutationType::KeccakEqualityMutation => {
+                // `keccak256(bytes(a)) == keccak256(bytes(b))`, or `!=`
+                return keccak_equality(node).is_some();
+            }
+            MutationType::LogicalOperatorMutation => {
+                return node.node_type() == Some("BinaryOperation".into())
+                    && matches!(node.operator().as_deref(), Some("&&" | "||"));
+            }
+            MutationType::LoopBoundMutation => {
+                // A `for` loop whose condition compares against an integer
+                // literal (e.g., `i < 3`)
+                return node.node_type() == Some("ForStatement".into())
+                    && !loop_bound_literals(node).is_empty();
+            }
             MutationType::RequireMutation => {
                 return node.node_type().map_or_else(
                     || false,
@@ -214,6 +588,11 @@ impl Mutation for MutationType {
                     },
                 );
             }
+            MutationType::RevertMutation => {
+                // A `revert` of a custom error, or an `if` guarding one
+                return node.node_type() == Some("RevertStatement".into())
+                    || guarded_revert(node).is_some();
+            }
             MutationType::SwapArgumentsFunctionMutation => {
                 if let Some(n) = node.node_type() {
                     return n == "FunctionCall" && node.arguments().len() > 1;
@@ -231,6 +610,16 @@ impl Mutation for MutationType {
                         );
                 }
             }
+            MutationType::TimeMutation => {
+                // A time literal (e.g., `30 days`), or an addition or
+                // subtraction involving `block.timestamp`
+                return time_literal(node).is_some() || is_timestamp_arithmetic(node);
+            }
+            MutationType::TryCatchMutation => {
+                if let Some(n) = node.node_type() {
+                    return n == "TryStatement" && node.clauses().len() > 1;
+                }
+            }
             MutationType::UnaryOperatorMutation => {
                 if let Some(n) = node.node_type() {
                     return n == "UnaryOperation";
@@ -247,15 +636,68 @@ impl Mutation for MutationType {
     /// * `node` - The Solidity AST node to mutate
     /// * `source` - The original source file: we use this to generate a new
     ///   source file
-    fn mutate(&self, node: &SolAST, source: Rc<Source>) -> Vec<Mutant> {
+    fn mutate(&self, node: &SolAST, source: Rc<Source>) -> Result<Vec<Mutant>, FromUtf8Error> {
+        self.replacements(node, source.contents())?
+            .into_iter()
+            .map(|e| Mutant::new(source.clone(), *self, e.start, e.end, e.repl))
+            .collect()
+    }
+}
+
+impl MutationType {
+    /// The edits of the mutants that `mutate()` generates at `node`, one per
+    /// mutant. Both `mutate()` and `estimate()` are built on this, so that
+    /// they agree on the replacements of every operator. This fails if the
+    /// source text of an edit, or that a replacement is built from, is not
+    /// valid UTF-8.
+    pub fn replacements(
+        &self,
+        node: &SolAST,
+        contents: &[u8],
+    ) -> Result<Vec<MutantEdit>, FromUtf8Error> {
         if !self.applies_to(node) {
-            return vec![];
+            return Ok(vec![]);
         }
-        match self {
+        let edit = |start: usize, end: usize, repl: String| MutantEdit { start, end, repl };
+        let edits: Vec<MutantEdit> = match self {
+            MutationType::ArrayLengthMutation => {
+                if is_array_length(node) {
+                    let (start, end) = node.get_bounds();
+                    let length = node.get_text(contents)?;
+                    ["+", "-"]
+                        .iter()
+                        .map(|op| edit(start, end, format!("({} {} 1)", length, op)))
+                        .collect()
+                } else {
+                    // Read the length of another array parameter with the
+                    // same element type instead
+                    let params = array_parameters(node);
+                    let mut edits = vec![];
+                    for access in node.get_node("body").traverse(&ArrayLengthCollector, ()) {
+                        let array = access.expression();
+                        let param = array
+                            .get_node("referencedDeclaration")
+                            .get_object()
+                            .and_then(|id| {
+                                params.iter().find(|p| Some(&p.0) == id.as_i64().as_ref())
+                            });
+                        if let Some((id, _, element_type)) = param {
+                            let (start, end) = array.get_bounds();
+                            edits.extend(
+                                params
+                                    .iter()
+                                    .filter(|(i, _, t)| i != id && t == element_type)
+                                    .map(|(_, name, _)| edit(start, end, name.clone())),
+                            );
+                        }
+                    }
+                    edits
+                }
+            }
             MutationType::AssignmentMutation => {
                 let rhs = node.right_hand_side();
                 let node_kind = rhs.node_kind();
-                let orig = rhs.get_text(source.contents());
+                let orig = rhs.get_text(contents)?;
                 let replacements: Vec<&str> = if let Some(kind) = node_kind {
                     if &kind == "bool" {
                         vec!["true", "false"]
@@ -275,14 +717,21 @@ impl Mutation for MutationType {
                 let (s, e) = rhs.get_bounds();
                 replacements
                     .iter()
-                    .map(|r| Mutant::new(source.clone(), *self, s, e, r.to_string()))
+                    .map(|r| edit(s, e, r.to_string()))
                     .collect()
             }
             MutationType::BinaryOpMutation => {
+                // Arithmetic operators never apply to addresses, fixed-size
+                // byte arrays, booleans, etc., so every replacement of, e.g.,
+                // `msg.sender == owner` would be rejected by solc
+                if has_non_numeric_operands(node) {
+                    return Ok(vec![]);
+                }
                 let orig = node.operator().unwrap();
                 let orig = String::from(orig.trim());
 
-                let ops: Vec<&str> = ["+", "-", "*", "/", "%", "**"]
+                let ops: Vec<&str> = self
+                    .replacement_tokens()
                     .iter()
                     .filter(|v| !orig.eq(*v))
                     .copied()
@@ -291,48 +740,103 @@ impl Mutation for MutationType {
                 let (_, endl) = node.left_expression().get_bounds();
                 let (startr, _) = node.right_expression().get_bounds();
                 ops.iter()
-                    .map(|op| Mutant::new(source.clone(), *self, endl, startr, op.to_string()))
+                    .map(|op| edit(endl, startr, op.to_string()))
+                    .collect()
+            }
+
+            MutationType::BoundaryLiteralMutation => {
+                // Shift each literal by one in both directions, within the
+                // values that its context allows
+                let mut edits = vec![];
+                for boundary in boundary_literals(node) {
+                    let (s, e) = boundary.literal.get_bounds();
+                    edits.extend(boundary.replacements().into_iter().map(|r| edit(s, e, r)));
+                }
+                edits
+            }
+
+            MutationType::CastMutation => {
+                // Only the type name of the cast is replaced: removing it
+                // leaves the parenthesized argument, and its width is changed
+                // one step in each direction within the same signedness
+                let (family, width) = integer_cast_type(node).unwrap();
+                let (start, end) = node.expression().get_bounds();
+                let narrower = INTEGER_CAST_WIDTHS.iter().rev().find(|w| **w < width);
+                let wider = INTEGER_CAST_WIDTHS.iter().find(|w| **w > width);
+                let mut replacements = vec![String::new()];
+                replacements.extend(
+                    narrower
+                        .into_iter()
+                        .chain(wider)
+                        .map(|w| format!("{}{}", family, w)),
+                );
+                replacements
+                    .into_iter()
+                    .map(|r| edit(start, end, r))
                     .collect()
             }
 
+            MutationType::KeccakEqualityMutation => {
+                // The idiom is mutated as a single comparison of `a` and
+                // `b`: comparing `a` with itself, or replacing the whole
+                // comparison with a constant
+                let (a, b) = keccak_equality(node).unwrap();
+                let a_text = a.get_text(contents)?;
+                let mut edits = vec![];
+                if a_text != b.get_text(contents)? {
+                    let (start, end) = b.get_bounds();
+                    edits.push(edit(start, end, a_text));
+                }
+                let (start, end) = node.get_bounds();
+                for constant in ["true", "false"] {
+                    edits.push(edit(start, end, constant.to_string()));
+                }
+                edits
+            }
+
             MutationType::DeleteExpressionMutation => {
                 let (start, end) = node.get_bounds();
                 let empty_expression_statement = "assert(true)".to_string();
-                vec![Mutant::new(
-                    source,
-                    *self,
-                    start,
-                    end,
-                    empty_expression_statement,
-                )]
+                vec![edit(start, end, empty_expression_statement)]
             }
             MutationType::ElimDelegateMutation => {
                 let (_, endl) = node.expression().expression().get_bounds();
                 let (_, endr) = node.expression().get_bounds();
 
-                vec![Mutant::new(
-                    source,
-                    *self,
-                    endl + 1,
-                    endr,
-                    "call".to_string(),
-                )]
+                vec![edit(endl + 1, endr, "call".to_string())]
             }
 
-            // TODO: Should we enable this? I'm not sure if this is the best mutation operator
             MutationType::FunctionCallMutation => {
-                // if let Some(arg) = node.arguments().choose(rand) {
-                //     node.replace_in_source(source, arg.get_text(source))
-                // } else {
-                //     node.get_text(source)
-                // }
-
-                vec![] // For now I'm removing this operator: not sure what it does!
+                // A call that returns a value is replaced by each of its
+                // arguments of the same type (`add(a, b)` becomes `a` and
+                // `b`). Calls that return nothing are left to
+                // DeleteExpressionMutation
+                let (start, end) = node.get_bounds();
+                let mut replacements: Vec<String> = vec![];
+                for arg in same_type_call_arguments(node) {
+                    let text = arg.get_text(contents)?;
+                    // Compound arguments are parenthesized, so that they bind
+                    // like the call they replace
+                    let text = match arg.node_type().as_deref() {
+                        Some(
+                            "Identifier" | "Literal" | "MemberAccess" | "IndexAccess"
+                            | "FunctionCall" | "TupleExpression",
+                        ) => text,
+                        _ => format!("({})", text),
+                    };
+                    if !replacements.contains(&text) {
+                        replacements.push(text);
+                    }
+                }
+                replacements
+                    .into_iter()
+                    .map(|r| edit(start, end, r))
+                    .collect()
             }
 
             MutationType::IfStatementMutation => {
                 let cond = node.condition();
-                let orig = cond.get_text(source.contents());
+                let orig = cond.get_text(contents)?;
                 let bs: Vec<&str> = ["true", "false"]
                     .iter()
                     .filter(|v| !orig.eq(*v))
@@ -341,23 +845,96 @@ impl Mutation for MutationType {
 
                 let (start, end) = cond.get_bounds();
 
-                bs.iter()
-                    .map(|r| Mutant::new(source.clone(), *self, start, end, r.to_string()))
-                    .collect()
+                bs.iter().map(|r| edit(start, end, r.to_string())).collect()
+            }
+
+            MutationType::LogicalOperatorMutation => {
+                // Swap `&&` and `||`, and replace the operation with either
+                // operand, as if it always short-circuited one way
+                let other = match node.operator().as_deref() {
+                    Some("&&") => "||",
+                    _ => "&&",
+                };
+                let (left, right) = (node.left_expression(), node.right_expression());
+                let (_, endl) = left.get_bounds();
+                let (startr, _) = right.get_bounds();
+                let (start, end) = node.get_bounds();
+                let mut edits = vec![edit(endl, startr, other.to_string())];
+                let left = left.get_text(contents)?;
+                let right = right.get_text(contents)?;
+                if right != left {
+                    edits.push(edit(start, end, right));
+                }
+                edits.push(edit(start, end, left));
+                edits
+            }
+
+            MutationType::LoopBoundMutation => {
+                // Replace each literal of the loop condition with its
+                // off-by-one neighborhood: 0 (the loop never runs), 1, and one
+                // more than the literal. Literals in the loop's body are left
+                // untouched
+                let mut edits = vec![];
+                for literal in loop_bound_literals(node) {
+                    let value = integer_literal_value(&literal).unwrap();
+                    let mut replacements: Vec<u128> = vec![0, 1];
+                    replacements.extend(value.checked_add(1));
+                    replacements.retain(|r| *r != value);
+                    let (start, end) = literal.get_bounds();
+                    edits.extend(
+                        replacements
+                            .into_iter()
+                            .map(|r| edit(start, end, r.to_string())),
+                    );
+                }
+                edits
             }
 
             MutationType::RequireMutation => {
                 let arg = &node.arguments()[0];
-                let orig = arg.get_text(source.contents());
+                let orig = arg.get_text(contents)?;
                 let bs: Vec<&str> = ["true", "false"]
                     .iter()
                     .filter(|v| !orig.eq(*v))
                     .copied()
                     .collect();
                 let (start, end) = arg.get_bounds();
-                bs.iter()
-                    .map(|r| Mutant::new(source.clone(), *self, start, end, r.to_string()))
-                    .collect()
+                bs.iter().map(|r| edit(start, end, r.to_string())).collect()
+            }
+
+            MutationType::RevertMutation => {
+                let (start, end) = node.get_bounds();
+                if let Some(body) = guarded_revert(node) {
+                    // Delete the guard, making the revert unconditional
+                    vec![edit(start, end, body.get_text(contents)?)]
+                } else {
+                    // Replace the revert with a no-op
+                    let mut edits = vec![edit(start, end, "assert(true)".to_string())];
+
+                    // Swap each pair of distinct arguments of the same type
+                    let args = node.get_node("errorCall").arguments();
+                    for (i, first) in args.iter().enumerate() {
+                        for second in args[i + 1..].iter() {
+                            let ty = first.get_type_descs().and_then(|t| t.type_string());
+                            let other_ty = second.get_type_descs().and_then(|t| t.type_string());
+                            if ty.is_none() || ty != other_ty {
+                                continue;
+                            }
+                            let (first_start, first_end) = first.get_bounds();
+                            let (second_start, second_end) = second.get_bounds();
+                            let first_arg = &contents[first_start..first_end];
+                            let second_arg = &contents[second_start..second_end];
+                            if first_arg == second_arg {
+                                continue;
+                            }
+                            let between = &contents[first_end..second_start];
+                            let repl =
+                                String::from_utf8([second_arg, between, first_arg].concat())?;
+                            edits.push(edit(first_start, second_end, repl));
+                        }
+                    }
+                    edits
+                }
             }
 
             MutationType::SwapArgumentsFunctionMutation => {
@@ -392,17 +969,93 @@ impl Mutation for MutationType {
                 let end = right_end;
                 let op = node.operator().unwrap();
                 let op = format!(" {} ", op.trim());
-                let contents = source.contents();
-                let left_contents =
-                    String::from_utf8(contents[left_start..left_end].to_vec()).unwrap();
-                let right_contents =
-                    String::from_utf8(contents[right_start..right_end].to_vec()).unwrap();
+                let left_contents = String::from_utf8(contents[left_start..left_end].to_vec())?;
+                let right_contents = String::from_utf8(contents[right_start..right_end].to_vec())?;
 
                 let mut repl: String = right_contents;
                 repl.push_str(&op);
                 repl.push_str(&left_contents);
 
-                vec![Mutant::new(source.clone(), *self, start, end, repl)]
+                vec![edit(start, end, repl)]
+            }
+
+            MutationType::TimeMutation => {
+                let (start, end) = node.get_bounds();
+                if let Some((quantity, unit)) = time_literal(node) {
+                    // Offset the quantity by one, and replace the unit with
+                    // each adjacent unit
+                    let mut replacements = vec![];
+                    if let Ok(value) = quantity.replace('_', "").parse::<u128>() {
+                        if let Some(v) = value.checked_add(1) {
+                            replacements.push(format!("{} {}", v, TIME_UNITS[unit]));
+                        }
+                        if value > 0 {
+                            replacements.push(format!("{} {}", value - 1, TIME_UNITS[unit]));
+                        }
+                    }
+                    let smaller = unit.checked_sub(1).map(|u| TIME_UNITS[u]);
+                    let larger = TIME_UNITS.get(unit + 1);
+                    replacements.extend(
+                        smaller
+                            .into_iter()
+                            .chain(larger.copied())
+                            .map(|u| format!("{} {}", quantity, u)),
+                    );
+                    replacements
+                        .into_iter()
+                        .map(|r| edit(start, end, r))
+                        .collect()
+                } else {
+                    // Flip the addition or subtraction
+                    let op = if node.operator().unwrap() == "+" {
+                        "-"
+                    } else {
+                        "+"
+                    };
+                    let (_, endl) = node.left_expression().get_bounds();
+                    let (startr, _) = node.right_expression().get_bounds();
+                    vec![edit(endl, startr, op.to_string())]
+                }
+            }
+
+            MutationType::TryCatchMutation => {
+                // The first clause is the success clause of the `try`: only
+                // the `catch` clauses that follow it are mutated
+                let clauses = node.clauses();
+                let catch_clauses = &clauses[1..];
+
+                // Delete each catch body in turn, skipping bodies that are
+                // already empty
+                let mut edits: Vec<MutantEdit> = catch_clauses
+                    .iter()
+                    .map(|c| c.block())
+                    .filter(|b| !b.statements().is_empty())
+                    .map(|b| {
+                        let (start, end) = b.get_bounds();
+                        edit(start, end, "{}".to_string())
+                    })
+                    .collect();
+
+                // Swap the bodies of `catch Error(...)` and a bare `catch`
+                let error_clause = catch_clauses
+                    .iter()
+                    .find(|c| c.error_name() == Some("Error".into()));
+                let bare_clause = catch_clauses.iter().find(|c| {
+                    c.error_name() == Some("".into())
+                        && c.parameters().get_object().unwrap_or_default().is_null()
+                });
+                if let (Some(error_clause), Some(bare_clause)) = (error_clause, bare_clause) {
+                    let mut bodies = [error_clause.block(), bare_clause.block()];
+                    bodies.sort_by_key(|b| b.get_bounds().0);
+                    let (first_start, first_end) = bodies[0].get_bounds();
+                    let (second_start, second_end) = bodies[1].get_bounds();
+                    let first_body = &contents[first_start..first_end];
+                    let between = &contents[first_end..second_start];
+                    let second_body = &contents[second_start..second_end];
+                    let repl = String::from_utf8([second_body, between, first_body].concat())?;
+                    edits.push(edit(first_start, second_end, repl));
+                }
+                edits
             }
 
             MutationType::UnaryOperatorMutation => {
@@ -414,7 +1067,7 @@ impl Mutation for MutationType {
                     .expect("Unary operation must have an operator!");
 
                 let (start, end) = node.get_bounds();
-                let is_prefix = source.contents()[start] == op.as_bytes()[0];
+                let is_prefix = contents[start] == op.as_bytes()[0];
                 let replacements: Vec<&str> = if is_prefix { prefix_ops } else { suffix_ops }
                     .iter()
                     .filter(|v| !op.eq(*v))
@@ -428,119 +1081,819 @@ impl Mutation for MutationType {
 
                 replacements
                     .iter()
-                    .map(|r| Mutant::new(source.clone(), *self, start, end, r.to_string()))
+                    .map(|r| edit(start, end, r.to_string()))
                     .collect()
             }
+        };
+        // The replaced text becomes the mutant's `orig`
+        for e in edits.iter() {
+            String::from_utf8(contents[e.start..e.end].to_vec())?;
         }
+        Ok(edits)
+    }
+
+    /// The number of mutants that `mutate()` generates at `node`, without
+    /// building any mutant (see `--estimate`). Nodes whose source text is not
+    /// valid UTF-8, which `mutate()` fails on, have none.
+    pub fn estimate(&self, node: &SolAST, contents: &[u8]) -> usize {
+        self.replacements(node, contents)
+            .map_or(0, |edits| edits.len())
+    }
+
+    /// The tokens that this operator replaces an operator with, which the
+    /// `replacements` of its configuration may restrict it to (see
+    /// `MutationSpec`). Operators whose replacements depend on the mutated
+    /// code have none.
+    pub fn replacement_tokens(&self) -> &'static [&'static str] {
+        match self {
+            MutationType::BinaryOpMutation => &["+", "-", "*", "/", "%", "**"],
+            _ => &[],
+        }
+    }
+
+    /// The short name of this operator, which selects it with `--mutations`
+    pub fn short_name(&self) -> String {
+        self.to_possible_value().unwrap().get_name().to_string()
+    }
+
+    /// The other names that select this operator with `--mutations`
+    pub fn aliases(&self) -> Vec<String> {
+        self.to_possible_value()
+            .unwrap()
+            .get_name_and_aliases()
+            .skip(1)
+            .map(|alias| alias.to_string())
+            .collect()
+    }
+
+    /// What this operator mutates
+    pub fn description(&self) -> String {
+        self.to_possible_value()
+            .unwrap()
+            .get_help()
+            .map(|help| help.to_string())
+            .unwrap_or_default()
+    }
+
+    /// The operator whose long name (see `to_string()`) is `name`
+    pub fn from_name(name: &str) -> Option<MutationType> {
+        MutationType::value_variants()
+            .iter()
+            .find(|op| op.to_string() == name)
+            .copied()
     }
-}
 
-impl MutationType {
     pub fn default_mutation_operators() -> Vec<MutationType> {
         vec![
+            // MutationType::ArrayLengthMutation,
             MutationType::AssignmentMutation,
             MutationType::BinaryOpMutation,
+            // MutationType::BoundaryLiteralMutation,
+            // MutationType::CastMutation,
             MutationType::DeleteExpressionMutation,
             MutationType::ElimDelegateMutation,
             MutationType::FunctionCallMutation,
             MutationType::IfStatementMutation,
+            // MutationType::LogicalOperatorMutation,
+            // MutationType::LoopBoundMutation,
             MutationType::RequireMutation,
             // MutationType::SwapArgumentsFunctionMutation,
             MutationType::SwapArgumentsOperatorMutation,
+            // MutationType::TimeMutation,
+            MutationType::TryCatchMutation,
             MutationType::UnaryOperatorMutation,
         ]
     }
+
+    /// Check if this operator is experimental: its behavior may change or it
+    /// may be removed between releases, and it is only run with
+    /// `--allow_experimental`
+    pub fn is_experimental(&self) -> bool {
+        matches!(self, MutationType::SwapArgumentsFunctionMutation)
+    }
+
+    /// Check if the mutants of this operator are exported without the
+    /// comment describing the mutation. Operators opt out when the comment
+    /// line would interfere with their edit, e.g. when inserting statements
+    /// or editing inline assembly. No current operator does.
+    pub fn no_comment(&self) -> bool {
+        false
+    }
+
+    /// All experimental operators
+    pub fn experimental_mutation_operators() -> Vec<MutationType> {
+        MutationType::value_variants()
+            .iter()
+            .filter(|op| op.is_experimental())
+            .copied()
+            .collect()
+    }
 }
 
-/// This testing module defines and uses the testing infrastructure, allowing
-/// for varying degrees of testing flexibility.
-///
-/// First, we define two types of assert functions:
-///
-/// 1. `assert_num_mutants_for_XXX`: make an assertion for the number of mutants
-///    generated by a provided set of operators for the given code
-///
-/// 2. `assert_exact_mutants_for_XXX`: make an assertion on the exact mutants
-///    generated by a provided set of operators for the given code
-///
-/// Next, we define two forms of code to make assertions about:
-///
-/// 1. **Statements:** we represent a list of statements as a `Vec<&str>`, and
-///    this makes it easy to write a simple program to mutate, e.g.,
-///
-///    ```
-///    vec!["uint256 a = 1;", "uint256 b = 2;", "uint256 c = a + b;"]
-///    ```
-///
-///    Functions `assert_exact_mutants_for_statements()` and
-///    `assert_num_mutants_for_statements()` use this program form to make
-///    assertions about mutations
-///
-/// 2. **Full Source:** sometimes we want to be able to specify a full source file.
-///    This is more verbose than using statements (as we need to write more
-///    boilerplate), but we have maximal flexibility in the code we are mutating.
-///
-///    Funcctions `assert_exact_mutants_for_source()` and
-///    `assert_num_mutants_for_source()` use this program form to make
-///    assertions about mutations
-#[cfg(test)]
-mod test {
-    use crate::test_util::*;
-    use crate::{MutationType, MutationType::*, Mutator, MutatorConf, Solc, Source};
-    use std::collections::HashSet;
-    use std::path::PathBuf;
-    use std::rc::Rc;
-    use std::{error, path::Path};
-    use tempfile::Builder;
+/// The type of the value of the expression `node`
+fn expression_type(node: &SolAST) -> Option<String> {
+    node.get_node("typeDescriptions").get_string("typeString")
+}
 
-    #[test]
-    pub fn test_assignment_mutation() -> Result<(), Box<dyn error::Error>> {
-        let ops = vec![AssignmentMutation];
-        assert_exact_mutants_for_statements(
-            &vec!["uint256 x;", "x = 3;"],
-            &ops,
-            &vec!["(-1)", "0", "1"],
-        );
-        assert_exact_mutants_for_statements(&vec!["int256 x;", "x = 1;"], &ops, &vec!["(-1)", "0"]);
-        assert_exact_mutants_for_statements(&vec!["int256 x;", "x = 0;"], &ops, &vec!["(-1)", "1"]);
-        // FIXME: The following three test cases are BROKEN!! Currently these
-        // all get mutated to [-1, 1, 0, false, true] because they are not
-        // 'number's. Validation would strip out the true/false. We would want
-        // constant propagation to strip out the 0 in `x = -0`
-        assert_num_mutants_for_statements(
-            &vec!["int256 x;", "x = -2;"],
-            &vec![AssignmentMutation],
-            5,
-        );
-        assert_num_mutants_for_statements(
-            &vec!["int256 x;", "x = -1;"],
-            &vec![AssignmentMutation],
-            5,
-        );
-        assert_num_mutants_for_statements(
-            &vec!["int256 x;", "x = -0;"],
-            &vec![AssignmentMutation],
-            5,
-        );
+/// If `node` calls a function (rather than converting a type or constructing
+/// a struct) that returns a single value, the arguments of the call that have
+/// the type of that value
+fn same_type_call_arguments(node: &SolAST) -> Vec<SolAST> {
+    if node.node_type().as_deref() != Some("FunctionCall")
+        || node.node_kind().as_deref() != Some("functionCall")
+    {
+        return vec![];
+    }
+    match expression_type(node) {
+        Some(ty) if !ty.starts_with("tuple(") => node
+            .arguments()
+            .into_iter()
+            .filter(|arg| expression_type(arg).as_ref() == Some(&ty))
+            .collect(),
+        _ => vec![],
+    }
+}
 
-        assert_exact_mutants_for_statements(&vec!["bool b;", "b = true;"], &ops, &vec!["false"]);
-        assert_exact_mutants_for_statements(&vec!["bool b;", "b = false;"], &ops, &vec!["true"]);
+/// If `node` compares the hashes of two byte strings, as in the string
+/// comparison idiom `keccak256(bytes(a)) == keccak256(bytes(b))` (or `!=`),
+/// return the compared expressions `a` and `b`
+fn keccak_equality(node: &SolAST) -> Option<(SolAST, SolAST)> {
+    if node.node_type().as_deref() != Some("BinaryOperation")
+        || !matches!(node.operator().as_deref(), Some("==" | "!="))
+    {
+        return None;
+    }
+    Some((
+        hashed_bytes_argument(&node.left_expression())?,
+        hashed_bytes_argument(&node.right_expression())?,
+    ))
+}
 
-        Ok(())
+/// If `node` is a call of the builtin `keccak256` on a `bytes` conversion,
+/// like `keccak256(bytes(a))`, return the converted expression `a`
+fn hashed_bytes_argument(node: &SolAST) -> Option<SolAST> {
+    let callee = node.expression();
+    // Builtins are referenced with negative ids, unlike a user-defined
+    // function that shadows `keccak256`
+    let builtin = callee
+        .get_node("referencedDeclaration")
+        .get_object()
+        .and_then(|id| id.as_i64())
+        .is_some_and(|id| id < 0);
+    if node.node_type().as_deref() != Some("FunctionCall")
+        || callee.node_type().as_deref() != Some("Identifier")
+        || callee.name().as_deref() != Some("keccak256")
+        || !builtin
+    {
+        return None;
     }
+    let [conversion] = node.arguments().try_into().ok()?;
+    // `typeName` is an `ElementaryTypeName` node since solc 0.6, and a
+    // string before
+    let elementary = conversion.expression();
+    let type_name = elementary
+        .get_node("typeName")
+        .name()
+        .or_else(|| elementary.get_string("typeName"));
+    if conversion.node_type().as_deref() != Some("FunctionCall")
+        || conversion.node_kind().as_deref() != Some("typeConversion")
+        || conversion.expression().node_type().as_deref() != Some("ElementaryTypeNameExpression")
+        || type_name.as_deref() != Some("bytes")
+    {
+        return None;
+    }
+    let [argument] = conversion.arguments().try_into().ok()?;
+    Some(argument)
+}
 
-    #[test]
-    pub fn test_binary_op_mutation() -> Result<(), Box<dyn error::Error>> {
-        let ops = vec![BinaryOpMutation];
-        let repls = vec!["+", "-", "*", "/", "%", "**"];
-        // Closure to drop the given operator for he set of replacements
-        let without = |s: &str| {
-            let r: Vec<&str> = repls
-                .iter()
-                .filter(|r| !s.eq(**r))
-                .map(|s| s.clone())
-                .collect();
-            r
+/// Check if a node reads the `length` of an array (e.g., `xs.length`)
+fn is_array_length(node: &SolAST) -> bool {
+    node.node_type() == Some("MemberAccess".into())
+        && node.get_string("memberName") == Some("length".into())
+        && array_element_type(&node.expression()).is_some()
+}
+
+/// Get the element type of an array-typed expression or declaration (e.g.,
+/// `uint256` for `uint256[] memory`), ignoring its data location
+fn array_element_type(node: &SolAST) -> Option<String> {
+    let type_string = node.get_node("typeDescriptions").get_string("typeString")?;
+    let mut ty = type_string.as_str();
+    for location in [" memory", " calldata", " storage ref", " storage pointer"] {
+        ty = ty.strip_suffix(location).unwrap_or(ty);
+    }
+    if !ty.ends_with(']') {
+        return None;
+    }
+    ty.rfind('[').map(|i| ty[..i].to_string())
+}
+
+/// Get the id, name, and element type of each array parameter of a function
+fn array_parameters(function: &SolAST) -> Vec<(i64, String, String)> {
+    function
+        .parameters()
+        .get_node("parameters")
+        .get_object()
+        .and_then(|ps| ps.as_array().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .map(SolAST::new)
+        .filter_map(|p| {
+            let id = p.get_node("id").get_object()?.as_i64()?;
+            Some((id, p.name()?, array_element_type(&p)?))
+        })
+        .collect()
+}
+
+/// Collects the array `.length` accesses of an AST
+struct ArrayLengthCollector;
+
+impl SolASTVisitor<(), SolAST> for ArrayLengthCollector {
+    fn visit_node(&self, node: &SolAST, _arg: &()) -> Option<SolAST> {
+        if is_array_length(node) {
+            Some(node.clone())
+        } else {
+            None
+        }
+    }
+}
+
+/// Get the body of an `if` statement without an `else` branch whose body is
+/// just a `revert` of a custom error, either on its own (`if (c) revert E();`)
+/// or in a block (`if (c) { revert E(); }`)
+fn guarded_revert(node: &SolAST) -> Option<SolAST> {
+    if node.node_type()? != "IfStatement" || node.false_body().node_type().is_some() {
+        return None;
+    }
+    let body = node.true_body();
+    let statement = match body.node_type()?.as_str() {
+        "Block" => match body.statements().as_slice() {
+            [statement] => statement.clone(),
+            _ => return None,
+        },
+        _ => body.clone(),
+    };
+    (statement.node_type()? == "RevertStatement").then_some(body)
+}
+
+/// Check if a binary operation is a comparison (e.g., `x < 10`)
+fn is_relational(node: &SolAST) -> bool {
+    node.operator()
+        .is_some_and(|op| ["<", "<=", ">", ">=", "==", "!="].contains(&op.as_str()))
+}
+
+/// Get the value of an integer literal such as `10`, `1_000`, or `0xff`.
+/// Literals with a subdenomination (e.g., `1 ether`), or that are not integers
+/// that fit in a `u128`, return `None`.
+fn integer_literal_value(node: &SolAST) -> Option<u128> {
+    if !node.is_literal() || node.node_kind() != Some("number".into()) {
+        return None;
+    }
+    if node.get_string("subdenomination").is_some() {
+        return None;
+    }
+    let value = node.get_string("value")?.replace('_', "");
+    match value.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// An integer literal that `BoundaryLiteralMutation` shifts by one
+struct BoundaryLiteral {
+    /// The literal
+    literal: SolAST,
+
+    /// Whether the literal is used as a signed integer, so that `0` may be
+    /// shifted to `-1`
+    signed: bool,
+
+    /// Whether the literal must not be shifted to `0` (e.g., the length of a
+    /// static array type)
+    nonzero: bool,
+
+    /// The largest value of the integer type the literal is used as, if it
+    /// is smaller than `u128::MAX`, so that it is not shifted past it
+    max: Option<u128>,
+}
+
+impl BoundaryLiteral {
+    /// The values that the literal is shifted to, by one in both directions
+    fn replacements(&self) -> Vec<String> {
+        let value = integer_literal_value(&self.literal).unwrap();
+        let mut replacements = vec![];
+        if let Some(v) = value.checked_add(1) {
+            if self.max.is_none_or(|max| v <= max) {
+                replacements.push(v.to_string());
+            }
+        }
+        if value > 1 || (value == 1 && !self.nonzero) {
+            replacements.push((value - 1).to_string());
+        } else if value == 0 && self.signed {
+            replacements.push("-1".to_string());
+        }
+        replacements
+    }
+}
+
+/// Get the integer literals of a node that `BoundaryLiteralMutation` shifts:
+/// the operands of a comparison (e.g., `10` in `i < 10`), the value assigned
+/// to a variable (`x = 10`, `uint8 x = 10`), and array sizes (`new
+/// uint256[](10)`, `uint256[10]`). Whether a literal is signed is decided by
+/// the other operand or the assigned variable.
+fn boundary_literals(node: &SolAST) -> Vec<BoundaryLiteral> {
+    // The literal is used as the integer type of `context`
+    let boundary = |literal: SolAST, context: &SolAST, nonzero: bool| BoundaryLiteral {
+        literal,
+        signed: is_signed_integer(context),
+        nonzero,
+        max: context
+            .get_type_descs()
+            .and_then(|t| t.type_string())
+            .and_then(|t| integer_type_max(t.trim_matches('"'))),
+    };
+    let unsized_context = SolAST::new(Value::Null);
+    let candidates = match node.node_type().as_deref() {
+        Some("BinaryOperation") if is_relational(node) => {
+            let (left, right) = (node.left_expression(), node.right_expression());
+            vec![
+                boundary(left.clone(), &right, false),
+                boundary(right, &left, false),
+            ]
+        }
+        Some("Assignment") => {
+            vec![boundary(
+                node.right_hand_side(),
+                &node.left_hand_side(),
+                false,
+            )]
+        }
+        Some("VariableDeclaration") => {
+            vec![boundary(node.get_node("value"), node, false)]
+        }
+        Some("VariableDeclarationStatement") => {
+            let declaration = SolAST::new(
+                node.get_object()
+                    .map_or(Value::Null, |o| o["declarations"][0].clone()),
+            );
+            vec![boundary(node.get_node("initialValue"), &declaration, false)]
+        }
+        Some("FunctionCall")
+            if node.expression().node_type().as_deref() == Some("NewExpression")
+                && node
+                    .expression()
+                    .get_node("typeName")
+                    .node_type()
+                    .as_deref()
+                    == Some("ArrayTypeName") =>
+        {
+            node.arguments()
+                .into_iter()
+                .map(|size| boundary(size, &unsized_context, false))
+                .collect()
+        }
+        Some("ArrayTypeName") => vec![boundary(node.get_node("length"), &unsized_context, true)],
+        _ => vec![],
+    };
+    candidates
+        .into_iter()
+        .filter(|b| integer_literal_value(&b.literal).is_some())
+        .collect()
+}
+
+/// The largest value of the integer type `type_string` (e.g., `255` for
+/// `uint8`), or `None` if it is not an integer type or its largest value is
+/// at least `u128::MAX`
+fn integer_type_max(type_string: &str) -> Option<u128> {
+    let (signed, width) = match type_string.strip_prefix("uint") {
+        Some(width) => (false, width),
+        None => (true, type_string.strip_prefix("int")?),
+    };
+    let bits: u32 = if width.is_empty() {
+        256
+    } else {
+        width.parse().ok()?
+    };
+    let bits = if signed { bits - 1 } else { bits };
+    (bits < 128).then(|| (1u128 << bits) - 1)
+}
+
+/// Get the integer literals of a `for` loop's condition (e.g., `3` in
+/// `i < 3`), in order
+fn loop_bound_literals(node: &SolAST) -> Vec<SolAST> {
+    node.condition().traverse(&IntegerLiteralCollector, ())
+}
+
+/// Collects the integer literals of an AST
+struct IntegerLiteralCollector;
+
+impl SolASTVisitor<(), SolAST> for IntegerLiteralCollector {
+    fn visit_node(&self, node: &SolAST, _arg: &()) -> Option<SolAST> {
+        integer_literal_value(node).map(|_| node.clone())
+    }
+}
+
+/// The time units that `TimeMutation` steps between, from smallest to largest
+const TIME_UNITS: [&str; 5] = ["seconds", "minutes", "hours", "days", "weeks"];
+
+/// Get the quantity (e.g., `30`) and the index of the unit in `TIME_UNITS` of
+/// a literal with a time subdenomination (e.g., `30 days`)
+fn time_literal(node: &SolAST) -> Option<(String, usize)> {
+    if !node.is_literal() || node.node_kind() != Some("number".into()) {
+        return None;
+    }
+    let subdenomination = node.get_string("subdenomination")?;
+    let unit = TIME_UNITS.iter().position(|u| *u == subdenomination)?;
+    Some((node.get_string("value")?, unit))
+}
+
+/// Check if a node is an addition or subtraction with an operand that
+/// involves `block.timestamp` (e.g., `block.timestamp + delay`)
+fn is_timestamp_arithmetic(node: &SolAST) -> bool {
+    node.node_type() == Some("BinaryOperation".into())
+        && node.operator().is_some_and(|op| op == "+" || op == "-")
+        && [node.left_expression(), node.right_expression()]
+            .into_iter()
+            .any(|operand| !operand.traverse(&BlockTimestampCollector, ()).is_empty())
+}
+
+/// Collects the `block.timestamp` accesses of an AST
+struct BlockTimestampCollector;
+
+impl SolASTVisitor<(), SolAST> for BlockTimestampCollector {
+    fn visit_node(&self, node: &SolAST, _arg: &()) -> Option<SolAST> {
+        let is_block_timestamp = node.node_type() == Some("MemberAccess".into())
+            && node.get_string("memberName") == Some("timestamp".into())
+            && node.expression().name() == Some("block".into());
+        is_block_timestamp.then(|| node.clone())
+    }
+}
+
+/// The integer widths that `CastMutation` steps between
+const INTEGER_CAST_WIDTHS: [usize; 6] = [8, 16, 32, 64, 128, 256];
+
+/// Get the signedness family (`uint` or `int`) and width of an explicit
+/// integer cast such as `uint128(x)`
+fn integer_cast_type(node: &SolAST) -> Option<(String, usize)> {
+    if node.node_type()? != "FunctionCall"
+        || node.get_string("kind")? != "typeConversion"
+        || node.arguments().len() != 1
+    {
+        return None;
+    }
+    let expression = node.expression();
+    if expression.node_type()? != "ElementaryTypeNameExpression" {
+        return None;
+    }
+    // The type string of the type name is `type(uint128)`, even for `uint`
+    let type_string = expression
+        .get_node("typeDescriptions")
+        .get_string("typeString")?;
+    let ty = type_string.strip_prefix("type(")?.strip_suffix(')')?;
+    let family = if ty.starts_with("uint") {
+        "uint"
+    } else {
+        "int"
+    };
+    let width = ty.strip_prefix(family)?.parse().ok()?;
+    Some((family.to_string(), width))
+}
+
+/// Check if an operand of a binary operation is known (from its resolved
+/// type) not to be a number, e.g., an address, a `bytes32`, or a `bool`.
+/// Operations that resolve to a user-defined operator are left to
+/// `restrict_user_defined_operators()`.
+fn has_non_numeric_operands(node: &SolAST) -> bool {
+    if node.get_object().is_some_and(|o| o["function"].is_i64()) {
+        return false;
+    }
+    [node.left_expression(), node.right_expression()]
+        .iter()
+        .filter_map(|operand| operand.get_type_descs().and_then(|t| t.type_string()))
+        .map(|t| t.trim_matches('"').to_string())
+        .any(|t| {
+            t != "null"
+                && !["int", "uint", "rational_const", "fixed", "ufixed"]
+                    .iter()
+                    .any(|prefix| t.starts_with(prefix))
+        })
+}
+
+/// Check if the type of an expression is a signed integer (e.g., `int256`)
+fn is_signed_integer(node: &SolAST) -> bool {
+    node.get_type_descs()
+        .and_then(|t| t.type_string())
+        .is_some_and(|t| t.trim_matches('"').starts_with("int"))
+}
+
+/// Check if a mutant of `node` is likely equivalent to the original program
+/// by cheap syntactic checks: its replacement is the original text up to
+/// whitespace (e.g., swapping the operands of `a - a`), or it turns an
+/// operation on two integer literals into one that folds to the same value
+/// (e.g., replacing `2 * 1` with `2 / 1`)
+pub fn is_likely_equivalent(node: &SolAST, mutant: &Mutant) -> bool {
+    if !mutant.edits.is_empty() {
+        return false;
+    }
+    if strip_whitespace(&mutant.orig) == strip_whitespace(&mutant.repl) {
+        return true;
+    }
+    if node.node_type().as_deref() != Some("BinaryOperation") {
+        return false;
+    }
+    let (Some(left), Some(right), Some(op)) = (
+        integer_literal_value(&node.left_expression()),
+        integer_literal_value(&node.right_expression()),
+        node.operator(),
+    ) else {
+        return false;
+    };
+    let mutated = match mutant.op {
+        MutationType::BinaryOpMutation => fold_literal_operation(left, mutant.repl.trim(), right),
+        MutationType::SwapArgumentsOperatorMutation => fold_literal_operation(right, &op, left),
+        _ => None,
+    };
+    mutated.is_some() && mutated == fold_literal_operation(left, &op, right)
+}
+
+/// Remove the whitespace of a code fragment, except within string literals
+fn strip_whitespace(code: &str) -> String {
+    let mut stripped = String::with_capacity(code.len());
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in code.chars() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None if c.is_whitespace() => continue,
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None => (),
+        }
+        stripped.push(c);
+    }
+    stripped
+}
+
+/// The value of a constant operation on integer literals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LiteralValue {
+    Number(i128),
+    Bool(bool),
+}
+
+/// Fold the binary operation `left op right` on two integer literals.
+/// Solidity evaluates literal expressions with arbitrary precision over the
+/// rationals, so this gives up (`None`) on overflow, on inexact divisions, and
+/// on the operators it doesn't model.
+fn fold_literal_operation(left: u128, op: &str, right: u128) -> Option<LiteralValue> {
+    let left = i128::try_from(left).ok()?;
+    let right = i128::try_from(right).ok()?;
+    let number = match op {
+        "+" => left.checked_add(right),
+        "-" => left.checked_sub(right),
+        "*" => left.checked_mul(right),
+        "/" => (right != 0 && left % right == 0).then(|| left / right),
+        "%" => (right != 0).then(|| left % right),
+        "**" => u32::try_from(right)
+            .ok()
+            .and_then(|exponent| left.checked_pow(exponent)),
+        "<" => return Some(LiteralValue::Bool(left < right)),
+        "<=" => return Some(LiteralValue::Bool(left <= right)),
+        ">" => return Some(LiteralValue::Bool(left > right)),
+        ">=" => return Some(LiteralValue::Bool(left >= right)),
+        "==" => return Some(LiteralValue::Bool(left == right)),
+        "!=" => return Some(LiteralValue::Bool(left != right)),
+        _ => None,
+    };
+    number.map(LiteralValue::Number)
+}
+
+/// This testing module defines and uses the testing infrastructure, allowing
+/// for varying degrees of testing flexibility.
+///
+/// First, we define two types of assert functions:
+///
+/// 1. `assert_num_mutants_for_XXX`: make an assertion for the number of mutants
+///    generated by a provided set of operators for the given code
+///
+/// 2. `assert_exact_mutants_for_XXX`: make an assertion on the exact mutants
+///    generated by a provided set of operators for the given code
+///
+/// Next, we define two forms of code to make assertions about:
+///
+/// 1. **Statements:** we represent a list of statements as a `Vec<&str>`, and
+///    this makes it easy to write a simple program to mutate, e.g.,
+///
+///    ```
+///    vec!["uint256 a = 1;", "uint256 b = 2;", "uint256 c = a + b;"]
+///    ```
+///
+///    Functions `assert_exact_mutants_for_statements()` and
+///    `assert_num_mutants_for_statements()` use this program form to make
+///    assertions about mutations
+///
+/// 2. **Full Source:** sometimes we want to be able to specify a full source file.
+///    This is more verbose than using statements (as we need to write more
+///    boilerplate), but we have maximal flexibility in the code we are mutating.
+///
+///    Funcctions `assert_exact_mutants_for_source()` and
+///    `assert_num_mutants_for_source()` use this program form to make
+///    assertions about mutations
+#[cfg(test)]
+mod test {
+    use super::integer_type_max;
+    use crate::test_util::*;
+    use crate::{
+        mutate_source, original_line, CommentStyle, LineShift, Mutant, MutantEdit, MutantEditError,
+        MutantWriter, Mutation, MutationType, MutationType::*, Mutator, MutatorConf, SolAST,
+        SolASTVisitor, Solc, Source,
+    };
+    use serde_json::json;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+    use std::{error, path::Path};
+    use tempfile::Builder;
+
+    #[test]
+    pub fn test_operator_names() {
+        use clap::ValueEnum;
+        // The row of the README's table of operators that lists `op`
+        let readme_row = |op: &MutationType| {
+            include_str!("../README.md")
+                .lines()
+                .find(|line| line.starts_with(&format!("| **{}** ", op.short_name())))
+        };
+        let mut names = HashSet::new();
+        for op in MutationType::value_variants() {
+            let long = op.to_string();
+            assert_eq!(MutationType::from_name(&long), Some(*op));
+            assert_eq!(serde_json::to_value(op).unwrap(), json!(long));
+            assert!(!op.description().is_empty(), "{} has no description", long);
+            let row = readme_row(op).unwrap_or_else(|| panic!("README.md does not list {}", long));
+            for short in std::iter::once(op.short_name()).chain(op.aliases()) {
+                assert_eq!(MutationType::from_str(&short, true), Ok(*op));
+                assert!(
+                    row.contains(&format!("{}`", short)) || row.contains(&format!("{}**", short))
+                );
+                assert!(
+                    names.insert(short.clone()),
+                    "`{}` names two operators",
+                    short
+                );
+            }
+            assert!(names.insert(long.clone()), "`{}` names two operators", long);
+        }
+    }
+
+    /// Every node of an AST
+    struct AllNodes;
+
+    impl SolASTVisitor<(), SolAST> for AllNodes {
+        fn visit_node(&self, node: &SolAST, _: &()) -> Option<SolAST> {
+            (node.src().is_some() && node.node_type().is_some()).then(|| node.clone())
+        }
+    }
+
+    #[test]
+    pub fn test_estimate_matches_mutate() {
+        use clap::ValueEnum;
+        // The benchmarks' ASTs as compiled for the regression outputs, which
+        // do not need solc
+        let mut files = HashSet::new();
+        let mut total = 0;
+        for config in std::fs::read_dir("resources/regressions").unwrap() {
+            let input_json = config.unwrap().path().join("input_json");
+            let Ok(entries) = std::fs::read_dir(input_json) else {
+                continue;
+            };
+            for entry in entries {
+                let path = entry.unwrap().path();
+                if path.to_str().unwrap().ends_with(".ast.json") {
+                    let ast: serde_json::Value =
+                        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+                    let filename = ast["absolutePath"].as_str().unwrap().to_string();
+                    if files.insert(filename.clone()) {
+                        let source = Rc::new(
+                            Source::new(PathBuf::from(filename), PathBuf::from(".")).unwrap(),
+                        );
+                        for node in SolAST::new(ast).traverse(&AllNodes, ()) {
+                            for op in MutationType::value_variants() {
+                                let mutants = op.mutate(&node, source.clone()).unwrap();
+                                assert_eq!(
+                                    op.estimate(&node, source.contents()),
+                                    mutants.len(),
+                                    "{} at {:?} of {}",
+                                    op.to_string(),
+                                    node.src(),
+                                    source.filename_as_str()
+                                );
+                                total += mutants.len();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        assert!(files.len() > 10);
+        assert!(total > 100);
+    }
+
+    #[test]
+    pub fn test_array_length_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![ArrayLengthMutation];
+        assert_exact_mutants_for_source(
+            NESTED_ARRAY_LOOPS,
+            &ops,
+            &vec![
+                "(xs.length + 1)",
+                "(xs.length - 1)",
+                "(ys.length + 1)",
+                "(ys.length - 1)",
+                // `xs` and `ys` swapped
+                "ys",
+                "xs",
+            ],
+        );
+        // Arrays with different element types are not swapped
+        assert_exact_mutants_for_source(
+            &NESTED_ARRAY_LOOPS.replace("uint256[] memory ys", "bool[] memory ys"),
+            &ops,
+            &vec![
+                "(xs.length + 1)",
+                "(xs.length - 1)",
+                "(ys.length + 1)",
+                "(ys.length - 1)",
+            ],
+        );
+        Ok(())
+    }
+
+    const NESTED_ARRAY_LOOPS: &str = "
+contract C {
+    function f(uint256[] memory xs, uint256[] memory ys) public pure returns (uint256 n) {
+        for (uint256 i = 0; i < xs.length; i++) {
+            for (uint256 j = 0; j < ys.length; j++) {
+                n += 1;
+            }
+        }
+    }
+}";
+
+    #[test]
+    pub fn test_assignment_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![AssignmentMutation];
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 x;", "x = 3;"],
+            &ops,
+            &vec!["(-1)", "0", "1"],
+        );
+        assert_exact_mutants_for_statements(&vec!["int256 x;", "x = 1;"], &ops, &vec!["(-1)", "0"]);
+        assert_exact_mutants_for_statements(&vec!["int256 x;", "x = 0;"], &ops, &vec!["(-1)", "1"]);
+        // FIXME: The following three test cases are BROKEN!! Currently these
+        // all get mutated to [-1, 1, 0, false, true] because they are not
+        // 'number's. Validation would strip out the true/false. We would want
+        // constant propagation to strip out the 0 in `x = -0`
+        assert_num_mutants_for_statements(
+            &vec!["int256 x;", "x = -2;"],
+            &vec![AssignmentMutation],
+            5,
+        );
+        assert_num_mutants_for_statements(
+            &vec!["int256 x;", "x = -1;"],
+            &vec![AssignmentMutation],
+            5,
+        );
+        assert_num_mutants_for_statements(
+            &vec!["int256 x;", "x = -0;"],
+            &vec![AssignmentMutation],
+            5,
+        );
+
+        assert_exact_mutants_for_statements(&vec!["bool b;", "b = true;"], &ops, &vec!["false"]);
+        assert_exact_mutants_for_statements(&vec!["bool b;", "b = false;"], &ops, &vec!["true"]);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_binary_op_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![BinaryOpMutation];
+        let repls = vec!["+", "-", "*", "/", "%", "**"];
+        // Closure to drop the given operator for he set of replacements
+        let without = |s: &str| {
+            let r: Vec<&str> = repls
+                .iter()
+                .filter(|r| !s.eq(**r))
+                .map(|s| s.clone())
+                .collect();
+            r
         };
         assert_exact_mutants_for_statements(&vec!["uint256 x = 1 + 2;"], &ops, &without("+"));
         assert_exact_mutants_for_statements(&vec!["uint256 x = 2 - 1;"], &ops, &without("-"));
@@ -552,14 +1905,509 @@ mod test {
     }
 
     #[test]
-    pub fn test_delete_expression_mutation() -> Result<(), Box<dyn error::Error>> {
-        let ops = vec![DeleteExpressionMutation];
-        assert_exact_mutants_for_statements(&vec!["gasleft();"], &ops, &vec!["assert(true)"]);
-        assert_exact_mutants_for_statements(
-            &vec!["uint256 x = 0;", "x = 3;"],
-            &ops,
-            &vec!["assert(true)"],
-        );
+    pub fn test_binary_op_mutation_non_numeric_operands() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![BinaryOpMutation];
+        // No arithmetic operator applies to addresses, fixed-size byte
+        // arrays, or booleans
+        assert_exact_mutants_for_statements(
+            &vec![
+                "address owner = address(1);",
+                "require(msg.sender == owner);",
+            ],
+            &ops,
+            &vec![],
+        );
+        assert_exact_mutants_for_statements(
+            &vec![
+                "bytes32 h = bytes32(0);",
+                "require(h != bytes32(uint256(1)));",
+            ],
+            &ops,
+            &vec![],
+        );
+        assert_exact_mutants_for_statements(
+            &vec!["bool b = true;", "require(b == false);"],
+            &ops,
+            &vec![],
+        );
+        // Numbers keep the full set of replacements
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 x = 1;", "require(x == 2);"],
+            &ops,
+            &vec!["+", "-", "*", "/", "%", "**"],
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_binary_op_mutation_user_defined_operators() -> Result<(), Box<dyn error::Error>> {
+        // `a + b` calls `add`, and only `-` is also defined for `Fixed`: the
+        // other replacements would not compile
+        let source = "// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.19;
+
+type Fixed is uint256;
+
+using {add as +, sub as -, eq as ==} for Fixed global;
+
+function add(Fixed a, Fixed b) pure returns (Fixed) {
+    return Fixed.wrap(Fixed.unwrap(a) + Fixed.unwrap(b));
+}
+
+function sub(Fixed a, Fixed b) pure returns (Fixed) {
+    return Fixed.wrap(Fixed.unwrap(a) - Fixed.unwrap(b));
+}
+
+function eq(Fixed a, Fixed b) pure returns (bool) {
+    return Fixed.unwrap(a) == Fixed.unwrap(b);
+}
+
+contract C {
+    function f(Fixed a, Fixed b) public pure returns (Fixed) {
+        require(a == b);
+        return a + b;
+    }
+}
+";
+        let mutator = apply_mutation_to_source(source, &[BinaryOpMutation])?;
+        let user_defined: Vec<(&str, Option<&str>)> = mutator
+            .mutants()
+            .iter()
+            .filter(|m| m.user_defined_operator.is_some())
+            .map(|m| (m.repl.as_str(), m.user_defined_operator.as_deref()))
+            .collect();
+        assert_eq!(user_defined, vec![("-", Some("add"))]);
+        // The user-defined `a == b` is not mutated, while the built-in `+`,
+        // `-`, and `==` of the operator functions keep all of their
+        // replacements
+        assert_eq!(mutator.mutants().len(), 1 + 5 + 5 + 6);
+        Ok(())
+    }
+
+    /// Replace `orig` in `contents` with `repl` and check that `line_shifts()`
+    /// maps every unchanged line of the mutant back to its original line, and
+    /// the inserted comment back to the mutated line
+    fn assert_line_shifts(contents: &str, orig: &str, repl: &str, expected: Vec<LineShift>) {
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("/tmp/Lines.sol"),
+            PathBuf::from("/tmp"),
+            contents.as_bytes().to_vec(),
+        ));
+        let start = contents.find(orig).unwrap();
+        let mutant = Mutant::new(
+            source,
+            TryCatchMutation,
+            start,
+            start + orig.len(),
+            repl.to_string(),
+        )
+        .unwrap();
+        let shifts = mutant.line_shifts().unwrap();
+        assert_eq!(shifts, expected);
+
+        let (mutated_line, _) = mutant.get_line_column().unwrap();
+        let comment_lines = mutated_line..mutated_line + shifts[0].delta as usize;
+        let original_lines: Vec<&str> = contents.lines().collect();
+        let mutant_string = mutant.as_source_string().unwrap();
+        for (i, line) in mutant_string.lines().enumerate() {
+            let expected = if comment_lines.contains(&(i + 1)) {
+                mutated_line
+            } else if let Some(j) = original_lines.iter().position(|l| l == &line) {
+                j + 1
+            } else {
+                continue;
+            };
+            assert_eq!(
+                original_line(&shifts, i + 1),
+                expected,
+                "mutant line {}: `{}`",
+                i + 1,
+                line
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_mutants_at_end_of_file() {
+        let mutant = |contents: &str, start: usize, end: usize, repl: &str| {
+            let source = Rc::new(Source::from_contents(
+                PathBuf::from("/tmp/End.sol"),
+                PathBuf::from("/tmp"),
+                contents.as_bytes().to_vec(),
+            ));
+            Mutant::new(
+                source,
+                DeleteExpressionMutation,
+                start,
+                end,
+                repl.to_string(),
+            )
+            .unwrap()
+        };
+
+        // The last statement ends with the file's last byte
+        let contents = "contract C {}\nx = 1;";
+        let start = contents.find("x = 1;").unwrap();
+        let m = mutant(contents, start, contents.len(), "/// x = 1;");
+        assert_eq!(m.get_line_column().unwrap(), (2, 1));
+        assert_eq!(
+            m.as_source_string().unwrap(),
+            "contract C {}\n/// DeleteExpressionMutation(`x = 1;` |==> `/// x = 1;`) of: `x = 1;`\n/// x = 1;"
+        );
+        assert!(MutantWriter::diff_mutant(&m)
+            .unwrap()
+            .contains("+/// x = 1;"));
+
+        // Mutants starting at the last byte, and at the very end, of a file
+        let m = mutant(contents, contents.len() - 1, contents.len(), "");
+        assert_eq!(m.get_line_column().unwrap(), (2, 6));
+        assert_eq!(m.source.get_line_column(contents.len()).unwrap(), (2, 7));
+        assert!(m.source.get_line_column(contents.len() + 1).is_err());
+
+        // Columns at the start of a line
+        assert_eq!(m.source.get_line_column(start + 1).unwrap(), (2, 2));
+        assert_eq!(m.source.get_line_column(start - 1).unwrap(), (1, 14));
+
+        // A file without newlines
+        let m = mutant("x = 1;", 4, 6, "2;");
+        assert_eq!(m.get_line_column().unwrap(), (1, 5));
+        assert!(m.as_source_string().unwrap().ends_with("x = 2;"));
+
+        // A file ending with a newline, mutated at its very end
+        let contents = "x = 1;\n";
+        let m = mutant(contents, contents.len(), contents.len(), "y = 2;");
+        assert_eq!(m.get_line_column().unwrap(), (2, 1));
+        assert!(m.as_source_string().unwrap().contains("y = 2;"));
+    }
+
+    #[test]
+    pub fn test_get_line_column_matches_scan() {
+        use rand::{Rng, SeedableRng};
+        // The line and column of `pos`, found by scanning `contents` up to it
+        let scan = |contents: &[u8], pos: usize| {
+            let (mut line, mut col) = (1, 1);
+            for c in &contents[..pos] {
+                if *c == b'\n' {
+                    (line, col) = (line + 1, 1);
+                } else {
+                    col += 1;
+                }
+            }
+            (line, col)
+        };
+        let mut files: Vec<Source> = [
+            "benchmarks/10Power/TenPower.sol",
+            "benchmarks/BinaryOpMutation/BinaryOpMutation.sol",
+            "benchmarks/ByteOrderMark/ByteOrderMark.sol",
+            "benchmarks/MultipleContracts/C.sol",
+        ]
+        .iter()
+        .map(|f| Source::new(PathBuf::from(f), PathBuf::from(".")).unwrap())
+        .collect();
+        for contents in ["x", "\n", "\n\n", "x\ny", "a\r\nb\r\n", "\u{feff}x = 1;\n"] {
+            files.push(Source::from_contents(
+                PathBuf::from("/tmp/Scan.sol"),
+                PathBuf::from("/tmp"),
+                contents.as_bytes().to_vec(),
+            ));
+        }
+
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+        for source in files.iter() {
+            let len = source.contents().len();
+            // The first byte, the last byte, the end of the file, every
+            // newline and the byte after it, and random positions
+            let mut positions = vec![0, len - 1, len];
+            for (i, c) in source.contents().iter().enumerate() {
+                if *c == b'\n' {
+                    positions.extend([i, i + 1]);
+                }
+            }
+            positions.extend((0..200).map(|_| rng.gen_range(0..=len)));
+            for pos in positions {
+                let (line, col) = source.get_line_column(pos).unwrap();
+                assert_eq!(
+                    (line, col),
+                    scan(source.contents(), pos),
+                    "position {} of {}",
+                    pos,
+                    source.filename().display()
+                );
+                assert!(col >= 1);
+                assert_eq!(source.get_position(line, col), Some(pos));
+            }
+            assert!(source.get_line_column(len + 1).is_err());
+        }
+
+        // An empty file has no lines
+        let empty = Source::from_contents(
+            PathBuf::from("/tmp/Empty.sol"),
+            PathBuf::from("/tmp"),
+            vec![],
+        );
+        assert!(empty.get_line_column(0).is_err());
+    }
+
+    #[test]
+    pub fn test_line_shifts() {
+        let contents = "contract C {\n  function f() public {\n    uint x = 1 + 2;\n    if (x > 0) {\n      x = 3;\n    }\n    x = 4;\n  }\n}\n";
+        assert_line_shifts(
+            contents,
+            "1 + 2",
+            "1 - 2",
+            vec![LineShift {
+                after_line: 2,
+                delta: 1,
+            }],
+        );
+        // Replacing a multi-line span with a single line
+        assert_line_shifts(
+            contents,
+            "{\n      x = 3;\n    }",
+            "{}",
+            vec![
+                LineShift {
+                    after_line: 3,
+                    delta: 1,
+                },
+                LineShift {
+                    after_line: 6,
+                    delta: -1,
+                },
+            ],
+        );
+        // Replacing a single line with multiple lines
+        assert_line_shifts(
+            contents,
+            "x = 4;",
+            "x = 5;\n    x = 6;",
+            vec![
+                LineShift {
+                    after_line: 6,
+                    delta: 1,
+                },
+                LineShift {
+                    after_line: 7,
+                    delta: 2,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    pub fn test_boundary_literal_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![BoundaryLiteralMutation];
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 x = block.number;", "if (x < 10) { x++; }"],
+            &ops,
+            &vec!["11", "9"],
+        );
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 x = block.number;", "if (100 >= x) { x++; }"],
+            &ops,
+            &vec!["101", "99"],
+        );
+        // Unsigned comparisons with zero are not mutated to -1
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 x = block.number;", "if (x > 0) { x++; }"],
+            &ops,
+            &vec!["1"],
+        );
+        assert_exact_mutants_for_statements(
+            &vec!["int256 x = int256(block.number);", "if (x > 0) { x++; }"],
+            &ops,
+            &vec!["1", "-1"],
+        );
+        // The initialization and the bound of a loop
+        assert_exact_mutants_for_statements(
+            &vec!["for (uint256 i = 0; i < 10; i++) {}"],
+            &ops,
+            &vec!["1", "11", "9"],
+        );
+        // Assigned values are signed if the variable is
+        assert_exact_mutants_for_statements(&vec!["uint8 x = 0;"], &ops, &vec!["1"]);
+        assert_exact_mutants_for_statements(&vec!["int8 x = 0;"], &ops, &vec!["1", "-1"]);
+        assert_exact_mutants_for_statements(
+            &vec!["uint8 x;", "x = 200;"],
+            &ops,
+            &vec!["201", "199"],
+        );
+        // Literals are not shifted past the largest value of their type
+        assert_exact_mutants_for_statements(&vec!["uint8 x = 255;"], &ops, &vec!["254"]);
+        assert_exact_mutants_for_statements(&vec!["int8 x = 127;"], &ops, &vec!["126"]);
+        assert_exact_mutants_for_statements(
+            &vec!["uint8 x = uint8(block.number);", "if (x < 255) { x++; }"],
+            &ops,
+            &vec!["254"],
+        );
+        // Array sizes
+        assert_exact_mutants_for_statements(
+            &vec!["uint256[] memory a = new uint256[](3);"],
+            &ops,
+            &vec!["4", "2"],
+        );
+        // Mutants that `AssignmentMutation` already generates are dropped
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 x;", "x = 1;"],
+            &vec![BoundaryLiteralMutation, AssignmentMutation],
+            &vec!["(-1)", "0", "2"],
+        );
+        // Other literals are left untouched
+        assert_exact_mutants_for_statements(&vec!["uint256 x = 10 + 1;"], &ops, &vec![]);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_integer_type_max() {
+        assert_eq!(integer_type_max("uint8"), Some(255));
+        assert_eq!(integer_type_max("int8"), Some(127));
+        assert_eq!(integer_type_max("uint64"), Some(u64::MAX as u128));
+        assert_eq!(integer_type_max("int128"), Some(i128::MAX as u128));
+        // Larger types cannot be exceeded by a `u128` literal shifted by one
+        assert_eq!(integer_type_max("uint128"), None);
+        assert_eq!(integer_type_max("uint256"), None);
+        assert_eq!(integer_type_max("uint"), None);
+        assert_eq!(integer_type_max("int_const 255"), None);
+        assert_eq!(integer_type_max("bool"), None);
+    }
+
+    #[test]
+    pub fn test_loop_bound_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![LoopBoundMutation];
+        // The `3` of the body and the `0` of the initialization are left
+        // untouched
+        assert_exact_mutants_for_statements(
+            &vec![
+                "uint256 x = 0;",
+                "for (uint256 i = 0; i < 3; i++) { x = x + 3; }",
+            ],
+            &ops,
+            &vec!["0", "1", "4"],
+        );
+        assert_exact_mutants_for_statements(
+            &vec![
+                "uint256 x = 0;",
+                "for (uint256 i = 0; i <= 1; i++) { x = x + 1; }",
+            ],
+            &ops,
+            &vec!["0", "2"],
+        );
+        // Only `for` loops are mutated
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 x = 0;", "while (x < 3) { x = x + 1; }"],
+            &ops,
+            &vec![],
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_cast_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![CastMutation];
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 a = 1;", "uint128 b = uint128(a);"],
+            &ops,
+            &vec!["", "uint64", "uint256"],
+        );
+        assert_exact_mutants_for_statements(
+            &vec!["int256 a = -1;", "int64 b = int64(a);"],
+            &ops,
+            &vec!["", "int32", "int128"],
+        );
+        // `uint` is `uint256`, which cannot be widened
+        assert_exact_mutants_for_statements(
+            &vec!["uint128 a = 1;", "uint b = uint(a);"],
+            &ops,
+            &vec!["", "uint128"],
+        );
+        // Casts to non-integer types are not mutated
+        assert_exact_mutants_for_statements(&vec!["address a = address(0);"], &ops, &vec![]);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_time_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![TimeMutation];
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 vesting = 30 days;"],
+            &ops,
+            &vec!["31 days", "29 days", "30 hours", "30 weeks"],
+        );
+        // There is no unit below `seconds`, and `0 seconds` is not decremented
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 t = 0 seconds;"],
+            &ops,
+            &vec!["1 seconds", "0 minutes"],
+        );
+        assert_exact_mutants_for_source(TIMELOCK, &ops, &vec!["-"]);
+        // Arithmetic that does not involve `block.timestamp` is not flipped
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 a = 1;", "uint256 b = a + 2;"],
+            &ops,
+            &vec![],
+        );
+        Ok(())
+    }
+
+    const TIMELOCK: &str = "\
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract Timelock {
+    uint256 constant CLIFF = 365;
+
+    function unlockTime() public view returns (uint256) {
+        return block.timestamp + CLIFF;
+    }
+}
+";
+
+    #[test]
+    pub fn test_delete_expression_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![DeleteExpressionMutation];
+        assert_exact_mutants_for_statements(&vec!["gasleft();"], &ops, &vec!["assert(true)"]);
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 x = 0;", "x = 3;"],
+            &ops,
+            &vec!["assert(true)"],
+        );
+        assert_exact_mutants_for_statements(
+            &vec!["uint256 x = 0;", "assert(x == 0);", "revert();"],
+            &ops,
+            &vec![],
+        );
+        Ok(())
+    }
+
+    const REQUIRE_THEN_CALL: &str = "\
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract C {
+    function foo() public {}
+
+    function bar(bool x) public {
+        require(x);
+        foo();
+    }
+}
+";
+
+    #[test]
+    pub fn test_delete_expression_exclusions() -> Result<(), Box<dyn error::Error>> {
+        let mutator = apply_mutation_to_source(REQUIRE_THEN_CALL, &[DeleteExpressionMutation])?;
+        let origs: Vec<&str> = mutator.mutants().iter().map(|m| m.orig.as_str()).collect();
+        assert_eq!(origs, vec!["foo()"]);
+
+        // An empty exclusion list restores the old behavior
+        let conf = MutatorConf {
+            mutation_operators: vec![DeleteExpressionMutation],
+            delete_expression_exclusions: vec![],
+            ..MutatorConf::default()
+        };
+        let mutator = apply_conf_to_source(REQUIRE_THEN_CALL, conf)?;
+        let origs: Vec<&str> = mutator.mutants().iter().map(|m| m.orig.as_str()).collect();
+        assert_eq!(origs, vec!["require(x)", "foo()"]);
         Ok(())
     }
 
@@ -608,11 +2456,111 @@ contract A {
 
     #[test]
     pub fn test_function_call_mutation() -> Result<(), Box<dyn error::Error>> {
-        let _ops = vec![FunctionCallMutation];
-        // TODO: how should I test this?
+        let ops = vec![FunctionCallMutation];
+        let code = "\
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.0;
+
+contract C {
+    uint256 total;
+
+    function add(uint256 a, uint256 b) internal pure returns (uint256) {
+        return a + b;
+    }
+
+    function record(uint256 a) internal {
+        total = a;
+    }
+
+    function f(uint256 a, uint256 b, bool ok) public {
+        uint x = add(a, b);
+        record(x);
+        uint256 y = uint256(uint8(x));
+        require(ok);
+    }
+}
+";
+        // `add(a, b)` is replaced by each argument; `record(x)`, type
+        // conversions, and `require` are left alone
+        assert_exact_mutants_for_source(code, &ops, &vec!["a", "b"]);
         Ok(())
     }
 
+    #[test]
+    fn test_function_call_mutation_in_memory() {
+        let source = "contract C {\n    function f(uint256 a, uint256 b, bool c) public {\n        uint256 x = add(a, b + 1, c);\n        record(x);\n    }\n}\n";
+        let at = |text: &str| {
+            let start = source.find(text).unwrap();
+            format!("{}:{}:0", start, text.len())
+        };
+        let typed = |node_type: &str, text: &str, ty: &str| json!({"nodeType": node_type, "src": at(text), "typeDescriptions": {"typeString": ty}});
+        let add = json!({
+            "nodeType": "FunctionCall",
+            "kind": "functionCall",
+            "src": at("add(a, b + 1, c)"),
+            "typeDescriptions": {"typeString": "uint256"},
+            "expression": typed("Identifier", "add", "function (uint256,uint256,bool) pure returns (uint256)"),
+            "arguments": [
+                typed("Identifier", "a", "uint256"),
+                typed("BinaryOperation", "b + 1", "uint256"),
+                typed("Identifier", "c", "bool"),
+            ],
+        });
+        let record = json!({
+            "nodeType": "ExpressionStatement",
+            "src": at("record(x)"),
+            "expression": {
+                "nodeType": "FunctionCall",
+                "kind": "functionCall",
+                "src": at("record(x)"),
+                "typeDescriptions": {"typeString": "tuple()"},
+                "expression": typed("Identifier", "record", "function (uint256)"),
+                "arguments": [typed("Identifier", "x", "uint256")],
+            }
+        });
+        let body = "{\n        uint256 x = add(a, b + 1, c);\n        record(x);\n    }";
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "src": at(source),
+            "nodes": [{
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "C",
+                "src": at(source.trim_end()),
+                "nodes": [{
+                    "nodeType": "FunctionDefinition",
+                    "kind": "function",
+                    "name": "f",
+                    "src": at(&format!("function f(uint256 a, uint256 b, bool c) public {}", body)),
+                    "body": {
+                        "nodeType": "Block",
+                        "src": at(body),
+                        "statements": [
+                            {"nodeType": "VariableDeclarationStatement", "src": at("uint256 x = add(a, b + 1, c)"), "initialValue": add},
+                            record,
+                        ]
+                    }
+                }]
+            }]
+        });
+        let conf = MutatorConf {
+            mutation_operators: vec![FunctionCallMutation],
+            ..MutatorConf::default()
+        };
+        let mutants = mutate_source(source, "C.sol", SolAST::new(ast), &conf);
+        let repls: Vec<(&str, &str)> = mutants
+            .iter()
+            .map(|m| (m.orig.as_str(), m.repl.as_str()))
+            .collect();
+        // The compound argument is parenthesized, the `bool` argument does
+        // not replace the `uint256` call, and `record(x)`, which returns
+        // nothing, is not mutated
+        assert_eq!(
+            repls,
+            vec![("add(a, b + 1, c)", "a"), ("add(a, b + 1, c)", "(b + 1)")]
+        );
+    }
+
     #[test]
     pub fn test_if_statement_mutation() -> Result<(), Box<dyn error::Error>> {
         let ops = vec![IfStatementMutation];
@@ -638,6 +2586,363 @@ contract A {
         Ok(())
     }
 
+    #[test]
+    pub fn test_logical_operator_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![LogicalOperatorMutation];
+        let decls = vec!["bool a = true;", "bool b = false;"];
+        let with_decls = |stmt: &'static str| {
+            let mut statements = decls.clone();
+            statements.push(stmt);
+            statements
+        };
+        assert_exact_mutants_for_statements(
+            &with_decls("require(a && b);"),
+            &ops,
+            &vec!["||", "a", "b"],
+        );
+        assert_exact_mutants_for_statements(
+            &with_decls("require(a || b);"),
+            &ops,
+            &vec!["&&", "a", "b"],
+        );
+        // Conditions of `if` and `while` statements are mutated too
+        assert_exact_mutants_for_statements(
+            &with_decls("if (a && b) {}"),
+            &ops,
+            &vec!["||", "a", "b"],
+        );
+        assert_exact_mutants_for_statements(
+            &with_decls("while (a || b) {}"),
+            &ops,
+            &vec!["&&", "a", "b"],
+        );
+        // Identical operands are replaced only once
+        assert_exact_mutants_for_statements(
+            &with_decls("require(a && a);"),
+            &ops,
+            &vec!["||", "a"],
+        );
+        // Nested operations are mutated separately
+        assert_num_mutants_for_statements(&with_decls("require(a && (b || a));"), &ops, 6);
+        // Along with the require mutation
+        assert_exact_mutants_for_statements(
+            &with_decls("require(a && b);"),
+            &vec![LogicalOperatorMutation, RequireMutation],
+            &vec!["||", "a", "b", "true", "false"],
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_revert_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![RevertMutation];
+        assert_exact_mutants_for_source(
+            CUSTOM_ERRORS,
+            &ops,
+            &vec![
+                // `withdraw`: the guard is deleted, the revert is deleted,
+                // and its `uint256` arguments are swapped
+                "revert InsufficientBalance(amount, balance)",
+                "assert(true)",
+                "balance, amount",
+                // `close`: the guarded block stays, and the revert is deleted
+                "{\n            revert Unauthorized(msg.sender);\n        }",
+                "assert(true)",
+            ],
+        );
+        // Arguments of different types are not swapped, and a revert with an
+        // `else` branch keeps its guard
+        assert_exact_mutants_for_source(
+            &CUSTOM_ERRORS
+                .replace(
+                    "uint256 needed, uint256 available",
+                    "uint256 needed, uint128 available",
+                )
+                .replace(
+                    "revert InsufficientBalance(amount, balance);",
+                    "revert InsufficientBalance(amount, uint128(balance)); else balance = 0;",
+                ),
+            &ops,
+            &vec![
+                "assert(true)",
+                "{\n            revert Unauthorized(msg.sender);\n        }",
+                "assert(true)",
+            ],
+        );
+        Ok(())
+    }
+
+    const CUSTOM_ERRORS: &str = "
+contract Vault {
+    error InsufficientBalance(uint256 needed, uint256 available);
+    error Unauthorized(address caller);
+
+    mapping(address => uint256) balances;
+    address owner;
+
+    function withdraw(uint256 amount) public {
+        uint256 balance = balances[msg.sender];
+        if (amount > balance) revert InsufficientBalance(amount, balance);
+        balances[msg.sender] = balance - amount;
+    }
+
+    function close() public {
+        if (msg.sender != owner) {
+            revert Unauthorized(msg.sender);
+        }
+    }
+}";
+
+    #[test]
+    pub fn test_revert_mutation_spans() {
+        let source = "contract C {\n    error E(uint256 a, uint256 b);\n    function f(uint256 x, uint256 y) public pure {\n        if (x > y) revert E(x, y);\n    }\n}\n";
+        let at = |text: &str| format!("{}:{}:0", source.find(text).unwrap(), text.len());
+        let ident = |name: &str, start: usize| {
+            json!({"nodeType": "Identifier", "name": name, "src": format!("{}:1:0", start),
+                   "typeDescriptions": {"typeString": "uint256", "typeIdentifier": "t_uint256"}})
+        };
+        let (cond, call) = (
+            source.find("x > y").unwrap(),
+            source.find("E(x, y)").unwrap(),
+        );
+        let revert = json!({
+            "nodeType": "RevertStatement",
+            "src": at("revert E(x, y)"),
+            "errorCall": {
+                "nodeType": "FunctionCall",
+                "src": at("E(x, y)"),
+                "expression": {"nodeType": "Identifier", "name": "E", "src": format!("{}:1:0", call)},
+                "arguments": [ident("x", call + 2), ident("y", call + 5)],
+            }
+        });
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "src": at(source),
+            "nodes": [{
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "C",
+                "src": at(source.trim_end()),
+                "nodes": [{
+                    "nodeType": "FunctionDefinition",
+                    "kind": "function",
+                    "name": "f",
+                    "src": at("function f(uint256 x, uint256 y) public pure {\n        if (x > y) revert E(x, y);\n    }"),
+                    "body": {
+                        "nodeType": "Block",
+                        "src": at("{\n        if (x > y) revert E(x, y);\n    }"),
+                        "statements": [{
+                            "nodeType": "IfStatement",
+                            "src": at("if (x > y) revert E(x, y)"),
+                            "condition": {"nodeType": "BinaryOperation", "operator": ">", "src": at("x > y"),
+                                          "leftExpression": ident("x", cond), "rightExpression": ident("y", cond + 4)},
+                            "trueBody": revert,
+                            "falseBody": null,
+                        }]
+                    }
+                }]
+            }]
+        });
+        let conf = MutatorConf {
+            mutation_operators: vec![RevertMutation],
+            ..MutatorConf::default()
+        };
+        let mutants = mutate_source(source, "C.sol", SolAST::new(ast), &conf);
+        let mutated: Vec<String> = mutants
+            .iter()
+            .map(|m| {
+                m.as_source_string()
+                    .unwrap()
+                    .lines()
+                    .nth(4)
+                    .unwrap()
+                    .trim()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            mutated,
+            vec![
+                "revert E(x, y);",
+                "if (x > y) assert(true);",
+                "if (x > y) revert E(y, x);",
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_keccak_equality_mutation() -> Result<(), Box<dyn error::Error>> {
+        let ops = vec![KeccakEqualityMutation];
+        let strings = vec!["string memory a = \"x\";", "string memory b = \"y\";"];
+        let statements = |comparison: &'static str| {
+            let mut statements = strings.clone();
+            statements.push(comparison);
+            statements
+        };
+        assert_exact_mutants_for_statements(
+            &statements("bool eq = keccak256(bytes(a)) == keccak256(bytes(b));"),
+            &ops,
+            &vec!["a", "true", "false"],
+        );
+        assert_exact_mutants_for_statements(
+            &statements("bool ne = keccak256(bytes(a)) != keccak256(bytes(b));"),
+            &ops,
+            &vec!["a", "true", "false"],
+        );
+        // A comparison of a string with itself is only replaced by constants
+        assert_exact_mutants_for_statements(
+            &statements("bool eq = keccak256(bytes(a)) == keccak256(bytes(a));"),
+            &ops,
+            &vec!["true", "false"],
+        );
+
+        // Ordinary uses of `keccak256` are not mutated
+        assert_exact_mutants_for_statements(
+            &statements("bytes32 h = keccak256(bytes(a));"),
+            &ops,
+            &vec![],
+        );
+        assert_exact_mutants_for_statements(
+            &statements("bool eq = keccak256(bytes(a)) == keccak256(abi.encodePacked(b));"),
+            &ops,
+            &vec![],
+        );
+        assert_exact_mutants_for_statements(
+            &statements("bool eq = keccak256(bytes(a)) == bytes32(0);"),
+            &ops,
+            &vec![],
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_try_catch_mutation() -> Result<(), Box<dyn error::Error>> {
+        let code = "\
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.9;
+
+contract Callee {
+    function get() external pure returns (uint256) {
+        return 1;
+    }
+}
+
+contract C {
+    Callee callee = new Callee();
+    uint256 public status;
+
+    function call() public {
+        try callee.get() returns (uint256 v) {
+            status = v;
+        } catch Error(string memory) {
+            status = 2;
+        } catch {
+            status = 3;
+        }
+    }
+}
+";
+        let ops = vec![TryCatchMutation];
+        let error_body = "{\n            status = 2;\n        }";
+        let bare_body = "{\n            status = 3;\n        }";
+        let swapped = format!("{} catch {}", bare_body, error_body);
+        let expected = vec!["{}", "{}", swapped.as_str()];
+        assert_exact_mutants_for_source(code, &ops, &expected);
+
+        // The deletions must cover exactly the catch bodies, and the swap
+        // must span from the first catch body to the end of the second
+        let mutator = apply_mutation_to_source(code, &ops)?;
+        let origs: HashSet<&str> = mutator.mutants().iter().map(|m| m.orig.as_str()).collect();
+        let unswapped = format!("{} catch {}", error_body, bare_body);
+        let expected: HashSet<&str> = vec![error_body, bare_body, unswapped.as_str()]
+            .into_iter()
+            .collect();
+        assert_eq!(origs, expected);
+        Ok(())
+    }
+
+    /// Replace `orig` in `contents` with `repl` and check that the mutant is
+    /// the replaced contents with exactly one comment line inserted above the
+    /// mutated line
+    fn assert_comment_on_one_line(contents: &str, op: MutationType, orig: &str, repl: &str) {
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("/tmp/C.sol"),
+            PathBuf::from("/tmp"),
+            contents.as_bytes().to_vec(),
+        ));
+        let start = contents.find(orig).unwrap();
+        let mutant = Mutant::new(source, op, start, start + orig.len(), repl.to_string()).unwrap();
+        let (line, _) = mutant.get_line_column().unwrap();
+        let mutated = mutant.as_source_string().unwrap();
+        let mut lines: Vec<&str> = mutated.lines().collect();
+        let comment = lines.remove(line - 1);
+        assert!(comment.trim_start().starts_with("/// "), "{}", comment);
+        assert_eq!(
+            lines,
+            contents.replacen(orig, repl, 1).lines().collect::<Vec<_>>(),
+            "{}",
+            mutated
+        );
+    }
+
+    #[test]
+    pub fn test_multi_line_catch_body_comment() {
+        let contents = "contract C {\n    function f(I i) public {\n        try i.g() {\n        } catch {\n            x = 1;\n            x = 2;\n        }\n    }\n}\n";
+        assert_comment_on_one_line(
+            contents,
+            TryCatchMutation,
+            "{\n            x = 1;\n            x = 2;\n        }",
+            "{}",
+        );
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("/tmp/C.sol"),
+            PathBuf::from("/tmp"),
+            contents.as_bytes().to_vec(),
+        ));
+        let start = contents.find("{\n            x = 1;").unwrap();
+        let body_end = contents.rfind("}\n    }").unwrap() + 1;
+        let mutant = Mutant::new(source, TryCatchMutation, start, body_end, "{}".into()).unwrap();
+        assert_eq!(
+            mutant.as_source_string().unwrap().lines().nth(3).unwrap(),
+            "        /// TryCatchMutation(`{ x = 1; x = 2; }` |==> `{}`) of: `} catch {`"
+        );
+    }
+
+    #[test]
+    pub fn test_multi_line_revert_guard_comment() {
+        let guard =
+            "if (msg.sender != owner) {\n            revert Unauthorized(msg.sender);\n        }";
+        let block = "{\n            revert Unauthorized(msg.sender);\n        }";
+        // Deleting the guard keeps the block, and deleting the revert keeps the
+        // guard; both mutants quote the whole multi-line guard
+        assert_comment_on_one_line(CUSTOM_ERRORS, RevertMutation, guard, block);
+        assert_comment_on_one_line(
+            CUSTOM_ERRORS,
+            RevertMutation,
+            "revert Unauthorized(msg.sender)",
+            "assert(true)",
+        );
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("/tmp/C.sol"),
+            PathBuf::from("/tmp"),
+            CUSTOM_ERRORS.as_bytes().to_vec(),
+        ));
+        let start = CUSTOM_ERRORS.find(guard).unwrap();
+        let mutant = Mutant::new(
+            source,
+            RevertMutation,
+            start,
+            start + guard.len(),
+            block.into(),
+        )
+        .unwrap();
+        let (line, _) = mutant.get_line_column().unwrap();
+        assert_eq!(
+            mutant.as_source_string().unwrap().lines().nth(line - 1).unwrap(),
+            "        /// RevertMutation(`if (msg.sender != owner) { revert Unauthorized(msg.sender); }` |==> `{ revert Unauthorized(msg.sender); }`) of: `if (msg.sender != owner) {`"
+        );
+    }
+
     #[test]
     pub fn test_unary_op_mutation() -> Result<(), Box<dyn error::Error>> {
         let ops = vec![UnaryOperatorMutation];
@@ -689,6 +2994,343 @@ contract A {
         Ok(())
     }
 
+    const MIXED_MUTABILITY: &str = "\
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.9;
+
+contract C {
+    uint256 public x;
+
+    function set(uint256 a) public {
+        x = a + 1;
+    }
+
+    function get() public view returns (uint256) {
+        return x + 1;
+    }
+
+    function add(uint256 a, uint256 b) public pure returns (uint256) {
+        return a + b;
+    }
+}
+";
+
+    #[test]
+    pub fn test_skip_pure_view() -> Result<(), Box<dyn error::Error>> {
+        let conf = MutatorConf {
+            mutation_operators: vec![BinaryOpMutation],
+            skip_pure_view: true,
+            ..MutatorConf::default()
+        };
+        let mutator = apply_conf_to_source(MIXED_MUTABILITY, conf)?;
+        // Only `set` is mutated
+        assert_eq!(mutator.mutants().len(), 5);
+        assert!(mutator.mutants().iter().all(|m| m.mutability.is_none()));
+        assert_eq!(mutator.stats.pure_view_functions_skipped, 2);
+        assert_eq!(mutator.stats.pure_view_functions_tagged, 0);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_tag_pure_view() -> Result<(), Box<dyn error::Error>> {
+        let conf = MutatorConf {
+            mutation_operators: vec![BinaryOpMutation],
+            tag_pure_view: true,
+            ..MutatorConf::default()
+        };
+        let mutator = apply_conf_to_source(MIXED_MUTABILITY, conf)?;
+        assert_eq!(mutator.mutants().len(), 15);
+        let count = |mutability: Option<&str>| {
+            mutator
+                .mutants()
+                .iter()
+                .filter(|m| m.mutability.as_deref() == mutability)
+                .count()
+        };
+        assert_eq!(count(None), 5);
+        assert_eq!(count(Some("view")), 5);
+        assert_eq!(count(Some("pure")), 5);
+        assert_eq!(mutator.stats.pure_view_functions_skipped, 0);
+        assert_eq!(mutator.stats.pure_view_functions_tagged, 2);
+        Ok(())
+    }
+
+    const ONLY_OWNER: &str = "\
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.9;
+
+contract C {
+    address owner;
+    uint256 public x;
+
+    modifier onlyOwner() {
+        require(msg.sender == owner);
+        _;
+    }
+
+    function set(uint256 a) public onlyOwner {
+        x = a;
+    }
+}
+";
+
+    #[test]
+    pub fn test_mutate_modifiers() -> Result<(), Box<dyn error::Error>> {
+        let mutate = |mutate_modifiers: bool, funcs_to_mutate: Option<Vec<String>>| {
+            let conf = MutatorConf {
+                mutation_operators: vec![RequireMutation, BinaryOpMutation],
+                mutate_modifiers,
+                funcs_to_mutate,
+                ..MutatorConf::default()
+            };
+            apply_conf_to_source(ONLY_OWNER, conf)
+        };
+        // Modifiers are mutated by default, and skipped with
+        // `--no_mutate_modifiers`
+        assert!(MutatorConf::default().mutate_modifiers);
+        let mutator = mutate(false, None)?;
+        assert!(mutator.mutants().is_empty());
+        assert_eq!(mutator.stats.modifiers_mutated, 0);
+
+        let mutator = mutate(true, None)?;
+        let modifier_start = ONLY_OWNER.find("modifier onlyOwner").unwrap();
+        let modifier_end = ONLY_OWNER.find("function set").unwrap();
+        assert!(mutator
+            .mutants()
+            .iter()
+            .all(|m| modifier_start < m.start && m.end < modifier_end));
+        let repls = |op: MutationType| -> HashSet<&str> {
+            mutator
+                .mutants()
+                .iter()
+                .filter(|m| m.op == op)
+                .map(|m| m.repl.as_str())
+                .collect()
+        };
+        assert_eq!(repls(RequireMutation), HashSet::from(["true", "false"]));
+        assert_eq!(
+            repls(BinaryOpMutation),
+            HashSet::from(["+", "-", "*", "/", "%", "**"])
+        );
+        assert_eq!(mutator.stats.modifiers_mutated, 1);
+
+        // `--functions` selects modifiers by name
+        let mutator = mutate(true, Some(vec!["set".into()]))?;
+        assert!(mutator.mutants().is_empty());
+        let mutator = mutate(true, Some(vec!["onlyOwner".into()]))?;
+        assert_eq!(mutator.mutants().len(), 8);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_invalid_utf8_source() {
+        let contents = b"// caf\xe9\ncontract C { uint x = 1 /* \xff */ + 2; }\n".to_vec();
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("/tmp/C.sol"),
+            PathBuf::from("/tmp"),
+            contents.clone(),
+        ));
+        // A span containing invalid UTF-8 cannot be mutated...
+        let plus = contents.iter().position(|c| *c == b'+').unwrap();
+        assert!(Mutant::new(source.clone(), BinaryOpMutation, 24, plus + 2, "-".into()).is_err());
+        // ...but the rest of the file can
+        let one = contents.iter().position(|c| *c == b'1').unwrap();
+        let mutant = Mutant::new(source, AssignmentMutation, one, one + 1, "0".into()).unwrap();
+        assert_eq!(
+            mutant.as_source_string().unwrap(),
+            "// caf\u{FFFD}\n/// AssignmentMutation(`1` |==> `0`) of: `contract C { uint x = 1 /* \u{FFFD} */ + 2; }`\ncontract C { uint x = 0 /* \u{FFFD} */ + 2; }\n"
+        );
+    }
+
+    #[test]
+    pub fn test_multi_edit_mutants() {
+        let contents = b"contract C {\n    uint x = a - bb;\n}\n".to_vec();
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("/tmp/C.sol"),
+            PathBuf::from("/tmp"),
+            contents.clone(),
+        ));
+        let find = |text: &str| {
+            contents
+                .windows(text.len())
+                .position(|w| w == text.as_bytes())
+                .unwrap()
+        };
+        let (a, bb) = (find("a -"), find("bb"));
+        let edit = |start: usize, end: usize, repl: &str| MutantEdit {
+            start,
+            end,
+            repl: repl.into(),
+        };
+        let with_edits = |edits: Vec<MutantEdit>| {
+            Mutant::with_edits(source.clone(), SwapArgumentsOperatorMutation, edits)
+        };
+
+        // Swapping the operands takes two edits of different lengths, which
+        // are applied at once
+        let swap = vec![edit(a, a + 1, "bb"), edit(bb, bb + 2, "a")];
+        let mutant = with_edits(swap.clone()).unwrap();
+        assert_eq!(mutant.edits(), swap);
+        assert_eq!((mutant.start, mutant.end), (a, bb + 2));
+        assert_eq!(mutant.orig, "a - bb");
+        assert_eq!(mutant.repl, "bb - a");
+        assert_eq!(
+            String::from_utf8(mutant.apply_to(&contents)).unwrap(),
+            "contract C {\n    uint x = bb - a;\n}\n"
+        );
+        assert!(mutant
+            .as_source_string()
+            .unwrap()
+            .ends_with("\n    uint x = bb - a;\n}\n"));
+
+        // A single edit makes a single-span mutant
+        let mutant = with_edits(vec![edit(a + 1, bb, " + ")]).unwrap();
+        assert!(mutant.edits.is_empty());
+        assert_eq!(mutant.edits(), vec![edit(a + 1, bb, " + ")]);
+        assert_eq!((mutant.orig.as_str(), mutant.repl.as_str()), (" - ", " + "));
+
+        // Overlapping and unordered edits are rejected
+        assert!(matches!(
+            with_edits(vec![edit(a, bb + 1, "x"), edit(bb, bb + 2, "y")]),
+            Err(MutantEditError::Overlap(..))
+        ));
+        assert!(matches!(
+            with_edits(vec![edit(bb, bb + 2, "a"), edit(a, a + 1, "bb")]),
+            Err(MutantEditError::Overlap(..))
+        ));
+        assert!(matches!(
+            with_edits(vec![edit(bb, contents.len() + 1, "")]),
+            Err(MutantEditError::OutOfBounds(..))
+        ));
+        assert!(matches!(with_edits(vec![]), Err(MutantEditError::NoEdits)));
+    }
+
+    #[test]
+    pub fn test_comment_styles() {
+        let contents = b"contract C {\n    uint x = 1 /* one */ + 2;\n}\n".to_vec();
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("/tmp/C.sol"),
+            PathBuf::from("/tmp"),
+            contents.clone(),
+        ));
+        let one = contents.iter().position(|c| *c == b'1').unwrap();
+        let mut mutant = Mutant::new(source, AssignmentMutation, one, one + 1, "0".into()).unwrap();
+        let comment = |mutant: &Mutant| {
+            let mutated = mutant.as_source_string().unwrap();
+            mutated.lines().nth(1).unwrap().to_string()
+        };
+        let description = "AssignmentMutation(`1` |==> `0`) of: `uint x = 1 /* one */ + 2;`";
+
+        assert_eq!(mutant.comment_style, CommentStyle::Natspec);
+        assert_eq!(comment(&mutant), format!("    /// {}", description));
+
+        mutant.comment_style = CommentStyle::Line;
+        assert_eq!(comment(&mutant), format!("    // {}", description));
+
+        // The `*/` of the original line must not end the block comment
+        mutant.comment_style = CommentStyle::Block;
+        assert_eq!(
+            comment(&mutant),
+            "    /* AssignmentMutation(`1` |==> `0`) of: `uint x = 1 /* one *\\/ + 2;` */"
+        );
+        assert_eq!(comment(&mutant).matches("*/").count(), 1);
+        // Only the comment line is inserted
+        assert_eq!(
+            mutant.as_source_string().unwrap().lines().count(),
+            contents.iter().filter(|c| **c == b'\n').count() + 1
+        );
+        assert_eq!(mutant.line_shifts().unwrap()[0].delta, 1);
+
+        assert_eq!(CommentStyle::Block.comment("*/*/"), "/* *\\/*\\/ */");
+    }
+
+    #[test]
+    pub fn test_invalid_utf8_fixture() -> Result<(), Box<dyn error::Error>> {
+        let filename = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("benchmarks")
+            .join("InvalidUTF8")
+            .join("InvalidUTF8.sol");
+        let outdir = Builder::new()
+            .prefix("gambit-compile-dir")
+            .rand_bytes(5)
+            .tempdir()?;
+        let mut mutator = make_mutator(
+            &vec![BinaryOpMutation],
+            filename,
+            outdir.path().to_path_buf(),
+        );
+        mutator.mutate()?;
+        // `a /* \xff */ + b` is skipped, while `c * d` is still mutated
+        let actuals: HashSet<&str> = mutator.mutants().iter().map(|m| m.repl.as_str()).collect();
+        assert_eq!(actuals, HashSet::from(["+", "-", "/", "%", "**"]));
+        assert_eq!(mutator.stats.invalid_utf8_candidates_skipped, 1);
+        for mutant in mutator.mutants() {
+            mutant.as_source_string()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_as_source_string_keeps_bom() -> Result<(), Box<dyn error::Error>> {
+        let contents = "\u{feff}contract C { uint256 x = 1 + 2; }\n";
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("C.sol"),
+            PathBuf::new(),
+            contents.as_bytes().to_vec(),
+        ));
+        assert!(source.has_bom());
+        let start = contents.find('+').unwrap();
+        let mutant = Mutant::new(source, BinaryOpMutation, start, start + 1, "-".into())?;
+        assert_eq!(mutant.orig, "+");
+        // The mark stays at the start of the file, before the comment
+        assert_eq!(
+            mutant.as_source_string()?,
+            "\u{feff}/// BinaryOpMutation(`+` |==> `-`) of: `contract C { uint256 x = 1 + 2; }`\ncontract C { uint256 x = 1 - 2; }\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_include_called_libraries() -> Result<(), Box<dyn error::Error>> {
+        let filename = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("benchmarks")
+            .join("UsingFor")
+            .join("C.sol");
+        for include_called_libraries in [false, true] {
+            let outdir = Builder::new()
+                .prefix("gambit-compile-dir")
+                .rand_bytes(5)
+                .tempdir()?;
+            let conf = MutatorConf {
+                mutation_operators: vec![BinaryOpMutation, RequireMutation],
+                include_called_libraries,
+                ..MutatorConf::default()
+            };
+            let mut mutator =
+                make_mutator_with_conf(conf, filename.clone(), outdir.path().to_path_buf());
+            mutator.mutate()?;
+            let (library_mutants, target_mutants): (Vec<&Mutant>, Vec<&Mutant>) = mutator
+                .mutants()
+                .iter()
+                .partition(|m| m.source.filename().ends_with("SafeCastLib.sol"));
+            // `x / 2` is mutated either way
+            assert_eq!(target_mutants.len(), 5);
+            assert!(target_mutants.iter().all(|m| m.target_file.is_none()));
+            if include_called_libraries {
+                // `x < 1 << 128` (both operators) and its `require`
+                assert_eq!(library_mutants.len(), 14);
+                assert!(library_mutants
+                    .iter()
+                    .all(|m| m.target_file.as_deref() == Some("C.sol")));
+                assert_eq!(mutator.stats.library_functions_included, 1);
+            } else {
+                assert!(library_mutants.is_empty());
+                assert_eq!(mutator.stats.library_functions_included, 0);
+            }
+        }
+        Ok(())
+    }
+
     fn assert_num_mutants_for_statements(
         statements: &Vec<&str>,
         ops: &Vec<MutationType>,
@@ -743,7 +3385,7 @@ contract A {
             .prefix("gambit-compile-dir")
             .rand_bytes(5)
             .tempdir()?;
-        let mut mutator = make_mutator(ops, source, outdir.into_path());
+        let mut mutator = make_mutator(ops, source, outdir.path().to_path_buf());
         mutator.mutate()?;
 
         Ok(mutator)
@@ -791,14 +3433,27 @@ contract A {
 
     fn apply_mutation_to_source(
         source: &str,
-        ops: &Vec<MutationType>,
+        ops: &[MutationType],
+    ) -> Result<Mutator, Box<dyn error::Error>> {
+        let conf = MutatorConf {
+            mutation_operators: ops.to_vec(),
+            ..MutatorConf::default()
+        };
+        apply_conf_to_source(source, conf)
+    }
+
+    /// Apply a full mutator configuration (rather than just a set of
+    /// operators) to the given source
+    fn apply_conf_to_source(
+        source: &str,
+        conf: MutatorConf,
     ) -> Result<Mutator, Box<dyn error::Error>> {
         let source = write_solidity_to_temp_file(source.to_string()).unwrap();
         let outdir = Builder::new()
             .prefix("gambit-compile-dir")
             .rand_bytes(5)
             .tempdir()?;
-        let mut mutator = make_mutator(ops, source, outdir.into_path());
+        let mut mutator = make_mutator_with_conf(conf, source, outdir.path().to_path_buf());
         mutator.mutate()?;
 
         Ok(mutator)
@@ -811,7 +3466,12 @@ contract A {
             mutation_operators: ops.clone(),
             funcs_to_mutate: None,
             contract: None,
+            ..MutatorConf::default()
         };
+        make_mutator_with_conf(conf, filename, outdir)
+    }
+
+    fn make_mutator_with_conf(conf: MutatorConf, filename: PathBuf, outdir: PathBuf) -> Mutator {
         let sourceroot = filename.parent().unwrap();
 
         let source = Source::new(filename.clone(), sourceroot.to_path_buf())
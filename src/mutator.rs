@@ -1,20 +1,49 @@
 use crate::{
-    default_gambit_output_directory, mutation::MutationType, source::Source, Mutant, MutantWriter,
-    MutateParams, Mutation, SolAST, SolASTVisitor, Solc,
+    abis_differ, collect_ast_context, default_gambit_output_directory, default_jobs,
+    discover_project_layout, is_solc_unavailable, merge_discovered_remappings,
+    mutation::{is_likely_equivalent, CommentStyle, MutationType},
+    report,
+    source::Source,
+    suggest_name, ConfigurationCheck, ContractAbis, Diagnostic, DiagnosticCode, FileAstContext,
+    FileEstimate, GambitError, Mutant, MutateParams, Mutation, MutationSpec, SolAST, SolASTVisitor,
+    Solc, ValidationStages, DEFAULT_MAX_AST_DEPTH,
 };
 use clap::ValueEnum;
-use std::{error, path::PathBuf, rc::Rc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    error, fmt, fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 use tempfile::{tempdir, NamedTempFile};
 
 /// This module is responsible for high level logic of running mutation over
 /// Solidity programs.
 
+/// Calls that `DeleteExpressionMutation` does not delete by default. Deleting
+/// these duplicates what `RequireMutation` already produces.
+pub const DEFAULT_DELETE_EXPRESSION_EXCLUSIONS: [&str; 3] = ["require", "assert", "revert"];
+
 /// The configuration for a mutator, this specifies the details of mutation
 #[derive(Debug, Clone)]
 pub struct MutatorConf {
     /// Mutation operators to apply during mutation
     pub mutation_operators: Vec<MutationType>,
 
+    /// The replacements that some of `mutation_operators` are restricted to
+    /// (see `MutationSpec`). Operators missing from this map try all of their
+    /// replacements.
+    pub operator_replacements: HashMap<MutationType, Vec<String>>,
+
     /// If this is `Some(fnames)` then only mutate functions with names in
     /// `fnames`. If this is `None` then mutate all function names
     pub funcs_to_mutate: Option<Vec<String>>,
@@ -22,264 +51,5854 @@ pub struct MutatorConf {
     /// If this is `Some(c)` then only mutate SolAST `ast` when `ast.contract ==
     /// c`. When this is `None` then no constraints are given.
     pub contract: Option<String>,
-}
 
-impl From<&MutateParams> for MutatorConf {
-    fn from(mutate_params: &MutateParams) -> Self {
-        let mutation_operators = if let Some(ops) = &mutate_params.mutations {
-            ops.iter()
-                .map(|op| {
-                    MutationType::from_str(op.as_str(), true)
-                        .unwrap_or_else(|_| panic!("Unrecognized mutation operator {op}"))
-                })
-                .collect()
-        } else {
-            MutationType::default_mutation_operators()
-        };
-        MutatorConf {
-            mutation_operators,
-            funcs_to_mutate: mutate_params.functions.clone(),
-            contract: mutate_params.contract.clone(),
-        }
-    }
-}
+    /// If this is `Some(fnames)` then never mutate functions with names in
+    /// `fnames`, even if they are selected by `funcs_to_mutate`
+    pub exclude_functions: Option<Vec<String>>,
 
-/// The mutator performs the actual logic of mutating a program, writes
-#[derive(Debug)]
-pub struct Mutator {
-    /// Configuration for this mutator
-    pub conf: MutatorConf,
+    /// If this is `Some(ranges)` then only keep the mutants of the mutated
+    /// file that start on a line in one of `ranges`
+    pub source_lines: Option<Vec<LineRange>>,
 
-    /// The original sources
-    pub sources: Vec<Rc<Source>>,
+    /// If this is `Some(cnames)` then never mutate contracts with names in
+    /// `cnames`, even if one of them is `contract`
+    pub exclude_contracts: Option<Vec<String>>,
 
-    /// The mutants, in order of generation
-    pub mutants: Vec<Mutant>,
+    /// Also mutate the functions that `contract` inherits and does not
+    /// override (see `inherited_contracts()`)
+    pub contract_closure: bool,
 
-    /// Solc configuration
-    solc: Solc,
+    /// Skip functions whose state mutability is `view` or `pure`
+    pub skip_pure_view: bool,
 
-    /// A temporary directory to store intermediate work
-    _tmp: PathBuf,
+    /// Mutate functions whose state mutability is `view` or `pure`, but tag
+    /// the resulting mutants with that mutability
+    pub tag_pure_view: bool,
+
+    /// Expression statements calling one of these functions are not deleted
+    /// by `DeleteExpressionMutation`
+    pub delete_expression_exclusions: Vec<String>,
+
+    /// Drop mutants that are trivially equivalent to another mutant (see
+    /// `drop_trivially_equivalent()`)
+    pub skip_trivially_equivalent: bool,
+
+    /// Also mutate the internal functions of libraries in other files that
+    /// are referenced by the mutated file (see `called_libraries()`)
+    pub include_called_libraries: bool,
+
+    /// Mutate the bodies of modifier definitions, which `funcs_to_mutate`
+    /// selects by name, like functions (see `--no_mutate_modifiers`)
+    pub mutate_modifiers: bool,
+
+    /// Mutate the statements of `unchecked { ... }` blocks (see
+    /// `--no_mutate_unchecked`)
+    pub mutate_unchecked: bool,
+
+    /// Collect the AST context of each mutated file, and the nodes enclosing
+    /// each mutant (see `--emit_original_ast`)
+    pub emit_original_ast: bool,
+
+    /// Only mutate functions that are reachable from an entry point of the
+    /// mutated contract (see `reachable_functions()`)
+    pub only_mutate_public_state_changing_paths: bool,
+
+    /// Subtrees of the AST nested more than this many levels deep are not
+    /// mutated (see `SolAST::prune_deeper_than()`)
+    pub max_ast_depth: usize,
+
+    /// The style of the comment inserted above each mutated line
+    pub comment_style: CommentStyle,
+
+    /// How mutants are validated
+    pub validation_mode: ValidationMode,
+
+    /// Validate mutants by compiling a temporary copy of the mutated file
+    /// with solc's command line interface instead of passing the mutated
+    /// contents to `solc --standard-json` (see `--legacy_validation`)
+    pub legacy_validation: bool,
+
+    /// Compare the ABI of each fully compiled mutant with the original's (see
+    /// `--compare_abi`)
+    pub compare_abi: bool,
+
+    /// How many mutants are validated at a time
+    pub validation_jobs: usize,
 }
 
-impl From<&MutateParams> for Mutator {
-    fn from(value: &MutateParams) -> Self {
-        let conf = MutatorConf::from(value);
-        let mut solc = Solc::new(
-            value.solc.clone(),
-            value
-                .outdir
-                .clone()
-                .unwrap_or(default_gambit_output_directory())
-                .into(),
-        );
-        solc.with_optimize(value.solc_optimize);
+/// How mutants are validated with solc (see `--validation_mode`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ValidationMode {
+    /// Only check that mutants parse (`solc --stop-after parsing`). This is
+    /// fast, but passes mutants that fail to type check
+    Fast,
+    /// Fully compile each mutant
+    #[default]
+    Full,
+    /// Check that mutants parse, and fully compile only those that do. This
+    /// accepts the same mutants as `full`
+    TwoStage,
+}
 
-        if let Some(evm_version) = value.solc_evm_version.clone() {
-            solc.with_evm_version(evm_version);
-        }
-        if let Some(basepath) = value.solc_base_path.clone() {
-            solc.with_basepath(basepath);
-        }
-        if let Some(allowpaths) = value.solc_allow_paths.clone() {
-            solc.with_allow_paths(allowpaths);
-        }
-        if let Some(remappings) = value.solc_remappings.clone() {
-            solc.with_remappings(remappings);
-        }
+/// An inclusive range of 1-based source lines, written `start:end` (or `line`
+/// for a single line), see `--source_lines`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
 
-        let sourceroot = match &value.sourceroot {
-            Some(sourceroot) => PathBuf::from(sourceroot),
-            None => {
-                // Attempt to use CWD as the sourceroot. Ensuer that the
-                // filename belongs to (is prefixed by) the sourceroot
-                let sourceroot = PathBuf::from(".").canonicalize().unwrap();
-                let filename = &value
-                    .filename
-                    .as_ref()
-                    .unwrap_or_else(|| panic!("Found unresolved filename in params: {:?}", value));
-                let filepath = PathBuf::from(filename).canonicalize().unwrap();
-                if !&filepath.starts_with(&sourceroot) {
-                    panic!("Unresolved sourceroot! Attempted to use the current working directory {} but filename {} was not a descendent.", sourceroot.display(), filepath.display());
-                }
+impl LineRange {
+    /// Does this range contain the 1-based `line`?
+    pub fn contains(&self, line: usize) -> bool {
+        self.start <= line && line <= self.end
+    }
+}
 
-                sourceroot
-            }
-        };
+impl std::str::FromStr for LineRange {
+    type Err = String;
 
-        let mut sources: Vec<Rc<Source>> = vec![];
-        if let Some(filename) = &value.filename {
-            log::info!("Creating Source from filename: {}", filename);
-            sources.push(Rc::new(
-                Source::new(filename.into(), sourceroot)
-                    .unwrap_or_else(|_| panic!("Couldn't read source {}", filename)),
-            ))
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .map_err(|_| format!("invalid line range `{}`: expected `start:end`", s))
+        };
+        let (start, end) = match s.split_once(':') {
+            Some((start, end)) => (parse(start)?, parse(end)?),
+            None => (parse(s)?, parse(s)?),
+        };
+        if start == 0 {
+            return Err(format!("invalid line range `{}`: lines start at 1", s));
+        }
+        if start > end {
+            return Err(format!("invalid line range `{}`: start is after end", s));
         }
-        Mutator::new(conf, sources, solc)
+        Ok(LineRange { start, end })
     }
 }
 
-impl Mutator {
-    pub fn new(conf: MutatorConf, sources: Vec<Rc<Source>>, solc: Solc) -> Mutator {
-        log::info!(
-            "Creating mutator:\n   conf: {:#?}\n    sources: {:?}\n    solc: {:#?}",
-            conf,
-            sources,
-            solc
-        );
-        Mutator {
-            conf,
-            sources,
-            mutants: vec![],
-            solc,
-            _tmp: "".into(),
-        }
+impl TryFrom<String> for LineRange {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
     }
+}
 
-    /// Run all mutations! This is the main external entry point into mutation.
-    /// This function:
-    ///
-    /// 1. Mutates each file
-    /// 2. TODO: Optionally validates (default: yes) all generated/filtered mutants
-    ///
-    /// and returns a Vec of mutants. These are not yet written to disk, and can
-    /// be further validated, suppressed, and downsampled as desired.
-    pub fn mutate(&mut self) -> Result<&Vec<Mutant>, Box<dyn error::Error>> {
-        let mut mutants: Vec<Mutant> = vec![];
+impl From<LineRange> for String {
+    fn from(range: LineRange) -> Self {
+        range.to_string()
+    }
+}
 
-        let solc = &self.solc;
-        for source in self.sources.iter() {
-            log::info!("Mutating source {}", source.filename().display());
+impl fmt::Display for LineRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start, self.end)
+    }
+}
 
-            match self.mutate_file(source.clone(), solc) {
-                Ok(mut file_mutants) => {
-                    log::info!("    Generated {} mutants from source", file_mutants.len());
-                    mutants.append(&mut file_mutants);
-                }
-                Err(e) => {
-                    log::warn!("Couldn't mutate source {}", source.filename().display());
-                    log::warn!("Encountered error: {}", e);
-                }
+/// An entry of `--functions`: a function (or modifier) name, optionally
+/// qualified by the contract defining it (`Contract.function`) and by the
+/// types of its parameters (`function(address,uint256)`, which selects one
+/// overload). A bare name selects the functions of that name in every
+/// contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionFilter {
+    pub contract: Option<String>,
+    pub name: String,
+    pub parameters: Option<Vec<String>>,
+}
+
+impl FunctionFilter {
+    pub fn parse(entry: &str) -> FunctionFilter {
+        let (qualified, parameters) = match entry.split_once('(') {
+            Some((qualified, parameters)) => {
+                let parameters = parameters.trim_end().trim_end_matches(')');
+                let parameters = parameters
+                    .split(',')
+                    .map(canonical_parameter_type)
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                (qualified, Some(parameters))
             }
+            None => (entry, None),
+        };
+        let (contract, name) = match qualified.rsplit_once('.') {
+            Some((contract, name)) => (Some(contract.trim().to_string()), name),
+            None => (None, qualified),
+        };
+        FunctionFilter {
+            contract,
+            name: name.trim().to_string(),
+            parameters,
         }
-
-        self.mutants.append(&mut mutants);
-        Ok(&self.mutants)
     }
 
-    /// Mutate a single file.
-    fn mutate_file(
-        &self,
-        source: Rc<Source>,
-        solc: &Solc,
-    ) -> Result<Vec<Mutant>, Box<dyn error::Error>> {
-        let ast = solc.compile_ast(source.filename())?;
-        if !solc.output_directory().exists() {
-            log::debug!(
-                "[Pre traverse] Output directory {} doesn't exist!",
-                solc.output_directory().display()
-            );
-        }
-        let result = ast.traverse(self, source).into_iter().flatten().collect();
-        if !solc.output_directory().exists() {
-            log::debug!(
-                "[Post traverse] Output directory {} doesn't exist!",
-                solc.output_directory().display()
-            );
-        }
-        Ok(result)
+    /// Check if this entry is qualified by a contract or parameter types
+    pub fn is_qualified(&self) -> bool {
+        self.contract.is_some() || self.parameters.is_some()
     }
 
-    /// Check if a node in the AST is an assert.
-    pub fn is_assert_call(node: &SolAST) -> bool {
-        node.name().map_or_else(|| false, |n| n == "assert")
+    /// Check if this entry selects the function `name` of `contract` (`None`
+    /// for free functions) whose parameters have the types `parameters`
+    /// (see `parameter_types()`)
+    pub fn matches(&self, contract: Option<&str>, name: &str, parameters: &[String]) -> bool {
+        self.name == name
+            && self.contract.as_deref().is_none_or(|c| contract == Some(c))
+            && self.parameters.as_deref().is_none_or(|p| p == parameters)
     }
+}
 
-    /// Get a slice of the mutants produced by this mutator
-    pub fn mutants(&self) -> &[Mutant] {
-        &self.mutants
+/// The types of the parameters of a function or modifier definition, as solc
+/// prints them but without data locations (e.g., `string` rather than
+/// `string memory`), so that they read like the function's signature
+pub fn parameter_types(node: &Value) -> Vec<String> {
+    node["parameters"]["parameters"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|p| {
+            canonical_parameter_type(p["typeDescriptions"]["typeString"].as_str().unwrap_or(""))
+        })
+        .collect()
+}
+
+/// Strip the data location and the `contract`/`struct`/`enum` keywords from
+/// a parameter type
+fn canonical_parameter_type(type_string: &str) -> String {
+    let mut t = type_string.trim();
+    for location in [
+        " storage ref",
+        " storage pointer",
+        " storage",
+        " memory",
+        " calldata",
+    ] {
+        t = t.strip_suffix(location).unwrap_or(t);
+    }
+    for keyword in ["contract ", "struct ", "enum "] {
+        t = t.strip_prefix(keyword).unwrap_or(t);
+    }
+    match t {
+        "address payable" => "address".to_string(),
+        _ => t.to_string(),
     }
+}
 
-    pub fn sources(&self) -> &Vec<Rc<Source>> {
-        &self.sources
+impl Default for MutatorConf {
+    fn default() -> Self {
+        MutatorConf {
+            mutation_operators: vec![],
+            operator_replacements: HashMap::new(),
+            funcs_to_mutate: None,
+            contract: None,
+            exclude_functions: None,
+            source_lines: None,
+            exclude_contracts: None,
+            contract_closure: false,
+            skip_pure_view: false,
+            tag_pure_view: false,
+            delete_expression_exclusions: default_delete_expression_exclusions(),
+            skip_trivially_equivalent: false,
+            include_called_libraries: false,
+            mutate_modifiers: true,
+            mutate_unchecked: true,
+            emit_original_ast: false,
+            only_mutate_public_state_changing_paths: false,
+            max_ast_depth: DEFAULT_MAX_AST_DEPTH,
+            comment_style: CommentStyle::default(),
+            validation_mode: ValidationMode::default(),
+            legacy_validation: false,
+            compare_abi: false,
+            validation_jobs: default_jobs(),
+        }
     }
+}
 
-    pub fn solc(&self) -> &Solc {
-        &self.solc
+fn default_delete_expression_exclusions() -> Vec<String> {
+    DEFAULT_DELETE_EXPRESSION_EXCLUSIONS
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Expand `@file` references in a list of mutation operators. Each `@file`
+/// is replaced by the operators listed in `file` (resolved relative to
+/// `base`), a JSON (or, with a `.yaml`/`.yml` extension, YAML) list of
+/// operator names. Other operators are kept as is.
+pub fn expand_mutation_operators(ops: &[String], base: &Path) -> Result<Vec<String>, String> {
+    let mut result = vec![];
+    for op in ops {
+        let file = match op.strip_prefix('@') {
+            Some(file) => base.join(file),
+            None => {
+                result.push(op.clone());
+                continue;
+            }
+        };
+        let contents = fs::read_to_string(&file).map_err(|e| {
+            format!(
+                "Could not read mutation operators from {}: {}",
+                file.display(),
+                e
+            )
+        })?;
+        let is_yaml = matches!(
+            file.extension().and_then(|e| e.to_str()),
+            Some("yaml") | Some("yml")
+        );
+        let names: Vec<String> = if is_yaml {
+            serde_yaml_ng::from_str(&contents).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_str(&contents).map_err(|e| e.to_string())
+        }
+        .map_err(|e| format!("Invalid mutation operators file {}: {}", file.display(), e))?;
+        for (i, name) in names.iter().enumerate() {
+            if MutationType::from_str(name.as_str(), true).is_err() {
+                let line = contents
+                    .find(name.as_str())
+                    .map_or(0, |pos| contents[..pos].matches('\n').count() + 1);
+                return Err(format!(
+                    "Invalid mutation operators file {}: unrecognized mutation operator `{}` (entry {}, line {})",
+                    file.display(),
+                    name,
+                    i + 1,
+                    line
+                ));
+            }
+        }
+        result.extend(names);
     }
+    Ok(result)
+}
 
-    /// validate a mutant by writing it to disk and compiling it. If compilation
-    /// fails then this is an invalid mutant.
-    pub fn validate_mutant(&self, mutant: &Mutant) -> Result<bool, Box<dyn error::Error>> {
-        let source_filename = mutant.source.filename();
-        let source_parent_dir = source_filename.parent().unwrap();
-        let mutant_file = NamedTempFile::new_in(source_parent_dir)?;
-        let mutant_file_path = mutant_file.path();
-        log::debug!(
-            "Validating mutant of {}: copying mutated code to {}",
-            source_filename.display(),
-            mutant_file_path.display()
-        );
-        let dir = tempdir()?;
-        MutantWriter::write_mutant_to_file(mutant_file_path, mutant)?;
-        let code = match self.solc().compile(mutant_file_path, dir.path()) {
-            Ok((code, _, _)) => code == 0,
-            Err(_) => false,
+/// Check that the `replacements` of each operator of `ops` that has any are
+/// replacement tokens of that operator (see
+/// `MutationType::replacement_tokens`)
+pub fn check_operator_replacements(ops: &[MutationSpec]) -> Result<(), String> {
+    for spec in ops {
+        let Some(replacements) = spec.replacements() else {
+            continue;
         };
-        Ok(code)
+        let op = MutationType::from_str(spec.name(), true)
+            .map_err(|_| format!("unrecognized mutation operator `{}`", spec.name()))?;
+        let tokens = op.replacement_tokens();
+        if tokens.is_empty() {
+            return Err(format!(
+                "mutation operator `{}` does not support `replacements`",
+                spec.name()
+            ));
+        }
+        if replacements.is_empty() {
+            return Err(format!(
+                "the `replacements` of mutation operator `{}` are empty",
+                spec.name()
+            ));
+        }
+        if let Some(illegal) = replacements.iter().find(|r| !tokens.contains(&r.as_str())) {
+            return Err(format!(
+                "`{}` is not a replacement of mutation operator `{}` (expected one of {})",
+                illegal,
+                spec.name(),
+                tokens
+                    .iter()
+                    .map(|t| format!("`{}`", t))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
     }
+    Ok(())
+}
 
-    pub fn get_valid_mutants(&self, mutants: &[Mutant]) -> Vec<Mutant> {
-        log::info!("Validating mutants...");
-        let mut valid_mutants = vec![];
-        for m in mutants.iter() {
-            if let Ok(true) = self.validate_mutant(m) {
-                valid_mutants.push(m.clone())
+/// Check the experimental operators among `ops` (see
+/// `MutationType::is_experimental`). Requesting one is an error unless
+/// `allow_experimental` is set, in which case the requested experimental
+/// operators are returned. Unrecognized operators are ignored.
+pub fn check_experimental_operators(
+    ops: &[String],
+    allow_experimental: bool,
+) -> Result<Vec<MutationType>, String> {
+    let mut experimental: Vec<MutationType> = vec![];
+    for op in ops {
+        if let Ok(op) = MutationType::from_str(op.as_str(), true) {
+            if op.is_experimental() && !experimental.contains(&op) {
+                experimental.push(op);
             }
         }
-        valid_mutants
     }
+    if experimental.is_empty() || allow_experimental {
+        return Ok(experimental);
+    }
+    let names: Vec<String> = experimental
+        .iter()
+        .map(|op| format!("`{}`", op.short_name()))
+        .collect();
+    Err(format!(
+        "experimental mutation operator(s) {} require `--allow_experimental` (`\"allow_experimental\": true` in a configuration file)",
+        names.join(", ")
+    ))
 }
 
-impl SolASTVisitor<Rc<Source>, Vec<Mutant>> for Mutator {
-    fn skip_node(&self, node: &SolAST, _source: &Rc<Source>) -> bool {
-        if let Some(e) = &node.element {
-            if let Some(e_obj) = e.as_object() {
-                if e_obj.contains_key("contractKind") {
-                    let contract_name = e_obj.get("name").unwrap();
-                    if let Some(contract) = &self.conf.contract {
-                        return contract != contract_name.as_str().unwrap();
-                    } else {
-                        return false;
-                    }
-                } else if node.node_kind() == Some("function".to_string()) {
-                    match &self.conf.funcs_to_mutate {
-                        Some(fns) => {
-                            if let Some(name) = node.name() {
-                                return !fns.contains(&name);
-                            }
-                            return true;
-                        }
-                        None => {
-                            return false;
-                        }
+impl From<&MutateParams> for MutatorConf {
+    fn from(mutate_params: &MutateParams) -> Self {
+        let mut mutation_operators = vec![];
+        let mut operator_replacements: HashMap<MutationType, Vec<String>> = HashMap::new();
+        let mut unrestricted = HashSet::new();
+        if let Some(ops) = &mutate_params.mutations {
+            for spec in ops {
+                let op = MutationType::from_str(spec.name(), true)
+                    .unwrap_or_else(|_| panic!("Unrecognized mutation operator {}", spec.name()));
+                if !mutation_operators.contains(&op) {
+                    mutation_operators.push(op);
+                }
+                // An operator that is also requested by name is unrestricted
+                match spec.replacements() {
+                    Some(replacements) => operator_replacements
+                        .entry(op)
+                        .or_default()
+                        .extend(replacements.iter().cloned()),
+                    None => {
+                        unrestricted.insert(op);
                     }
                 }
             }
+        } else {
+            mutation_operators = MutationType::default_mutation_operators();
+        }
+        operator_replacements.retain(|op, _| !unrestricted.contains(op));
+        MutatorConf {
+            mutation_operators,
+            operator_replacements,
+            funcs_to_mutate: mutate_params.functions.clone(),
+            contract: mutate_params.contract.clone(),
+            exclude_functions: mutate_params.exclude_functions.clone(),
+            source_lines: mutate_params.source_lines.clone(),
+            exclude_contracts: mutate_params.exclude_contracts.clone(),
+            contract_closure: mutate_params.contract_closure,
+            skip_pure_view: mutate_params.skip_pure_view,
+            tag_pure_view: mutate_params.tag_pure_view,
+            delete_expression_exclusions: mutate_params
+                .delete_expression_exclusions
+                .clone()
+                .unwrap_or_else(default_delete_expression_exclusions),
+            skip_trivially_equivalent: mutate_params.skip_trivially_equivalent,
+            include_called_libraries: mutate_params.include_called_libraries,
+            mutate_modifiers: !mutate_params.no_mutate_modifiers,
+            mutate_unchecked: !mutate_params.no_mutate_unchecked,
+            emit_original_ast: mutate_params.emit_original_ast,
+            only_mutate_public_state_changing_paths: mutate_params
+                .only_mutate_public_state_changing_paths,
+            max_ast_depth: mutate_params.max_ast_depth,
+            comment_style: mutate_params.comment_style,
+            validation_mode: mutate_params.validation_mode,
+            legacy_validation: mutate_params.legacy_validation,
+            compare_abi: mutate_params.compare_abi,
+            validation_jobs: mutate_params.jobs.unwrap_or_else(default_jobs).max(1),
         }
-        false
     }
+}
 
-    fn visit_node(&self, node: &SolAST, arg: &Rc<Source>) -> Option<Vec<Mutant>> {
-        let op_node_pairs: Vec<Mutant> = self
-            .conf
-            .mutation_operators
+/// Statistics collected while mutating, reported alongside the generated
+/// mutants
+#[derive(Debug, Clone, Default)]
+pub struct MutationStatistics {
+    /// The number of `view`/`pure` functions that were skipped
+    pub pure_view_functions_skipped: usize,
+
+    /// The number of `view`/`pure` functions whose mutants were tagged with
+    /// their mutability
+    pub pure_view_functions_tagged: usize,
+
+    /// The number of mutation candidates (an operator applied to a node) that
+    /// were skipped because their source text is not valid UTF-8
+    pub invalid_utf8_candidates_skipped: usize,
+
+    /// The number of mutants that were dropped because they are likely
+    /// equivalent to the original program (see `is_likely_equivalent()`)
+    pub likely_equivalent_mutants_skipped: usize,
+
+    /// The number of functions of called libraries that were mutated along
+    /// with the mutated file (see `--include_called_libraries`)
+    pub library_functions_included: usize,
+
+    /// The number of inherited functions of base contracts that were mutated
+    /// along with the `--contract` (see `--contract_closure`)
+    pub inherited_functions_included: usize,
+
+    /// The number of modifiers whose bodies were mutated (see
+    /// `--no_mutate_modifiers`)
+    pub modifiers_mutated: usize,
+
+    /// The number of AST subtrees that were not mutated because they are
+    /// nested more than `--max_ast_depth` levels deep
+    pub deep_subtrees_pruned: usize,
+
+    /// The number of functions that were not mutated because they are not
+    /// reachable from an entry point (see
+    /// `--only_mutate_public_state_changing_paths`)
+    pub unreachable_functions_skipped: usize,
+
+    /// The number of mutation candidates in those unreachable functions
+    pub unreachable_candidates_skipped: usize,
+
+    /// The number of mutants that were dropped because they are trivially
+    /// equivalent to another mutant (see `--skip_trivially_equivalent`)
+    pub trivially_equivalent_mutants_skipped: usize,
+
+    /// The number of mutants that were dropped because they don't start on
+    /// one of the `--source_lines`
+    pub mutants_outside_source_lines: usize,
+
+    /// The contracts and functions in scope of each mutated source unit,
+    /// whose mutants are counted in the mutation report
+    pub mutated_units: Vec<MutatedUnit>,
+
+    /// With `--emit_original_ast`, the AST context of each mutated source
+    /// unit
+    pub ast_contexts: Vec<FileAstContext>,
+
+    /// The `--contract` and `--functions` names that the mutated source
+    /// units define
+    pub filter_names: FilterNameMatches,
+}
+
+/// The contracts and functions in scope of a mutated source unit (see
+/// `MutationReport`)
+#[derive(Debug, Clone)]
+pub struct MutatedUnit {
+    /// The mutated file
+    pub source: Rc<Source>,
+
+    /// The name and bounds of each contract in scope
+    pub contracts: Vec<(String, (usize, usize))>,
+
+    /// The functions (and, unless `--no_mutate_modifiers`, modifiers) in scope
+    pub functions: Vec<FunctionInfo>,
+}
+
+/// The `--contract` and `--functions` names of a run, and whether any mutated
+/// source unit defines them. A name that is never found (e.g., a typo) selects
+/// nothing, which would otherwise silently generate no mutants.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilterNameMatches {
+    /// Each `--contract` name, and whether it was found
+    pub contracts: BTreeMap<String, bool>,
+
+    /// Each `--functions` entry, and whether it selected a function
+    pub functions: BTreeMap<String, bool>,
+
+    /// The names of the functions (and, unless `--no_mutate_modifiers`, modifiers)
+    /// that the `--functions` entries could select: those of the `--contract`
+    /// (and free functions) when it is found, and all of them otherwise
+    pub available: BTreeSet<String>,
+}
+
+impl FilterNameMatches {
+    /// Record the names of `conf`'s filters that `ast` defines. Only the
+    /// functions of the `--contract` and free functions are selected, like
+    /// in `Mutator::check_names()`
+    pub fn record(&mut self, ast: &SolAST, conf: &MutatorConf) {
+        let contracts: Vec<String> = ast
+            .nodes()
             .iter()
-            .filter(|m| m.applies_to(node))
-            .flat_map(|m| m.mutate(node, arg.clone()))
+            .filter(|n| n.node_type().as_deref() == Some("ContractDefinition"))
+            .filter_map(|n| n.name())
+            .collect();
+        if let Some(contract) = &conf.contract {
+            *self.contracts.entry(contract.clone()).or_default() |= contracts.contains(contract);
+        }
+        let contract = conf.contract.as_ref().filter(|c| contracts.contains(c));
+        let modifiers = if conf.mutate_modifiers {
+            FunctionInfo::collect_modifiers(ast)
+        } else {
+            vec![]
+        };
+        let functions: Vec<FunctionInfo> = FunctionInfo::collect(ast)
+            .into_iter()
+            .chain(modifiers)
+            .filter(|f| {
+                contract.is_none() || f.contract.is_none() || f.contract.as_ref() == contract
+            })
             .collect();
+        for entry in conf.funcs_to_mutate.iter().flatten() {
+            let filter = FunctionFilter::parse(entry);
+            let found = functions
+                .iter()
+                .any(|f| filter.matches(f.contract.as_deref(), &f.name, &f.parameters));
+            *self.functions.entry(entry.clone()).or_default() |= found;
+        }
+        if conf.funcs_to_mutate.is_some() {
+            self.available.extend(
+                functions
+                    .into_iter()
+                    .map(|f| f.name)
+                    .filter(|name| !name.is_empty()),
+            );
+        }
+    }
 
-        Some(op_node_pairs)
+    /// Add the names recorded by `other` (e.g., for another file)
+    pub fn merge(&mut self, other: &FilterNameMatches) {
+        for (contract, found) in other.contracts.iter() {
+            *self.contracts.entry(contract.clone()).or_default() |= found;
+        }
+        for (function, found) in other.functions.iter() {
+            *self.functions.entry(function.clone()).or_default() |= found;
+        }
+        self.available.extend(other.available.iter().cloned());
+    }
+
+    /// The names that were never found, as the options naming them (e.g.,
+    /// ``--functions `transferForm` ``)
+    pub fn unmatched(&self) -> Vec<String> {
+        let unmatched = |option: &str, names: &BTreeMap<String, bool>| {
+            names
+                .iter()
+                .filter(|(_, found)| !**found)
+                .map(|(name, _)| format!("{} `{}`", option, name))
+                .collect::<Vec<_>>()
+        };
+        let mut names = unmatched("--contract", &self.contracts);
+        names.append(&mut unmatched("--functions", &self.functions));
+        names
+    }
+}
+
+/// A place where a mutation operator applies, as reported by
+/// `--locations_only`. Every mutant that a full run generates for this
+/// candidate starts within `span`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MutationCandidate {
+    /// The mutation operator that applies
+    pub op: MutationType,
+
+    /// The path of the file, relative to its sourceroot
+    pub file: String,
+
+    /// The (1-indexed) line where the candidate node starts
+    pub line: usize,
+
+    /// The (1-indexed) column where the candidate node starts
+    pub col: usize,
+
+    /// The byte offsets `[start, end)` of the candidate node in the file
+    pub span: (usize, usize),
+}
+
+/// How the mutation operators fare at the AST nodes covering a location of a
+/// file, as reported by `--explain`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// The path of the file, relative to its sourceroot
+    pub file: String,
+
+    /// The (1-indexed) line being explained
+    pub line: usize,
+
+    /// The (1-indexed) column being explained
+    pub col: usize,
+
+    /// Why the location is not mutated at all, if it is in a contract or
+    /// function that is not selected for mutation
+    pub out_of_scope: Option<String>,
+
+    /// The nodes covering the location, from the innermost statement down to
+    /// the innermost node, and the ancestors of that statement that generate
+    /// mutants covering the location
+    pub nodes: Vec<NodeTrace>,
+}
+
+/// How the mutation operators fare at a single AST node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeTrace {
+    /// The node's type (e.g., `BinaryOperation`)
+    pub node_type: String,
+
+    /// The (1-indexed) line and column where the node starts
+    pub line: usize,
+    pub col: usize,
+
+    /// The first line of the node's source text
+    pub text: String,
+
+    /// One trace per configured operator
+    pub operators: Vec<OperatorTrace>,
+}
+
+/// Whether a mutation operator applied to a node, and what it proposed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperatorTrace {
+    pub op: MutationType,
+
+    /// Why the operator generated no mutants here, if it didn't
+    pub skipped: Option<String>,
+
+    /// The mutants the operator generated here
+    pub replacements: Vec<ReplacementTrace>,
+}
+
+/// A mutant proposed by an operator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplacementTrace {
+    /// The (1-indexed) line and column where the mutant starts
+    pub line: usize,
+    pub col: usize,
+    pub orig: String,
+    pub repl: String,
+
+    /// Remarks on the mutant (e.g., that it duplicates an earlier mutant).
+    /// These don't keep the mutant from being generated.
+    pub notes: Vec<String>,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Explaining {}:{}:{}", self.file, self.line, self.col)?;
+        if let Some(reason) = &self.out_of_scope {
+            writeln!(f, "  Not mutated: {}", reason)?;
+        }
+        if self.nodes.is_empty() {
+            writeln!(f, "  No mutated AST node covers this location")?;
+        }
+        for node in self.nodes.iter() {
+            writeln!(
+                f,
+                "{} at {}:{}: `{}`",
+                node.node_type, node.line, node.col, node.text
+            )?;
+            for trace in node.operators.iter() {
+                let name = trace.op.short_name();
+                match &trace.skipped {
+                    Some(reason) => writeln!(f, "  {}: {}", name, reason)?,
+                    None => writeln!(f, "  {}: {} mutants", name, trace.replacements.len())?,
+                }
+                for r in trace.replacements.iter() {
+                    write!(f, "    {}:{}: `{}` -> `{}`", r.line, r.col, r.orig, r.repl)?;
+                    if !r.notes.is_empty() {
+                        write!(f, " ({})", r.notes.join("; "))?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A function definition in a source file. This is used to attribute mutants
+/// to the function (and contract) that they were generated in.
+#[derive(Debug, Clone)]
+pub struct FunctionInfo {
+    /// The function's name
+    pub name: String,
+
+    /// The name of the contract defining this function, or `None` for free
+    /// functions
+    pub contract: Option<String>,
+
+    /// The function's kind (e.g., `function`, `constructor`, `fallback`)
+    pub kind: Option<String>,
+
+    /// The function's state mutability
+    pub state_mutability: Option<String>,
+
+    /// The types of the function's parameters (see `parameter_types()`)
+    pub parameters: Vec<String>,
+
+    /// The start of the function definition in the source
+    pub start: usize,
+
+    /// The end of the function definition in the source
+    pub end: usize,
+
+    /// The bounds of the only statement of the function's body, if its body
+    /// has exactly one statement
+    pub sole_statement: Option<(usize, usize)>,
+}
+
+impl FunctionInfo {
+    fn from_node(node: &SolAST, contract: Option<String>) -> FunctionInfo {
+        let (start, end) = node.get_bounds();
+        let statements = node.get_node("body").statements();
+        FunctionInfo {
+            name: node.name().unwrap_or_default(),
+            contract,
+            kind: node.node_kind(),
+            state_mutability: node.state_mutability(),
+            parameters: node
+                .element
+                .as_ref()
+                .map(parameter_types)
+                .unwrap_or_default(),
+            start,
+            end,
+            sole_statement: match statements.as_slice() {
+                [statement] => Some(statement.get_bounds()),
+                _ => None,
+            },
+        }
+    }
+
+    /// Collect all function definitions (both free functions and functions
+    /// defined in contracts) in a source unit
+    pub fn collect(ast: &SolAST) -> Vec<FunctionInfo> {
+        Self::collect_definitions(ast, "FunctionDefinition")
+    }
+
+    /// Collect all modifier definitions in a source unit. Their `kind` is
+    /// `modifier`.
+    pub fn collect_modifiers(ast: &SolAST) -> Vec<FunctionInfo> {
+        let mut modifiers = Self::collect_definitions(ast, "ModifierDefinition");
+        for modifier in modifiers.iter_mut() {
+            modifier.kind = Some("modifier".into());
+        }
+        modifiers
+    }
+
+    fn collect_definitions(ast: &SolAST, node_type: &str) -> Vec<FunctionInfo> {
+        let mut definitions = vec![];
+        for node in ast.nodes() {
+            match node.node_type().as_deref() {
+                Some(t) if t == node_type => definitions.push(Self::from_node(&node, None)),
+                Some("ContractDefinition") => {
+                    for n in node.nodes() {
+                        if n.node_type().as_deref() == Some(node_type) {
+                            definitions.push(Self::from_node(&n, node.name()));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        definitions
+    }
+
+    /// Does this function contain the source position `pos`?
+    pub fn contains(&self, pos: usize) -> bool {
+        self.start <= pos && pos < self.end
+    }
+
+    /// Is this function's state mutability `view` or `pure`?
+    pub fn is_pure_or_view(&self) -> bool {
+        is_pure_or_view(self.state_mutability.as_deref())
+    }
+}
+
+/// Drop the `DeleteExpressionMutation` mutants of statements that are the
+/// only statement of their function when another mutant of the statement
+/// already has (nearly) the same effect. Deleting a function's only
+/// `require(c)` is equivalent to the `RequireMutation` mutant `require(true)`,
+/// and deleting the only assignment of a trivial setter mostly duplicates its
+/// `AssignmentMutation` mutants.
+pub fn drop_trivially_equivalent(mutants: Vec<Mutant>, functions: &[FunctionInfo]) -> Vec<Mutant> {
+    let is_equivalent_deletion = |deletion: &Mutant| {
+        deletion.op == MutationType::DeleteExpressionMutation
+            && functions
+                .iter()
+                .any(|f| f.sole_statement == Some((deletion.start, deletion.end)))
+            && mutants.iter().any(|m| {
+                let weakens = match m.op {
+                    MutationType::RequireMutation => m.repl == "true",
+                    MutationType::AssignmentMutation => true,
+                    _ => false,
+                };
+                weakens && deletion.start <= m.start && m.end <= deletion.end
+            })
+    };
+    let keep: Vec<bool> = mutants.iter().map(|m| !is_equivalent_deletion(m)).collect();
+    mutants
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(m, keep)| keep.then_some(m))
+        .collect()
+}
+
+/// The operators that `BinaryOpMutation` may replace a user-defined operator
+/// with, if they are user-defined for the same type (`**` cannot be
+/// user-defined)
+const USER_DEFINABLE_ARITHMETIC_OPERATORS: [&str; 5] = ["+", "-", "*", "/", "%"];
+
+/// A binary operation that resolves to a user-defined operator, as in `a + b`
+/// given `using {add as +} for T global` (Solidity 0.8.19+)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserDefinedOperation {
+    /// The function implementing the operator, if the `using for` directive
+    /// binding it is in the same source unit
+    pub function: Option<String>,
+
+    /// The operators that the operator may be replaced with: the arithmetic
+    /// operators that are also user-defined for the operands' type. This is
+    /// empty for comparisons, which must return a `bool`, and when the
+    /// binding directive is in another source unit.
+    pub replacements: Vec<String>,
+}
+
+/// Find the binary operations of a source unit that resolve to user-defined
+/// operators, keyed by the span that `BinaryOpMutation` replaces (from the
+/// end of the left operand to the start of the right operand)
+pub fn user_defined_operations(ast: &SolAST) -> HashMap<(usize, usize), UserDefinedOperation> {
+    // The name and type of each function bound to an operator, and the
+    // operators that are user-defined for each type
+    let mut bindings: HashMap<i64, (String, i64)> = HashMap::new();
+    let mut type_operators: HashMap<i64, Vec<String>> = HashMap::new();
+    let mut operations: Vec<&Value> = vec![];
+    let mut worklist: Vec<&Value> = ast.element.iter().collect();
+    while let Some(value) = worklist.pop() {
+        match value {
+            Value::Object(o) => {
+                if value["nodeType"] == "UsingForDirective" {
+                    let ty = value["typeName"]["referencedDeclaration"].as_i64();
+                    for binding in value["functionList"].as_array().into_iter().flatten() {
+                        let definition = &binding["definition"];
+                        if let (Some(ty), Some(op), Some(id), Some(name)) = (
+                            ty,
+                            binding["operator"].as_str(),
+                            definition["referencedDeclaration"].as_i64(),
+                            definition["name"].as_str(),
+                        ) {
+                            bindings.insert(id, (name.to_string(), ty));
+                            type_operators.entry(ty).or_default().push(op.to_string());
+                        }
+                    }
+                } else if value["nodeType"] == "BinaryOperation" && value["function"].is_i64() {
+                    operations.push(value);
+                }
+                worklist.extend(o.values());
+            }
+            Value::Array(a) => worklist.extend(a.iter()),
+            _ => (),
+        }
+    }
+
+    let mut result = HashMap::new();
+    for operation in operations {
+        let node = SolAST::new(operation.clone());
+        let (_, endl) = node.left_expression().get_bounds();
+        let (startr, _) = node.right_expression().get_bounds();
+        let orig = node.operator().unwrap_or_default();
+        let binding = operation["function"]
+            .as_i64()
+            .and_then(|id| bindings.get(&id));
+        let replacements = match binding {
+            Some((_, ty)) if USER_DEFINABLE_ARITHMETIC_OPERATORS.contains(&orig.as_str()) => {
+                USER_DEFINABLE_ARITHMETIC_OPERATORS
+                    .iter()
+                    .filter(|op| **op != orig && type_operators[ty].iter().any(|o| o == *op))
+                    .map(|op| op.to_string())
+                    .collect()
+            }
+            _ => vec![],
+        };
+        result.insert(
+            (endl, startr),
+            UserDefinedOperation {
+                function: binding.map(|(name, _)| name.clone()),
+                replacements,
+            },
+        );
+    }
+    result
+}
+
+/// Restrict the `BinaryOpMutation` mutants of user-defined operators (see
+/// `user_defined_operations()`) to their allowed replacements, tagging the
+/// remaining ones with the function implementing the operator. The standard
+/// replacements of a user-defined operator may not be defined for its type,
+/// or may silently call a different function.
+pub fn restrict_user_defined_operators(
+    mutants: Vec<Mutant>,
+    operations: &HashMap<(usize, usize), UserDefinedOperation>,
+) -> Vec<Mutant> {
+    mutants
+        .into_iter()
+        .filter_map(|mut m| {
+            if m.op != MutationType::BinaryOpMutation {
+                return Some(m);
+            }
+            match operations.get(&(m.start, m.end)) {
+                Some(operation) if operation.replacements.contains(&m.repl) => {
+                    m.user_defined_operator = operation.function.clone();
+                    Some(m)
+                }
+                Some(_) => None,
+                None => Some(m),
+            }
+        })
+        .collect()
+}
+
+/// Drop the `BoundaryLiteralMutation` mutants that replace a literal with a
+/// value that a mutant of another operator already replaces it with (e.g.,
+/// both it and `AssignmentMutation` mutate `x = 1` to `x = 0`)
+pub fn drop_duplicate_boundary_mutants(mutants: Vec<Mutant>) -> Vec<Mutant> {
+    let key = |m: &Mutant| {
+        let value = m.repl.trim_start_matches('(').trim_end_matches(')');
+        (m.start, m.end, value.to_string())
+    };
+    let others: HashSet<(usize, usize, String)> = mutants
+        .iter()
+        .filter(|m| m.op != MutationType::BoundaryLiteralMutation)
+        .map(key)
+        .collect();
+    mutants
+        .into_iter()
+        .filter(|m| m.op != MutationType::BoundaryLiteralMutation || !others.contains(&key(m)))
+        .collect()
+}
+
+fn is_pure_or_view(mutability: Option<&str>) -> bool {
+    matches!(mutability, Some("view") | Some("pure"))
+}
+
+/// The mutator performs the actual logic of mutating a program, writes
+#[derive(Debug)]
+pub struct Mutator {
+    /// Configuration for this mutator
+    pub conf: MutatorConf,
+
+    /// The original sources
+    pub sources: Vec<Rc<Source>>,
+
+    /// The mutants, in order of generation
+    pub mutants: Vec<Mutant>,
+
+    /// Statistics collected during mutation
+    pub stats: MutationStatistics,
+
+    /// Solc configuration
+    solc: Solc,
+
+    /// When set, mutants are no longer validated after this instant (see
+    /// `--max_validation_seconds`)
+    validation_deadline: Option<Instant>,
+
+    /// When set, mutants are no longer validated once the flag is raised
+    /// (e.g., by Ctrl-C; see `run_mutate_cancellable()`)
+    cancellation: Option<Arc<AtomicBool>>,
+
+    /// The validation result of each mutant that was validated, keyed by its
+    /// file, span, and replacement, so that a mutant is compiled at most once
+    validation_cache: RefCell<HashMap<ValidationKey, bool>>,
+
+    /// The mutants that could not be validated because solc kept failing for
+    /// reasons unrelated to them (see `GambitError::SolcUnavailable`), keyed
+    /// like `validation_cache`. These are passed through unvalidated
+    validation_errors: RefCell<HashSet<ValidationKey>>,
+
+    /// The number of mutants that were compiled to validate them
+    validation_attempts: Cell<usize>,
+
+    /// How mutants were checked and rejected by each validation stage
+    validation_stages: RefCell<ValidationStages>,
+
+    /// The wall-clock time it took to validate each compiled mutant, keyed
+    /// like `validation_cache`
+    validation_durations: RefCell<HashMap<ValidationKey, Duration>>,
+
+    /// With `--compare_abi`, the ABIs of the contracts of each original file,
+    /// extracted when its first mutant is validated. This is `None` for files
+    /// whose ABIs could not be extracted
+    original_abis: RefCell<HashMap<PathBuf, Option<ContractAbis>>>,
+
+    /// With `--compare_abi`, whether each fully compiled mutant changes the
+    /// ABI, keyed like `validation_cache`
+    abi_changes: RefCell<HashMap<ValidationKey, bool>>,
+
+    /// The vyper binary, when mutating Vyper sources (`--language vyper`)
+    #[cfg(feature = "vyper")]
+    vyper: Option<String>,
+
+    /// A temporary directory to store intermediate work
+    _tmp: PathBuf,
+}
+
+/// Build the mutator of a parameter set. Solc settings that cannot be read
+/// are a `GambitError::Configuration`, and a filename or sourceroot that
+/// cannot be resolved, or a source that cannot be read, is a
+/// `GambitError::FileResolution`.
+impl TryFrom<&MutateParams> for Mutator {
+    type Error = GambitError;
+
+    fn try_from(value: &MutateParams) -> Result<Self, Self::Error> {
+        let conf = MutatorConf::from(value);
+        let mut solc = Solc::new(
+            value.solc.clone(),
+            value
+                .outdir
+                .clone()
+                .unwrap_or(default_gambit_output_directory())
+                .into(),
+        );
+        solc.with_optimize(value.solc_optimize);
+        solc.with_retries(value.solc_retries);
+
+        if let Some(evm_version) = value.solc_evm_version.clone() {
+            solc.with_evm_version(evm_version);
+        }
+        // Settings files are resolved when the configuration is read
+        if let Some(settings) = &value.solc_settings {
+            let settings = settings
+                .load(Path::new("."))
+                .map_err(GambitError::Configuration)?;
+            solc.with_settings(&settings);
+        }
+        // The remappings and import paths of the project, if one was given.
+        // Solc resolves include paths only alongside a base path, which
+        // defaults to the project root
+        let layout = value
+            .project_root
+            .as_ref()
+            .map(|root| discover_project_layout(Path::new(root)))
+            .unwrap_or_default();
+        let basepath = value.solc_base_path.clone().or_else(|| {
+            (!layout.import_paths.is_empty())
+                .then(|| value.project_root.clone())
+                .flatten()
+        });
+        if let Some(basepath) = basepath {
+            solc.with_basepath(basepath);
+        }
+        if let Some(allowpaths) = value.solc_allow_paths.clone() {
+            solc.with_allow_paths(allowpaths);
+        }
+        for include_path in value.solc_include_path.iter().chain(&layout.import_paths) {
+            solc.with_include_path(include_path.clone());
+        }
+        let remappings = merge_discovered_remappings(
+            value.solc_remappings.as_deref().unwrap_or_default(),
+            &layout.remappings,
+        );
+        if !remappings.is_empty() {
+            solc.with_remappings(remappings);
+        }
+
+        let sourceroot = match &value.sourceroot {
+            Some(sourceroot) => PathBuf::from(sourceroot),
+            None => {
+                // Attempt to use CWD as the sourceroot. Ensuer that the
+                // filename belongs to (is prefixed by) the sourceroot
+                let sourceroot = PathBuf::from(".").canonicalize().map_err(|e| {
+                    GambitError::FileResolution(format!(
+                        "Could not resolve the current working directory: {}",
+                        e
+                    ))
+                })?;
+                let filename = value.filename.as_ref().ok_or_else(|| {
+                    GambitError::FileResolution("Found a configuration without a filename".into())
+                })?;
+                let filepath = PathBuf::from(filename).canonicalize().map_err(|e| {
+                    GambitError::FileResolution(format!(
+                        "Could not resolve filename `{}`: {}",
+                        filename, e
+                    ))
+                })?;
+                if !filepath.starts_with(&sourceroot) {
+                    return Err(GambitError::FileResolution(format!(
+                        "Resolved filename `{}` is not prefixed by the current working directory {}",
+                        filepath.display(),
+                        sourceroot.display()
+                    )));
+                }
+
+                sourceroot
+            }
+        };
+
+        let mut sources: Vec<Rc<Source>> = vec![];
+        if let Some(filename) = &value.filename {
+            log::info!("Creating Source from filename: {}", filename);
+            let source = Source::new(filename.into(), sourceroot).map_err(|e| {
+                GambitError::FileResolution(format!("Could not read source {}: {}", filename, e))
+            })?;
+            sources.push(Rc::new(source))
+        }
+        let mutator = Mutator::new(conf, sources, solc);
+        #[cfg(feature = "vyper")]
+        let mutator = Mutator {
+            vyper: (value.language == crate::Language::Vyper).then(|| value.vyper.clone()),
+            ..mutator
+        };
+        Ok(mutator)
+    }
+}
+
+impl Mutator {
+    pub fn new(conf: MutatorConf, sources: Vec<Rc<Source>>, solc: Solc) -> Mutator {
+        log::info!(
+            "Creating mutator:\n   conf: {:#?}\n    sources: {:?}\n    solc: {:#?}",
+            conf,
+            sources,
+            solc
+        );
+        Mutator {
+            conf,
+            sources,
+            mutants: vec![],
+            stats: MutationStatistics::default(),
+            solc,
+            validation_deadline: None,
+            cancellation: None,
+            validation_cache: RefCell::new(HashMap::new()),
+            validation_errors: RefCell::new(HashSet::new()),
+            validation_attempts: Cell::new(0),
+            validation_stages: RefCell::new(ValidationStages::default()),
+            validation_durations: RefCell::new(HashMap::new()),
+            original_abis: RefCell::new(HashMap::new()),
+            abi_changes: RefCell::new(HashMap::new()),
+            #[cfg(feature = "vyper")]
+            vyper: None,
+            _tmp: "".into(),
+        }
+    }
+
+    /// Run all mutations! This is the main external entry point into mutation.
+    /// This function:
+    ///
+    /// 1. Mutates each file
+    /// 2. TODO: Optionally validates (default: yes) all generated/filtered mutants
+    ///
+    /// and returns a Vec of mutants. These are not yet written to disk, and can
+    /// be further validated, suppressed, and downsampled as desired.
+    pub fn mutate(&mut self) -> Result<&Vec<Mutant>, Box<dyn error::Error>> {
+        let mut mutants: Vec<Mutant> = vec![];
+        let mut stats = self.stats.clone();
+
+        let solc = &self.solc;
+        for source in self.sources.iter() {
+            log::info!("Mutating source {}", source.filename().display());
+
+            match self.mutate_file(source.clone(), solc, &mut stats) {
+                Ok(mut file_mutants) => {
+                    log::info!("    Generated {} mutants from source", file_mutants.len());
+                    mutants.append(&mut file_mutants);
+                }
+                // solc could not be invoked at all: no other source can be
+                // mutated either
+                Err(e) if e.is::<GambitError>() => return Err(e),
+                Err(e) => {
+                    log::warn!("Couldn't mutate source {}", source.filename().display());
+                    log::warn!("Encountered error: {}", e);
+                }
+            }
+        }
+
+        if self.conf.skip_pure_view {
+            log::info!(
+                "Skipped {} view/pure functions",
+                stats.pure_view_functions_skipped
+            );
+        }
+        if self.conf.tag_pure_view {
+            log::info!(
+                "Tagged mutants of {} view/pure functions",
+                stats.pure_view_functions_tagged
+            );
+        }
+        if self.conf.include_called_libraries {
+            log::info!(
+                "Included {} functions of called libraries",
+                stats.library_functions_included
+            );
+        }
+        if self.conf.contract_closure {
+            log::info!(
+                "Included {} inherited functions of base contracts",
+                stats.inherited_functions_included
+            );
+        }
+        if self.conf.mutate_modifiers {
+            log::info!("Mutated {} modifiers", stats.modifiers_mutated);
+        }
+        if self.conf.only_mutate_public_state_changing_paths {
+            log::info!(
+                "Skipped {} unreachable functions ({} mutation candidates)",
+                stats.unreachable_functions_skipped,
+                stats.unreachable_candidates_skipped
+            );
+        }
+        if self.conf.skip_trivially_equivalent {
+            log::info!(
+                "Skipped {} trivially equivalent mutants",
+                stats.trivially_equivalent_mutants_skipped
+            );
+        }
+        if stats.likely_equivalent_mutants_skipped > 0 {
+            log::info!(
+                "{} mutants skipped (likely equivalent)",
+                stats.likely_equivalent_mutants_skipped
+            );
+        }
+        if self.conf.source_lines.is_some() {
+            log::info!(
+                "Skipped {} mutants outside of the source lines",
+                stats.mutants_outside_source_lines
+            );
+        }
+        if stats.invalid_utf8_candidates_skipped > 0 {
+            log::warn!(
+                "Skipped {} mutation candidates with invalid UTF-8 source text",
+                stats.invalid_utf8_candidates_skipped
+            );
+        }
+
+        for mutant in mutants.iter_mut() {
+            mutant.comment_style = self.conf.comment_style;
+        }
+        self.stats = stats;
+        self.mutants.append(&mut mutants);
+        Ok(&self.mutants)
+    }
+
+    /// Mutate a single file.
+    fn mutate_file(
+        &self,
+        source: Rc<Source>,
+        solc: &Solc,
+        stats: &mut MutationStatistics,
+    ) -> Result<Vec<Mutant>, Box<dyn error::Error>> {
+        if source.has_bom() {
+            report(
+                Diagnostic::new(
+                    DiagnosticCode::ByteOrderMark,
+                    format!(
+                        "{} starts with a UTF-8 byte order mark, which is kept at the start of its mutants",
+                        source.filename().display()
+                    ),
+                )
+                .with_file(source.filename().display()),
+            );
+        }
+        #[cfg(feature = "vyper")]
+        if let Some(vyper) = &self.vyper {
+            if self.conf.only_mutate_public_state_changing_paths {
+                log::warn!("--only_mutate_public_state_changing_paths is not supported for Vyper: mutating every function");
+            }
+            let ast = crate::compile_vyper_ast(vyper, source.filename())?;
+            let mutants = crate::mutate_vyper_ast(ast, source, &self.conf);
+            return Ok(self.restrict_to_source_lines(mutants, stats));
+        }
+        let ast = solc.compile_ast(source.filename())?;
+        stats.filter_names.record(&ast, &self.conf);
+        for warning in filter_name_warnings(
+            &ast,
+            self.conf.contract.as_deref(),
+            self.conf.funcs_to_mutate.as_deref(),
+        ) {
+            report(
+                Diagnostic::new(
+                    DiagnosticCode::NearMissFilterName,
+                    format!("{}: {}", source.filename().display(), warning),
+                )
+                .with_file(source.filename().display()),
+            );
+        }
+        // Qualified entries may name the contracts of other files that
+        // `--contract_closure` and `--include_called_libraries` mutate
+        if !self.conf.contract_closure && !self.conf.include_called_libraries {
+            for warning in qualified_function_warnings(&ast, self.conf.funcs_to_mutate.as_deref()) {
+                report(
+                    Diagnostic::new(
+                        DiagnosticCode::UnknownQualifiedContract,
+                        format!("{}: {}", source.filename().display(), warning),
+                    )
+                    .with_file(source.filename().display()),
+                );
+            }
+        }
+        if !solc.output_directory().exists() {
+            log::debug!(
+                "[Pre traverse] Output directory {} doesn't exist!",
+                solc.output_directory().display()
+            );
+        }
+        // The target file's mutants are tagged with their defining contract
+        // below, after `mutate_ast()` consumed the AST
+        let closure_unit = self.conf.contract_closure.then(|| ast.clone());
+        let mut result = self.mutate_ast(ast, source.clone(), stats);
+        if !solc.output_directory().exists() {
+            log::debug!(
+                "[Post traverse] Output directory {} doesn't exist!",
+                solc.output_directory().display()
+            );
+        }
+        if let (Some(unit), Some(contract)) = (closure_unit, &self.conf.contract) {
+            tag_contracts(&mut result, &unit, contract);
+            match self.mutate_inherited_contracts(&source, contract, solc, stats) {
+                Ok(mut inherited_mutants) => result.append(&mut inherited_mutants),
+                Err(e) => log::warn!(
+                    "Couldn't mutate the contracts that {} inherits from: {}",
+                    contract,
+                    e
+                ),
+            }
+        }
+        if self.conf.include_called_libraries {
+            match self.mutate_called_libraries(&source, solc, stats) {
+                Ok(mut library_mutants) => result.append(&mut library_mutants),
+                Err(e) => log::warn!(
+                    "Couldn't mutate the libraries called by {}: {}",
+                    source.filename().display(),
+                    e
+                ),
+            }
+        }
+        Ok(self.restrict_to_source_lines(result, stats))
+    }
+
+    /// Drop the mutants of the mutated file that don't start on one of the
+    /// `conf.source_lines`, before they are validated. Mutants of other files
+    /// (e.g., of called libraries, which are tagged with a target file) are
+    /// kept, since the lines refer to the mutated file.
+    fn restrict_to_source_lines(
+        &self,
+        mutants: Vec<Mutant>,
+        stats: &mut MutationStatistics,
+    ) -> Vec<Mutant> {
+        let ranges = match &self.conf.source_lines {
+            Some(ranges) => ranges,
+            None => return mutants,
+        };
+        let generated = mutants.len();
+        let mutants: Vec<Mutant> = mutants
+            .into_iter()
+            .filter(|m| {
+                m.target_file.is_some()
+                    || m.get_line_column()
+                        .is_ok_and(|(line, _)| ranges.iter().any(|r| r.contains(line)))
+            })
+            .collect();
+        stats.mutants_outside_source_lines += generated - mutants.len();
+        mutants
+    }
+
+    /// Mutate the internal functions of the libraries (in other files) that
+    /// `source` references. The mutants are tagged with `source`'s relative
+    /// filename as their target file.
+    fn mutate_called_libraries(
+        &self,
+        source: &Rc<Source>,
+        solc: &Solc,
+        stats: &mut MutationStatistics,
+    ) -> Result<Vec<Mutant>, Box<dyn error::Error>> {
+        let mut units = solc.compile_source_units(source.filename())?;
+        // The libraries are cloned out of their units below, so deep subtrees
+        // must be pruned first (see `SolAST::prune_deeper_than()`)
+        for unit in units.iter_mut() {
+            unit.prune_deeper_than(self.conf.max_ast_depth);
+        }
+        let unit_path = |unit: &SolAST| {
+            unit.get_string("absolutePath")
+                .and_then(|name| solc.resolve_source_unit_name(&name))
+                .and_then(|path| path.canonicalize().ok())
+        };
+        let target = match units
+            .iter()
+            .find(|u| unit_path(u).as_deref() == Some(source.physical_path()))
+        {
+            Some(target) => target,
+            None => {
+                log::warn!(
+                    "Couldn't find the source unit of {} to look up called libraries",
+                    source.filename().display()
+                );
+                return Ok(vec![]);
+            }
+        };
+        let target_file = source.relative_filename()?.to_str().unwrap().to_string();
+
+        // Every library function is in scope: the contract and function
+        // constraints only select what to mutate in the target file
+        let conf = MutatorConf {
+            contract: None,
+            funcs_to_mutate: None,
+            include_called_libraries: false,
+            // Only the functions that the target file can call are included
+            only_mutate_public_state_changing_paths: false,
+            ..self.conf.clone()
+        };
+        let mutator = Mutator::new(conf, vec![], Solc::new(String::new(), PathBuf::new()));
+
+        let mut mutants = vec![];
+        for library_unit in called_libraries(target, &units) {
+            let path = match unit_path(&library_unit) {
+                Some(path) if path.starts_with(source.sourceroot().canonicalize()?) => path,
+                _ => {
+                    let library_file = library_unit.get_string("absolutePath").unwrap_or_default();
+                    report(
+                        Diagnostic::new(
+                            DiagnosticCode::LibraryOutsideSourceroot,
+                            format!(
+                                "Skipping called library file {}: it is not in the sourceroot {}",
+                                library_file,
+                                source.sourceroot().display()
+                            ),
+                        )
+                        .with_file(library_file),
+                    );
+                    continue;
+                }
+            };
+            let library_source = Rc::new(Source::new(path, source.sourceroot().to_path_buf())?);
+            stats.library_functions_included += FunctionInfo::collect(&library_unit).len();
+            for mut mutant in mutator.mutate_ast(library_unit, library_source, stats) {
+                mutant.target_file = Some(target_file.clone());
+                mutants.push(mutant);
+            }
+        }
+        Ok(mutants)
+    }
+
+    /// Mutate the code that `contract`, defined in `source`, inherits from
+    /// its base contracts (see `inherited_contracts()`), which may be defined
+    /// in `source` itself or in the files it imports. The mutants of other
+    /// files are tagged with `source`'s relative filename as their target
+    /// file.
+    fn mutate_inherited_contracts(
+        &self,
+        source: &Rc<Source>,
+        contract: &str,
+        solc: &Solc,
+        stats: &mut MutationStatistics,
+    ) -> Result<Vec<Mutant>, Box<dyn error::Error>> {
+        let mut units = solc.compile_source_units(source.filename())?;
+        // The base contracts are cloned out of their units below, so deep
+        // subtrees must be pruned first (see `SolAST::prune_deeper_than()`)
+        for unit in units.iter_mut() {
+            unit.prune_deeper_than(self.conf.max_ast_depth);
+        }
+        let unit_path = |unit: &SolAST| {
+            unit.get_string("absolutePath")
+                .and_then(|name| solc.resolve_source_unit_name(&name))
+                .and_then(|path| path.canonicalize().ok())
+        };
+        let target_file = source.relative_filename()?.to_str().unwrap().to_string();
+
+        let mut mutants = vec![];
+        for base_unit in inherited_contracts(contract, &units) {
+            let path = match unit_path(&base_unit) {
+                Some(path) if path == source.physical_path() => {
+                    mutants.append(&mut self.mutate_inherited_unit(
+                        base_unit,
+                        source.clone(),
+                        contract,
+                        stats,
+                    ));
+                    continue;
+                }
+                Some(path) if path.starts_with(source.sourceroot().canonicalize()?) => path,
+                _ => {
+                    let base_file = base_unit.get_string("absolutePath").unwrap_or_default();
+                    report(
+                        Diagnostic::new(
+                            DiagnosticCode::BaseContractOutsideSourceroot,
+                            format!(
+                                "Skipping base contract file {}: it is not in the sourceroot {}",
+                                base_file,
+                                source.sourceroot().display()
+                            ),
+                        )
+                        .with_file(base_file),
+                    );
+                    continue;
+                }
+            };
+            let base_source = Rc::new(Source::new(path, source.sourceroot().to_path_buf())?);
+            for mut mutant in self.mutate_inherited_unit(base_unit, base_source, contract, stats) {
+                mutant.target_file = Some(target_file.clone());
+                mutants.push(mutant);
+            }
+        }
+        Ok(mutants)
+    }
+
+    /// Mutate a copy of a source unit that only contains inherited code (see
+    /// `inherited_contracts()`), tagging the mutants with their defining
+    /// contract and with `contract` as their filter contract
+    fn mutate_inherited_unit(
+        &self,
+        unit: SolAST,
+        source: Rc<Source>,
+        contract: &str,
+        stats: &mut MutationStatistics,
+    ) -> Vec<Mutant> {
+        // The unit only contains base contracts of `contract`, which are all
+        // in scope
+        let conf = MutatorConf {
+            contract: None,
+            contract_closure: false,
+            include_called_libraries: false,
+            ..self.conf.clone()
+        };
+        // Inherited functions are selected by the `--functions` entries too
+        stats.filter_names.record(&unit, &conf);
+        let mutator = Mutator::new(conf, vec![], Solc::new(String::new(), PathBuf::new()));
+        stats.inherited_functions_included += FunctionInfo::collect(&unit).len();
+        let mut mutants = mutator.mutate_ast(unit.clone(), source, stats);
+        tag_contracts(&mut mutants, &unit, contract);
+        mutants
+    }
+
+    /// Mutate a single file given its AST
+    fn mutate_ast(
+        &self,
+        ast: SolAST,
+        source: Rc<Source>,
+        stats: &mut MutationStatistics,
+    ) -> Vec<Mutant> {
+        // The AST context describes the whole unit, before pruning
+        let original = self.conf.emit_original_ast.then(|| ast.clone());
+        let mut ast = ast;
+        stats.deep_subtrees_pruned += self.prune_deep_subtrees(&mut ast, &source);
+        self.skip_unreachable_functions(&mut ast, &source, stats);
+        self.skip_unselected_overloads(&mut ast);
+        let functions = FunctionInfo::collect(&ast);
+        let modifiers = FunctionInfo::collect_modifiers(&ast);
+        stats
+            .mutated_units
+            .push(self.mutated_unit(&ast, &source, &functions, &modifiers));
+        let user_operations = user_defined_operations(&ast);
+        if log::log_enabled!(log::Level::Debug) {
+            let assembly = ast.clone().traverse(&AssemblyCounter { mutator: self }, ());
+            log::debug!(
+                "    Skipped {} inline assembly statements of {}",
+                assembly.len(),
+                source.filename().display()
+            );
+        }
+        let mut result: Vec<Mutant> = vec![];
+        for (mut mutants, skipped, equivalent) in ast.traverse(self, source.clone()) {
+            result.append(&mut mutants);
+            stats.invalid_utf8_candidates_skipped += skipped;
+            stats.likely_equivalent_mutants_skipped += equivalent;
+        }
+        result = drop_duplicate_boundary_mutants(result);
+        if !user_operations.is_empty() {
+            let generated = result.len();
+            result = restrict_user_defined_operators(result, &user_operations);
+            log::info!(
+                "    Skipped {} mutants of {} user-defined operators",
+                generated - result.len(),
+                user_operations.len()
+            );
+        }
+        if self.conf.skip_trivially_equivalent {
+            let generated = result.len();
+            result = drop_trivially_equivalent(result, &functions);
+            stats.trivially_equivalent_mutants_skipped += generated - result.len();
+        }
+        if self.conf.mutate_modifiers {
+            for modifier in modifiers.iter().filter(|m| self.function_in_scope(m)) {
+                log::info!(
+                    "    Generated {} mutants in modifier {}",
+                    result.iter().filter(|m| modifier.contains(m.start)).count(),
+                    modifier.name
+                );
+                stats.modifiers_mutated += 1;
+            }
+        }
+
+        let pure_view_functions = functions
+            .iter()
+            .filter(|f| f.is_pure_or_view() && self.function_in_scope(f))
+            .count();
+        if self.conf.skip_pure_view {
+            stats.pure_view_functions_skipped += pure_view_functions;
+        } else if self.conf.tag_pure_view {
+            stats.pure_view_functions_tagged += pure_view_functions;
+            for mutant in result.iter_mut() {
+                let function = functions.iter().find(|f| f.contains(mutant.start));
+                if let Some(f) = function.filter(|f| f.is_pure_or_view()) {
+                    mutant.mutability = f.state_mutability.clone();
+                }
+            }
+        }
+        if let Some(original) = original {
+            match collect_ast_context(&original, &source, &mut result) {
+                Ok(context) => stats.ast_contexts.push(context),
+                Err(e) => log::warn!(
+                    "Couldn't collect the AST context of {}: {}",
+                    source.filename().display(),
+                    e
+                ),
+            }
+        }
+        result
+    }
+
+    /// The contracts and functions of `ast` that are in scope: functions and
+    /// modifiers selected by the configuration (except `view`/`pure`
+    /// functions with `--skip_pure_view`), and the contracts that are not
+    /// excluded
+    fn mutated_unit(
+        &self,
+        ast: &SolAST,
+        source: &Rc<Source>,
+        functions: &[FunctionInfo],
+        modifiers: &[FunctionInfo],
+    ) -> MutatedUnit {
+        let contracts = ast
+            .nodes()
+            .iter()
+            .filter(|n| n.node_type().as_deref() == Some("ContractDefinition"))
+            .filter_map(|n| Some((n.name()?, n.get_bounds())))
+            .filter(|(name, _)| {
+                !self.is_excluded_contract(name)
+                    && self.conf.contract.as_ref().is_none_or(|c| c == name)
+            })
+            .collect();
+        let modifiers = if self.conf.mutate_modifiers {
+            modifiers
+        } else {
+            &[]
+        };
+        let functions = functions
+            .iter()
+            .filter(|f| !(self.conf.skip_pure_view && f.is_pure_or_view()))
+            .chain(modifiers)
+            .filter(|f| self.function_in_scope(f))
+            .cloned()
+            .collect();
+        MutatedUnit {
+            source: source.clone(),
+            contracts,
+            functions,
+        }
+    }
+
+    /// Prune the subtrees of `ast` nested more than `max_ast_depth` levels
+    /// deep, warning about them (see `SolAST::prune_deeper_than()`)
+    fn prune_deep_subtrees(&self, ast: &mut SolAST, source: &Source) -> usize {
+        let pruned = ast.prune_deeper_than(self.conf.max_ast_depth);
+        if pruned > 0 {
+            report(
+                Diagnostic::new(
+                    DiagnosticCode::DeepSubtreesPruned,
+                    format!(
+                        "Not mutating {} subtrees of {} nested more than {} levels deep (see --max_ast_depth)",
+                        pruned,
+                        source.filename().display(),
+                        self.conf.max_ast_depth
+                    ),
+                )
+                .with_file(source.filename().display()),
+            );
+        }
+        pruned
+    }
+
+    /// Remove the functions and modifiers from `ast` that a qualified entry
+    /// of `funcs_to_mutate` names, but that no entry selects by contract and
+    /// parameter types (e.g., `TokenA.transfer` when only `TokenB.transfer`
+    /// is selected, see `FunctionFilter`). `is_out_of_scope()` only compares
+    /// names, since a function node does not record its contract.
+    fn skip_unselected_overloads(&self, ast: &mut SolAST) {
+        let qualified = self
+            .conf
+            .funcs_to_mutate
+            .iter()
+            .flatten()
+            .any(|f| FunctionFilter::parse(f).is_qualified());
+        if !qualified {
+            return;
+        }
+        let remove_unselected = |nodes: Option<&mut Value>, contract: Option<&str>| {
+            if let Some(Value::Array(nodes)) = nodes {
+                nodes.retain(|n| {
+                    let is_function = matches!(
+                        n["nodeType"].as_str(),
+                        Some("FunctionDefinition" | "ModifierDefinition")
+                    );
+                    let name = n["name"].as_str().unwrap_or_default();
+                    !is_function
+                        || !self.is_selected_function(name)
+                        || self.is_selected_overload(contract, name, &parameter_types(n))
+                });
+            }
+        };
+        if let Some(unit) = ast.element.as_mut() {
+            if let Some(Value::Array(nodes)) = unit.get_mut("nodes") {
+                for node in nodes.iter_mut() {
+                    if node["nodeType"] == "ContractDefinition" {
+                        let contract = node["name"].as_str().map(|c| c.to_string());
+                        remove_unselected(node.get_mut("nodes"), contract.as_deref());
+                    }
+                }
+            }
+            // Free functions
+            remove_unselected(unit.get_mut("nodes"), None);
+        }
+    }
+
+    /// With `only_mutate_public_state_changing_paths`, remove the functions
+    /// that are not reachable from an entry point from `ast` (see
+    /// `reachable_functions()`), counting the functions and candidates that
+    /// are skipped this way
+    fn skip_unreachable_functions(
+        &self,
+        ast: &mut SolAST,
+        source: &Rc<Source>,
+        stats: &mut MutationStatistics,
+    ) {
+        if !self.conf.only_mutate_public_state_changing_paths {
+            return;
+        }
+        let reachable = reachable_functions(ast, self.conf.contract.as_deref());
+        let mut removed = vec![];
+        let mut remove_unreachable = |nodes: Option<&mut Value>| {
+            if let Some(Value::Array(nodes)) = nodes {
+                let (unreachable, kept): (Vec<Value>, Vec<Value>) =
+                    std::mem::take(nodes).into_iter().partition(|n| {
+                        n["nodeType"] == "FunctionDefinition"
+                            && n["id"].as_i64().is_some_and(|id| !reachable.contains(&id))
+                    });
+                *nodes = kept;
+                removed.extend(unreachable.into_iter().map(SolAST::new));
+            }
+        };
+        if let Some(unit) = ast.element.as_mut() {
+            if let Some(Value::Array(nodes)) = unit.get_mut("nodes") {
+                for node in nodes.iter_mut() {
+                    let in_scope = match &self.conf.contract {
+                        Some(contract) => node["name"] == contract.as_str(),
+                        None => true,
+                    };
+                    if node["nodeType"] == "ContractDefinition" && in_scope {
+                        remove_unreachable(node.get_mut("nodes"));
+                    }
+                }
+            }
+            // Free functions
+            remove_unreachable(unit.get_mut("nodes"));
+        }
+
+        let locator = CandidateLocator {
+            mutator: self,
+            file: String::new(),
+        };
+        for function in removed {
+            if self.is_out_of_scope(&function) {
+                continue;
+            }
+            stats.unreachable_functions_skipped += 1;
+            stats.unreachable_candidates_skipped += function
+                .traverse(&locator, source.clone())
+                .iter()
+                .map(|candidates| candidates.len())
+                .sum::<usize>();
+        }
+    }
+
+    /// Check this mutator's configuration without generating any mutants
+    /// (see `gambit check`): each source must compile, and the contract and
+    /// functions selected by `--contract` and `--functions` must be defined
+    /// in the sources or the files they import. Each source is compiled once,
+    /// in a temporary directory. The names are only checked when every
+    /// source compiles.
+    pub fn check(&self) -> Result<Vec<ConfigurationCheck>, Box<dyn error::Error>> {
+        #[cfg(feature = "vyper")]
+        if self.vyper.is_some() {
+            return Err(Box::new(GambitError::Configuration(
+                "gambit check is not supported for Vyper".into(),
+            )));
+        }
+        let mut checks = vec![];
+        let mut units = vec![];
+        for source in self.sources.iter() {
+            log::info!("Checking {}", source.filename().display());
+            let check = format!("compile {}", source.filename().display());
+            match self.solc.compile_source_units(source.filename()) {
+                Ok(mut source_units) => {
+                    checks.push(ConfigurationCheck::pass(check));
+                    units.append(&mut source_units);
+                }
+                Err(e) => checks.push(ConfigurationCheck::fail(check, e.to_string())),
+            }
+        }
+        if checks.iter().all(|c| c.passed()) {
+            checks.append(&mut self.check_names(&units));
+        }
+        Ok(checks)
+    }
+
+    /// Check that the contract and functions selected by this mutator's
+    /// configuration are defined in the source units `units`
+    fn check_names(&self, units: &[SolAST]) -> Vec<ConfigurationCheck> {
+        let mut checks = vec![];
+        let mut functions = vec![];
+        let mut modifiers = vec![];
+        for unit in units {
+            functions.append(&mut FunctionInfo::collect(unit));
+            modifiers.append(&mut FunctionInfo::collect_modifiers(unit));
+        }
+        let unknown = |kind: &str, name: &str, names: &[String]| match suggest_name(name, names) {
+            Some(suggestion) => format!(
+                "unknown {} `{}`; did you mean `{}`?",
+                kind, name, suggestion
+            ),
+            None => format!("unknown {} `{}`", kind, name),
+        };
+        if let Some(contract) = &self.conf.contract {
+            let check = format!("contract `{}` is defined", contract);
+            let contracts: Vec<String> = units
+                .iter()
+                .flat_map(|u| u.nodes())
+                .filter(|n| n.node_type().as_deref() == Some("ContractDefinition"))
+                .filter_map(|n| n.name())
+                .collect();
+            checks.push(if contracts.contains(contract) {
+                ConfigurationCheck::pass(check)
+            } else {
+                ConfigurationCheck::fail(check, unknown("contract", contract, &contracts))
+            });
+        }
+        let function_names: Vec<String> = functions.iter().map(|f| f.name.clone()).collect();
+        for name in self.conf.funcs_to_mutate.iter().flatten() {
+            let check = format!("function `{}` is defined", name);
+            let filter = FunctionFilter::parse(name);
+            let selects =
+                |f: &&FunctionInfo| filter.matches(f.contract.as_deref(), &f.name, &f.parameters);
+            let is_modifier = modifiers.iter().any(|m| selects(&m));
+            let defined: Vec<&FunctionInfo> = functions
+                .iter()
+                .chain(modifiers.iter().filter(|_| self.conf.mutate_modifiers))
+                .filter(selects)
+                .collect();
+            let failure = if defined.is_empty() && is_modifier {
+                Some(format!(
+                    "`{}` is a modifier, which is not mutated (see --no_mutate_modifiers)",
+                    name
+                ))
+            } else if defined.is_empty() && !function_names.contains(&filter.name) {
+                Some(unknown("function", &filter.name, &function_names))
+            } else if defined.is_empty() {
+                Some(format!(
+                    "no function `{}` matches the contract or parameter types of `{}`",
+                    filter.name, name
+                ))
+            } else {
+                // Without --contract_closure, only the functions of the
+                // selected contract (and free functions) are mutated
+                self.conf
+                    .contract
+                    .as_ref()
+                    .filter(|_| !self.conf.contract_closure)
+                    .filter(|c| {
+                        !defined
+                            .iter()
+                            .any(|f| f.contract.as_ref().is_none_or(|fc| fc == *c))
+                    })
+                    .map(|c| format!("function `{}` is not defined in contract `{}`", name, c))
+            };
+            checks.push(match failure {
+                None => ConfigurationCheck::pass(check),
+                Some(failure) => ConfigurationCheck::fail(check, failure),
+            });
+        }
+        checks
+    }
+
+    /// Find where this mutator's operators apply in each source, without
+    /// generating any mutants. This is much cheaper than `mutate()`: no
+    /// replacement text is built, and nothing is validated or exported.
+    pub fn locate(&self) -> Result<Vec<MutationCandidate>, Box<dyn error::Error>> {
+        #[cfg(feature = "vyper")]
+        if self.vyper.is_some() {
+            return Err(Box::new(GambitError::Configuration(
+                "--locations_only is not supported for Vyper".into(),
+            )));
+        }
+        let mut candidates = vec![];
+        for source in self.sources.iter() {
+            log::info!("Locating candidates in {}", source.filename().display());
+            match self.solc.compile_ast(source.filename()) {
+                Ok(ast) => candidates.append(&mut self.locate_in_ast(ast, source.clone())),
+                Err(e) if e.is::<GambitError>() => return Err(e),
+                Err(e) => {
+                    log::warn!(
+                        "Couldn't locate candidates in {}",
+                        source.filename().display()
+                    );
+                    log::warn!("Encountered error: {}", e);
+                }
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Find where this mutator's operators apply in a single file given its
+    /// AST. The candidates are listed in the order that `mutate_ast()`
+    /// generates their mutants.
+    fn locate_in_ast(&self, ast: SolAST, source: Rc<Source>) -> Vec<MutationCandidate> {
+        let mut ast = ast;
+        self.prune_deep_subtrees(&mut ast, &source);
+        self.skip_unreachable_functions(&mut ast, &source, &mut MutationStatistics::default());
+        self.skip_unselected_overloads(&mut ast);
+        let file = match source.relative_filename() {
+            Ok(file) => file.to_str().unwrap().to_string(),
+            Err(_) => source.filename_as_str(),
+        };
+        let locator = CandidateLocator {
+            mutator: self,
+            file,
+        };
+        ast.traverse(&locator, source)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Estimate how many mutants each of this mutator's operators generates
+    /// in each source, without generating any mutants (see `--estimate`).
+    /// Like `locate()`, this only compiles each source to its AST. Functions
+    /// pulled in by `--contract_closure` and `--include_called_libraries` are
+    /// not estimated.
+    pub fn estimate(&self) -> Result<Vec<FileEstimate>, Box<dyn error::Error>> {
+        #[cfg(feature = "vyper")]
+        if self.vyper.is_some() {
+            return Err(Box::new(GambitError::Configuration(
+                "--estimate is not supported for Vyper".into(),
+            )));
+        }
+        let mut estimates = vec![];
+        for source in self.sources.iter() {
+            log::info!("Estimating mutants of {}", source.filename().display());
+            match self.solc.compile_ast(source.filename()) {
+                Ok(ast) => estimates.push(self.estimate_in_ast(ast, source.clone())),
+                Err(e) if e.is::<GambitError>() => return Err(e),
+                Err(e) => {
+                    log::warn!(
+                        "Couldn't estimate mutants of {}",
+                        source.filename().display()
+                    );
+                    log::warn!("Encountered error: {}", e);
+                }
+            }
+        }
+        Ok(estimates)
+    }
+
+    /// Estimate how many mutants each of this mutator's operators generates
+    /// in a single file given its AST. The estimate is exact, except that it
+    /// counts the `BoundaryLiteralMutation` mutants that duplicate mutants of
+    /// other operators, the likely equivalent mutants (see
+    /// `is_likely_equivalent()`), and, with `--skip_trivially_equivalent`, the
+    /// trivially equivalent mutants, which are all dropped when mutating. With
+    /// `--source_lines`, the mutants of a node are counted when the node
+    /// starts on one of the lines.
+    fn estimate_in_ast(&self, ast: SolAST, source: Rc<Source>) -> FileEstimate {
+        let mut ast = ast;
+        self.prune_deep_subtrees(&mut ast, &source);
+        self.skip_unreachable_functions(&mut ast, &source, &mut MutationStatistics::default());
+        self.skip_unselected_overloads(&mut ast);
+        let file = match source.relative_filename() {
+            Ok(file) => file.to_str().unwrap().to_string(),
+            Err(_) => source.filename_as_str(),
+        };
+        let mut operators: BTreeMap<String, usize> = self
+            .conf
+            .mutation_operators
+            .iter()
+            .map(|op| (op.to_string(), 0))
+            .collect();
+        let estimator = MutantEstimator {
+            mutator: self,
+            user_operations: user_defined_operations(&ast),
+        };
+        for (op, count) in ast.traverse(&estimator, source).into_iter().flatten() {
+            *operators.entry(op.to_string()).or_default() += count;
+        }
+        let total = operators.values().sum();
+        FileEstimate {
+            file,
+            operators,
+            total,
+        }
+    }
+
+    /// Explain how this mutator's operators fare at `line` and `col` of each
+    /// source (see `--explain`). Nothing is validated or exported.
+    pub fn explain(
+        &self,
+        line: usize,
+        col: usize,
+    ) -> Result<Vec<Explanation>, Box<dyn error::Error>> {
+        #[cfg(feature = "vyper")]
+        if self.vyper.is_some() {
+            return Err(Box::new(GambitError::Configuration(
+                "--explain is not supported for Vyper".into(),
+            )));
+        }
+        let mut explanations = vec![];
+        for source in self.sources.iter() {
+            let ast = self.solc.compile_ast(source.filename())?;
+            explanations.push(self.explain_in_ast(ast, source.clone(), line, col)?);
+        }
+        Ok(explanations)
+    }
+
+    /// Explain how this mutator's operators fare at `line` and `col` of a
+    /// single file given its AST. Like `locate_in_ast()`, this scopes the AST
+    /// exactly like `mutate_ast()`.
+    fn explain_in_ast(
+        &self,
+        ast: SolAST,
+        source: Rc<Source>,
+        line: usize,
+        col: usize,
+    ) -> Result<Explanation, Box<dyn error::Error>> {
+        let pos = source.get_position(line, col).ok_or_else(|| {
+            GambitError::Configuration(format!(
+                "--explain: {} has no line {} column {}",
+                source.filename().display(),
+                line,
+                col
+            ))
+        })?;
+        let mut ast = ast;
+        self.prune_deep_subtrees(&mut ast, &source);
+        self.skip_unreachable_functions(&mut ast, &source, &mut MutationStatistics::default());
+        self.skip_unselected_overloads(&mut ast);
+        let file = match source.relative_filename() {
+            Ok(file) => file.to_str().unwrap().to_string(),
+            Err(_) => source.filename_as_str(),
+        };
+
+        // The covering nodes, from the outermost to the innermost
+        let covering = ast.traverse(&CoveringNodes { pos }, ());
+        let out_of_scope = covering.iter().find(|n| self.is_out_of_scope(n));
+        if let Some(node) = out_of_scope {
+            return Ok(Explanation {
+                file,
+                line,
+                col,
+                out_of_scope: Some(self.out_of_scope_reason(node)),
+                nodes: vec![],
+            });
+        }
+        let innermost_statement = covering.iter().rposition(is_statement).unwrap_or(0);
+
+        let mut seen = HashSet::new();
+        let mut nodes = vec![];
+        for (i, node) in covering.iter().enumerate() {
+            let mut operators: Vec<OperatorTrace> = self
+                .conf
+                .mutation_operators
+                .iter()
+                .map(|op| self.trace_operator(*op, node, &source, &mut seen))
+                .collect();
+            // Above the innermost statement, only the operators whose mutants
+            // cover the location are of interest
+            if i < innermost_statement || is_container(node) {
+                operators.retain(|trace| {
+                    trace.replacements.iter().any(|r| {
+                        source
+                            .get_position(r.line, r.col)
+                            .is_some_and(|start| start <= pos && pos <= start + r.orig.len())
+                    })
+                });
+                if operators.is_empty() {
+                    continue;
+                }
+            }
+            let (start, _) = node.get_bounds();
+            let (line, col) = source.get_line_column(start)?;
+            let text = node
+                .get_text(source.contents())
+                .unwrap_or_default()
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            nodes.push(NodeTrace {
+                node_type: node.node_type().unwrap_or_default(),
+                line,
+                col,
+                text,
+                operators,
+            });
+        }
+        Ok(Explanation {
+            file,
+            line,
+            col,
+            out_of_scope: None,
+            nodes,
+        })
+    }
+
+    /// Trace how `op` fares at `node`. `seen` holds the spans and
+    /// replacements of the mutants traced so far, to point out duplicates.
+    fn trace_operator(
+        &self,
+        op: MutationType,
+        node: &SolAST,
+        source: &Rc<Source>,
+        seen: &mut HashSet<(usize, usize, String)>,
+    ) -> OperatorTrace {
+        let skipped = |reason: String| OperatorTrace {
+            op,
+            skipped: Some(reason),
+            replacements: vec![],
+        };
+        if !op.applies_to(node) {
+            return skipped(format!(
+                "does not apply to {}",
+                node.node_type().unwrap_or_default()
+            ));
+        }
+        if self.is_excluded(&op, node) {
+            return skipped("excluded by --delete_expression_exclusions".into());
+        }
+        let mutants = match op.mutate(node, source.clone()) {
+            Ok(mutants) => mutants,
+            Err(e) => {
+                return skipped(format!(
+                    "skipped: the source text is not valid UTF-8 ({})",
+                    e
+                ))
+            }
+        };
+        if mutants.is_empty() {
+            return skipped("applies, but proposes no replacements".into());
+        }
+        let replacements = mutants
+            .into_iter()
+            .map(|m| {
+                let (line, col) = source.get_line_column(m.start).unwrap_or((0, 0));
+                let mut notes = vec![];
+                if m.orig.trim() == m.repl.trim() {
+                    notes.push("identical to the original text".to_string());
+                }
+                if !seen.insert((m.start, m.end, m.repl.clone())) {
+                    notes.push("duplicates an earlier mutant".to_string());
+                }
+                ReplacementTrace {
+                    line,
+                    col,
+                    orig: m.orig,
+                    repl: m.repl,
+                    notes,
+                }
+            })
+            .collect();
+        OperatorTrace {
+            op,
+            skipped: None,
+            replacements,
+        }
+    }
+
+    /// Describe why `node` is out of scope (see `is_out_of_scope()`)
+    fn out_of_scope_reason(&self, node: &SolAST) -> String {
+        let name = node.name().unwrap_or_default();
+        if node.node_type().as_deref() == Some("ContractDefinition") {
+            format!("contract `{}` is not selected by --contract", name)
+        } else if node.node_type().as_deref() == Some("ModifierDefinition") {
+            if self.conf.mutate_modifiers {
+                format!("modifier `{}` is not selected by --functions", name)
+            } else {
+                format!(
+                    "modifier `{}` is not mutated (see --no_mutate_modifiers)",
+                    name
+                )
+            }
+        } else if node.node_type().as_deref() == Some("UncheckedBlock") {
+            "the location is in an unchecked block (see --no_mutate_unchecked)".to_string()
+        } else if self.conf.skip_pure_view && is_pure_or_view(node.state_mutability().as_deref()) {
+            format!("function `{}` is view or pure (see --skip_pure_view)", name)
+        } else {
+            format!("function `{}` is not selected by --functions", name)
+        }
+    }
+
+    /// The operators of this mutator's configuration that apply to `node`
+    fn applicable_operators<'a>(
+        &'a self,
+        node: &'a SolAST,
+    ) -> impl Iterator<Item = &'a MutationType> + 'a {
+        self.conf
+            .mutation_operators
+            .iter()
+            .filter(move |m| m.applies_to(node) && !self.is_excluded(m, node))
+    }
+
+    /// Check if `node` (and all of its descendants) should not be mutated,
+    /// either because it is a contract other than `contract` or in
+    /// `exclude_contracts`, a function or modifier not in `funcs_to_mutate`
+    /// or in `exclude_functions`, a skipped `view`/`pure` function, or a
+    /// modifier while `mutate_modifiers` is off, or an `unchecked` block while
+    /// `mutate_unchecked` is off
+    fn is_out_of_scope(&self, node: &SolAST) -> bool {
+        if !self.conf.mutate_unchecked && node.node_type().as_deref() == Some("UncheckedBlock") {
+            return true;
+        }
+        if let Some(e) = &node.element {
+            if let Some(e_obj) = e.as_object() {
+                if e_obj.contains_key("contractKind") {
+                    let contract_name = e_obj.get("name").unwrap().as_str().unwrap();
+                    if self.is_excluded_contract(contract_name) {
+                        return true;
+                    }
+                    if let Some(contract) = &self.conf.contract {
+                        return contract != contract_name;
+                    } else {
+                        return false;
+                    }
+                } else if node.node_type().as_deref() == Some("ModifierDefinition") {
+                    if !self.conf.mutate_modifiers {
+                        return true;
+                    }
+                    return !node
+                        .name()
+                        .is_some_and(|name| self.is_selected_function(&name));
+                } else if node.node_kind() == Some("function".to_string()) {
+                    if self.conf.skip_pure_view
+                        && is_pure_or_view(node.state_mutability().as_deref())
+                    {
+                        return true;
+                    }
+                    match node.name() {
+                        Some(name) => return !self.is_selected_function(&name),
+                        None => return self.conf.funcs_to_mutate.is_some(),
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Check if a function is selected for mutation by the `contract`,
+    /// `funcs_to_mutate`, and exclusion constraints of this mutator's
+    /// configuration
+    fn function_in_scope(&self, function: &FunctionInfo) -> bool {
+        if let Some(contract) = &function.contract {
+            if self.is_excluded_contract(contract) {
+                return false;
+            }
+        }
+        if let Some(contract) = &self.conf.contract {
+            if function.contract.as_ref() != Some(contract) {
+                return false;
+            }
+        }
+        self.is_selected_function(&function.name)
+            && self.is_selected_overload(
+                function.contract.as_deref(),
+                &function.name,
+                &function.parameters,
+            )
+    }
+
+    /// Check if the function or modifier `name` is selected by
+    /// `funcs_to_mutate` and not in `exclude_functions`. Exclusion wins when
+    /// both name it
+    fn is_selected_function(&self, name: &str) -> bool {
+        let excluded = self
+            .conf
+            .exclude_functions
+            .as_ref()
+            .is_some_and(|fns| fns.iter().any(|f| f == name));
+        let selected = self
+            .conf
+            .funcs_to_mutate
+            .as_ref()
+            .is_none_or(|fns| fns.iter().any(|f| FunctionFilter::parse(f).name == name));
+        selected && !excluded
+    }
+
+    /// Check if the function or modifier `name` of `contract`, whose
+    /// parameters have the types `parameters`, is selected by the contract
+    /// and parameter types of the `funcs_to_mutate` entries naming it (see
+    /// `FunctionFilter`). Unlike `is_selected_function()`, this ignores
+    /// `exclude_functions`.
+    fn is_selected_overload(
+        &self,
+        contract: Option<&str>,
+        name: &str,
+        parameters: &[String],
+    ) -> bool {
+        self.conf.funcs_to_mutate.as_ref().is_none_or(|fns| {
+            fns.iter()
+                .any(|f| FunctionFilter::parse(f).matches(contract, name, parameters))
+        })
+    }
+
+    /// Check if the contract `name` is in `exclude_contracts`
+    fn is_excluded_contract(&self, name: &str) -> bool {
+        self.conf
+            .exclude_contracts
+            .as_ref()
+            .is_some_and(|cs| cs.iter().any(|c| c == name))
+    }
+
+    /// Check if `op` should not be applied to `node` because `node` is an
+    /// expression statement calling a function in
+    /// `delete_expression_exclusions`
+    fn is_excluded(&self, op: &MutationType, node: &SolAST) -> bool {
+        if *op != MutationType::DeleteExpressionMutation {
+            return false;
+        }
+        let expr = node.expression();
+        if expr.node_type() != Some("FunctionCall".into()) {
+            return false;
+        }
+        match expr.expression().name() {
+            Some(callee) => self.conf.delete_expression_exclusions.contains(&callee),
+            None => false,
+        }
+    }
+
+    /// Check if `op` may replace code with `repl`: it may unless its
+    /// replacements are restricted to others (see `operator_replacements`)
+    fn allows_replacement(&self, op: &MutationType, repl: &str) -> bool {
+        self.conf
+            .operator_replacements
+            .get(op)
+            .is_none_or(|replacements| replacements.iter().any(|r| r == repl))
+    }
+
+    /// Check if a node in the AST is an assert.
+    pub fn is_assert_call(node: &SolAST) -> bool {
+        node.name().map_or_else(|| false, |n| n == "assert")
+    }
+
+    /// Get a slice of the mutants produced by this mutator
+    pub fn mutants(&self) -> &[Mutant] {
+        &self.mutants
+    }
+
+    pub fn sources(&self) -> &Vec<Rc<Source>> {
+        &self.sources
+    }
+
+    pub fn solc(&self) -> &Solc {
+        &self.solc
+    }
+
+    /// validate a mutant by compiling it. If compilation fails then this is an
+    /// invalid mutant.
+    pub fn validate_mutant(&self, mutant: &Mutant) -> Result<bool, Box<dyn error::Error>> {
+        #[cfg(feature = "vyper")]
+        if let Some(vyper) = &self.vyper {
+            return crate::validate_vyper_mutant(vyper, mutant);
+        }
+        let contents = mutant.as_source_string()?;
+        let original_abis = self.original_abis(&mutant.source);
+        let start = Instant::now();
+        let (valid, stages, abi_changed) = validate_mutated_source(
+            self.solc(),
+            self.conf.validation_mode,
+            self.conf.legacy_validation,
+            original_abis.as_ref(),
+            mutant.source.filename(),
+            &contents,
+        )?;
+        self.validation_durations
+            .borrow_mut()
+            .insert(validation_key(mutant), start.elapsed());
+        self.validation_stages.borrow_mut().merge(&stages);
+        if let Some(changed) = abi_changed {
+            self.abi_changes
+                .borrow_mut()
+                .insert(validation_key(mutant), changed);
+        }
+        Ok(valid)
+    }
+
+    /// With `--compare_abi`, the ABIs of the contracts of the original
+    /// `source`, which are extracted once per file
+    fn original_abis(&self, source: &Source) -> Option<ContractAbis> {
+        if !self.conf.compare_abi || self.conf.legacy_validation {
+            return None;
+        }
+        self.original_abis
+            .borrow_mut()
+            .entry(source.filename().to_path_buf())
+            .or_insert_with(|| {
+                let name = self.solc.source_unit_name(source.filename()).ok()?;
+                let contents = String::from_utf8_lossy(source.contents()).into_owned();
+                let sources = HashMap::from([(name, contents)]);
+                match self.solc.compile_abi_standard_json(&sources) {
+                    Ok((errors, abis)) if errors.is_empty() => Some(abis),
+                    _ => {
+                        log::warn!(
+                            "Couldn't extract the ABI of {}; its mutants' ABIs are not compared",
+                            source.filename().display()
+                        );
+                        None
+                    }
+                }
+            })
+            .clone()
+    }
+
+    /// With `--compare_abi`, whether `mutant` changes the ABI of its file's
+    /// contracts. This is `None` if the mutant was not fully compiled during
+    /// validation
+    pub fn abi_changed(&self, mutant: &Mutant) -> Option<bool> {
+        self.abi_changes
+            .borrow()
+            .get(&validation_key(mutant))
+            .copied()
+    }
+
+    /// Whether `mutant` could not be validated because solc kept failing for
+    /// reasons unrelated to it
+    pub fn validation_error(&self, mutant: &Mutant) -> bool {
+        self.validation_errors
+            .borrow()
+            .contains(&validation_key(mutant))
+    }
+
+    /// How mutants were checked and rejected by each validation stage so far
+    pub fn validation_stages(&self) -> ValidationStages {
+        self.validation_stages.borrow().clone()
+    }
+
+    /// The wall-clock time it took to validate `mutant`. This is `None` if
+    /// the mutant was not compiled to validate it
+    pub fn validation_time(&self, mutant: &Mutant) -> Option<Duration> {
+        self.validation_durations
+            .borrow()
+            .get(&validation_key(mutant))
+            .copied()
+    }
+
+    /// Each of `mutants` that was compiled to validate it, whether it is
+    /// valid, and the time it took (see `ValidationTimes`). Mutants with the
+    /// same contents as an earlier one were compiled once, and are left out
+    pub fn validation_times<'a>(&self, mutants: &'a [Mutant]) -> Vec<(&'a Mutant, bool, Duration)> {
+        let cache = self.validation_cache.borrow();
+        let durations = self.validation_durations.borrow();
+        let mut seen = HashSet::new();
+        mutants
+            .iter()
+            .filter_map(|mutant| {
+                let key = validation_key(mutant);
+                let valid = *cache.get(&key)?;
+                let duration = *durations.get(&key)?;
+                seen.insert(key).then_some((mutant, valid, duration))
+            })
+            .collect()
+    }
+
+    /// Stop validating mutants at `deadline`: mutants that would be validated
+    /// after it are passed through unvalidated instead
+    pub fn set_validation_deadline(&mut self, deadline: Option<Instant>) {
+        self.validation_deadline = deadline;
+    }
+
+    /// Stop validating mutants once `flag` is raised: mutants that would be
+    /// validated after it are passed through unvalidated, like past the
+    /// validation deadline
+    pub fn set_cancellation(&mut self, flag: Arc<AtomicBool>) {
+        self.cancellation = Some(flag);
+    }
+
+    /// Whether the cancellation flag (see `set_cancellation()`) was raised
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::SeqCst))
+    }
+
+    /// Validate `mutant` if the validation deadline has not passed yet and
+    /// the run was not cancelled. Returns `None` if the mutant should be
+    /// passed through unvalidated, which is also the case for mutants that
+    /// solc could not be run on (see `validation_error()`). Mutants that were already validated are not
+    /// compiled again.
+    pub fn validate_mutant_before_deadline(&self, mutant: &Mutant) -> Option<bool> {
+        let key = validation_key(mutant);
+        if let Some(valid) = self.validation_cache.borrow().get(&key) {
+            return Some(*valid);
+        }
+        if self.validation_errors.borrow().contains(&key)
+            || self
+                .validation_deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            || self.is_cancelled()
+        {
+            return None;
+        }
+        self.validation_attempts
+            .set(self.validation_attempts.get() + 1);
+        let valid = match self.validate_mutant(mutant) {
+            Ok(valid) => valid,
+            Err(e) if is_solc_unavailable(e.as_ref()) => {
+                self.validation_errors.borrow_mut().insert(key);
+                return None;
+            }
+            Err(_) => false,
+        };
+        self.validation_cache.borrow_mut().insert(key, valid);
+        Some(valid)
+    }
+
+    /// Like `validate_mutant_before_deadline()` for each of `mutants`, but
+    /// compiling up to `conf.validation_jobs` mutants at a time. The results
+    /// are in the order of `mutants`, regardless of the order in which the
+    /// mutants were compiled.
+    pub fn validate_mutants_before_deadline(&self, mutants: &[&Mutant]) -> Vec<Option<bool>> {
+        #[cfg(feature = "vyper")]
+        let serial = self.conf.validation_jobs <= 1 || self.vyper.is_some();
+        #[cfg(not(feature = "vyper"))]
+        let serial = self.conf.validation_jobs <= 1;
+        if serial {
+            return mutants
+                .iter()
+                .map(|m| self.validate_mutant_before_deadline(m))
+                .collect();
+        }
+
+        // Compile the first mutant of each key that was not validated yet.
+        // Mutants whose source cannot be computed are invalid.
+        let keys: Vec<ValidationKey> = mutants.iter().map(|m| validation_key(m)).collect();
+        let mut jobs: Vec<ValidationJob> = vec![];
+        {
+            let cache = self.validation_cache.borrow();
+            let mut pending = HashSet::new();
+            let errors = self.validation_errors.borrow();
+            for (i, key) in keys.iter().enumerate() {
+                if !cache.contains_key(key) && !errors.contains(key) && pending.insert(key) {
+                    let mutant = mutants[i];
+                    jobs.push(ValidationJob {
+                        index: i,
+                        filename: mutant.source.filename().to_path_buf(),
+                        contents: mutant.as_source_string().ok(),
+                        original_abis: self.original_abis(&mutant.source),
+                    });
+                }
+            }
+        }
+        let (solc, mode, legacy, deadline, cancellation) = (
+            &self.solc,
+            self.conf.validation_mode,
+            self.conf.legacy_validation,
+            self.validation_deadline,
+            self.cancellation.as_deref(),
+        );
+        let next = AtomicUsize::new(0);
+        // The outcome of each job, which is `None` if solc could not be run,
+        // and the time it took to compile the mutant, if it was compiled
+        let outcomes: Vec<(usize, Option<ValidationOutcome>, Option<Duration>)> =
+            thread::scope(|scope| {
+                let workers: Vec<_> = (0..self.conf.validation_jobs.min(jobs.len()))
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let mut outcomes = vec![];
+                            while let Some(job) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                                // Past the deadline, or once the run is
+                                // cancelled, mutants are left unvalidated
+                                if deadline.is_some_and(|deadline| Instant::now() >= deadline)
+                                    || cancellation.is_some_and(|flag| flag.load(Ordering::SeqCst))
+                                {
+                                    continue;
+                                }
+                                let start = Instant::now();
+                                let outcome = match &job.contents {
+                                    Some(contents) => match validate_mutated_source(
+                                        solc,
+                                        mode,
+                                        legacy,
+                                        job.original_abis.as_ref(),
+                                        &job.filename,
+                                        contents,
+                                    ) {
+                                        Ok(outcome) => Some(outcome),
+                                        Err(e) if is_solc_unavailable(e.as_ref()) => None,
+                                        Err(_) => Some(Default::default()),
+                                    },
+                                    None => Some(Default::default()),
+                                };
+                                let duration = job.contents.as_ref().map(|_| start.elapsed());
+                                outcomes.push((job.index, outcome, duration));
+                            }
+                            outcomes
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .flat_map(|worker| worker.join().unwrap())
+                    .collect()
+            });
+
+        let mut cache = self.validation_cache.borrow_mut();
+        let mut stages = self.validation_stages.borrow_mut();
+        let mut abi_changes = self.abi_changes.borrow_mut();
+        let mut errors = self.validation_errors.borrow_mut();
+        let mut durations = self.validation_durations.borrow_mut();
+        for (i, outcome, duration) in outcomes {
+            self.validation_attempts
+                .set(self.validation_attempts.get() + 1);
+            let Some((valid, job_stages, abi_changed)) = outcome else {
+                errors.insert(keys[i].clone());
+                continue;
+            };
+            stages.merge(&job_stages);
+            cache.insert(keys[i].clone(), valid);
+            if let Some(duration) = duration {
+                durations.insert(keys[i].clone(), duration);
+            }
+            if let Some(changed) = abi_changed {
+                abi_changes.insert(keys[i].clone(), changed);
+            }
+        }
+        keys.iter().map(|key| cache.get(key).copied()).collect()
+    }
+
+    /// The number of mutants that were compiled to validate them
+    pub fn validation_attempts(&self) -> usize {
+        self.validation_attempts.get()
+    }
+
+    pub fn get_valid_mutants(&self, mutants: &[Mutant]) -> Vec<Mutant> {
+        log::info!("Validating mutants...");
+        let results = self.validate_mutants_before_deadline(&mutants.iter().collect::<Vec<_>>());
+        let mut valid_mutants = vec![];
+        for (m, valid) in mutants.iter().zip(results) {
+            match valid {
+                Some(true) => {
+                    let mut m = m.clone();
+                    m.validated = true;
+                    valid_mutants.push(m)
+                }
+                Some(false) => (),
+                None => valid_mutants.push(m.clone()),
+            }
+        }
+        valid_mutants
+    }
+}
+
+/// A mutant that is validated on a worker thread (see
+/// `validate_mutants_before_deadline()`)
+struct ValidationJob {
+    /// The position of the mutant among the validated mutants
+    index: usize,
+
+    /// The mutated file
+    filename: PathBuf,
+
+    /// The mutated contents, which are `None` if they cannot be computed
+    contents: Option<String>,
+
+    /// With `--compare_abi`, the ABIs of the original file's contracts
+    original_abis: Option<ContractAbis>,
+}
+
+/// Whether mutated contents are valid, how they were checked and rejected by
+/// each validation stage, and whether they change the ABI (see
+/// `validate_mutated_source()`)
+type ValidationOutcome = (bool, ValidationStages, Option<bool>);
+
+/// The key of a mutant in the validation cache: its file, span, and
+/// replacement
+type ValidationKey = (PathBuf, usize, usize, String);
+
+fn validation_key(mutant: &Mutant) -> ValidationKey {
+    (
+        mutant.source.physical_path().to_path_buf(),
+        mutant.start,
+        mutant.end,
+        mutant.repl.clone(),
+    )
+}
+
+/// Validate the mutated `contents` of `filename` by checking them with solc
+/// according to `mode`: by default, the contents are passed to
+/// `solc --standard-json` in place of `filename`, and with `legacy`, they are
+/// written to a temporary file next to `filename` that is compiled instead.
+/// Returns whether the contents are valid, along with how they were checked
+/// and rejected by each validation stage. With `original_abis`, the ABIs of
+/// contents that are fully compiled are extracted and compared with them, and
+/// whether they differ is returned, too.
+fn validate_mutated_source(
+    solc: &Solc,
+    mode: ValidationMode,
+    legacy: bool,
+    original_abis: Option<&ContractAbis>,
+    filename: &Path,
+    contents: &str,
+) -> Result<ValidationOutcome, Box<dyn error::Error>> {
+    if legacy {
+        return validate_mutated_file(solc, mode, filename, contents)
+            .map(|(valid, stages)| (valid, stages, None));
+    }
+    let sources = HashMap::from([(solc.source_unit_name(filename)?, contents.to_string())]);
+    log::debug!(
+        "Validating mutant of {} with solc --standard-json",
+        filename.display()
+    );
+    let mut stages = ValidationStages::default();
+    if mode != ValidationMode::Full {
+        let start = Instant::now();
+        let parses = accepted(solc.check_syntax_standard_json(&sources), |errors| {
+            errors.is_empty()
+        })?;
+        stages.syntax_checks += 1;
+        stages.rejected_by_syntax_check += !parses as usize;
+        stages.syntax_check_ms += start.elapsed().as_millis() as u64;
+        if !parses || mode == ValidationMode::Fast {
+            return Ok((parses, stages, None));
+        }
+    }
+    let start = Instant::now();
+    let (compiles, abi_changed) = match original_abis {
+        Some(original_abis) => match solc.compile_abi_standard_json(&sources) {
+            Ok((errors, abis)) if errors.is_empty() => {
+                (true, Some(abis_differ(original_abis, &abis)))
+            }
+            Err(e) if is_solc_unavailable(e.as_ref()) => return Err(e),
+            _ => (false, None),
+        },
+        None => (
+            accepted(solc.compile_standard_json(&sources), |errors| {
+                errors.is_empty()
+            })?,
+            None,
+        ),
+    };
+    stages.compilations += 1;
+    stages.rejected_by_compilation += !compiles as usize;
+    stages.compilation_ms += start.elapsed().as_millis() as u64;
+    Ok((compiles, stages, abi_changed))
+}
+
+/// Like `validate_mutated_source()`, writing the mutated `contents` to a
+/// temporary file next to `filename` and compiling that file with solc's
+/// command line interface (see `--legacy_validation`)
+fn validate_mutated_file(
+    solc: &Solc,
+    mode: ValidationMode,
+    filename: &Path,
+    contents: &str,
+) -> Result<(bool, ValidationStages), Box<dyn error::Error>> {
+    let source_parent_dir = filename.parent().unwrap();
+    let mutant_file = NamedTempFile::new_in(source_parent_dir)?;
+    let mutant_file_path = mutant_file.path();
+    log::debug!(
+        "Validating mutant of {}: copying mutated code to {}",
+        filename.display(),
+        mutant_file_path.display()
+    );
+    let dir = tempdir()?;
+    fs::write(mutant_file_path, contents)?;
+    let mut stages = ValidationStages::default();
+    if mode != ValidationMode::Full {
+        let start = Instant::now();
+        let parses = accepted(
+            solc.check_syntax(mutant_file_path, dir.path()),
+            |(code, _, _)| code == 0,
+        )?;
+        stages.syntax_checks += 1;
+        stages.rejected_by_syntax_check += !parses as usize;
+        stages.syntax_check_ms += start.elapsed().as_millis() as u64;
+        if !parses || mode == ValidationMode::Fast {
+            return Ok((parses, stages));
+        }
+    }
+    let start = Instant::now();
+    let compiles = accepted(
+        solc.compile(mutant_file_path, dir.path()),
+        |(code, _, _)| code == 0,
+    )?;
+    stages.compilations += 1;
+    stages.rejected_by_compilation += !compiles as usize;
+    stages.compilation_ms += start.elapsed().as_millis() as u64;
+    Ok((compiles, stages))
+}
+
+/// Whether the solc invocation of a validation stage accepted the mutant,
+/// according to `accepts`. Failures to run solc at all are propagated, since
+/// they say nothing about the mutant
+fn accepted<T>(
+    result: Result<T, Box<dyn error::Error>>,
+    accepts: impl FnOnce(T) -> bool,
+) -> Result<bool, Box<dyn error::Error>> {
+    match result {
+        Ok(output) => Ok(accepts(output)),
+        Err(e) if is_solc_unavailable(e.as_ref()) => Err(e),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Describe the `contract` and `functions` filter values that name no contract
+/// or function of `ast` but nearly match one: a name that only differs in case
+/// (identifiers are case-sensitive in Solidity) or that is a small edit away.
+/// Each description suggests the name that was probably meant.
+pub fn filter_name_warnings(
+    ast: &SolAST,
+    contract: Option<&str>,
+    functions: Option<&[String]>,
+) -> Vec<String> {
+    let near_miss = |kind: &str, name: &str, names: &[String]| {
+        if names.iter().any(|n| n == name) {
+            return None;
+        }
+        suggest_name(name, names).map(|suggestion| {
+            format!(
+                "{} `{}` matches no {} in this file; did you mean `{}`?{}",
+                if kind == "contract" {
+                    "--contract"
+                } else {
+                    "--functions"
+                },
+                name,
+                kind,
+                suggestion,
+                if suggestion.eq_ignore_ascii_case(name) {
+                    " (names are case-sensitive)"
+                } else {
+                    ""
+                }
+            )
+        })
+    };
+    let contracts: Vec<String> = ast
+        .nodes()
+        .iter()
+        .filter(|n| n.node_type().as_deref() == Some("ContractDefinition"))
+        .filter_map(|n| n.name())
+        .collect();
+    let mut warnings = vec![];
+    warnings.extend(contract.and_then(|c| near_miss("contract", c, &contracts)));
+
+    // Only suggest the functions of the selected contract, if it exists
+    let contract = contract.filter(|c| contracts.iter().any(|n| n == c));
+    let mut names: Vec<String> = FunctionInfo::collect(ast)
+        .into_iter()
+        .chain(FunctionInfo::collect_modifiers(ast))
+        .filter(|f| contract.is_none() || f.contract.as_deref() == contract)
+        .map(|f| f.name)
+        .filter(|name| !name.is_empty())
+        .collect();
+    names.sort();
+    names.dedup();
+    for function in functions.into_iter().flatten() {
+        let function = FunctionFilter::parse(function);
+        warnings.extend(near_miss("function", &function.name, &names));
+    }
+    warnings
+}
+
+/// Describe the contract-qualified `functions` filter values (see
+/// `FunctionFilter`) whose contract `ast` does not define
+pub fn qualified_function_warnings(ast: &SolAST, functions: Option<&[String]>) -> Vec<String> {
+    let contracts: Vec<String> = ast
+        .nodes()
+        .iter()
+        .filter(|n| n.node_type().as_deref() == Some("ContractDefinition"))
+        .filter_map(|n| n.name())
+        .collect();
+    functions
+        .into_iter()
+        .flatten()
+        .filter_map(|function| {
+            let contract = FunctionFilter::parse(function).contract?;
+            (!contracts.contains(&contract)).then(|| {
+                format!(
+                    "--functions `{}` names contract `{}`, which is not defined in this file",
+                    function, contract
+                )
+            })
+        })
+        .collect()
+}
+
+/// Find the libraries in other source units that are referenced by `target`
+/// (e.g., by a `using L for T;` directive or a call `L.f(x)`). This returns,
+/// for each source unit defining such libraries, a copy of the unit that only
+/// contains those libraries' internal and private functions, which are the
+/// functions that are compiled into the referencing contracts.
+pub fn called_libraries(target: &SolAST, units: &[SolAST]) -> Vec<SolAST> {
+    let refs = match &target.element {
+        Some(target) => referenced_declarations(target),
+        None => HashSet::new(),
+    };
+
+    let target_path = target.get_string("absolutePath");
+    let mut result = vec![];
+    for unit in units {
+        if unit.get_string("absolutePath") == target_path {
+            continue;
+        }
+        let libraries: Vec<Value> = unit
+            .nodes()
+            .into_iter()
+            .filter(|n| {
+                n.node_type().as_deref() == Some("ContractDefinition")
+                    && n.get_string("contractKind").as_deref() == Some("library")
+                    && n.get_object()
+                        .and_then(|o| o["id"].as_i64())
+                        .is_some_and(|id| refs.contains(&id))
+            })
+            .filter_map(|library| {
+                let functions: Vec<Value> = library
+                    .nodes()
+                    .into_iter()
+                    .filter(|n| {
+                        n.node_type().as_deref() == Some("FunctionDefinition")
+                            && matches!(
+                                n.get_string("visibility").as_deref(),
+                                Some("internal") | Some("private")
+                            )
+                    })
+                    .filter_map(|n| n.element)
+                    .collect();
+                let mut library = library.element?;
+                library["nodes"] = Value::Array(functions);
+                Some(library)
+            })
+            .collect();
+        if libraries.is_empty() {
+            continue;
+        }
+        let mut unit = unit.element.clone().unwrap();
+        unit["nodes"] = Value::Array(libraries);
+        result.push(SolAST::new(unit));
+    }
+    result
+}
+
+/// Find the base contracts that `contract` inherits from, following its C3
+/// linearization (`linearizedBaseContracts`) across all source units. This
+/// returns, for each source unit defining such base contracts, a copy of the
+/// unit that only contains those contracts, without the functions and
+/// modifiers that are overridden within the inheritance chain: what remains
+/// is the code that `contract` inherits.
+pub fn inherited_contracts(contract: &str, units: &[SolAST]) -> Vec<SolAST> {
+    let contracts: Vec<&Value> = units
+        .iter()
+        .filter_map(|unit| unit.element.as_ref())
+        .flat_map(|unit| unit["nodes"].as_array().into_iter().flatten())
+        .filter(|n| n["nodeType"] == "ContractDefinition")
+        .collect();
+    let chain: Vec<i64> = match contracts.iter().find(|c| c["name"] == contract) {
+        Some(c) => c["linearizedBaseContracts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| id.as_i64())
+            .collect(),
+        None => return vec![],
+    };
+    let bases: HashSet<i64> = chain.iter().skip(1).copied().collect();
+
+    // Every function or modifier that some member of the chain overrides
+    let overridden: HashSet<i64> = contracts
+        .iter()
+        .filter(|c| c["id"].as_i64().is_some_and(|id| chain.contains(&id)))
+        .flat_map(|c| c["nodes"].as_array().into_iter().flatten())
+        .flat_map(|member| [&member["baseFunctions"], &member["baseModifiers"]])
+        .flat_map(|base| base.as_array().into_iter().flatten())
+        .filter_map(|base| base.as_i64())
+        .collect();
+
+    let mut result = vec![];
+    for unit in units {
+        let inherited: Vec<Value> = unit
+            .nodes()
+            .into_iter()
+            .filter(|n| {
+                n.node_type().as_deref() == Some("ContractDefinition")
+                    && n.get_object()
+                        .and_then(|o| o["id"].as_i64())
+                        .is_some_and(|id| bases.contains(&id))
+            })
+            .filter_map(|base| {
+                let members: Vec<Value> = base
+                    .nodes()
+                    .into_iter()
+                    .filter_map(|n| n.element)
+                    .filter(|n| !n["id"].as_i64().is_some_and(|id| overridden.contains(&id)))
+                    .collect();
+                let mut base = base.element?;
+                base["nodes"] = Value::Array(members);
+                Some(base)
+            })
+            .collect();
+        if inherited.is_empty() {
+            continue;
+        }
+        let mut unit = unit.element.clone().unwrap();
+        unit["nodes"] = Value::Array(inherited);
+        result.push(SolAST::new(unit));
+    }
+    result
+}
+
+/// Tag `mutants` of `unit` with the contract defining their code and with
+/// `filter` as the contract whose inheritance chain they belong to
+fn tag_contracts(mutants: &mut [Mutant], unit: &SolAST, filter: &str) {
+    for mutant in mutants.iter_mut() {
+        mutant.defining_contract = defining_contract(unit, mutant.start);
+        mutant.filter_contract = Some(filter.to_string());
+    }
+}
+
+/// The contract defining the code at byte offset `start` of `unit`, if any
+fn defining_contract(unit: &SolAST, start: usize) -> Option<String> {
+    unit.nodes()
+        .into_iter()
+        .filter(|n| n.node_type().as_deref() == Some("ContractDefinition"))
+        .find(|c| {
+            let (s, e) = c.get_bounds();
+            s <= start && start < e
+        })
+        .and_then(|c| c.name())
+}
+
+/// Collect the ids of the declarations referenced anywhere within `value`
+/// (e.g., the callees of function calls, or the libraries of `using for`
+/// directives)
+fn referenced_declarations(value: &Value) -> HashSet<i64> {
+    let mut refs = HashSet::new();
+    let mut worklist = vec![value];
+    while let Some(value) = worklist.pop() {
+        match value {
+            Value::Object(o) => {
+                if let Some(id) = o.get("referencedDeclaration").and_then(|id| id.as_i64()) {
+                    refs.insert(id);
+                }
+                worklist.extend(o.values());
+            }
+            Value::Array(a) => worklist.extend(a.iter()),
+            _ => (),
+        }
+    }
+    refs
+}
+
+/// Find the ids of the functions and modifiers of a source unit that are
+/// reachable from an entry point: a public or external function, constructor,
+/// `receive` or `fallback` function of `contract` or of a contract it inherits
+/// from (or of any contract if `contract` is `None`). State variable
+/// initializers and other members of these contracts are entry points too.
+///
+/// This is conservative: any reference to a function counts as a call, so
+/// functions used as function pointers are reachable, and so is every
+/// override of a reachable virtual function or modifier.
+pub fn reachable_functions(ast: &SolAST, contract: Option<&str>) -> HashSet<i64> {
+    let unit_nodes = match ast.element.as_ref().map(|unit| &unit["nodes"]) {
+        Some(Value::Array(nodes)) => nodes,
+        _ => return HashSet::new(),
+    };
+    let contracts = unit_nodes
+        .iter()
+        .filter(|n| n["nodeType"] == "ContractDefinition");
+    let entry_contracts: HashSet<i64> = match contract {
+        Some(name) => contracts
+            .filter(|c| c["name"] == name)
+            .flat_map(|c| {
+                c["linearizedBaseContracts"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+            })
+            .filter_map(|id| id.as_i64())
+            .collect(),
+        None => contracts.filter_map(|c| c["id"].as_i64()).collect(),
+    };
+
+    // Functions and modifiers by id, and the overrides of each of them
+    let mut callables: HashMap<i64, &Value> = HashMap::new();
+    let mut overrides: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut reachable = HashSet::new();
+    // The nodes whose references are reachable
+    let mut worklist: Vec<&Value> = vec![];
+    // Each member of a contract (and each free-standing declaration), and
+    // whether it belongs to an entry contract
+    let mut members: Vec<(&Value, bool)> = vec![];
+    for node in unit_nodes {
+        if node["nodeType"] == "ContractDefinition" {
+            let is_entry = node["id"]
+                .as_i64()
+                .is_some_and(|id| entry_contracts.contains(&id));
+            if is_entry {
+                // Base constructor arguments, as in `contract C is B(f())`
+                worklist.push(&node["baseContracts"]);
+            }
+            for member in node["nodes"].as_array().into_iter().flatten() {
+                members.push((member, is_entry));
+            }
+        } else {
+            // Free functions are never entry points, but file-level constants
+            // may call them
+            members.push((node, true));
+        }
+    }
+    for (member, is_entry) in members {
+        let id = member["id"].as_i64();
+        match (member["nodeType"].as_str(), id) {
+            (Some("FunctionDefinition") | Some("ModifierDefinition"), Some(id)) => {
+                callables.insert(id, member);
+                for base in [&member["baseFunctions"], &member["baseModifiers"]] {
+                    for base in base.as_array().into_iter().flatten() {
+                        if let Some(base) = base.as_i64() {
+                            overrides.entry(base).or_default().push(id);
+                        }
+                    }
+                }
+                let entry_point = matches!(
+                    member["kind"].as_str(),
+                    Some("constructor") | Some("receive") | Some("fallback")
+                ) || matches!(
+                    member["visibility"].as_str(),
+                    Some("public") | Some("external")
+                );
+                if is_entry && entry_point && member["nodeType"] == "FunctionDefinition" {
+                    reachable.insert(id);
+                    worklist.push(member);
+                }
+            }
+            _ if is_entry => worklist.push(member),
+            _ => (),
+        }
+    }
+
+    while let Some(node) = worklist.pop() {
+        let mut refs: Vec<i64> = referenced_declarations(node).into_iter().collect();
+        while let Some(id) = refs.pop() {
+            if let Some(callable) = callables.get(&id) {
+                if reachable.insert(id) {
+                    worklist.push(callable);
+                    refs.extend(overrides.get(&id).into_iter().flatten());
+                }
+            }
+        }
+    }
+    reachable
+}
+
+/// Mutate in-memory source code given its solc AST (as produced by `solc
+/// --ast-compact-json`). This neither touches the filesystem nor invokes solc,
+/// and the generated mutants are not validated.
+///
+/// `virtual_path` is used as the source's filename and does not need to exist.
+/// Each returned mutant carries its operator, span, and original and
+/// replacement text; the mutated source and its diff are computed on demand
+/// with `Mutant::as_source_string()` and `MutantWriter::diff_mutant()`.
+///
+/// Unlike a `mutate_source(source, virtual_path, conf)` that parses `source`
+/// itself, this takes the AST from the caller: Gambit mutates solc's AST and
+/// has no Solidity parser of its own, so parsing would mean invoking solc
+/// (see `Solc::compile_ast()`). Given the AST, nothing can fail, so the
+/// mutants are returned as is rather than in a `Result`, and as `Mutant`s
+/// rather than a separate descriptor type, since a `Mutant` already computes
+/// its mutated source lazily.
+pub fn mutate_source(
+    source: &str,
+    virtual_path: &str,
+    ast: SolAST,
+    conf: &MutatorConf,
+) -> Vec<Mutant> {
+    let source = Rc::new(Source::from_contents(
+        PathBuf::from(virtual_path),
+        PathBuf::new(),
+        source.as_bytes().to_vec(),
+    ));
+    // The compiler is never invoked
+    let solc = Solc::new(String::new(), PathBuf::new());
+    let mutator = Mutator::new(conf.clone(), vec![], solc);
+    let mut stats = MutationStatistics::default();
+    let mutants = match (&conf.contract, conf.contract_closure) {
+        (Some(contract), true) => {
+            // Only the base contracts defined in `source` itself are included
+            let inherited = inherited_contracts(contract, std::slice::from_ref(&ast));
+            let unit = ast.clone();
+            let mut mutants = mutator.mutate_ast(ast, source.clone(), &mut stats);
+            tag_contracts(&mut mutants, &unit, contract);
+            for base_unit in inherited {
+                mutants.append(&mut mutator.mutate_inherited_unit(
+                    base_unit,
+                    source.clone(),
+                    contract,
+                    &mut stats,
+                ));
+            }
+            mutants
+        }
+        _ => mutator.mutate_ast(ast, source, &mut stats),
+    };
+    mutator.restrict_to_source_lines(mutants, &mut stats)
+}
+
+/// Find where the operators of `conf` apply in `source`, given its AST,
+/// without generating any mutants (see `Mutator::locate()`). Like
+/// `mutate_source()`, this never touches the filesystem.
+pub fn locate_source(
+    source: &str,
+    virtual_path: &str,
+    ast: SolAST,
+    conf: &MutatorConf,
+) -> Vec<MutationCandidate> {
+    let source = Rc::new(Source::from_contents(
+        PathBuf::from(virtual_path),
+        PathBuf::new(),
+        source.as_bytes().to_vec(),
+    ));
+    let solc = Solc::new(String::new(), PathBuf::new());
+    let mutator = Mutator::new(conf.clone(), vec![], solc);
+    mutator.locate_in_ast(ast, source)
+}
+
+/// Estimate how many mutants each operator of `conf` generates in `source`,
+/// given its AST, without generating any mutants (see `Mutator::estimate()`).
+/// Like `mutate_source()`, this never touches the filesystem.
+pub fn estimate_source(
+    source: &str,
+    virtual_path: &str,
+    ast: SolAST,
+    conf: &MutatorConf,
+) -> FileEstimate {
+    let source = Rc::new(Source::from_contents(
+        PathBuf::from(virtual_path),
+        PathBuf::new(),
+        source.as_bytes().to_vec(),
+    ));
+    let solc = Solc::new(String::new(), PathBuf::new());
+    let mutator = Mutator::new(conf.clone(), vec![], solc);
+    mutator.estimate_in_ast(ast, source)
+}
+
+/// Explain how the operators of `conf` fare at a location of `source`, given
+/// its AST (see `Mutator::explain()`). Like `mutate_source()`, this never
+/// touches the filesystem.
+pub fn explain_source(
+    source: &str,
+    virtual_path: &str,
+    ast: SolAST,
+    conf: &MutatorConf,
+    line: usize,
+    col: usize,
+) -> Result<Explanation, Box<dyn error::Error>> {
+    let source = Rc::new(Source::from_contents(
+        PathBuf::from(virtual_path),
+        PathBuf::new(),
+        source.as_bytes().to_vec(),
+    ));
+    let solc = Solc::new(String::new(), PathBuf::new());
+    let mutator = Mutator::new(conf.clone(), vec![], solc);
+    mutator.explain_in_ast(ast, source, line, col)
+}
+
+/// Visiting a node produces its mutants along with the number of candidates
+/// that were skipped because their source text is not valid UTF-8
+impl SolASTVisitor<Rc<Source>, (Vec<Mutant>, usize, usize)> for Mutator {
+    fn skip_node(&self, node: &SolAST, _source: &Rc<Source>) -> bool {
+        self.is_out_of_scope(node)
+    }
+
+    /// Apply the operators to a node, returning its mutants along with the
+    /// number of candidates skipped for invalid UTF-8 and the number of
+    /// mutants dropped as likely equivalent
+    fn visit_node(&self, node: &SolAST, arg: &Rc<Source>) -> Option<(Vec<Mutant>, usize, usize)> {
+        let mut op_node_pairs: Vec<Mutant> = vec![];
+        let mut skipped = 0;
+        let mut equivalent = 0;
+        for m in self.applicable_operators(node) {
+            match m.mutate(node, arg.clone()) {
+                Ok(mutants) => {
+                    for mutant in mutants {
+                        if !self.allows_replacement(m, &mutant.repl) {
+                            continue;
+                        }
+                        if is_likely_equivalent(node, &mutant) {
+                            equivalent += 1;
+                        } else {
+                            op_node_pairs.push(mutant);
+                        }
+                    }
+                }
+                Err(e) => {
+                    let (start, end) = node.get_bounds();
+                    log::warn!(
+                        "Skipping {} of {}[{}..{}]: {}",
+                        m.to_string(),
+                        arg.filename().display(),
+                        start,
+                        end,
+                        e
+                    );
+                    skipped += 1;
+                }
+            }
+        }
+
+        Some((op_node_pairs, skipped, equivalent))
+    }
+}
+
+/// Collects the AST nodes whose span covers a position, from the outermost to
+/// the innermost
+struct CoveringNodes {
+    pos: usize,
+}
+
+impl SolASTVisitor<(), SolAST> for CoveringNodes {
+    fn skip_node(&self, node: &SolAST, _arg: &()) -> bool {
+        node.src().is_some() && {
+            let (start, end) = node.get_bounds();
+            self.pos < start || end < self.pos
+        }
+    }
+
+    fn visit_node(&self, node: &SolAST, _arg: &()) -> Option<SolAST> {
+        (node.src().is_some() && node.node_type().is_some()).then(|| node.clone())
+    }
+}
+
+/// Visiting an in-scope `InlineAssembly` statement counts it: no operator
+/// applies to the Yul code it contains, so its statements are never mutated
+struct AssemblyCounter<'a> {
+    mutator: &'a Mutator,
+}
+
+impl SolASTVisitor<(), ()> for AssemblyCounter<'_> {
+    fn skip_node(&self, node: &SolAST, _arg: &()) -> bool {
+        self.mutator.is_out_of_scope(node) || node.node_type().is_some_and(|t| t.starts_with("Yul"))
+    }
+
+    fn visit_node(&self, node: &SolAST, _arg: &()) -> Option<()> {
+        (node.node_type().as_deref() == Some("InlineAssembly")).then_some(())
+    }
+}
+
+/// Check if a node is a statement (e.g., an `ExpressionStatement` or a
+/// `Return`)
+fn is_statement(node: &SolAST) -> bool {
+    node.node_type().is_some_and(|t| is_statement_type(&t))
+}
+
+/// Check if a node type is a statement's (see `is_statement()`)
+pub(crate) fn is_statement_type(node_type: &str) -> bool {
+    node_type.ends_with("Statement")
+        || ["Return", "Break", "Continue", "Throw", "InlineAssembly"].contains(&node_type)
+}
+
+/// Check if a node contains declarations rather than code (e.g., a contract)
+fn is_container(node: &SolAST) -> bool {
+    node.node_type().is_some_and(|t| {
+        [
+            "SourceUnit",
+            "ContractDefinition",
+            "FunctionDefinition",
+            "ModifierDefinition",
+            "Block",
+            "UncheckedBlock",
+        ]
+        .contains(&t.as_str())
+    })
+}
+
+/// Finds the mutation candidates of a file (see `Mutator::locate()`) with the
+/// same scoping and operator applicability as `Mutator`'s own traversal
+struct CandidateLocator<'a> {
+    mutator: &'a Mutator,
+    /// The file being traversed, as reported in each candidate
+    file: String,
+}
+
+impl SolASTVisitor<Rc<Source>, Vec<MutationCandidate>> for CandidateLocator<'_> {
+    fn skip_node(&self, node: &SolAST, _source: &Rc<Source>) -> bool {
+        self.mutator.is_out_of_scope(node)
+    }
+
+    fn visit_node(&self, node: &SolAST, source: &Rc<Source>) -> Option<Vec<MutationCandidate>> {
+        let ops: Vec<&MutationType> = self.mutator.applicable_operators(node).collect();
+        if ops.is_empty() {
+            return None;
+        }
+        let (start, end) = node.get_bounds();
+        let (line, col) = source.get_line_column(start).ok()?;
+        Some(
+            ops.into_iter()
+                .map(|op| MutationCandidate {
+                    op: *op,
+                    file: self.file.clone(),
+                    line,
+                    col,
+                    span: (start, end),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Visiting a node estimates how many mutants each applicable operator
+/// generates at it (see `Mutator::estimate()`)
+struct MutantEstimator<'a> {
+    mutator: &'a Mutator,
+    /// The binary operations of the file that resolve to user-defined
+    /// operators, whose `BinaryOpMutation` mutants are restricted to
+    /// user-defined replacements (see `restrict_user_defined_operators()`)
+    user_operations: HashMap<(usize, usize), UserDefinedOperation>,
+}
+
+impl SolASTVisitor<Rc<Source>, Vec<(MutationType, usize)>> for MutantEstimator<'_> {
+    fn skip_node(&self, node: &SolAST, _source: &Rc<Source>) -> bool {
+        self.mutator.is_out_of_scope(node)
+    }
+
+    fn visit_node(&self, node: &SolAST, source: &Rc<Source>) -> Option<Vec<(MutationType, usize)>> {
+        if let Some(ranges) = &self.mutator.conf.source_lines {
+            let (line, _) = source.get_line_column(node.get_bounds().0).ok()?;
+            if !ranges.iter().any(|r| r.contains(line)) {
+                return None;
+            }
+        }
+        let contents = source.contents();
+        let estimates: Vec<(MutationType, usize)> = self
+            .mutator
+            .applicable_operators(node)
+            .map(|op| {
+                let count = op.estimate(node, contents);
+                match op {
+                    MutationType::BinaryOpMutation if count > 0 => {
+                        (*op, self.binary_operation_estimate(node))
+                    }
+                    _ => (*op, count),
+                }
+            })
+            .collect();
+        (!estimates.is_empty()).then_some(estimates)
+    }
+}
+
+impl MutantEstimator<'_> {
+    /// The number of `BinaryOpMutation` mutants of `node`: the replacements
+    /// of the user-defined operator it resolves to (if any), or else of the
+    /// built-in operators, that differ from its operator and that the
+    /// configuration allows
+    fn binary_operation_estimate(&self, node: &SolAST) -> usize {
+        let (_, endl) = node.left_expression().get_bounds();
+        let (startr, _) = node.right_expression().get_bounds();
+        let op = MutationType::BinaryOpMutation;
+        let replacements: Vec<&str> = match self.user_operations.get(&(endl, startr)) {
+            Some(operation) => operation.replacements.iter().map(|r| r.as_str()).collect(),
+            None => op.replacement_tokens().to_vec(),
+        };
+        let orig = node.operator().unwrap_or_default();
+        replacements
+            .iter()
+            .filter(|r| **r != orig.trim() && self.mutator.allows_replacement(&op, r))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        abis_differ, called_libraries, check_experimental_operators, check_operator_replacements,
+        check_solc_settings, estimate_source, expand_mutation_operators, explain_source,
+        filter_name_warnings, inherited_contracts, is_likely_equivalent, locate_source,
+        mutate_source, parameter_types, parse_json_unbounded, qualified_function_warnings,
+        reachable_functions, source::Source, user_defined_operations, with_fields, AstBuilder,
+        FilterNameMatches, FunctionFilter, GambitError, LineRange, Mutant, MutantWriter,
+        MutateParams, MutationCandidate, MutationSpec, MutationStatistics, MutationType, Mutator,
+        MutatorConf, SolAST, Solc, SolcSettings, UserDefinedOperation, ValidationMode,
+        ValidationTimes,
+    };
+    use clap::ValueEnum;
+    use serde_json::{json, Value};
+    use std::{
+        collections::BTreeMap,
+        fs,
+        path::{Path, PathBuf},
+        rc::Rc,
+        str::FromStr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    /// Compute the `src` field of the first occurrence of `text` in `source`
+    fn src(source: &str, text: &str) -> String {
+        let start = source.find(text).unwrap();
+        format!("{}:{}:0", start, text.len())
+    }
+
+    #[test]
+    fn test_mutate_source_in_memory() {
+        let source = "contract C {\n    function f() public pure returns (uint256) {\n        return 1 + 2;\n    }\n}\n";
+        let b = AstBuilder::new(source);
+        let ast = b.source_unit(vec![
+            b.contract("C", vec![b.function("1 + 2", b.binop("1 + 2"))])
+        ]);
+        let conf = MutatorConf {
+            mutation_operators: vec![MutationType::BinaryOpMutation],
+            ..MutatorConf::default()
+        };
+        let mutants = mutate_source(source, "virtual/C.sol", SolAST::new(ast), &conf);
+        let repls: Vec<&str> = mutants.iter().map(|m| m.repl.as_str()).collect();
+        assert_eq!(repls, vec!["-", "*", "/", "%", "**"]);
+
+        let mutant = &mutants[0];
+        assert_eq!(mutant.source.filename().to_str(), Some("virtual/C.sol"));
+        assert_eq!(mutant.orig, " + ");
+        assert_eq!(mutant.get_line_column().unwrap(), (3, 17));
+        let mutated = mutant.as_source_string().unwrap();
+        assert!(mutated.contains("        return 1-2;"));
+        let diff = MutantWriter::diff_mutant(mutant).unwrap();
+        assert!(diff.contains("+        return 1-2;"));
+    }
+
+    #[test]
+    fn test_check_names() {
+        let definition = |node_type: &str, name: &str| json!({"nodeType": node_type, "kind": "function", "name": name, "src": "0:0:0"});
+        let contract = |name: &str, nodes: Vec<Value>| json!({"nodeType": "ContractDefinition", "contractKind": "contract", "name": name, "src": "0:0:0", "nodes": nodes});
+        let unit = SolAST::new(json!({
+            "nodeType": "SourceUnit",
+            "src": "0:0:0",
+            "nodes": [
+                contract("Vault", vec![
+                    definition("FunctionDefinition", "deposit"),
+                    definition("ModifierDefinition", "onlyOwner"),
+                ]),
+                contract("Token", vec![definition("FunctionDefinition", "transfer")]),
+            ]
+        }));
+        let failures = |conf: MutatorConf| -> Vec<Option<String>> {
+            let mutator = Mutator::new(conf, vec![], Solc::new(String::new(), PathBuf::new()));
+            mutator
+                .check_names(std::slice::from_ref(&unit))
+                .into_iter()
+                .map(|c| c.failure)
+                .collect()
+        };
+        let functions = |names: &[&str]| Some(names.iter().map(|n| n.to_string()).collect());
+
+        let conf = MutatorConf {
+            contract: Some("Vault".into()),
+            funcs_to_mutate: functions(&["deposit", "depost", "transfer", "onlyOwner"]),
+            mutate_modifiers: false,
+            ..MutatorConf::default()
+        };
+        assert_eq!(
+            failures(conf),
+            vec![
+                None,
+                None,
+                Some("unknown function `depost`; did you mean `deposit`?".into()),
+                Some("function `transfer` is not defined in contract `Vault`".into()),
+                Some(
+                    "`onlyOwner` is a modifier, which is not mutated (see --no_mutate_modifiers)"
+                        .into()
+                ),
+            ]
+        );
+
+        let conf = MutatorConf {
+            contract: Some("vault".into()),
+            funcs_to_mutate: functions(&["onlyOwner"]),
+            ..MutatorConf::default()
+        };
+        assert_eq!(
+            failures(conf),
+            vec![
+                Some("unknown contract `vault`; did you mean `Vault`?".into()),
+                Some("function `onlyOwner` is not defined in contract `vault`".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_name_matches() {
+        let definition = |node_type: &str, name: &str| json!({"nodeType": node_type, "kind": "function", "name": name, "src": "0:0:0"});
+        let contract = |name: &str, nodes: Vec<Value>| json!({"nodeType": "ContractDefinition", "contractKind": "contract", "name": name, "src": "0:0:0", "nodes": nodes});
+        let unit = |nodes: Vec<Value>| {
+            SolAST::new(json!({"nodeType": "SourceUnit", "src": "0:0:0", "nodes": nodes}))
+        };
+        let token = unit(vec![
+            contract(
+                "Token",
+                vec![
+                    definition("FunctionDefinition", "transfer"),
+                    definition("FunctionDefinition", "transferFrom"),
+                    definition("ModifierDefinition", "onlyOwner"),
+                ],
+            ),
+            contract("Vault", vec![definition("FunctionDefinition", "deposit")]),
+        ]);
+        let vault = unit(vec![contract(
+            "Vault",
+            vec![definition("FunctionDefinition", "withdraw")],
+        )]);
+        let functions = |names: &[&str]| Some(names.iter().map(|n| n.to_string()).collect());
+
+        // Only the functions of the `--contract` are selected
+        let conf = MutatorConf {
+            contract: Some("Token".into()),
+            funcs_to_mutate: functions(&["transferForm", "transfer", "deposit", "onlyOwner"]),
+            ..MutatorConf::default()
+        };
+        let mut matches = FilterNameMatches::default();
+        matches.record(&token, &conf);
+        assert_eq!(
+            matches.unmatched(),
+            vec!["--functions `deposit`", "--functions `transferForm`"]
+        );
+        let available: Vec<&str> = matches.available.iter().map(|n| n.as_str()).collect();
+        assert_eq!(available, vec!["onlyOwner", "transfer", "transferFrom"]);
+
+        // A name is found if any mutated file defines it
+        let conf = MutatorConf {
+            contract: Some("Vault".into()),
+            funcs_to_mutate: functions(&["withdraw", "Vault.deposit"]),
+            ..MutatorConf::default()
+        };
+        let mut token_matches = FilterNameMatches::default();
+        token_matches.record(&token, &conf);
+        assert_eq!(token_matches.unmatched(), vec!["--functions `withdraw`"]);
+        let mut vault_matches = FilterNameMatches::default();
+        vault_matches.record(&vault, &conf);
+        vault_matches.merge(&token_matches);
+        assert!(vault_matches.unmatched().is_empty());
+
+        let conf = MutatorConf {
+            contract: Some("Vualt".into()),
+            ..MutatorConf::default()
+        };
+        let mut matches = FilterNameMatches::default();
+        matches.record(&vault, &conf);
+        assert_eq!(matches.unmatched(), vec!["--contract `Vualt`"]);
+        assert!(matches.available.is_empty());
+    }
+
+    #[test]
+    fn test_mutate_unchecked() {
+        let source = "contract C {\n    uint x;\n    function f(uint a, uint b) public {\n        unchecked { x = a + b; }\n    }\n}\n";
+        // The identifier `name` at the start of the first occurrence of `at`
+        let identifier = |name: &str, at: &str| json!({"nodeType": "Identifier", "name": name, "src": format!("{}:1:0", source.find(at).unwrap())});
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "src": format!("0:{}:0", source.len()),
+            "nodes": [{
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "C",
+                "src": format!("0:{}:0", source.len() - 1),
+                "nodes": [{
+                    "nodeType": "FunctionDefinition",
+                    "kind": "function",
+                    "name": "f",
+                    "stateMutability": "nonpayable",
+                    "src": src(source, "function f(uint a, uint b) public {\n        unchecked { x = a + b; }\n    }"),
+                    "body": {
+                        "nodeType": "Block",
+                        "src": src(source, "{\n        unchecked { x = a + b; }\n    }"),
+                        "statements": [{
+                            "nodeType": "UncheckedBlock",
+                            "src": src(source, "unchecked { x = a + b; }"),
+                            "statements": [{
+                                "nodeType": "ExpressionStatement",
+                                "src": src(source, "x = a + b;"),
+                                "expression": {
+                                    "nodeType": "Assignment",
+                                    "operator": "=",
+                                    "src": src(source, "x = a + b"),
+                                    "leftHandSide": identifier("x", "x = a"),
+                                    "rightHandSide": {
+                                        "nodeType": "BinaryOperation",
+                                        "operator": "+",
+                                        "src": src(source, "a + b"),
+                                        "leftExpression": identifier("a", "a + b"),
+                                        "rightExpression": identifier("b", "b; }"),
+                                    }
+                                }
+                            }]
+                        }]
+                    }
+                }]
+            }]
+        });
+        let mutate = |mutate_unchecked: bool| {
+            let conf = MutatorConf {
+                mutation_operators: vec![MutationType::BinaryOpMutation],
+                mutate_unchecked,
+                ..MutatorConf::default()
+            };
+            mutate_source(source, "virtual/C.sol", SolAST::new(ast.clone()), &conf)
+        };
+        let mutants = mutate(true);
+        let repls: Vec<&str> = mutants.iter().map(|m| m.repl.as_str()).collect();
+        assert_eq!(repls, vec!["-", "*", "/", "%", "**"]);
+        assert!(mutants[0]
+            .as_source_string()
+            .unwrap()
+            .contains("unchecked { x = a-b; }"));
+        assert!(mutate(false).is_empty());
+    }
+
+    #[test]
+    fn test_skip_trivially_equivalent() {
+        let source = "contract C {\n    uint x;\n    function setX(uint v) external { x = v; }\n    function check(uint v) external { require(v > 0); }\n    function both(uint v) external { require(v > 0); x = v; }\n}\n";
+        let at = |start: usize, len: usize| format!("{}:{}:0", start, len);
+        let identifier = |name: &str, start: usize| json!({"nodeType": "Identifier", "name": name, "src": at(start, 1)});
+        // `x = v;` and `require(v > 0);` statements starting at `start`
+        let assignment = |start: usize| {
+            json!({
+                "nodeType": "ExpressionStatement",
+                "src": at(start, 6),
+                "expression": {
+                    "nodeType": "Assignment",
+                    "operator": "=",
+                    "src": at(start, 5),
+                    "leftHandSide": identifier("x", start),
+                    "rightHandSide": identifier("v", start + 4),
+                }
+            })
+        };
+        let require = |start: usize| {
+            json!({
+                "nodeType": "ExpressionStatement",
+                "src": at(start, 15),
+                "expression": {
+                    "nodeType": "FunctionCall",
+                    "src": at(start, 14),
+                    "expression": {"nodeType": "Identifier", "name": "require", "src": at(start, 7)},
+                    "arguments": [{
+                        "nodeType": "BinaryOperation",
+                        "operator": ">",
+                        "src": at(start + 8, 5),
+                        "leftExpression": identifier("v", start + 8),
+                        "rightExpression": {"nodeType": "Literal", "kind": "number", "value": "0", "src": at(start + 12, 1)},
+                    }],
+                }
+            })
+        };
+        let function = |name: &str, statements: &dyn Fn(usize) -> Vec<Value>| {
+            let start = source.find(&format!("function {}", name)).unwrap();
+            let end = start + source[start..].find('}').unwrap() + 1;
+            let body = start + source[start..].find('{').unwrap();
+            json!({
+                "nodeType": "FunctionDefinition",
+                "kind": "function",
+                "name": name,
+                "stateMutability": "nonpayable",
+                "src": at(start, end - start),
+                "body": {
+                    "nodeType": "Block",
+                    "src": at(body, end - body),
+                    "statements": statements(body + 2),
+                }
+            })
+        };
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "src": at(0, source.len()),
+            "nodes": [{
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "C",
+                "src": at(0, source.len() - 1),
+                "nodes": [
+                    function("setX", &|start| vec![assignment(start)]),
+                    function("check", &|start| vec![require(start)]),
+                    function("both", &|start| vec![require(start), assignment(start + 16)]),
+                ]
+            }]
+        });
+        let conf = MutatorConf {
+            mutation_operators: vec![
+                MutationType::AssignmentMutation,
+                MutationType::DeleteExpressionMutation,
+                MutationType::RequireMutation,
+            ],
+            delete_expression_exclusions: vec![],
+            ..MutatorConf::default()
+        };
+        let mutate = |conf: MutatorConf| {
+            let source = Rc::new(Source::from_contents(
+                PathBuf::from("virtual/C.sol"),
+                PathBuf::new(),
+                source.as_bytes().to_vec(),
+            ));
+            let mutator = Mutator::new(conf, vec![], Solc::new(String::new(), PathBuf::new()));
+            let mut stats = MutationStatistics::default();
+            let mutants = mutator.mutate_ast(SolAST::new(ast.clone()), source, &mut stats);
+            let deleted: Vec<(usize, String)> = mutants
+                .iter()
+                .filter(|m| m.op == MutationType::DeleteExpressionMutation)
+                .map(|m| (m.get_line_column().unwrap().0, m.orig.clone()))
+                .collect();
+            (
+                mutants.len(),
+                deleted,
+                stats.trivially_equivalent_mutants_skipped,
+            )
+        };
+
+        // Every statement is deleted by default
+        let (total, deleted, skipped) = mutate(conf.clone());
+        assert_eq!(
+            deleted,
+            vec![
+                (3, "x = v;".to_string()),
+                (4, "require(v > 0);".to_string()),
+                (5, "require(v > 0);".to_string()),
+                (5, "x = v;".to_string()),
+            ]
+        );
+        assert_eq!(skipped, 0);
+
+        // Deleting the only statement of a setter or a single-require
+        // function is dropped in favor of its assignment and require mutants,
+        // while the statements of longer bodies are still deleted
+        let conf = MutatorConf {
+            skip_trivially_equivalent: true,
+            ..conf
+        };
+        let (filtered_total, deleted, skipped) = mutate(conf.clone());
+        assert_eq!(
+            deleted,
+            vec![
+                (5, "require(v > 0);".to_string()),
+                (5, "x = v;".to_string()),
+            ]
+        );
+        assert_eq!(skipped, 2);
+        assert_eq!(filtered_total, total - 2);
+
+        // Without the equivalent mutant, the deletion is kept
+        let conf = MutatorConf {
+            mutation_operators: vec![MutationType::DeleteExpressionMutation],
+            ..conf
+        };
+        let (_, deleted, skipped) = mutate(conf);
+        assert_eq!(deleted.len(), 4);
+        assert_eq!(skipped, 0);
+    }
+
+    #[test]
+    fn test_skip_likely_equivalent() {
+        let source = "contract C {\n    uint x;\n    function f(uint a) public {\n        x = a-a;\n        x = 2 * 1;\n    }\n}\n";
+        let at = |text: &str, offset: usize| format!("{}:1:0", source.find(text).unwrap() + offset);
+        let statement = |text: &str, operator: &str, left: Value, right: Value| {
+            let expression = &text[4..text.len() - 1];
+            json!({
+                "nodeType": "ExpressionStatement",
+                "src": src(source, text),
+                "expression": {
+                    "nodeType": "Assignment",
+                    "operator": "=",
+                    "src": src(source, &text[..text.len() - 1]),
+                    "leftHandSide": {"nodeType": "Identifier", "name": "x", "src": at(text, 0)},
+                    "rightHandSide": {
+                        "nodeType": "BinaryOperation",
+                        "operator": operator,
+                        "src": src(source, expression),
+                        "leftExpression": left,
+                        "rightExpression": right,
+                    }
+                }
+            })
+        };
+        let a = |offset: usize| json!({"nodeType": "Identifier", "name": "a", "src": at("a-a", offset)});
+        let literal = |value: &str, offset: usize| json!({"nodeType": "Literal", "kind": "number", "value": value, "src": at("2 * 1", offset)});
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "src": format!("0:{}:0", source.len()),
+            "nodes": [{
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "C",
+                "src": format!("0:{}:0", source.len() - 1),
+                "nodes": [{
+                    "nodeType": "FunctionDefinition",
+                    "kind": "function",
+                    "name": "f",
+                    "stateMutability": "nonpayable",
+                    "src": src(source, "function f(uint a) public {\n        x = a-a;\n        x = 2 * 1;\n    }"),
+                    "body": {
+                        "nodeType": "Block",
+                        "src": src(source, "{\n        x = a-a;\n        x = 2 * 1;\n    }"),
+                        "statements": [
+                            statement("x = a-a;", "-", a(0), a(2)),
+                            statement("x = 2 * 1;", "*", literal("2", 0), literal("1", 4)),
+                        ]
+                    }
+                }]
+            }]
+        });
+        let conf = MutatorConf {
+            mutation_operators: vec![
+                MutationType::BinaryOpMutation,
+                MutationType::SwapArgumentsOperatorMutation,
+            ],
+            ..MutatorConf::default()
+        };
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("virtual/C.sol"),
+            PathBuf::new(),
+            source.as_bytes().to_vec(),
+        ));
+        let mutator = Mutator::new(conf, vec![], Solc::new(String::new(), PathBuf::new()));
+        let mut stats = MutationStatistics::default();
+        let mutants = mutator.mutate_ast(SolAST::new(ast), source.clone(), &mut stats);
+        let mutated: Vec<(String, String)> = mutants
+            .iter()
+            .map(|m| (m.orig.clone(), m.repl.clone()))
+            .collect();
+        // Swapping the operands of `a-a` only adds whitespace, and `2 / 1` and
+        // `2 ** 1` fold to the value of `2 * 1`
+        let expected: Vec<(&str, &str)> = vec![
+            ("-", "+"),
+            ("-", "*"),
+            ("-", "/"),
+            ("-", "%"),
+            ("-", "**"),
+            (" * ", "+"),
+            (" * ", "-"),
+            (" * ", "%"),
+        ];
+        assert_eq!(
+            mutated,
+            expected
+                .into_iter()
+                .map(|(o, r)| (o.to_string(), r.to_string()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(stats.likely_equivalent_mutants_skipped, 3);
+
+        // A replacement that only differs in whitespace is equivalent, except
+        // within string literals
+        let node = SolAST::new(json!({"nodeType": "Identifier", "src": "0:1:0"}));
+        let operation = source
+            .contents()
+            .windows(3)
+            .position(|w| w == b"a-a")
+            .unwrap();
+        let respaced = Mutant::new(
+            source.clone(),
+            MutationType::BinaryOpMutation,
+            operation,
+            operation + 3,
+            "a -\ta".into(),
+        )
+        .unwrap();
+        assert!(is_likely_equivalent(&node, &respaced));
+        let mut quoted = respaced.clone();
+        quoted.orig = "\"a-a\"".into();
+        quoted.repl = "\"a - a\"".into();
+        assert!(!is_likely_equivalent(&node, &quoted));
+    }
+
+    #[test]
+    fn test_explain_source() {
+        let source = "contract C {\n    function f() public pure returns (uint256) {\n        return 1 + 2;\n    }\n}\n";
+        let b = AstBuilder::new(source);
+        let ast = b.source_unit(vec![
+            b.contract("C", vec![b.function("1 + 2", b.binop("1 + 2"))])
+        ]);
+        let conf = MutatorConf {
+            mutation_operators: MutationType::default_mutation_operators(),
+            ..MutatorConf::default()
+        };
+        let mutants = mutate_source(source, "virtual/C.sol", SolAST::new(ast.clone()), &conf);
+        assert!(!mutants.is_empty());
+
+        // Every mutant of a normal run shows up in the explanation of its
+        // location, along with the operator that generated it
+        for mutant in mutants.iter() {
+            let (line, col) = mutant.get_line_column().unwrap();
+            let explanation = explain_source(
+                source,
+                "virtual/C.sol",
+                SolAST::new(ast.clone()),
+                &conf,
+                line,
+                col,
+            )
+            .unwrap();
+            assert_eq!(explanation.out_of_scope, None);
+            let found = explanation.nodes.iter().any(|node| {
+                node.operators.iter().any(|trace| {
+                    trace.op == mutant.op
+                        && trace.replacements.iter().any(|r| {
+                            (r.line, r.col) == (line, col)
+                                && r.orig == mutant.orig
+                                && r.repl == mutant.repl
+                        })
+                })
+            });
+            assert!(found, "{} missing from\n{}", mutant.repl, explanation);
+        }
+
+        // Operators that don't apply to the node at the location say why
+        let explanation =
+            explain_source(source, "virtual/C.sol", SolAST::new(ast), &conf, 3, 18).unwrap();
+        let binop = explanation
+            .nodes
+            .iter()
+            .find(|node| node.node_type == "BinaryOperation")
+            .unwrap();
+        let skipped = binop
+            .operators
+            .iter()
+            .find(|trace| trace.op == MutationType::RequireMutation)
+            .unwrap();
+        assert!(skipped.skipped.as_ref().unwrap().contains("does not apply"));
+        assert!(explanation.to_string().contains("binary-op-mutation"));
+    }
+
+    #[test]
+    fn test_validation_deadline() {
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("virtual/C.sol"),
+            PathBuf::new(),
+            b"contract C { uint256 x = 1 + 2; }".to_vec(),
+        ));
+        let mutants: Vec<Mutant> = ["-", "*"]
+            .iter()
+            .map(|repl| {
+                Mutant::new(
+                    source.clone(),
+                    MutationType::BinaryOpMutation,
+                    26,
+                    29,
+                    repl.to_string(),
+                )
+                .unwrap()
+            })
+            .collect();
+        let solc = Solc::new("no-such-solc".to_string(), PathBuf::new());
+        let mutator = Mutator::new(MutatorConf::default(), vec![], solc);
+
+        // Mutants that fail to validate are dropped
+        assert!(mutator.get_valid_mutants(&mutants).is_empty());
+        assert_eq!(mutator.validation_attempts(), 2);
+
+        // Mutants that were already validated are not compiled again
+        assert!(mutator.get_valid_mutants(&mutants).is_empty());
+        assert_eq!(mutator.validation_attempts(), 2);
+
+        // Past the deadline, mutants are passed through unvalidated
+        let mut mutator = Mutator::new(
+            MutatorConf::default(),
+            vec![],
+            Solc::new("no-such-solc".to_string(), PathBuf::new()),
+        );
+        mutator.set_validation_deadline(Some(Instant::now()));
+        let passed = mutator.get_valid_mutants(&mutants);
+        assert_eq!(passed.len(), 2);
+        assert!(passed.iter().all(|m| !m.validated));
+
+        // So are they once the run is cancelled
+        let mut mutator = Mutator::new(
+            MutatorConf::default(),
+            vec![],
+            Solc::new("no-such-solc".to_string(), PathBuf::new()),
+        );
+        let cancelled = Arc::new(AtomicBool::new(false));
+        mutator.set_cancellation(cancelled.clone());
+        assert!(mutator.get_valid_mutants(&mutants[..1]).is_empty());
+        cancelled.store(true, Ordering::SeqCst);
+        assert!(mutator.is_cancelled());
+        let passed = mutator.get_valid_mutants(&mutants);
+        assert_eq!(passed.len(), 1);
+        assert!(!passed[0].validated);
+        assert_eq!(mutator.validation_attempts(), 1);
+    }
+
+    #[test]
+    fn test_parallel_validation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::write(root.join("C.sol"), "contract C { uint256 x = 1 + 2; }").unwrap();
+        let source = Rc::new(Source::new(root.join("C.sol"), root.clone()).unwrap());
+        // The last mutant duplicates the first one
+        let mutants: Vec<Mutant> = ["-", "*", "/", "%", "**", "-"]
+            .iter()
+            .map(|repl| {
+                Mutant::new(
+                    source.clone(),
+                    MutationType::BinaryOpMutation,
+                    26,
+                    29,
+                    repl.to_string(),
+                )
+                .unwrap()
+            })
+            .collect();
+        let conf = MutatorConf {
+            validation_jobs: 4,
+            legacy_validation: true,
+            ..MutatorConf::default()
+        };
+
+        // `true` accepts every mutant, and `reject` rejects every mutant with
+        // an error (solc failing without any output is a transient failure)
+        let reject = root.join("reject");
+        fs::write(&reject, "#!/bin/sh\necho 'Error: rejected' >&2\nexit 1\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&reject, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        for (solc, valid) in [("true", true), (reject.to_str().unwrap(), false)] {
+            let mutator = Mutator::new(conf.clone(), vec![], Solc::new(solc.into(), root.clone()));
+            let refs: Vec<&Mutant> = mutants.iter().collect();
+            assert_eq!(
+                mutator.validate_mutants_before_deadline(&refs),
+                vec![Some(valid); 6]
+            );
+            assert_eq!(mutator.validation_attempts(), 5);
+            assert_eq!(mutator.validation_stages().compilations, 5);
+            // Mutants that were already validated are not compiled again
+            assert_eq!(
+                mutator.get_valid_mutants(&mutants).len(),
+                6 * valid as usize
+            );
+            assert_eq!(mutator.validation_attempts(), 5);
+        }
+
+        // Past the deadline, mutants are passed through unvalidated
+        let mut mutator = Mutator::new(conf, vec![], Solc::new("false".into(), root.clone()));
+        mutator.set_validation_deadline(Some(Instant::now()));
+        let passed = mutator.get_valid_mutants(&mutants);
+        assert_eq!(passed.len(), 6);
+        assert!(passed.iter().all(|m| !m.validated));
+        assert_eq!(mutator.validation_attempts(), 0);
+    }
+
+    #[test]
+    fn test_standard_json_validation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("contracts")).unwrap();
+        let code = "import \"./B.sol\";\ncontract A is B { uint256 x = 1 + 2; }\n";
+        fs::write(root.join("contracts/A.sol"), code).unwrap();
+        fs::write(root.join("contracts/B.sol"), "contract B {}\n").unwrap();
+        let source = Rc::new(Source::new(root.join("contracts/A.sol"), root.clone()).unwrap());
+        let start = code.find("1 + 2").unwrap();
+        let mutant = |repl: &str| {
+            Mutant::new(
+                source.clone(),
+                MutationType::BinaryOpMutation,
+                start,
+                start + 5,
+                repl.into(),
+            )
+            .unwrap()
+        };
+
+        // A stand-in for solc that records its input, and reports an error
+        // for mutants that contain `BROKEN`
+        let fake_solc = root.join("fake-solc");
+        fs::write(
+            &fake_solc,
+            format!(
+                "#!/bin/sh\necho \"$@\" > {0}/args\ncat > {0}/input.json\n\
+                 if grep -q BROKEN {0}/input.json; then\n\
+                 echo '{{\"errors\": [{{\"severity\": \"error\", \"message\": \"broken\"}}]}}'\n\
+                 else\necho '{{\"errors\": [{{\"severity\": \"warning\", \"message\": \"w\"}}]}}'\nfi\n",
+                root.display()
+            ),
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&fake_solc, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let mut solc = Solc::new(fake_solc.to_str().unwrap().into(), root.clone());
+        solc.with_basepath(root.to_str().unwrap().into());
+        solc.with_remappings(vec!["@lib/=lib/".into()]);
+        let mutator = Mutator::new(MutatorConf::default(), vec![], solc);
+
+        // The mutated contents replace the original file's under its source
+        // unit name, so `./B.sol` resolves as it does for the original
+        assert!(mutator.validate_mutant(&mutant("1 - 2")).unwrap());
+        let input: Value =
+            serde_json::from_str(&fs::read_to_string(root.join("input.json")).unwrap()).unwrap();
+        assert_eq!(
+            input["sources"]["contracts/A.sol"]["content"],
+            mutant("1 - 2").as_source_string().unwrap()
+        );
+        assert_eq!(input["sources"].as_object().unwrap().len(), 1);
+        assert_eq!(input["settings"]["remappings"], json!(["@lib/=lib/"]));
+        assert_eq!(input["settings"].get("stopAfter"), None);
+        let args = fs::read_to_string(root.join("args")).unwrap();
+        assert!(args.starts_with("--standard-json --base-path"));
+        // No temporary copy of the mutated file is written
+        assert_eq!(fs::read_dir(root.join("contracts")).unwrap().count(), 2);
+
+        // Only errors reject a mutant
+        assert!(!mutator.validate_mutant(&mutant("BROKEN")).unwrap());
+        assert_eq!(mutator.validation_stages().rejected_by_compilation, 1);
+
+        // Syntax checks stop after parsing
+        let conf = MutatorConf {
+            validation_mode: ValidationMode::Fast,
+            ..MutatorConf::default()
+        };
+        let mutator = Mutator::new(conf, vec![], mutator.solc().clone());
+        assert!(mutator.validate_mutant(&mutant("1 * 2")).unwrap());
+        let input: Value =
+            serde_json::from_str(&fs::read_to_string(root.join("input.json")).unwrap()).unwrap();
+        assert_eq!(input["settings"]["stopAfter"], "parsing");
+    }
+
+    #[test]
+    fn test_compare_abi() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A {\n    function f() public returns (uint256) {\n        return 1 + 2;\n    }\n}\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let mutant = |orig: &str, repl: &str| {
+            let start = code.find(orig).unwrap();
+            Mutant::new(
+                source.clone(),
+                MutationType::BinaryOpMutation,
+                start,
+                start + orig.len(),
+                repl.into(),
+            )
+            .unwrap()
+        };
+        // Preserves the ABI
+        let preserving = mutant("1 + 2", "1 - 2");
+        // Changes the ABI: `f` is no longer part of it
+        let changing = mutant("public", "internal");
+
+        // A stand-in for solc that reports `f` in the ABI of `A` unless it is
+        // internal
+        let fake_solc = root.join("fake-solc");
+        fs::write(
+            &fake_solc,
+            "#!/bin/sh
+if grep -q internal; then abi='[]'; else              abi='[{\"type\": \"function\", \"name\": \"f\", \"inputs\": []}]'; fi
+             echo \"{\\\"contracts\\\": {\\\"A.sol\\\": {\\\"A\\\": {\\\"abi\\\": $abi}}}}\"
+",
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&fake_solc, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let mut solc = Solc::new(fake_solc.to_str().unwrap().into(), root.clone());
+        solc.with_basepath(root.to_str().unwrap().into());
+
+        for jobs in [1, 2] {
+            let conf = MutatorConf {
+                compare_abi: true,
+                validation_jobs: jobs,
+                ..MutatorConf::default()
+            };
+            let mutator = Mutator::new(conf, vec![], solc.clone());
+            let mutants = vec![preserving.clone(), changing.clone()];
+            assert_eq!(mutator.get_valid_mutants(&mutants).len(), 2);
+            assert_eq!(mutator.abi_changed(&preserving), Some(false));
+            assert_eq!(mutator.abi_changed(&changing), Some(true));
+        }
+
+        // ABIs are not compared unless requested
+        let mutator = Mutator::new(MutatorConf::default(), vec![], solc.clone());
+        assert!(mutator.validate_mutant(&changing).unwrap());
+        assert_eq!(mutator.abi_changed(&changing), None);
+
+        // ABIs are compared structurally
+        let abis = |abi: Value| BTreeMap::from([("A".to_string(), abi)]);
+        let f = json!({"type": "function", "name": "f", "inputs": []});
+        let g = json!({"inputs": [], "name": "g", "type": "function"});
+        assert!(!abis_differ(
+            &abis(json!([f.clone(), g.clone()])),
+            &abis(json!([g.clone(), f.clone()]))
+        ));
+        assert!(abis_differ(&abis(json!([f.clone(), g])), &abis(json!([f]))));
+    }
+
+    #[test]
+    fn test_solc_retries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A { uint256 x = 1 + 2; }\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let start = code.find("1 + 2").unwrap();
+        let mutant = |repl: &str| {
+            Mutant::new(
+                source.clone(),
+                MutationType::BinaryOpMutation,
+                start,
+                start + 5,
+                repl.into(),
+            )
+            .unwrap()
+        };
+
+        // A stand-in for solc that counts its invocations and fails the first
+        // `failures` of them without any output, alternately by killing itself
+        // and by exiting with a non-zero code
+        let fake_solc = root.join("fake-solc");
+        fs::write(
+            &fake_solc,
+            format!(
+                "#!/bin/sh\ncat > /dev/null\n\
+                 n=$(($(cat {0}/count 2>/dev/null || echo 0) + 1))\necho $n > {0}/count\n\
+                 if [ $n -le $(cat {0}/failures) ]; then\n\
+                 if [ $((n % 2)) -eq 1 ]; then kill -9 $$; else exit 3; fi\nfi\n\
+                 echo '{{\"errors\": []}}'\n",
+                root.display()
+            ),
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&fake_solc, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let count = || {
+            fs::read_to_string(root.join("count"))
+                .unwrap()
+                .trim()
+                .parse::<usize>()
+                .unwrap()
+        };
+        let mut solc = Solc::new(fake_solc.to_str().unwrap().into(), root.clone());
+        solc.with_basepath(root.to_str().unwrap().into());
+        solc.with_retries(2);
+
+        // Transient failures are retried
+        fs::write(root.join("failures"), "2").unwrap();
+        let mutator = Mutator::new(MutatorConf::default(), vec![], solc.clone());
+        assert_eq!(
+            mutator.validate_mutant_before_deadline(&mutant("1 - 2")),
+            Some(true)
+        );
+        assert_eq!(count(), 3);
+        assert!(!mutator.validation_error(&mutant("1 - 2")));
+
+        // A mutant that solc keeps failing on is passed through unvalidated,
+        // flagged as a validation error, and not compiled again
+        fs::write(root.join("failures"), "100").unwrap();
+        assert_eq!(
+            mutator.validate_mutant_before_deadline(&mutant("1 * 2")),
+            None
+        );
+        assert_eq!(count(), 6);
+        assert!(mutator.validation_error(&mutant("1 * 2")));
+        assert_eq!(
+            mutator.validate_mutant_before_deadline(&mutant("1 * 2")),
+            None
+        );
+        assert_eq!(count(), 6);
+        let valid = mutator.get_valid_mutants(&[mutant("1 * 2")]);
+        assert_eq!(valid.len(), 1);
+        assert!(!valid[0].validated);
+
+        // Likewise when validating in parallel
+        let conf = MutatorConf {
+            validation_jobs: 2,
+            ..MutatorConf::default()
+        };
+        let mutator = Mutator::new(conf, vec![], solc);
+        let mutants = [mutant("1 / 2"), mutant("1 % 2")];
+        assert_eq!(
+            mutator.validate_mutants_before_deadline(&mutants.iter().collect::<Vec<_>>()),
+            vec![None, None]
+        );
+        assert!(mutants.iter().all(|m| mutator.validation_error(m)));
+    }
+
+    #[test]
+    fn test_solc_settings() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A { uint256 x = 1 + 2; }\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let start = code.find("1 + 2").unwrap();
+        let mutants: Vec<Mutant> = ["1 - 2", "1 * 2", "1 / 2"]
+            .iter()
+            .map(|repl| {
+                let op = MutationType::BinaryOpMutation;
+                Mutant::new(source.clone(), op, start, start + 5, repl.to_string()).unwrap()
+            })
+            .collect();
+
+        // A stand-in for solc that records its input and arguments, and only
+        // compiles the contract via IR
+        let fake_solc = root.join("fake-solc");
+        fs::write(
+            &fake_solc,
+            format!(
+                "#!/bin/sh\necho \"$@\" > {0}/args\ncat > {0}/input.json\n\
+                 if grep -q '\"viaIR\":true' {0}/input.json || echo \"$@\" | grep -q via-ir; then\n\
+                 echo '{{\"errors\": []}}'\n\
+                 else\necho '{{\"errors\": [{{\"severity\": \"error\", \"message\": \"Stack too deep\"}}]}}'\nfi\n",
+                root.display()
+            ),
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&fake_solc, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let mut solc = Solc::new(fake_solc.to_str().unwrap().into(), root.clone());
+        solc.with_basepath(root.to_str().unwrap().into());
+
+        // Without the settings, no mutant compiles
+        let mutator = Mutator::new(MutatorConf::default(), vec![], solc.clone());
+        assert!(mutator.get_valid_mutants(&mutants).is_empty());
+
+        let settings = json!({
+            "viaIR": true,
+            "evmVersion": "paris",
+            "optimizer": {"runs": 1000},
+            "outputSelection": {},
+            "unknown": 1,
+        });
+        let settings = settings.as_object().unwrap();
+        let (passed, warnings) = check_solc_settings(settings);
+        assert_eq!(
+            passed.keys().collect::<Vec<_>>(),
+            ["evmVersion", "optimizer", "viaIR"]
+        );
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("`outputSelection`, which Gambit sets itself"));
+        assert!(warnings[1].contains("unknown solc setting `unknown`"));
+
+        // With them, every mutant compiles, and the settings are merged into
+        // those set by Gambit
+        solc.with_settings(settings);
+        let mutator = Mutator::new(MutatorConf::default(), vec![], solc.clone());
+        assert_eq!(mutator.get_valid_mutants(&mutants).len(), 3);
+        let input: Value =
+            serde_json::from_str(&fs::read_to_string(root.join("input.json")).unwrap()).unwrap();
+        assert_eq!(input["settings"]["viaIR"], true);
+        assert_eq!(input["settings"]["evmVersion"], "paris");
+        assert_eq!(
+            input["settings"]["optimizer"],
+            json!({"enabled": false, "runs": 1000})
+        );
+        assert_eq!(input["settings"].get("unknown"), None);
+        assert!(input["settings"]["outputSelection"]["A.sol"].is_object());
+
+        // On the command line, the settings are passed as flags, and
+        // `--solc_evm_version` takes precedence
+        solc.with_evm_version("london".into());
+        solc.compile(&root.join("A.sol"), &root).unwrap();
+        let args = fs::read_to_string(root.join("args")).unwrap();
+        assert!(args.contains("--optimize-runs 1000 --evm-version london --via-ir"));
+        assert!(!args.contains("--optimize "));
+
+        // Settings are read from a file relative to the configuration
+        fs::write(root.join("settings.json"), "{\"viaIR\": true}").unwrap();
+        let file = SolcSettings::from_str("settings.json").unwrap();
+        assert_eq!(file, SolcSettings::File("settings.json".into()));
+        assert_eq!(file.load(&root).unwrap()["viaIR"], true);
+        assert!(SolcSettings::File("missing.json".into())
+            .load(&root)
+            .is_err());
+        fs::write(root.join("settings.json"), "[]").unwrap();
+        assert!(file
+            .load(&root)
+            .unwrap_err()
+            .contains("must hold a JSON object"));
+        assert_eq!(
+            SolcSettings::from_str(" {\"viaIR\": true}").unwrap(),
+            SolcSettings::Inline(json!({"viaIR": true}).as_object().unwrap().clone())
+        );
+        assert!(SolcSettings::from_str("{viaIR}").is_err());
+    }
+
+    #[test]
+    fn test_validation_times() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "contract A { uint256 x = 1 + 2; }\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let start = code.find("1 + 2").unwrap();
+        let mutant = |op: MutationType, repl: &str| {
+            Mutant::new(source.clone(), op, start, start + 5, repl.into()).unwrap()
+        };
+        // The last mutant duplicates the second one
+        let mutants = vec![
+            mutant(MutationType::BinaryOpMutation, "1 ** 2"),
+            mutant(MutationType::BinaryOpMutation, "1 - 2"),
+            mutant(MutationType::AssignmentMutation, "0"),
+            mutant(MutationType::BinaryOpMutation, "1 * 2"),
+            mutant(MutationType::AssignmentMutation, "1 - 2"),
+        ];
+
+        // A stand-in for solc that takes longer to compile some mutants, and
+        // rejects the assignment of `0`
+        let fake_solc = root.join("fake-solc");
+        fs::write(
+            &fake_solc,
+            "#!/bin/sh\ninput=$(cat)\ncase \"$input\" in\n\
+             *'1 ** 2'*) sleep 0.4 ;;\n\
+             *'= 0;'*) sleep 0.2; echo '{\"errors\": [{\"severity\": \"error\", \"message\": \"e\"}]}'; exit 0 ;;\n\
+             esac\necho '{\"errors\": []}'\n",
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&fake_solc, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let mut solc = Solc::new(fake_solc.to_str().unwrap().into(), root.clone());
+        solc.with_basepath(root.to_str().unwrap().into());
+
+        for jobs in [1, 2] {
+            let conf = MutatorConf {
+                validation_jobs: jobs,
+                ..MutatorConf::default()
+            };
+            let mutator = Mutator::new(conf, vec![], solc.clone());
+            assert_eq!(mutator.get_valid_mutants(&mutants).len(), 4);
+            let time = |i: usize| mutator.validation_time(&mutants[i]).unwrap();
+            assert!(time(0) >= Duration::from_millis(400));
+            assert!(time(2) >= Duration::from_millis(200));
+            assert!(time(0) > time(2) && time(2) > time(1) && time(2) > time(3));
+            // The duplicate was not compiled again, but took as long
+            assert_eq!(time(4), time(1));
+
+            let times = ValidationTimes::of(mutator.validation_times(&mutants));
+            let slowest: Vec<(&str, bool)> = times
+                .slowest
+                .iter()
+                .map(|m| (m.repl.as_str(), m.valid))
+                .collect();
+            assert_eq!(slowest[..2], [("1 ** 2", true), ("0", false)]);
+            assert_eq!(slowest.len(), 4);
+            assert_eq!(times.slowest[0].validation_ms, time(0).as_millis() as u64);
+            assert_eq!((times.slowest[0].line, times.slowest[0].col), (1, 26));
+
+            let binary = &times.operators["BinaryOpMutation"];
+            assert_eq!(binary.validated, 3);
+            assert!(binary.median_ms < 400);
+            assert_eq!(
+                binary.mean_ms,
+                ((time(0) + time(1) + time(3)) / 3).as_millis() as u64
+            );
+            let assignment = &times.operators["AssignmentMutation"];
+            assert_eq!(assignment.validated, 1);
+            assert_eq!(assignment.mean_ms, assignment.median_ms);
+            assert!(assignment.median_ms >= 200);
+        }
+
+        // Mutants that were not compiled have no validation time
+        let mutator = Mutator::new(MutatorConf::default(), vec![], solc);
+        assert_eq!(mutator.validation_time(&mutants[0]), None);
+        assert!(mutator.validation_times(&mutants).is_empty());
+    }
+
+    #[test]
+    fn test_validate_mutant_with_relative_import() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("contracts")).unwrap();
+        let code = "// SPDX-License-Identifier: MIT\npragma solidity >=0.8.0;\nimport \"./Sibling.sol\";\ncontract A { function f() public pure returns (uint256) { return Sibling.one() + 1; } }\n";
+        fs::write(root.join("contracts/A.sol"), code).unwrap();
+        fs::write(
+            root.join("contracts/Sibling.sol"),
+            "// SPDX-License-Identifier: MIT\npragma solidity >=0.8.0;\nlibrary Sibling { function one() internal pure returns (uint256) { return 1; } }\n",
+        )
+        .unwrap();
+        let source = Rc::new(Source::new(root.join("contracts/A.sol"), root.clone()).unwrap());
+        let start = code.find("+ 1").unwrap();
+        let mutant = |repl: &str| {
+            Mutant::new(
+                source.clone(),
+                MutationType::BinaryOpMutation,
+                start,
+                start + 1,
+                repl.into(),
+            )
+            .unwrap()
+        };
+        for legacy_validation in [false, true] {
+            let conf = MutatorConf {
+                legacy_validation,
+                ..MutatorConf::default()
+            };
+            let mut solc = Solc::new("solc".into(), root.clone());
+            solc.with_basepath(root.to_str().unwrap().into());
+            let mutator = Mutator::new(conf, vec![], solc);
+            assert!(mutator.validate_mutant(&mutant("-")).unwrap());
+            assert!(!mutator.validate_mutant(&mutant("+ +")).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_compare_abi_with_solc() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let code = "// SPDX-License-Identifier: MIT\npragma solidity >=0.8.0;\ncontract A { function f() public pure returns (uint256) { return 1 + 2; } }\n";
+        fs::write(root.join("A.sol"), code).unwrap();
+        let source = Rc::new(Source::new(root.join("A.sol"), root.clone()).unwrap());
+        let mutant = |orig: &str, repl: &str| {
+            let start = code.find(orig).unwrap();
+            Mutant::new(
+                source.clone(),
+                MutationType::BinaryOpMutation,
+                start,
+                start + orig.len(),
+                repl.into(),
+            )
+            .unwrap()
+        };
+        let conf = MutatorConf {
+            compare_abi: true,
+            ..MutatorConf::default()
+        };
+        let mut solc = Solc::new("solc".into(), root.clone());
+        solc.with_basepath(root.to_str().unwrap().into());
+        let mutator = Mutator::new(conf, vec![], solc);
+        // An external function has the same ABI as a public one
+        let external = mutant("public", "external");
+        assert!(mutator.validate_mutant(&external).unwrap());
+        assert_eq!(mutator.abi_changed(&external), Some(false));
+        let internal = mutant("public", "internal");
+        assert!(mutator.validate_mutant(&internal).unwrap());
+        assert_eq!(mutator.abi_changed(&internal), Some(true));
+    }
+
+    #[test]
+    fn test_validation_stages() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::write(root.join("C.sol"), "contract C { uint256 x = 1 + 2; }").unwrap();
+        let source = Rc::new(Source::new(root.join("C.sol"), root.clone()).unwrap());
+        let mutant =
+            Mutant::new(source, MutationType::BinaryOpMutation, 26, 29, "-".into()).unwrap();
+        let stages = |validation_mode| {
+            let conf = MutatorConf {
+                validation_mode,
+                ..MutatorConf::default()
+            };
+            let solc = Solc::new("no-such-solc".to_string(), PathBuf::new());
+            let mutator = Mutator::new(conf, vec![], solc);
+            assert!(!mutator.validate_mutant(&mutant).unwrap());
+            let stages = mutator.validation_stages();
+            (
+                stages.syntax_checks,
+                stages.rejected_by_syntax_check,
+                stages.compilations,
+                stages.rejected_by_compilation,
+            )
+        };
+        // Mutants rejected by the syntax check are never compiled
+        assert_eq!(stages(ValidationMode::Full), (0, 0, 1, 1));
+        assert_eq!(stages(ValidationMode::TwoStage), (1, 1, 0, 0));
+        assert_eq!(stages(ValidationMode::Fast), (1, 1, 0, 0));
+    }
+
+    #[test]
+    fn test_operator_replacements() {
+        let source = "contract C {\n    function f(uint a, uint b) public returns (uint) {\n        return a * b;\n    }\n}\n";
+        let at = |offset: usize| format!("{}:1:0", source.find("a * b").unwrap() + offset);
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "src": format!("0:{}:0", source.len()),
+            "nodes": [{
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "C",
+                "src": format!("0:{}:0", source.len() - 1),
+                "nodes": [{
+                    "nodeType": "FunctionDefinition",
+                    "kind": "function",
+                    "name": "f",
+                    "stateMutability": "nonpayable",
+                    "src": src(source, "function f(uint a, uint b) public returns (uint) {\n        return a * b;\n    }"),
+                    "body": {
+                        "nodeType": "Block",
+                        "src": src(source, "{\n        return a * b;\n    }"),
+                        "statements": [{
+                            "nodeType": "Return",
+                            "src": src(source, "return a * b;"),
+                            "expression": {
+                                "nodeType": "BinaryOperation",
+                                "operator": "*",
+                                "src": src(source, "a * b"),
+                                "leftExpression": {"nodeType": "Identifier", "name": "a", "src": at(0)},
+                                "rightExpression": {"nodeType": "Identifier", "name": "b", "src": at(4)},
+                            }
+                        }]
+                    }
+                }]
+            }]
+        });
+        let conf = |mutations: Value| {
+            let params: MutateParams = serde_json::from_value(json!({
+                "filename": "C.sol",
+                "mutations": mutations,
+            }))
+            .unwrap();
+            MutatorConf::from(&params)
+        };
+        let replacements = |conf: &MutatorConf| -> Vec<String> {
+            mutate_source(source, "C.sol", SolAST::new(ast.clone()), conf)
+                .iter()
+                .map(|m| m.repl.clone())
+                .collect()
+        };
+
+        let restricted = conf(json!([
+            {"operator": "arithmetic-operator-replacement", "replacements": ["+", "-"]},
+        ]));
+        assert_eq!(
+            restricted.mutation_operators,
+            vec![MutationType::BinaryOpMutation]
+        );
+        assert_eq!(replacements(&restricted), vec!["+", "-"]);
+        let estimate = estimate_source(source, "C.sol", SolAST::new(ast.clone()), &restricted);
+        assert_eq!(estimate.total, 2);
+
+        // The operator itself is not a mutant of the restricted set
+        let restricted = conf(json!([{"operator": "aor", "replacements": ["*", "/"]}]));
+        assert_eq!(replacements(&restricted), vec!["/"]);
+
+        // Naming the operator as well lifts the restriction
+        let unrestricted = conf(json!([
+            {"operator": "binary-op-mutation", "replacements": ["+"]},
+            "binary-op-mutation",
+        ]));
+        assert_eq!(
+            unrestricted.mutation_operators,
+            vec![MutationType::BinaryOpMutation]
+        );
+        assert_eq!(replacements(&unrestricted).len(), 5);
+    }
+
+    #[test]
+    fn test_check_operator_replacements() {
+        let specs =
+            |mutations: Value| -> Vec<MutationSpec> { serde_json::from_value(mutations).unwrap() };
+        assert_eq!(
+            check_operator_replacements(&specs(json!([
+                "require-mutation",
+                {"operator": "binary-op-mutation", "replacements": ["+", "**"]},
+                {"operator": "unary-operator-mutation"},
+            ]))),
+            Ok(())
+        );
+        let err = check_operator_replacements(&specs(json!([
+            {"operator": "binary-op-mutation", "replacements": ["+", "&&"]},
+        ])))
+        .unwrap_err();
+        assert!(err.starts_with("`&&` is not a replacement of mutation operator `binary-op-mutation` (expected one of `+`, `-`"), "{}", err);
+        let err = check_operator_replacements(&specs(json!([
+            {"operator": "require-mutation", "replacements": ["true"]},
+        ])))
+        .unwrap_err();
+        assert!(err.contains("does not support `replacements`"), "{}", err);
+        let err = check_operator_replacements(&specs(json!([
+            {"operator": "binary-op-mutation", "replacements": []},
+        ])))
+        .unwrap_err();
+        assert!(err.contains("are empty"), "{}", err);
+        let err = check_operator_replacements(&specs(json!([
+            {"operator": "no-such-mutation", "replacements": ["+"]},
+        ])))
+        .unwrap_err();
+        assert!(err.contains("unrecognized"), "{}", err);
+        // Unknown fields are rejected
+        assert!(serde_json::from_value::<MutationSpec>(json!({
+            "operator": "binary-op-mutation",
+            "replacement": ["+"],
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_check_experimental_operators() {
+        let ops = |ops: &[&str]| -> Vec<String> { ops.iter().map(|s| s.to_string()).collect() };
+        assert!(MutationType::SwapArgumentsFunctionMutation.is_experimental());
+        assert!(!MutationType::default_mutation_operators()
+            .iter()
+            .any(|op| op.is_experimental()));
+
+        let stable = ops(&["binary-op-mutation", "require-mutation"]);
+        assert_eq!(check_experimental_operators(&stable, false), Ok(vec![]));
+        assert_eq!(check_experimental_operators(&stable, true), Ok(vec![]));
+
+        let requested = ops(&[
+            "binary-op-mutation",
+            "swap-arguments-function-mutation",
+            "swap-arguments-function-mutation",
+        ]);
+        let err = check_experimental_operators(&requested, false).unwrap_err();
+        assert!(err.contains("`swap-arguments-function-mutation`"));
+        assert!(err.contains("--allow_experimental"));
+        assert_eq!(
+            check_experimental_operators(&requested, true),
+            Ok(vec![MutationType::SwapArgumentsFunctionMutation])
+        );
+    }
+
+    #[test]
+    fn test_expand_mutation_operators() {
+        let dir = tempfile::tempdir().unwrap();
+        let ops = |ops: &[&str]| -> Vec<String> { ops.iter().map(|s| s.to_string()).collect() };
+        fs::write(
+            dir.path().join("ops.json"),
+            "[\"binary-op-mutation\", \"unary-operator-mutation\"]",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("ops.yaml"),
+            "- require-mutation\n- assignment-mutation\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("bad.json"),
+            "[\n  \"binary-op-mutation\",\n  \"no-such-mutation\"\n]",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("bad.yaml"),
+            "- binary-op-mutation\n- [nested]\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            expand_mutation_operators(&ops(&["@ops.json", "if-statement-mutation"]), dir.path()),
+            Ok(ops(&[
+                "binary-op-mutation",
+                "unary-operator-mutation",
+                "if-statement-mutation"
+            ]))
+        );
+        assert_eq!(
+            expand_mutation_operators(&ops(&["@ops.yaml"]), dir.path()),
+            Ok(ops(&["require-mutation", "assignment-mutation"]))
+        );
+
+        let err = expand_mutation_operators(&ops(&["@bad.json"]), dir.path()).unwrap_err();
+        assert!(
+            err.ends_with("unrecognized mutation operator `no-such-mutation` (entry 2, line 3)"),
+            "{}",
+            err
+        );
+        // Syntax and type errors carry serde's line and column
+        let err = expand_mutation_operators(&ops(&["@bad.yaml"]), dir.path()).unwrap_err();
+        assert!(err.contains("bad.yaml"), "{}", err);
+        assert!(err.contains("line 2 column 3"), "{}", err);
+        let err = expand_mutation_operators(&ops(&["@missing.json"]), dir.path()).unwrap_err();
+        assert!(
+            err.starts_with("Could not read mutation operators"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_called_libraries() {
+        let function = |name: &str, visibility: &str| json!({"nodeType": "FunctionDefinition", "name": name, "visibility": visibility});
+        let library = |id: i64, name: &str| {
+            json!({
+                "nodeType": "ContractDefinition",
+                "contractKind": "library",
+                "id": id,
+                "name": name,
+                "nodes": [function("internalF", "internal"), function("externalF", "external")]
+            })
+        };
+        let target = SolAST::new(json!({
+            "nodeType": "SourceUnit",
+            "absolutePath": "C.sol",
+            "nodes": [{
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "C",
+                "nodes": [{
+                    "nodeType": "UsingForDirective",
+                    "libraryName": {"nodeType": "IdentifierPath", "referencedDeclaration": 1}
+                }]
+            }]
+        }));
+        let units = vec![
+            target.clone(),
+            SolAST::new(json!({
+                "nodeType": "SourceUnit",
+                "absolutePath": "SafeCastLib.sol",
+                "nodes": [library(1, "SafeCastLib"), library(2, "Unused")]
+            })),
+            SolAST::new(json!({
+                "nodeType": "SourceUnit",
+                "absolutePath": "Unused.sol",
+                "nodes": [library(3, "AlsoUnused")]
+            })),
+        ];
+
+        let libraries = called_libraries(&target, &units);
+        assert_eq!(libraries.len(), 1);
+        let unit = &libraries[0];
+        assert_eq!(unit.get_string("absolutePath").unwrap(), "SafeCastLib.sol");
+        let contracts = unit.nodes();
+        assert_eq!(contracts.len(), 1);
+        assert_eq!(contracts[0].name().unwrap(), "SafeCastLib");
+        let functions: Vec<String> = contracts[0]
+            .nodes()
+            .iter()
+            .map(|f| f.name().unwrap())
+            .collect();
+        assert_eq!(functions, vec!["internalF"]);
+    }
+
+    #[test]
+    fn test_user_defined_operators() {
+        // `+` and `==` resolve to user-defined operators (`add` and `eq`),
+        // `*` to an operator bound in another file, and `1 + 2` is built-in
+        let source = "a + b; a * b; a == b; 1 + 2;";
+        let operand = |start: usize| -> Value {
+            json!({"nodeType": "Identifier", "src": format!("{}:1:0", start)})
+        };
+        let binop = |expr: &str, function: Option<i64>| -> Value {
+            let start = source.find(expr).unwrap();
+            let mut node = json!({
+                "nodeType": "BinaryOperation",
+                "operator": expr.split(' ').nth(1).unwrap(),
+                "src": src(source, expr),
+                "leftExpression": operand(start),
+                "rightExpression": operand(start + expr.len() - 1),
+            });
+            if let Some(function) = function {
+                node["function"] = json!(function);
+            }
+            node
+        };
+        let binding = |id: i64, name: &str, operator: &str| {
+            json!({
+                "definition": {"nodeType": "IdentifierPath", "name": name, "referencedDeclaration": id},
+                "operator": operator
+            })
+        };
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "src": format!("0:{}:0", source.len()),
+            "nodes": [
+                {
+                    "nodeType": "UsingForDirective",
+                    "functionList": [binding(10, "add", "+"), binding(11, "sub", "-"), binding(12, "eq", "==")],
+                    "typeName": {"nodeType": "UserDefinedTypeName", "referencedDeclaration": 1},
+                    "global": true
+                },
+                binop("a + b", Some(10)),
+                binop("a * b", Some(20)),
+                binop("a == b", Some(12)),
+                binop("1 + 2", None),
+            ]
+        });
+
+        let operations = user_defined_operations(&SolAST::new(ast.clone()));
+        assert_eq!(operations.len(), 3);
+        let at = |expr: &str| {
+            let start = source.find(expr).unwrap();
+            &operations[&(start + 1, start + expr.len() - 1)]
+        };
+        assert_eq!(
+            at("a + b"),
+            &UserDefinedOperation {
+                function: Some("add".into()),
+                replacements: vec!["-".into()]
+            }
+        );
+        assert_eq!(at("a * b").function, None);
+        assert!(at("a * b").replacements.is_empty());
+        assert_eq!(at("a == b").function, Some("eq".into()));
+        assert!(at("a == b").replacements.is_empty());
+
+        let conf = MutatorConf {
+            mutation_operators: vec![MutationType::BinaryOpMutation],
+            ..MutatorConf::default()
+        };
+        let mutants = mutate_source(source, "C.sol", SolAST::new(ast.clone()), &conf);
+        let user_defined: Vec<(&str, Option<&str>)> = mutants
+            .iter()
+            .filter(|m| m.start < source.find("1 + 2").unwrap())
+            .map(|m| (m.repl.as_str(), m.user_defined_operator.as_deref()))
+            .collect();
+        assert_eq!(user_defined, vec![("-", Some("add"))]);
+        // The built-in operator keeps all of its replacements, untagged
+        assert_eq!(mutants.len(), 6);
+        assert!(mutants[1..]
+            .iter()
+            .all(|m| m.user_defined_operator.is_none()));
+
+        // The estimate only counts the user-defined replacements
+        let estimate = estimate_source(source, "C.sol", SolAST::new(ast), &conf);
+        assert_eq!(estimate.operators["BinaryOpMutation"], 6);
+        assert_eq!(estimate.total, 6);
+    }
+
+    #[test]
+    fn test_contract_closure() {
+        let source = "contract Base {\n    function f() public returns (uint256) {\n        return 1 + 2;\n    }\n    function g() public virtual returns (uint256) {\n        return 3 + 4;\n    }\n}\ncontract Child is Base {\n    function g() public override returns (uint256) {\n        return 5 + 6;\n    }\n}\n";
+        let b = AstBuilder::new(source);
+        let function = |id: i64, expr: &str, base_functions: &[i64]| {
+            with_fields(
+                b.function(expr, b.binop(expr)),
+                json!({"id": id, "baseFunctions": base_functions}),
+            )
+        };
+        let contract = |id: i64, name: &str, chain: &[i64], nodes: Vec<Value>| {
+            with_fields(
+                b.contract(name, nodes),
+                json!({"id": id, "linearizedBaseContracts": chain}),
+            )
+        };
+        let ast = with_fields(
+            b.source_unit(vec![
+                contract(
+                    1,
+                    "Base",
+                    &[1],
+                    vec![function(2, "1 + 2", &[]), function(3, "3 + 4", &[])],
+                ),
+                contract(4, "Child", &[4, 1], vec![function(5, "5 + 6", &[3])]),
+            ]),
+            json!({"absolutePath": "C.sol"}),
+        );
+
+        // Only `f` is inherited: `Base.g` is overridden by `Child.g`
+        let inherited = inherited_contracts("Child", &[SolAST::new(ast.clone())]);
+        assert_eq!(inherited.len(), 1);
+        let contracts = inherited[0].nodes();
+        assert_eq!(contracts.len(), 1);
+        assert_eq!(contracts[0].name().unwrap(), "Base");
+        let functions: Vec<String> = contracts[0]
+            .nodes()
+            .iter()
+            .map(|f| f.name().unwrap())
+            .collect();
+        assert_eq!(functions, vec!["f"]);
+
+        let mutated = |contract_closure: bool| {
+            let conf = MutatorConf {
+                mutation_operators: vec![MutationType::BinaryOpMutation],
+                contract: Some("Child".into()),
+                contract_closure,
+                ..MutatorConf::default()
+            };
+            let mutants = mutate_source(source, "C.sol", SolAST::new(ast.clone()), &conf);
+            mutants
+                .iter()
+                .map(|m| {
+                    let orig =
+                        String::from_utf8(source.as_bytes()[m.start - 1..m.end + 1].to_vec());
+                    (
+                        orig.unwrap(),
+                        m.defining_contract.clone(),
+                        m.filter_contract.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Strict mode only mutates the functions defined in `Child`, untagged
+        let strict = mutated(false);
+        assert_eq!(strict.len(), 5);
+        assert!(strict.iter().all(|m| m == &("5 + 6".into(), None, None)));
+
+        // Closure mode also mutates the inherited `f`, but not `Base.g`
+        let closure = mutated(true);
+        assert_eq!(closure.len(), 10);
+        let child = (
+            "5 + 6".to_string(),
+            Some("Child".to_string()),
+            Some("Child".to_string()),
+        );
+        let base = (
+            "1 + 2".to_string(),
+            Some("Base".to_string()),
+            Some("Child".to_string()),
+        );
+        assert_eq!(closure.iter().filter(|m| **m == child).count(), 5);
+        assert_eq!(closure.iter().filter(|m| **m == base).count(), 5);
+    }
+
+    #[test]
+    fn test_only_mutate_reachable_functions() {
+        let source = "contract C {\n    function f() public returns (uint256) {\n        return g();\n    }\n    function g() internal returns (uint256) {\n        return 1 + 2;\n    }\n    function dead() internal returns (uint256) {\n        return 3 + 4;\n    }\n}\n";
+        let b = AstBuilder::new(source);
+        let function = |id: i64, returned: &str, expression: Value| {
+            with_fields(b.function(returned, expression), json!({"id": id}))
+        };
+        let call_g = json!({
+            "nodeType": "FunctionCall",
+            "src": b.src("g()"),
+            "arguments": [],
+            "expression": {"nodeType": "Identifier", "name": "g", "referencedDeclaration": 3, "src": b.src("g()")}
+        });
+        let ast = b.source_unit(vec![with_fields(
+            b.contract(
+                "C",
+                vec![
+                    function(2, "g()", call_g),
+                    function(3, "1 + 2", b.binop("1 + 2")),
+                    function(4, "3 + 4", b.binop("3 + 4")),
+                ],
+            ),
+            json!({"id": 1, "linearizedBaseContracts": [1]}),
+        )]);
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("C.sol"),
+            PathBuf::new(),
+            source.as_bytes().to_vec(),
+        ));
+        let mutated = |only_mutate_public_state_changing_paths: bool| {
+            let conf = MutatorConf {
+                mutation_operators: vec![MutationType::BinaryOpMutation],
+                only_mutate_public_state_changing_paths,
+                ..MutatorConf::default()
+            };
+            let mutator = Mutator::new(conf, vec![], Solc::new(String::new(), PathBuf::new()));
+            let mut stats = MutationStatistics::default();
+            let mutants = mutator.mutate_ast(SolAST::new(ast.clone()), source.clone(), &mut stats);
+            let origs: Vec<String> = mutants
+                .iter()
+                .map(|m| source.contents()[m.start - 1..m.end + 1].to_vec())
+                .map(|text| String::from_utf8(text).unwrap())
+                .collect();
+            (origs, stats)
+        };
+
+        let (origs, stats) = mutated(false);
+        assert_eq!(origs.iter().filter(|o| *o == "1 + 2").count(), 5);
+        assert_eq!(origs.iter().filter(|o| *o == "3 + 4").count(), 5);
+        assert_eq!(stats.unreachable_functions_skipped, 0);
+
+        let (origs, stats) = mutated(true);
+        assert_eq!(origs.iter().filter(|o| *o == "1 + 2").count(), 5);
+        assert_eq!(origs.iter().filter(|o| *o == "3 + 4").count(), 0);
+        assert_eq!(stats.unreachable_functions_skipped, 1);
+        assert_eq!(stats.unreachable_candidates_skipped, 1);
+    }
+
+    #[test]
+    fn test_exclude_functions_and_contracts() {
+        let source = "contract C {\n    function a() public returns (uint256) {\n        return 1 + 2;\n    }\n    function b() public returns (uint256) {\n        return 3 + 4;\n    }\n    function c() public returns (uint256) {\n        return 5 + 6;\n    }\n}\ncontract D {\n    function d() public returns (uint256) {\n        return 7 + 8;\n    }\n}\n";
+        let b = AstBuilder::new(source);
+        let function = |expr: &str| b.function(expr, b.binop(expr));
+        let ast = b.source_unit(vec![
+            b.contract(
+                "C",
+                vec![function("1 + 2"), function("3 + 4"), function("5 + 6")],
+            ),
+            b.contract("D", vec![function("7 + 8")]),
+        ]);
+        // The mutated expressions
+        let mutated = |conf: MutatorConf| -> Vec<String> {
+            let conf = MutatorConf {
+                mutation_operators: vec![MutationType::BinaryOpMutation],
+                ..conf
+            };
+            let mut exprs: Vec<String> =
+                mutate_source(source, "C.sol", SolAST::new(ast.clone()), &conf)
+                    .iter()
+                    .map(|m| source[m.start - 1..m.end + 1].to_string())
+                    .collect();
+            exprs.dedup();
+            exprs
+        };
+        let names = |names: &[&str]| Some(names.iter().map(|n| n.to_string()).collect());
+
+        let conf = MutatorConf {
+            contract: Some("C".into()),
+            exclude_functions: names(&["b"]),
+            ..MutatorConf::default()
+        };
+        assert_eq!(mutated(conf), vec!["1 + 2", "5 + 6"]);
+
+        // Exclusion wins over `funcs_to_mutate` and `contract`
+        let conf = MutatorConf {
+            funcs_to_mutate: names(&["a", "b"]),
+            exclude_functions: names(&["b"]),
+            ..MutatorConf::default()
+        };
+        assert_eq!(mutated(conf), vec!["1 + 2"]);
+        let conf = MutatorConf {
+            contract: Some("C".into()),
+            exclude_contracts: names(&["C"]),
+            ..MutatorConf::default()
+        };
+        assert!(mutated(conf).is_empty());
+        let conf = MutatorConf {
+            exclude_contracts: names(&["D"]),
+            ..MutatorConf::default()
+        };
+        assert_eq!(mutated(conf), vec!["1 + 2", "3 + 4", "5 + 6"]);
+
+        // The same exclusions, from a configuration file
+        let params: MutateParams = serde_json::from_value(json!({
+            "filename": "C.sol",
+            "exclude_functions": ["b"],
+            "exclude_contracts": ["D"],
+        }))
+        .unwrap();
+        assert_eq!(mutated(MutatorConf::from(&params)), vec!["1 + 2", "5 + 6"]);
+
+        // Line ranges keep the mutants starting on one of their lines, and
+        // combine with the function and contract filters
+        let lines = |ranges: &[&str]| Some(ranges.iter().map(|r| r.parse().unwrap()).collect());
+        let conf = MutatorConf {
+            source_lines: lines(&["5:7"]),
+            ..MutatorConf::default()
+        };
+        assert_eq!(mutated(conf), vec!["3 + 4"]);
+        let conf = MutatorConf {
+            funcs_to_mutate: names(&["a", "c"]),
+            source_lines: lines(&["1:6", "14"]),
+            ..MutatorConf::default()
+        };
+        assert_eq!(mutated(conf), vec!["1 + 2"]);
+        let params: MutateParams = serde_json::from_value(json!({
+            "filename": "C.sol",
+            "contract": "C",
+            "source_lines": ["6", "8:20"],
+        }))
+        .unwrap();
+        assert_eq!(mutated(MutatorConf::from(&params)), vec!["3 + 4", "5 + 6"]);
+    }
+
+    #[test]
+    fn test_parse_line_range() {
+        let range = |s: &str| s.parse::<LineRange>();
+        assert_eq!(range("3:5"), Ok(LineRange { start: 3, end: 5 }));
+        assert_eq!(range("7"), Ok(LineRange { start: 7, end: 7 }));
+        assert!(range("3:5").unwrap().contains(5));
+        assert!(!range("3:5").unwrap().contains(6));
+        assert!(range("0:5").is_err());
+        assert!(range("5:3").is_err());
+        assert!(range("3-5").is_err());
+        assert!(range("").is_err());
+        assert_eq!(LineRange { start: 3, end: 5 }.to_string(), "3:5");
+    }
+
+    #[test]
+    fn test_filter_name_warnings() {
+        let function =
+            |name: &str| json!({"nodeType": "FunctionDefinition", "name": name, "src": "0:0:0"});
+        let ast = SolAST::new(json!({
+            "nodeType": "SourceUnit",
+            "nodes": [
+                {"nodeType": "ContractDefinition", "name": "Vault", "nodes": [function("deposit"), function("withdraw"), function("")]},
+                {"nodeType": "ContractDefinition", "name": "Utils", "nodes": [function("getarray")]},
+                function("freeHelper"),
+            ]
+        }));
+        let fns = |names: &[&str]| -> Vec<String> { names.iter().map(|s| s.to_string()).collect() };
+
+        // Exact names and unrelated names are not reported
+        assert!(filter_name_warnings(&ast, Some("Vault"), Some(&fns(&["deposit"]))).is_empty());
+        assert!(filter_name_warnings(&ast, Some("Token"), Some(&fns(&["mint"]))).is_empty());
+
+        // Case mismatches
+        assert_eq!(
+            filter_name_warnings(&ast, Some("vault"), Some(&fns(&["Deposit"]))),
+            vec![
+                "--contract `vault` matches no contract in this file; did you mean `Vault`? (names are case-sensitive)",
+                "--functions `Deposit` matches no function in this file; did you mean `deposit`? (names are case-sensitive)",
+            ]
+        );
+
+        // Typos, suggesting only functions of the selected contract
+        assert_eq!(
+            filter_name_warnings(&ast, Some("Vault"), Some(&fns(&["withdrew", "getarry"]))),
+            vec![
+                "--functions `withdrew` matches no function in this file; did you mean `withdraw`?"
+            ]
+        );
+        assert_eq!(
+            filter_name_warnings(&ast, None, Some(&fns(&["getarry", "freeHelpr"]))),
+            vec![
+                "--functions `getarry` matches no function in this file; did you mean `getarray`?",
+                "--functions `freeHelpr` matches no function in this file; did you mean `freeHelper`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_qualified_functions() {
+        let source = "contract TokenA {\n    function transfer(address to, uint256 v) public returns (uint256) {\n        return 1 + 2;\n    }\n}\ncontract TokenB {\n    function transfer(address to, uint256 v) public returns (uint256) {\n        return 3 + 4;\n    }\n    function transfer(address to) public returns (uint256) {\n        return 5 + 6;\n    }\n}\n";
+        let b = AstBuilder::new(source);
+        let parameter = |type_string: &str| -> Value {
+            json!({"nodeType": "VariableDeclaration", "src": "0:0:0", "typeDescriptions": {"typeString": type_string}})
+        };
+        let function = |expr: &str, parameters: Vec<Value>| -> Value {
+            with_fields(
+                b.function(expr, b.binop(expr)),
+                json!({"parameters": {"nodeType": "ParameterList", "src": "0:0:0", "parameters": parameters}}),
+            )
+        };
+        let ast = SolAST::new(b.source_unit(vec![
+            b.contract(
+                "TokenA",
+                vec![function(
+                    "1 + 2",
+                    vec![parameter("address"), parameter("uint256")],
+                )],
+            ),
+            b.contract(
+                "TokenB",
+                vec![
+                    function("3 + 4", vec![parameter("address"), parameter("uint256")]),
+                    function("5 + 6", vec![parameter("address payable")]),
+                ],
+            ),
+        ]));
+        // The mutated expressions
+        let mutated = |functions: &[&str]| -> Vec<String> {
+            let conf = MutatorConf {
+                mutation_operators: vec![MutationType::BinaryOpMutation],
+                funcs_to_mutate: Some(functions.iter().map(|f| f.to_string()).collect()),
+                ..MutatorConf::default()
+            };
+            let mut exprs: Vec<String> = mutate_source(source, "C.sol", ast.clone(), &conf)
+                .iter()
+                .map(|m| source[m.start - 1..m.end + 1].to_string())
+                .collect();
+            exprs.dedup();
+            exprs
+        };
+
+        // A bare name selects the functions of every contract
+        assert_eq!(mutated(&["transfer"]), vec!["1 + 2", "3 + 4", "5 + 6"]);
+        // A contract-qualified name selects all overloads of one contract
+        assert_eq!(mutated(&["TokenB.transfer"]), vec!["3 + 4", "5 + 6"]);
+        assert_eq!(mutated(&["TokenA.transfer"]), vec!["1 + 2"]);
+        // A signature selects one overload, in one or every contract
+        assert_eq!(
+            mutated(&["TokenB.transfer(address, uint256)"]),
+            vec!["3 + 4"]
+        );
+        assert_eq!(
+            mutated(&["transfer(address,uint256)"]),
+            vec!["1 + 2", "3 + 4"]
+        );
+        assert_eq!(mutated(&["TokenB.transfer(address)"]), vec!["5 + 6"]);
+        assert_eq!(
+            mutated(&["TokenA.transfer", "TokenB.transfer(address)"]),
+            vec!["1 + 2", "5 + 6"]
+        );
+        assert!(mutated(&["TokenA.transfer(address)"]).is_empty());
+        assert!(mutated(&["TokenC.transfer"]).is_empty());
+
+        // Only qualifiers naming a contract that is not defined are reported
+        let fns = |names: &[&str]| -> Vec<String> { names.iter().map(|s| s.to_string()).collect() };
+        assert_eq!(
+            qualified_function_warnings(
+                &ast,
+                Some(&fns(&["transfer", "TokenB.transfer", "TokenC.transfer(address)"]))
+            ),
+            vec!["--functions `TokenC.transfer(address)` names contract `TokenC`, which is not defined in this file"]
+        );
+        assert!(filter_name_warnings(&ast, None, Some(&fns(&["TokenC.transfer"]))).is_empty());
+    }
+
+    #[test]
+    fn test_parse_function_filter() {
+        let filter =
+            |contract: Option<&str>, name: &str, parameters: Option<&[&str]>| FunctionFilter {
+                contract: contract.map(|c| c.to_string()),
+                name: name.to_string(),
+                parameters: parameters.map(|p| p.iter().map(|t| t.to_string()).collect()),
+            };
+        assert_eq!(FunctionFilter::parse("f"), filter(None, "f", None));
+        assert!(!FunctionFilter::parse("f").is_qualified());
+        assert_eq!(FunctionFilter::parse("C.f"), filter(Some("C"), "f", None));
+        assert_eq!(FunctionFilter::parse("f()"), filter(None, "f", Some(&[])));
+        // Data locations and keywords are dropped, as in the AST's types
+        assert_eq!(
+            FunctionFilter::parse("C.f(string memory, uint256[] calldata, struct S.T)"),
+            filter(Some("C"), "f", Some(&["string", "uint256[]", "S.T"]))
+        );
+        let types = parameter_types(&json!({"parameters": {"parameters": [
+            {"typeDescriptions": {"typeString": "string memory"}},
+            {"typeDescriptions": {"typeString": "contract IERC20"}},
+            {"typeDescriptions": {"typeString": "struct S.T storage pointer"}},
+        ]}}));
+        assert_eq!(types, vec!["string", "IERC20", "S.T"]);
+        assert!(FunctionFilter::parse("C.f(string,IERC20,S.T)").matches(Some("C"), "f", &types));
+        assert!(!FunctionFilter::parse("C.f(string)").matches(Some("C"), "f", &types));
+        assert!(!FunctionFilter::parse("C.f").matches(None, "f", &types));
+    }
+
+    #[test]
+    fn test_reachable_functions() {
+        let function = |id: i64, kind: &str, visibility: &str, body: Value| json!({"nodeType": "FunctionDefinition", "id": id, "kind": kind, "visibility": visibility, "body": body});
+        let reference = |id: i64| json!({"nodeType": "Identifier", "referencedDeclaration": id});
+        let ast = SolAST::new(json!({
+            "nodeType": "SourceUnit",
+            "nodes": [
+                // Only called by the dead `B.helper`
+                function(1, "freeFunction", "internal", json!([])),
+                // Called by a file-level constant
+                function(2, "freeFunction", "internal", json!([])),
+                {"nodeType": "VariableDeclaration", "id": 3, "value": reference(2)},
+                {
+                    "nodeType": "ContractDefinition",
+                    "id": 10,
+                    "name": "B",
+                    "linearizedBaseContracts": [10],
+                    "nodes": [
+                        // Virtual, overridden by `C.hook`
+                        function(11, "function", "internal", json!([])),
+                        function(12, "function", "internal", reference(1)),
+                        function(13, "constructor", "internal", reference(11)),
+                        {"nodeType": "ModifierDefinition", "id": 14, "visibility": "internal", "body": reference(15)},
+                        // Only used by the modifier
+                        function(15, "function", "private", json!([])),
+                    ]
+                },
+                {
+                    "nodeType": "ContractDefinition",
+                    "id": 20,
+                    "name": "C",
+                    "linearizedBaseContracts": [20, 10],
+                    "nodes": [
+                        {"nodeType": "FunctionDefinition", "id": 21, "kind": "function", "visibility": "internal", "baseFunctions": [11], "body": reference(22)},
+                        // Used as a function pointer by a state variable
+                        function(22, "function", "internal", json!([])),
+                        {"nodeType": "VariableDeclaration", "id": 23, "value": reference(24)},
+                        function(24, "function", "private", json!([])),
+                        function(25, "function", "external", json!({"modifiers": [reference(14)]})),
+                        function(26, "function", "internal", json!([])),
+                    ]
+                },
+                {
+                    "nodeType": "ContractDefinition",
+                    "id": 30,
+                    "name": "D",
+                    "linearizedBaseContracts": [30],
+                    "nodes": [
+                        function(31, "function", "public", reference(26)),
+                        {"nodeType": "VariableDeclaration", "id": 32, "value": reference(12)},
+                    ]
+                }
+            ]
+        }));
+        let sorted = |reachable: std::collections::HashSet<i64>| {
+            let mut reachable: Vec<i64> = reachable.into_iter().collect();
+            reachable.sort();
+            reachable
+        };
+        assert_eq!(
+            sorted(reachable_functions(&ast, Some("C"))),
+            vec![2, 11, 13, 14, 15, 21, 22, 24, 25]
+        );
+        assert_eq!(
+            sorted(reachable_functions(&ast, None)),
+            vec![1, 2, 11, 12, 13, 14, 15, 21, 22, 24, 25, 26, 31]
+        );
+    }
+
+    #[test]
+    fn test_mutate_deeply_nested_expression() {
+        // `1 + 1 + ... + 1` parses as a left-nested chain of binary
+        // operations, one level per term
+        let terms = 4000;
+        let body = vec!["1"; terms].join(" + ");
+        let source = format!("contract C {{ uint256 x = {}; }}\n", body);
+        let start = source.find(&body).unwrap();
+        let literal = |i: usize| {
+            format!(
+                r#"{{"nodeType":"Literal","kind":"number","value":"1","src":"{}:1:0"}}"#,
+                start + 4 * i
+            )
+        };
+        let mut expr = literal(0);
+        for i in 1..terms {
+            expr = format!(
+                r#"{{"nodeType":"BinaryOperation","operator":"+","src":"{}:{}:0","leftExpression":{},"rightExpression":{}}}"#,
+                start,
+                4 * i + 1,
+                expr,
+                literal(i)
+            );
+        }
+        let ast = format!(
+            r#"{{"nodeType":"SourceUnit","src":"0:{}:0","nodes":[{{"nodeType":"ContractDefinition","contractKind":"contract","name":"C","src":"0:{}:0","nodes":[{{"nodeType":"VariableDeclaration","name":"x","src":"{}:{}:0","value":{}}}]}}]}}"#,
+            source.len(),
+            source.len() - 1,
+            start - 12,
+            body.len() + 12,
+            expr
+        );
+        assert!(serde_json::from_str::<Value>(&ast).is_err());
+        let ast = parse_json_unbounded(ast.as_bytes()).unwrap();
+
+        // The outermost operation is nested 5 levels deep, and the operations
+        // nested more than `max_ast_depth` levels deep are not mutated
+        let conf = MutatorConf {
+            mutation_operators: vec![MutationType::BinaryOpMutation],
+            ..MutatorConf::default()
+        };
+        let mutants = mutate_source(&source, "virtual/C.sol", SolAST::new(ast), &conf);
+        assert_eq!(mutants.len(), 5 * (conf.max_ast_depth - 4));
+        assert_eq!(mutants[0].orig, " + ");
+        assert_eq!(mutants[0].start, start + 4 * (terms - 1) - 3);
+        assert_eq!(
+            mutants.last().unwrap().start,
+            mutants[0].start - 4 * (conf.max_ast_depth - 5)
+        );
+    }
+
+    #[test]
+    fn test_estimate_matches_mutants() {
+        // Every operator but `BoundaryLiteralMutation`, whose mutants that
+        // duplicate other operators' are only dropped when mutating
+        let operators: Vec<MutationType> = MutationType::value_variants()
+            .iter()
+            .filter(|op| **op != MutationType::BoundaryLiteralMutation)
+            .copied()
+            .collect();
+        let outdir = tempfile::tempdir().unwrap();
+        for file in [
+            "benchmarks/10Power/TenPower.sol",
+            "benchmarks/AssignmentMutation/AssignmentMutation.sol",
+            "benchmarks/BinaryOpMutation/BinaryOpMutation.sol",
+            "benchmarks/DeleteExpressionMutation/DeleteExpressionMutation.sol",
+            "benchmarks/ElimDelegateMutation/ElimDelegateMutation.sol",
+            "benchmarks/FunctionCallMutation/FunctionCallMutation.sol",
+            "benchmarks/IfStatementMutation/IfStatementMutation.sol",
+            "benchmarks/MultipleContracts/C.sol",
+            "benchmarks/RequireMutation/RequireMutation.sol",
+            "benchmarks/SwapArgumentsOperatorMutation/SwapArgumentsOperatorMutation.sol",
+            "benchmarks/UnaryOperatorMutation/UnaryOperatorMutation.sol",
+        ] {
+            let params: MutateParams = serde_json::from_value(json!({
+                "filename": file,
+                "outdir": outdir.path(),
+            }))
+            .unwrap();
+            let mut mutator = Mutator::try_from(&params).unwrap();
+            mutator.conf.mutation_operators = operators.clone();
+            let estimate = mutator.estimate().unwrap().remove(0);
+            let mutants = mutator.mutate().unwrap();
+            for (op, count) in estimate.operators.iter() {
+                let generated = mutants.iter().filter(|m| m.op.to_string() == *op).count();
+                assert_eq!(*count, generated, "{} mutants of {}", op, file);
+            }
+            assert_eq!(estimate.total, mutants.len());
+        }
+    }
+
+    #[test]
+    fn test_project_root_layout() {
+        let outdir = tempfile::tempdir().unwrap();
+        let root = "benchmarks/FoundryProject";
+        let vault = "benchmarks/FoundryProject/src/Vault.sol";
+        let params: MutateParams = serde_json::from_value(json!({
+            "filename": vault,
+            "outdir": outdir.path(),
+            "project_root": root,
+        }))
+        .unwrap();
+        let mutator = Mutator::try_from(&params).unwrap();
+        // The project root is the base path, `src/` and `lib/` are include
+        // paths, and the remappings of `remappings.txt` are resolved
+        // against the root
+        assert_eq!(
+            mutator.solc.source_unit_name(Path::new(vault)).unwrap(),
+            "src/Vault.sol"
+        );
+        assert!(mutator
+            .solc
+            .resolve_source_unit_name("openzeppelin-contracts/contracts/utils/math/Math.sol")
+            .is_some());
+        let remappings = mutator.solc.remappings();
+        assert_eq!(remappings.len(), 1);
+        let vault_root = Path::new(root).canonicalize().unwrap();
+        assert_eq!(
+            remappings[0],
+            format!(
+                "@openzeppelin={}",
+                vault_root.join("lib/openzeppelin-contracts").display()
+            )
+        );
+
+        // An explicit base path and remapping take precedence
+        let params: MutateParams = serde_json::from_value(json!({
+            "filename": vault,
+            "outdir": outdir.path(),
+            "project_root": root,
+            "solc_base_path": "benchmarks",
+            "solc_remappings": ["@openzeppelin=/lib/oz"],
+        }))
+        .unwrap();
+        let mutator = Mutator::try_from(&params).unwrap();
+        assert_eq!(
+            mutator.solc.source_unit_name(Path::new(vault)).unwrap(),
+            "FoundryProject/src/Vault.sol"
+        );
+        assert_eq!(mutator.solc.remappings(), ["@openzeppelin=/lib/oz"]);
+
+        // Without a project root, solc gets no base path or remappings
+        let params: MutateParams = serde_json::from_value(json!({
+            "filename": vault,
+            "outdir": outdir.path(),
+        }))
+        .unwrap();
+        let mutator = Mutator::try_from(&params).unwrap();
+        assert!(Path::new(&mutator.solc.source_unit_name(Path::new(vault)).unwrap()).is_absolute());
+        assert!(mutator.solc.remappings().is_empty());
+    }
+
+    #[test]
+    fn test_mutator_errors() {
+        let outdir = tempfile::tempdir().unwrap();
+        let mutator = |params: Value| {
+            let params: MutateParams = serde_json::from_value(params).unwrap();
+            Mutator::try_from(&params).map(|_| ())
+        };
+        // A filename that does not exist, with and without a sourceroot
+        let missing = json!({"filename": "benchmarks/NoSuchFile.sol", "outdir": outdir.path()});
+        assert!(matches!(
+            mutator(missing),
+            Err(GambitError::FileResolution(_))
+        ));
+        let missing = json!({
+            "filename": "benchmarks/NoSuchFile.sol",
+            "sourceroot": ".",
+            "outdir": outdir.path(),
+        });
+        assert!(matches!(
+            mutator(missing),
+            Err(GambitError::FileResolution(_))
+        ));
+        // A filename outside of the current working directory
+        let outside = json!({"filename": outdir.path(), "outdir": outdir.path()});
+        assert!(matches!(
+            mutator(outside),
+            Err(GambitError::FileResolution(_))
+        ));
+        // Solc settings that cannot be read
+        let settings = json!({
+            "filename": "benchmarks/10Power/TenPower.sol",
+            "outdir": outdir.path(),
+            "solc_settings": "benchmarks/no_such_settings.json",
+        });
+        assert!(matches!(
+            mutator(settings),
+            Err(GambitError::Configuration(_))
+        ));
+    }
+
+    #[test]
+    fn test_locate_source() {
+        // A contract of many functions `fI`, each returning
+        // `1 + 2 - 3 * 4 / 5`
+        let num_functions = 300;
+        let mut source = "contract C {\n".to_string();
+        let mut functions = vec![];
+        let binop = |op: &str, start: usize, len: usize, left: Value, right: Value| {
+            json!({"nodeType": "BinaryOperation", "operator": op, "src": format!("{}:{}:0", start, len),
+                   "leftExpression": left, "rightExpression": right})
+        };
+        let literal = |value: usize, start: usize| json!({"nodeType": "Literal", "kind": "number", "value": value.to_string(), "src": format!("{}:1:0", start)});
+        for i in 0..num_functions {
+            let start = source.len();
+            let header = format!(
+                "    function f{}() public pure returns (uint256) {{ return ",
+                i
+            );
+            let function = format!("{}1 + 2 - 3 * 4 / 5; }}", header);
+            let e = start + header.len();
+            let expr = binop(
+                "-",
+                e,
+                17,
+                binop("+", e, 5, literal(1, e), literal(2, e + 4)),
+                binop(
+                    "/",
+                    e + 8,
+                    9,
+                    binop("*", e + 8, 5, literal(3, e + 8), literal(4, e + 12)),
+                    literal(5, e + 16),
+                ),
+            );
+            functions.push(json!({
+                "nodeType": "FunctionDefinition",
+                "kind": "function",
+                "name": format!("f{}", i),
+                "stateMutability": "pure",
+                "src": format!("{}:{}:0", start, function.len()),
+                "body": {
+                    "nodeType": "Block",
+                    "src": format!("{}:{}:0", e - 9, function.len() - header.len() + 9),
+                    "statements": [{
+                        "nodeType": "Return",
+                        "src": format!("{}:18:0", e - 7),
+                        "expression": expr,
+                    }]
+                }
+            }));
+            source.push_str(&function);
+            source.push('\n');
+        }
+        source.push_str("}\n");
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "src": format!("0:{}:0", source.len()),
+            "nodes": [{
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "C",
+                "src": format!("0:{}:0", source.len() - 1),
+                "nodes": functions,
+            }]
+        });
+        let conf = MutatorConf {
+            mutation_operators: MutationType::default_mutation_operators(),
+            ..MutatorConf::default()
+        };
+
+        let now = Instant::now();
+        let candidates = locate_source(&source, "C.sol", SolAST::new(ast.clone()), &conf);
+        let locate_time = now.elapsed();
+        let now = Instant::now();
+        let mutants = mutate_source(&source, "C.sol", SolAST::new(ast.clone()), &conf);
+        let mutate_time = now.elapsed();
+        let estimate = estimate_source(&source, "C.sol", SolAST::new(ast), &conf);
+        assert!(
+            locate_time < mutate_time,
+            "locating took {:?}, mutating took {:?}",
+            locate_time,
+            mutate_time
+        );
+
+        // Every candidate is reported at the start of its node, and the
+        // mutants of each candidate are generated in the order of the
+        // candidates, within their spans
+        let source = Source::from_contents(PathBuf::from("C.sol"), PathBuf::new(), source.into());
+        for c in candidates.iter() {
+            assert_eq!(c.file, "C.sol");
+            assert_eq!(source.get_line_column(c.span.0).unwrap(), (c.line, c.col));
+        }
+        let mut remaining = candidates.iter().peekable();
+        for mutant in mutants.iter() {
+            let produced_by = |c: &&MutationCandidate| {
+                c.op == mutant.op && c.span.0 <= mutant.start && mutant.end <= c.span.1
+            };
+            // A candidate can produce several mutants, or none at all
+            while remaining.next_if(|c| !produced_by(c)).is_some() {}
+            assert!(
+                remaining.peek().is_some(),
+                "no candidate for {:?} mutant at {}",
+                mutant.op,
+                mutant.start
+            );
+        }
+        assert!(candidates.len() >= num_functions * 4);
+        assert_eq!(estimate.total, mutants.len());
     }
 }
@@ -0,0 +1,1379 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    error, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use flate2::read::GzDecoder;
+
+use schemars::{schema_for, JsonSchema};
+use serde::{
+    de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use serde_json::Value;
+
+use crate::{
+    ExportFormat, GambitError, LineShift, Mutant, MutantAstContext, MutantEdit, MutateParams,
+    ValidateResultsParams,
+};
+
+/// The version of the `gambit_results.json` format written by this version of
+/// Gambit. This is bumped whenever a field is removed or changes meaning.
+///
+/// Version 2 moved the mutant entries, which were the top-level array of
+/// version 1, into the `mutants` field of a top-level object (see
+/// `GambitResults`).
+pub const RESULTS_FORMAT_VERSION: u32 = 2;
+
+/// The contents of `gambit_results.json`: the mutants written to an output
+/// directory, and the files that were mutated to generate them. The JSON
+/// Schema for this format (see `results_schema()`) is published at
+/// `resources/gambit_results.schema.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GambitResults {
+    /// The version of the results format (see `RESULTS_FORMAT_VERSION`)
+    pub format_version: u32,
+
+    /// The mutated files, one for each mutation session (i.e., configuration
+    /// entry), in the order they were mutated
+    pub files: Vec<ResultsFile>,
+
+    /// Every mutant, in order of their ids
+    pub mutants: Vec<MutantEntry>,
+}
+
+/// A file of `gambit_results.json`: one mutation session of a single file,
+/// with the filters and operators it applied and the mutants it generated.
+/// Two configuration entries mutating the same file are two sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ResultsFile {
+    /// The path of the mutated file, relative to `sourceroot`
+    pub original: String,
+
+    /// The source root that `original` is relative to
+    pub sourceroot: String,
+
+    /// The mutation operators that were applied, sorted by name
+    pub operators: Vec<String>,
+
+    /// The functions that mutation was restricted to (`--functions`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub functions: Option<Vec<String>>,
+
+    /// The contract that mutation was restricted to (`--contract`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+
+    /// The path to the solc binary used for this file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solc: Option<String>,
+
+    /// The version of the solc binary used for this file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solc_version: Option<String>,
+
+    /// Statistics about the mutants of this file
+    pub statistics: FileStatistics,
+
+    /// The ids of the entries of `mutants` generated for this file. These
+    /// include the mutants of libraries called by the file (see
+    /// `target_file`).
+    pub mutant_ids: Vec<String>,
+}
+
+/// Statistics about the mutants of a `ResultsFile`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FileStatistics {
+    /// The number of mutants written for the file
+    pub mutants: usize,
+
+    /// The number of those mutants that were validated
+    pub validated: usize,
+
+    /// The number of those mutants that could not be validated because solc
+    /// kept failing for reasons unrelated to them
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub validation_errors: usize,
+
+    /// The number of mutants requested with `num_mutants`, if any. Fewer
+    /// mutants are written when fewer valid mutants exist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested: Option<usize>,
+
+    /// The number of mutants that were compiled to validate them while
+    /// sampling `num_mutants` mutants, if the mutants were sampled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_attempts: Option<usize>,
+
+    /// How the mutants were validated, stage by stage, if they were
+    /// validated with a `--validation_mode` other than `full`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_stages: Option<ValidationStages>,
+
+    /// How long it took to validate the mutants of the file, if any mutant
+    /// was compiled to validate it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_times: Option<ValidationTimes>,
+}
+
+/// The number of mutants listed in `ValidationTimes::slowest`
+pub const SLOWEST_MUTANTS_LISTED: usize = 10;
+
+/// The wall-clock time it took to validate the mutants of a file, to find the
+/// mutants (and the operators generating them) that dominate validation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ValidationTimes {
+    /// The slowest mutants to validate, slowest first, including those that
+    /// were found invalid. At most `SLOWEST_MUTANTS_LISTED` are listed
+    pub slowest: Vec<SlowMutant>,
+
+    /// The validation time of the mutants of each mutation operator
+    pub operators: BTreeMap<String, OperatorValidationTimes>,
+}
+
+/// A mutant listed in `ValidationTimes::slowest`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SlowMutant {
+    /// The mutation operator that generated the mutant
+    pub operator: String,
+
+    /// The line of the mutation in the original file
+    pub line: usize,
+
+    /// The column of the mutation in the original file
+    pub col: usize,
+
+    /// The source text that the mutation replaced
+    pub orig: String,
+
+    /// The source text that replaced `orig`
+    pub repl: String,
+
+    /// Whether the mutant compiled
+    pub valid: bool,
+
+    /// The time it took to validate the mutant, in milliseconds
+    pub validation_ms: u64,
+}
+
+/// The validation time of the mutants of a mutation operator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OperatorValidationTimes {
+    /// The number of mutants of the operator that were validated
+    pub validated: usize,
+
+    /// The mean time it took to validate them, in milliseconds
+    pub mean_ms: u64,
+
+    /// The median time it took to validate them, in milliseconds
+    pub median_ms: u64,
+}
+
+impl ValidationTimes {
+    /// Aggregate the validation times of `mutants`: each validated mutant,
+    /// whether it compiled, and the time it took to validate it
+    pub fn of<'a>(
+        mutants: impl IntoIterator<Item = (&'a Mutant, bool, Duration)>,
+    ) -> ValidationTimes {
+        let mut mutants: Vec<(&Mutant, bool, Duration)> = mutants.into_iter().collect();
+        // Mutants that took as long keep the order they were generated in
+        mutants.sort_by_key(|(_, _, time)| std::cmp::Reverse(*time));
+        let slowest = mutants
+            .iter()
+            .take(SLOWEST_MUTANTS_LISTED)
+            .map(|(mutant, valid, time)| {
+                let (line, col) = mutant.get_line_column().unwrap_or((0, 0));
+                SlowMutant {
+                    operator: mutant.op.to_string(),
+                    line,
+                    col,
+                    orig: mutant.orig.clone(),
+                    repl: mutant.repl.clone(),
+                    valid: *valid,
+                    validation_ms: time.as_millis() as u64,
+                }
+            })
+            .collect();
+
+        let mut times: BTreeMap<String, Vec<Duration>> = BTreeMap::new();
+        for (mutant, _, time) in mutants.iter() {
+            times.entry(mutant.op.to_string()).or_default().push(*time);
+        }
+        let operators = times
+            .into_iter()
+            .map(|(operator, times)| {
+                // `times` are sorted, slowest first
+                let n = times.len();
+                let median = if n % 2 == 1 {
+                    times[n / 2]
+                } else {
+                    (times[n / 2 - 1] + times[n / 2]) / 2
+                };
+                let mean = times.iter().sum::<Duration>() / n as u32;
+                let times = OperatorValidationTimes {
+                    validated: n,
+                    mean_ms: mean.as_millis() as u64,
+                    median_ms: median.as_millis() as u64,
+                };
+                (operator, times)
+            })
+            .collect();
+        ValidationTimes { slowest, operators }
+    }
+}
+
+/// The stages of validating the mutants of a file (see `--validation_mode`):
+/// how many mutants each stage checked and rejected, and the time it took
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ValidationStages {
+    /// The number of mutants that were checked for syntax errors (with
+    /// `solc --stop-after parsing`)
+    pub syntax_checks: usize,
+
+    /// The number of those mutants that were rejected by the syntax check
+    pub rejected_by_syntax_check: usize,
+
+    /// The time spent checking syntax, in milliseconds
+    pub syntax_check_ms: u64,
+
+    /// The number of mutants that were fully compiled
+    pub compilations: usize,
+
+    /// The number of those mutants that failed to compile
+    pub rejected_by_compilation: usize,
+
+    /// The time spent compiling, in milliseconds
+    pub compilation_ms: u64,
+}
+
+impl ValidationStages {
+    /// Add the checks and rejections of `other` to `self`
+    pub fn merge(&mut self, other: &ValidationStages) {
+        self.syntax_checks += other.syntax_checks;
+        self.rejected_by_syntax_check += other.rejected_by_syntax_check;
+        self.syntax_check_ms += other.syntax_check_ms;
+        self.compilations += other.compilations;
+        self.rejected_by_compilation += other.rejected_by_compilation;
+        self.compilation_ms += other.compilation_ms;
+    }
+}
+
+impl FileStatistics {
+    /// Compute the statistics of `mutants`
+    pub fn of<'a>(mutants: impl IntoIterator<Item = &'a MutantEntry>) -> FileStatistics {
+        let mut statistics = FileStatistics::default();
+        for mutant in mutants {
+            statistics.mutants += 1;
+            statistics.validated += mutant.validated as usize;
+            statistics.validation_errors += mutant.validation_error as usize;
+        }
+        statistics
+    }
+}
+
+impl GambitResults {
+    /// Group mutant entries without session information (e.g., those of a
+    /// format version 1 file) into results with one file for each original
+    /// file, applying the operators found among its mutants
+    pub fn from_entries(format_version: u32, mutants: Vec<MutantEntry>) -> GambitResults {
+        let mut files: Vec<ResultsFile> = vec![];
+        for mutant in mutants.iter() {
+            let i = match files
+                .iter()
+                .position(|f| f.original == mutant.original && f.sourceroot == mutant.sourceroot)
+            {
+                Some(i) => i,
+                None => {
+                    files.push(ResultsFile {
+                        original: mutant.original.clone(),
+                        sourceroot: mutant.sourceroot.clone(),
+                        operators: vec![],
+                        functions: None,
+                        contract: None,
+                        solc: mutant.solc.clone(),
+                        solc_version: mutant.solc_version.clone(),
+                        statistics: FileStatistics::default(),
+                        mutant_ids: vec![],
+                    });
+                    files.len() - 1
+                }
+            };
+            let file = &mut files[i];
+            if !file.operators.contains(&mutant.description) {
+                file.operators.push(mutant.description.clone());
+            }
+            file.mutant_ids.push(mutant.id.clone());
+        }
+        for file in files.iter_mut() {
+            file.operators.sort();
+            file.statistics =
+                FileStatistics::of(mutants.iter().filter(|m| file.mutant_ids.contains(&m.id)));
+        }
+        GambitResults {
+            format_version,
+            files,
+            mutants,
+        }
+    }
+}
+
+/// Parse the contents of a `gambit_results.json` file. This reads both the
+/// current layout and the top-level array of format version 1, whose mutants
+/// are grouped into files with `GambitResults::from_entries()`.
+pub fn parse_results(json: &str) -> Result<GambitResults, serde_json::Error> {
+    match serde_json::from_str(json)? {
+        Value::Array(entries) => {
+            let entries: Vec<MutantEntry> = serde_json::from_value(Value::Array(entries))?;
+            let format_version = entries.first().map_or(1, |e| e.format_version);
+            Ok(GambitResults::from_entries(format_version, entries))
+        }
+        results => serde_json::from_value(results),
+    }
+}
+
+/// Call `f` on each mutant entry of a `gambit_results.json` file, in either
+/// layout (see `parse_results()`), as the entries are read from `reader`. Only
+/// a single entry is held in memory at a time. This returns `false` if the
+/// results have no `mutants` array.
+pub fn for_each_results_entry<R: io::Read>(
+    reader: R,
+    f: impl FnMut(Value),
+) -> Result<bool, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_reader(io::BufReader::new(reader));
+    let found = deserializer.deserialize_any(ResultsVisitor(f))?;
+    deserializer.end()?;
+    Ok(found)
+}
+
+/// Find the entry of mutant `mid` in the `gambit_results.json` of `outdir`
+pub fn find_results_entry(outdir: &Path, mid: &str) -> Result<MutantEntry, Box<dyn error::Error>> {
+    let path = outdir.join("gambit_results.json");
+    let file = fs::File::open(&path).map_err(|e| {
+        GambitError::FileResolution(format!("Could not read {}: {}", path.display(), e))
+    })?;
+    let mut found = None;
+    for_each_results_entry(file, |entry| {
+        if found.is_none() && entry["id"] == mid {
+            found = Some(entry);
+        }
+    })?;
+    let entry = found.ok_or_else(|| {
+        GambitError::Configuration(format!("No mutant `{}` in {}", mid, path.display()))
+    })?;
+    Ok(serde_json::from_value(entry)?)
+}
+
+/// Visits the top level of `gambit_results.json` for
+/// `for_each_results_entry()`
+struct ResultsVisitor<F>(F);
+
+impl<'de, F: FnMut(Value)> Visitor<'de> for ResultsVisitor<F> {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "an object with a `mutants` array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, seq: A) -> Result<bool, A::Error> {
+        EntriesVisitor(&mut self.0).visit_seq(seq)?;
+        Ok(true)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<bool, A::Error> {
+        let mut found = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "mutants" {
+                map.next_value_seed(EntriesVisitor(&mut self.0))?;
+                found = true;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// Visits an array of mutant entries, calling a function on each entry
+struct EntriesVisitor<'a, F>(&'a mut F);
+
+impl<'de, F: FnMut(Value)> DeserializeSeed<'de> for EntriesVisitor<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(Value)> Visitor<'de> for EntriesVisitor<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "an array of mutant entries")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(entry) = seq.next_element::<Value>()? {
+            (self.0)(entry);
+        }
+        Ok(())
+    }
+}
+
+fn default_comment_inserted() -> bool {
+    true
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// An entry of `gambit_results.json`, describing a single mutant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MutantEntry {
+    /// The version of the results format (see `RESULTS_FORMAT_VERSION`)
+    pub format_version: u32,
+
+    /// The mutant id: either a positive integer or a hex content hash
+    pub id: String,
+
+    /// The path to the exported mutant, relative to the output directory
+    pub name: String,
+
+    /// The mutation operator that generated this mutant
+    pub description: String,
+
+    /// A unified diff from the original file to the mutant. This is empty
+    /// when the diff is stored in `diff_file` instead
+    pub diff: String,
+
+    /// The path of a file holding the diff, relative to the output directory,
+    /// for diffs larger than `--max_inline_diff_bytes` or stored with
+    /// `--compress_diffs`. Files whose path ends in `.gz` are gzip-compressed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_file: Option<String>,
+
+    /// The source root that `original` is relative to
+    pub sourceroot: String,
+
+    /// The path of the original file, relative to `sourceroot`
+    pub original: String,
+
+    /// The source text that the mutation replaced
+    #[serde(default)]
+    pub orig: String,
+
+    /// The source text that replaced `orig`
+    #[serde(default)]
+    pub repl: String,
+
+    /// The edits of a mutant that edits several regions of the original
+    /// file, in order. `orig` and `repl` then cover the whole edited region,
+    /// from the start of the first edit to the end of the last one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edits: Vec<MutantEdit>,
+
+    /// The (1-indexed) line of the original file where the mutation starts
+    #[schemars(range(min = 1))]
+    pub line: usize,
+
+    /// The (1-indexed) column of the original file where the mutation starts
+    #[schemars(range(min = 1))]
+    pub col: usize,
+
+    /// Mutants of the same file whose spans intersect share a span group:
+    /// they target the same code, and usually stand or fall together. Groups
+    /// are numbered from 1 in order of their first span (see `span_groups()`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub span_group: Option<usize>,
+
+    /// How lines of the exported mutant map back to lines of the original
+    /// file: original lines after `after_line` appear `delta` lines later in
+    /// the mutant (see `original_line()`)
+    pub line_shifts: Vec<LineShift>,
+
+    /// Whether a comment describing the mutation was inserted above the
+    /// mutated line. Some operators opt out of the comment, which then does
+    /// not shift the lines of the mutant
+    #[serde(default = "default_comment_inserted")]
+    pub comment_inserted: bool,
+
+    /// The state mutability (`view` or `pure`) of the mutated function, when
+    /// pure/view tagging is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mutability: Option<String>,
+
+    /// The path to the solc binary used for this mutant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solc: Option<String>,
+
+    /// The version of the solc binary used for this mutant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solc_version: Option<String>,
+
+    /// Whether the mutant was validated by compiling it. Unvalidated mutants
+    /// may not compile: validation was either skipped or ran out of time
+    /// (`--max_validation_seconds`)
+    #[serde(default)]
+    pub validated: bool,
+
+    /// Whether the mutant could not be validated because solc kept failing
+    /// for reasons unrelated to it (e.g., it was killed by a signal), even
+    /// after `--solc_retries` retries. Such mutants are exported unvalidated
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub validation_error: bool,
+
+    /// With `--compare_abi`, whether the mutant changes the ABI of the
+    /// original file's contracts. Absent when the ABIs were not compared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abi_changed: Option<bool>,
+
+    /// The wall-clock time it took to validate the mutant, in milliseconds.
+    /// Absent when the mutant was not compiled to validate it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_ms: Option<u64>,
+
+    /// The language of the mutated file when it is not Solidity (e.g.,
+    /// `vyper`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// For mutants of a library called by the mutated file, the mutated file
+    /// that pulled the library in, relative to `sourceroot`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_file: Option<String>,
+
+    /// With `--contract_closure`, the contract defining the mutated code:
+    /// either `filter_contract` or one of the base contracts it inherits from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defining_contract: Option<String>,
+
+    /// With `--contract_closure`, the `--contract` whose inheritance chain
+    /// the mutated code belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_contract: Option<String>,
+
+    /// For mutants of an operator that resolves to a user-defined operator
+    /// (`using {add as +} for T global`), the function implementing the
+    /// original operator. These mutants only replace it with operators that
+    /// are also user-defined for its type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_defined_operator: Option<String>,
+
+    /// With `--emit_original_ast`, the ids of the AST nodes enclosing the
+    /// mutant in `ast_context.json`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ast_context: Option<MutantAstContext>,
+}
+
+/// The name of the run manifest that is written next to `gambit_results.json`
+pub const MANIFEST_FILENAME: &str = "gambit_manifest.json";
+
+/// The run manifest, `gambit_manifest.json`, records how the mutants in an
+/// output directory were requested.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunManifest {
+    /// The names of the mutation operators requested by the configurations
+    /// that write to the output directory, sorted by name
+    pub requested_operators: Vec<String>,
+
+    /// The random stream that the mutants of each sampled file (relative to
+    /// its sourceroot) were sampled from (see `sampling_stream()`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sampling_streams: BTreeMap<String, u64>,
+
+    /// Whether the run was interrupted (e.g., with Ctrl-C), so that
+    /// `gambit_results.json` only lists the mutants validated until then
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+}
+
+/// The name of the job manifest, which is written next to
+/// `gambit_results.json` with `--emit_manifest`
+pub const JOB_MANIFEST_FILENAME: &str = "manifest.json";
+
+/// The job manifest, `manifest.json`, lists what a verification harness needs
+/// to check each mutant on its own: it replaces the original file with the
+/// mutant (e.g., `cp "$mutant" "$original_path"`) and runs the verifier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobManifest {
+    /// The version of Gambit that wrote the output directory
+    pub gambit_version: String,
+
+    /// How the mutants were exported: as mutated files (`full`), or as
+    /// patches of the original files (`patch`)
+    pub export_format: ExportFormat,
+
+    /// The configurations that write to the output directory, with their
+    /// paths canonicalized, to reproduce the run
+    pub configurations: Vec<MutateParams>,
+
+    /// A job for each mutant, in the order of `gambit_results.json`
+    pub jobs: Vec<MutantJob>,
+}
+
+/// The job of a mutant in the job manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MutantJob {
+    /// The mutant's id
+    pub id: String,
+
+    /// The absolute path of the exported mutant (the mutated file, or a
+    /// patch with `--export_format patch`). Missing if the mutant was not
+    /// exported (e.g., with `--no_export`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mutant: Option<String>,
+
+    /// The path of the original file relative to its sourceroot, which is
+    /// the file's source unit name when compiling the project
+    pub original: String,
+
+    /// The absolute path of the original file
+    pub original_path: String,
+
+    /// The operator that generated the mutant
+    pub operator: String,
+
+    /// The line of the mutation in the original file, starting at 1
+    pub line: usize,
+
+    /// The column of the mutation in the original file, starting at 1
+    pub col: usize,
+}
+
+/// The name of the mutation report that is written next to
+/// `gambit_results.json`
+pub const MUTATION_REPORT_FILENAME: &str = "mutation_report.json";
+
+/// The mutation report, `mutation_report.json`, counts the mutants of each
+/// mutated file, contract, and function. It lists every contract and function
+/// in scope, so the functions that no mutants were generated for stand out.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MutationReport {
+    /// The mutated files, in the order they were mutated
+    pub files: Vec<FileReport>,
+}
+
+/// The mutants of a single file in the mutation report
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileReport {
+    /// The path of the file, relative to its sourceroot
+    pub file: String,
+
+    /// The number of mutants of the file
+    pub mutants: usize,
+
+    /// The contracts of the file in scope, in source order
+    pub contracts: Vec<ContractReport>,
+
+    /// The free functions of the file in scope, in source order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub free_functions: Vec<FunctionReport>,
+}
+
+/// The mutants of a contract in the mutation report
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractReport {
+    /// The contract's name
+    pub name: String,
+
+    /// The number of mutants of the contract, including those outside of its
+    /// functions (e.g., in state variable initializers)
+    pub mutants: usize,
+
+    /// The functions of the contract in scope, in source order
+    pub functions: Vec<FunctionReport>,
+}
+
+/// The mutants of a function (or modifier) in the mutation report
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionReport {
+    /// The function's name, or its kind for functions without a name (e.g.,
+    /// `constructor`)
+    pub name: String,
+
+    /// The (1-indexed) line where the function is defined, which tells
+    /// overloads apart
+    pub line: usize,
+
+    /// The number of mutants of the function
+    pub mutants: usize,
+}
+
+impl MutationReport {
+    /// The number of functions in the report
+    pub fn num_functions(&self) -> usize {
+        self.files
+            .iter()
+            .map(|f| {
+                f.free_functions.len()
+                    + f.contracts.iter().map(|c| c.functions.len()).sum::<usize>()
+            })
+            .sum()
+    }
+
+    /// Render the report as a table with a row for each file, contract, and
+    /// function, indented by nesting
+    pub fn table(&self) -> String {
+        let mut rows: Vec<(String, usize)> = vec![];
+        let function_row = |f: &FunctionReport, indent: &str| {
+            (format!("{}{} (line {})", indent, f.name, f.line), f.mutants)
+        };
+        for file in self.files.iter() {
+            rows.push((file.file.clone(), file.mutants));
+            for contract in file.contracts.iter() {
+                rows.push((format!("  {}", contract.name), contract.mutants));
+                rows.extend(contract.functions.iter().map(|f| function_row(f, "    ")));
+            }
+            rows.extend(file.free_functions.iter().map(|f| function_row(f, "  ")));
+        }
+        let width = rows
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .chain(std::iter::once("Scope".len()))
+            .max()
+            .unwrap_or_default();
+        let mut table = format!("{:<width$}  Mutants\n", "Scope");
+        for (name, mutants) in rows {
+            table += &format!("{:<width$}  {:>7}\n", name, mutants);
+        }
+        table
+    }
+}
+
+/// The name of the mutant count estimate written by `--estimate`
+pub const ESTIMATE_FILENAME: &str = "estimate.json";
+
+/// How many mutants each mutation operator is estimated to generate in each
+/// file, as printed and written to `estimate.json` by `--estimate`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MutantEstimate {
+    /// The estimate of each file, in the order the files were given
+    pub files: Vec<FileEstimate>,
+
+    /// The estimated number of mutants of all files
+    pub total: usize,
+}
+
+/// How many mutants each mutation operator is estimated to generate in a
+/// single file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEstimate {
+    /// The path of the file, relative to its sourceroot
+    pub file: String,
+
+    /// The estimated number of mutants of each requested operator
+    pub operators: BTreeMap<String, usize>,
+
+    /// The estimated number of mutants of the file
+    pub total: usize,
+}
+
+impl MutantEstimate {
+    pub fn new(files: Vec<FileEstimate>) -> MutantEstimate {
+        let total = files.iter().map(|f| f.total).sum();
+        MutantEstimate { files, total }
+    }
+
+    /// Render the estimate as a table with a row for each file and a column
+    /// for each operator, followed by the totals of each row and column
+    pub fn table(&self) -> String {
+        let operators: BTreeSet<&String> =
+            self.files.iter().flat_map(|f| f.operators.keys()).collect();
+        let mut rows: Vec<(String, Vec<usize>)> = self
+            .files
+            .iter()
+            .map(|f| {
+                let mut counts: Vec<usize> = operators
+                    .iter()
+                    .map(|op| f.operators.get(*op).copied().unwrap_or_default())
+                    .collect();
+                counts.push(f.total);
+                (f.file.clone(), counts)
+            })
+            .collect();
+        let mut totals: Vec<usize> = operators
+            .iter()
+            .map(|op| self.files.iter().filter_map(|f| f.operators.get(*op)).sum())
+            .collect();
+        totals.push(self.total);
+        rows.push(("Total".into(), totals));
+
+        let width = rows
+            .iter()
+            .map(|(file, _)| file.chars().count())
+            .chain(std::iter::once("File".len()))
+            .max()
+            .unwrap_or_default();
+        let headers: Vec<&str> = operators
+            .iter()
+            .map(|op| op.as_str())
+            .chain(std::iter::once("Total"))
+            .collect();
+        let mut table = format!("{:<width$}", "File");
+        for header in headers.iter() {
+            table += &format!("  {}", header);
+        }
+        table += "\n";
+        for (file, counts) in rows {
+            table += &format!("{:<width$}", file);
+            for (header, count) in headers.iter().zip(counts) {
+                table += &format!("  {:>w$}", count, w = header.len());
+            }
+            table += "\n";
+        }
+        table
+    }
+}
+
+/// A single check of a configuration by `gambit check`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigurationCheck {
+    /// What was checked (e.g., `compile Foo.sol`)
+    pub check: String,
+
+    /// Why the check failed, or `None` if it passed
+    pub failure: Option<String>,
+}
+
+impl ConfigurationCheck {
+    pub fn pass(check: impl Into<String>) -> ConfigurationCheck {
+        ConfigurationCheck {
+            check: check.into(),
+            failure: None,
+        }
+    }
+
+    pub fn fail(check: impl Into<String>, failure: impl Into<String>) -> ConfigurationCheck {
+        ConfigurationCheck {
+            check: check.into(),
+            failure: Some(failure.into()),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// The checks of a configuration by `gambit check`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckReport {
+    /// The checks, in the order they were performed
+    pub checks: Vec<ConfigurationCheck>,
+}
+
+impl CheckReport {
+    /// Did every check pass?
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed())
+    }
+
+    /// Render the report with a `PASS` or `FAIL` line for each check,
+    /// followed by the overall result
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        for check in self.checks.iter() {
+            match &check.failure {
+                None => report += &format!("PASS  {}\n", check.check),
+                Some(failure) => report += &format!("FAIL  {}: {}\n", check.check, failure),
+            }
+        }
+        let failed = self.checks.iter().filter(|c| !c.passed()).count();
+        if failed == 0 {
+            report += &format!("PASS ({} checks)\n", self.checks.len());
+        } else {
+            report += &format!("FAIL ({} of {} checks failed)\n", failed, self.checks.len());
+        }
+        report
+    }
+}
+
+/// The name of the mutant index that is written next to `gambit_results.json`
+pub const INDEX_FILENAME: &str = "index.json";
+
+/// The mutant index, `index.json`, maps the path of each mutated file,
+/// relative to its sourceroot (the `original` of its entries), to a summary
+/// of its mutants. This lets tools split work by file without reading all
+/// of `gambit_results.json`.
+pub type MutantIndex = BTreeMap<String, FileIndexEntry>;
+
+/// The mutants of a single file in the mutant index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileIndexEntry {
+    /// The number of mutants of the file
+    pub mutant_count: usize,
+
+    /// The id of the first mutant of the file
+    pub first_id: String,
+
+    /// The id of the last mutant of the file. Mutants of other files may lie
+    /// between the first and last ids when several configuration entries
+    /// mutate the same file.
+    pub last_id: String,
+
+    /// The mutation operators that generated the file's mutants, sorted by
+    /// name
+    pub operators_used: Vec<String>,
+}
+
+/// Compute the mutant index of `mutants`, which are in order of their ids
+pub fn mutant_index<'a>(mutants: impl IntoIterator<Item = &'a MutantEntry>) -> MutantIndex {
+    let mut files: BTreeMap<String, (FileIndexEntry, BTreeSet<String>)> = BTreeMap::new();
+    for mutant in mutants {
+        let (entry, operators) = files.entry(mutant.original.clone()).or_insert_with(|| {
+            let entry = FileIndexEntry {
+                mutant_count: 0,
+                first_id: mutant.id.clone(),
+                last_id: mutant.id.clone(),
+                operators_used: vec![],
+            };
+            (entry, BTreeSet::new())
+        });
+        entry.mutant_count += 1;
+        entry.last_id = mutant.id.clone();
+        operators.insert(mutant.description.clone());
+    }
+    files
+        .into_iter()
+        .map(|(file, (mut entry, operators))| {
+            entry.operators_used = operators.into_iter().collect();
+            (file, entry)
+        })
+        .collect()
+}
+
+/// The JSON Schema of `gambit_results.json`, pretty printed
+pub fn results_schema() -> String {
+    serde_json::to_string_pretty(&schema_for!(GambitResults)).unwrap()
+}
+
+/// Validate a `gambit_results.json` file (see the
+/// [ValidateResultsParams][ValidateResultsParams] struct for detailed
+/// documentation). The violations found, if any, are listed on stdout and
+/// fail with a `GambitError::InvalidResults`.
+///
+/// [ValidateResultsParams]:crate::cli::ValidateResultsParams
+pub fn validate(params: ValidateResultsParams) -> Result<(), Box<dyn error::Error>> {
+    if params.print_schema {
+        println!("{}", results_schema());
+        return Ok(());
+    }
+    let path = params.results.unwrap();
+    let json = fs::read_to_string(&path)?;
+    let violations = validate_results_in(&json, Path::new(&path).parent());
+    if violations.is_empty() {
+        println!("{}: no violations found", path);
+        return Ok(());
+    }
+    for violation in violations.iter() {
+        println!("[-] {}", violation);
+    }
+    Err(Box::new(GambitError::InvalidResults(format!(
+        "Found {} violations in {}",
+        violations.len(),
+        path
+    ))))
+}
+
+/// Check the contents of a `gambit_results.json` file against the results
+/// schema and the following invariants, returning a description of each
+/// violation:
+///
+/// 1. the results and every entry have the current `format_version`
+/// 2. ids are either all numeric, unique, and contiguous from 1, or are all
+///    unique hex content hashes
+/// 3. `line` and `col` are positive
+/// 4. each diff applies cleanly to its original file, when the original file
+///    (`sourceroot/original`) exists
+/// 5. every mutant is listed by exactly one of the `files`
+///
+/// Diffs stored in a `diff_file` are only checked by `validate_results_in()`.
+pub fn validate_results(json: &str) -> Vec<String> {
+    validate_results_in(json, None)
+}
+
+/// Like `validate_results()`, reading the `diff_file`s of entries from
+/// `outdir`, the directory containing the results, if it is given: these must
+/// exist, and their diffs must apply like inline diffs
+pub fn validate_results_in(json: &str, outdir: Option<&Path>) -> Vec<String> {
+    let value: Value = match serde_json::from_str(json) {
+        Ok(v) => v,
+        Err(e) => return vec![format!("Invalid JSON: {}", e)],
+    };
+    let mut results = match value {
+        Value::Object(results) => results,
+        Value::Array(_) => {
+            return vec![format!(
+                "Expected a top-level object: a top-level array is the layout of format_version 1 (expected {})",
+                RESULTS_FORMAT_VERSION
+            )]
+        }
+        _ => return vec!["Expected a top-level object".to_string()],
+    };
+
+    let mut violations = vec![];
+    let values = match results.remove("mutants") {
+        Some(Value::Array(values)) => values,
+        _ => return vec!["Expected a `mutants` array".to_string()],
+    };
+    let files = match results.remove("files") {
+        Some(files) => match serde_json::from_value::<Vec<ResultsFile>>(files) {
+            Ok(files) => Some(files),
+            Err(e) => {
+                violations.push(format!("files: {}", e));
+                None
+            }
+        },
+        None => {
+            violations.push("missing field `files`".to_string());
+            None
+        }
+    };
+    match results.remove("format_version").map(|v| v.as_u64()) {
+        Some(Some(version)) if version == RESULTS_FORMAT_VERSION as u64 => (),
+        Some(Some(version)) => violations.push(format!(
+            "unsupported format_version {} (expected {})",
+            version, RESULTS_FORMAT_VERSION
+        )),
+        _ => violations.push("missing or ill-formed field `format_version`".to_string()),
+    }
+    for field in results.keys() {
+        violations.push(format!("unknown field `{}`", field));
+    }
+
+    let mut entries = vec![];
+    for (i, v) in values.iter().enumerate() {
+        match serde_json::from_value::<MutantEntry>(v.clone()) {
+            Ok(entry) => entries.push((i, entry)),
+            Err(e) => violations.push(format!("entry {}: {}", i, e)),
+        }
+    }
+
+    for (i, entry) in entries.iter() {
+        if entry.format_version != RESULTS_FORMAT_VERSION {
+            violations.push(format!(
+                "entry {} (id {}): unsupported format_version {} (expected {})",
+                i, entry.id, entry.format_version, RESULTS_FORMAT_VERSION
+            ));
+        }
+        if entry.line == 0 || entry.col == 0 {
+            violations.push(format!(
+                "entry {} (id {}): line and col must be positive, found {}:{}",
+                i, entry.id, entry.line, entry.col
+            ));
+        }
+        let diff = match (&entry.diff_file, outdir) {
+            (Some(diff_file), Some(outdir)) => match read_diff_file(&outdir.join(diff_file)) {
+                Ok(diff) => diff,
+                Err(e) => {
+                    violations.push(format!(
+                        "entry {} (id {}): cannot read diff_file {}: {}",
+                        i, entry.id, diff_file, e
+                    ));
+                    continue;
+                }
+            },
+            _ => entry.diff.clone(),
+        };
+        let original = PathBuf::from(&entry.sourceroot).join(&entry.original);
+        if let Ok(contents) = fs::read_to_string(original) {
+            if let Err(e) = check_diff_applies(&diff, &contents) {
+                violations.push(format!(
+                    "entry {} (id {}): diff does not apply to {}: {}",
+                    i, entry.id, entry.original, e
+                ));
+            }
+        }
+    }
+
+    let ids: Vec<&str> = entries.iter().map(|(_, e)| e.id.as_str()).collect();
+    if let Some(violation) = check_ids(&ids) {
+        violations.push(violation);
+    }
+
+    // Only check how mutants are listed if the files are well-formed
+    let files = match &files {
+        Some(files) => files,
+        None => return violations,
+    };
+    let mut listed: HashSet<&str> = HashSet::new();
+    for file in files.iter() {
+        for id in file.mutant_ids.iter() {
+            if !ids.contains(&id.as_str()) {
+                violations.push(format!("file {}: unknown mutant id {}", file.original, id));
+            } else if !listed.insert(id) {
+                violations.push(format!(
+                    "file {}: mutant {} is listed by more than one file",
+                    file.original, id
+                ));
+            }
+        }
+    }
+    for id in ids.iter().filter(|id| !listed.contains(*id)) {
+        violations.push(format!("mutant {} is not listed by any file", id));
+    }
+    violations
+}
+
+/// Read the `diff_file` of a results entry at `path`, decompressing it if it is
+/// gzip-compressed (i.e., its name ends in `.gz`, see `--compress_diffs`)
+pub fn read_diff_file(path: &Path) -> io::Result<String> {
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        let mut diff = String::new();
+        GzDecoder::new(fs::File::open(path)?).read_to_string(&mut diff)?;
+        Ok(diff)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+/// Check that mutant ids are either numeric, unique, and contiguous from 1, or
+/// are unique hex content hashes, optionally with a `-<n>` suffix that tells
+/// colliding hashes apart (see `--stable_ids`)
+fn check_ids(ids: &[&str]) -> Option<String> {
+    let is_hash = |id: &&str| {
+        let (hash, suffix) = id.split_once('-').unwrap_or((id, "1"));
+        hash.len() >= 8
+            && hash.chars().all(|c| c.is_ascii_hexdigit())
+            && suffix.parse::<usize>().is_ok()
+    };
+    let numeric: Vec<usize> = ids.iter().filter_map(|id| id.parse().ok()).collect();
+    let mut sorted = numeric.clone();
+    sorted.sort();
+    let contiguous = sorted.iter().enumerate().all(|(i, id)| *id == i + 1);
+    if numeric.len() == ids.len() && contiguous {
+        return None;
+    }
+    // Some hashes consist of decimal digits only
+    if ids.iter().all(is_hash) {
+        let unique: HashSet<&&str> = ids.iter().collect();
+        if unique.len() != ids.len() {
+            return Some("hash-form ids are not unique".to_string());
+        }
+    } else if numeric.len() == ids.len() {
+        return Some(format!(
+            "ids are not unique and contiguous from 1 to {}",
+            ids.len()
+        ));
+    } else {
+        let bad: Vec<&str> = ids
+            .iter()
+            .filter(|id| id.parse::<usize>().is_err() && !is_hash(id))
+            .cloned()
+            .collect();
+        return Some(if bad.is_empty() {
+            "ids mix numeric and hash forms".to_string()
+        } else {
+            format!("ids are neither numeric nor hash-form: {}", bad.join(", "))
+        });
+    }
+    None
+}
+
+/// Check that every context (` `) and removed (`-`) line of each hunk of the
+/// unified diff `diff` matches `original`
+fn check_diff_applies(diff: &str, original: &str) -> Result<(), String> {
+    let lines: Vec<&str> = original.split('\n').collect();
+    // The index into `lines` of the next line of the current hunk
+    let mut pos: Option<usize> = None;
+    for line in diff.split('\n') {
+        if let Some(header) = line.strip_prefix("@@ -") {
+            let start = header
+                .split([',', ' '])
+                .next()
+                .and_then(|s| s.parse::<usize>().ok())
+                .ok_or(format!("malformed hunk header `{}`", line))?;
+            pos = Some(start.saturating_sub(1));
+        } else if let Some(p) = pos.as_mut() {
+            match line.chars().next() {
+                Some(' ') | Some('-') => {
+                    if lines.get(*p) != Some(&&line[1..]) {
+                        return Err(format!("line {} does not match", *p + 1));
+                    }
+                    *p += 1;
+                }
+                Some('+') | Some('\\') | None => (),
+                _ => return Err(format!("malformed diff line `{}`", line)),
+            }
+        } else if !(line.starts_with("---") || line.starts_with("+++") || line.is_empty()) {
+            return Err(format!("unexpected line `{}` before the first hunk", line));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("resources")
+            .join("validate_results")
+            .join(name);
+        let json = fs::read_to_string(path).unwrap();
+        // Fixtures refer to their original files relative to the crate root
+        json.replace("$CRATE", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_published_schema_is_up_to_date() {
+        let published = fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/gambit_results.schema.json"),
+        )
+        .unwrap();
+        assert_eq!(published.trim_end(), results_schema());
+    }
+
+    #[test]
+    fn test_validate_valid_results() {
+        assert_eq!(
+            validate_results(&fixture("valid.json")),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_validate_broken_results() {
+        let violations = validate_results(&fixture("broken.json"));
+        assert_eq!(violations.len(), 4, "{:#?}", violations);
+        assert!(violations[0].starts_with("entry 1: unknown field `extra`"));
+        assert!(violations[1].contains("line and col must be positive"));
+        assert!(violations[2].contains("diff does not apply"));
+        assert_eq!(
+            violations[3],
+            "ids are not unique and contiguous from 1 to 3"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gambit_results.json");
+        fs::write(&path, fixture("broken.json")).unwrap();
+        let e = validate(ValidateResultsParams {
+            results: Some(path.to_str().unwrap().to_string()),
+            print_schema: false,
+        })
+        .unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<GambitError>(),
+            Some(GambitError::InvalidResults(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_results_layout() {
+        let valid: Value = serde_json::from_str(&fixture("valid.json")).unwrap();
+        let legacy = serde_json::to_string(&valid["mutants"]).unwrap();
+        assert!(validate_results(&legacy)[0].contains("layout of format_version 1"));
+
+        let mut unlisted = valid.clone();
+        unlisted["files"][0]["mutant_ids"] = serde_json::json!(["1", "1", "3"]);
+        unlisted["format_version"] = 1.into();
+        assert_eq!(
+            validate_results(&unlisted.to_string()),
+            vec![
+                "unsupported format_version 1 (expected 2)",
+                "file 10Power/TenPower.sol: mutant 1 is listed by more than one file",
+                "file 10Power/TenPower.sol: unknown mutant id 3",
+                "mutant 2 is not listed by any file",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let json = fixture("valid.json");
+        let results = parse_results(&json).unwrap();
+        assert_eq!(results.format_version, RESULTS_FORMAT_VERSION);
+        assert_eq!(results.files[0].mutant_ids, vec!["1", "2"]);
+
+        // A format version 1 file is a bare array of mutants, which are
+        // grouped into one file per original file
+        let mut mutants: Value = serde_json::from_str::<Value>(&json).unwrap()["mutants"].clone();
+        for mutant in mutants.as_array_mut().unwrap() {
+            mutant["format_version"] = 1.into();
+        }
+        let legacy = parse_results(&mutants.to_string()).unwrap();
+        assert_eq!(legacy.format_version, 1);
+        assert_eq!(legacy.mutants.len(), 2);
+        assert_eq!(legacy.files.len(), 1);
+        let file = &legacy.files[0];
+        assert_eq!(file.original, "10Power/TenPower.sol");
+        assert_eq!(file.operators, vec!["BinaryOpMutation"]);
+        assert_eq!(file.mutant_ids, vec!["1", "2"]);
+        assert_eq!(file.statistics, results.files[0].statistics);
+    }
+
+    #[test]
+    fn test_for_each_results_entry() {
+        let json = fixture("valid.json");
+        let expected = serde_json::from_str::<Value>(&json).unwrap()["mutants"].clone();
+        let read = |json: &str| {
+            let mut entries = vec![];
+            for_each_results_entry(json.as_bytes(), |e| entries.push(e))
+                .map(|found| (found, entries))
+        };
+        assert_eq!(
+            read(&json).unwrap(),
+            (true, expected.as_array().unwrap().clone())
+        );
+        // The top-level array of format version 1
+        assert_eq!(read(&expected.to_string()).unwrap().1.len(), 2);
+
+        assert_eq!(read("{\"files\": []}").unwrap(), (false, vec![]));
+        assert!(read("{\"mutants\": {}}").is_err());
+        assert!(read("[{}] trailing").is_err());
+    }
+
+    #[test]
+    fn test_check_ids() {
+        assert_eq!(check_ids(&["1", "2", "3"]), None);
+        assert_eq!(check_ids(&["3", "1", "2"]), None);
+        assert!(check_ids(&["1", "1", "2"]).is_some());
+        assert!(check_ids(&["1", "3"]).is_some());
+        assert_eq!(check_ids(&["deadbeef00", "0123456789"]), None);
+        assert!(check_ids(&["deadbeef00", "deadbeef00"]).is_some());
+        assert!(check_ids(&["1", "deadbeef00"]).is_some());
+        assert!(check_ids(&["mutant-1"]).is_some());
+        assert_eq!(check_ids(&["deadbeef", "deadbeef-2", "12345678"]), None);
+        assert!(check_ids(&["deadbeef", "deadbeef-x"]).is_some());
+    }
+
+    #[test]
+    fn test_estimate_table() {
+        let file = |file: &str, operators: &[(&str, usize)]| FileEstimate {
+            file: file.into(),
+            operators: operators
+                .iter()
+                .map(|(op, n)| (op.to_string(), *n))
+                .collect(),
+            total: operators.iter().map(|(_, n)| n).sum(),
+        };
+        let estimate = MutantEstimate::new(vec![
+            file("A.sol", &[("BinaryOpMutation", 10), ("RequireMutation", 2)]),
+            file("lib/B.sol", &[("BinaryOpMutation", 5)]),
+        ]);
+        assert_eq!(estimate.total, 17);
+        assert_eq!(
+            estimate.table(),
+            "File       BinaryOpMutation  RequireMutation  Total\n\
+             A.sol                    10                2     12\n\
+             lib/B.sol                 5                0      5\n\
+             Total                    15                2     17\n"
+        );
+    }
+}
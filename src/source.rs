@@ -14,15 +14,30 @@ pub enum SourceError {
 
 impl fmt::Display for SourceError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "SourceError")
+        match self {
+            SourceError::PositionOutOfBoundsError(pos, filename) => {
+                write!(f, "position {} is out of bounds in {}", pos, filename)
+            }
+            SourceError::LineColumnLookupError(pos, filename) => write!(
+                f,
+                "couldn't find the line and column of position {} in {}",
+                pos, filename
+            ),
+        }
     }
 }
 
 impl error::Error for SourceError {}
 
+/// The UTF-8 byte order mark that some editors write at the start of a file
+pub const UTF8_BOM: &str = "\u{feff}";
+
 /// A source file, including its name, contents, and source root, to be mutated.
 pub struct Source {
     filename: PathBuf,
+    /// The canonical path of the file on disk, which identifies the file
+    /// regardless of the sourceroot it was reached from
+    physical_path: PathBuf,
     sourceroot: PathBuf,
     contents: Vec<u8>,
     newlines: Vec<usize>,
@@ -32,6 +47,7 @@ impl std::fmt::Debug for Source {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         f.debug_struct("Source")
             .field("filename", &self.filename)
+            .field("physical_path", &self.physical_path)
             .field("sourceroot", &self.sourceroot)
             .field("contents", &String::from("[...]"))
             .field("newlines", &String::from("[...]"))
@@ -41,21 +57,33 @@ impl std::fmt::Debug for Source {
 
 impl Source {
     pub fn new(filename: PathBuf, sourceroot: PathBuf) -> Result<Source, Box<dyn error::Error>> {
+        let physical_path = filename.canonicalize()?;
         let filename = simplify_path(&filename)?;
         let contents = read_source(&filename)?;
+        Ok(Source {
+            physical_path,
+            ..Source::from_contents(filename, sourceroot, contents)
+        })
+    }
+
+    /// Create a source from in-memory contents without touching the
+    /// filesystem. The `filename` is used as is, and may be a virtual path
+    /// that does not exist on disk; it is also used as the physical path.
+    pub fn from_contents(filename: PathBuf, sourceroot: PathBuf, contents: Vec<u8>) -> Source {
         let newlines: Vec<usize> = contents
             .iter()
             .enumerate()
             .filter(|(_, c)| **c == b'\n')
-            .map(|(i, _)| i + 2)
+            .map(|(i, _)| i)
             .collect();
 
-        Ok(Source {
+        Source {
+            physical_path: filename.clone(),
             filename,
             sourceroot,
             contents,
             newlines,
-        })
+        }
     }
 
     /// Get the filename of this source
@@ -63,6 +91,12 @@ impl Source {
         self.filename.as_path()
     }
 
+    /// Get the canonical path of this source's file on disk. Unlike
+    /// `filename()`, this is the same for every `Source` of a given file.
+    pub fn physical_path(&self) -> &Path {
+        self.physical_path.as_path()
+    }
+
     /// Get the filename of this source as a string
     pub fn filename_as_str(&self) -> String {
         self.filename.to_str().unwrap().into()
@@ -77,37 +111,60 @@ impl Source {
         &self.contents
     }
 
+    /// Check if this source starts with a UTF-8 byte order mark. The mark is
+    /// part of the contents, so that positions match those of solc's AST.
+    pub fn has_bom(&self) -> bool {
+        self.contents.starts_with(UTF8_BOM.as_bytes())
+    }
+
     /// Get the sourceroot for this source file
     pub fn sourceroot(&self) -> &Path {
         self.sourceroot.as_path()
     }
 
     /// Get a (line, column) pair that represents which line and column this
-    /// mutant occurs at. Lines and columns are both 1-indexed.
+    /// mutant occurs at. Lines and columns are both 1-indexed, and a newline
+    /// is the last column of the line it ends.
     pub fn get_line_column(&self, pos: usize) -> Result<(usize, usize), Box<dyn error::Error>> {
-        if pos >= self.contents.len() {
-            return Err(Box::new(SourceError::PositionOutOfBoundsError(
+        // An empty file has no lines
+        if self.contents.is_empty() {
+            return Err(Box::new(SourceError::LineColumnLookupError(
                 pos,
                 self.filename_as_str(),
             )));
         }
-
-        let newlines = &self.newlines;
-        if let Some((lineno, nlpos)) = newlines
-            .iter()
-            .enumerate()
-            .rev()
-            .find(|(_, nlpos)| nlpos < &&pos)
-        {
-            let columnno = pos - nlpos + 2;
-            Ok((lineno + 2, columnno))
-        } else if &pos < newlines.first().unwrap() {
-            Ok((1, pos + 1))
-        } else {
-            Err(Box::new(SourceError::LineColumnLookupError(
+        // The end of the file is a valid position, e.g., for the end of a span
+        // ending with the file's last byte
+        if pos > self.contents.len() {
+            return Err(Box::new(SourceError::PositionOutOfBoundsError(
                 pos,
                 self.filename_as_str(),
-            )))
+            )));
         }
+
+        // The number of newlines before `pos`
+        let lineno = self.newlines.partition_point(|nlpos| *nlpos < pos);
+        let line_start = match lineno {
+            0 => 0,
+            _ => self.newlines[lineno - 1] + 1,
+        };
+        Ok((lineno + 1, pos - line_start + 1))
+    }
+
+    /// Get the position of a (1-indexed) line and column: the inverse of
+    /// `get_line_column()`. Returns `None` if the line has no such column.
+    pub fn get_position(&self, line: usize, col: usize) -> Option<usize> {
+        let line_start = match line {
+            0 => return None,
+            1 => 0,
+            _ => self.newlines.get(line - 2)? + 1,
+        };
+        let line_end = self
+            .newlines
+            .get(line - 1)
+            .copied()
+            .unwrap_or(self.contents.len());
+        let pos = line_start + col.checked_sub(1)?;
+        (pos <= line_end).then_some(pos)
     }
 }
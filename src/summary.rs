@@ -1,83 +1,894 @@
-use std::{collections::HashSet, error, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashSet},
+    error,
+    path::{Path, PathBuf},
+};
 
+use clap::ValueEnum;
+use serde::Serialize;
 use serde_json::Value;
 
-use crate::SummaryParams;
+use crate::{
+    for_each_results_entry, normalize_path, read_diff_file, report, Diagnostic, DiagnosticCode,
+    GambitError, MutantIndex, MutationType, RunManifest, StatisticsGrouping, SummaryFormat,
+    SummaryParams, INDEX_FILENAME, MANIFEST_FILENAME,
+};
 
 /// Summarize an existing mutation run (see the [SummaryParams][SummaryParams]
 /// struct for detailed documentation)
 ///
 /// [SummaryParams]:crate::cli::SummaryParams
 pub fn summarize(params: SummaryParams) -> Result<(), Box<dyn error::Error>> {
+    if let Some(runs) = &params.compare {
+        let (before, after) = (Path::new(&runs[0]), Path::new(&runs[1]));
+        let comparison = compare_runs(
+            &read_results(before)?,
+            &read_results(after)?,
+            params.line_tolerance,
+        );
+        match params.format {
+            SummaryFormat::Text => print_comparison(&comparison, &runs[0], &runs[1]),
+            SummaryFormat::Json => println!("{}", serde_json::to_string_pretty(&comparison)?),
+        }
+        return Ok(());
+    }
     let mutation_dir = PathBuf::from(params.mutation_directory);
     let gambit_results_json_path = mutation_dir.join("gambit_results.json");
 
     if !&mutation_dir.is_dir() {
-        log::error!("Missing mutation directory: `{}`", mutation_dir.display());
-        log::error!("Suggestions:");
-        log::error!("  [+] Run `gambit mutate` to generate mutants");
-        log::error!("  [+] Use the `--mutation-directory` flag to specify a different location");
+        report(
+            Diagnostic::new(
+                DiagnosticCode::FileResolutionError,
+                format!("Missing mutation directory: `{}`", mutation_dir.display()),
+            )
+            .with_file(mutation_dir.display())
+            .with_note("Suggestions:")
+            .with_note("[+] Run `gambit mutate` to generate mutants")
+            .with_note("[+] Use the `--mutation-directory` flag to specify a different location"),
+        );
         std::process::exit(1);
     } else if !&gambit_results_json_path.is_file() {
-        log::error!(
-            "Missing JSON `{}` in mutation directory `{}`",
-            gambit_results_json_path.display(),
-            mutation_dir.display()
-        );
-        log::error!("Suggestions:");
-        log::error!("  [+] Run `gambit mutate` to generate mutants");
-        log::error!(
-            "  [+] Use the `--mutation-directory` flag to specify a different mutation directory"
+        report(
+            Diagnostic::new(
+                DiagnosticCode::FileResolutionError,
+                format!(
+                    "Missing JSON `{}` in mutation directory `{}`",
+                    gambit_results_json_path.display(),
+                    mutation_dir.display()
+                ),
+            )
+            .with_file(gambit_results_json_path.display())
+            .with_note("Suggestions:")
+            .with_note("[+] Run `gambit mutate` to generate mutants")
+            .with_note(
+                "[+] Use the `--mutation-directory` flag to specify a different mutation directory",
+            ),
         );
         std::process::exit(1);
     }
-    let gambit_results_json = std::fs::read_to_string(gambit_results_json_path.as_path());
-    match gambit_results_json {
+    let filter = match EntryFilter::new(params.ops.as_deref(), params.file.clone()) {
+        Ok(filter) => filter.with_abi_changed(params.abi_changed),
         Err(e) => {
-            log::error!(
-                "Couldn't read results JSON at {}:\nError: {}",
-                gambit_results_json_path.display(),
-                e
-            );
+            report(Diagnostic::new(DiagnosticCode::ConfigurationError, e));
             std::process::exit(1);
         }
-        Ok(json) => {
-            let v: Value = serde_json::from_str(&json)?;
-            if !v.is_array() {
-                log::error!(
-                    "Ill-formed results JSON found at: {}\nExpected an array",
-                    gambit_results_json_path.display()
-                );
-                std::process::exit(1);
+    };
+    let json = params.format == SummaryFormat::Json;
+    if params.statistics && params.group_by == StatisticsGrouping::File && filter.is_empty() {
+        // The mutant index already counts the mutants of each file
+        if let Some(index) = read_index(&mutation_dir)? {
+            let counts = index
+                .into_iter()
+                .map(|(file, entry)| (file, entry.mutant_count))
+                .collect();
+            if json {
+                let summary = RunSummary::new(StatisticsGrouping::File, counts, None);
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            } else {
+                print_counts(StatisticsGrouping::File, counts);
             }
-            let v = v.as_array().unwrap();
-            match params.mids {
-                Some(mids) => {
-                    let mids: HashSet<String> = HashSet::from_iter(mids.iter().cloned());
-                    for (i, value) in v.iter().enumerate() {
-                        let mid = value
-                            .as_object()
-                            .expect("Expected an object")
-                            .get("id")
-                            .expect("Expected mutant to have `id` field")
-                            .as_str()
-                            .expect("Expected `id` field to be a string")
-                            .to_string();
-                        if mids.contains(&mid) {
-                            print_mutant_summary(i, value);
-                        }
-                    }
+            return Ok(());
+        }
+    }
+    let gambit_results_json = open_results(&gambit_results_json_path);
+    // Entries are summarized as they are read, so that large results are
+    // never held in memory as a whole. Statistics are computed from the
+    // entries without their diffs.
+    let mids: Option<HashSet<String>> = params.mids.map(HashSet::from_iter);
+    let is_selected = |value: &Value| {
+        mids.as_ref().is_none_or(|mids| {
+            let mid = value
+                .as_object()
+                .expect("Expected an object")
+                .get("id")
+                .expect("Expected mutant to have `id` field")
+                .as_str()
+                .expect("Expected `id` field to be a string");
+            mids.contains(mid)
+        })
+    };
+    // The listed mutants are ordered by id, so a first pass collects the ids
+    // of the selected mutants to find the page that is listed
+    let page = if params.statistics {
+        Page::default()
+    } else {
+        let mut ids = vec![];
+        for_each_results_entry(open_results(&gambit_results_json_path), |value| {
+            if filter.matches(&value) && is_selected(&value) {
+                ids.push(value["id"].as_str().unwrap_or_default().to_string());
+            }
+        })?;
+        paginate(&ids, params.offset, params.limit)
+    };
+    // The listed mutants are printed as they are read when the results are
+    // already ordered by id, and buffered otherwise
+    let streamed = page.positions.windows(2).all(|w| w[0] < w[1]);
+    let ranks: BTreeMap<usize, usize> = page
+        .positions
+        .iter()
+        .enumerate()
+        .map(|(rank, &position)| (position, rank))
+        .collect();
+    let mut buffered: Vec<Option<(usize, Value)>> = vec![None; page.positions.len()];
+    let mut entries = vec![];
+    // The listed mutants, for JSON output
+    let mut mutants = vec![];
+    let mut list = |i: usize, value: &Value| {
+        if json {
+            mutants.push(MutantSummaryEntry::from_entry(value));
+        } else {
+            print_mutant_summary(i, value);
+            if params.with_commands {
+                print_mutant_commands(value, &mutation_dir);
+            }
+        }
+    };
+    let mut i = 0;
+    let mut selected = 0;
+    let found = for_each_results_entry(gambit_results_json, |mut value| {
+        if !filter.matches(&value) {
+            i += 1;
+            return;
+        }
+        if !params.statistics && is_selected(&value) {
+            if let Some(&rank) = ranks.get(&selected) {
+                let mut value = value.clone();
+                inline_diff_file(&mut value, &mutation_dir);
+                if streamed {
+                    list(i, &value);
+                } else {
+                    buffered[rank] = Some((i, value));
                 }
-                None => {
-                    v.iter().enumerate().for_each(|(i, m)| {
-                        print_mutant_summary(i, m);
+            }
+            selected += 1;
+        }
+        // JSON output always includes statistics
+        if params.statistics || json {
+            if let Some(entry) = value.as_object_mut() {
+                entry.remove("diff");
+            }
+            entries.push(value);
+        }
+        i += 1;
+    })?;
+    for (i, value) in buffered.into_iter().flatten() {
+        list(i, &value);
+    }
+    if page.remaining > 0 && !json {
+        println!(
+            "\n{} more mutants not shown, use --limit/--offset to list them (e.g., --offset {})",
+            page.remaining,
+            params.offset.saturating_add(page.positions.len())
+        );
+    }
+    if !found {
+        return Err(Box::new(GambitError::InvalidResults(format!(
+            "Ill-formed results JSON found at {}: expected an object with a `mutants` array",
+            gambit_results_json_path.display()
+        ))));
+    }
+    if params.statistics || json {
+        let manifest = read_manifest(&mutation_dir)?;
+        if params.requested_only && manifest.is_none() {
+            log::warn!(
+                "No {} in `{}`: ignoring --requested-only",
+                MANIFEST_FILENAME,
+                mutation_dir.display()
+            );
+        }
+        if json {
+            let counts = filter.retain_counts(
+                params.group_by,
+                statistics_counts(
+                    &entries,
+                    params.group_by,
+                    manifest.as_ref(),
+                    params.requested_only,
+                ),
+            );
+            let mutants = (!params.statistics).then_some(mutants);
+            let mut summary = RunSummary::new(params.group_by, counts, mutants);
+            summary.omitted_mutants = (page.remaining > 0).then_some(page.remaining);
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        } else {
+            print_statistics(
+                &entries,
+                params.group_by,
+                manifest.as_ref(),
+                params.requested_only,
+                &filter,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The summary of a run, as printed by `gambit summary --format json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunSummary {
+    /// What `counts` groups mutants by: `operator`, `solc version`, or `file`
+    pub group_by: String,
+
+    /// The number of mutants in each group. When grouping by operator,
+    /// requested operators that generated no mutants have a count of 0
+    pub counts: BTreeMap<String, usize>,
+
+    /// The total number of mutants
+    pub total_mutants: usize,
+
+    /// The selected mutants (see `--mids`), unless only statistics were
+    /// requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mutants: Option<Vec<MutantSummaryEntry>>,
+
+    /// The number of selected mutants after the listed ones that were not
+    /// listed because of `--limit`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub omitted_mutants: Option<usize>,
+}
+
+impl RunSummary {
+    pub fn new(
+        group_by: StatisticsGrouping,
+        counts: Vec<(String, usize)>,
+        mutants: Option<Vec<MutantSummaryEntry>>,
+    ) -> RunSummary {
+        RunSummary {
+            group_by: grouping_name(group_by).to_string(),
+            total_mutants: counts.iter().map(|(_, count)| count).sum(),
+            counts: counts.into_iter().collect(),
+            mutants,
+            omitted_mutants: None,
+        }
+    }
+}
+
+/// A mutant of a run, as summarized by `gambit summary --format json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MutantSummaryEntry {
+    /// The mutant id
+    pub mid: String,
+
+    /// The command line name of the operator that generated the mutant (e.g.,
+    /// `binary-op-mutation`), if it is known
+    pub op_short: Option<String>,
+
+    /// The name of the operator that generated the mutant (e.g.,
+    /// `BinaryOpMutation`)
+    pub op_long: String,
+
+    /// The source text that the mutation replaced
+    pub orig: String,
+
+    /// The source text that replaced `orig`
+    pub repl: String,
+
+    /// The path of the exported mutant, relative to the output directory
+    pub path: String,
+
+    /// The diff of the mutant and the original file
+    pub diff: String,
+}
+
+impl MutantSummaryEntry {
+    fn from_entry(entry: &Value) -> MutantSummaryEntry {
+        let field = |name: &str| {
+            entry
+                .get(name)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let op_long = field("description");
+        let op_short = MutationType::from_name(&op_long).map(|op| op.short_name());
+        MutantSummaryEntry {
+            mid: field("id"),
+            op_short,
+            op_long,
+            orig: field("orig"),
+            repl: field("repl"),
+            path: field("name"),
+            diff: field("diff"),
+        }
+    }
+}
+
+/// Count the mutants in `gambit_results.json` entries by the given grouping,
+/// returning `(group, count)` pairs sorted by group. Entries without the
+/// grouped field are counted under `"unknown"`.
+pub fn compute_statistics(entries: &[Value], group_by: StatisticsGrouping) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in entries {
+        let group = match group_by {
+            StatisticsGrouping::Operator => entry.get("description"),
+            StatisticsGrouping::Solc => entry.get("solc_version").or(entry.get("solc")),
+            StatisticsGrouping::File => entry.get("original"),
+        }
+        .and_then(|g| g.as_str())
+        .unwrap_or("unknown");
+        *counts.entry(group.to_string()).or_default() += 1;
+    }
+    counts.into_iter().collect()
+}
+
+/// The span groups of a run (see `span_groups()`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanGroupStatistics {
+    /// The number of span groups
+    pub num_groups: usize,
+
+    /// The number of mutants in the largest group
+    pub largest_size: usize,
+
+    /// Where the largest group starts, as `file:line:col`
+    pub largest_location: String,
+}
+
+/// Compute span group statistics from the `span_group` fields of
+/// `gambit_results.json` entries, or `None` if no entry has one (e.g., for
+/// runs of older versions of Gambit). Ties for the largest group go to the
+/// group with the lowest id.
+pub fn span_group_statistics(entries: &[Value]) -> Option<SpanGroupStatistics> {
+    // The size and earliest (line, column, file) of each group
+    let mut groups: BTreeMap<u64, (usize, (u64, u64, String))> = BTreeMap::new();
+    for entry in entries {
+        let group = match entry.get("span_group").and_then(|g| g.as_u64()) {
+            Some(group) => group,
+            None => continue,
+        };
+        let line = entry.get("line").and_then(|l| l.as_u64()).unwrap_or(0);
+        let col = entry.get("col").and_then(|c| c.as_u64()).unwrap_or(0);
+        let file = entry
+            .get("original")
+            .and_then(|f| f.as_str())
+            .unwrap_or("unknown");
+        let location = (line, col, file.to_string());
+        groups
+            .entry(group)
+            .and_modify(|(size, start)| {
+                *size += 1;
+                if location < *start {
+                    *start = location.clone();
+                }
+            })
+            .or_insert((1, location));
+    }
+    let mut largest: Option<&(usize, (u64, u64, String))> = None;
+    for group in groups.values() {
+        if largest.is_none_or(|l| group.0 > l.0) {
+            largest = Some(group);
+        }
+    }
+    largest.map(|(size, (line, col, file))| SpanGroupStatistics {
+        num_groups: groups.len(),
+        largest_size: *size,
+        largest_location: format!("{}:{}:{}", file, line, col),
+    })
+}
+
+/// Read the run manifest of a mutation directory, if it has one
+pub fn read_manifest(mutation_dir: &Path) -> Result<Option<RunManifest>, Box<dyn error::Error>> {
+    let manifest_path = mutation_dir.join(MANIFEST_FILENAME);
+    if !manifest_path.is_file() {
+        return Ok(None);
+    }
+    let manifest = serde_json::from_str(&std::fs::read_to_string(&manifest_path)?)
+        .map_err(|e| format!("Ill-formed manifest {}: {}", manifest_path.display(), e))?;
+    Ok(Some(manifest))
+}
+
+/// Read the mutant index of a mutation directory, if it has one
+pub fn read_index(mutation_dir: &Path) -> Result<Option<MutantIndex>, Box<dyn error::Error>> {
+    let index_path = mutation_dir.join(INDEX_FILENAME);
+    if !index_path.is_file() {
+        return Ok(None);
+    }
+    let index = serde_json::from_str(&std::fs::read_to_string(&index_path)?)
+        .map_err(|e| format!("Ill-formed mutant index {}: {}", index_path.display(), e))?;
+    Ok(Some(index))
+}
+
+/// Join per-operator counts (from `compute_statistics()`) with the operators
+/// requested in a run: requested operators that produced no mutants are added
+/// with a count of 0, and if `requested_only` is set, operators that were not
+/// requested are dropped.
+pub fn join_requested_operators(
+    counts: Vec<(String, usize)>,
+    manifest: &RunManifest,
+    requested_only: bool,
+) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = counts
+        .into_iter()
+        .filter(|(op, _)| !requested_only || manifest.requested_operators.contains(op))
+        .collect();
+    for op in manifest.requested_operators.iter() {
+        counts.entry(op.clone()).or_default();
+    }
+    counts.into_iter().collect()
+}
+
+/// Print the number (and percentage) of mutants in each group. When grouping
+/// by operator and a manifest is available, requested operators that produced
+/// no mutants are listed (and flagged) as well.
+fn print_statistics(
+    entries: &[Value],
+    group_by: StatisticsGrouping,
+    manifest: Option<&RunManifest>,
+    requested_only: bool,
+    filter: &EntryFilter,
+) {
+    print_counts(
+        group_by,
+        filter.retain_counts(
+            group_by,
+            statistics_counts(entries, group_by, manifest, requested_only),
+        ),
+    );
+    if let Some(groups) = span_group_statistics(entries) {
+        println!(
+            "{}: {} (largest: {} mutants at {})",
+            ansi_term::Style::new().bold().paint("Span groups"),
+            groups.num_groups,
+            groups.largest_size,
+            groups.largest_location
+        );
+    }
+}
+
+/// The number of mutants in each group, joined with the requested operators
+/// of `manifest` when grouping by operator (see
+/// `join_requested_operators()`)
+fn statistics_counts(
+    entries: &[Value],
+    group_by: StatisticsGrouping,
+    manifest: Option<&RunManifest>,
+    requested_only: bool,
+) -> Vec<(String, usize)> {
+    let counts = compute_statistics(entries, group_by);
+    match (group_by, manifest) {
+        (StatisticsGrouping::Operator, Some(manifest)) => {
+            join_requested_operators(counts, manifest, requested_only)
+        }
+        _ => counts,
+    }
+}
+
+/// The name of a grouping, as printed in summaries
+fn grouping_name(group_by: StatisticsGrouping) -> &'static str {
+    match group_by {
+        StatisticsGrouping::Operator => "operator",
+        StatisticsGrouping::Solc => "solc version",
+        StatisticsGrouping::File => "file",
+    }
+}
+
+/// Print the number (and percentage) of mutants in each group, followed by
+/// the total number of mutants
+fn print_counts(group_by: StatisticsGrouping, counts: Vec<(String, usize)>) {
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+    println!(
+        "{}",
+        ansi_term::Style::new()
+            .bold()
+            .paint(format!("Mutants by {}:", grouping_name(group_by)))
+    );
+    for (group, count) in counts {
+        if count == 0 {
+            println!(
+                "{}",
+                ansi_term::Color::Red.paint(format!(
+                    "    {}: 0 (0.00%) [!] requested but generated no mutants",
+                    group
+                ))
+            );
+            continue;
+        }
+        println!(
+            "    {}: {} ({:.2}%)",
+            group,
+            count,
+            100.0 * count as f64 / total as f64
+        );
+    }
+    println!(
+        "{}: {}",
+        ansi_term::Style::new().bold().paint("Total mutants"),
+        total
+    );
+}
+
+/// Read the entries of a `gambit_results.json` file, given either the file or
+/// the output directory containing it
+pub fn read_results(path: &Path) -> Result<Vec<Value>, Box<dyn error::Error>> {
+    let results_path = if path.is_dir() {
+        path.join("gambit_results.json")
+    } else {
+        path.to_path_buf()
+    };
+    if !results_path.is_file() {
+        return Err(Box::new(GambitError::FileResolution(format!(
+            "No gambit_results.json at {}",
+            path.display()
+        ))));
+    }
+    let mut entries = vec![];
+    match for_each_results_entry(std::fs::File::open(&results_path)?, |e| entries.push(e))? {
+        true => Ok(entries),
+        false => Err(Box::new(GambitError::InvalidResults(format!(
+            "Ill-formed results JSON found at {}: expected an object with a `mutants` array",
+            results_path.display()
+        )))),
+    }
+}
+
+/// A mutant of a run, as compared with the mutants of another run. Mutant ids
+/// are not stable across runs, so mutants are identified by their operator,
+/// file, and original and replacement text instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ComparedMutant {
+    pub operator: String,
+    pub file: String,
+    pub orig: String,
+    pub repl: String,
+    pub line: usize,
+}
+
+impl ComparedMutant {
+    fn from_entry(entry: &Value) -> ComparedMutant {
+        let field = |name: &str| {
+            entry
+                .get(name)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+        ComparedMutant {
+            operator: field("description"),
+            file: field("original"),
+            orig: field("orig"),
+            repl: field("repl"),
+            line: entry.get("line").and_then(|l| l.as_u64()).unwrap_or(0) as usize,
+        }
+    }
+
+    /// Do `self` and `other` apply the same mutation (possibly on different
+    /// lines)?
+    fn same_mutation(&self, other: &ComparedMutant) -> bool {
+        self.operator == other.operator
+            && self.file == other.file
+            && self.orig == other.orig
+            && self.repl == other.repl
+    }
+}
+
+/// A mutant of the first run that was matched with a mutant of the second run
+/// on a different line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MovedMutant {
+    #[serde(flatten)]
+    pub mutant: ComparedMutant,
+    pub new_line: usize,
+}
+
+/// The change in the number of mutants of an operator between two runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OperatorDelta {
+    pub operator: String,
+    pub before: usize,
+    pub after: usize,
+    pub delta: i64,
+}
+
+/// The differences between the mutants of two runs (see `compare_runs()`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunComparison {
+    /// Mutants of the second run that match no mutant of the first run
+    pub added: Vec<ComparedMutant>,
+    /// Mutants of the first run that match no mutant of the second run
+    pub removed: Vec<ComparedMutant>,
+    /// Mutants that were matched on different lines
+    pub moved: Vec<MovedMutant>,
+    /// The number of mutants of each operator in both runs
+    pub operator_deltas: Vec<OperatorDelta>,
+}
+
+/// Compare the `gambit_results.json` entries of two runs. Two mutants match if
+/// they apply the same mutation (see `ComparedMutant`) on lines that are at
+/// most `line_tolerance` lines apart. Mutants on the same line are matched
+/// first, and then each remaining mutant is matched with the closest
+/// remaining candidate.
+pub fn compare_runs(before: &[Value], after: &[Value], line_tolerance: usize) -> RunComparison {
+    let before_mutants: Vec<ComparedMutant> =
+        before.iter().map(ComparedMutant::from_entry).collect();
+    let mut after_mutants: Vec<Option<ComparedMutant>> = after
+        .iter()
+        .map(|e| Some(ComparedMutant::from_entry(e)))
+        .collect();
+    let mut matched = vec![false; before_mutants.len()];
+    let mut moved = vec![];
+    for max_distance in [0, line_tolerance] {
+        for (i, mutant) in before_mutants.iter().enumerate() {
+            if matched[i] {
+                continue;
+            }
+            let closest = after_mutants
+                .iter()
+                .enumerate()
+                .filter_map(|(j, m)| {
+                    m.as_ref()
+                        .filter(|m| m.same_mutation(mutant))
+                        .map(|m| (j, m.line.abs_diff(mutant.line)))
+                })
+                .filter(|(_, distance)| *distance <= max_distance)
+                .min_by_key(|(_, distance)| *distance);
+            if let Some((j, _)) = closest {
+                let other = after_mutants[j].take().unwrap();
+                matched[i] = true;
+                if other.line != mutant.line {
+                    moved.push(MovedMutant {
+                        mutant: mutant.clone(),
+                        new_line: other.line,
                     });
                 }
             }
         }
+    }
+    let removed = before_mutants
+        .into_iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(m, _)| m)
+        .collect();
+    let added = after_mutants.into_iter().flatten().collect();
+
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for (op, count) in compute_statistics(before, StatisticsGrouping::Operator) {
+        counts.entry(op).or_default().0 = count;
+    }
+    for (op, count) in compute_statistics(after, StatisticsGrouping::Operator) {
+        counts.entry(op).or_default().1 = count;
+    }
+    let operator_deltas = counts
+        .into_iter()
+        .map(|(operator, (before, after))| OperatorDelta {
+            operator,
+            before,
+            after,
+            delta: after as i64 - before as i64,
+        })
+        .collect();
+
+    RunComparison {
+        added,
+        removed,
+        moved,
+        operator_deltas,
+    }
+}
+
+/// Print the added, removed, and moved mutants of a comparison along with the
+/// per-operator deltas
+fn print_comparison(comparison: &RunComparison, before: &str, after: &str) {
+    let bold = ansi_term::Style::new().bold();
+    let describe = |m: &ComparedMutant| format!("{}(`{}` |==> `{}`)", m.operator, m.orig, m.repl);
+    println!(
+        "{}",
+        bold.paint(format!("Comparing {} to {}", before, after))
+    );
+
+    println!(
+        "{}",
+        bold.paint(format!("Added mutants ({}):", comparison.added.len()))
+    );
+    for m in comparison.added.iter() {
+        println!(
+            "{}",
+            ansi_term::Color::Green.paint(format!("    + {}:{} {}", m.file, m.line, describe(m)))
+        );
+    }
+    println!(
+        "{}",
+        bold.paint(format!("Removed mutants ({}):", comparison.removed.len()))
+    );
+    for m in comparison.removed.iter() {
+        println!(
+            "{}",
+            ansi_term::Color::Red.paint(format!("    - {}:{} {}", m.file, m.line, describe(m)))
+        );
+    }
+    println!(
+        "{}",
+        bold.paint(format!("Moved mutants ({}):", comparison.moved.len()))
+    );
+    for m in comparison.moved.iter() {
+        println!(
+            "{}",
+            ansi_term::Color::Cyan.paint(format!(
+                "    ~ {}:{} -> {} {}",
+                m.mutant.file,
+                m.mutant.line,
+                m.new_line,
+                describe(&m.mutant)
+            ))
+        );
+    }
+    println!("{}", bold.paint("Mutants by operator:"));
+    for d in comparison.operator_deltas.iter() {
+        println!(
+            "    {}: {} -> {} ({:+})",
+            d.operator, d.before, d.after, d.delta
+        );
+    }
+}
+
+/// The mutants that `gambit summary` summarizes (see `--ops` and `--file`)
+#[derive(Debug, Default)]
+struct EntryFilter {
+    /// If this is `Some(ops)`, only the mutants of these operators (by their
+    /// names in `gambit_results.json`, e.g., `BinaryOpMutation`)
+    ops: Option<HashSet<String>>,
+
+    /// If this is `Some(s)`, only the mutants of original files whose path
+    /// contains `s`
+    file: Option<String>,
+
+    /// Only the mutants that change the ABI (see `--compare_abi`)
+    abi_changed: bool,
+}
+
+impl EntryFilter {
+    /// Filter by the operators named `ops`, either by their command line
+    /// names (e.g., `binary-op-mutation`) or their names in
+    /// `gambit_results.json` (e.g., `BinaryOpMutation`), and by `file`.
+    /// Returns an error naming the first unknown operator
+    fn new(ops: Option<&[String]>, file: Option<String>) -> Result<EntryFilter, String> {
+        let ops = match ops {
+            Some(ops) => Some(
+                ops.iter()
+                    .map(|name| {
+                        MutationType::from_str(name, true)
+                            .ok()
+                            .or_else(|| MutationType::from_name(name))
+                            .map(|op| op.to_string())
+                            .ok_or_else(|| format!("Unknown mutation operator `{}` in --ops", name))
+                    })
+                    .collect::<Result<HashSet<String>, String>>()?,
+            ),
+            None => None,
+        };
+        Ok(EntryFilter {
+            ops,
+            file,
+            abi_changed: false,
+        })
+    }
+
+    /// Only select the mutants whose entry records that they change the ABI
+    fn with_abi_changed(mut self, abi_changed: bool) -> EntryFilter {
+        self.abi_changed = abi_changed;
+        self
+    }
+
+    /// Check if this filter selects every mutant
+    fn is_empty(&self) -> bool {
+        self.ops.is_none() && self.file.is_none() && !self.abi_changed
+    }
+
+    /// Check if the `gambit_results.json` entry `entry` is selected
+    fn matches(&self, entry: &Value) -> bool {
+        let field = |name: &str| entry.get(name).and_then(Value::as_str).unwrap_or_default();
+        self.ops
+            .as_ref()
+            .is_none_or(|ops| ops.contains(field("description")))
+            && self
+                .file
+                .as_ref()
+                .is_none_or(|file| field("original").contains(file.as_str()))
+            && (!self.abi_changed || entry.get("abi_changed") == Some(&Value::Bool(true)))
+    }
+
+    /// Drop the operators that are not selected from per-operator `counts`,
+    /// e.g., requested operators that were joined from the manifest
+    fn retain_counts(
+        &self,
+        group_by: StatisticsGrouping,
+        counts: Vec<(String, usize)>,
+    ) -> Vec<(String, usize)> {
+        match (&self.ops, group_by) {
+            (Some(ops), StatisticsGrouping::Operator) => counts
+                .into_iter()
+                .filter(|(op, _)| ops.contains(op))
+                .collect(),
+            _ => counts,
+        }
+    }
+}
+
+/// The mutants listed by `gambit summary` (see `--limit` and `--offset`)
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Page {
+    /// The positions of the listed mutants among the selected mutants (in
+    /// the order of `gambit_results.json`), in order of their ids
+    positions: Vec<usize>,
+
+    /// The number of selected mutants after the listed ones
+    remaining: usize,
+}
+
+/// Find the page of the mutants with `ids` (in the order of
+/// `gambit_results.json`) that skips `offset` mutants and lists at most
+/// `limit` of them (all of them if `limit` is 0), in order of their ids.
+/// Numeric ids are ordered by value, before any other ids.
+fn paginate(ids: &[String], offset: usize, limit: usize) -> Page {
+    let key = |id: &str| {
+        let value = id.parse::<u64>().ok();
+        (value.is_none(), value, id.to_string())
     };
+    let mut positions: Vec<usize> = (0..ids.len()).collect();
+    positions.sort_by_cached_key(|&i| key(&ids[i]));
+    let limit = if limit == 0 { usize::MAX } else { limit };
+    let positions: Vec<usize> = positions.into_iter().skip(offset).take(limit).collect();
+    let remaining = ids
+        .len()
+        .saturating_sub(offset.saturating_add(positions.len()));
+    Page {
+        positions,
+        remaining,
+    }
+}
 
-    Ok(())
+/// Open the `gambit_results.json` at `path`, exiting with an error if it
+/// can't be read
+fn open_results(path: &Path) -> std::fs::File {
+    match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            report(
+                Diagnostic::new(
+                    DiagnosticCode::FileResolutionError,
+                    format!("Couldn't read results JSON at {}: {}", path.display(), e),
+                )
+                .with_file(path.display()),
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Read the diff of a `gambit_results.json` entry that is stored in a
+/// `diff_file` (relative to the output directory `outdir`) into its `diff`
+/// field
+fn inline_diff_file(entry: &mut Value, outdir: &Path) {
+    let diff_file = match entry.get("diff_file").and_then(Value::as_str) {
+        Some(diff_file) => outdir.join(diff_file),
+        None => return,
+    };
+    match read_diff_file(&diff_file) {
+        Ok(diff) => entry["diff"] = Value::String(diff),
+        Err(e) => log::warn!("Couldn't read diff file {}: {}", diff_file.display(), e),
+    }
 }
 
 /// Print a mutant summary, or a warning if a value is poorly formed.
@@ -141,3 +952,366 @@ fn print_mutant_summary(i: usize, mutant_json: &Value) {
         );
     }
 }
+
+/// The commands to inspect an exported mutant: its absolute path, and a
+/// `diff -u` of the original file against it, unless the mutant was exported
+/// as a patch (see `--export_format`). Paths are shell-quoted, so each
+/// command can be copied and pasted as is. Returns `None` if the entry is
+/// missing its `name`, `sourceroot`, or `original` field.
+fn mutant_commands(mutant_json: &Value, mutation_dir: &Path) -> Option<(String, Option<String>)> {
+    let field = |name: &str| mutant_json.get(name).and_then(Value::as_str);
+    let absolute = |path: PathBuf| match std::env::current_dir() {
+        Ok(cwd) => normalize_path(&cwd.join(path)),
+        Err(_) => path,
+    };
+    let name = field("name")?;
+    let mutant = absolute(mutation_dir.join(name));
+    let original = absolute(Path::new(field("sourceroot")?).join(field("original")?));
+    let mutant = shell_quote(&mutant.display().to_string());
+    let original = shell_quote(&original.display().to_string());
+    let diff = (!name.ends_with(".patch")).then(|| format!("diff -u {} {}", original, mutant));
+    Some((mutant, diff))
+}
+
+/// Quote `s` for a POSIX shell. Words made only of safe characters are
+/// returned as is; anything else is single-quoted.
+fn shell_quote(s: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-+=.,:/@%".contains(c);
+    if !s.is_empty() && s.chars().all(safe) {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
+/// Print the commands to inspect an exported mutant (see `--with-commands`)
+fn print_mutant_commands(mutant_json: &Value, mutation_dir: &Path) {
+    match mutant_commands(mutant_json, mutation_dir) {
+        Some((mutant, diff)) => {
+            println!(
+                "{}: {}",
+                ansi_term::Style::new().bold().paint("Exported"),
+                mutant
+            );
+            if let Some(diff) = diff {
+                println!("{}: {}", ansi_term::Style::new().bold().paint("Diff"), diff);
+            }
+        }
+        None => log::warn!(
+            "Couldn't build the commands of mutant entry {}",
+            mutant_json
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        compare_runs, compute_statistics, join_requested_operators, mutant_commands, paginate,
+        read_manifest, read_results, shell_quote, span_group_statistics, ComparedMutant,
+        EntryFilter, MovedMutant, OperatorDelta, SpanGroupStatistics,
+    };
+    use crate::StatisticsGrouping;
+    use serde_json::{json, Value};
+    use std::path::Path;
+
+    #[test]
+    fn test_statistics_grouping() {
+        let entries = vec![
+            json!({"id": "1", "description": "BinaryOpMutation", "original": "A.sol", "solc": "/usr/bin/solc", "solc_version": "0.8.19"}),
+            json!({"id": "2", "description": "RequireMutation", "original": "A.sol", "solc": "/usr/bin/solc", "solc_version": "0.8.19"}),
+            json!({"id": "3", "description": "BinaryOpMutation", "original": "B.sol", "solc": "/opt/solc-0.7.6", "solc_version": "0.7.6"}),
+            json!({"id": "4", "description": "BinaryOpMutation"}),
+        ];
+        assert_eq!(
+            compute_statistics(&entries, StatisticsGrouping::Solc),
+            vec![
+                ("0.7.6".to_string(), 1),
+                ("0.8.19".to_string(), 2),
+                ("unknown".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            compute_statistics(&entries, StatisticsGrouping::Operator),
+            vec![
+                ("BinaryOpMutation".to_string(), 3),
+                ("RequireMutation".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            compute_statistics(&entries, StatisticsGrouping::File),
+            vec![
+                ("A.sol".to_string(), 2),
+                ("B.sol".to_string(), 1),
+                ("unknown".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_span_group_statistics() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/summary");
+        let results = read_results(&dir).unwrap();
+        // Swapping the arguments of `a ** decimals` overlaps each mutant of
+        // its operator
+        assert_eq!(
+            span_group_statistics(&results),
+            Some(SpanGroupStatistics {
+                num_groups: 1,
+                largest_size: 6,
+                largest_location: "10Power/TenPower.sol:8:23".to_string(),
+            })
+        );
+        // Ties go to the first group
+        let entries = vec![
+            json!({"original": "A.sol", "line": 3, "col": 5, "span_group": 1}),
+            json!({"original": "A.sol", "line": 9, "col": 1, "span_group": 2}),
+            json!({"original": "A.sol", "line": 2, "col": 7, "span_group": 1}),
+            json!({"original": "B.sol", "line": 1, "col": 1, "span_group": 3}),
+            json!({"original": "B.sol", "line": 1, "col": 4, "span_group": 3}),
+        ];
+        assert_eq!(
+            span_group_statistics(&entries),
+            Some(SpanGroupStatistics {
+                num_groups: 3,
+                largest_size: 2,
+                largest_location: "A.sol:2:7".to_string(),
+            })
+        );
+        // Results written before span groups were recorded have none
+        let results = read_results(&dir.join("compare/before.json")).unwrap();
+        assert_eq!(span_group_statistics(&results), None);
+    }
+
+    #[test]
+    fn test_join_requested_operators() {
+        // The manifest requests `FunctionCallMutation`, which generated no
+        // mutants, but not `SwapArgumentsOperatorMutation`, which did
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/summary");
+        let results: Value = serde_json::from_str(
+            &std::fs::read_to_string(dir.join("gambit_results.json")).unwrap(),
+        )
+        .unwrap();
+        let manifest = read_manifest(&dir).unwrap().unwrap();
+        let counts = compute_statistics(results.as_array().unwrap(), StatisticsGrouping::Operator);
+        assert_eq!(
+            join_requested_operators(counts.clone(), &manifest, false),
+            vec![
+                ("BinaryOpMutation".to_string(), 5),
+                ("FunctionCallMutation".to_string(), 0),
+                ("SwapArgumentsOperatorMutation".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            join_requested_operators(counts, &manifest, true),
+            vec![
+                ("BinaryOpMutation".to_string(), 5),
+                ("FunctionCallMutation".to_string(), 0)
+            ]
+        );
+        assert_eq!(read_manifest(&dir.join("missing")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_compare_runs() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/summary/compare");
+        let before = read_results(&dir.join("before.json")).unwrap();
+        let after = read_results(&dir.join("after.json")).unwrap();
+        let mutant = |operator: &str, line: usize, orig: &str, repl: &str| ComparedMutant {
+            operator: operator.to_string(),
+            file: "contracts/C.sol".to_string(),
+            orig: orig.to_string(),
+            repl: repl.to_string(),
+            line,
+        };
+
+        // Mutant ids differ between the runs, and are ignored
+        let comparison = compare_runs(&before, &after, 10);
+        assert_eq!(
+            comparison.added,
+            vec![
+                mutant("UnaryOperatorMutation", 14, "-", "~"),
+                // Too far from line 20 to be considered moved
+                mutant("BinaryOpMutation", 40, "+", "*"),
+            ]
+        );
+        assert_eq!(
+            comparison.removed,
+            vec![
+                mutant("RequireMutation", 12, "x > 0", "true"),
+                mutant("BinaryOpMutation", 20, "+", "*"),
+            ]
+        );
+        assert_eq!(
+            comparison.moved,
+            vec![MovedMutant {
+                mutant: mutant("BinaryOpMutation", 8, "*", "/"),
+                new_line: 11,
+            }]
+        );
+        let delta = |operator: &str, before: usize, after: usize| OperatorDelta {
+            operator: operator.to_string(),
+            before,
+            after,
+            delta: after as i64 - before as i64,
+        };
+        assert_eq!(
+            comparison.operator_deltas,
+            vec![
+                delta("BinaryOpMutation", 3, 3),
+                delta("RequireMutation", 1, 0),
+                delta("UnaryOperatorMutation", 0, 1),
+            ]
+        );
+
+        // With a smaller tolerance, the moved mutant is removed and re-added
+        let comparison = compare_runs(&before, &after, 2);
+        assert!(comparison.moved.is_empty());
+        assert_eq!(comparison.added.len(), 3);
+        assert_eq!(comparison.removed.len(), 3);
+    }
+
+    #[test]
+    fn test_compare_runs_prefers_same_line() {
+        let entry = |line: usize| json!({"description": "BinaryOpMutation", "original": "C.sol", "orig": "+", "repl": "-", "line": line});
+        // The mutant on line 12 is unchanged, rather than the one on line 10
+        // moving to line 12
+        let comparison = compare_runs(&[entry(10), entry(12)], &[entry(12)], 10);
+        assert!(comparison.moved.is_empty());
+        assert!(comparison.added.is_empty());
+        assert_eq!(comparison.removed.len(), 1);
+        assert_eq!(comparison.removed[0].line, 10);
+    }
+
+    #[test]
+    fn test_mutant_commands() {
+        assert_eq!(
+            shell_quote("/tmp/gambit_out/C.sol"),
+            "/tmp/gambit_out/C.sol"
+        );
+        assert_eq!(shell_quote("/tmp/my dir/C.sol"), "'/tmp/my dir/C.sol'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote(""), "''");
+
+        let entry = json!({
+            "id": "3",
+            "name": "mutants/3/contracts/C.sol",
+            "sourceroot": "/home/user/my project",
+            "original": "contracts/C.sol",
+        });
+        let (mutant, diff) = mutant_commands(&entry, Path::new("/home/user/gambit_out")).unwrap();
+        assert_eq!(mutant, "/home/user/gambit_out/mutants/3/contracts/C.sol");
+        assert_eq!(
+            diff.unwrap(),
+            "diff -u '/home/user/my project/contracts/C.sol' /home/user/gambit_out/mutants/3/contracts/C.sol"
+        );
+
+        // Exports under a project root have absolute names
+        let entry = json!({
+            "name": "/home/user/root/contracts/C.sol",
+            "sourceroot": "/home/user/root",
+            "original": "contracts/C.sol",
+        });
+        let (mutant, _) = mutant_commands(&entry, Path::new("gambit_out")).unwrap();
+        assert_eq!(mutant, "/home/user/root/contracts/C.sol");
+
+        // Patches are diffs already
+        let entry = json!({
+            "name": "mutants/3/contracts/C.sol.patch",
+            "sourceroot": "/home/user/root",
+            "original": "contracts/C.sol",
+        });
+        let (mutant, diff) = mutant_commands(&entry, Path::new("/tmp/gambit_out")).unwrap();
+        assert_eq!(mutant, "/tmp/gambit_out/mutants/3/contracts/C.sol.patch");
+        assert_eq!(diff, None);
+
+        assert!(mutant_commands(&json!({"id": "1"}), Path::new("gambit_out")).is_none());
+    }
+
+    #[test]
+    fn test_paginate() {
+        // Ids are ordered numerically, whatever their order in the results
+        let ids: Vec<String> = ["3", "1", "10", "2", "5", "4", "x"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        let listed = |offset: usize, limit: usize| {
+            let page = paginate(&ids, offset, limit);
+            let listed: Vec<&str> = page.positions.iter().map(|&i| ids[i].as_str()).collect();
+            (listed, page.remaining)
+        };
+        assert_eq!(listed(0, 3), (vec!["1", "2", "3"], 4));
+        // Consecutive pages neither overlap nor skip mutants
+        assert_eq!(listed(3, 3), (vec!["4", "5", "10"], 1));
+        assert_eq!(listed(6, 3), (vec!["x"], 0));
+        assert_eq!(listed(7, 3), (vec![], 0));
+        assert_eq!(listed(10, 3), (vec![], 0));
+        // A limit of 0 lists every mutant
+        assert_eq!(listed(0, 0), (vec!["1", "2", "3", "4", "5", "10", "x"], 0));
+        assert_eq!(listed(5, 0), (vec!["10", "x"], 0));
+        assert_eq!(listed(0, 7), (listed(0, 0).0, 0));
+        // The largest offset does not overflow
+        assert_eq!(listed(usize::MAX, 3), (vec![], 0));
+    }
+
+    #[test]
+    fn test_entry_filter() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/summary/filter");
+        let results = read_results(&dir).unwrap();
+        let counts = |filter: EntryFilter, group_by: StatisticsGrouping| {
+            let entries: Vec<Value> = results
+                .iter()
+                .filter(|e| filter.matches(e))
+                .cloned()
+                .collect();
+            filter.retain_counts(group_by, compute_statistics(&entries, group_by))
+        };
+        let count = |name: &str, n: usize| (name.to_string(), n);
+        let ops = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        let filter = EntryFilter::new(None, None).unwrap();
+        assert!(filter.is_empty());
+        assert_eq!(counts(filter, StatisticsGrouping::Operator).len(), 4);
+
+        // Operators are named like on the command line, or like in the results
+        let filter = EntryFilter::new(Some(&ops(&["binary-op-mutation", "RequireMutation"])), None);
+        assert_eq!(
+            counts(filter.unwrap(), StatisticsGrouping::Operator),
+            vec![count("BinaryOpMutation", 3), count("RequireMutation", 2)]
+        );
+        let filter = EntryFilter::new(None, Some("Token".into())).unwrap();
+        assert_eq!(
+            counts(filter, StatisticsGrouping::Operator),
+            vec![
+                count("BinaryOpMutation", 1),
+                count("RequireMutation", 1),
+                count("UnaryOperatorMutation", 1)
+            ]
+        );
+        let filter = EntryFilter::new(
+            Some(&ops(&["binary-op-mutation"])),
+            Some("contracts/".into()),
+        );
+        assert_eq!(
+            counts(filter.unwrap(), StatisticsGrouping::File),
+            vec![
+                count("contracts/Token.sol", 1),
+                count("contracts/Vault.sol", 2)
+            ]
+        );
+
+        let filter = EntryFilter::new(
+            Some(&ops(&["binary-op-mutation", "no-such-mutation"])),
+            None,
+        );
+        assert!(filter.unwrap_err().contains("no-such-mutation"));
+
+        // Only mutants recorded as changing the ABI
+        let filter = EntryFilter::new(None, None).unwrap().with_abi_changed(true);
+        assert!(!filter.is_empty());
+        assert_eq!(
+            counts(filter, StatisticsGrouping::File),
+            vec![count("contracts/Vault.sol", 1)]
+        );
+    }
+}
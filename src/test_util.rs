@@ -1,4 +1,5 @@
 use crate::{SolAST, SolASTVisitor};
+use serde_json::{json, Value};
 use std::{io::prelude::*, path::PathBuf};
 use tempfile::Builder;
 
@@ -71,3 +72,112 @@ impl SolASTVisitor<(), SolAST> for ExprParserHelper {
         }
     }
 }
+
+/// Build the solc AST of a hand-written Solidity source, for tests that don't
+/// invoke solc. Nodes are located by the first occurrence of their text in the
+/// source, so each returned expression must occur once.
+pub struct AstBuilder<'a> {
+    source: &'a str,
+}
+
+impl<'a> AstBuilder<'a> {
+    pub fn new(source: &'a str) -> AstBuilder<'a> {
+        AstBuilder { source }
+    }
+
+    /// The `src` field of the first occurrence of `text`
+    pub fn src(&self, text: &str) -> String {
+        let start = self.source.find(text).unwrap();
+        format!("{}:{}:0", start, text.len())
+    }
+
+    /// A number literal `value` starting at `start`
+    pub fn literal(&self, value: &str, start: usize) -> Value {
+        json!({"nodeType": "Literal", "kind": "number", "value": value, "src": format!("{}:1:0", start)})
+    }
+
+    /// A binary operation `expr` of two number literals, such as `1 + 2`
+    pub fn binop(&self, expr: &str) -> Value {
+        let start = self.source.find(expr).unwrap();
+        let parts: Vec<&str> = expr.split(' ').collect();
+        let right = expr.len() - parts[2].len();
+        json!({
+            "nodeType": "BinaryOperation",
+            "operator": parts[1],
+            "src": self.src(expr),
+            "leftExpression": self.literal(parts[0], start),
+            "rightExpression": self.literal(parts[2], start + right),
+        })
+    }
+
+    /// The function whose body is `return <returned>;`, returning `expression`.
+    /// Its name, visibility (`public` by default), and mutability are read
+    /// from its header.
+    pub fn function(&self, returned: &str, expression: Value) -> Value {
+        let statement = format!("return {};", returned);
+        let at = self.source.find(&statement).unwrap();
+        let start = self.source[..at].rfind("function").unwrap();
+        let end = at + self.source[at..].find('}').unwrap() + 1;
+        let body = start + self.source[start..].find('{').unwrap();
+        let header = &self.source[start..body];
+        let name = &header["function ".len()..header.find('(').unwrap()];
+        let words: Vec<&str> = header[header.find(')').unwrap()..]
+            .split([' ', '(', ')'])
+            .collect();
+        let visibility = ["public", "external", "internal", "private"]
+            .into_iter()
+            .find(|v| words.contains(v))
+            .unwrap_or("public");
+        let mutability = ["pure", "view", "payable"]
+            .into_iter()
+            .find(|m| words.contains(m))
+            .unwrap_or("nonpayable");
+        json!({
+            "nodeType": "FunctionDefinition",
+            "kind": "function",
+            "name": name,
+            "visibility": visibility,
+            "stateMutability": mutability,
+            "src": format!("{}:{}:0", start, end - start),
+            "body": {
+                "nodeType": "Block",
+                "src": format!("{}:{}:0", body, end - body),
+                "statements": [{
+                    "nodeType": "Return",
+                    "src": self.src(&statement),
+                    "expression": expression,
+                }]
+            }
+        })
+    }
+
+    /// The contract `name`, which ends at the first unindented `}` after it
+    pub fn contract(&self, name: &str, nodes: Vec<Value>) -> Value {
+        let start = self.source.find(&format!("contract {}", name)).unwrap();
+        let end = start + self.source[start..].find("\n}").unwrap() + 2;
+        json!({
+            "nodeType": "ContractDefinition",
+            "contractKind": "contract",
+            "name": name,
+            "src": format!("{}:{}:0", start, end - start),
+            "nodes": nodes,
+        })
+    }
+
+    /// The source unit of the whole source
+    pub fn source_unit(&self, nodes: Vec<Value>) -> Value {
+        json!({
+            "nodeType": "SourceUnit",
+            "src": format!("0:{}:0", self.source.len()),
+            "nodes": nodes,
+        })
+    }
+}
+
+/// `node` along with the further (or overriding) `fields`
+pub fn with_fields(mut node: Value, fields: Value) -> Value {
+    if let (Some(node), Value::Object(fields)) = (node.as_object_mut(), fields) {
+        node.extend(fields);
+    }
+    node
+}
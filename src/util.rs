@@ -1,11 +1,15 @@
 use std::{
     error::Error,
     fs::File,
-    io::Read,
+    io::{Read, Write},
     path::{Component, Path, PathBuf},
 };
 
 use ansi_term::{ANSIGenericString, Color, Style};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{report, Diagnostic, DiagnosticCode};
 
 static EQUAL: &str = "=";
 pub static DEFAULT_GAMBIT_OUTPUT_DIRECTORY: &str = "gambit_out";
@@ -111,20 +115,136 @@ pub fn repair_remapping(remap_str: &str, resolve_against: Option<&str>) -> Strin
     result
 }
 
+/// Check a list of (repaired) remappings for duplicate prefixes, returning
+/// the normalized list of remappings that should be handed to solc.
+///
+/// Duplicate remappings of a prefix to the same target are collapsed into a
+/// single remapping. Remappings of a prefix to _different_ targets are an
+/// error unless `allow_override` is `true`, in which case the last remapping of
+/// that prefix wins and a `RemappingOverride` warning is reported.
+pub fn normalize_remappings(
+    remappings: &[String],
+    allow_override: bool,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut result: Vec<(String, String)> = vec![];
+    for remapping in remappings {
+        let (prefix, target) = remapping
+            .split_once(EQUAL)
+            .ok_or_else(|| format!("Invalid remapping `{}`", remapping))?;
+        match result.iter_mut().find(|(p, _)| p == prefix) {
+            None => result.push((prefix.to_string(), target.to_string())),
+            Some((_, t)) if t == target => {
+                log::debug!("Dropping duplicate remapping {}", remapping);
+            }
+            Some((_, t)) => {
+                if !allow_override {
+                    return Err(format!(
+                        "Conflicting remappings for prefix `{}`: `{}` and `{}`",
+                        prefix, t, target
+                    )
+                    .into());
+                }
+                report(Diagnostic::new(
+                    DiagnosticCode::RemappingOverride,
+                    format!(
+                        "Remapping `{}={}` overrides earlier remapping `{}={}`",
+                        prefix, target, prefix, t
+                    ),
+                ));
+                *t = target.to_string();
+            }
+        }
+    }
+    Ok(result
+        .into_iter()
+        .map(|(prefix, target)| prefix + EQUAL + &target)
+        .collect())
+}
+
+/// Append the pass-through values given on the command line (`cli`) to those
+/// of a configuration entry, dropping values the entry already has. Returns
+/// `None` if neither has any values.
+pub fn append_cli_values(entry: Option<Vec<String>>, cli: &[String]) -> Option<Vec<String>> {
+    if cli.is_empty() {
+        return entry;
+    }
+    let mut values = entry.unwrap_or_default();
+    for value in cli {
+        if !values.contains(value) {
+            values.push(value.clone());
+        }
+    }
+    Some(values)
+}
+
+/// Resolve an executable name to a path, searching the `PATH` environment
+/// variable if `cmd` is not itself a path. Returns `None` if no such executable
+/// can be found.
+pub fn resolve_executable(cmd: &str) -> Option<PathBuf> {
+    let cmd_path = PathBuf::from(cmd);
+    if cmd_path.components().count() > 1 {
+        return cmd_path.canonicalize().ok();
+    }
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(cmd))
+            .find(|p| p.is_file())
+            .and_then(|p| p.canonicalize().ok())
+    })
+}
+
+/// The number of jobs to run at a time when none is specified (e.g., by
+/// `--jobs`): the available parallelism, or 1 if it cannot be determined
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
 type CommandOutput = (Option<i32>, Vec<u8>, Vec<u8>);
 
 /// Utility for invoking any command `cmd` with `args`.
 /// Returns the tuple (`status.code`, `stdout` and `stderr`).
 pub fn invoke_command(cmd: &str, args: Vec<&str>) -> Result<CommandOutput, Box<dyn Error>> {
-    let out = std::process::Command::new(cmd)
+    let res = std::process::Command::new(cmd)
         .args(args.iter().map(|a| a.to_string()))
-        .output();
-    if out.is_err() {
-        panic!("Failed to invoke {cmd}.");
-    } else {
-        let res = out.ok().unwrap();
-        Ok((res.status.code(), res.stdout, res.stderr))
-    }
+        .output()
+        .map_err(|e| format!("Failed to invoke {cmd}: {e}"))?;
+    Ok((res.status.code(), res.stdout, res.stderr))
+}
+
+/// Like `invoke_command()`, writing `input` to the command's stdin
+pub fn invoke_command_with_input(
+    cmd: &str,
+    args: Vec<&str>,
+    input: &[u8],
+) -> Result<CommandOutput, Box<dyn Error>> {
+    let mut child = std::process::Command::new(cmd)
+        .args(args.iter().map(|a| a.to_string()))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to invoke {cmd}: {e}"))?;
+    let mut stdin = child.stdin.take().unwrap();
+    // Write from another thread, so that a command that writes a lot of
+    // output before reading all of its input can't deadlock
+    let res = std::thread::scope(|scope| {
+        let writer = scope.spawn(move || stdin.write_all(input));
+        let output = child.wait_with_output();
+        // A command may exit without reading its input
+        let _ = writer.join();
+        output
+    })
+    .map_err(|e| format!("Failed to invoke {cmd}: {e}"))?;
+    Ok((res.status.code(), res.stdout, res.stderr))
+}
+
+/// Parse JSON without `serde_json`'s nesting limit, growing the stack as
+/// needed. The ASTs of long expressions are deeply nested: each term of a sum
+/// of thousands of terms adds a level.
+pub fn parse_json_unbounded<R: Read>(reader: R) -> Result<Value, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    deserializer.disable_recursion_limit();
+    Value::deserialize(serde_stacker::Deserializer::new(&mut deserializer))
 }
 
 pub fn read_source(orig_path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
@@ -193,10 +313,438 @@ pub fn rel_path_from_base(path: &Path, base: &Path) -> Result<PathBuf, Box<dyn E
     }
 }
 
+/// The edit distance between `a` and `b`: the number of character
+/// insertions, deletions, substitutions, and transpositions of adjacent
+/// characters that turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // d[i][j] is the distance between the first i chars of a and the first j
+    // chars of b
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Suggest which of `names` was meant by `name`, a name that is not among
+/// them: a name that only differs from it in case or, failing that, the
+/// closest name within a small edit distance
+pub fn suggest_name<'a>(name: &str, names: &'a [String]) -> Option<&'a str> {
+    if let Some(same) = names.iter().find(|n| n.eq_ignore_ascii_case(name)) {
+        return Some(same);
+    }
+    let max_distance = (name.chars().count() / 3).clamp(1, 3);
+    names
+        .iter()
+        .map(|n| (edit_distance(name, n), n))
+        .filter(|(d, _)| *d <= max_distance)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, n)| n.as_str())
+}
+
+/// Resolve `filename`, a path that does not exist on disk, as solc would
+/// resolve it as an import: through the longest of the `remappings`
+/// (`[context:]prefix=target`) whose prefix it starts with, or else relative
+/// to one of the `search_dirs` (the base path and include path). Relative
+/// remapping targets and search directories are resolved against `base`.
+/// Returns the canonical path of the file, if it exists.
+pub fn resolve_import_mapped_filename(
+    filename: &str,
+    remappings: &[String],
+    search_dirs: &[String],
+    base: &Path,
+) -> Option<PathBuf> {
+    let remapped = remappings
+        .iter()
+        .filter_map(|remapping| {
+            let (prefix, target) = remapping.split_once(EQUAL)?;
+            // Remappings with a context apply to imports from that context;
+            // a filename given by the user has none, so the context is ignored
+            let prefix = prefix.split_once(':').map_or(prefix, |(_, p)| p);
+            let rest = filename.strip_prefix(prefix.trim_end_matches('/'))?;
+            let rest = rest.strip_prefix('/')?;
+            Some((prefix.len(), base.join(target).join(rest)))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, path)| path);
+    remapped
+        .into_iter()
+        .chain(search_dirs.iter().map(|dir| base.join(dir).join(filename)))
+        .find(|path| path.is_file())
+        .and_then(|path| path.canonicalize().ok())
+}
+
+/// The import remappings and import paths of a Foundry or Hardhat project
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ProjectLayout {
+    /// The remappings declared in `remappings.txt` and `foundry.toml`, with
+    /// their targets resolved against the project root
+    pub remappings: Vec<String>,
+
+    /// The canonical paths of the project's source and library directories
+    /// (`src/`, `lib/`, `node_modules/`, and those named in `foundry.toml`)
+    pub import_paths: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FoundryConfig {
+    #[serde(default)]
+    profile: FoundryProfiles,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FoundryProfiles {
+    #[serde(default)]
+    default: FoundryProfile,
+}
+
+/// The settings of a `foundry.toml` profile that affect import resolution
+#[derive(Debug, Default, Deserialize)]
+struct FoundryProfile {
+    src: Option<String>,
+    libs: Option<Vec<String>>,
+    #[serde(default)]
+    remappings: Vec<String>,
+}
+
+/// Discover the layout of the project at `project_root` from its
+/// `remappings.txt` and `foundry.toml` (if present) and the conventional
+/// source and library directories that exist. Remappings whose targets do not
+/// exist are ignored with a warning, and when several remappings share a
+/// prefix the first one is kept.
+pub fn discover_project_layout(project_root: &Path) -> ProjectLayout {
+    let mut declared: Vec<String> = vec![];
+    let mut dirs: Vec<String> = vec!["src".into(), "lib".into(), "node_modules".into()];
+
+    let remappings_txt = project_root.join("remappings.txt");
+    if let Ok(contents) = std::fs::read_to_string(&remappings_txt) {
+        log::info!("Reading remappings from {}", remappings_txt.display());
+        declared.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from),
+        );
+    }
+    let foundry_toml = project_root.join("foundry.toml");
+    if let Ok(contents) = std::fs::read_to_string(&foundry_toml) {
+        log::info!("Reading project layout from {}", foundry_toml.display());
+        match toml::from_str::<FoundryConfig>(&contents) {
+            Ok(config) => {
+                let profile = config.profile.default;
+                declared.extend(profile.remappings);
+                dirs.extend(profile.src);
+                dirs.extend(profile.libs.into_iter().flatten());
+            }
+            Err(e) => log::warn!("Ignoring {}: {}", foundry_toml.display(), e),
+        }
+    }
+
+    let mut remappings: Vec<String> = vec![];
+    for remapping in declared {
+        let Some((_, target)) = remapping.split_once(EQUAL) else {
+            log::warn!("Ignoring invalid remapping `{}`", remapping);
+            continue;
+        };
+        if !project_root.join(target).exists() {
+            log::warn!(
+                "Ignoring remapping `{}`: `{}` does not exist",
+                remapping,
+                project_root.join(target).display()
+            );
+            continue;
+        }
+        let repaired = repair_remapping(&remapping, project_root.to_str());
+        if !remappings
+            .iter()
+            .any(|r| remapping_prefix(r) == remapping_prefix(&repaired))
+        {
+            remappings.push(repaired);
+        }
+    }
+
+    let mut import_paths: Vec<String> = vec![];
+    for dir in dirs {
+        if let Ok(path) = project_root.join(dir).canonicalize() {
+            let path = path.to_str().unwrap().to_string();
+            if Path::new(&path).is_dir() && !import_paths.contains(&path) {
+                import_paths.push(path);
+            }
+        }
+    }
+    ProjectLayout {
+        remappings,
+        import_paths,
+    }
+}
+
+/// Combine the explicitly given `remappings` with the `discovered` remappings
+/// of a project. An explicit remapping overrides any discovered remapping of
+/// the same prefix.
+pub fn merge_discovered_remappings(remappings: &[String], discovered: &[String]) -> Vec<String> {
+    discovered
+        .iter()
+        .filter(|d| {
+            !remappings
+                .iter()
+                .any(|r| remapping_prefix(r) == remapping_prefix(d))
+        })
+        .chain(remappings)
+        .cloned()
+        .collect()
+}
+
+/// The names of the configuration files that `gambit mutate` discovers, in
+/// order of preference
+pub const CONFIGURATION_FILENAMES: [&str; 2] = ["gambit.json", "gambit.toml"];
+
+/// Find the configuration file of the project containing `dir`: the
+/// `gambit.json` (or else `gambit.toml`) of `dir` or of its closest ancestor
+/// that has one
+pub fn discover_configuration(dir: &Path) -> Option<PathBuf> {
+    let dir = dir.canonicalize().ok()?;
+    dir.ancestors()
+        .flat_map(|d| CONFIGURATION_FILENAMES.iter().map(move |name| d.join(name)))
+        .find(|path| path.is_file())
+}
+
+/// Translate the contents of a `gambit.toml` configuration file into the
+/// equivalent JSON configuration. The configurations of a file with several
+/// of them are listed in a `[[configurations]]` array of tables; otherwise,
+/// the top-level table is the only configuration.
+pub fn toml_configuration_to_json(contents: &str) -> Result<String, String> {
+    let value: Value = toml::from_str(contents).map_err(|e| e.to_string())?;
+    let value = match value {
+        Value::Object(mut table) if table.len() == 1 && table.contains_key("configurations") => {
+            table.remove("configurations").unwrap()
+        }
+        value => value,
+    };
+    serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+/// The `[context:]prefix` of a (repaired) remapping
+fn remapping_prefix(remapping: &str) -> &str {
+    remapping.split_once(EQUAL).map_or(remapping, |(p, _)| p)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resolve_import_mapped_filename() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let token = root.join("node_modules/@openzeppelin/contracts/token/ERC20.sol");
+        std::fs::create_dir_all(token.parent().unwrap()).unwrap();
+        std::fs::write(&token, "").unwrap();
+        let strings = |xs: &[&str]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        let resolve = |remappings: &[&str], search_dirs: &[&str]| {
+            resolve_import_mapped_filename(
+                "@openzeppelin/contracts/token/ERC20.sol",
+                &strings(remappings),
+                &strings(search_dirs),
+                &root,
+            )
+        };
+
+        // Through a remapping, with or without trailing slashes and contexts
+        assert_eq!(
+            resolve(&["@openzeppelin=node_modules/@openzeppelin"], &[]),
+            Some(token.clone())
+        );
+        assert_eq!(
+            resolve(&["src:@openzeppelin/=node_modules/@openzeppelin/"], &[]),
+            Some(token.clone())
+        );
+        // The longest matching prefix wins
+        assert_eq!(
+            resolve(
+                &[
+                    "@openzeppelin=elsewhere",
+                    "@openzeppelin/contracts=node_modules/@openzeppelin/contracts"
+                ],
+                &[]
+            ),
+            Some(token.clone())
+        );
+        // Through an include path
+        assert_eq!(resolve(&[], &["node_modules"]), Some(token.clone()));
+        // A prefix only matches whole path components
+        assert_eq!(resolve(&["@open=node_modules/@openzeppelin"], &[]), None);
+        assert_eq!(resolve(&[], &[]), None);
+    }
+
+    #[test]
+    fn test_discover_project_layout() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        for dir in [
+            "src",
+            "lib/openzeppelin-contracts",
+            "lib/forge-std/src",
+            "test",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let abs = |dir: &str| root.join(dir).to_str().unwrap().to_string();
+
+        // Nothing to read: only the conventional directories that exist
+        let layout = discover_project_layout(&root);
+        assert!(layout.remappings.is_empty());
+        assert_eq!(layout.import_paths, vec![abs("src"), abs("lib")]);
+
+        std::fs::write(
+            root.join("remappings.txt"),
+            "@openzeppelin/=lib/openzeppelin-contracts/\n\n# comment\nmissing/=lib/missing/\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("foundry.toml"),
+            "[profile.default]\nsrc = \"src\"\nlibs = [\"lib\", \"test\"]\nremappings = [\n  \"forge-std/=lib/forge-std/src/\",\n  \"@openzeppelin/=lib/forge-std/\",\n]\n",
+        )
+        .unwrap();
+        let layout = discover_project_layout(&root);
+        // Missing targets are dropped, and `remappings.txt` wins over
+        // `foundry.toml` for a shared prefix
+        assert_eq!(
+            layout.remappings,
+            vec![
+                format!("@openzeppelin={}", abs("lib/openzeppelin-contracts")),
+                format!("forge-std={}", abs("lib/forge-std/src")),
+            ]
+        );
+        assert_eq!(
+            layout.import_paths,
+            vec![abs("src"), abs("lib"), abs("test")]
+        );
+    }
+
+    #[test]
+    fn test_merge_discovered_remappings() {
+        let strings = |xs: &[&str]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        let discovered = strings(&["@oz=/p/lib/oz", "forge-std=/p/lib/forge-std/src"]);
+        assert_eq!(
+            merge_discovered_remappings(&strings(&["@oz=/elsewhere/oz"]), &discovered),
+            strings(&["forge-std=/p/lib/forge-std/src", "@oz=/elsewhere/oz"])
+        );
+        assert_eq!(merge_discovered_remappings(&[], &discovered), discovered);
+    }
+
+    #[test]
+    fn test_discover_configuration() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let nested = root.join("src/tokens");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(discover_configuration(&nested), None);
+
+        // The closest configuration wins, and `gambit.json` over `gambit.toml`
+        std::fs::write(root.join("gambit.toml"), "").unwrap();
+        assert_eq!(
+            discover_configuration(&nested),
+            Some(root.join("gambit.toml"))
+        );
+        std::fs::write(root.join("gambit.json"), "{}").unwrap();
+        assert_eq!(
+            discover_configuration(&nested),
+            Some(root.join("gambit.json"))
+        );
+        std::fs::write(root.join("src/gambit.toml"), "").unwrap();
+        assert_eq!(
+            discover_configuration(&nested),
+            Some(root.join("src/gambit.toml"))
+        );
+    }
+
+    #[test]
+    fn test_toml_configuration_to_json() {
+        let json = |toml: &str| {
+            serde_json::from_str::<Value>(&toml_configuration_to_json(toml).unwrap()).unwrap()
+        };
+        assert_eq!(
+            json("filename = \"C.sol\"\nnum_mutants = 3\nmutations = [\"binary-op-mutation\"]\n"),
+            serde_json::json!({
+                "filename": "C.sol",
+                "num_mutants": 3,
+                "mutations": ["binary-op-mutation"],
+            })
+        );
+        assert_eq!(
+            json("[[configurations]]\nfilename = \"A.sol\"\n\n[[configurations]]\nfilename = \"B.sol\"\n"),
+            serde_json::json!([{"filename": "A.sol"}, {"filename": "B.sol"}])
+        );
+        assert!(toml_configuration_to_json("filename = ").is_err());
+    }
+
+    #[test]
+    fn test_append_cli_values() {
+        let strings = |xs: &[&str]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        assert_eq!(append_cli_values(None, &[]), None);
+        assert_eq!(
+            append_cli_values(Some(strings(&["a"])), &[]),
+            Some(strings(&["a"]))
+        );
+        assert_eq!(
+            append_cli_values(None, &strings(&["b"])),
+            Some(strings(&["b"]))
+        );
+        assert_eq!(
+            append_cli_values(Some(strings(&["a", "b"])), &strings(&["b", "c"])),
+            Some(strings(&["a", "b", "c"]))
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("Vault", "Vault"), 0);
+        assert_eq!(edit_distance("Vault", "vault"), 1);
+        assert_eq!(edit_distance("Vualt", "Vault"), 1);
+        assert_eq!(edit_distance("getarry", "getarray"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_suggest_name() {
+        let names: Vec<String> = ["Vault", "VaultFactory", "getarray", "get10PowerDecimals"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        // Names differing in case are preferred
+        assert_eq!(suggest_name("vault", &names), Some("Vault"));
+        assert_eq!(suggest_name("VAULTFACTORY", &names), Some("VaultFactory"));
+        // Typos
+        assert_eq!(suggest_name("Vualt", &names), Some("Vault"));
+        assert_eq!(suggest_name("getarry", &names), Some("getarray"));
+        assert_eq!(
+            suggest_name("get10PowerDecimal", &names),
+            Some("get10PowerDecimals")
+        );
+        // Names that are too different are not suggested
+        assert_eq!(suggest_name("Token", &names), None);
+        assert_eq!(suggest_name("V", &names), None);
+    }
+
     #[test]
     fn test_get_indent1() {
         let s = "";
@@ -313,6 +861,46 @@ mod tests {
     use crate::simplify_path;
     use std::path::PathBuf;
 
+    #[test]
+    fn test_resolve_executable() {
+        let sh = resolve_executable("sh").expect("sh should be on the PATH");
+        assert!(sh.is_absolute());
+        assert_eq!(resolve_executable(sh.to_str().unwrap()), Some(sh));
+        assert_eq!(resolve_executable("gambit-no-such-executable"), None);
+    }
+
+    #[test]
+    fn test_normalize_remappings_benign_duplicate() {
+        let remappings = vec![
+            "@oz=/project/lib/openzeppelin".to_string(),
+            "solmate=/project/lib/solmate/src".to_string(),
+            "@oz=/project/lib/openzeppelin".to_string(),
+        ];
+        let normalized = normalize_remappings(&remappings, false).unwrap();
+        assert_eq!(
+            normalized,
+            vec![
+                "@oz=/project/lib/openzeppelin".to_string(),
+                "solmate=/project/lib/solmate/src".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_remappings_conflict() {
+        let remappings = vec![
+            "@oz=/project/lib/openzeppelin".to_string(),
+            "@oz=/project/node_modules/@openzeppelin".to_string(),
+        ];
+        assert!(normalize_remappings(&remappings, false).is_err());
+
+        let normalized = normalize_remappings(&remappings, true).unwrap();
+        assert_eq!(
+            normalized,
+            vec!["@oz=/project/node_modules/@openzeppelin".to_string()]
+        );
+    }
+
     #[test]
     pub fn test_simplify_path() {
         assert_simplifed_path("benchmarks/10Power", "benchmarks/10Power");
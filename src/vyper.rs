@@ -0,0 +1,301 @@
+//! Experimental support for mutating Vyper contracts (behind the `vyper`
+//! feature).
+//!
+//! Vyper sources are parsed with `vyper -f ast`, whose JSON AST is wrapped in a
+//! `SolAST` so that it can be traversed like a Solidity AST. Vyper nodes are
+//! identified by their `ast_type` and, like Solidity nodes, carry their
+//! location in a `src` field. Only a reduced set of mutation operators is
+//! supported (see `VYPER_MUTATION_OPERATORS`).
+
+use crate::{
+    invoke_command, parse_json_unbounded, report, Diagnostic, DiagnosticCode, GambitError, Mutant,
+    MutantWriter, MutationType, MutatorConf, SolAST, SolASTVisitor, Source,
+};
+use std::{error, path::Path, rc::Rc, string::FromUtf8Error};
+
+/// The mutation operators that can be applied to Vyper sources
+pub const VYPER_MUTATION_OPERATORS: [MutationType; 4] = [
+    MutationType::BinaryOpMutation,
+    MutationType::BoundaryLiteralMutation,
+    MutationType::IfStatementMutation,
+    MutationType::RequireMutation,
+];
+
+/// Parse a Vyper source file with `vyper -f ast`
+pub fn compile_vyper_ast(vyper: &str, filename: &Path) -> Result<SolAST, Box<dyn error::Error>> {
+    let (code, stdout, stderr) =
+        invoke_command(vyper, vec!["-f", "ast", filename.to_str().unwrap()])
+            .map_err(|e| GambitError::Solc(e.to_string()))?;
+    if code != Some(0) {
+        return Err(format!(
+            "Failed to parse {} with {}: {}",
+            filename.display(),
+            vyper,
+            String::from_utf8_lossy(&stderr)
+        )
+        .into());
+    }
+    let output = parse_json_unbounded(stdout.as_slice())?;
+    Ok(SolAST::new(output["ast"].clone()))
+}
+
+/// Validate a Vyper mutant by compiling it with `vyper`
+pub fn validate_vyper_mutant(vyper: &str, mutant: &Mutant) -> Result<bool, Box<dyn error::Error>> {
+    let source_parent_dir = mutant.source.filename().parent().unwrap();
+    let mutant_file = tempfile::Builder::new()
+        .suffix(".vy")
+        .tempfile_in(source_parent_dir)?;
+    MutantWriter::write_mutant_to_file(mutant_file.path(), mutant)?;
+    let (code, _, _) = invoke_command(vyper, vec![mutant_file.path().to_str().unwrap()])?;
+    Ok(code == Some(0))
+}
+
+/// Mutate a Vyper source given its AST (as produced by `vyper -f ast`). Only
+/// the operators of `conf` that support Vyper are applied, and only the
+/// functions selected by `conf.funcs_to_mutate` and not in
+/// `conf.exclude_functions` are mutated.
+pub fn mutate_vyper_ast(ast: SolAST, source: Rc<Source>, conf: &MutatorConf) -> Vec<Mutant> {
+    let mut ast = ast;
+    let pruned = ast.prune_deeper_than(conf.max_ast_depth);
+    if pruned > 0 {
+        report(
+            Diagnostic::new(
+                DiagnosticCode::DeepSubtreesPruned,
+                format!(
+                    "Not mutating {} subtrees of {} nested more than {} levels deep (see --max_ast_depth)",
+                    pruned,
+                    source.filename().display(),
+                    conf.max_ast_depth
+                ),
+            )
+            .with_file(source.filename().display()),
+        );
+    }
+    let ops: Vec<MutationType> = conf
+        .mutation_operators
+        .iter()
+        .filter(|op| VYPER_MUTATION_OPERATORS.contains(op))
+        .copied()
+        .collect();
+    let mutator = VyperMutator {
+        ops,
+        funcs_to_mutate: conf.funcs_to_mutate.clone(),
+        exclude_functions: conf.exclude_functions.clone(),
+    };
+    ast.traverse(&mutator, source)
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Applies mutation operators to Vyper AST nodes
+struct VyperMutator {
+    ops: Vec<MutationType>,
+    funcs_to_mutate: Option<Vec<String>>,
+    exclude_functions: Option<Vec<String>>,
+}
+
+impl SolASTVisitor<Rc<Source>, Vec<Mutant>> for VyperMutator {
+    fn skip_node(&self, node: &SolAST, _source: &Rc<Source>) -> bool {
+        if ast_type(node).as_deref() != Some("FunctionDef") {
+            return false;
+        }
+        let name = node.get_string("name");
+        let listed = |fns: &Vec<String>| name.as_ref().is_some_and(|name| fns.contains(name));
+        self.exclude_functions.as_ref().is_some_and(listed)
+            || self
+                .funcs_to_mutate
+                .as_ref()
+                .is_some_and(|fns| !listed(fns))
+    }
+
+    fn visit_node(&self, node: &SolAST, source: &Rc<Source>) -> Option<Vec<Mutant>> {
+        let mut mutants = vec![];
+        for op in self.ops.iter() {
+            match mutate_vyper_node(*op, node, source.clone()) {
+                Ok(mut op_mutants) => mutants.append(&mut op_mutants),
+                Err(e) => log::warn!("Skipping {} candidate: {}", op.to_string(), e),
+            }
+        }
+        Some(mutants)
+    }
+}
+
+/// The type of a Vyper AST node (e.g., `BinOp`)
+fn ast_type(node: &SolAST) -> Option<String> {
+    node.get_string("ast_type")
+}
+
+/// Generate the mutants of a Vyper AST node by a single operator
+fn mutate_vyper_node(
+    op: MutationType,
+    node: &SolAST,
+    source: Rc<Source>,
+) -> Result<Vec<Mutant>, FromUtf8Error> {
+    let mutant = |start: usize, end: usize, repl: &str| {
+        Mutant::new(source.clone(), op, start, end, repl.to_string()).map(|mut m| {
+            m.language = Some("vyper".to_string());
+            m
+        })
+    };
+    let node_type = ast_type(node);
+    let mutants: Vec<Result<Mutant, FromUtf8Error>> = match (op, node_type.as_deref()) {
+        (MutationType::BinaryOpMutation, Some("BinOp")) => {
+            // The operator is replaced along with its surrounding whitespace
+            let (_, endl) = node.get_node("left").get_bounds();
+            let (startr, _) = node.get_node("right").get_bounds();
+            let orig = String::from_utf8(source.contents()[endl..startr].to_vec())?;
+            ["+", "-", "*", "/", "%", "**"]
+                .iter()
+                .filter(|r| orig.trim() != **r)
+                .map(|r| mutant(endl, startr, r))
+                .collect()
+        }
+        (MutationType::BoundaryLiteralMutation, Some("Compare")) => {
+            let mut mutants = vec![];
+            for literal in [node.get_node("left"), node.get_node("right")] {
+                if ast_type(&literal).as_deref() != Some("Int") {
+                    continue;
+                }
+                let value = literal.get_object().and_then(|o| o["value"].as_i64());
+                if let Some(value) = value {
+                    let (start, end) = literal.get_bounds();
+                    for r in [value.checked_add(1), value.checked_sub(1)]
+                        .into_iter()
+                        .flatten()
+                    {
+                        mutants.push(mutant(start, end, &r.to_string()));
+                    }
+                }
+            }
+            mutants
+        }
+        (MutationType::IfStatementMutation, Some("If"))
+        | (MutationType::RequireMutation, Some("Assert")) => {
+            let test = node.get_node("test");
+            let orig = test.get_text(source.contents())?;
+            let (start, end) = test.get_bounds();
+            ["True", "False"]
+                .iter()
+                .filter(|r| orig != **r)
+                .map(|r| mutant(start, end, r))
+                .collect()
+        }
+        _ => vec![],
+    };
+    mutants.into_iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::path::PathBuf;
+
+    /// A Vyper AST node of type `ast_type` spanning the first occurrence of
+    /// `text` in `source`
+    fn node(source: &str, text: &str, ast_type: &str, fields: Value) -> Value {
+        let start = source.find(text).unwrap();
+        let mut node = json!({"ast_type": ast_type, "src": format!("{}:{}:0", start, text.len())});
+        node.as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        node
+    }
+
+    #[test]
+    fn test_mutate_vyper_ast() {
+        let source = "@external\ndef f(x: uint256) -> uint256:\n    assert x < 10\n    if x > 0:\n        return x + 1\n    return 0\n";
+        let name = |n: &str| node(source, n, "Name", json!({"id": n}));
+        let int = |text: &str, value: i64| node(source, text, "Int", json!({"value": value}));
+        let compare = |text: &str, op: &str, left: Value, right: Value| {
+            node(
+                source,
+                text,
+                "Compare",
+                json!({"op": {"ast_type": op}, "left": left, "right": right}),
+            )
+        };
+        let mut ast = node(
+            source,
+            source,
+            "Module",
+            json!({"body": [node(source, "def f", "FunctionDef", json!({"name": "f", "body": [
+                node(source, "assert x < 10", "Assert", json!({
+                    "test": compare("x < 10", "Lt", name("x"), int("10", 10))
+                })),
+                node(source, "if x > 0:\n        return x + 1", "If", json!({
+                    "test": compare("x > 0", "Gt", name("x"), int("0", 0)),
+                    "body": [node(source, "return x + 1", "Return", json!({
+                        "value": node(source, "x + 1", "BinOp", json!({
+                            "op": {"ast_type": "Add"},
+                            "left": name("x"),
+                            "right": int("1\n", 1),
+                        }))
+                    }))]
+                })),
+            ]}))]}),
+        );
+        // Names and literals are found by their first occurrence: fix up the
+        // operands of `x + 1`
+        let binop = &mut ast["body"][0]["body"][1]["body"][0]["value"];
+        let plus = source.find("x + 1").unwrap();
+        binop["left"]["src"] = json!(format!("{}:1:0", plus));
+        binop["right"]["src"] = json!(format!("{}:1:0", plus + 4));
+
+        let source = Rc::new(Source::from_contents(
+            PathBuf::from("C.vy"),
+            PathBuf::new(),
+            source.as_bytes().to_vec(),
+        ));
+        let conf = MutatorConf {
+            mutation_operators: vec![
+                MutationType::BinaryOpMutation,
+                MutationType::BoundaryLiteralMutation,
+                MutationType::IfStatementMutation,
+                MutationType::RequireMutation,
+                // Not supported for Vyper
+                MutationType::DeleteExpressionMutation,
+            ],
+            ..MutatorConf::default()
+        };
+        let mutants = mutate_vyper_ast(SolAST::new(ast.clone()), source.clone(), &conf);
+        let actual: Vec<(String, &str, &str)> = mutants
+            .iter()
+            .map(|m| (m.op.to_string(), m.orig.trim(), m.repl.as_str()))
+            .collect();
+        let expected: Vec<(String, &str, &str)> = vec![
+            ("RequireMutation", "x < 10", "True"),
+            ("RequireMutation", "x < 10", "False"),
+            ("BoundaryLiteralMutation", "10", "11"),
+            ("BoundaryLiteralMutation", "10", "9"),
+            ("IfStatementMutation", "x > 0", "True"),
+            ("IfStatementMutation", "x > 0", "False"),
+            // The `If` node's `body` is traversed before its `test`
+            ("BinaryOpMutation", "+", "-"),
+            ("BinaryOpMutation", "+", "*"),
+            ("BinaryOpMutation", "+", "/"),
+            ("BinaryOpMutation", "+", "%"),
+            ("BinaryOpMutation", "+", "**"),
+            ("BoundaryLiteralMutation", "0", "1"),
+            ("BoundaryLiteralMutation", "0", "-1"),
+        ]
+        .into_iter()
+        .map(|(op, orig, repl)| (op.to_string(), orig, repl))
+        .collect();
+        assert_eq!(actual, expected);
+        assert!(mutants
+            .iter()
+            .all(|m| m.language.as_deref() == Some("vyper")));
+        assert!(mutants[6]
+            .as_source_string()
+            .unwrap()
+            .contains("return x-1"));
+
+        let conf = MutatorConf {
+            funcs_to_mutate: Some(vec!["g".into()]),
+            ..conf
+        };
+        assert!(mutate_vyper_ast(SolAST::new(ast), source, &conf).is_empty());
+    }
+}
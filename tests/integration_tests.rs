@@ -1,8 +1,18 @@
 use ansi_term::{Color, Style};
-use gambit::MutateParams;
+use gambit::{
+    drop_cross_entry_duplicates, GambitError, Mutant, MutateParams, MutationType, Source,
+    ValidationMode, EXIT_CONFIGURATION_ERROR, EXIT_FILE_RESOLUTION_ERROR, EXIT_INTERRUPTED,
+    EXIT_NO_MUTANTS, EXIT_SOLC_ERROR,
+};
 use project_root::get_project_root;
 use serde_json;
-use std::{collections::HashSet, env, error, path::PathBuf};
+use std::{
+    collections::HashSet,
+    env, error,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 /// All test cases are generated by running Gambit on the individual files
 /// listed in `all.gconf` and then using `scripts/mutants_log_to_regression.py`
@@ -94,12 +104,6 @@ fn multiple_contracts_1() {
         "multiple-contracts-1.gconf",
         &vec![
             //C.get10PowerDecimals
-            (
-                "DeleteExpressionMutation",
-                "assert(c[0] == e)",
-                "assert(true)",
-                (7, 9),
-            ),
             ("BinaryOpMutation", "**", "+", (24, 24)),
             ("BinaryOpMutation", "**", "-", (24, 24)),
             ("BinaryOpMutation", "**", "*", (24, 24)),
@@ -111,12 +115,6 @@ fn multiple_contracts_1() {
                 "decimals ** a",
                 (24, 23),
             ),
-            (
-                "DeleteExpressionMutation",
-                "assert(c[0] == e)",
-                "assert(true)",
-                (29, 9),
-            ),
         ],
     )
 }
@@ -154,12 +152,6 @@ fn multiple_contracts_3() {
         &vec![
             /* Utils.getarray */
             /* Utils.add */
-            (
-                "DeleteExpressionMutation",
-                "assert(c[0] == e)",
-                "assert(true)",
-                (7, 9),
-            ),
             ("BinaryOpMutation", "+", "-", (11, 17)),
             ("BinaryOpMutation", "+", "*", (11, 17)),
             ("BinaryOpMutation", "+", "/", (11, 17)),
@@ -182,12 +174,6 @@ fn multiple_contracts_3() {
                 "decimals ** a",
                 (24, 23),
             ),
-            (
-                "DeleteExpressionMutation",
-                "assert(c[0] == e)",
-                "assert(true)",
-                (29, 9),
-            ),
             (
                 "DeleteExpressionMutation",
                 "Utils.getarray(b, address(this))",
@@ -228,6 +214,1159 @@ fn multiple_contracts_4() {
     )
 }
 
+/// Configurations that share an output directory but use different solc
+/// binaries record which solc produced each entry of `gambit_results.json`
+#[cfg(unix)]
+#[test]
+fn mixed_solc() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = tempfile::tempdir().unwrap();
+    // A second solc binary: a wrapper around the `solc` on the PATH
+    let wrapper = tmp.path().join("solc-wrapper");
+    std::fs::write(&wrapper, "#!/bin/sh\nexec solc \"$@\"\n").unwrap();
+    std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let wrapper = wrapper
+        .canonicalize()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let outdir = tmp.path().join("gambit_out").to_str().unwrap().to_string();
+    let mut mutate_params = get_config_json("multiple-contracts-1.gconf").unwrap();
+    for params in mutate_params.iter_mut() {
+        params.outdir = Some(outdir.clone());
+    }
+    mutate_params[1].solc = wrapper.clone();
+    // `Utils.getarray` only contains an `assert`, which is not deleted by default
+    mutate_params[0].delete_expression_exclusions = Some(vec![]);
+
+    assert_eq!(
+        gambit::outdirs_with_mixed_solc(&mutate_params),
+        vec![(outdir.clone(), vec!["solc".to_string(), wrapper.clone()])]
+    );
+
+    gambit::run_mutate(mutate_params).unwrap();
+    let results = std::fs::read_to_string(PathBuf::from(&outdir).join("gambit_results.json"));
+    let results: serde_json::Value = serde_json::from_str(&results.unwrap()).unwrap();
+    let solcs: Vec<&str> = results["mutants"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["solc"].as_str().unwrap())
+        .collect();
+    let solc = gambit::resolve_executable("solc").unwrap();
+    // One mutant of `Utils.getarray` and six of `C.get10PowerDecimals`
+    let mut expected = vec![solc.to_str().unwrap()];
+    expected.extend([wrapper.as_str(); 6]);
+    assert_eq!(solcs, expected);
+    assert!(results["mutants"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|e| e["solc_version"].is_string()));
+    // Each configuration entry is a mutation session with its own solc
+    let files = results["files"].as_array().unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0]["solc"], solc.to_str().unwrap());
+    assert_eq!(files[1]["solc"], wrapper.as_str());
+}
+
+/// Two-stage validation accepts exactly the mutants that full validation
+/// accepts, and fast validation accepts at least those
+#[test]
+fn validation_modes_agree() {
+    let tmp = tempfile::tempdir().unwrap();
+    let valid_mutants = |mode: ValidationMode| {
+        let outdir = tmp.path().join(format!("{:?}", mode));
+        let mut mutate_params = get_config_json("all.gconf").unwrap();
+        for params in mutate_params.iter_mut() {
+            params.outdir = Some(outdir.to_str().unwrap().to_string());
+            params.skip_validate = false;
+            params.validation_mode = mode;
+        }
+        let results = gambit::run_mutate(mutate_params).unwrap();
+        let mutants: HashSet<(String, String, usize, String)> = results
+            .values()
+            .flatten()
+            .map(|m| {
+                let file = m.source.filename_as_str();
+                (m.op.to_string(), file, m.start, m.repl.clone())
+            })
+            .collect();
+        let results = std::fs::read_to_string(outdir.join("gambit_results.json")).unwrap();
+        let results: serde_json::Value = serde_json::from_str(&results).unwrap();
+        (mutants, results["files"].as_array().unwrap().clone())
+    };
+    let (full, files) = valid_mutants(ValidationMode::Full);
+    assert!(files
+        .iter()
+        .all(|f| f["statistics"].get("validation_stages").is_none()));
+
+    let (two_stage, files) = valid_mutants(ValidationMode::TwoStage);
+    assert_eq!(two_stage, full);
+    for file in files.iter() {
+        let stages = &file["statistics"]["validation_stages"];
+        let checked = stages["syntax_checks"].as_u64().unwrap();
+        let parsed = checked - stages["rejected_by_syntax_check"].as_u64().unwrap();
+        assert_eq!(stages["compilations"].as_u64().unwrap(), parsed);
+    }
+
+    let (fast, _) = valid_mutants(ValidationMode::Fast);
+    assert!(fast.is_superset(&full));
+}
+
+/// `generate_mutants()` returns mutants in memory, without touching the
+/// output directory
+#[test]
+fn in_memory_mutants() {
+    let tmp = tempfile::tempdir().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let mut params = get_config_json("all.gconf").unwrap().remove(0);
+    params.outdir = Some(outdir.to_str().unwrap().to_string());
+    params.num_mutants = Some(2);
+    let mutants = gambit::generate_mutants(&params).unwrap();
+    assert_eq!(mutants.len(), 2);
+    assert!(!outdir.exists());
+    for mutant in mutants.iter() {
+        assert!(mutant.as_source_string().unwrap().contains(&mutant.repl));
+        let json = serde_json::to_value(mutant).unwrap();
+        assert_eq!(json["source"], mutant.source.filename_as_str().as_str());
+        assert_eq!(json["op"], mutant.op.to_string().as_str());
+    }
+}
+
+/// `generate_mutants()` reports a filename or sourceroot that it cannot
+/// resolve as an error instead of panicking
+#[test]
+fn in_memory_mutants_unresolved_paths() {
+    let tmp = tempfile::tempdir().unwrap();
+    let outside = tmp.path().join("Outside.sol");
+    std::fs::copy("benchmarks/10Power/TenPower.sol", &outside).unwrap();
+    for (filename, sourceroot) in [
+        (None, None),
+        (Some("benchmarks/NoSuchFile.sol"), None),
+        (
+            Some("benchmarks/10Power/TenPower.sol"),
+            Some("benchmarks/NoSuchDir"),
+        ),
+        (
+            Some("benchmarks/10Power/TenPower.sol"),
+            Some("benchmarks/BinaryOpMutation"),
+        ),
+        // Without a sourceroot, the filename must be in the current directory
+        (Some(outside.to_str().unwrap()), None),
+    ] {
+        let params: MutateParams = serde_json::from_value(serde_json::json!({
+            "filename": filename,
+            "sourceroot": sourceroot,
+            "skip_validate": true,
+        }))
+        .unwrap();
+        match gambit::generate_mutants(&params) {
+            Err(GambitError::FileResolution(_)) => {}
+            other => panic!(
+                "{:?} in {:?}: {:?}",
+                filename,
+                sourceroot,
+                other.map(|m| m.len())
+            ),
+        }
+    }
+}
+
+/// Failures are reported with distinct exit codes (see `EXIT_CODES_HELP`)
+#[test]
+fn exit_codes() {
+    let tenpower = "benchmarks/10Power/TenPower.sol";
+    assert_eq!(
+        gambit_exit_code(&["--filename", tenpower, "--num_mutants", "0"]),
+        Some(EXIT_CONFIGURATION_ERROR)
+    );
+    assert_eq!(
+        gambit_exit_code(&[
+            "--filename",
+            tenpower,
+            "--solc_remappings",
+            "a=benchmarks",
+            "a=resources",
+        ]),
+        Some(EXIT_CONFIGURATION_ERROR)
+    );
+    assert_eq!(
+        gambit_exit_code(&["--filename", "benchmarks/NoSuchFile.sol"]),
+        Some(EXIT_FILE_RESOLUTION_ERROR)
+    );
+    assert_eq!(
+        gambit_exit_code(&["--filename", tenpower, "--project_root", "no/such/root"]),
+        Some(EXIT_FILE_RESOLUTION_ERROR)
+    );
+    for path_arg in [
+        "--solc_allow_paths",
+        "--solc_include_path",
+        "--solc_base_path",
+    ] {
+        assert_eq!(
+            gambit_exit_code(&["--filename", tenpower, path_arg, "no/such/path"]),
+            Some(EXIT_FILE_RESOLUTION_ERROR)
+        );
+    }
+    assert_eq!(
+        gambit_exit_code(&["--filename", tenpower, "--solc", "/no/such/solc"]),
+        Some(EXIT_SOLC_ERROR)
+    );
+    // `TenPower` calls no functions, so `function-call-mutation` generates
+    // no mutants
+    assert_eq!(
+        gambit_exit_code(&[
+            "--filename",
+            tenpower,
+            "--mutations",
+            "function-call-mutation",
+            "--fail_on_zero_mutants",
+        ]),
+        Some(EXIT_NO_MUTANTS)
+    );
+}
+
+/// `gambit check` fails a configuration naming a function that the contract
+/// does not define, without writing to the output directory
+#[test]
+fn check_unknown_function() {
+    let mut params = get_config_json("multiple-contracts-2.gconf").unwrap();
+    let outdir = PathBuf::from(params[0].outdir.clone().unwrap());
+    if outdir.exists() {
+        std::fs::remove_dir_all(&outdir).unwrap();
+    }
+    params[0].functions = Some(vec!["ad".into()]);
+    let report = gambit::run_check(params).unwrap();
+    assert!(!report.passed());
+    let failures: Vec<&str> = report
+        .checks
+        .iter()
+        .filter_map(|c| c.failure.as_deref())
+        .collect();
+    assert_eq!(failures, vec!["unknown function `ad`; did you mean `add`?"]);
+    assert!(!outdir.exists());
+}
+
+/// A `--functions` name that no mutated file defines is warned about, listing
+/// the functions that could have been meant, and fails the run with `--strict`
+#[test]
+fn unknown_function_name() {
+    let tenpower = "benchmarks/10Power/TenPower.sol";
+    let args = ["--filename", tenpower, "--functions", "get10PowerDecimal"];
+    let diagnostics = gambit_json_diagnostics(&args);
+    let warning = diagnostics.iter().find(|d| d["code"] == "W0019").unwrap();
+    assert_eq!(warning["level"], "warning");
+    assert!(warning["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("--functions `get10PowerDecimal` matched nothing"));
+    assert_eq!(
+        warning["notes"],
+        serde_json::json!(["Available functions: get10PowerDecimals"])
+    );
+    assert_eq!(gambit_exit_code(&args), Some(0));
+
+    let strict = [&args[..], &["--strict"]].concat();
+    assert_eq!(gambit_exit_code(&strict), Some(EXIT_CONFIGURATION_ERROR));
+    let args = ["--filename", tenpower, "--contract", "TenPowr", "--strict"];
+    assert_eq!(gambit_exit_code(&args), Some(EXIT_CONFIGURATION_ERROR));
+}
+
+/// Run `gambit mutate` with `args` from the project root, writing to a
+/// temporary output directory, and return its exit code
+fn gambit_exit_code(args: &[&str]) -> Option<i32> {
+    let outdir = tempfile::tempdir().unwrap();
+    std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .current_dir(get_project_root().unwrap())
+        .arg("mutate")
+        .args(args)
+        .arg("--outdir")
+        .arg(outdir.path())
+        .output()
+        .unwrap()
+        .status
+        .code()
+}
+
+/// With `--message-format json`, errors are printed on stderr as single-line
+/// JSON objects with stable codes
+#[test]
+fn message_format_json() {
+    let tenpower = "benchmarks/10Power/TenPower.sol";
+    let diagnostics = gambit_json_diagnostics(&["--filename", tenpower, "--num_mutants", "0"]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["level"], "error");
+    assert_eq!(diagnostics[0]["code"], "E0002");
+    assert!(diagnostics[0]["message"].is_string());
+
+    let diagnostics = gambit_json_diagnostics(&["--filename", "benchmarks/NoSuchFile.sol"]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "E0003");
+
+    let diagnostics = gambit_json_diagnostics(&["--filename", tenpower, "--solc", "/no/such/solc"]);
+    assert!(diagnostics.iter().all(|d| d["level"] == "error"));
+    assert_eq!(diagnostics.first().unwrap()["code"], "E0004");
+    assert_eq!(diagnostics.first().unwrap()["file"], tenpower);
+    assert_eq!(diagnostics.last().unwrap()["code"], "E0004");
+}
+
+/// Experimental operators are a configuration error without
+/// `--allow_experimental`, and are warned about once with it
+#[test]
+fn experimental_operators() {
+    let tenpower = "benchmarks/10Power/TenPower.sol";
+    let experimental = "swap-arguments-function-mutation";
+    let args = ["--filename", tenpower, "--mutations", experimental];
+    assert_eq!(gambit_exit_code(&args), Some(EXIT_CONFIGURATION_ERROR));
+    let diagnostics = gambit_json_diagnostics(&args);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "E0002");
+    let message = diagnostics[0]["message"].as_str().unwrap();
+    assert!(message.contains(experimental));
+    assert!(message.contains("--allow_experimental"));
+
+    let diagnostics = gambit_json_diagnostics(&[
+        "--filename",
+        tenpower,
+        "--mutations",
+        experimental,
+        experimental,
+        "--allow_experimental",
+        "--solc",
+        "/no/such/solc",
+    ]);
+    assert_eq!(diagnostics[0]["code"], "W0014");
+    assert_eq!(
+        diagnostics.iter().filter(|d| d["code"] == "W0014").count(),
+        1
+    );
+}
+
+/// Run `gambit --message-format json mutate` with `args` like
+/// `gambit_exit_code()`, and parse each line it prints on stderr
+fn gambit_json_diagnostics(args: &[&str]) -> Vec<serde_json::Value> {
+    let outdir = tempfile::tempdir().unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .current_dir(get_project_root().unwrap())
+        .args(["--message-format", "json", "mutate"])
+        .args(args)
+        .arg("--outdir")
+        .arg(outdir.path())
+        .output()
+        .unwrap();
+    String::from_utf8(output.stderr)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+/// `gambit summary --format json` prints the statistics and selected mutants
+/// of a run as a single JSON object
+#[test]
+fn summary_json() {
+    let root = get_project_root().unwrap();
+    let summary = |args: &[&str]| -> serde_json::Value {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+            .current_dir(&root)
+            .args(["summary", "--format", "json"])
+            .args(["--mutation-directory", "resources/summary"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(!stdout.contains('\x1b'));
+        serde_json::from_str(&stdout).unwrap()
+    };
+
+    let json = summary(&["--mids", "1", "6"]);
+    assert_eq!(json["group_by"], "operator");
+    assert_eq!(json["total_mutants"], 6);
+    assert_eq!(json["counts"]["BinaryOpMutation"], 5);
+    assert_eq!(json["counts"]["FunctionCallMutation"], 0);
+    let mutants = json["mutants"].as_array().unwrap();
+    assert_eq!(mutants.len(), 2);
+    assert_eq!(mutants[0]["mid"], "1");
+    assert_eq!(mutants[0]["op_short"], "binary-op-mutation");
+    assert_eq!(mutants[0]["op_long"], "BinaryOpMutation");
+    assert_eq!(mutants[0]["repl"], "+");
+    assert_eq!(mutants[0]["path"], "mutants/1/10Power/TenPower.sol");
+    assert!(mutants[0]["diff"].as_str().unwrap().contains("a+decimals"));
+    assert_eq!(mutants[1]["op_long"], "SwapArgumentsOperatorMutation");
+
+    // Only statistics are printed with `--statistics`
+    let json = summary(&["--statistics"]);
+    assert_eq!(json["total_mutants"], 6);
+    assert!(json.get("mutants").is_none());
+}
+
+/// When an entry of a configuration fails, the mutants of the entries before
+/// it in the same output directory are still exported and logged
+#[test]
+fn partial_outputs() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = get_project_root().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let entry = |solc: &str| {
+        serde_json::json!({
+            "filename": root.join("benchmarks/10Power/TenPower.sol").to_str().unwrap(),
+            "sourceroot": root.join("benchmarks").to_str().unwrap(),
+            "mutations": ["binary-op-mutation"],
+            "solc": solc,
+            "outdir": outdir.to_str().unwrap(),
+        })
+    };
+    let conf = tmp.path().join("partial.json");
+    let entries = serde_json::json!([entry("solc"), entry("/no/such/solc"), entry("solc")]);
+    std::fs::write(&conf, entries.to_string()).unwrap();
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .args(["mutate", "--json"])
+        .arg(&conf)
+        .output()
+        .unwrap()
+        .status;
+    assert_eq!(status.code(), Some(EXIT_SOLC_ERROR));
+    let log = std::fs::read_to_string(outdir.join("mutants.log")).unwrap();
+    assert!(log.lines().count() > 0);
+    assert!(log.lines().all(|l| l.contains("10Power/TenPower.sol")));
+    assert!(outdir.join("mutants/1/10Power/TenPower.sol").is_file());
+    assert!(outdir.join("gambit_results.json").is_file());
+}
+
+/// Settings that apply to a whole output directory must be the same for all
+/// entries writing to it
+#[test]
+fn outdir_settings_must_agree() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = get_project_root().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let entry = |settings: serde_json::Value| {
+        let mut entry = serde_json::json!({
+            "filename": root.join("benchmarks/10Power/TenPower.sol").to_str().unwrap(),
+            "sourceroot": root.join("benchmarks").to_str().unwrap(),
+            "outdir": outdir.to_str().unwrap(),
+        });
+        entry
+            .as_object_mut()
+            .unwrap()
+            .extend(settings.as_object().unwrap().clone());
+        entry
+    };
+    let status = |entries: serde_json::Value| {
+        let conf = tmp.path().join("outdir.json");
+        std::fs::write(&conf, entries.to_string()).unwrap();
+        std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+            .args(["mutate", "--json"])
+            .arg(&conf)
+            .output()
+            .unwrap()
+            .status
+            .code()
+    };
+    let readme = root.join("README.md").to_str().unwrap().to_string();
+    let disagreeing = [
+        (
+            serde_json::json!({"export_full_project": true}),
+            serde_json::json!({}),
+        ),
+        (
+            serde_json::json!({"export_full_project": true, "project_root": root.join("benchmarks").to_str().unwrap()}),
+            serde_json::json!({"export_full_project": true, "project_root": root.to_str().unwrap()}),
+        ),
+        (
+            serde_json::json!({"copy_into_mutants": [readme]}),
+            serde_json::json!({}),
+        ),
+        (
+            serde_json::json!({"copy_into_mutants": [readme], "link_into_mutants": true}),
+            serde_json::json!({"copy_into_mutants": [readme]}),
+        ),
+        (
+            serde_json::json!({"export_format": "patch"}),
+            serde_json::json!({"export_format": "full"}),
+        ),
+    ];
+    for (first, second) in disagreeing {
+        assert_eq!(
+            status(serde_json::json!([entry(first), entry(second)])),
+            Some(EXIT_CONFIGURATION_ERROR)
+        );
+        assert!(!outdir.exists());
+    }
+}
+
+/// Remappings given on the command line apply to the entries of a
+/// configuration file that don't have them
+#[test]
+fn cli_remappings_with_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().canonicalize().unwrap();
+    std::fs::create_dir_all(root.join("lib")).unwrap();
+    std::fs::write(
+        root.join("lib/Math.sol"),
+        "// SPDX-License-Identifier: GPL-3.0-only\npragma solidity ^0.8.13;\n\nlibrary Math {\n    function add(uint256 a, uint256 b) internal pure returns (uint256) {\n        return a + b;\n    }\n}\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(
+        root.join("src/C.sol"),
+        "// SPDX-License-Identifier: GPL-3.0-only\npragma solidity ^0.8.13;\n\nimport \"@math/Math.sol\";\n\ncontract C {\n    function f(uint256 x) public pure returns (uint256) {\n        return Math.add(x, 1) * 2;\n    }\n}\n",
+    )
+    .unwrap();
+    let conf = root.join("gambit.json");
+    let entry = serde_json::json!({
+        "filename": "src/C.sol",
+        "sourceroot": ".",
+        "mutations": ["binary-op-mutation"],
+        "solc_allow_paths": ["lib"],
+        "skip_validate": true,
+    });
+    std::fs::write(&conf, entry.to_string()).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .current_dir(&root)
+        .args(["mutate", "--json", "gambit.json"])
+        .args(["--solc_remappings", "@math=lib"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let results: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(root.join("gambit_out/gambit_results.json")).unwrap(),
+    )
+    .unwrap();
+    assert!(!results["mutants"].as_array().unwrap().is_empty());
+}
+
+/// A filename given as a remapped import path is mutated like the file it
+/// resolves to
+#[test]
+fn remapped_filename() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().canonicalize().unwrap();
+    let lib = root.join("node_modules/@math");
+    std::fs::create_dir_all(&lib).unwrap();
+    std::fs::write(
+        lib.join("Math.sol"),
+        "// SPDX-License-Identifier: GPL-3.0-only\npragma solidity ^0.8.13;\n\nlibrary Math {\n    function add(uint256 a, uint256 b) internal pure returns (uint256) {\n        return a + b;\n    }\n}\n",
+    )
+    .unwrap();
+    let mutate = |filename: &str, outdir: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+            .current_dir(&root)
+            .args(["mutate", "--filename", filename, "--outdir", outdir])
+            .args(["--solc_remappings", "@math=node_modules/@math"])
+            .args(["--mutations", "binary-op-mutation", "--skip_validate"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+        std::fs::read_to_string(root.join(outdir).join("gambit_results.json")).unwrap()
+    };
+    let remapped = mutate("@math/Math.sol", "remapped");
+    let physical = mutate("node_modules/@math/Math.sol", "physical");
+    assert!(remapped.contains("BinaryOpMutation"));
+    assert_eq!(remapped, physical);
+}
+
+/// The remappings of a project's `remappings.txt` are used with
+/// `--project_root`, without any `--solc_remappings`
+#[test]
+fn project_root_remappings() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = get_project_root().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .current_dir(&root)
+        .args([
+            "mutate",
+            "--filename",
+            "benchmarks/FoundryProject/src/Vault.sol",
+        ])
+        .args(["--project_root", "benchmarks/FoundryProject"])
+        .args(["--mutations", "binary-op-mutation"])
+        .arg("--outdir")
+        .arg(&outdir)
+        .output()
+        .unwrap();
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let results: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+            .unwrap();
+    assert!(!results["mutants"].as_array().unwrap().is_empty());
+}
+
+/// Several files passed to `--filename` are mutated into the same output
+/// directory, with mutant ids numbered across all of them
+#[test]
+fn cli_multiple_filenames() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = get_project_root().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .current_dir(&root)
+        .args(["mutate", "--filename"])
+        .args([
+            "benchmarks/10Power/TenPower.sol",
+            "benchmarks/BinaryOpMutation/BinaryOpMutation.sol",
+        ])
+        .args(["--sourceroot", "benchmarks"])
+        .args(["--mutations", "binary-op-mutation", "--skip_validate"])
+        .arg("--outdir")
+        .arg(&outdir)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let results: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+            .unwrap();
+    let mutants = results["mutants"].as_array().unwrap();
+    let ids: Vec<String> = mutants
+        .iter()
+        .map(|m| m["id"].as_str().unwrap().to_string())
+        .collect();
+    let expected_ids: Vec<String> = (1..=mutants.len()).map(|i| i.to_string()).collect();
+    assert_eq!(ids, expected_ids);
+    let originals: HashSet<&str> = mutants
+        .iter()
+        .map(|m| m["original"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        originals,
+        HashSet::from([
+            "10Power/TenPower.sol",
+            "BinaryOpMutation/BinaryOpMutation.sol"
+        ])
+    );
+    let log = std::fs::read_to_string(outdir.join("mutants.log")).unwrap();
+    assert_eq!(log.lines().count(), mutants.len());
+    assert!(log.lines().any(|l| l.contains("10Power/TenPower.sol")));
+    assert!(log
+        .lines()
+        .any(|l| l.contains("BinaryOpMutation/BinaryOpMutation.sol")));
+}
+
+/// A library that one entry mutates with `include_called_libraries` and that
+/// another entry targets is mutated once, by the entry targeting it
+#[test]
+fn target_and_dependency() {
+    let tmp = tempfile::tempdir().unwrap();
+    let benchmarks = get_project_root().unwrap().join("benchmarks");
+    let entry = |file: &str, include_called_libraries: bool, outdir: &str| {
+        serde_json::json!({
+            "filename": benchmarks.join("UsingFor").join(file),
+            "sourceroot": benchmarks,
+            "outdir": tmp.path().join(outdir),
+            "include_called_libraries": include_called_libraries,
+            "skip_validate": true,
+        })
+    };
+    // Run the configuration `entries` writing to the output directory `name`
+    // and return its mutants' files, originals, and replacements, along with
+    // its warnings
+    let run = |name: &str, entries: Vec<serde_json::Value>| {
+        let config = tmp.path().join(format!("{}.json", name));
+        std::fs::write(&config, serde_json::Value::from(entries).to_string()).unwrap();
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+            .args(["mutate", "--json"])
+            .arg(&config)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        assert_eq!(output.status.code(), Some(0), "{}", stderr);
+        let results: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(tmp.path().join(name).join("gambit_results.json")).unwrap(),
+        )
+        .unwrap();
+        let mut mutants: Vec<(String, String, String)> = results["mutants"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| {
+                let field = |f: &str| m[f].as_str().unwrap().to_string();
+                (field("original"), field("orig"), field("repl"))
+            })
+            .collect();
+        mutants.sort();
+        (mutants, stderr)
+    };
+
+    let (single, _) = run("single", vec![entry("C.sol", true, "single")]);
+    let (double, stderr) = run(
+        "double",
+        vec![
+            entry("C.sol", true, "double"),
+            entry("SafeCastLib.sol", false, "double"),
+        ],
+    );
+    assert!(single
+        .iter()
+        .any(|(file, _, _)| file == "UsingFor/SafeCastLib.sol"));
+    assert_eq!(double, single);
+    assert!(stderr.contains("W0016"), "{}", stderr);
+}
+
+/// Entries for the same output directory sample `num_mutants` mutants among
+/// those that earlier entries did not generate
+#[test]
+fn sampling_after_cross_entry_duplicates() {
+    let tmp = tempfile::tempdir().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let benchmarks = get_project_root().unwrap().join("benchmarks");
+    let entry = |seed: u64| {
+        serde_json::json!({
+            "filename": benchmarks.join("10Power/TenPower.sol"),
+            "sourceroot": benchmarks,
+            "outdir": outdir,
+            "num_mutants": 3,
+            "seed": seed,
+            "skip_validate": true,
+        })
+    };
+    let config = tmp.path().join("gambit.json");
+    std::fs::write(&config, serde_json::json!([entry(1), entry(2)]).to_string()).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .args(["mutate", "--json"])
+        .arg(&config)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr);
+    assert!(!stderr.contains("W0003"), "{}", stderr);
+    let results: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+            .unwrap();
+    assert_eq!(results["mutants"].as_array().unwrap().len(), 6);
+}
+
+/// Mutants of a file that another entry targets are left to that entry, and
+/// mutants generated by an earlier entry are not generated again
+#[test]
+fn cross_entry_duplicates() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().canonicalize().unwrap();
+    let code = "contract A {\n    uint x = 1 + 2;\n}\n";
+    for file in ["A.sol", "B.sol", "L.sol"] {
+        std::fs::write(root.join(file), code).unwrap();
+    }
+    let source = |file: &str| Rc::new(Source::new(root.join(file), root.clone()).unwrap());
+    let (a, b, l) = (source("A.sol"), source("B.sol"), source("L.sol"));
+    let start = code.find('1').unwrap();
+    let mutant = |source: &Rc<Source>, repl: &str| {
+        let op = MutationType::BinaryOpMutation;
+        Mutant::new(source.clone(), op, start, start + 5, repl.into()).unwrap()
+    };
+    let targets = HashSet::from([root.join("A.sol"), root.join("B.sol")]);
+    let earlier = vec![(mutant(&a, "1 - 2"), true), (mutant(&l, "1 - 2"), true)];
+    let mut mutants = vec![
+        mutant(&a, "1 * 2"),
+        mutant(&b, "1 - 2"),
+        mutant(&l, "1 - 2"),
+        mutant(&l, "1 * 2"),
+    ];
+    let dropped =
+        drop_cross_entry_duplicates(&mut mutants, &earlier, &root.join("B.sol"), &targets);
+    assert_eq!(dropped, 2);
+    let kept: Vec<(String, &str)> = mutants
+        .iter()
+        .map(|m| (m.source.filename_as_str(), m.repl.as_str()))
+        .collect();
+    assert_eq!(
+        kept,
+        vec![
+            (b.filename_as_str(), "1 - 2"),
+            (l.filename_as_str(), "1 * 2")
+        ]
+    );
+}
+
+/// A run that is interrupted before its first entry writes nothing, and
+/// reports the interruption with its own exit code
+#[test]
+fn interrupted_run() {
+    let tmp = tempfile::tempdir().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let params: MutateParams = serde_json::from_value(serde_json::json!({
+        "filename": "benchmarks/10Power/TenPower.sol",
+        "outdir": outdir.to_str().unwrap(),
+    }))
+    .unwrap();
+    let interrupted = Arc::new(AtomicBool::new(true));
+    let e = gambit::run_mutate_cancellable(vec![params], interrupted).unwrap_err();
+    assert_eq!(gambit::exit_code_for(e.as_ref()), EXIT_INTERRUPTED);
+    assert!(!outdir.exists());
+}
+
+/// With `emit_original_ast`, the AST context of every mutant resolves to the
+/// functions, statements, and expressions listed in `ast_context.json`
+#[test]
+fn emit_original_ast() {
+    let tmp = tempfile::tempdir().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let params: MutateParams = serde_json::from_value(serde_json::json!({
+        "filename": "benchmarks/10Power/TenPower.sol",
+        "outdir": outdir.to_str().unwrap(),
+        "emit_original_ast": true,
+    }))
+    .unwrap();
+    gambit::run_mutate(vec![params]).unwrap();
+    let read = |name: &str| -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(outdir.join(name)).unwrap()).unwrap()
+    };
+    let results = read("gambit_results.json");
+    let context = read(gambit::AST_CONTEXT_FILENAME);
+    let files = context["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    let ids = |key: &str| -> Vec<serde_json::Value> {
+        files[0][key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].clone())
+            .collect()
+    };
+    let mutants = results["mutants"].as_array().unwrap();
+    assert!(!mutants.is_empty());
+    for mutant in mutants {
+        let ast_context = &mutant["ast_context"];
+        assert!(ids("functions").contains(&ast_context["function"]));
+        assert!(ids("statements").contains(&ast_context["statement"]));
+        assert!(ids("expressions").contains(&ast_context["expression"]));
+    }
+}
+
+/// With `emit_manifest`, the job manifest has a job for every mutant, whose
+/// exported file and original file exist
+#[test]
+fn emit_manifest() {
+    let tmp = tempfile::tempdir().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let params: MutateParams = serde_json::from_value(serde_json::json!({
+        "filename": "benchmarks/10Power/TenPower.sol",
+        "outdir": outdir.to_str().unwrap(),
+        "emit_manifest": true,
+    }))
+    .unwrap();
+    gambit::run_mutate(vec![params]).unwrap();
+    let read = |name: &str| std::fs::read_to_string(outdir.join(name)).unwrap();
+    let results: serde_json::Value = serde_json::from_str(&read("gambit_results.json")).unwrap();
+    let manifest: gambit::JobManifest =
+        serde_json::from_str(&read(gambit::JOB_MANIFEST_FILENAME)).unwrap();
+    assert_eq!(manifest.configurations.len(), 1);
+    let filename = manifest.configurations[0].filename.as_ref().unwrap();
+    assert!(Path::new(filename).is_absolute());
+
+    let mutants = results["mutants"].as_array().unwrap();
+    assert!(!mutants.is_empty());
+    assert_eq!(manifest.jobs.len(), mutants.len());
+    for (job, mutant) in manifest.jobs.iter().zip(mutants) {
+        assert_eq!(job.id, mutant["id"]);
+        assert_eq!(job.operator, mutant["description"]);
+        assert_eq!(&job.original_path, filename);
+        assert!(Path::new(job.mutant.as_ref().unwrap()).is_file());
+    }
+}
+
+/// The mutants of a file starting with a UTF-8 byte order mark keep the mark
+/// at their start, exactly once, and still compile
+#[test]
+fn byte_order_mark() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = get_project_root().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .current_dir(&root)
+        .args(["mutate", "--filename"])
+        .arg("benchmarks/ByteOrderMark/ByteOrderMark.sol")
+        .args(["--sourceroot", "benchmarks"])
+        .args(["--mutations", "binary-op-mutation"])
+        .arg("--outdir")
+        .arg(&outdir)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("W0015"));
+    let results: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+            .unwrap();
+    let mutants = results["mutants"].as_array().unwrap();
+    assert!(!mutants.is_empty());
+    for mutant in mutants {
+        assert_eq!(mutant["validated"], true);
+        let contents =
+            std::fs::read_to_string(outdir.join(mutant["name"].as_str().unwrap())).unwrap();
+        assert!(contents.starts_with('\u{feff}'));
+        assert_eq!(contents.matches('\u{feff}').count(), 1);
+    }
+}
+
+/// An output directory is not deleted when its `mutants` subdirectory belongs
+/// to another tool, or when it is the root of a repository, without `--force`
+#[test]
+fn foreign_outdir_is_not_deleted() {
+    let tmp = tempfile::tempdir().unwrap();
+    let outdir = tmp.path().join("out");
+    let foreign = outdir.join("mutants/keep.txt");
+    std::fs::create_dir_all(foreign.parent().unwrap()).unwrap();
+    std::fs::write(&foreign, "not Gambit's").unwrap();
+    let gambit = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+            .current_dir(get_project_root().unwrap())
+            .args(["mutate", "--filename", "benchmarks/10Power/TenPower.sol"])
+            .arg("--outdir")
+            .arg(&outdir)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = gambit(&[]);
+    assert_eq!(output.status.code(), Some(EXIT_CONFIGURATION_ERROR));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+    assert!(foreign.is_file());
+
+    // Exporting to another mutants subdirectory would still delete it
+    let output = gambit(&["--mutants_subdir", "gambit_mutants"]);
+    assert_eq!(output.status.code(), Some(EXIT_CONFIGURATION_ERROR));
+    assert!(foreign.is_file());
+
+    std::fs::remove_dir_all(foreign.parent().unwrap()).unwrap();
+    std::fs::create_dir(outdir.join(".git")).unwrap();
+    assert_eq!(gambit(&[]).status.code(), Some(EXIT_CONFIGURATION_ERROR));
+    assert!(outdir.join(".git").is_dir());
+}
+
+/// Rerunning into an output directory only replaces the outputs of the
+/// earlier run with `--overwrite` (or a confirmation on the terminal), and
+/// keeps the files that Gambit did not write, while output directories that
+/// Gambit did not write are left alone
+#[test]
+fn outdir_foreign_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let gambit = |outdir: &PathBuf, args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+            .current_dir(get_project_root().unwrap())
+            .args(["mutate", "--filename", "benchmarks/10Power/TenPower.sol"])
+            .arg("--outdir")
+            .arg(outdir)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    // The outputs of an earlier run, along with triage notes
+    let outdir = tmp.path().join("gambit_out");
+    std::fs::create_dir_all(outdir.join("mutants/7")).unwrap();
+    std::fs::create_dir_all(outdir.join("diffs")).unwrap();
+    for file in [
+        ".gambit",
+        "mutants/.gambit",
+        "mutants/7/Old.sol",
+        "diffs/7.diff",
+        "gambit_results.json",
+        "notes.md",
+    ] {
+        std::fs::write(outdir.join(file), "").unwrap();
+    }
+    // Without `--overwrite` and without a terminal to confirm on, nothing is
+    // deleted
+    let output = gambit(&outdir, &[]);
+    assert_eq!(output.status.code(), Some(EXIT_CONFIGURATION_ERROR));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--overwrite"));
+    assert!(outdir.join("mutants/7/Old.sol").is_file());
+    assert!(outdir.join("diffs/7.diff").is_file());
+
+    // Preserve: the earlier outputs are replaced, but the notes are kept
+    gambit(&outdir, &["--overwrite", "--skip_validate"]);
+    assert!(!outdir.join("mutants/7").exists());
+    assert!(!outdir.join("diffs").exists());
+    assert!(outdir.join("notes.md").is_file());
+
+    // Abort: a directory that Gambit did not write is not touched, and its
+    // files are listed
+    let foreign = tmp.path().join("foreign");
+    std::fs::create_dir_all(foreign.join("build")).unwrap();
+    std::fs::write(foreign.join("notes.md"), "").unwrap();
+    let output = gambit(&foreign, &["--overwrite"]);
+    assert_eq!(output.status.code(), Some(EXIT_CONFIGURATION_ERROR));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("build, notes.md"), "{}", stderr);
+    assert!(foreign.join("notes.md").is_file());
+    assert!(foreign.join("build").is_dir());
+}
+
+/// `--overwrite` on the command line applies to every entry of a
+/// configuration file, so a configuration can be rerun without a terminal
+#[test]
+fn rerun_configuration_with_overwrite() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().canonicalize().unwrap();
+    std::fs::copy(
+        get_project_root()
+            .unwrap()
+            .join("benchmarks/10Power/TenPower.sol"),
+        root.join("C.sol"),
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("c.json"),
+        r#"{"filename": "C.sol", "sourceroot": ".", "skip_validate": true}"#,
+    )
+    .unwrap();
+    let gambit = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+            .current_dir(&root)
+            .args(["mutate", "--json", "c.json"])
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .unwrap()
+    };
+
+    assert_eq!(gambit(&[]).status.code(), Some(0));
+    assert!(root.join("gambit_out/gambit_results.json").is_file());
+    // A rerun needs `--overwrite` without a terminal to confirm on
+    let output = gambit(&[]);
+    assert_eq!(output.status.code(), Some(EXIT_CONFIGURATION_ERROR));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--overwrite"));
+    let output = gambit(&["--overwrite"]);
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(root.join("gambit_out/gambit_results.json").is_file());
+}
+
+/// Without `--filename` or `--json`, `gambit mutate` uses the configuration
+/// file of the project containing the current directory, unless a
+/// configuration file is given explicitly or `--no_discover` is passed
+#[test]
+fn discovered_configuration() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().canonicalize().unwrap();
+    let nested = root.join("test/unit");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::copy(
+        get_project_root()
+            .unwrap()
+            .join("benchmarks/10Power/TenPower.sol"),
+        root.join("C.sol"),
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("gambit.toml"),
+        "filename = \"C.sol\"\noutdir = \"discovered\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("explicit.json"),
+        r#"{"filename": "C.sol", "sourceroot": ".", "outdir": "explicit"}"#,
+    )
+    .unwrap();
+    let gambit = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+            .current_dir(&nested)
+            .arg("mutate")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = gambit(&[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("gambit.toml"));
+    assert!(root.join("discovered/gambit_results.json").is_file());
+
+    // An explicit configuration file takes precedence
+    let output = gambit(&["--json", "../../explicit.json"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("gambit.toml"));
+    assert!(root.join("explicit/gambit_results.json").is_file());
+
+    assert_eq!(
+        gambit(&["--no_discover"]).status.code(),
+        Some(EXIT_CONFIGURATION_ERROR)
+    );
+}
+
+/// Arguments given on the command line override the values of a discovered
+/// configuration, and their paths are relative to the current directory
+#[test]
+fn discovered_configuration_precedence() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().canonicalize().unwrap();
+    let nested = root.join("test/unit");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::copy(
+        get_project_root()
+            .unwrap()
+            .join("benchmarks/10Power/TenPower.sol"),
+        root.join("C.sol"),
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("gambit.toml"),
+        "filename = \"C.sol\"\noutdir = \"discovered\"\nnum_mutants = 5\nseed = 1\n",
+    )
+    .unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .current_dir(&nested)
+        .args(["mutate", "--outdir", "cli_out", "--num_mutants", "2"])
+        .args(["--seed", "7", "--skip_validate"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!root.join("discovered").exists());
+    let results: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(nested.join("cli_out/gambit_results.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(results["mutants"].as_array().unwrap().len(), 2);
+}
+
+/// Requesting more mutants than there are valid mutants terminates, emits all
+/// of the valid mutants, and reports the shortfall
+#[test]
+fn more_mutants_than_available() {
+    let tmp = tempfile::tempdir().unwrap();
+    let outdir = tmp.path().join("gambit_out");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gambit"))
+        .current_dir(get_project_root().unwrap())
+        .args(["mutate", "--filename", "benchmarks/10Power/TenPower.sol"])
+        .args(["--num_mutants", "1000"])
+        .arg("--outdir")
+        .arg(&outdir)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("W0003"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("(1000 requested)"));
+    let results: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(outdir.join("gambit_results.json")).unwrap())
+            .unwrap();
+    let statistics = &results["files"][0]["statistics"];
+    assert_eq!(statistics["requested"], 1000);
+    let num_mutants = results["mutants"].as_array().unwrap().len();
+    assert!(num_mutants > 0 && num_mutants < 1000);
+    assert_eq!(statistics["mutants"], num_mutants);
+    assert_eq!(statistics["validated"], num_mutants);
+}
+
 /// Assert the expected mutations of JSON configuration file located in
 /// `benchmarks/config-jsons`.
 ///
@@ -364,8 +1503,9 @@ fn get_config_json(config_json: &str) -> Result<Vec<MutateParams>, Box<dyn error
                 .unwrap()
                 .to_string()
         });
-        // Update overwrite
+        // Update overwrite: tests rerun into the same output directories
         params.no_overwrite = false;
+        params.overwrite = true;
     }
 
     Ok(mutate_params)
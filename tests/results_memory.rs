@@ -0,0 +1,100 @@
+//! Peak memory use of writing and reading a large `gambit_results.json`. This
+//! is a test binary of its own so that its allocator only counts this test.
+
+use gambit::{for_each_results_entry, Mutant, MutantWriter, MutationType, Source};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The system allocator, keeping track of the peak number of allocated bytes
+struct PeakAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+/// Run `f`, returning its result along with the peak number of bytes that
+/// were allocated on top of those allocated before it ran
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    let result = f();
+    (result, PEAK.load(Ordering::SeqCst) - before)
+}
+
+#[test]
+fn large_results_are_streamed() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().canonicalize().unwrap();
+    // 20 long lines with 25 mutants each: every diff holds several of these
+    // lines, making the diffs of all 500 mutants tens of megabytes
+    let line = format!("    uint256 x = {}1;\n", "1 + ".repeat(1250));
+    let code = format!("contract C {{\n{}}}\n", line.repeat(20));
+    fs::write(root.join("C.sol"), &code).unwrap();
+    let source = Rc::new(Source::new(root.join("C.sol"), root.clone()).unwrap());
+    let mutants: Vec<(Mutant, bool)> = code
+        .match_indices('+')
+        .step_by(50)
+        .map(|(start, _)| {
+            let mutant = Mutant::new(
+                source.clone(),
+                MutationType::BinaryOpMutation,
+                start,
+                start + 1,
+                "-".to_string(),
+            )
+            .unwrap();
+            (mutant, false)
+        })
+        .collect();
+    assert_eq!(mutants.len(), 500);
+
+    let outdir = root.join("gambit_out");
+    fs::create_dir_all(&outdir).unwrap();
+    let writer = MutantWriter::new(outdir.to_str().unwrap().to_string(), None);
+    let (written, write_peak) = peak_allocation(|| writer.write_mutants(&mutants));
+    written.unwrap();
+    let results_json = outdir.join("gambit_results.json");
+    let size = fs::metadata(&results_json).unwrap().len() as usize;
+    assert!(size > 16 << 20, "results are only {} bytes", size);
+    assert!(
+        write_peak < size / 4,
+        "writing {} bytes of results allocated {} bytes",
+        size,
+        write_peak
+    );
+
+    let mut num_entries = 0;
+    let (found, read_peak) = peak_allocation(|| {
+        for_each_results_entry(fs::File::open(&results_json).unwrap(), |_| num_entries += 1)
+    });
+    assert!(found.unwrap());
+    assert_eq!(num_entries, mutants.len());
+    assert!(
+        read_peak < size / 16,
+        "reading {} bytes of results allocated {} bytes",
+        size,
+        read_peak
+    );
+}